@@ -0,0 +1,18 @@
+// Rebuild when migrations change so sqlx::migrate! embeds the current set
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+
+    // gRPC stubs for proto/trading.proto, with the vendored protoc
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto");
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+            std::env::set_var("PROTOC", protoc);
+        }
+        tonic_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/trading.proto"], &["proto"])
+            .expect("compile proto/trading.proto");
+    }
+}
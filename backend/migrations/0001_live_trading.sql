@@ -1,6 +1,6 @@
 -- ============================================
 -- LIVE TRADING TABLES
--- Migration: 001_add_live_trading.sql
+-- Migration: 0001_live_trading.sql
 -- ============================================
 
 -- ============================================
@@ -49,6 +49,12 @@ CREATE TABLE IF NOT EXISTS live_trading_config (
     disabled_at TIMESTAMPTZ
 );
 
+-- Databases created before the pair selection filters existed
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS max_pairs INT,
+ADD COLUMN IF NOT EXISTS min_volume_24h_usd FLOAT,
+ADD COLUMN IF NOT EXISTS max_cost_min FLOAT;
+
 -- Insert initial row with NULL values (user must configure)
 INSERT INTO live_trading_config (id, is_enabled)
 VALUES (1, FALSE)
@@ -1,5 +1,5 @@
 -- ============================================
--- Migration: 002_rename_start_currency.sql
+-- Migration: 0002_rename_start_currency.sql
 -- Rename base_currency to start_currency for clarity
 -- The column represents the starting currency for triangular arbitrage
 -- ============================================
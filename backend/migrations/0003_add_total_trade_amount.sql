@@ -1,5 +1,5 @@
 -- ============================================
--- Migration: 002_add_total_trade_amount.sql
+-- Migration: 0003_add_total_trade_amount.sql
 -- Add total_trade_amount to live_trading_state
 -- ============================================
 
@@ -1,3 +1,12 @@
+-- Shared updated_at trigger function (also created by db/init.sql)
+CREATE OR REPLACE FUNCTION update_updated_at_column()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
 -- Migration: Add live_opportunities and live_scanner_status tables
 -- Date: 2024-12-19
 -- Description: Track opportunities found by live trading scanner
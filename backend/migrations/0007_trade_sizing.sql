@@ -0,0 +1,10 @@
+-- Migration: Profit-tiered trade sizing
+-- Auto-execution sizes each trade by the highest tier its net profit reaches,
+-- falling back to trade_amount, and never exceeding max_safe_amount
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS sizing_tiers JSONB,            -- e.g. [{"min_profit_pct": 0.2, "amount": 50}, {"min_profit_pct": 0.5, "amount": 200}]
+ADD COLUMN IF NOT EXISTS max_safe_amount FLOAT;         -- Hard cap on any auto-execution amount
+
+COMMENT ON COLUMN live_trading_config.sizing_tiers IS 'Profit-tiered sizing ladder: min_profit_pct in percent (0.2 = 0.2%), amount in start currency';
+COMMENT ON COLUMN live_trading_config.max_safe_amount IS 'Upper bound for any auto-executed trade amount';
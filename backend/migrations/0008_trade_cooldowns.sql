@@ -0,0 +1,11 @@
+-- Migration: Configurable trade cooldowns
+-- NULL or 0 leaves a cooldown off (the default)
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS global_cooldown_ms INT,        -- Pause after any trade
+ADD COLUMN IF NOT EXISTS path_cooldown_ms INT,          -- Pause before re-trading the same path
+ADD COLUMN IF NOT EXISTS pair_failure_cooldown_ms INT;  -- Pause before re-using a pair whose leg failed
+
+COMMENT ON COLUMN live_trading_config.global_cooldown_ms IS 'Milliseconds to wait after any trade before the next one (0 = off)';
+COMMENT ON COLUMN live_trading_config.path_cooldown_ms IS 'Milliseconds before the same path may be traded again (0 = off)';
+COMMENT ON COLUMN live_trading_config.pair_failure_cooldown_ms IS 'Milliseconds a pair is skipped after one of its legs failed (0 = off)';
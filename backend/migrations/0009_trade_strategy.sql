@@ -0,0 +1,13 @@
+-- Migration: Trade strategy attribution
+-- Each trade records the detection strategy that produced it plus free-form tags,
+-- so PnL can be split per strategy in trade history
+
+ALTER TABLE live_trades
+ADD COLUMN IF NOT EXISTS strategy VARCHAR(50),                -- triangular, cross_pair, manual
+ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';   -- e.g. {partial_resolution}
+
+CREATE INDEX IF NOT EXISTS idx_live_trades_strategy ON live_trades(strategy);
+CREATE INDEX IF NOT EXISTS idx_live_trades_tags ON live_trades USING GIN (tags);
+
+COMMENT ON COLUMN live_trades.strategy IS 'Detection strategy: triangular (3 legs), cross_pair (4+ legs), manual (operator path)';
+COMMENT ON COLUMN live_trades.tags IS 'Free-form attribution labels; filter with ?tag= on /api/live/trades';
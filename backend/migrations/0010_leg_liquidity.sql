@@ -0,0 +1,11 @@
+-- Migration: Per-leg liquidity filter for the scanner
+-- Paths with a leg that can't fill the trade amount within the top N book levels
+-- and X bps of the best price are dropped before execution.
+-- Both NULL keeps the filter off; setting one uses 10 levels / 50 bps for the other.
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS leg_depth_levels INT,        -- Order book levels a leg may consume
+ADD COLUMN IF NOT EXISTS max_leg_slippage_bps FLOAT;  -- Max average-fill slippage vs best price
+
+COMMENT ON COLUMN live_trading_config.leg_depth_levels IS 'Book levels each leg must fill the trade amount within (NULL = filter off)';
+COMMENT ON COLUMN live_trading_config.max_leg_slippage_bps IS 'Max slippage in basis points of a leg fill vs top of book (NULL = filter off)';
@@ -0,0 +1,36 @@
+-- Migration: Execution audit log
+-- Append-only record of order requests/responses, guard decisions, config changes
+-- and circuit-breaker events for post-incident review. UPDATE and DELETE are rejected.
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    actor VARCHAR(20) NOT NULL,       -- auto, api, system
+    category VARCHAR(20) NOT NULL,    -- order, guard, config, breaker
+    action VARCHAR(100) NOT NULL,     -- e.g. order_request, config_update, circuit_breaker_tripped
+    details JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_occurred_at ON audit_log(occurred_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_category ON audit_log(category);
+
+CREATE OR REPLACE FUNCTION audit_log_immutable()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_log_no_update ON audit_log;
+CREATE TRIGGER audit_log_no_update
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW
+    EXECUTE FUNCTION audit_log_immutable();
+
+DROP TRIGGER IF EXISTS audit_log_no_truncate ON audit_log;
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION audit_log_immutable();
+
+COMMENT ON TABLE audit_log IS 'Append-only execution audit trail (UPDATE/DELETE/TRUNCATE raise an error)';
@@ -0,0 +1,9 @@
+-- Migration: Per-currency minimum reserves
+-- Balance per currency that auto-execution never spends, e.g. {"USD": 500}.
+-- Trades starting in a reserved currency are sized down to balance - reserve
+-- and skipped once nothing is left above it.
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS currency_reserves JSONB;  -- {currency: amount kept untouched}
+
+COMMENT ON COLUMN live_trading_config.currency_reserves IS 'Balance per currency never used for trading, e.g. {"USD": 500} (NULL = no reserves)';
@@ -0,0 +1,29 @@
+-- Migration: Order fill journal
+-- Every executions-channel message by order_id, recorded whether or not a
+-- pending order was waiting for it, so fills that arrive after a timeout or
+-- twice after a reconnect can be reconciled. Written only with FILL_JOURNAL_DB=true.
+
+CREATE TABLE IF NOT EXISTS order_fills (
+    id BIGSERIAL PRIMARY KEY,
+    received_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    order_id VARCHAR(64) NOT NULL,
+    cl_ord_id VARCHAR(64),
+    exec_id VARCHAR(64),
+    exec_type VARCHAR(20) NOT NULL,     -- pending_new, new, trade, filled, canceled, expired, ...
+    order_status VARCHAR(20) NOT NULL,
+    symbol VARCHAR(30),
+    side VARCHAR(10),
+    last_qty FLOAT NOT NULL DEFAULT 0,
+    last_price FLOAT NOT NULL DEFAULT 0,
+    cum_qty FLOAT NOT NULL DEFAULT 0,
+    cum_cost FLOAT NOT NULL DEFAULT 0,
+    avg_price FLOAT NOT NULL DEFAULT 0,
+    fee_usd FLOAT NOT NULL DEFAULT 0,
+    matched BOOLEAN NOT NULL DEFAULT FALSE,    -- a pending order was waiting for it
+    duplicate BOOLEAN NOT NULL DEFAULT FALSE   -- same exec already journaled
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_fills_order_id ON order_fills(order_id);
+CREATE INDEX IF NOT EXISTS idx_order_fills_received_at ON order_fills(received_at DESC);
+
+COMMENT ON TABLE order_fills IS 'Executions-channel messages by order_id, independent of pending-order matching';
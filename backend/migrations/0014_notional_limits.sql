@@ -0,0 +1,11 @@
+-- Migration: Traded notional limits
+-- USD notional of every filled leg is summed over the last hour and the last
+-- 24 hours; auto-execution skips trades that would take either window past
+-- its limit. NULL = no limit.
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS max_notional_per_hour FLOAT,  -- USD traded across all legs, rolling hour
+ADD COLUMN IF NOT EXISTS max_notional_per_day FLOAT;   -- USD traded across all legs, rolling 24h
+
+COMMENT ON COLUMN live_trading_config.max_notional_per_hour IS 'Max USD notional filled across all legs in any rolling hour (NULL = no limit)';
+COMMENT ON COLUMN live_trading_config.max_notional_per_day IS 'Max USD notional filled across all legs in any rolling 24 hours (NULL = no limit)';
@@ -0,0 +1,9 @@
+-- Migration: Scan-only pairs
+-- Pairs listed here are still scanned (and their opportunities reported) but
+-- never traded: any path with a leg through one of them is refused before
+-- the first order, e.g. ["DOGE/BTC", "USDT/EUR"].
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS execution_disabled_pairs JSONB;  -- ["BASE/QUOTE", ...]
+
+COMMENT ON COLUMN live_trading_config.execution_disabled_pairs IS 'Pairs that are scanned but never executed, e.g. ["DOGE/BTC"] (NULL = all pairs tradable)';
@@ -0,0 +1,10 @@
+-- Migration: Profit thresholds per path length
+-- Longer cycles carry more execution risk, so they can be required to clear
+-- a higher net profit than min_profit_threshold. Keys are leg counts, values
+-- decimals like min_profit_threshold, e.g. {"3": 0.0015, "4": 0.0035, "5": 0.006}.
+-- A path uses the entry for the longest configured length not above its own.
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS leg_profit_thresholds JSONB;  -- {"<legs>": threshold, ...}
+
+COMMENT ON COLUMN live_trading_config.leg_profit_thresholds IS 'Min net profit (decimal) by path length, e.g. {"3": 0.0015, "4": 0.0035} (NULL = min_profit_threshold for all)';
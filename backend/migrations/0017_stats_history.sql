@@ -0,0 +1,31 @@
+-- Migration: Engine stats history
+-- Engine, scanner and executor counters sampled every STATS_SAMPLE_SECS, so
+-- dashboards can chart them without an external time-series database.
+-- Written only with STATS_HISTORY_DB=true; counters are cumulative since
+-- engine start, the dashboard derives rates from consecutive samples.
+
+CREATE TABLE IF NOT EXISTS stats_history (
+    id BIGSERIAL PRIMARY KEY,
+    sampled_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    is_running BOOLEAN NOT NULL DEFAULT FALSE,
+    pairs_monitored BIGINT NOT NULL DEFAULT 0,
+    cycles_completed BIGINT NOT NULL DEFAULT 0,
+    events_received BIGINT NOT NULL DEFAULT 0,
+    opportunities_found BIGINT NOT NULL DEFAULT 0,
+    paths_filtered_liquidity BIGINT NOT NULL DEFAULT 0,
+    trades_executed BIGINT NOT NULL DEFAULT 0,
+    trades_successful BIGINT NOT NULL DEFAULT 0,
+    trades_failed BIGINT NOT NULL DEFAULT 0,
+    total_profit FLOAT NOT NULL DEFAULT 0,
+    total_loss FLOAT NOT NULL DEFAULT 0,
+    daily_profit FLOAT NOT NULL DEFAULT 0,
+    daily_loss FLOAT NOT NULL DEFAULT 0,
+    orders_sent BIGINT NOT NULL DEFAULT 0,
+    orders_filled BIGINT NOT NULL DEFAULT 0,
+    orders_failed BIGINT NOT NULL DEFAULT 0,
+    ws_wire_bytes BIGINT NOT NULL DEFAULT 0     -- public socket bytes received
+);
+
+CREATE INDEX IF NOT EXISTS idx_stats_history_sampled_at ON stats_history(sampled_at DESC);
+
+COMMENT ON TABLE stats_history IS 'Periodic samples of engine/scanner/executor counters for dashboard charts';
@@ -0,0 +1,9 @@
+-- Migration: Daily reset timezone
+-- Daily loss/profit counters roll over automatically at local midnight in this
+-- IANA timezone (e.g. America/New_York), instead of only on a manual reset.
+-- Each rollover stamps live_trading_state.last_daily_reset.
+
+ALTER TABLE live_trading_config
+ADD COLUMN IF NOT EXISTS daily_reset_timezone VARCHAR(64);  -- NULL = UTC
+
+COMMENT ON COLUMN live_trading_config.daily_reset_timezone IS 'IANA timezone whose midnight resets the daily counters (NULL = UTC)';
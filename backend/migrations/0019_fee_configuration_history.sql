@@ -0,0 +1,49 @@
+-- Migration: Fee configuration history
+-- Every change to fee_configuration (Kraken tier refresh or manual edit) is
+-- recorded with the old and new rates, so PnL analysis can join trades
+-- against the fee schedule that was actually in effect when they executed.
+-- Filled by a trigger, so no writer can change the fees without a row here.
+
+CREATE TABLE IF NOT EXISTS fee_configuration_history (
+    id BIGSERIAL PRIMARY KEY,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    fee_source VARCHAR(20) NOT NULL,           -- source of the new values
+    old_maker_fee DOUBLE PRECISION,            -- NULL on the first configuration
+    old_taker_fee DOUBLE PRECISION,
+    new_maker_fee DOUBLE PRECISION NOT NULL,
+    new_taker_fee DOUBLE PRECISION NOT NULL,
+    volume_tier VARCHAR(50),
+    thirty_day_volume DOUBLE PRECISION
+);
+
+CREATE INDEX IF NOT EXISTS idx_fee_configuration_history_changed_at ON fee_configuration_history(changed_at DESC);
+
+CREATE OR REPLACE FUNCTION record_fee_configuration_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT'
+        OR NEW.maker_fee IS DISTINCT FROM OLD.maker_fee
+        OR NEW.taker_fee IS DISTINCT FROM OLD.taker_fee
+        OR NEW.fee_source IS DISTINCT FROM OLD.fee_source THEN
+        INSERT INTO fee_configuration_history (
+            fee_source, old_maker_fee, old_taker_fee, new_maker_fee, new_taker_fee,
+            volume_tier, thirty_day_volume
+        ) VALUES (
+            NEW.fee_source,
+            CASE WHEN TG_OP = 'UPDATE' THEN OLD.maker_fee END,
+            CASE WHEN TG_OP = 'UPDATE' THEN OLD.taker_fee END,
+            NEW.maker_fee, NEW.taker_fee,
+            NEW.volume_tier, NEW.thirty_day_volume
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS record_fee_configuration_change ON fee_configuration;
+CREATE TRIGGER record_fee_configuration_change
+    AFTER INSERT OR UPDATE ON fee_configuration
+    FOR EACH ROW
+    EXECUTE FUNCTION record_fee_configuration_change();
+
+COMMENT ON TABLE fee_configuration_history IS 'Every maker/taker fee change with its source and the previous values (written by trigger)';
@@ -0,0 +1,21 @@
+-- Migration: Operator notes on trades and opportunities
+-- Free-text notes and labels attached by operators after the fact, so
+-- incident context ("Kraken outage", "manual resolve approved by X") is
+-- stored with the trade instead of in someone's chat history.
+-- Set with PATCH /api/live/trades/:trade_id/notes and /api/opportunities/:id/notes.
+
+ALTER TABLE live_trades
+ADD COLUMN IF NOT EXISTS notes TEXT,
+ADD COLUMN IF NOT EXISTS labels TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN IF NOT EXISTS notes_updated_at TIMESTAMP;
+
+ALTER TABLE live_opportunities
+ADD COLUMN IF NOT EXISTS notes TEXT,
+ADD COLUMN IF NOT EXISTS labels TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN IF NOT EXISTS notes_updated_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_live_trades_labels ON live_trades USING GIN (labels);
+
+COMMENT ON COLUMN live_trades.notes IS 'Operator note, e.g. incident context or who approved a manual resolve';
+COMMENT ON COLUMN live_trades.labels IS 'Operator labels, separate from the engine-set tags';
+COMMENT ON COLUMN live_opportunities.notes IS 'Operator note; annotated opportunities are kept past the 7-day cleanup';
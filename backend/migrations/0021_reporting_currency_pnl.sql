@@ -0,0 +1,16 @@
+-- Migration: Trade P&L in the reporting currency
+-- profit_loss stays in the currency the trade started in (EUR for a
+-- EUR → ... → EUR cycle); the conversion into the reporting currency is
+-- stored next to it with the rate taken when the trade completed, so
+-- daily totals and loss limits add EUR and USD trades up correctly.
+-- live_trading_state totals are kept in the reporting currency.
+
+ALTER TABLE live_trades
+ADD COLUMN IF NOT EXISTS pnl_currency VARCHAR(20),
+ADD COLUMN IF NOT EXISTS pnl_reporting DOUBLE PRECISION,
+ADD COLUMN IF NOT EXISTS reporting_currency VARCHAR(20),
+ADD COLUMN IF NOT EXISTS fx_rate DOUBLE PRECISION;
+
+COMMENT ON COLUMN live_trades.pnl_currency IS 'Currency profit_loss is in (the start currency)';
+COMMENT ON COLUMN live_trades.pnl_reporting IS 'profit_loss in reporting_currency at fx_rate';
+COMMENT ON COLUMN live_trades.fx_rate IS 'Reporting currency per unit of pnl_currency when the trade completed';
@@ -0,0 +1,17 @@
+-- Migration: Audit log retention
+-- audit_log stays append-only, except for the retention job: a DELETE is
+-- let through in a transaction that has set audit_log.retention = 'on'
+-- (SET LOCAL), which only the cleanup job does. UPDATE and TRUNCATE are
+-- still rejected.
+
+CREATE OR REPLACE FUNCTION audit_log_immutable()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' AND current_setting('audit_log.retention', true) = 'on' THEN
+        RETURN OLD;
+    END IF;
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON TABLE audit_log IS 'Append-only execution audit trail (UPDATE/TRUNCATE raise an error; DELETE only by the retention job)';
@@ -0,0 +1,26 @@
+-- Migration: Trading sessions
+-- A session runs from enabling trading to disabling it (by the operator,
+-- a quick disable or the dead man's switch). The config it was enabled
+-- with is kept, and trades saved while it's open carry its session_id,
+-- so sessions run with different settings can be compared side by side.
+-- Summaries are served by GET /api/sessions.
+
+CREATE TABLE IF NOT EXISTS trading_sessions (
+    id SERIAL PRIMARY KEY,
+    session_id VARCHAR(64) NOT NULL UNIQUE,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    ended_at TIMESTAMP,
+    end_reason TEXT,
+    config JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_trading_sessions_started_at ON trading_sessions(started_at DESC);
+
+ALTER TABLE live_trades
+ADD COLUMN IF NOT EXISTS session_id VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS idx_live_trades_session_id ON live_trades(session_id);
+
+COMMENT ON TABLE trading_sessions IS 'Periods trading was enabled, with the config each was enabled with';
+COMMENT ON COLUMN trading_sessions.ended_at IS 'NULL while the session is open (at most one is)';
+COMMENT ON COLUMN live_trades.session_id IS 'Trading session open when the trade was saved (NULL outside one)';
@@ -0,0 +1,435 @@
+//! Trade Analytics
+//!
+//! Aggregates over live_trades for dashboards, so the frontend doesn't
+//! have to rebuild them from raw trade lists: win rate, average and median
+//! profit per trade, P&L by hour, the most profitable paths, the pairs
+//! whose legs fail most and average execution time.
+//!
+//! P&L is taken in the reporting currency where the trade recorded it and
+//! in its start currency otherwise. Wins and profit averages cover
+//! completed trades; failing pairs come from the failed legs stored with
+//! FAILED and PARTIAL trades.
+//!
+//! `what_if` replays recorded opportunities (live_opportunities) against
+//! other thresholds and cooldowns, to see what a configuration change would
+//! have traded. Each opportunity at or above a threshold triggers a trade
+//! unless the global or path cooldown of an earlier triggered one is still
+//! running; its estimated P&L is the profit expected when it was found.
+//! Pair failure cooldowns aren't replayed (the history has no fills), and
+//! opportunities never recorded can't be: thresholds below the lowest
+//! recorded profit undercount.
+
+use crate::db::{LiveOpportunity, LiveTrade};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Longest window a summary may cover
+pub const MAX_WINDOW_HOURS: i64 = 24 * 30;
+
+/// Entries in the top paths / failing pairs lists
+const TOP_N: usize = 10;
+
+/// Most threshold × cooldown combinations one what-if run replays
+pub const MAX_WHAT_IF_SCENARIOS: usize = 100;
+
+/// Parse a window like "30m", "24h" or "7d" (a bare number is hours)
+pub fn parse_window(value: &str) -> Result<Duration, String> {
+    let value = value.trim().to_lowercase();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => value.split_at(at),
+        None => (value.as_str(), "h"),
+    };
+    let amount: i64 = number.parse().map_err(|_| format!("Invalid window '{}'", value))?;
+    let window = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => return Err(format!("Invalid window '{}': use m, h or d", value)),
+    };
+    if amount <= 0 || window > Duration::hours(MAX_WINDOW_HOURS) {
+        return Err(format!("Window must be between 1m and {}d", MAX_WINDOW_HOURS / 24));
+    }
+    Ok(window)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HourlyPnl {
+    pub hour: DateTime<Utc>,
+    pub trades: usize,
+    pub pnl: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathProfit {
+    pub path: String,
+    pub trades: usize,
+    pub total_pnl: f64,
+    pub avg_profit_pct: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailingPair {
+    pub pair: String,
+    pub failures: usize,
+    /// Most recent error on the pair
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsSummary {
+    pub window_secs: i64,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub trades: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub partial: usize,
+    /// Completed trades with a profit, of all completed (None without any)
+    pub win_rate: Option<f64>,
+    pub total_pnl: f64,
+    pub avg_profit: Option<f64>,
+    pub median_profit: Option<f64>,
+    pub avg_profit_pct: Option<f64>,
+    pub avg_execution_ms: Option<f64>,
+    /// Hours with trades, oldest first
+    pub pnl_by_hour: Vec<HourlyPnl>,
+    pub top_paths: Vec<PathProfit>,
+    pub top_failing_pairs: Vec<FailingPair>,
+}
+
+/// P&L in the reporting currency, else the start currency
+fn trade_pnl(trade: &LiveTrade) -> Option<f64> {
+    trade.pnl_reporting.or(trade.profit_loss)
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) { (sorted[mid - 1] + sorted[mid]) / 2.0 } else { sorted[mid] })
+}
+
+/// Summarize the trades created within `window` before `now`
+pub fn summarize(trades: &[LiveTrade], window: Duration, now: DateTime<Utc>) -> AnalyticsSummary {
+    let from = now - window;
+    let in_window: Vec<&LiveTrade> = trades.iter().filter(|t| t.created_at.is_some_and(|at| at >= from)).collect();
+    let count = |status: &str| in_window.iter().filter(|t| t.status == status).count();
+
+    let completed: Vec<&LiveTrade> = in_window.iter().copied().filter(|t| t.status == "COMPLETED").collect();
+    let profits: Vec<f64> = completed.iter().filter_map(|t| trade_pnl(t)).collect();
+    let profit_pcts: Vec<f64> = completed.iter().filter_map(|t| t.profit_loss_pct).collect();
+    let execution_ms: Vec<f64> = in_window.iter().filter_map(|t| t.total_execution_ms).collect();
+
+    let mut by_hour: HashMap<DateTime<Utc>, HourlyPnl> = HashMap::new();
+    let mut by_path: HashMap<&str, (usize, f64, Vec<f64>)> = HashMap::new();
+    for trade in &in_window {
+        let Some(created) = trade.created_at else { continue };
+        let hour = created.duration_trunc(Duration::hours(1)).unwrap_or(created);
+        let entry = by_hour.entry(hour).or_insert(HourlyPnl { hour, trades: 0, pnl: 0.0 });
+        entry.trades += 1;
+        entry.pnl += trade_pnl(trade).unwrap_or(0.0);
+    }
+    for trade in &completed {
+        let entry = by_path.entry(trade.path.as_str()).or_default();
+        entry.0 += 1;
+        entry.1 += trade_pnl(trade).unwrap_or(0.0);
+        entry.2.extend(trade.profit_loss_pct);
+    }
+
+    // Newest first, so the first error seen per pair is the latest
+    let mut failing: HashMap<String, FailingPair> = HashMap::new();
+    let mut by_newest = in_window.clone();
+    by_newest.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+    for trade in by_newest.iter().filter(|t| t.status == "FAILED" || t.status == "PARTIAL") {
+        let legs = trade.leg_fills.as_ref().and_then(|l| l.as_array());
+        for leg in legs.into_iter().flatten() {
+            if leg.get("success").and_then(|s| s.as_bool()) != Some(false) {
+                continue;
+            }
+            let Some(pair) = leg.get("pair").and_then(|p| p.as_str()) else { continue };
+            let entry = failing.entry(pair.to_string()).or_insert_with(|| FailingPair {
+                pair: pair.to_string(),
+                failures: 0,
+                last_error: leg.get("error").and_then(|e| e.as_str()).map(String::from),
+            });
+            entry.failures += 1;
+        }
+    }
+
+    let mut pnl_by_hour: Vec<HourlyPnl> = by_hour.into_values().collect();
+    pnl_by_hour.sort_by_key(|h| h.hour);
+    let mut top_paths: Vec<PathProfit> = by_path
+        .into_iter()
+        .filter(|(_, (_, total, _))| *total > 0.0)
+        .map(|(path, (trades, total_pnl, pcts))| PathProfit {
+            path: path.to_string(),
+            trades,
+            total_pnl,
+            avg_profit_pct: mean(&pcts).unwrap_or(0.0),
+        })
+        .collect();
+    top_paths.sort_by(|a, b| b.total_pnl.total_cmp(&a.total_pnl));
+    top_paths.truncate(TOP_N);
+    let mut top_failing_pairs: Vec<FailingPair> = failing.into_values().collect();
+    top_failing_pairs.sort_by(|a, b| b.failures.cmp(&a.failures).then_with(|| a.pair.cmp(&b.pair)));
+    top_failing_pairs.truncate(TOP_N);
+
+    AnalyticsSummary {
+        window_secs: window.num_seconds(),
+        from,
+        to: now,
+        trades: in_window.len(),
+        completed: completed.len(),
+        failed: count("FAILED"),
+        partial: count("PARTIAL"),
+        win_rate: (!completed.is_empty())
+            .then(|| completed.iter().filter(|t| trade_pnl(t).unwrap_or(0.0) > 0.0).count() as f64 / completed.len() as f64),
+        total_pnl: in_window.iter().filter_map(|t| trade_pnl(t)).sum(),
+        avg_profit: mean(&profits),
+        median_profit: median(&profits),
+        avg_profit_pct: mean(&profit_pcts),
+        avg_execution_ms: mean(&execution_ms),
+        pnl_by_hour,
+        top_paths,
+        top_failing_pairs,
+    }
+}
+
+/// Cooldowns tried in a what-if run (milliseconds, 0 = off)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WhatIfCooldown {
+    pub global_ms: u64,
+    pub path_ms: u64,
+}
+
+/// What one threshold and cooldown would have done
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WhatIfScenario {
+    /// Net profit in percent
+    pub threshold_pct: f64,
+    pub cooldown: WhatIfCooldown,
+    /// Opportunities at or above the threshold
+    pub qualifying: usize,
+    /// Trades that would have triggered
+    pub trades: usize,
+    /// Qualifying opportunities a cooldown would have blocked
+    pub cooled_down: usize,
+    /// Expected profit of the triggered trades, in USD
+    pub estimated_pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WhatIfReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Recorded opportunities replayed
+    pub opportunities: usize,
+    /// Lowest recorded profit in percent: thresholds below it undercount
+    pub min_recorded_profit_pct: Option<f64>,
+    /// Thresholds ascending, then cooldowns in the order given
+    pub scenarios: Vec<WhatIfScenario>,
+}
+
+/// Expected profit in USD: as recorded, else from the recorded size or
+/// `trade_amount`
+fn expected_pnl(opp: &LiveOpportunity, trade_amount: Option<f64>) -> f64 {
+    opp.expected_profit_usd
+        .or_else(|| Some(opp.expected_profit_pct / 100.0 * opp.trade_amount.or(trade_amount)?))
+        .unwrap_or(0.0)
+}
+
+fn replay(found: &[(DateTime<Utc>, &LiveOpportunity)], threshold_pct: f64, cooldown: WhatIfCooldown, trade_amount: Option<f64>) -> WhatIfScenario {
+    let mut scenario = WhatIfScenario { threshold_pct, cooldown, qualifying: 0, trades: 0, cooled_down: 0, estimated_pnl: 0.0 };
+    let mut global_until: Option<DateTime<Utc>> = None;
+    let mut paths: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for &(at, opp) in found.iter().filter(|(_, o)| o.expected_profit_pct >= threshold_pct) {
+        scenario.qualifying += 1;
+        if global_until.is_some_and(|until| at < until) || paths.get(opp.path.as_str()).is_some_and(|until| at < *until) {
+            scenario.cooled_down += 1;
+            continue;
+        }
+        scenario.trades += 1;
+        scenario.estimated_pnl += expected_pnl(opp, trade_amount);
+        global_until = Some(at + Duration::milliseconds(cooldown.global_ms as i64));
+        paths.insert(opp.path.as_str(), at + Duration::milliseconds(cooldown.path_ms as i64));
+    }
+    scenario
+}
+
+/// Replay the opportunities found within `window` before `now` against
+/// every threshold and cooldown combination
+pub fn what_if(
+    opportunities: &[LiveOpportunity],
+    thresholds_pct: &[f64],
+    cooldowns: &[WhatIfCooldown],
+    trade_amount: Option<f64>,
+    window: Duration,
+    now: DateTime<Utc>,
+) -> WhatIfReport {
+    let from = now - window;
+    let mut found: Vec<(DateTime<Utc>, &LiveOpportunity)> = opportunities
+        .iter()
+        .filter_map(|o| Some((o.found_at?, o)))
+        .filter(|(at, _)| *at >= from)
+        .collect();
+    found.sort_by_key(|(at, o)| (*at, o.id));
+
+    let mut thresholds = thresholds_pct.to_vec();
+    thresholds.sort_by(|a, b| a.total_cmp(b));
+    thresholds.dedup();
+    let scenarios = thresholds
+        .iter()
+        .flat_map(|t| cooldowns.iter().map(move |c| (*t, *c)))
+        .map(|(threshold, cooldown)| replay(&found, threshold, cooldown, trade_amount))
+        .collect();
+
+    WhatIfReport {
+        from,
+        to: now,
+        opportunities: found.len(),
+        min_recorded_profit_pct: found.iter().map(|(_, o)| o.expected_profit_pct).min_by(|a, b| a.total_cmp(b)),
+        scenarios,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn trade(path: &str, status: &str, pnl: Option<f64>, minutes_ago: i64, now: DateTime<Utc>) -> LiveTrade {
+        LiveTrade {
+            id: 0,
+            trade_id: format!("t-{}", minutes_ago),
+            path: path.to_string(),
+            legs: 3,
+            amount_in: 100.0,
+            amount_out: None,
+            profit_loss: pnl,
+            profit_loss_pct: pnl,
+            pnl_currency: Some("USD".to_string()),
+            pnl_reporting: None,
+            reporting_currency: None,
+            fx_rate: None,
+            status: status.to_string(),
+            current_leg: None,
+            error_message: None,
+            held_currency: None,
+            held_amount: None,
+            held_value_usd: None,
+            resolved_at: None,
+            resolved_amount_usd: None,
+            resolution_trade_id: None,
+            order_ids: None,
+            leg_fills: None,
+            started_at: None,
+            completed_at: None,
+            total_execution_ms: Some(100.0),
+            opportunity_profit_pct: None,
+            strategy: None,
+            tags: vec![],
+            session_id: None,
+            notes: None,
+            labels: vec![],
+            notes_updated_at: None,
+            created_at: Some(now - Duration::minutes(minutes_ago)),
+        }
+    }
+
+    #[test]
+    fn test_summary_over_window() {
+        assert_eq!(parse_window("24h").unwrap(), Duration::hours(24));
+        assert_eq!(parse_window("7d").unwrap(), Duration::days(7));
+        assert_eq!(parse_window("90").unwrap(), Duration::hours(90));
+        assert!(parse_window("0h").is_err());
+        assert!(parse_window("31d").is_err());
+        assert!(parse_window("1w").is_err());
+
+        let now = Utc::now();
+        let a = "USD → BTC → ETH → USD";
+        let b = "USD → SOL → BTC → USD";
+        let mut failed = trade(b, "FAILED", None, 30, now);
+        failed.leg_fills = Some(json!([
+            {"leg": 1, "pair": "SOL/USD", "success": true},
+            {"leg": 2, "pair": "SOL/BTC", "success": false, "error": "EOrder:Insufficient funds"}
+        ]));
+        let trades = vec![
+            trade(a, "COMPLETED", Some(0.4), 10, now),
+            trade(a, "COMPLETED", Some(0.2), 20, now),
+            trade(b, "COMPLETED", Some(-0.3), 40, now),
+            failed,
+            // Outside the window
+            trade(a, "COMPLETED", Some(5.0), 25 * 60, now),
+        ];
+
+        let summary = summarize(&trades, Duration::hours(24), now);
+        assert_eq!((summary.trades, summary.completed, summary.failed), (4, 3, 1));
+        assert!((summary.win_rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((summary.total_pnl - 0.3).abs() < 1e-9);
+        assert!((summary.median_profit.unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(summary.avg_execution_ms, Some(100.0));
+        assert_eq!(summary.pnl_by_hour.iter().map(|h| h.trades).sum::<usize>(), 4);
+        assert_eq!(summary.top_paths.len(), 1);
+        assert_eq!((summary.top_paths[0].path.as_str(), summary.top_paths[0].trades), (a, 2));
+        assert_eq!(summary.top_failing_pairs, vec![FailingPair {
+            pair: "SOL/BTC".to_string(),
+            failures: 1,
+            last_error: Some("EOrder:Insufficient funds".to_string()),
+        }]);
+    }
+
+    #[test]
+    fn test_what_if_replay() {
+        let now = Utc::now();
+        let opp = |id: i32, path: &str, pct: f64, seconds_ago: i64| LiveOpportunity {
+            id,
+            found_at: Some(now - Duration::seconds(seconds_ago)),
+            path: path.to_string(),
+            legs: 3,
+            expected_profit_pct: pct,
+            expected_profit_usd: None,
+            trade_amount: None,
+            status: "PENDING".to_string(),
+            status_reason: None,
+            trade_id: None,
+            pairs_scanned: None,
+            paths_found: None,
+            notes: None,
+            labels: vec![],
+            notes_updated_at: None,
+            created_at: None,
+            updated_at: None,
+        };
+        let a = "USD → BTC → ETH → USD";
+        let b = "USD → SOL → BTC → USD";
+        let opportunities = vec![
+            opp(1, a, 0.30, 60),
+            opp(2, a, 0.30, 55),
+            opp(3, b, 0.10, 50),
+            opp(4, b, 0.25, 40),
+            // Outside the window
+            opp(5, a, 1.00, 7200),
+        ];
+        let none = WhatIfCooldown { global_ms: 0, path_ms: 0 };
+        let path_only = WhatIfCooldown { global_ms: 0, path_ms: 10_000 };
+        let long_global = WhatIfCooldown { global_ms: 30_000, path_ms: 0 };
+
+        let report = what_if(&opportunities, &[0.2, 0.05], &[none, path_only, long_global], Some(100.0), Duration::hours(1), now);
+        assert_eq!((report.opportunities, report.min_recorded_profit_pct), (4, Some(0.10)));
+        let outcome: Vec<(f64, usize, usize)> = report.scenarios.iter().map(|s| (s.threshold_pct, s.trades, s.cooled_down)).collect();
+        assert_eq!(outcome, vec![
+            (0.05, 4, 0), (0.05, 3, 1), (0.05, 1, 3),
+            (0.2, 3, 0), (0.2, 2, 1), (0.2, 1, 2),
+        ]);
+        // 0.30% + 0.30% + 0.10% + 0.25% of 100 USD
+        assert!((report.scenarios[0].estimated_pnl - 0.95).abs() < 1e-9);
+    }
+}
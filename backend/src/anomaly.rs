@@ -0,0 +1,342 @@
+//! Profit Anomaly Detection
+//!
+//! Real triangular edges are a fraction of a percent; an opportunity far
+//! above that almost always comes from a bad quote rather than a real
+//! mispricing. An opportunity over ANOMALY_MAX_PROFIT_PCT (default 3.0,
+//! 0 = off) is quarantined instead of executed:
+//! - the leg whose mid disagrees most with its triangles is blamed (the leg
+//!   with the oldest quote when no leg has enough triangles)
+//! - that pair is flagged for ANOMALY_HOLD_SECS (default 300), and every
+//!   opportunity through it is quarantined too until the hold runs out or
+//!   an operator clears it
+//! - a newly flagged pair raises a notification
+//!
+//! Flagged pairs stay in the scan; only execution is withheld. GET
+//! /api/opportunities/anomalies lists them with the latest quarantined
+//! opportunities.
+#![allow(dead_code)]
+
+use crate::consistency::pair_divergence;
+use crate::notifications::{Notifications, Severity};
+use crate::order_book::OrderBookCache;
+use crate::types::{Opportunity, PriceEdge};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Quarantined opportunities kept for the status
+const RECENT_QUARANTINED: usize = 50;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AnomalyPolicy {
+    /// Net profit (%) above which an opportunity is implausible (0 = never)
+    pub max_profit_pct: f64,
+    /// Seconds a blamed pair stays flagged
+    pub hold_secs: u64,
+}
+
+impl Default for AnomalyPolicy {
+    fn default() -> Self {
+        Self { max_profit_pct: 3.0, hold_secs: 300 }
+    }
+}
+
+impl AnomalyPolicy {
+    /// Create from ANOMALY_MAX_PROFIT_PCT and ANOMALY_HOLD_SECS
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            max_profit_pct: env("ANOMALY_MAX_PROFIT_PCT").unwrap_or(defaults.max_profit_pct),
+            hold_secs: env("ANOMALY_HOLD_SECS").unwrap_or(defaults.hold_secs),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_profit_pct > 0.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyReason {
+    /// Net profit over the policy's maximum
+    ImplausibleProfit,
+    /// A leg goes through a flagged pair
+    SuspectPair,
+}
+
+/// Why an opportunity was quarantined
+#[derive(Debug, Clone, Serialize)]
+pub struct Anomaly {
+    pub reason: AnomalyReason,
+    /// The blamed or flagged pair
+    pub pair: String,
+    pub path: String,
+    pub net_profit_pct: f64,
+    /// Median divergence of the pair from its triangles, when it has enough
+    pub divergence_pct: Option<f64>,
+}
+
+/// A pair blamed for an implausible opportunity
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspectPair {
+    pub pair: String,
+    pub flagged_at: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub divergence_pct: Option<f64>,
+    /// The opportunity it was blamed for
+    pub path: String,
+    pub net_profit_pct: f64,
+    /// Opportunities quarantined because of it
+    pub quarantined: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantinedOpportunity {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub anomaly: Anomaly,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnomalyStatus {
+    pub policy: AnomalyPolicy,
+    pub quarantined: u64,
+    pub suspects: Vec<SuspectPair>,
+    /// Newest first
+    pub recent: Vec<QuarantinedOpportunity>,
+}
+
+pub struct AnomalyDetector {
+    policy: AnomalyPolicy,
+    notifications: Option<Arc<Notifications>>,
+    suspects: RwLock<HashMap<String, SuspectPair>>,
+    recent: RwLock<VecDeque<QuarantinedOpportunity>>,
+    quarantined: AtomicU64,
+}
+
+impl AnomalyDetector {
+    pub fn new(policy: AnomalyPolicy) -> Self {
+        Self {
+            policy,
+            notifications: None,
+            suspects: RwLock::new(HashMap::new()),
+            recent: RwLock::new(VecDeque::with_capacity(RECENT_QUARANTINED)),
+            quarantined: AtomicU64::new(0),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(AnomalyPolicy::from_env())
+    }
+
+    /// Raise newly flagged pairs in `notifications`
+    pub fn with_notifications(mut self, notifications: Arc<Notifications>) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+
+    pub fn policy(&self) -> &AnomalyPolicy {
+        &self.policy
+    }
+
+    /// Whether `opp` must not be executed, blaming a pair when its profit is
+    /// implausible (prices are only read in that case)
+    pub fn check(&self, opp: &Opportunity, cache: &OrderBookCache, now: DateTime<Utc>) -> Option<Anomaly> {
+        self.check_with(opp, || cache.get_all_prices(), now)
+    }
+
+    fn check_with(
+        &self,
+        opp: &Opportunity,
+        prices: impl FnOnce() -> HashMap<String, PriceEdge>,
+        now: DateTime<Utc>,
+    ) -> Option<Anomaly> {
+        if !self.policy.enabled() {
+            return None;
+        }
+        let anomaly = if opp.net_profit_pct > self.policy.max_profit_pct {
+            let (pair, divergence_pct) = Self::blame(opp, &prices(), now)?;
+            self.flag(opp, &pair, divergence_pct, now);
+            Anomaly {
+                reason: AnomalyReason::ImplausibleProfit,
+                pair,
+                path: opp.path.clone(),
+                net_profit_pct: opp.net_profit_pct,
+                divergence_pct,
+            }
+        } else {
+            let mut suspects = self.suspects.write();
+            suspects.retain(|_, s| s.until > now);
+            let pair = &opp.legs_detail.iter().find(|leg| suspects.contains_key(&leg.pair))?.pair;
+            let suspect = suspects.get_mut(pair)?;
+            suspect.quarantined += 1;
+            Anomaly {
+                reason: AnomalyReason::SuspectPair,
+                pair: suspect.pair.clone(),
+                path: opp.path.clone(),
+                net_profit_pct: opp.net_profit_pct,
+                divergence_pct: suspect.divergence_pct,
+            }
+        };
+
+        self.quarantined.fetch_add(1, Ordering::Relaxed);
+        let mut recent = self.recent.write();
+        if recent.len() == RECENT_QUARANTINED {
+            recent.pop_front();
+        }
+        recent.push_back(QuarantinedOpportunity { at: now, anomaly: anomaly.clone() });
+        Some(anomaly)
+    }
+
+    /// The leg most at odds with its triangles, else the one quoted longest ago
+    fn blame(opp: &Opportunity, prices: &HashMap<String, PriceEdge>, now: DateTime<Utc>) -> Option<(String, Option<f64>)> {
+        let divergent = opp
+            .legs_detail
+            .iter()
+            .filter_map(|leg| Some((leg.pair.clone(), pair_divergence(prices, &leg.pair, now)?)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        if let Some((pair, divergence)) = divergent {
+            return Some((pair, Some(divergence)));
+        }
+        let oldest = opp
+            .legs_detail
+            .iter()
+            .min_by_key(|leg| prices.get(&leg.pair).map(|e| e.last_update))?;
+        Some((oldest.pair.clone(), None))
+    }
+
+    fn flag(&self, opp: &Opportunity, pair: &str, divergence_pct: Option<f64>, now: DateTime<Utc>) {
+        let until = now + Duration::seconds(self.policy.hold_secs as i64);
+        let mut suspects = self.suspects.write();
+        if let Some(suspect) = suspects.get_mut(pair).filter(|s| s.until > now) {
+            suspect.until = until;
+            suspect.quarantined += 1;
+            return;
+        }
+        suspects.insert(pair.to_string(), SuspectPair {
+            pair: pair.to_string(),
+            flagged_at: now,
+            until,
+            divergence_pct,
+            path: opp.path.clone(),
+            net_profit_pct: opp.net_profit_pct,
+            quarantined: 1,
+        });
+        drop(suspects);
+
+        if let Some(notifications) = &self.notifications {
+            let title = format!(
+                "Implausible {:.2}% opportunity on {}: {} flagged for validation{}",
+                opp.net_profit_pct,
+                opp.path,
+                pair,
+                divergence_pct.map(|d| format!(" ({:.2}% off triangles)", d)).unwrap_or_default()
+            );
+            notifications.push(Severity::Warning, "anomaly", title, serde_json::json!({
+                "pair": pair,
+                "path": opp.path,
+                "net_profit_pct": opp.net_profit_pct,
+                "divergence_pct": divergence_pct,
+                "until": until,
+            }));
+        }
+    }
+
+    /// Release a flagged pair before its hold runs out
+    pub fn clear(&self, pair: &str) -> bool {
+        self.suspects.write().remove(pair).is_some()
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> AnomalyStatus {
+        let mut suspects: Vec<SuspectPair> =
+            self.suspects.read().values().filter(|s| s.until > now).cloned().collect();
+        suspects.sort_by_key(|s| std::cmp::Reverse(s.flagged_at));
+        AnomalyStatus {
+            policy: self.policy,
+            quarantined: self.quarantined.load(Ordering::Relaxed),
+            suspects,
+            recent: self.recent.read().iter().rev().cloned().collect(),
+        }
+    }
+}
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        Self::new(AnomalyPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LegDetail;
+
+    fn edge(base: &str, quote: &str, mid: f64) -> (String, PriceEdge) {
+        let pair = format!("{}/{}", base, quote);
+        (pair.clone(), PriceEdge {
+            pair,
+            base: base.to_string(),
+            quote: quote.to_string(),
+            bid: mid * 0.9999,
+            ask: mid * 1.0001,
+            volume_24h: 0.0,
+            last_update: Utc::now(),
+        })
+    }
+
+    fn opportunity(path: &str, pairs: &[&str], net_profit_pct: f64) -> Opportunity {
+        Opportunity {
+            legs_detail: pairs
+                .iter()
+                .map(|p| LegDetail { pair: p.to_string(), action: "buy".to_string(), rate: 1.0 })
+                .collect(),
+            ..Opportunity::for_test(path, net_profit_pct)
+        }
+    }
+
+    #[test]
+    fn test_implausible_profit_flags_divergent_leg() {
+        let prices: HashMap<String, PriceEdge> = [
+            edge("BTC", "USD", 50000.0),
+            edge("EUR", "USD", 1.10),
+            edge("ETH", "USD", 2500.0),
+            edge("ETH", "BTC", 0.05),
+            edge("BTC", "EUR", 45454.5),
+            // Broken quote: should be ~1 USD
+            edge("USDC", "USD", 1.30),
+            edge("USDC", "EUR", 0.909),
+            edge("BTC", "USDC", 50000.0),
+        ]
+        .into_iter()
+        .collect();
+        let detector = AnomalyDetector::new(AnomalyPolicy { max_profit_pct: 3.0, hold_secs: 300 });
+        let now = Utc::now();
+
+        let broken = opportunity("USD → BTC → USDC → USD", &["BTC/USD", "BTC/USDC", "USDC/USD"], 29.5);
+        let anomaly = detector.check_with(&broken, || prices.clone(), now).unwrap();
+        assert_eq!((anomaly.reason, anomaly.pair.as_str()), (AnomalyReason::ImplausibleProfit, "USDC/USD"));
+
+        // A plausible opportunity through the flagged pair is held back too
+        let through = opportunity("USD → USDC → EUR → USD", &["USDC/USD", "USDC/EUR", "EUR/USD"], 0.4);
+        let anomaly = detector.check_with(&through, HashMap::new, now).unwrap();
+        assert_eq!(anomaly.reason, AnomalyReason::SuspectPair);
+        let clean = opportunity("USD → BTC → ETH → USD", &["BTC/USD", "ETH/BTC", "ETH/USD"], 0.4);
+        assert!(detector.check_with(&clean, HashMap::new, now).is_none());
+
+        let status = detector.status(now);
+        assert_eq!((status.quarantined, status.suspects.len(), status.suspects[0].quarantined), (2, 1, 2));
+
+        // Released by its hold running out, or by an operator
+        assert!(detector.check_with(&through, HashMap::new, now + Duration::seconds(301)).is_none());
+        detector.check_with(&broken, || prices.clone(), now);
+        assert!(detector.clear("USDC/USD"));
+        assert!(detector.check_with(&through, HashMap::new, now).is_none());
+    }
+}
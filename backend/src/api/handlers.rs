@@ -2,12 +2,26 @@
 //!
 //! All endpoint handlers for the trading API.
 
-use crate::db::{ConfigUpdate, NewLiveTrade};
+use crate::analytics::{self, parse_window, WhatIfCooldown, MAX_WHAT_IF_SCENARIOS, MAX_WINDOW_HOURS};
+use crate::audit::{AuditActor, AuditCategory};
+use crate::config_schema::{ConfigError, ConfigPatch, FeePatch};
+use crate::db::ConfigUpdate;
+use crate::executor::PriceImprovementStats;
+use crate::export::{csv_stream, ExportFormat, ExportKind, ExportRange};
+use crate::loadgen::LoadGenConfig;
+use crate::opportunity_cache;
+use crate::opportunity_filter::FilterConfig;
 use crate::restrictions::{AddRemoveRequest, UpdateRequest};
+use crate::trading::EngineError;
+use crate::trading_day::{self, DailyResetStatus};
+use crate::universe::{Universe, UniverseTrigger};
+use crate::valuation::PricingSource;
 use crate::AppState;
+use super::read_cache;
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -56,7 +70,7 @@ fn is_dst(dt: &DateTime<Utc>) -> bool {
 
     // April-October: DST active
     // December-February: Standard time
-    month >= 4 && month <= 10
+    (4..=10).contains(&month)
 }
 
 /// Format a UTC datetime in Eastern Time (ET/EDT)
@@ -96,6 +110,26 @@ pub fn bad_request(error: &str) -> Response {
     ).into_response()
 }
 
+/// 400 listing every invalid field, 500 for storage failures
+fn config_error_response(error: ConfigError) -> Response {
+    match error {
+        ConfigError::Invalid(ref errors) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "success": false,
+                "error": error.to_string(),
+                "errors": errors
+            }))
+        ).into_response(),
+        ConfigError::Database(e) => error_response(&e),
+    }
+}
+
+/// Record an operator action in the audit log
+fn audit_api(state: &AppState, category: AuditCategory, action: &str, details: serde_json::Value) {
+    state.engine.audit().record(AuditActor::Api, category, action, details);
+}
+
 // ==========================================
 // Request Types
 // ==========================================
@@ -120,6 +154,53 @@ fn default_disable_reason() -> String {
 pub struct ExecuteTradeRequest {
     pub path: String,
     pub amount: Option<f64>,
+    /// Labels stored with the trade for attribution
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+const MAX_NOTE_CHARS: usize = 2_000;
+const MAX_LABELS: usize = 20;
+const MAX_LABEL_CHARS: usize = 50;
+
+/// Operator notes for a trade or opportunity. Omitted fields are left as
+/// they are; an empty note clears it.
+#[derive(Debug, Deserialize)]
+pub struct NotesRequest {
+    pub notes: Option<String>,
+    pub labels: Option<Vec<String>>,
+}
+
+impl NotesRequest {
+    /// Trimmed note and de-duplicated, trimmed labels
+    fn validated(self) -> Result<(Option<String>, Option<Vec<String>>), String> {
+        if self.notes.is_none() && self.labels.is_none() {
+            return Err("Nothing to update: send notes and/or labels".to_string());
+        }
+        let notes = self.notes.map(|n| n.trim().to_string());
+        if notes.as_ref().is_some_and(|n| n.chars().count() > MAX_NOTE_CHARS) {
+            return Err(format!("Notes are limited to {} characters", MAX_NOTE_CHARS));
+        }
+        let labels = match self.labels {
+            Some(raw) => {
+                let mut labels: Vec<String> = Vec::new();
+                for label in raw.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+                    if label.chars().count() > MAX_LABEL_CHARS {
+                        return Err(format!("Label '{}' is longer than {} characters", label, MAX_LABEL_CHARS));
+                    }
+                    if !labels.iter().any(|l| l == label) {
+                        labels.push(label.to_string());
+                    }
+                }
+                if labels.len() > MAX_LABELS {
+                    return Err(format!("At most {} labels", MAX_LABELS));
+                }
+                Some(labels)
+            }
+            None => None,
+        };
+        Ok((notes, labels))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -131,6 +212,10 @@ pub struct TradesQuery {
     pub status: Option<String>,
     #[serde(default = "default_hours")]
     pub hours: i32,
+    /// Only trades from this strategy (triangular, cross_pair, manual, stablecoin)
+    pub strategy: Option<String>,
+    /// Only trades carrying this tag
+    pub tag: Option<String>,
 }
 
 fn default_limit() -> i64 { 20 }
@@ -150,11 +235,107 @@ pub struct FeeConfigUpdate {
     pub taker_fee: Option<f64>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DeadManSwitchUpdate {
+    pub enabled: bool,
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default = "default_audit_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    /// order, guard, config, breaker
+    pub category: Option<String>,
+    /// auto, api, system
+    pub actor: Option<String>,
+    /// e.g. config_change, reserve_block
+    pub action: Option<String>,
+    #[serde(default = "default_hours")]
+    pub hours: i32,
+}
+
+fn default_audit_limit() -> i64 { 100 }
+
 #[derive(Debug, Deserialize)]
 pub struct LimitQuery {
     pub limit: Option<usize>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DepthQuery {
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairRouteQuery {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationsQuery {
+    /// Only notifications newer than this id (for polling)
+    pub since_id: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FillsQuery {
+    /// Exchange order_id or our cl_ord_id; omitted = recent orders
+    pub order_id: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsHistoryQuery {
+    #[serde(default = "default_history_minutes")]
+    pub minutes: u64,
+}
+
+fn default_history_minutes() -> u64 { 60 }
+
+/// Stats history windows are capped at a week
+const MAX_HISTORY_MINUTES: u64 = 7 * 24 * 60;
+
+#[derive(Debug, Deserialize)]
+pub struct GuardsQuery {
+    /// Cached opportunity ID (all cached opportunities when absent)
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    /// e.g. "30m", "24h", "7d"
+    #[serde(default = "default_analytics_window")]
+    pub window: String,
+}
+
+fn default_analytics_window() -> String { "24h".to_string() }
+
+/// Most trades an analytics summary reads
+const MAX_ANALYTICS_TRADES: i64 = 50_000;
+
+#[derive(Debug, Deserialize)]
+pub struct WhatIfRequest {
+    /// History replayed, in hours
+    #[serde(default = "default_what_if_hours")]
+    pub hours: i64,
+    /// Net profit thresholds in percent (default: the configured one)
+    #[serde(default)]
+    pub thresholds_pct: Vec<f64>,
+    /// Cooldowns (default: the configured ones)
+    #[serde(default)]
+    pub cooldowns: Vec<WhatIfCooldown>,
+}
+
+fn default_what_if_hours() -> i64 { 24 }
+
+/// Most recorded opportunities a what-if run replays
+const MAX_WHAT_IF_OPPORTUNITIES: i64 = 200_000;
+
 // ==========================================
 // Health & Status Handlers
 // ==========================================
@@ -174,6 +355,8 @@ pub async fn get_status(
     
     Json(serde_json::json!({
         "is_running": stats.is_running,
+        "status": if !stats.is_running { "stopped" } else if stats.degraded { "degraded" } else { "running" },
+        "panics_since_start": stats.panics_since_start,
         "engine": "rust_v2",
         "pairs_monitored": stats.pairs_monitored,
         "currencies_tracked": stats.currencies_tracked,
@@ -184,9 +367,29 @@ pub async fn get_status(
         "uptime_seconds": stats.uptime_seconds,
         "scan_cycle_ms": stats.scan_cycle_ms,
         "last_scan_at": stats.last_scan_at,
+        "ws_traffic": stats.ws_traffic,
+        "ws_parse": stats.ws_parse,
+        "cache_memory": stats.cache_memory,
+        "read_cache": state.read_cache.stats(),
     }))
 }
 
+/// GET /api/snapshot - Stats, config and state gathered in one read
+pub async fn get_engine_snapshot(State(state): State<Arc<AppState>>) -> Response {
+    Json(serde_json::json!({
+        "success": true,
+        "snapshot": state.engine.get_engine_snapshot().await
+    })).into_response()
+}
+
+/// Panics recorded by the hook and the health of supervised tasks
+pub async fn get_engine_errors(State(state): State<Arc<AppState>>) -> Response {
+    Json(serde_json::json!({
+        "success": true,
+        "errors": state.engine.get_errors()
+    })).into_response()
+}
+
 pub async fn restart_engine(
     State(state): State<Arc<AppState>>,
 ) -> Response {
@@ -269,6 +472,19 @@ pub async fn get_config(
                 "max_pairs": config.max_pairs,
                 "min_volume_24h_usd": config.min_volume_24h_usd,
                 "max_cost_min": config.max_cost_min,
+                "sizing_tiers": config.sizing_tiers,
+                "max_safe_amount": config.max_safe_amount,
+                "global_cooldown_ms": config.global_cooldown_ms,
+                "path_cooldown_ms": config.path_cooldown_ms,
+                "pair_failure_cooldown_ms": config.pair_failure_cooldown_ms,
+                "leg_depth_levels": config.leg_depth_levels,
+                "max_leg_slippage_bps": config.max_leg_slippage_bps,
+                "currency_reserves": config.currency_reserves,
+                "max_notional_per_hour": config.max_notional_per_hour,
+                "max_notional_per_day": config.max_notional_per_day,
+                "execution_disabled_pairs": config.execution_disabled_pairs,
+                "leg_profit_thresholds": config.leg_profit_thresholds,
+                "daily_reset_timezone": config.daily_reset_timezone,
                 "session": session_info
             })).into_response()
         },
@@ -400,10 +616,7 @@ pub async fn get_configuration_status(
     };
 
     // Get fee configuration
-    let fee_config = match state.db.get_fee_configuration().await {
-        Ok(fc) => fc,
-        Err(_) => crate::db::FeeConfiguration::default(),
-    };
+    let fee_config = state.db.get_fee_configuration().await.unwrap_or_default();
 
     let fees_configured = fee_config.fee_source != "pending";
     if !fees_configured {
@@ -454,15 +667,80 @@ pub async fn update_config(
     State(state): State<Arc<AppState>>,
     Json(updates): Json<ConfigUpdate>,
 ) -> Response {
-    match state.db.update_config(updates).await {
-        Ok(config) => {
-            state.engine.sync_config(&config).await;
-            Json(serde_json::json!({
+    let patch = ConfigPatch { trading: Some(updates), fees: None };
+    match state.engine.apply_config(patch, AuditActor::Api, "live_config").await {
+        Ok(applied) => Json(serde_json::json!({
+            "success": true,
+            "message": "Configuration updated",
+            "config": applied.config,
+            "changes": applied.changes
+        })).into_response(),
+        Err(e) => config_error_response(e),
+    }
+}
+
+/// GET /api/config - All settings as one document
+pub async fn get_config_document(
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    match state.engine.get_config_document().await {
+        Ok(document) => Json(serde_json::json!({
+            "success": true,
+            "data": document
+        })).into_response(),
+        Err(e) => config_error_response(e),
+    }
+}
+
+/// PUT /api/config - Update any part of the document; lists every invalid field
+pub async fn update_config_document(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<serde_json::Value>,
+) -> Response {
+    let patch = match ConfigPatch::parse(&body) {
+        Ok(patch) => patch,
+        Err(e) => return config_error_response(e),
+    };
+    match state.engine.apply_config(patch, AuditActor::Api, "config").await {
+        Ok(applied) => match state.engine.get_config_document().await {
+            Ok(document) => Json(serde_json::json!({
                 "success": true,
-                "message": "Configuration updated",
-                "config": config
-            })).into_response()
-        }
+                "message": if applied.changes.is_empty() { "No changes" } else { "Configuration updated" },
+                "changes": applied.changes,
+                "data": document
+            })).into_response(),
+            Err(e) => config_error_response(e),
+        },
+        Err(e) => config_error_response(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigHistoryQuery {
+    #[serde(default = "default_audit_limit")]
+    pub limit: i64,
+    #[serde(default = "default_hours")]
+    pub hours: i32,
+}
+
+/// GET /api/config/history - Recent configuration diffs, newest first
+pub async fn get_config_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConfigHistoryQuery>,
+) -> Response {
+    match state.db.get_audit_entries(
+        params.limit.clamp(1, 1000),
+        0,
+        Some(AuditCategory::Config.as_str()),
+        None,
+        Some("config_change"),
+        params.hours,
+    ).await {
+        Ok(entries) => Json(serde_json::json!({
+            "success": true,
+            "count": entries.len(),
+            "data": entries
+        })).into_response(),
         Err(e) => error_response(&e.to_string()),
     }
 }
@@ -527,9 +805,16 @@ pub async fn enable_trading(
     match state.db.enable_trading().await {
         Ok(config) => {
             // Start the full HFT engine (WebSocket connection, HFT loop, execution engine)
-            if let Err(e) = state.engine.start().await {
+            let started = state.engine.start().await;
+            state.read_cache.invalidate();
+            if let Err(e) = started {
                 return error_response(&format!("Failed to start HFT engine: {}", e));
             }
+            audit_api(&state, AuditCategory::Config, "trading_enabled", serde_json::json!({
+                "trade_amount": config.trade_amount,
+                "min_profit_threshold": config.min_profit_threshold,
+                "start_currency": config.start_currency,
+            }));
 
             info!("HFT Engine STARTED: trade_amount=${}, min_profit={:.2}%, start_currency={}",
                 config.trade_amount.unwrap_or(0.0),
@@ -554,6 +839,8 @@ pub async fn disable_trading(
         Ok(config) => {
             // Stop the full HFT engine (WebSocket, HFT loop, execution engine)
             state.engine.stop().await;
+            state.read_cache.invalidate();
+            audit_api(&state, AuditCategory::Config, "trading_disabled", serde_json::json!({ "reason": req.reason }));
             info!("HFT Engine STOPPED: {}", req.reason);
             Json(serde_json::json!({
                 "success": true,
@@ -572,19 +859,44 @@ pub async fn disable_trading(
 pub async fn get_live_status(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
+    // Read the generation before querying so a concurrent write can't be cached
+    let generation = state.db.write_generation();
+    if let Some(cached) = state.read_cache.get(read_cache::LIVE_STATUS, generation) {
+        return Json(cached);
+    }
+
     let config = state.db.get_config().await.unwrap_or_default();
     let db_state = state.db.get_state().await.unwrap_or_default();
     let engine_stats = state.engine.get_stats().await;
     
-    Json(serde_json::json!({
+    let response = serde_json::json!({
         "config": config,
         "state": db_state,
         "engine": {
             "is_running": engine_stats.is_running,
             "pairs_monitored": engine_stats.pairs_monitored,
             "auto_execution_enabled": state.engine.is_auto_execution_enabled(),
-        }
-    }))
+            "paths_filtered_liquidity": engine_stats.paths_filtered_liquidity,
+            "events_skipped_deep": engine_stats.events_skipped_deep,
+            "warmup": state.engine.get_warmup().await,
+            "reconnects": state.engine.get_reconnect_stats(),
+            "dead_man_switch": state.engine.get_dead_man_status(),
+            "safe_mode": state.engine.get_safe_mode(),
+            "throttle": state.engine.get_throttle(),
+            "scan_fairness": state.engine.get_scan_fairness(),
+            "exec_queue": state.engine.get_exec_queue(),
+            "depth_tiers": state.engine.get_depth_tiers(),
+            "strategy_plugins": state.engine.get_strategy_plugins(),
+            "ws_token": state.engine.get_ws_token_stats(),
+        },
+        "daily_reset": DailyResetStatus::new(
+            trading_day::timezone_or_utc(config.daily_reset_timezone.as_deref()),
+            db_state.last_daily_reset,
+            Utc::now(),
+        )
+    });
+    state.read_cache.put(read_cache::LIVE_STATUS, generation, response.clone());
+    Json(response)
 }
 
 pub async fn get_state(
@@ -599,9 +911,43 @@ pub async fn get_state(
     }
 }
 
+pub async fn get_cooldowns(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let cooldowns = state.engine.get_cooldowns().await;
+    Json(serde_json::json!({
+        "count": cooldowns.len(),
+        "cooldowns": cooldowns,
+    }))
+}
+
+pub async fn get_audit_log(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AuditQuery>,
+) -> Response {
+    let limit = params.limit.clamp(1, 1000);
+    match state.db.get_audit_entries(
+        limit,
+        params.offset,
+        params.category.as_deref(),
+        params.actor.as_deref(),
+        params.action.as_deref(),
+        params.hours,
+    ).await {
+        Ok(entries) => Json(serde_json::json!({
+            "success": true,
+            "count": entries.len(),
+            "dropped": state.engine.audit().dropped(),
+            "data": entries
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
 pub async fn get_circuit_breaker(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
+    let notional = state.engine.get_notional_headroom().await;
     match state.db.get_state().await {
         Ok(s) => Json(serde_json::json!({
             "is_broken": s.is_circuit_broken,
@@ -611,6 +957,10 @@ pub async fn get_circuit_breaker(
             "daily_profit": s.daily_profit,
             "total_loss": s.total_loss,
             "total_profit": s.total_profit,
+            "currency": crate::valuation::reporting_currency_from_env(),
+            "notional": notional,
+            "throttle": state.engine.get_throttle(),
+            "sync": state.engine.get_breaker_sync(),
         })),
         Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
     }
@@ -619,9 +969,9 @@ pub async fn get_circuit_breaker(
 pub async fn reset_circuit_breaker(
     State(state): State<Arc<AppState>>,
 ) -> Response {
-    match state.db.reset_circuit_breaker().await {
+    match state.engine.reset_circuit_breaker().await {
         Ok(s) => {
-            state.engine.reset_circuit_breaker().await;
+            audit_api(&state, AuditCategory::Breaker, "circuit_breaker_reset", serde_json::json!({}));
             Json(serde_json::json!({
                 "success": true,
                 "message": "Circuit breaker reset",
@@ -639,6 +989,7 @@ pub async fn trigger_circuit_breaker(
     match state.db.trip_circuit_breaker(&params.reason).await {
         Ok(s) => {
             state.engine.trip_circuit_breaker(&params.reason).await;
+            audit_api(&state, AuditCategory::Breaker, "circuit_breaker_tripped", serde_json::json!({ "reason": params.reason }));
             Json(serde_json::json!({
                 "success": true,
                 "message": format!("Circuit breaker triggered: {}", params.reason),
@@ -687,6 +1038,19 @@ pub async fn reset_all_stats(
     }
 }
 
+/// GET /api/live/daily-reset - Timezone, trading day and next automatic reset
+pub async fn get_daily_reset(
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    match state.engine.get_daily_reset().await {
+        Ok(status) => Json(serde_json::json!({
+            "success": true,
+            "daily_reset": status
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
 // ==========================================
 // Trade Execution Handlers
 // ==========================================
@@ -701,38 +1065,36 @@ pub async fn execute_trade(
         return bad_request("Trade amount not configured. Please set from the dashboard.");
     }
     
-    match state.engine.execute_trade(&req.path, amount).await {
-        Ok(result) => {
-            let trade = NewLiveTrade {
-                trade_id: result.id.clone(),
-                path: result.path.clone(),
-                legs: result.legs.len() as i32,
-                amount_in: result.start_amount,
-                amount_out: Some(result.end_amount),
-                profit_loss: Some(result.profit_amount),
-                profit_loss_pct: Some(result.profit_pct),
-                status: if result.success { "COMPLETED".to_string() } else { "FAILED".to_string() },
-                current_leg: Some(result.legs.len() as i32),
-                error_message: result.error.clone(),
-                held_currency: None,
-                held_amount: None,
-                held_value_usd: None,
-                order_ids: Some(serde_json::json!(result.legs.iter().map(|l| &l.order_id).collect::<Vec<_>>())),
-                leg_fills: Some(serde_json::to_value(&result.legs).unwrap_or_default()),
-                started_at: Some(result.executed_at),
-                completed_at: Some(chrono::Utc::now()),
-                total_execution_ms: Some(result.total_duration_ms as f64),
-                opportunity_profit_pct: None,
-            };
-            
-            let _ = state.db.save_trade(&trade).await;
-            
-            Json(serde_json::json!({
-                "success": true,
-                "data": result
-            })).into_response()
+    match state.engine.execute_trade(&req.path, amount, req.tags.clone()).await {
+        // The engine saved it and counted it against the guards
+        Ok(result) => Json(serde_json::json!({
+            "success": true,
+            "data": result
+        })).into_response(),
+        Err(e) => {
+            audit_api(&state, AuditCategory::Guard, "manual_trade_rejected", serde_json::json!({
+                "path": req.path,
+                "amount": amount,
+                "error": e.to_string(),
+            }));
+            match e {
+                // Nothing was sent: tell the operator how much is missing
+                EngineError::InsufficientFunds { currency, needed, available, needed_usd, available_usd } => (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "success": false,
+                        "error": format!("Insufficient {}: need {:.8}, have {:.8}", currency, needed, available),
+                        "error_code": "insufficient_funds",
+                        "currency": currency,
+                        "needed": needed,
+                        "available": available,
+                        "needed_usd": needed_usd,
+                        "available_usd": available_usd,
+                    }))
+                ).into_response(),
+                e => error_response(&e.to_string()),
+            }
         }
-        Err(e) => error_response(&e.to_string()),
     }
 }
 
@@ -745,9 +1107,11 @@ pub async fn get_trades(
     Query(params): Query<TradesQuery>,
 ) -> impl IntoResponse {
     // Get total count for pagination
-    let total_count = state.db.get_trades_count(params.status.as_deref(), params.hours).await.unwrap_or(0);
+    let strategy = params.strategy.as_deref();
+    let tag = params.tag.as_deref();
+    let total_count = state.db.get_trades_count(params.status.as_deref(), params.hours, strategy, tag).await.unwrap_or(0);
 
-    match state.db.get_trades_paginated(params.limit, params.offset, params.status.as_deref(), params.hours).await {
+    match state.db.get_trades_paginated(params.limit, params.offset, params.status.as_deref(), params.hours, strategy, tag).await {
         Ok(trades) => Json(serde_json::json!({
             "trades": trades,
             "pagination": {
@@ -773,6 +1137,48 @@ pub async fn get_partial_trades(
     }
 }
 
+pub async fn get_in_flight_trades(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let trades = state.engine.in_flight_trades().await;
+    Json(serde_json::json!({
+        "count": trades.len(),
+        "trades": trades,
+    }))
+}
+
+/// Stop one executing trade: its open order is cancelled and anything it
+/// already acquired is sold back into the start currency. The trade is then
+/// recorded like any other failed trade (PARTIAL if the unwind failed).
+pub async fn abort_trade(
+    State(state): State<Arc<AppState>>,
+    Path(trade_id): Path<String>,
+) -> Response {
+    match state.engine.abort_trade(&trade_id).await {
+        Ok(Some(abort)) => {
+            audit_api(&state, AuditCategory::Order, "trade_abort_requested", serde_json::json!({
+                "trade_id": abort.trade_id,
+                "path": abort.path,
+                "leg": abort.leg,
+                "cancel_sent": abort.cancel_sent,
+            }));
+            Json(serde_json::json!({
+                "success": true,
+                "message": format!("Abort requested for trade {} at leg {}", abort.trade_id, abort.leg),
+                "abort": abort
+            })).into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": "Trade is not in flight"
+            }))
+        ).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
 pub async fn get_trade(
     State(state): State<Arc<AppState>>,
     Path(trade_id): Path<String>,
@@ -793,10 +1199,61 @@ pub async fn get_trade(
     }
 }
 
+/// Attach operator notes and labels to a trade
+pub async fn update_trade_notes(
+    State(state): State<Arc<AppState>>,
+    Path(trade_id): Path<String>,
+    Json(req): Json<NotesRequest>,
+) -> Response {
+    let (notes, labels) = match req.validated() {
+        Ok(update) => update,
+        Err(e) => return bad_request(&e),
+    };
+    match state.db.set_trade_notes(&trade_id, notes.as_deref(), labels.as_deref()).await {
+        Ok(Some(trade)) => {
+            audit_api(&state, AuditCategory::Order, "trade_notes_updated", serde_json::json!({
+                "trade_id": trade_id,
+                "notes": notes,
+                "labels": labels,
+            }));
+            Json(serde_json::json!({
+                "success": true,
+                "data": trade
+            })).into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": "Trade not found"
+            }))
+        ).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+/// Optional pricing source override (bid, mid or last)
+#[derive(Debug, Deserialize)]
+pub struct ValuationQuery {
+    pub pricing: Option<String>,
+}
+
+impl ValuationQuery {
+    fn source(&self) -> Result<Option<PricingSource>, String> {
+        self.pricing.as_deref().map(PricingSource::parse).transpose()
+    }
+}
+
 pub async fn preview_resolve_partial(
     State(state): State<Arc<AppState>>,
     Path(trade_id): Path<String>,
+    Query(query): Query<ValuationQuery>,
 ) -> Response {
+    let source = match query.source() {
+        Ok(s) => s,
+        Err(e) => return bad_request(&e),
+    };
+
     let trade = match state.db.get_trade(&trade_id).await {
         Ok(Some(t)) => t,
         Ok(None) => return (
@@ -816,20 +1273,10 @@ pub async fn preview_resolve_partial(
     let held_currency = trade.held_currency.as_ref().unwrap_or(&"UNKNOWN".to_string()).clone();
     let held_amount = trade.held_amount.unwrap_or(0.0);
     
-    // Get current price for the held currency - try both pair formats
-    let pair1 = format!("{}/USD", held_currency);
-    let pair2 = format!("USD/{}", held_currency);
-    
-    let (current_price, estimated_usd) = if let Some(price) = state.engine.get_price(&pair1) {
-        // Direct pair (e.g., EUR/USD) - multiply to get USD
-        (price, held_amount * price)
-    } else if let Some(price) = state.engine.get_price(&pair2) {
-        // Reverse pair (e.g., USD/EUR) - divide to get USD
-        (1.0 / price, held_amount / price)
-    } else {
-        // No price found
-        (0.0, 0.0)
-    };
+    // Priced like positions: direct pair or a multi-hop route to USD
+    let valuation = state.engine.value_holding(&held_currency, held_amount, source);
+    let current_price = valuation.usd_rate.unwrap_or(0.0);
+    let estimated_usd = valuation.usd_value.unwrap_or(0.0);
     
     let original_amount = trade.amount_in;
     let estimated_loss = original_amount - estimated_usd;
@@ -843,6 +1290,8 @@ pub async fn preview_resolve_partial(
         "estimated_usd": estimated_usd,
         "original_amount": original_amount,
         "estimated_loss": estimated_loss,
+        "pricing_source": valuation.source,
+        "valuation_route": valuation.route,
         "path": trade.path,
         "action": format!("Sell {:.6} {} for ~${:.2} USD", held_amount, held_currency, estimated_usd)
     })).into_response()
@@ -905,7 +1354,13 @@ pub async fn resolve_partial_trade(
 
 pub async fn get_positions(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ValuationQuery>,
 ) -> Response {
+    let source = match query.source() {
+        Ok(s) => s,
+        Err(e) => return bad_request(&e),
+    };
+
     // Get total portfolio value directly from Kraken TradeBalance API
     // This is the authoritative source for total USD value
     let total_usd = match state.engine.get_trade_balance().await {
@@ -932,56 +1387,30 @@ pub async fn get_positions(
         }
     };
 
-    match state.engine.get_positions().await {
+    match state.engine.get_valued_positions(source).await {
         Ok(positions) => {
             // Extract quote currency balances (USD, EUR)
             let mut usd_balance = 0.0;
             let mut eur_balance = 0.0;
 
-            // Get EUR/USD rate for conversion (fallback to reasonable default)
-            let eur_usd_rate = state.engine.get_price("EUR/USD").unwrap_or(1.04);
+            // EUR/USD rate for the summary (fallback to reasonable default)
+            let eur_usd_rate = state.engine.value_holding("EUR", 1.0, source).usd_rate.unwrap_or(1.04);
 
-            // Build list of positions with USD values
+            // Position USD values come from the valuation service (optional -
+            // total_usd from TradeBalance is authoritative)
             let mut positions_with_values: Vec<serde_json::Value> = Vec::new();
 
             for pos in &positions {
-                let mut usd_value: Option<f64> = None;
-
                 match pos.currency.as_str() {
-                    "USD" | "ZUSD" => {
-                        usd_balance += pos.balance;
-                        usd_value = Some(pos.balance);
-                    },
-                    "USDT" | "USDC" => {
-                        usd_value = Some(pos.balance);
-                    },
-                    "EUR" | "ZEUR" => {
-                        eur_balance += pos.balance;
-                        let value = pos.balance * eur_usd_rate;
-                        usd_value = Some(value);
-                    },
-                    currency => {
-                        // Try to get USD price for this currency from cache
-                        let pair = format!("{}/USD", currency);
-                        if let Some(price) = state.engine.get_price(&pair) {
-                            let value = pos.balance * price;
-                            usd_value = Some(value);
-                        } else {
-                            // Try EUR pair and convert
-                            let eur_pair = format!("{}/EUR", currency);
-                            if let Some(eur_price) = state.engine.get_price(&eur_pair) {
-                                let value = pos.balance * eur_price * eur_usd_rate;
-                                usd_value = Some(value);
-                            }
-                            // Individual position USD value is optional - total_usd from TradeBalance is authoritative
-                        }
-                    }
+                    "USD" | "ZUSD" => usd_balance += pos.balance,
+                    "EUR" | "ZEUR" => eur_balance += pos.balance,
+                    _ => {}
                 }
 
                 positions_with_values.push(serde_json::json!({
                     "currency": pos.currency,
                     "balance": pos.balance,
-                    "usd_value": usd_value
+                    "usd_value": pos.usd_value
                 }));
             }
 
@@ -999,6 +1428,7 @@ pub async fn get_positions(
                     "eur_usd_rate": eur_usd_rate
                 },
                 "fetched_at": fetched_at,
+                "pricing_source": source.unwrap_or_else(|| state.engine.pricing_source()),
                 "positions": positions_with_values
             })).into_response()
         },
@@ -1021,6 +1451,70 @@ pub async fn get_positions(
     }
 }
 
+/// Fill journal: one order's executions, or the most recent orders
+pub async fn get_fills(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FillsQuery>,
+) -> Response {
+    match query.order_id {
+        Some(order_id) => match state.engine.get_fills(&order_id).await {
+            Ok(fills) => Json(serde_json::json!({
+                "success": true,
+                "order_id": order_id,
+                "count": fills.len(),
+                "fills": fills
+            })).into_response(),
+            Err(e) => error_response(&e.to_string()),
+        },
+        None => {
+            let (stats, orders) = state.engine.get_fill_orders(query.limit.unwrap_or(50).min(500));
+            Json(serde_json::json!({
+                "success": true,
+                "stats": stats,
+                "orders": orders
+            })).into_response()
+        }
+    }
+}
+
+/// Last reconciliation against Kraken (orphan/phantom orders, untracked trades, balance drift)
+pub async fn get_reconciliation(State(state): State<Arc<AppState>>) -> Response {
+    Json(serde_json::json!({
+        "success": true,
+        "reconciliation": state.engine.get_reconciliation()
+    })).into_response()
+}
+
+/// Trade write-ahead log settings and counters
+pub async fn get_trade_wal(State(state): State<Arc<AppState>>) -> Response {
+    Json(serde_json::json!({
+        "success": true,
+        "trade_wal": state.engine.get_trade_wal_status()
+    })).into_response()
+}
+
+/// Reconcile now instead of waiting for the next scheduled run
+pub async fn run_reconciliation(State(state): State<Arc<AppState>>) -> Response {
+    let report = state.engine.reconcile_now().await;
+    let success = report.error.is_none();
+    Json(serde_json::json!({
+        "success": success,
+        "report": report
+    })).into_response()
+}
+
+pub async fn get_notifications(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<NotificationsQuery>,
+) -> Response {
+    let notifications = state.engine.get_notifications(query.since_id, query.limit.unwrap_or(50).min(200));
+    Json(serde_json::json!({
+        "success": true,
+        "count": notifications.len(),
+        "notifications": notifications
+    })).into_response()
+}
+
 // ==========================================
 // Scanner Handlers
 // ==========================================
@@ -1062,12 +1556,62 @@ pub async fn stop_scanner(
 pub async fn get_opportunities(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let opportunities = state.engine.get_cached_opportunities();
+    let generation = state.db.write_generation();
+    if let Some(cached) = state.read_cache.get(read_cache::OPPORTUNITIES, generation) {
+        return Json(cached);
+    }
+
+    let opportunities = state.engine.get_cached_opportunities_with_age();
     
-    Json(serde_json::json!({
+    let response = serde_json::json!({
         "count": opportunities.len(),
         "opportunities": opportunities,
-    }))
+        "invalidated": state.engine.opportunities_invalidated(),
+    });
+    state.read_cache.put(read_cache::OPPORTUNITIES, generation, response.clone());
+    Json(response)
+}
+
+/// GET /api/opportunities/guards - Every auto-execution guard evaluated
+/// against the cached opportunities, with the values each one compared
+pub async fn get_opportunity_guards(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GuardsQuery>,
+) -> Response {
+    let verdicts = state.engine.explain_opportunity_guards(query.id.as_deref()).await;
+    if query.id.is_some() && verdicts.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": "Opportunity not cached"
+            }))
+        ).into_response();
+    }
+    Json(serde_json::json!({
+        "success": true,
+        "count": verdicts.len(),
+        "data": verdicts
+    })).into_response()
+}
+
+/// GET /api/opportunities/records - Cached opportunities as packed binary
+/// records. The layout is in the X-Record-Dtype header as numpy (name,
+/// format) pairs: `np.frombuffer(body, dtype=[tuple(f) for f in dtype])`.
+pub async fn get_opportunity_records(
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let opportunities = state.engine.get_cached_opportunities_with_age();
+    let dtype = serde_json::to_string(&opportunity_cache::RECORD_FIELDS).unwrap_or_default();
+    (
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::HeaderName::from_static("x-record-dtype"), dtype),
+            (header::HeaderName::from_static("x-record-count"), opportunities.len().to_string()),
+            (header::HeaderName::from_static("x-opportunities-invalidated"), state.engine.opportunities_invalidated().to_string()),
+        ],
+        opportunity_cache::pack_records(&opportunities),
+    ).into_response()
 }
 
 pub async fn trigger_scan(
@@ -1091,6 +1635,154 @@ pub async fn trigger_scan(
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DetailedScanRequest {
+    /// Defaults to the configured start currencies
+    #[serde(default)]
+    pub base_currencies: Option<Vec<String>>,
+    #[serde(default = "default_scan_top")]
+    pub top: usize,
+}
+
+fn default_scan_top() -> usize {
+    20
+}
+
+/// Most opportunities a detailed scan returns
+const MAX_SCAN_TOP: usize = 500;
+
+/// POST /api/scan/detailed - One full scan with filter counts, timing and the best cycles
+pub async fn scan_detailed(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<DetailedScanRequest>,
+) -> Response {
+    let top = request.top.min(MAX_SCAN_TOP);
+    match state.engine.scan_detailed(request.base_currencies, top).await {
+        Ok(report) => Json(serde_json::json!({
+            "success": true,
+            "report": report
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PathScanRequest {
+    /// e.g. "USD → BTC → ETH → USD"
+    pub path: String,
+    /// Start-currency amount (defaults to the configured trade amount's equivalent)
+    #[serde(default)]
+    pub amount: Option<f64>,
+}
+
+/// POST /api/scan/path - Price one path against the current books and say
+/// why it would or wouldn't trade
+pub async fn scan_path(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<PathScanRequest>,
+) -> Response {
+    match state.engine.inspect_path(&request.path, request.amount).await {
+        Ok(inspection) => Json(serde_json::json!({
+            "success": true,
+            "inspection": inspection
+        })).into_response(),
+        Err(EngineError::Config(e)) => bad_request(&e),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchScanRequest {
+    /// Defaults to the configured start currencies
+    #[serde(default)]
+    pub base_currencies: Option<Vec<String>>,
+}
+
+/// POST /api/scan/batch - Profitable opportunities per base currency from one traversal
+pub async fn scan_batch(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BatchScanRequest>,
+) -> Response {
+    match state.engine.scan_batch(request.base_currencies).await {
+        Ok(by_base) => Json(serde_json::json!({
+            "success": true,
+            "total_opportunities": by_base.values().map(Vec::len).sum::<usize>(),
+            "results": by_base
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+/// Rolling quality score per pair (spread, update rate, gaps over the last hour)
+pub async fn get_pair_quality(State(state): State<Arc<AppState>>) -> Response {
+    Json(serde_json::json!({
+        "success": true,
+        "min_score": crate::pair_quality::min_score_from_env(),
+        "pairs": state.engine.get_pair_quality()
+    })).into_response()
+}
+
+// ==========================================
+// Scan Universe Handlers
+// ==========================================
+
+pub async fn get_universes(State(state): State<Arc<AppState>>) -> Response {
+    Json(serde_json::json!({
+        "success": true,
+        "universes": state.engine.get_universes()
+    })).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UniverseRequest {
+    pub currencies: Vec<String>,
+    /// Percent; defaults to the main threshold
+    #[serde(default)]
+    pub min_profit_pct: Option<f64>,
+    #[serde(default)]
+    pub trigger: UniverseTrigger,
+    #[serde(default)]
+    pub base_currencies: Option<Vec<String>>,
+}
+
+pub async fn put_universe(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(request): Json<UniverseRequest>,
+) -> Response {
+    let universe = Universe {
+        name,
+        currencies: request.currencies,
+        min_profit_pct: request.min_profit_pct,
+        trigger: request.trigger,
+        base_currencies: request.base_currencies,
+    };
+    match state.engine.upsert_universe(universe) {
+        Ok(universe) => {
+            audit_api(&state, AuditCategory::Config, "universe_updated", serde_json::json!(universe));
+            Json(serde_json::json!({
+                "success": true,
+                "universe": universe
+            })).into_response()
+        }
+        Err(e) => bad_request(&e.to_string()),
+    }
+}
+
+pub async fn delete_universe(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Response {
+    if !state.engine.remove_universe(&name) {
+        return bad_request(&format!("Unknown universe '{}'", name));
+    }
+    audit_api(&state, AuditCategory::Config, "universe_removed", serde_json::json!({ "name": name }));
+    Json(serde_json::json!({
+        "success": true,
+        "message": format!("Removed universe {}", name)
+    })).into_response()
+}
+
 // ==========================================
 // Order Book Health Handler
 // ==========================================
@@ -1105,7 +1797,13 @@ pub async fn get_orderbook_health(
         0
     };
     let skipped_total = health.skipped_no_orderbook + health.skipped_thin_depth 
-        + health.skipped_stale + health.skipped_bad_spread + health.skipped_no_price;
+        + health.skipped_stale + health.skipped_bad_spread + health.skipped_no_price
+        + health.skipped_inconsistent + health.skipped_low_quality;
+    let consistency = state.engine.get_consistency_report();
+    let index_check = state.engine.get_index_report();
+    let allocations = state.engine.get_book_allocations();
+    let signals = state.engine.get_book_signals();
+    let quality = state.engine.get_pair_quality();
     
     Json(serde_json::json!({
         "total_pairs": health.total_pairs,
@@ -1122,8 +1820,17 @@ pub async fn get_orderbook_health(
             "thin_depth": health.skipped_thin_depth,
             "stale": health.skipped_stale,
             "bad_spread": health.skipped_bad_spread,
-            "no_price": health.skipped_no_price
+            "no_price": health.skipped_no_price,
+            "inconsistent": health.skipped_inconsistent,
+            "low_quality": health.skipped_low_quality
         },
+        "restricted": state.engine.get_restricted_graph(),
+        "rest_bootstrap": state.engine.get_rest_bootstrap(),
+        "consistency": consistency,
+        "index_check": index_check,
+        "allocations": allocations,
+        "signals": signals,
+        "quality": quality,
         "thresholds": {
             "min_depth": 3,
             "max_staleness_ms": 5000,
@@ -1149,6 +1856,53 @@ pub async fn get_prices(
     }))
 }
 
+/// GET /api/orderbook/:pair - Cached bid/ask ladder (top `depth` levels, default 10)
+/// The pair may be given URL-encoded (BTC%2FUSD) or with a dash (BTC-USD)
+pub async fn get_order_book(
+    State(state): State<Arc<AppState>>,
+    Path(pair): Path<String>,
+    Query(params): Query<DepthQuery>,
+) -> Response {
+    let pair = pair.replace('-', "/").to_uppercase();
+    let depth = params.depth.unwrap_or(10).max(1);
+    match state.engine.get_order_book(&pair, depth) {
+        Some(ladder) => Json(serde_json::json!({
+            "success": true,
+            "data": ladder
+        })).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": format!("No order book data for {}", pair)
+            }))
+        ).into_response(),
+    }
+}
+
+/// Last trade prints of a pair (newest first) with its realized volatility
+pub async fn get_trade_feed(
+    State(state): State<Arc<AppState>>,
+    Path(pair): Path<String>,
+    Query(params): Query<LimitQuery>,
+) -> Response {
+    let pair = pair.replace('-', "/").to_uppercase();
+    let limit = params.limit.unwrap_or(50).max(1);
+    match state.engine.get_trade_feed(&pair, limit) {
+        Some(feed) => Json(serde_json::json!({
+            "success": true,
+            "data": feed
+        })).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": format!("Unknown pair {}", pair)
+            }))
+        ).into_response(),
+    }
+}
+
 pub async fn get_currencies(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
@@ -1169,6 +1923,199 @@ pub async fn get_pairs(
     }))
 }
 
+/// GET /api/pairs/metadata - Base, quote, wsname, altname and status per pair
+pub async fn get_pair_metadata(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "data": state.engine.get_pair_metadata()
+    }))
+}
+
+/// GET /api/pairs/find?from=BTC&to=ETH - The pair and side converting one currency into another
+pub async fn find_pair(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PairRouteQuery>,
+) -> Response {
+    let (from, to) = (params.from.to_uppercase(), params.to.to_uppercase());
+    match state.engine.find_pair(&from, &to) {
+        Some(route) => Json(serde_json::json!({
+            "success": true,
+            "pair": route.pair,
+            "side": if route.sells_base { "sell" } else { "buy" }
+        })).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": format!("No registered pair for {} -> {}", from, to)
+            }))
+        ).into_response(),
+    }
+}
+
+/// GET /api/pairs/ranking - Candidate pairs scored by volume, trade history and cycles
+pub async fn get_pair_ranking(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "data": state.engine.get_pair_ranking()
+    }))
+}
+
+/// GET /api/pairs/refresh - Listings and delistings the pair set followed, newest first
+pub async fn get_pair_refresh(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let history = state.engine.get_pair_refresh_history();
+    Json(serde_json::json!({
+        "success": true,
+        "count": history.len(),
+        "data": history
+    }))
+}
+
+/// POST /api/pairs/refresh - Check AssetPairs for listings and delistings now
+pub async fn refresh_pairs(
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    match state.engine.refresh_pair_set().await {
+        Ok(diff) => Json(serde_json::json!({
+            "success": true,
+            "data": diff
+        })).into_response(),
+        Err(e) => bad_request(&e.to_string()),
+    }
+}
+
+/// POST /api/pairs/:pair/resubscribe - Rebuild one pair's book from a fresh snapshot
+/// The pair may be given URL-encoded (BTC%2FUSD) or with a dash (BTC-USD)
+pub async fn resubscribe_pair(
+    State(state): State<Arc<AppState>>,
+    Path(pair): Path<String>,
+) -> Response {
+    let pair = pair.replace('-', "/").to_uppercase();
+    match state.engine.resubscribe_pair(&pair).await {
+        Ok(()) => {
+            audit_api(&state, AuditCategory::Config, "pair_resubscribed", serde_json::json!({ "pair": pair }));
+            Json(serde_json::json!({
+                "success": true,
+                "message": format!("Resubscribing {}", pair)
+            })).into_response()
+        }
+        Err(e) => bad_request(&e.to_string()),
+    }
+}
+
+pub async fn get_subscriptions(
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    match state.engine.get_active_subscriptions().await {
+        Ok(subscriptions) => Json(serde_json::json!({
+            "success": true,
+            "count": subscriptions.len(),
+            "data": subscriptions
+        })).into_response(),
+        Err(e) => bad_request(&e.to_string()),
+    }
+}
+
+pub async fn subscribe_pair(
+    State(state): State<Arc<AppState>>,
+    Path(pair): Path<String>,
+) -> Response {
+    let pair = pair.replace('-', "/").to_uppercase();
+    match state.engine.subscribe_pair(&pair).await {
+        Ok(()) => {
+            audit_api(&state, AuditCategory::Config, "pair_subscribed", serde_json::json!({ "pair": pair }));
+            Json(serde_json::json!({
+                "success": true,
+                "message": format!("Subscribing {}", pair)
+            })).into_response()
+        }
+        Err(e) => bad_request(&e.to_string()),
+    }
+}
+
+pub async fn unsubscribe_pair(
+    State(state): State<Arc<AppState>>,
+    Path(pair): Path<String>,
+) -> Response {
+    let pair = pair.replace('-', "/").to_uppercase();
+    match state.engine.unsubscribe_pair(&pair).await {
+        Ok(()) => {
+            audit_api(&state, AuditCategory::Config, "pair_unsubscribed", serde_json::json!({ "pair": pair }));
+            Json(serde_json::json!({
+                "success": true,
+                "message": format!("Unsubscribed {}", pair)
+            })).into_response()
+        }
+        Err(e) => bad_request(&e.to_string()),
+    }
+}
+
+/// Pairs flagged for implausibly profitable opportunities, and the
+/// opportunities quarantined because of them
+pub async fn get_anomalies(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "data": state.engine.get_anomalies()
+    }))
+}
+
+/// Release a flagged pair once its quotes have been validated
+pub async fn clear_anomaly(
+    State(state): State<Arc<AppState>>,
+    Path(pair): Path<String>,
+) -> Response {
+    let pair = pair.replace('-', "/").to_uppercase();
+    if !state.engine.clear_anomaly(&pair) {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": format!("{} isn't flagged", pair)
+            }))
+        ).into_response();
+    }
+    audit_api(&state, AuditCategory::Guard, "anomaly_cleared", serde_json::json!({ "pair": pair }));
+    Json(serde_json::json!({
+        "success": true,
+        "message": format!("Released {}", pair)
+    })).into_response()
+}
+
+/// GET /api/opportunities/filter - What's cached and sent to the webhook
+pub async fn get_opportunity_filter(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "data": state.engine.get_opportunity_filter()
+    }))
+}
+
+/// PUT /api/opportunities/filter - Replace the filter (omitted fields = no condition)
+pub async fn set_opportunity_filter(
+    State(state): State<Arc<AppState>>,
+    Json(config): Json<FilterConfig>,
+) -> Response {
+    match state.engine.set_opportunity_filter(config) {
+        Ok(status) => {
+            audit_api(&state, AuditCategory::Config, "opportunity_filter", serde_json::json!(status.config));
+            Json(serde_json::json!({
+                "success": true,
+                "data": status
+            })).into_response()
+        }
+        Err(e) => bad_request(&e),
+    }
+}
+
 // ==========================================
 // Event Scanner Stats Handler
 // ==========================================
@@ -1176,13 +2123,322 @@ pub async fn get_pairs(
 pub async fn get_event_scanner_stats(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let stats = state.engine.get_event_scanner_stats();
+    let stats = state.engine.get_event_scanner_stats_detailed();
     Json(serde_json::json!({
         "success": true,
         "data": stats
     }))
 }
 
+/// Book events dropped on a full event channel, per pair and per minute
+pub async fn get_event_channel_stats(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "data": state.engine.get_event_channel_stats()
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScannerProfilingRequest {
+    pub enabled: bool,
+    #[serde(default)]
+    pub reset: bool,
+}
+
+/// POST /api/event-scanner-stats/profiling - Turn scan timing on/off
+pub async fn set_scanner_profiling(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ScannerProfilingRequest>,
+) -> impl IntoResponse {
+    let profile = state.engine.set_scanner_profiling(request.enabled, request.reset);
+    audit_api(&state, AuditCategory::Config, "scanner_profiling", serde_json::json!({
+        "enabled": request.enabled,
+        "reset": request.reset,
+    }));
+    Json(serde_json::json!({
+        "success": true,
+        "data": profile
+    }))
+}
+
+// ==========================================
+// Stats History Handler
+// ==========================================
+
+/// GET /api/stats/history?minutes= - Sampled engine/scanner/executor counters, oldest first
+pub async fn get_stats_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatsHistoryQuery>,
+) -> Response {
+    let minutes = query.minutes.clamp(1, MAX_HISTORY_MINUTES);
+    match state.engine.get_stats_history(minutes).await {
+        Ok((stats, samples)) => Json(serde_json::json!({
+            "success": true,
+            "minutes": minutes,
+            "history": stats,
+            "count": samples.len(),
+            "samples": samples
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+// ==========================================
+// Trading Sessions Handler
+// ==========================================
+
+/// GET /api/sessions?limit=20 - Trading sessions (enable to disable), newest
+/// first, with their config, trades, P&L and breaker events
+pub async fn get_sessions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LimitQuery>,
+) -> Response {
+    let limit = params.limit.unwrap_or(20).clamp(1, 500) as i64;
+    match state.db.get_session_summaries(limit).await {
+        Ok(sessions) => Json(serde_json::json!({
+            "success": true,
+            "count": sessions.len(),
+            "data": sessions
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+// ==========================================
+// Analytics Handler
+// ==========================================
+
+/// GET /api/analytics/summary?window=24h - Win rate, profit per trade, P&L by
+/// hour, top paths, failing pairs and execution time from live_trades
+pub async fn get_analytics_summary(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Response {
+    let window = match parse_window(&query.window) {
+        Ok(window) => window,
+        Err(e) => return bad_request(&e),
+    };
+    let hours = (window.num_minutes() + 59) / 60;
+    match state.db.get_trades(MAX_ANALYTICS_TRADES, None, hours as i32).await {
+        Ok(trades) => Json(serde_json::json!({
+            "success": true,
+            "window": query.window,
+            // Only the newest MAX_ANALYTICS_TRADES were summarized
+            "truncated": trades.len() as i64 == MAX_ANALYTICS_TRADES,
+            "data": analytics::summarize(&trades, window, Utc::now())
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+/// POST /api/analytics/what-if - What other thresholds and cooldowns would
+/// have traded over recently recorded opportunities
+pub async fn what_if(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WhatIfRequest>,
+) -> Response {
+    if !(1..=MAX_WINDOW_HOURS).contains(&req.hours) {
+        return bad_request(&format!("hours must be between 1 and {}", MAX_WINDOW_HOURS));
+    }
+    if req.thresholds_pct.iter().any(|t| !t.is_finite()) {
+        return bad_request("thresholds_pct must be numbers");
+    }
+    if req.thresholds_pct.len().max(1) * req.cooldowns.len().max(1) > MAX_WHAT_IF_SCENARIOS {
+        return bad_request(&format!("At most {} threshold and cooldown combinations", MAX_WHAT_IF_SCENARIOS));
+    }
+    match state.engine.what_if(req.hours, req.thresholds_pct, req.cooldowns, MAX_WHAT_IF_OPPORTUNITIES).await {
+        Ok((report, truncated)) => Json(serde_json::json!({
+            "success": true,
+            // Only the newest MAX_WHAT_IF_OPPORTUNITIES were replayed
+            "truncated": truncated,
+            "data": report
+        })).into_response(),
+        Err(EngineError::Config(e)) => bad_request(&e),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+// ==========================================
+// Retention Handlers
+// ==========================================
+
+/// GET /api/admin/cleanup - Retention per table and the last cleanup run
+pub async fn get_retention(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "data": state.engine.get_retention()
+    }))
+}
+
+/// POST /api/admin/cleanup - Archive and delete rows past their retention now
+pub async fn run_cleanup(
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    match state.engine.run_cleanup(AuditActor::Api, "manual").await {
+        Ok(report) => Json(serde_json::json!({
+            "success": true,
+            "data": report
+        })).into_response(),
+        Err(e) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "success": false,
+                "error": e
+            }))
+        ).into_response(),
+    }
+}
+
+// ==========================================
+// Load Generator Handler
+// ==========================================
+
+/// POST /api/loadgen - Run synthetic book updates through a private cache,
+/// event channel and scanner; results appear in GET /api/snapshot
+pub async fn start_loadgen(
+    State(state): State<Arc<AppState>>,
+    Json(config): Json<LoadGenConfig>,
+) -> Response {
+    match state.engine.start_loadgen(config) {
+        Ok(()) => {
+            audit_api(&state, AuditCategory::Config, "loadgen_started", serde_json::json!(config));
+            Json(serde_json::json!({
+                "success": true,
+                "config": config
+            })).into_response()
+        }
+        Err(e) => bad_request(&e),
+    }
+}
+
+// ==========================================
+// Chaos Testing Handler
+// ==========================================
+
+/// GET /api/chaos - Injected faults and the recoveries that followed
+pub async fn get_chaos_stats(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "chaos": state.engine.get_chaos_stats()
+    }))
+}
+
+// ==========================================
+// Runtime Handlers
+// ==========================================
+
+/// GET /api/runtimes - Market-data vs execution runtime queue latency
+pub async fn get_runtimes(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let (policy, runtimes) = state.engine.get_runtime_stats();
+    Json(serde_json::json!({
+        "success": true,
+        "policy": policy,
+        "runtimes": runtimes
+    }))
+}
+
+// ==========================================
+// Opportunity Webhook Handlers
+// ==========================================
+
+/// GET /api/webhook - Endpoint and delivery stats
+pub async fn get_webhook(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "data": state.engine.get_webhook_stats()
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookRequest {
+    pub enabled: bool,
+    pub url: Option<String>,
+    pub secret: Option<String>,
+}
+
+/// POST /api/webhook - Turn opportunity delivery on/off, set URL/secret
+pub async fn set_webhook(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<WebhookRequest>,
+) -> Response {
+    let details = serde_json::json!({
+        "enabled": request.enabled,
+        "url": request.url,
+        "secret_changed": request.secret.is_some(),
+    });
+    match state.engine.configure_webhook(request.enabled, request.url, request.secret) {
+        Ok(stats) => {
+            audit_api(&state, AuditCategory::Config, "opportunity_webhook", details);
+            Json(serde_json::json!({
+                "success": true,
+                "data": stats
+            })).into_response()
+        }
+        Err(e) => bad_request(&e),
+    }
+}
+
+// ==========================================
+// Export Handlers
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Only "csv" (the default)
+    #[serde(default = "default_export_format")]
+    pub format: String,
+    /// RFC 3339 timestamp or YYYY-MM-DD (inclusive)
+    pub from: Option<String>,
+    /// RFC 3339 timestamp or YYYY-MM-DD (exclusive)
+    pub to: Option<String>,
+}
+
+fn default_export_format() -> String { "csv".to_string() }
+
+/// GET /api/export/trades - Stream trades with leg detail as CSV
+pub async fn export_trades(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ExportQuery>,
+) -> Response {
+    export_rows(&state, ExportKind::Trades, &params)
+}
+
+/// GET /api/export/opportunities - Stream logged opportunities as CSV
+pub async fn export_opportunities(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ExportQuery>,
+) -> Response {
+    export_rows(&state, ExportKind::Opportunities, &params)
+}
+
+fn export_rows(state: &AppState, kind: ExportKind, params: &ExportQuery) -> Response {
+    if let Err(e) = ExportFormat::parse(&params.format) {
+        return bad_request(&e);
+    }
+    let range = match ExportRange::parse(params.from.as_deref(), params.to.as_deref()) {
+        Ok(r) => r,
+        Err(e) => return bad_request(&e),
+    };
+    let filename = format!("{}_{}.csv", kind.as_str(), Utc::now().format("%Y%m%d_%H%M%S"));
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(csv_stream(state.db.clone(), kind, range)),
+    ).into_response()
+}
+
 // ==========================================
 // Fee Config Handlers
 // ==========================================
@@ -1220,24 +2476,15 @@ pub async fn update_fee_config(
         return bad_request("Cannot update fees while engine is running. Please stop the engine first.");
     }
 
-    // Validate fees
-    let maker_fee = updates.maker_fee.unwrap_or(0.0);
-    let taker_fee = updates.taker_fee.unwrap_or(0.0);
-
-    if maker_fee < 0.0 || maker_fee > 0.1 {
-        return bad_request("Maker fee must be between 0% and 10%");
-    }
-    if taker_fee < 0.0 || taker_fee > 0.1 {
-        return bad_request("Taker fee must be between 0% and 10%");
-    }
-
-    // Update in database
-    match state.db.update_fee_manual(maker_fee, taker_fee).await {
-        Ok(fee_config) => {
-            // Also update the engine's fee config
-            state.engine.update_fee_config(Some(maker_fee), Some(taker_fee)).await;
+    let patch = ConfigPatch {
+        trading: None,
+        fees: Some(FeePatch { maker_fee: updates.maker_fee, taker_fee: updates.taker_fee }),
+    };
+    match state.engine.apply_config(patch, AuditActor::Api, "fees").await {
+        Ok(applied) => {
+            let fee_config = applied.fees;
             info!("Fee configuration manually updated: maker={:.4}%, taker={:.4}%",
-                maker_fee * 100.0, taker_fee * 100.0);
+                fee_config.maker_fee * 100.0, fee_config.taker_fee * 100.0);
             Json(serde_json::json!({
                 "success": true,
                 "message": "Fee configuration updated manually",
@@ -1249,7 +2496,7 @@ pub async fn update_fee_config(
                 }
             })).into_response()
         }
-        Err(e) => error_response(&e.to_string()),
+        Err(e) => config_error_response(e),
     }
 }
 
@@ -1306,14 +2553,31 @@ pub async fn fetch_fees_from_kraken(
     }
 }
 
+/// Fee history requests are capped at this many changes
+const MAX_FEE_HISTORY: usize = 1000;
+
+/// GET /api/live/fee-config/history - Fee changes, newest first (?limit=, default 100)
+pub async fn get_fee_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LimitQuery>,
+) -> Response {
+    let limit = params.limit.unwrap_or(100).clamp(1, MAX_FEE_HISTORY);
+    match state.db.get_fee_history(limit as i64).await {
+        Ok(changes) => Json(serde_json::json!({
+            "success": true,
+            "count": changes.len(),
+            "changes": changes
+        })).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
 pub async fn get_fee_stats(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let fee_config = match state.db.get_fee_configuration().await {
-        Ok(fc) => fc,
-        Err(_) => crate::db::FeeConfiguration::default(),
-    };
+    let fee_config = state.db.get_fee_configuration().await.unwrap_or_default();
     let stats = state.engine.get_stats().await;
+    let execution = state.engine.get_execution_stats().await;
 
     Json(serde_json::json!({
         "success": true,
@@ -1324,11 +2588,20 @@ pub async fn get_fee_stats(
                 "fee_source": fee_config.fee_source,
                 "is_configured": fee_config.fee_source != "pending"
             },
-            "orders_sent": 0,
-            "orders_filled": 0,
-            "maker_orders_attempted": 0,
-            "maker_orders_filled": 0,
+            "orders_sent": execution.orders_sent,
+            "orders_filled": execution.orders_filled,
+            "maker_orders_attempted": execution.maker_orders_attempted,
+            "maker_orders_filled": execution.maker_orders_filled,
+            "maker_replacements": execution.maker_replacements,
+            "amends_sent": execution.amends_sent,
+            "amends_succeeded": execution.amends_succeeded,
+            "amends_failed": execution.amends_failed,
+            "amend_success_rate": execution.amend_success_rate,
             "total_fee_savings": 0.0,
+            "price_improvement": {
+                "by_order_type": PriceImprovementStats::by_order_type(&execution.price_improvement),
+                "by_pair": execution.price_improvement,
+            },
             "uptime_seconds": stats.uptime_seconds
         }
     }))
@@ -1347,6 +2620,8 @@ pub async fn quick_disable(
             state.engine.disable_auto_execution();
             state.engine.disable_trading();
             state.engine.trip_circuit_breaker("Emergency quick disable").await;
+            state.read_cache.invalidate();
+            audit_api(&state, AuditCategory::Breaker, "quick_disable", serde_json::json!({}));
             info!("EMERGENCY: Quick disable activated");
             Json(serde_json::json!({
                 "success": true,
@@ -1358,6 +2633,84 @@ pub async fn quick_disable(
     }
 }
 
+// ==========================================
+// Dead-Man's Switch Handlers
+// ==========================================
+
+pub async fn heartbeat(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "dead_man_switch": state.engine.heartbeat()
+    }))
+}
+
+pub async fn get_dead_man_switch(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(state.engine.get_dead_man_status())
+}
+
+pub async fn update_dead_man_switch(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DeadManSwitchUpdate>,
+) -> Response {
+    match state.engine.configure_dead_man_switch(req.enabled, req.timeout_secs) {
+        Ok(status) => {
+            audit_api(&state, AuditCategory::Config, "dead_man_switch_update", serde_json::json!({
+                "enabled": req.enabled,
+                "timeout_secs": req.timeout_secs,
+            }));
+            Json(serde_json::json!({
+                "success": true,
+                "dead_man_switch": status
+            })).into_response()
+        }
+        Err(e) => bad_request(&e),
+    }
+}
+
+// ==========================================
+// Safe Mode Handlers
+// ==========================================
+
+/// GET /api/live/safe-mode - Whether execution is held back, and why
+pub async fn get_safe_mode(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "success": true,
+        "safe_mode": state.engine.get_safe_mode()
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmResumeRequest {
+    pub operator_id: String,
+    pub reason: String,
+}
+
+/// POST /api/live/confirm-resume - Leave safe mode and allow execution again
+pub async fn confirm_resume(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ConfirmResumeRequest>,
+) -> Response {
+    match state.engine.confirm_resume(&req.operator_id, &req.reason) {
+        Ok(record) => {
+            audit_api(&state, AuditCategory::Breaker, "safe_mode_resumed", serde_json::json!(record));
+            state.read_cache.invalidate();
+            Json(serde_json::json!({
+                "success": true,
+                "message": format!("Execution resumed by {}", record.operator_id),
+                "resume": record,
+                "safe_mode": state.engine.get_safe_mode()
+            })).into_response()
+        }
+        Err(e) => bad_request(&e),
+    }
+}
+
 // ==========================================
 // Kraken Live Fees Handler
 // ==========================================
@@ -1419,6 +2772,39 @@ pub async fn get_past_opportunities(
     }
 }
 
+/// Attach operator notes and labels to a recorded opportunity
+pub async fn update_opportunity_notes(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Json(req): Json<NotesRequest>,
+) -> Response {
+    let (notes, labels) = match req.validated() {
+        Ok(update) => update,
+        Err(e) => return bad_request(&e),
+    };
+    match state.db.set_opportunity_notes(id, notes.as_deref(), labels.as_deref()).await {
+        Ok(Some(opportunity)) => {
+            audit_api(&state, AuditCategory::Order, "opportunity_notes_updated", serde_json::json!({
+                "opportunity_id": id,
+                "notes": notes,
+                "labels": labels,
+            }));
+            Json(serde_json::json!({
+                "success": true,
+                "data": opportunity
+            })).into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "success": false,
+                "error": "Opportunity not found"
+            }))
+        ).into_response(),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
 // ==========================================
 // Restrictions Management
 // ==========================================
@@ -1432,6 +2818,7 @@ pub async fn get_restrictions(
     Json(serde_json::json!({
         "success": true,
         "data": config,
+        "graph": state.engine.get_restricted_graph(),
     }))
 }
 
@@ -1469,6 +2856,7 @@ pub async fn reload_restrictions(
 
     match state.restrictions.load_from_file() {
         Ok(()) => {
+            state.engine.apply_restrictions(&state.restrictions);
             let config = state.restrictions.get_config();
             Json(serde_json::json!({
                 "success": true,
@@ -1496,6 +2884,7 @@ pub async fn add_blocked_currency(
 
     match state.restrictions.add_blocked_currency(&request.currency) {
         Ok(()) => {
+            state.engine.apply_restrictions(&state.restrictions);
             let blocked = state.restrictions.get_blocked_currencies();
             Json(serde_json::json!({
                 "success": true,
@@ -1523,6 +2912,7 @@ pub async fn remove_blocked_currency(
 
     match state.restrictions.remove_blocked_currency(&request.currency) {
         Ok(()) => {
+            state.engine.apply_restrictions(&state.restrictions);
             let blocked = state.restrictions.get_blocked_currencies();
             Json(serde_json::json!({
                 "success": true,
@@ -1551,6 +2941,7 @@ pub async fn update_restrictions(
     if let Some(blocked) = request.blocked_currencies {
         match state.restrictions.update_restrictions(blocked.clone(), request.allowed_assets, "api_update") {
             Ok(()) => {
+                state.engine.apply_restrictions(&state.restrictions);
                 let config = state.restrictions.get_config();
                 Json(serde_json::json!({
                     "success": true,
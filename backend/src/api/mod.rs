@@ -4,11 +4,14 @@
 //! All API endpoints for the trading platform.
 
 mod handlers;
+pub mod read_cache;
 mod websocket;
 
+pub use read_cache::ReadCache;
+
 use crate::AppState;
 use axum::{
-    routing::{get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -29,6 +32,8 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // ==========================================
         .route("/api/health", get(handlers::health_check))
         .route("/api/status", get(handlers::get_status))
+        .route("/api/snapshot", get(handlers::get_engine_snapshot))
+        .route("/api/engine/errors", get(handlers::get_engine_errors))
         .route("/api/engine/restart", post(handlers::restart_engine))
         
         // ==========================================
@@ -45,20 +50,40 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/api/live/disable", post(handlers::disable_trading))
         .route("/api/live/quick-disable", post(handlers::quick_disable))
         
+        // ==========================================
+        // Dead-Man's Switch
+        // ==========================================
+        .route("/api/live/heartbeat", post(handlers::heartbeat))
+        .route("/api/live/dead-man-switch", get(handlers::get_dead_man_switch))
+        .route("/api/live/dead-man-switch", put(handlers::update_dead_man_switch))
+        
+        // ==========================================
+        // Safe Mode
+        // ==========================================
+        .route("/api/live/safe-mode", get(handlers::get_safe_mode))
+        .route("/api/live/confirm-resume", post(handlers::confirm_resume))
+        
         // ==========================================
         // Trading Status & State
         // ==========================================
         .route("/api/live/status", get(handlers::get_live_status))
         .route("/api/live/state", get(handlers::get_state))
+        .route("/api/live/cooldowns", get(handlers::get_cooldowns))
         .route("/api/live/circuit-breaker", get(handlers::get_circuit_breaker))
         .route("/api/live/circuit-breaker/reset", post(handlers::reset_circuit_breaker))
         .route("/api/live/circuit-breaker/trigger", post(handlers::trigger_circuit_breaker))
         
+        // ==========================================
+        // Audit Log
+        // ==========================================
+        .route("/api/live/audit", get(handlers::get_audit_log))
+        
         // ==========================================
         // Stats Reset
         // ==========================================
         .route("/api/live/reset-daily", post(handlers::reset_daily_stats))
         .route("/api/live/reset-all", post(handlers::reset_all_stats))
+        .route("/api/live/daily-reset", get(handlers::get_daily_reset))
         
         // ==========================================
         // Trade Execution
@@ -70,14 +95,22 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // ==========================================
         .route("/api/live/trades", get(handlers::get_trades))
         .route("/api/live/trades/partial", get(handlers::get_partial_trades))
+        .route("/api/live/trades/in-flight", get(handlers::get_in_flight_trades))
         .route("/api/live/trades/:trade_id", get(handlers::get_trade))
+        .route("/api/live/trades/:trade_id/notes", patch(handlers::update_trade_notes))
         .route("/api/live/trades/:trade_id/resolve-preview", get(handlers::preview_resolve_partial))
         .route("/api/live/trades/:trade_id/resolve", post(handlers::resolve_partial_trade))
+        .route("/api/live/trades/:trade_id/abort", post(handlers::abort_trade))
         
         // ==========================================
         // Positions
         // ==========================================
         .route("/api/live/positions", get(handlers::get_positions))
+        .route("/api/live/fills", get(handlers::get_fills))
+        .route("/api/live/reconciliation", get(handlers::get_reconciliation))
+        .route("/api/live/reconciliation/run", post(handlers::run_reconciliation))
+        .route("/api/live/trade-wal", get(handlers::get_trade_wal))
+        .route("/api/notifications", get(handlers::get_notifications))
         
         // ==========================================
         // Scanner Control
@@ -91,7 +124,26 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // ==========================================
         .route("/api/opportunities", get(handlers::get_opportunities))
         .route("/api/opportunities/past", get(handlers::get_past_opportunities))
+        .route("/api/opportunities/records", get(handlers::get_opportunity_records))
+        .route("/api/opportunities/guards", get(handlers::get_opportunity_guards))
+        .route("/api/opportunities/anomalies", get(handlers::get_anomalies))
+        .route("/api/opportunities/anomalies/:pair", delete(handlers::clear_anomaly))
+        .route("/api/opportunities/filter", get(handlers::get_opportunity_filter))
+        .route("/api/opportunities/filter", put(handlers::set_opportunity_filter))
+        .route("/api/opportunities/:id/notes", patch(handlers::update_opportunity_notes))
         .route("/api/scan", post(handlers::trigger_scan))
+        .route("/api/scan/detailed", post(handlers::scan_detailed))
+        .route("/api/scan/batch", post(handlers::scan_batch))
+        .route("/api/scan/path", post(handlers::scan_path))
+        .route("/api/universes", get(handlers::get_universes))
+        .route("/api/universes/:name", put(handlers::put_universe))
+        .route("/api/universes/:name", delete(handlers::delete_universe))
+
+        // ==========================================
+        // Export (CSV)
+        // ==========================================
+        .route("/api/export/trades", get(handlers::export_trades))
+        .route("/api/export/opportunities", get(handlers::export_opportunities))
         
         // ==========================================
         // Order Book Health
@@ -102,13 +154,60 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // Market Data (prices, currencies, pairs)
         // ==========================================
         .route("/api/prices/live", get(handlers::get_prices))
+        .route("/api/orderbook/:pair", get(handlers::get_order_book))
+        .route("/api/trades-feed/:pair", get(handlers::get_trade_feed))
         .route("/api/currencies", get(handlers::get_currencies))
         .route("/api/pairs", get(handlers::get_pairs))
+        .route("/api/pairs/ranking", get(handlers::get_pair_ranking))
+        .route("/api/pairs/metadata", get(handlers::get_pair_metadata))
+        .route("/api/pairs/find", get(handlers::find_pair))
+        .route("/api/pairs/quality", get(handlers::get_pair_quality))
+        .route("/api/pairs/refresh", get(handlers::get_pair_refresh).post(handlers::refresh_pairs))
+        .route("/api/pairs/:pair/resubscribe", post(handlers::resubscribe_pair))
+        .route("/api/subscriptions", get(handlers::get_subscriptions))
+        .route("/api/subscriptions/:pair", post(handlers::subscribe_pair))
+        .route("/api/subscriptions/:pair", delete(handlers::unsubscribe_pair))
         
         // ==========================================
         // Event Scanner Stats
         // ==========================================
         .route("/api/event-scanner-stats", get(handlers::get_event_scanner_stats))
+        .route("/api/event-scanner-stats/profiling", post(handlers::set_scanner_profiling))
+        .route("/api/event-channel", get(handlers::get_event_channel_stats))
+        
+        // ==========================================
+        // Stats History
+        // ==========================================
+        .route("/api/stats/history", get(handlers::get_stats_history))
+        .route("/api/analytics/summary", get(handlers::get_analytics_summary))
+        .route("/api/sessions", get(handlers::get_sessions))
+        .route("/api/analytics/what-if", post(handlers::what_if))
+        
+        // ==========================================
+        // Retention
+        // ==========================================
+        .route("/api/admin/cleanup", get(handlers::get_retention).post(handlers::run_cleanup))
+        
+        // ==========================================
+        // Chaos Testing
+        // ==========================================
+        .route("/api/chaos", get(handlers::get_chaos_stats))
+        
+        // ==========================================
+        // Load Generator
+        // ==========================================
+        .route("/api/loadgen", post(handlers::start_loadgen))
+        
+        // ==========================================
+        // Runtimes
+        // ==========================================
+        .route("/api/runtimes", get(handlers::get_runtimes))
+        
+        // ==========================================
+        // Opportunity Webhook
+        // ==========================================
+        .route("/api/webhook", get(handlers::get_webhook))
+        .route("/api/webhook", post(handlers::set_webhook))
         
         // ==========================================
         // Fee Configuration
@@ -117,9 +216,11 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/api/fees", put(handlers::update_fee_config))
         .route("/api/fees/fetch", post(handlers::fetch_fees_from_kraken))
         .route("/api/fees/stats", get(handlers::get_fee_stats))
+        .route("/api/fees/history", get(handlers::get_fee_history))
         // Legacy endpoints for backwards compatibility
         .route("/api/live/fee-config", get(handlers::get_fee_config))
         .route("/api/live/fee-config", put(handlers::update_fee_config))
+        .route("/api/live/fee-config/history", get(handlers::get_fee_history))
         .route("/api/live/fee-stats", get(handlers::get_fee_stats))
         .route("/api/live/kraken-fees", get(handlers::get_kraken_fees))
         
@@ -128,6 +229,13 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // ==========================================
         .route("/ws", get(websocket::ws_handler))
 
+        // ==========================================
+        // Unified Configuration Document
+        // ==========================================
+        .route("/api/config", get(handlers::get_config_document))
+        .route("/api/config", put(handlers::update_config_document))
+        .route("/api/config/history", get(handlers::get_config_history))
+
         // ==========================================
         // Geographic Restrictions (Canada)
         // ==========================================
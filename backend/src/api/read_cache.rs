@@ -0,0 +1,136 @@
+//! Read-model cache for hot dashboard endpoints
+//!
+//! The dashboard polls `/api/live/status` and `/api/opportunities` every
+//! second or so. Building those responses takes DB round trips and engine
+//! locks, so the finished JSON is kept here for a short TTL.
+//!
+//! An entry is served only while:
+//! - it is younger than the TTL, and
+//! - the DB write generation it was built from is still current
+//!   (any config/state/trade write bumps it, see `Database::write_generation`)
+//!
+//! Handlers that change engine state without a DB write call `invalidate()`.
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Default entry lifetime - short enough that engine counters look live
+const DEFAULT_TTL_MS: u64 = 500;
+
+/// Cache keys
+pub const LIVE_STATUS: &str = "live_status";
+pub const OPPORTUNITIES: &str = "opportunities";
+
+struct CachedEntry {
+    value: Value,
+    generation: u64,
+    cached_at: Instant,
+}
+
+/// Hit/miss counters
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadCacheStats {
+    pub ttl_ms: u64,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// TTL + write-generation keyed response cache
+pub struct ReadCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<&'static str, CachedEntry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ReadCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Create from API_READ_CACHE_TTL_MS (0 disables caching)
+    pub fn from_env() -> Self {
+        let ttl_ms = std::env::var("API_READ_CACHE_TTL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_TTL_MS);
+        Self::new(Duration::from_millis(ttl_ms))
+    }
+
+    /// Get a cached response if it is fresh and built from the current generation
+    pub fn get(&self, key: &'static str, generation: u64) -> Option<Value> {
+        let entries = self.entries.read();
+        match entries.get(key) {
+            Some(entry) if entry.generation == generation && entry.cached_at.elapsed() < self.ttl => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.value.clone())
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Store a freshly built response
+    pub fn put(&self, key: &'static str, generation: u64, value: Value) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries.write().insert(key, CachedEntry {
+            value,
+            generation,
+            cached_at: Instant::now(),
+        });
+    }
+
+    /// Drop all entries (engine started/stopped, auto-execution toggled, ...)
+    pub fn invalidate(&self) {
+        self.entries.write().clear();
+    }
+
+    pub fn stats(&self) -> ReadCacheStats {
+        ReadCacheStats {
+            ttl_ms: self.ttl.as_millis() as u64,
+            entries: self.entries.read().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_generation_and_ttl_invalidate() {
+        let cache = ReadCache::new(Duration::from_millis(50));
+        cache.put(LIVE_STATUS, 1, json!({"n": 1}));
+
+        assert_eq!(cache.get(LIVE_STATUS, 1), Some(json!({"n": 1})));
+        // A write happened since the entry was built
+        assert_eq!(cache.get(LIVE_STATUS, 2), None);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(LIVE_STATUS, 1), None);
+
+        cache.put(OPPORTUNITIES, 1, json!([]));
+        cache.invalidate();
+        assert_eq!(cache.get(OPPORTUNITIES, 1), None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 3));
+    }
+}
@@ -0,0 +1,119 @@
+//! Execution Audit Log
+//!
+//! Append-only trail for post-incident review. Every entry records when it
+//! happened, who caused it and what was decided:
+//! - order: order requests and exchange responses, per leg
+//! - guard: decisions that kept an opportunity from executing
+//! - config: configuration and trading on/off changes
+//! - breaker: circuit-breaker and dead-man's switch events
+//!
+//! Entries are written to the `audit_log` table, which rejects UPDATE and
+//! DELETE. Writes go through a bounded channel to a background task so the
+//! hot path never waits on the database; if the channel is full the entry
+//! is dropped and counted.
+#![allow(dead_code)]
+
+use crate::db::{Database, NewAuditEntry};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Entries buffered before new ones are dropped
+const CHANNEL_CAPACITY: usize = 10_000;
+
+/// Who caused an audited event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditActor {
+    /// HFT loop auto-execution
+    Auto,
+    /// Operator request through the HTTP API
+    Api,
+    /// Engine-internal safety mechanism (e.g. dead-man's switch)
+    System,
+}
+
+impl AuditActor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditActor::Auto => "auto",
+            AuditActor::Api => "api",
+            AuditActor::System => "system",
+        }
+    }
+}
+
+/// What kind of event was audited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditCategory {
+    Order,
+    Guard,
+    Config,
+    Breaker,
+}
+
+impl AuditCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditCategory::Order => "order",
+            AuditCategory::Guard => "guard",
+            AuditCategory::Config => "config",
+            AuditCategory::Breaker => "breaker",
+        }
+    }
+}
+
+/// Handle for appending audit entries (cheap to clone)
+#[derive(Clone)]
+pub struct AuditLog {
+    tx: mpsc::Sender<NewAuditEntry>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl AuditLog {
+    /// Create the log and spawn its DB writer task
+    pub fn new(db: Database) -> Self {
+        let (tx, mut rx) = mpsc::channel::<NewAuditEntry>(CHANNEL_CAPACITY);
+
+        tokio::spawn(async move {
+            while let Some(entry) = rx.recv().await {
+                if let Err(e) = db.insert_audit_entry(&entry).await {
+                    warn!("Failed to write audit entry {}/{}: {}", entry.category, entry.action, e);
+                }
+            }
+        });
+
+        Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Append an entry without waiting for the write
+    pub fn record(&self, actor: AuditActor, category: AuditCategory, action: &str, details: Value) {
+        let entry = NewAuditEntry {
+            occurred_at: Utc::now(),
+            actor: actor.as_str().to_string(),
+            category: category.as_str().to_string(),
+            action: action.to_string(),
+            details,
+        };
+
+        if self.tx.try_send(entry).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!("Audit log backlog full - {} entries dropped so far", dropped);
+            }
+        }
+    }
+
+    /// Entries dropped because the writer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
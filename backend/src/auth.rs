@@ -9,13 +9,22 @@
 //! 2. Decode API secret from base64
 //! 3. Create HMAC-SHA512 of (URI path + SHA256 hash) using decoded secret
 //! 4. Base64 encode the HMAC result for API-Sign header
+//!
+//! The WebSocket token is cached and shared by every caller. Only one of
+//! them fetches a new one at a time; the others wait for its result (or
+//! keep using the old token while it's still valid). A background task
+//! refreshes it ahead of the buffer, so order placement never waits on
+//! the REST call.
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use hmac::{Hmac, Mac};
 use reqwest::Client;
 use serde::Deserialize;
 use sha2::{Digest, Sha256, Sha512};
-use std::sync::atomic::{AtomicU64, Ordering};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use parking_lot::RwLock;
 use thiserror::Error;
@@ -32,6 +41,7 @@ fn get_kraken_api_url() -> String {
 
 const TOKEN_REFRESH_BUFFER_SECS: u64 = 60; // Refresh 1 minute before expiry
 const TOKEN_VALIDITY_SECS: u64 = 900; // 15 minutes
+const TOKEN_RETRY_SECS: u64 = 30; // Background retry after a failed refresh
 
 /// Authentication errors
 #[derive(Debug, Error)]
@@ -69,7 +79,8 @@ pub struct KrakenAuth {
     client: Client,
 
     // Cached WebSocket token
-    ws_token: RwLock<Option<CachedToken>>,
+    ws_token: TokenCache,
+    token_refresh_started: AtomicBool,
 
     // Nonce counter (must be increasing)
     nonce_counter: AtomicU64,
@@ -84,6 +95,110 @@ struct CachedToken {
     expires_at: Instant,
 }
 
+impl CachedToken {
+    /// Not yet inside the refresh buffer
+    fn is_fresh(&self, now: Instant) -> bool {
+        self.expires_at > now + Duration::from_secs(TOKEN_REFRESH_BUFFER_SECS)
+    }
+}
+
+/// WebSocket token counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TokenStats {
+    /// REST calls to GetWebSocketsToken
+    pub fetches: u64,
+    pub fetch_failures: u64,
+    /// Requests answered from the cache
+    pub cache_hits: u64,
+    /// Requests that waited for another caller's fetch instead of making their own
+    pub coalesced: u64,
+    /// Seconds until the cached token expires (None when there is none)
+    pub expires_in_secs: Option<u64>,
+}
+
+/// Token shared by all callers, fetched by at most one of them at a time
+#[derive(Default)]
+struct TokenCache {
+    cached: RwLock<Option<CachedToken>>,
+    refresh: tokio::sync::Mutex<()>,
+    fetches: AtomicU64,
+    fetch_failures: AtomicU64,
+    cache_hits: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl TokenCache {
+    fn current(&self, now: Instant, fresh_only: bool) -> Option<String> {
+        let cached = self.cached.read();
+        cached
+            .as_ref()
+            .filter(|t| if fresh_only { t.is_fresh(now) } else { t.expires_at > now })
+            .map(|t| t.token.clone())
+    }
+
+    /// The cached token, or one from `fetch` when it's due. Concurrent callers
+    /// share a single fetch.
+    async fn get<F, Fut>(&self, fetch: F) -> Result<String, AuthError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String, AuthError>>,
+    {
+        if let Some(token) = self.current(Instant::now(), true) {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(token);
+        }
+
+        let _guard = match self.refresh.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => {
+                // Someone is already fetching: the old token still works until it expires
+                if let Some(token) = self.current(Instant::now(), false) {
+                    self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(token);
+                }
+                let guard = self.refresh.lock().await;
+                if let Some(token) = self.current(Instant::now(), true) {
+                    self.coalesced.fetch_add(1, Ordering::Relaxed);
+                    return Ok(token);
+                }
+                guard
+            }
+        };
+
+        info!("Fetching new WebSocket token from Kraken API");
+        self.fetches.fetch_add(1, Ordering::Relaxed);
+        let token = fetch().await.inspect_err(|_| {
+            self.fetch_failures.fetch_add(1, Ordering::Relaxed);
+        })?;
+        let now = Instant::now();
+        *self.cached.write() = Some(CachedToken {
+            token: token.clone(),
+            obtained_at: now,
+            expires_at: now + Duration::from_secs(TOKEN_VALIDITY_SECS),
+        });
+        Ok(token)
+    }
+
+    /// When the background task should refresh next
+    fn refresh_due(&self) -> Option<Instant> {
+        self.cached
+            .read()
+            .as_ref()
+            .map(|t| t.expires_at - Duration::from_secs(TOKEN_REFRESH_BUFFER_SECS))
+    }
+
+    fn stats(&self) -> TokenStats {
+        let now = Instant::now();
+        TokenStats {
+            fetches: self.fetches.load(Ordering::Relaxed),
+            fetch_failures: self.fetch_failures.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            expires_in_secs: self.cached.read().as_ref().map(|t| t.expires_at.saturating_duration_since(now).as_secs()),
+        }
+    }
+}
+
 impl KrakenAuth {
     /// Create a new authenticator with API credentials
     pub fn new(api_key: String, api_secret: String) -> Result<Self, AuthError> {
@@ -114,7 +229,8 @@ impl KrakenAuth {
                 .timeout(Duration::from_secs(30))
                 .build()
                 .unwrap_or_default(),
-            ws_token: RwLock::new(None),
+            ws_token: TokenCache::default(),
+            token_refresh_started: AtomicBool::new(false),
             nonce_counter: AtomicU64::new(initial_nonce),
         })
     }
@@ -126,16 +242,40 @@ impl KrakenAuth {
             api_key: String::new(),
             api_secret: Vec::new(),
             client: Client::new(),
-            ws_token: RwLock::new(None),
+            ws_token: TokenCache::default(),
+            token_refresh_started: AtomicBool::new(false),
             nonce_counter: AtomicU64::new(0),
         }
     }
 
+    /// Create an authenticator whose WebSocket token is already cached, so
+    /// the private socket can be driven without the REST API
+    #[cfg(test)]
+    pub fn with_cached_token(token: &str) -> Self {
+        let auth = Self {
+            api_key: "test-key".to_string(),
+            api_secret: b"test-secret".to_vec(),
+            ..Self::new_public_only()
+        };
+        let now = Instant::now();
+        *auth.ws_token.cached.write() = Some(CachedToken {
+            token: token.to_string(),
+            obtained_at: now,
+            expires_at: now + Duration::from_secs(TOKEN_VALIDITY_SECS),
+        });
+        auth
+    }
+
     /// Check if credentials are configured
     pub fn is_configured(&self) -> bool {
         !self.api_key.is_empty() && !self.api_secret.is_empty()
     }
 
+    /// Shared HTTP client for private REST calls
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
     /// Get API key
     pub fn api_key(&self) -> &str {
         &self.api_key
@@ -182,32 +322,41 @@ impl KrakenAuth {
             return Err(AuthError::NotConfigured);
         }
 
-        // Check if we have a valid cached token
-        {
-            let cached = self.ws_token.read();
-            if let Some(ref token) = *cached {
-                if token.expires_at > Instant::now() + Duration::from_secs(TOKEN_REFRESH_BUFFER_SECS) {
-                    debug!("Using cached WebSocket token");
-                    return Ok(token.token.clone());
-                }
-            }
-        }
+        self.ws_token.get(|| self.fetch_ws_token()).await
+    }
 
-        // Need to refresh token
-        info!("Fetching new WebSocket token from Kraken API");
-        let token = self.fetch_ws_token().await?;
-
-        // Cache the token
-        {
-            let mut cached = self.ws_token.write();
-            *cached = Some(CachedToken {
-                token: token.clone(),
-                obtained_at: Instant::now(),
-                expires_at: Instant::now() + Duration::from_secs(TOKEN_VALIDITY_SECS),
-            });
+    /// Keep the WebSocket token refreshed ahead of expiry in the background,
+    /// so callers always hit the cache. Idempotent; stops when the
+    /// authenticator is dropped.
+    pub fn start_token_refresh(self: &Arc<Self>) {
+        if !self.is_configured() || self.token_refresh_started.swap(true, Ordering::SeqCst) {
+            return;
         }
+        let auth: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                let Some(due) = auth.upgrade().and_then(|a| a.ws_token.refresh_due()) else {
+                    // Nothing cached yet (or a fetch failed): try again shortly
+                    tokio::time::sleep(Duration::from_secs(TOKEN_RETRY_SECS)).await;
+                    match auth.upgrade() {
+                        Some(a) => { let _ = a.get_ws_token().await; }
+                        None => return,
+                    }
+                    continue;
+                };
+                tokio::time::sleep_until(due.into()).await;
+                let Some(auth) = auth.upgrade() else { return };
+                if let Err(e) = auth.get_ws_token().await {
+                    debug!("Background WebSocket token refresh failed: {}", e);
+                    tokio::time::sleep(Duration::from_secs(TOKEN_RETRY_SECS)).await;
+                }
+            }
+        });
+    }
 
-        Ok(token)
+    /// Token fetch and cache counters
+    pub fn token_stats(&self) -> TokenStats {
+        self.ws_token.stats()
     }
 
     /// Fetch a new WebSocket token from the REST API
@@ -267,5 +416,35 @@ mod tests {
         assert!(!auth.is_configured());
     }
 
+    #[tokio::test]
+    async fn test_concurrent_token_requests_share_one_fetch() {
+        let cache = Arc::new(TokenCache::default());
+        let calls = Arc::new(AtomicU64::new(0));
+
+        let requests = (0..8).map(|_| {
+            let cache = Arc::clone(&cache);
+            let calls = Arc::clone(&calls);
+            tokio::spawn(async move {
+                cache.get(|| async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Ok("token-1".to_string())
+                }).await
+            })
+        });
+        for request in requests.collect::<Vec<_>>() {
+            assert_eq!(request.await.unwrap().unwrap(), "token-1");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Cached now: no fetch at all
+        let token = cache.get(|| async { Err(AuthError::TokenError("unused".into())) }).await.unwrap();
+        assert_eq!(token, "token-1");
+        let stats = cache.stats();
+        assert_eq!(stats.fetches, 1);
+        assert_eq!(stats.coalesced + stats.cache_hits, 8);
+        assert!(stats.expires_in_secs.unwrap() > TOKEN_VALIDITY_SECS - 5);
+    }
+
     // Note: Full authentication tests require valid API credentials
 }
@@ -0,0 +1,153 @@
+//! Circuit Breaker Reconciliation
+//!
+//! The breaker is kept in two places that can disagree: the DB's
+//! live_trading_state and the HFT loop's Stopped state. The loop trips
+//! itself on a loss limit without writing the DB, and a breaker tripped in
+//! the DB (by SQL, another process or before a restart) never reached a
+//! running loop. Every BREAKER_SYNC_SECS (default 5, 0 = off) the two are
+//! compared, and on a divergence the tripped side wins:
+//! - DB tripped, loop trading: the loop stops with the DB's reason
+//! - loop tripped, DB clear: the DB is tripped with the loop's reason
+//!
+//! Nothing is ever reset here; only an operator reset clears both. Resets
+//! hold the same lock as a check, so a check can't see one side already
+//! reset and trip it again.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BreakerSyncPolicy {
+    /// Seconds between checks (0 = never)
+    pub interval_secs: u64,
+}
+
+impl Default for BreakerSyncPolicy {
+    fn default() -> Self {
+        Self { interval_secs: 5 }
+    }
+}
+
+impl BreakerSyncPolicy {
+    /// Create from BREAKER_SYNC_SECS
+    pub fn from_env() -> Self {
+        Self {
+            interval_secs: std::env::var("BREAKER_SYNC_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(Self::default().interval_secs),
+        }
+    }
+}
+
+/// Where the breaker was tripped to heal a divergence
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "trip", rename_all = "snake_case")]
+pub enum BreakerHeal {
+    /// Only the DB was tripped
+    Engine { reason: String },
+    /// Only the engine was tripped
+    Db { reason: String },
+}
+
+/// What to do about the two states (None when they agree)
+pub fn reconcile(db_reason: Option<&str>, engine_reason: Option<&str>) -> Option<BreakerHeal> {
+    match (db_reason, engine_reason) {
+        (Some(reason), None) => Some(BreakerHeal::Engine { reason: reason.to_string() }),
+        (None, Some(reason)) => Some(BreakerHeal::Db { reason: reason.to_string() }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerDivergence {
+    pub at: DateTime<Utc>,
+    pub heal: BreakerHeal,
+    /// Why healing failed (the divergence is retried on the next check)
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerSyncStatus {
+    pub policy: BreakerSyncPolicy,
+    pub checks: u64,
+    pub divergences: u64,
+    pub last_divergence: Option<BreakerDivergence>,
+}
+
+pub struct BreakerSync {
+    policy: BreakerSyncPolicy,
+    lock: Mutex<()>,
+    checks: AtomicU64,
+    divergences: AtomicU64,
+    last_divergence: RwLock<Option<BreakerDivergence>>,
+}
+
+impl BreakerSync {
+    pub fn new(policy: BreakerSyncPolicy) -> Self {
+        Self {
+            policy,
+            lock: Mutex::new(()),
+            checks: AtomicU64::new(0),
+            divergences: AtomicU64::new(0),
+            last_divergence: RwLock::new(None),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(BreakerSyncPolicy::from_env())
+    }
+
+    pub fn policy(&self) -> &BreakerSyncPolicy {
+        &self.policy
+    }
+
+    /// Held across a check, and across a reset of both sides
+    pub async fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().await
+    }
+
+    pub fn record_check(&self) {
+        self.checks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_divergence(&self, divergence: BreakerDivergence) {
+        self.divergences.fetch_add(1, Ordering::Relaxed);
+        *self.last_divergence.write() = Some(divergence);
+    }
+
+    pub fn status(&self) -> BreakerSyncStatus {
+        BreakerSyncStatus {
+            policy: self.policy,
+            checks: self.checks.load(Ordering::Relaxed),
+            divergences: self.divergences.load(Ordering::Relaxed),
+            last_divergence: self.last_divergence.read().clone(),
+        }
+    }
+}
+
+impl Default for BreakerSync {
+    fn default() -> Self {
+        Self::new(BreakerSyncPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tripped_side_wins() {
+        assert_eq!(reconcile(None, None), None);
+        assert_eq!(reconcile(Some("manual"), Some("Daily loss limit exceeded")), None);
+        assert_eq!(reconcile(Some("manual"), None), Some(BreakerHeal::Engine { reason: "manual".to_string() }));
+        assert_eq!(
+            reconcile(None, Some("Daily loss limit exceeded")),
+            Some(BreakerHeal::Db { reason: "Daily loss limit exceeded".to_string() })
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Chaos Testing Hooks
+//!
+//! Fault injection for exercising the reconnect / resync / timeout paths on
+//! purpose instead of waiting for the exchange to misbehave. Off unless
+//! CHAOS_ENABLED=true; each fault then fires with its own probability:
+//!
+//! - CHAOS_WS_DROP_PCT: public frames discarded before parsing
+//! - CHAOS_DISCONNECT_PCT: public socket torn down on a frame (reconnect path)
+//! - CHAOS_CHECKSUM_PCT: book update checksum flipped (resync path)
+//! - CHAOS_ACK_DELAY_PCT / CHAOS_ACK_DELAY_MS: private messages held back
+//!   before handling (order timeout path)
+//!
+//! While enabled, incremental book updates are checked against the cached
+//! book's CRC32; a mismatch - injected or caused by dropped frames - resyncs
+//! the pair. Injected faults and the recoveries that followed are counted
+//! and served by `GET /api/chaos`. Recoveries are only counted up to the
+//! faults that can have caused them, so counters line up one to one.
+
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::warn;
+
+/// Fault probabilities, each in percent (0-100)
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ChaosPolicy {
+    pub enabled: bool,
+    pub ws_drop_pct: f64,
+    pub disconnect_pct: f64,
+    pub checksum_pct: f64,
+    pub ack_delay_pct: f64,
+    pub ack_delay_ms: u64,
+}
+
+impl ChaosPolicy {
+    /// Read CHAOS_ENABLED and the CHAOS_* probabilities (all off by default)
+    pub fn from_env() -> Self {
+        let pct = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| v.is_finite())
+                .map(|v| v.clamp(0.0, 100.0))
+                .unwrap_or(0.0)
+        };
+        Self {
+            enabled: std::env::var("CHAOS_ENABLED").is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1"),
+            ws_drop_pct: pct("CHAOS_WS_DROP_PCT"),
+            disconnect_pct: pct("CHAOS_DISCONNECT_PCT"),
+            checksum_pct: pct("CHAOS_CHECKSUM_PCT"),
+            ack_delay_pct: pct("CHAOS_ACK_DELAY_PCT"),
+            ack_delay_ms: std::env::var("CHAOS_ACK_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(6_000),
+        }
+    }
+}
+
+/// Kinds of injected fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    WsDrop,
+    Disconnect,
+    Checksum,
+    AckDelay,
+}
+
+/// Counters for the API
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChaosStats {
+    pub policy: ChaosPolicy,
+    pub frames_dropped: u64,
+    pub disconnects_forced: u64,
+    pub checksums_corrupted: u64,
+    pub acks_delayed: u64,
+    /// Book updates whose checksum disagreed with the cached book
+    pub checksum_mismatches: u64,
+    /// Reconnects after a forced disconnect
+    pub reconnects: u64,
+    /// Fresh snapshots after a checksum mismatch
+    pub resyncs: u64,
+    /// Orders timed out while acks were being delayed
+    pub order_timeouts: u64,
+    /// Pairs awaiting a fresh snapshot
+    pub resyncs_pending: usize,
+}
+
+pub struct ChaosMonkey {
+    policy: ChaosPolicy,
+    frames_dropped: AtomicU64,
+    disconnects_forced: AtomicU64,
+    checksums_corrupted: AtomicU64,
+    acks_delayed: AtomicU64,
+    checksum_mismatches: AtomicU64,
+    reconnects: AtomicU64,
+    resyncs: AtomicU64,
+    order_timeouts: AtomicU64,
+    resyncing: Mutex<HashSet<String>>,
+}
+
+impl ChaosMonkey {
+    pub fn new(policy: ChaosPolicy) -> Self {
+        if policy.enabled {
+            warn!(
+                "CHAOS TESTING ENABLED - drop {}%, disconnect {}%, checksum {}%, ack delay {}% ({}ms)",
+                policy.ws_drop_pct, policy.disconnect_pct, policy.checksum_pct, policy.ack_delay_pct, policy.ack_delay_ms
+            );
+        }
+        Self {
+            policy,
+            frames_dropped: AtomicU64::new(0),
+            disconnects_forced: AtomicU64::new(0),
+            checksums_corrupted: AtomicU64::new(0),
+            acks_delayed: AtomicU64::new(0),
+            checksum_mismatches: AtomicU64::new(0),
+            reconnects: AtomicU64::new(0),
+            resyncs: AtomicU64::new(0),
+            order_timeouts: AtomicU64::new(0),
+            resyncing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(ChaosPolicy::from_env())
+    }
+
+    fn counter(&self, fault: Fault) -> &AtomicU64 {
+        match fault {
+            Fault::WsDrop => &self.frames_dropped,
+            Fault::Disconnect => &self.disconnects_forced,
+            Fault::Checksum => &self.checksums_corrupted,
+            Fault::AckDelay => &self.acks_delayed,
+        }
+    }
+
+    /// Roll for `fault`; counts it when it fires
+    pub fn inject(&self, fault: Fault) -> bool {
+        if !self.policy.enabled {
+            return false;
+        }
+        let pct = match fault {
+            Fault::WsDrop => self.policy.ws_drop_pct,
+            Fault::Disconnect => self.policy.disconnect_pct,
+            Fault::Checksum => self.policy.checksum_pct,
+            Fault::AckDelay => self.policy.ack_delay_pct,
+        };
+        let fire = pct > 0.0 && rand::thread_rng().gen_range(0.0..100.0) < pct;
+        if fire {
+            self.counter(fault).fetch_add(1, Ordering::Relaxed);
+        }
+        fire
+    }
+
+    /// Delay to hold a private message back for, when AckDelay fires
+    pub fn ack_delay(&self) -> Option<Duration> {
+        self.inject(Fault::AckDelay).then(|| Duration::from_millis(self.policy.ack_delay_ms))
+    }
+
+    /// Whether book checksums are verified (only while chaos testing)
+    pub fn verifies_checksums(&self) -> bool {
+        self.policy.enabled
+    }
+
+    /// A checksum mismatch on `pair`. Returns true when a resync should be
+    /// requested (not already pending).
+    pub fn checksum_mismatch(&self, pair: &str) -> bool {
+        self.checksum_mismatches.fetch_add(1, Ordering::Relaxed);
+        self.resyncing.lock().insert(pair.to_string())
+    }
+
+    /// A snapshot arrived for `pair`
+    pub fn snapshot_received(&self, pair: &str) {
+        if self.policy.enabled && self.resyncing.lock().remove(pair) {
+            self.resyncs.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Increment `counter`, but never past `bound`
+    fn recover(counter: &AtomicU64, bound: &AtomicU64) {
+        let bound = bound.load(Ordering::Relaxed);
+        let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| (n < bound).then_some(n + 1));
+    }
+
+    /// The public socket connected again
+    pub fn reconnected(&self) {
+        Self::recover(&self.reconnects, &self.disconnects_forced);
+    }
+
+    /// An order timed out waiting for its ack
+    pub fn order_timed_out(&self) {
+        Self::recover(&self.order_timeouts, &self.acks_delayed);
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            policy: self.policy,
+            frames_dropped: self.frames_dropped.load(Ordering::Relaxed),
+            disconnects_forced: self.disconnects_forced.load(Ordering::Relaxed),
+            checksums_corrupted: self.checksums_corrupted.load(Ordering::Relaxed),
+            acks_delayed: self.acks_delayed.load(Ordering::Relaxed),
+            checksum_mismatches: self.checksum_mismatches.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            resyncs: self.resyncs.load(Ordering::Relaxed),
+            order_timeouts: self.order_timeouts.load(Ordering::Relaxed),
+            resyncs_pending: self.resyncing.lock().len(),
+        }
+    }
+}
+
+impl Default for ChaosMonkey {
+    /// Disabled: never injects
+    fn default() -> Self {
+        Self::new(ChaosPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faults_and_bounded_recoveries() {
+        let off = ChaosMonkey::default();
+        assert!(!off.inject(Fault::Disconnect));
+        assert!(!off.verifies_checksums());
+
+        let chaos = ChaosMonkey::new(ChaosPolicy {
+            enabled: true,
+            disconnect_pct: 100.0,
+            ack_delay_pct: 100.0,
+            ack_delay_ms: 250,
+            ..Default::default()
+        });
+        assert!(chaos.inject(Fault::Disconnect));
+        assert!(!chaos.inject(Fault::WsDrop));
+        assert_eq!(chaos.ack_delay(), Some(Duration::from_millis(250)));
+
+        // One forced disconnect pays for one reconnect, not two
+        chaos.reconnected();
+        chaos.reconnected();
+        chaos.order_timed_out();
+        chaos.order_timed_out();
+
+        // A second mismatch on the same pair doesn't request another resync
+        assert!(chaos.checksum_mismatch("BTC/USD"));
+        assert!(!chaos.checksum_mismatch("BTC/USD"));
+        chaos.snapshot_received("ETH/USD");
+        assert_eq!(chaos.stats().resyncs_pending, 1);
+        chaos.snapshot_received("BTC/USD");
+
+        let stats = chaos.stats();
+        assert_eq!(stats.disconnects_forced, 1);
+        assert_eq!(stats.reconnects, 1);
+        assert_eq!(stats.acks_delayed, 1);
+        assert_eq!(stats.order_timeouts, 1);
+        assert_eq!(stats.checksum_mismatches, 2);
+        assert_eq!(stats.resyncs, 1);
+        assert_eq!(stats.resyncs_pending, 0);
+    }
+}
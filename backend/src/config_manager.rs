@@ -1,12 +1,37 @@
 //! Config manager - stores engine configuration settings
 //!
 //! This module manages runtime-configurable settings for the HFT scanning engine.
-//! Primary settings: fee rates and min profit threshold.
+//! Primary settings: fee rates and min profit threshold, optionally
+//! overridden per path length (live_trading_config.leg_profit_thresholds).
 
 use crate::types::EngineConfig;
 use parking_lot::RwLock;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use tracing::info;
 
+/// Parse and validate the leg_profit_thresholds JSON from live_trading_config:
+/// an object of leg count -> threshold (decimal), e.g. {"3": 0.0015, "4": 0.0035}
+pub fn parse_leg_thresholds(value: &Value) -> Result<BTreeMap<usize, f64>, String> {
+    let entries = value
+        .as_object()
+        .ok_or("leg_profit_thresholds must be an object of leg count -> threshold")?;
+    let mut thresholds = BTreeMap::new();
+    for (legs, threshold) in entries {
+        let count: usize = legs
+            .parse()
+            .ok()
+            .filter(|n| (2..=10).contains(n))
+            .ok_or_else(|| format!("leg_profit_thresholds: '{}' is not a leg count (2-10)", legs))?;
+        let threshold = threshold
+            .as_f64()
+            .filter(|t| t.is_finite())
+            .ok_or_else(|| format!("leg_profit_thresholds: threshold for {} legs must be a number", legs))?;
+        thresholds.insert(count, threshold);
+    }
+    Ok(thresholds)
+}
+
 /// Manages engine configuration
 pub struct ConfigManager {
     config: RwLock<EngineConfig>,
@@ -45,6 +70,16 @@ impl ConfigManager {
         }
     }
 
+    /// Replace the per-path-length thresholds (empty = global threshold only)
+    pub fn update_leg_thresholds(&self, thresholds: BTreeMap<usize, f64>) {
+        let mut config = self.config.write();
+        if config.leg_thresholds != thresholds {
+            let summary: Vec<String> = thresholds.iter().map(|(legs, t)| format!("{} legs: {:.4}%", legs, t * 100.0)).collect();
+            info!("Updated leg profit thresholds: [{}]", summary.join(", "));
+            config.leg_thresholds = thresholds;
+        }
+    }
+
     /// Update fee rate with explicit source tracking
     pub fn update_fee_rate(&self, fee_rate: f64, source: &str) {
         let mut config = self.config.write();
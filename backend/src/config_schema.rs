@@ -0,0 +1,422 @@
+//! Unified Configuration Document
+//!
+//! Settings used to be changed through separate endpoints, each with its own
+//! validation: live_trading_config via /api/live/config, fees via /api/fees,
+//! with EngineConfig and the HFT loop config derived from them. This module
+//! describes all of it as one document:
+//!
+//! ```json
+//! { "trading": { "trade_amount": 10, ... }, "fees": { "maker_fee": 0.0016, "taker_fee": 0.0026 } }
+//! ```
+//!
+//! An update is a partial document (unset/null fields keep their value). It is
+//! merged over the current document, validated as a whole (every offending
+//! field is reported, not just the first), diffed against the current one and
+//! applied through `TradingEngine::apply_config`, which fans the result out to
+//! the database, the config manager and the HFT loop and logs the diff.
+#![allow(dead_code)]
+
+use crate::config_manager::parse_leg_thresholds;
+use crate::db::{ConfigUpdate, FeeConfiguration, LiveTradingConfig};
+use crate::executor::parse_disabled_pairs;
+use crate::hft_loop::{parse_reserves, SizingTier};
+use crate::trading_day::parse_timezone;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// Fee rates (decimal, e.g. 0.0026 = 0.26%)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeSettings {
+    pub maker_fee: f64,
+    pub taker_fee: f64,
+}
+
+/// The whole runtime configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigDocument {
+    pub trading: ConfigUpdate,
+    pub fees: FeeSettings,
+}
+
+/// Partial document accepted by updates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigPatch {
+    pub trading: Option<ConfigUpdate>,
+    pub fees: Option<FeePatch>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeePatch {
+    pub maker_fee: Option<f64>,
+    pub taker_fee: Option<f64>,
+}
+
+/// How strictly the stored configuration is checked when the server starts
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct StartupConfigPolicy {
+    /// Refuse to start with start settings unset, fees still pending or no
+    /// restrictions file, instead of waiting for the dashboard to fill them
+    pub require_complete: bool,
+}
+
+impl StartupConfigPolicy {
+    /// Create from STARTUP_REQUIRE_CONFIG (default false)
+    pub fn from_env() -> Self {
+        Self {
+            require_complete: std::env::var("STARTUP_REQUIRE_CONFIG")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// One invalid field, e.g. {"field": "trading.max_pairs", "message": "must be at least 1"}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// One changed field with its old and new value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Invalid configuration: {}", .0.iter().map(|e| format!("{} {}", e.field, e.message)).collect::<Vec<_>>().join("; "))]
+    Invalid(Vec<FieldError>),
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+impl ConfigPatch {
+    /// Parse a request body, reporting unknown sections/fields and type errors by field
+    pub fn parse(body: &Value) -> Result<Self, ConfigError> {
+        let sections = body.as_object().ok_or_else(|| ConfigError::Invalid(vec![FieldError {
+            field: "document".to_string(),
+            message: "must be an object with trading and/or fees".to_string(),
+        }]))?;
+
+        let known = |section: Value| -> Vec<String> {
+            section.as_object().map(|o| o.keys().cloned().collect()).unwrap_or_default()
+        };
+        let trading_fields = known(serde_json::to_value(ConfigUpdate::default()).unwrap_or_default());
+        let fee_fields = known(serde_json::to_value(FeePatch::default()).unwrap_or_default());
+
+        let mut errors = Vec::new();
+        for (section, fields) in sections {
+            let allowed = match section.as_str() {
+                "trading" => &trading_fields,
+                "fees" => &fee_fields,
+                _ => {
+                    errors.push(FieldError { field: section.clone(), message: "unknown section".to_string() });
+                    continue;
+                }
+            };
+            let Some(fields) = fields.as_object() else {
+                errors.push(FieldError { field: section.clone(), message: "must be an object".to_string() });
+                continue;
+            };
+            for (field, value) in fields {
+                let path = format!("{}.{}", section, field);
+                // Legacy alias accepted by /api/live/config
+                let name = if section == "trading" && field == "base_currency" { "start_currency" } else { field.as_str() };
+                if !allowed.iter().any(|f| f == name) {
+                    errors.push(FieldError { field: path, message: "unknown field".to_string() });
+                    continue;
+                }
+                // Type-check each field on its own so every bad one is listed
+                let single = serde_json::json!({ name: value });
+                let result = match section.as_str() {
+                    "trading" => serde_json::from_value::<ConfigUpdate>(single).err(),
+                    _ => serde_json::from_value::<FeePatch>(single).err(),
+                };
+                if let Some(e) = result {
+                    errors.push(FieldError { field: path, message: e.to_string() });
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(errors));
+        }
+        serde_json::from_value(body.clone()).map_err(invalid_document)
+    }
+}
+
+impl ConfigDocument {
+    /// Current document from the stored live and fee configuration
+    pub fn from_parts(config: &LiveTradingConfig, fees: &FeeConfiguration) -> Self {
+        Self {
+            trading: ConfigUpdate {
+                trade_amount: config.trade_amount,
+                min_profit_threshold: config.min_profit_threshold,
+                max_daily_loss: config.max_daily_loss,
+                max_total_loss: config.max_total_loss,
+                start_currency: config.start_currency.clone(),
+                max_pairs: config.max_pairs,
+                min_volume_24h_usd: config.min_volume_24h_usd,
+                max_cost_min: config.max_cost_min,
+                sizing_tiers: config.sizing_tiers.clone(),
+                max_safe_amount: config.max_safe_amount,
+                global_cooldown_ms: config.global_cooldown_ms,
+                path_cooldown_ms: config.path_cooldown_ms,
+                pair_failure_cooldown_ms: config.pair_failure_cooldown_ms,
+                leg_depth_levels: config.leg_depth_levels,
+                max_leg_slippage_bps: config.max_leg_slippage_bps,
+                currency_reserves: config.currency_reserves.clone(),
+                max_notional_per_hour: config.max_notional_per_hour,
+                max_notional_per_day: config.max_notional_per_day,
+                execution_disabled_pairs: config.execution_disabled_pairs.clone(),
+                leg_profit_thresholds: config.leg_profit_thresholds.clone(),
+                daily_reset_timezone: config.daily_reset_timezone.clone(),
+            },
+            fees: FeeSettings {
+                maker_fee: fees.maker_fee,
+                taker_fee: fees.taker_fee,
+            },
+        }
+    }
+
+    /// Apply a patch: set fields replace the current value, unset ones keep it
+    pub fn merged(&self, patch: &ConfigPatch) -> Result<Self, ConfigError> {
+        let mut doc = serde_json::to_value(self).map_err(invalid_document)?;
+        let patch = serde_json::to_value(patch).map_err(invalid_document)?;
+        merge(&mut doc, &patch);
+        serde_json::from_value(doc).map_err(invalid_document)
+    }
+
+    /// Check every field, returning the document with normalized JSON
+    /// settings (sorted sizing tiers, uppercase reserve currencies)
+    pub fn validate(mut self) -> Result<Self, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let mut fail = |field: &str, message: String| errors.push(FieldError {
+            field: field.to_string(),
+            message,
+        });
+        let t = &mut self.trading;
+
+        for (field, value) in [
+            ("trading.trade_amount", t.trade_amount),
+            ("trading.max_daily_loss", t.max_daily_loss),
+            ("trading.max_total_loss", t.max_total_loss),
+            ("trading.max_safe_amount", t.max_safe_amount),
+            ("trading.max_notional_per_hour", t.max_notional_per_hour),
+            ("trading.max_notional_per_day", t.max_notional_per_day),
+        ] {
+            if value.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+                fail(field, "must be greater than 0".to_string());
+            }
+        }
+        if t.min_profit_threshold.is_some_and(|v| !v.is_finite()) {
+            fail("trading.min_profit_threshold", "must be a number".to_string());
+        }
+        if t.start_currency.as_deref().is_some_and(|c| c.split(',').all(|s| s.trim().is_empty())) {
+            fail("trading.start_currency", "must name at least one currency".to_string());
+        }
+        if t.max_pairs.is_some_and(|v| v < 1) {
+            fail("trading.max_pairs", "must be at least 1".to_string());
+        }
+        for (field, value) in [
+            ("trading.min_volume_24h_usd", t.min_volume_24h_usd),
+            ("trading.max_cost_min", t.max_cost_min),
+            ("trading.max_leg_slippage_bps", t.max_leg_slippage_bps),
+        ] {
+            if value.is_some_and(|v| !v.is_finite() || v < 0.0) {
+                fail(field, "must be 0 or greater".to_string());
+            }
+        }
+        for (field, value) in [
+            ("trading.global_cooldown_ms", t.global_cooldown_ms),
+            ("trading.path_cooldown_ms", t.path_cooldown_ms),
+            ("trading.pair_failure_cooldown_ms", t.pair_failure_cooldown_ms),
+        ] {
+            if value.is_some_and(|v| v < 0) {
+                fail(field, "must be 0 or greater".to_string());
+            }
+        }
+        if let (Some(hour), Some(day)) = (t.max_notional_per_hour, t.max_notional_per_day) {
+            if hour > day {
+                fail("trading.max_notional_per_hour", "must not exceed max_notional_per_day".to_string());
+            }
+        }
+        if t.leg_depth_levels.is_some_and(|v| v < 1) {
+            fail("trading.leg_depth_levels", "must be at least 1".to_string());
+        }
+        if let Some(ref tiers) = t.sizing_tiers {
+            match SizingTier::parse_tiers(tiers) {
+                Ok(parsed) => t.sizing_tiers = serde_json::to_value(parsed).ok(),
+                Err(e) => fail("trading.sizing_tiers", e),
+            }
+        }
+        if let Some(ref reserves) = t.currency_reserves {
+            match parse_reserves(reserves) {
+                Ok(parsed) => t.currency_reserves = serde_json::to_value(parsed).ok(),
+                Err(e) => fail("trading.currency_reserves", e),
+            }
+        }
+        if let Some(ref pairs) = t.execution_disabled_pairs {
+            match parse_disabled_pairs(pairs) {
+                Ok(parsed) => t.execution_disabled_pairs = serde_json::to_value(parsed).ok(),
+                Err(e) => fail("trading.execution_disabled_pairs", e),
+            }
+        }
+        if let Some(ref thresholds) = t.leg_profit_thresholds {
+            match parse_leg_thresholds(thresholds) {
+                Ok(parsed) => t.leg_profit_thresholds = serde_json::to_value(parsed).ok(),
+                Err(e) => fail("trading.leg_profit_thresholds", e),
+            }
+        }
+        if let Some(ref timezone) = t.daily_reset_timezone {
+            match parse_timezone(timezone) {
+                Ok(tz) => t.daily_reset_timezone = Some(tz.name().to_string()),
+                Err(e) => fail("trading.daily_reset_timezone", e),
+            }
+        }
+
+        for (field, value) in [("fees.maker_fee", self.fees.maker_fee), ("fees.taker_fee", self.fees.taker_fee)] {
+            if !(0.0..=0.1).contains(&value) {
+                fail(field, "must be between 0% and 10% (0.0 - 0.1)".to_string());
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Fields that differ from `before`, as "section.field"
+    pub fn diff(&self, before: &ConfigDocument) -> Vec<ConfigChange> {
+        let (old, new) = match (serde_json::to_value(before), serde_json::to_value(self)) {
+            (Ok(old), Ok(new)) => (old, new),
+            _ => return Vec::new(),
+        };
+
+        let mut changes = Vec::new();
+        if let (Value::Object(old), Value::Object(new)) = (&old, &new) {
+            for (section, new_fields) in new {
+                let (Some(Value::Object(old_fields)), Value::Object(new_fields)) = (old.get(section), new_fields) else {
+                    continue;
+                };
+                for (field, new_value) in new_fields {
+                    let old_value = old_fields.get(field).cloned().unwrap_or(Value::Null);
+                    if &old_value != new_value {
+                        changes.push(ConfigChange {
+                            field: format!("{}.{}", section, field),
+                            old: old_value,
+                            new: new_value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    /// Settings the engine won't start without that are unset, as
+    /// "section.field"
+    pub fn missing_required(&self) -> Vec<String> {
+        let t = &self.trading;
+        [
+            ("trading.start_currency", t.start_currency.as_deref().is_some_and(|c| !c.trim().is_empty())),
+            ("trading.max_pairs", t.max_pairs.is_some()),
+            ("trading.min_volume_24h_usd", t.min_volume_24h_usd.is_some()),
+            ("trading.max_cost_min", t.max_cost_min.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| !set)
+        .map(|(field, _)| field.to_string())
+        .collect()
+    }
+
+    pub fn fees_changed(changes: &[ConfigChange]) -> bool {
+        changes.iter().any(|c| c.field.starts_with("fees."))
+    }
+
+    pub fn trading_changed(changes: &[ConfigChange]) -> bool {
+        changes.iter().any(|c| c.field.starts_with("trading."))
+    }
+}
+
+fn invalid_document(e: serde_json::Error) -> ConfigError {
+    ConfigError::Invalid(vec![FieldError { field: "document".to_string(), message: e.to_string() }])
+}
+
+/// Merge `patch` sections into `base`. Set fields replace the whole value
+/// (a new reserves object replaces the old one); null fields leave it as is.
+fn merge(base: &mut Value, patch: &Value) {
+    let (Value::Object(base), Value::Object(patch)) = (base, patch) else {
+        return;
+    };
+    for (section, fields) in patch {
+        let (Some(Value::Object(current)), Value::Object(fields)) = (base.get_mut(section), fields) else {
+            continue;
+        };
+        for (field, value) in fields {
+            if !value.is_null() {
+                current.insert(field.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_validate_and_diff() {
+        let current = ConfigDocument::from_parts(&LiveTradingConfig::default(), &FeeConfiguration {
+            maker_fee: 0.0016,
+            taker_fee: 0.0026,
+            ..FeeConfiguration::default()
+        });
+
+        // Every offending field is reported
+        let patch: ConfigPatch = serde_json::from_value(serde_json::json!({
+            "trading": { "max_pairs": 0, "path_cooldown_ms": -5, "sizing_tiers": [{"min_profit_pct": 0.2, "amount": -1}] },
+            "fees": { "taker_fee": 0.5 }
+        })).unwrap();
+        let errors = current.merged(&patch).unwrap().validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["trading.max_pairs", "trading.path_cooldown_ms", "trading.sizing_tiers", "fees.taker_fee"]);
+
+        // Unknown sections/fields and bad types are rejected up front, all listed
+        let err = ConfigPatch::parse(&serde_json::json!({
+            "engine": {},
+            "trading": { "trade_amnt": 5, "max_pairs": "many", "base_currency": "USD" }
+        })).unwrap_err();
+        let ConfigError::Invalid(errors) = err else { panic!("expected field errors") };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["engine", "trading.max_pairs", "trading.trade_amnt"]);
+
+        let patch = ConfigPatch::parse(&serde_json::json!({
+            "trading": { "trade_amount": 25.0, "currency_reserves": {"usd": 500} },
+            "fees": { "maker_fee": 0.001 }
+        })).unwrap();
+        let updated = current.merged(&patch).unwrap().validate().unwrap();
+        assert_eq!(updated.fees, FeeSettings { maker_fee: 0.001, taker_fee: 0.0026 });
+        assert_eq!(updated.trading.max_pairs, current.trading.max_pairs);
+
+        let changes = updated.diff(&current);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["fees.maker_fee", "trading.currency_reserves", "trading.trade_amount"]);
+        assert_eq!(changes[1].new, serde_json::json!({"USD": 500.0}));
+        assert!(ConfigDocument::fees_changed(&changes) && ConfigDocument::trading_changed(&changes));
+        assert!(updated.diff(&updated).is_empty());
+
+        // What start() would refuse to run without
+        let patch = ConfigPatch::parse(&serde_json::json!({
+            "trading": { "start_currency": " ", "max_pairs": 30, "min_volume_24h_usd": 100000.0 }
+        })).unwrap();
+        let partial = current.merged(&patch).unwrap();
+        assert_eq!(partial.missing_required(), vec!["trading.start_currency", "trading.max_cost_min"]);
+    }
+}
@@ -0,0 +1,359 @@
+//! Price Triangulation Consistency Monitor
+//!
+//! Periodically cross-checks cached prices against each other:
+//! - For every pair X/Y that can also be reached through a third currency
+//!   (X/Z · Z/Y), the direct mid is compared with the implied mid
+//! - A pair that disagrees with most of its triangles is flagged as broken
+//! - Crossed books (bid > ask) and books that stopped updating are flagged too
+//!
+//! Flagged pairs are quarantined in the OrderBookCache so the scanner leaves
+//! them out of the graph until their quotes look sane again.
+#![allow(dead_code)]
+
+use crate::order_book::OrderBookCache;
+use crate::types::PriceEdge;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+// ============================================================================
+// Thresholds
+// ============================================================================
+
+/// How often the monitor re-evaluates all cached prices
+const CHECK_INTERVAL_MS: u64 = 1000;
+
+/// Max divergence (%) between direct and implied mid for a consistent triangle
+/// Real triangular edges are well under 1% - anything beyond this is bad data
+pub const MAX_TRIANGLE_DIVERGENCE_PCT: f64 = 2.0;
+
+/// Quote age (ms) after which a pair is considered stale
+/// Much looser than MAX_ORDERBOOK_STALENESS_MS: this catches dead feeds, not slow ones
+pub const STALE_QUOTE_MS: i64 = 30_000;
+
+/// Minimum triangles needed before a pair can be judged divergent
+const MIN_TRIANGLES: usize = 2;
+
+// ============================================================================
+// Report Types
+// ============================================================================
+
+/// Why a pair was excluded from scanning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagReason {
+    /// Best bid above best ask
+    Crossed,
+    /// Mid disagrees with the majority of triangles through other currencies
+    Divergent,
+    /// No update for longer than STALE_QUOTE_MS
+    Stale,
+}
+
+impl FlagReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagReason::Crossed => "crossed",
+            FlagReason::Divergent => "divergent",
+            FlagReason::Stale => "stale",
+        }
+    }
+}
+
+/// A pair currently excluded from scanning
+#[derive(Debug, Clone, Serialize)]
+pub struct FlaggedPair {
+    pub pair: String,
+    pub reason: FlagReason,
+    /// Median divergence across triangles (only for Divergent)
+    pub divergence_pct: Option<f64>,
+}
+
+/// Result of one consistency pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConsistencyReport {
+    pub pairs_checked: usize,
+    pub triangles_checked: usize,
+    pub flagged: Vec<FlaggedPair>,
+    /// Share of consistent triangles each currency takes part in (1.0 = all consistent)
+    pub currency_scores: BTreeMap<String, f64>,
+    pub last_check: String,
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+/// Background monitor that quarantines pairs with broken or stale quotes
+pub struct PriceConsistencyMonitor {
+    cache: Arc<OrderBookCache>,
+    report: RwLock<ConsistencyReport>,
+    is_running: Arc<AtomicBool>,
+}
+
+impl PriceConsistencyMonitor {
+    pub fn new(cache: Arc<OrderBookCache>) -> Self {
+        Self {
+            cache,
+            report: RwLock::new(ConsistencyReport::default()),
+            is_running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Spawn the periodic check task (no-op if already running)
+    pub fn start(self: &Arc<Self>) {
+        if self.is_running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let monitor = Arc::clone(self);
+        tokio::spawn(async move {
+            info!("Price consistency monitor started");
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(CHECK_INTERVAL_MS));
+
+            while monitor.is_running.load(Ordering::SeqCst) {
+                interval.tick().await;
+                monitor.check_once();
+            }
+
+            info!("Price consistency monitor stopped");
+        });
+    }
+
+    /// Stop the check task and release all quarantined pairs
+    pub fn stop(&self) {
+        self.is_running.store(false, Ordering::SeqCst);
+        self.cache.set_quarantined(HashMap::new());
+    }
+
+    /// Run a single consistency pass and apply the quarantine
+    pub fn check_once(&self) -> ConsistencyReport {
+        let prices = self.cache.get_all_prices();
+        let report = evaluate(&prices, Utc::now());
+
+        let quarantined: HashMap<String, String> = report.flagged
+            .iter()
+            .map(|f| (f.pair.clone(), f.reason.as_str().to_string()))
+            .collect();
+
+        // Log only changes to the flagged set
+        let previous = self.cache.get_quarantined();
+        for flagged in &report.flagged {
+            if !previous.contains_key(&flagged.pair) {
+                warn!(
+                    "Quarantining {} ({}{})",
+                    flagged.pair,
+                    flagged.reason.as_str(),
+                    flagged.divergence_pct.map(|d| format!(", {:.2}% off triangles", d)).unwrap_or_default()
+                );
+            }
+        }
+        for pair in previous.keys() {
+            if !quarantined.contains_key(pair) {
+                info!("Releasing {} from quarantine - quotes consistent again", pair);
+            }
+        }
+
+        self.cache.set_quarantined(quarantined);
+        *self.report.write() = report.clone();
+        report
+    }
+
+    /// Get the latest report
+    pub fn get_report(&self) -> ConsistencyReport {
+        self.report.read().clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::SeqCst)
+    }
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+/// Evaluate price consistency across all pairs
+fn evaluate(prices: &HashMap<String, PriceEdge>, now: DateTime<Utc>) -> ConsistencyReport {
+    let mut flagged = Vec::new();
+
+    // Mid price by (base, quote) for pairs that pass the basic checks
+    let mut mids: HashMap<(&str, &str), (&str, f64)> = HashMap::new();
+
+    for (pair, edge) in prices {
+        if edge.bid <= 0.0 || edge.ask <= 0.0 {
+            continue; // No quote yet - the scanner skips these on its own
+        }
+        if edge.bid > edge.ask {
+            flagged.push(FlaggedPair { pair: pair.clone(), reason: FlagReason::Crossed, divergence_pct: None });
+            continue;
+        }
+        if (now - edge.last_update).num_milliseconds() > STALE_QUOTE_MS {
+            flagged.push(FlaggedPair { pair: pair.clone(), reason: FlagReason::Stale, divergence_pct: None });
+            continue;
+        }
+        mids.insert((edge.base.as_str(), edge.quote.as_str()), (pair.as_str(), (edge.bid + edge.ask) / 2.0));
+    }
+
+    // Conversion rate from -> to, using either orientation of the pair
+    let rate = |from: &str, to: &str| -> Option<f64> {
+        if let Some((_, mid)) = mids.get(&(from, to)) {
+            return Some(*mid);
+        }
+        mids.get(&(to, from)).map(|(_, mid)| 1.0 / mid)
+    };
+
+    let currencies: Vec<&str> = {
+        let mut c: Vec<&str> = mids.keys().flat_map(|(b, q)| [*b, *q]).collect();
+        c.sort_unstable();
+        c.dedup();
+        c
+    };
+
+    let mut triangles_checked = 0usize;
+    // currency -> (consistent triangles, total triangles)
+    let mut currency_counts: HashMap<&str, (u32, u32)> = HashMap::new();
+
+    for (&(base, quote), &(pair, direct)) in &mids {
+        let mut divergences: Vec<f64> = Vec::new();
+
+        for &via in &currencies {
+            if via == base || via == quote {
+                continue;
+            }
+            let implied = match (rate(base, via), rate(via, quote)) {
+                (Some(a), Some(b)) => a * b,
+                _ => continue,
+            };
+
+            let divergence = (direct / implied - 1.0).abs() * 100.0;
+            divergences.push(divergence);
+            triangles_checked += 1;
+
+            let consistent = divergence <= MAX_TRIANGLE_DIVERGENCE_PCT;
+            for currency in [base, quote, via] {
+                let entry = currency_counts.entry(currency).or_insert((0, 0));
+                entry.1 += 1;
+                if consistent {
+                    entry.0 += 1;
+                }
+            }
+        }
+
+        if divergences.len() < MIN_TRIANGLES {
+            continue;
+        }
+
+        // Lower median: a healthy pair that shares one triangle with a broken
+        // pair must not get flagged along with it
+        divergences.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let median = divergences[(divergences.len() - 1) / 2];
+
+        if median > MAX_TRIANGLE_DIVERGENCE_PCT {
+            flagged.push(FlaggedPair {
+                pair: pair.to_string(),
+                reason: FlagReason::Divergent,
+                divergence_pct: Some(median),
+            });
+        }
+    }
+
+    flagged.sort_by(|a, b| a.pair.cmp(&b.pair));
+
+    let currency_scores = currency_counts
+        .into_iter()
+        .map(|(currency, (consistent, total))| (currency.to_string(), consistent as f64 / total as f64))
+        .collect();
+
+    ConsistencyReport {
+        pairs_checked: prices.len(),
+        triangles_checked,
+        flagged,
+        currency_scores,
+        last_check: now.to_rfc3339(),
+    }
+}
+
+/// Median divergence (%) of `pair`'s mid from its triangles through other
+/// currencies (None without a sane quote or MIN_TRIANGLES triangles)
+pub fn pair_divergence(prices: &HashMap<String, PriceEdge>, pair: &str, now: DateTime<Utc>) -> Option<f64> {
+    let sane = |edge: &PriceEdge| {
+        edge.bid > 0.0 && edge.ask >= edge.bid && (now - edge.last_update).num_milliseconds() <= STALE_QUOTE_MS
+    };
+    let target = prices.get(pair).filter(|e| sane(e))?;
+    let mids: HashMap<(&str, &str), f64> = prices
+        .values()
+        .filter(|e| sane(e))
+        .map(|e| ((e.base.as_str(), e.quote.as_str()), (e.bid + e.ask) / 2.0))
+        .collect();
+    let rate = |from: &str, to: &str| -> Option<f64> {
+        mids.get(&(from, to)).copied().or_else(|| mids.get(&(to, from)).map(|mid| 1.0 / mid))
+    };
+
+    let (base, quote) = (target.base.as_str(), target.quote.as_str());
+    let direct = (target.bid + target.ask) / 2.0;
+    let mut vias: Vec<&str> = mids.keys().flat_map(|(b, q)| [*b, *q]).filter(|c| *c != base && *c != quote).collect();
+    vias.sort_unstable();
+    vias.dedup();
+    let mut divergences: Vec<f64> = vias
+        .into_iter()
+        .filter_map(|via| Some(rate(base, via)? * rate(via, quote)?))
+        .map(|implied| (direct / implied - 1.0).abs() * 100.0)
+        .collect();
+    if divergences.len() < MIN_TRIANGLES {
+        return None;
+    }
+    divergences.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    Some(divergences[(divergences.len() - 1) / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(base: &str, quote: &str, mid: f64, age_ms: i64) -> (String, PriceEdge) {
+        let pair = format!("{}/{}", base, quote);
+        (pair.clone(), PriceEdge {
+            pair,
+            base: base.to_string(),
+            quote: quote.to_string(),
+            bid: mid * 0.9999,
+            ask: mid * 1.0001,
+            volume_24h: 0.0,
+            last_update: Utc::now() - chrono::Duration::milliseconds(age_ms),
+        })
+    }
+
+    #[test]
+    fn test_divergent_and_stale_pairs_flagged() {
+        let prices: HashMap<String, PriceEdge> = [
+            edge("BTC", "USD", 50000.0, 0),
+            edge("ETH", "USD", 2500.0, 0),
+            edge("EUR", "USD", 1.10, 0),
+            edge("ETH", "BTC", 0.05, 0),
+            edge("BTC", "EUR", 45454.5, 0),
+            edge("ETH", "EUR", 2272.7, 0),
+            // Broken quote: should be ~1 USD
+            edge("USDC", "USD", 1.30, 0),
+            edge("USDC", "EUR", 0.909, 0),
+            edge("BTC", "USDC", 50000.0, 0),
+            // Dead feed
+            edge("SOL", "USD", 100.0, STALE_QUOTE_MS + 1000),
+        ]
+        .into_iter()
+        .collect();
+
+        let report = evaluate(&prices, Utc::now());
+        let flagged: HashMap<&str, FlagReason> = report.flagged.iter().map(|f| (f.pair.as_str(), f.reason)).collect();
+
+        assert_eq!(flagged.get("USDC/USD"), Some(&FlagReason::Divergent));
+        assert_eq!(flagged.get("SOL/USD"), Some(&FlagReason::Stale));
+        assert!(!flagged.contains_key("BTC/USD"));
+        assert!(!flagged.contains_key("ETH/BTC"));
+        assert!(report.currency_scores["ETH"] > report.currency_scores["USDC"]);
+    }
+}
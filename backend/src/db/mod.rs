@@ -5,8 +5,11 @@ mod models;
 
 pub use models::*;
 
+use chrono::{DateTime, Utc};
+use sqlx::migrate::Migrator;
 use sqlx::postgres::{PgPool, PgPoolOptions};
-use sqlx::FromRow;
+use sqlx::{FromRow, Row};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use thiserror::Error;
 use tracing::info;
@@ -15,16 +18,26 @@ use tracing::info;
 pub enum DbError {
     #[error("Database error: {0}")]
     Sqlx(#[from] sqlx::Error),
+    #[error("Migration error: {0}")]
+    Migrate(#[from] sqlx::migrate::MigrateError),
     #[error("Record not found")]
     NotFound,
     #[error("Invalid data: {0}")]
     InvalidData(String),
 }
 
+/// Schema migrations embedded at compile time from backend/migrations
+/// docker-compose and db/migrations/apply_all_migrations.sql run the same
+/// files, so every migration is idempotent (databases created that way can
+/// switch to DB_AUTO_MIGRATE later)
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 /// Database connection wrapper
 #[derive(Clone)]
 pub struct Database {
     pool: Arc<PgPool>,
+    /// Bumped after every config/state/trade write (read caches compare against it)
+    write_generation: Arc<AtomicU64>,
 }
 
 impl Database {
@@ -39,14 +52,46 @@ impl Database {
         
         Ok(Self {
             pool: Arc::new(pool),
+            write_generation: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Pool that only connects when first used, so components can run
+    /// without a reachable database (writes then fail after `acquire_timeout`)
+    #[cfg(test)]
+    pub fn connect_lazy(database_url: &str, acquire_timeout: std::time::Duration) -> Result<Self, DbError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .acquire_timeout(acquire_timeout)
+            .connect_lazy(database_url)?;
+
+        Ok(Self {
+            pool: Arc::new(pool),
+            write_generation: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    /// Apply any pending embedded migrations
+    pub async fn migrate(&self) -> Result<(), DbError> {
+        MIGRATOR.run(self.pool()).await?;
+        info!("Database migrations applied ({} embedded)", MIGRATOR.iter().count());
+        Ok(())
+    }
+
     /// Get a reference to the connection pool
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
 
+    /// Current write generation - changes whenever config, state or trades change
+    pub fn write_generation(&self) -> u64 {
+        self.write_generation.load(Ordering::Acquire)
+    }
+
+    fn mark_write(&self) {
+        self.write_generation.fetch_add(1, Ordering::Release);
+    }
+
     // ==========================================
     // Config Operations
     // ==========================================
@@ -59,6 +104,11 @@ impl Database {
                 id, is_enabled, trade_amount, min_profit_threshold,
                 max_daily_loss, max_total_loss, start_currency, custom_currencies,
                 max_pairs, min_volume_24h_usd, max_cost_min,
+                sizing_tiers, max_safe_amount,
+                global_cooldown_ms, path_cooldown_ms, pair_failure_cooldown_ms,
+                leg_depth_levels, max_leg_slippage_bps, currency_reserves,
+                max_notional_per_hour, max_notional_per_day, execution_disabled_pairs, leg_profit_thresholds,
+                daily_reset_timezone,
                 created_at, updated_at, enabled_at, disabled_at
             FROM live_trading_config
             WHERE id = 1
@@ -87,12 +137,30 @@ impl Database {
                 max_pairs = COALESCE($6, max_pairs),
                 min_volume_24h_usd = COALESCE($7, min_volume_24h_usd),
                 max_cost_min = COALESCE($8, max_cost_min),
+                sizing_tiers = COALESCE($9, sizing_tiers),
+                max_safe_amount = COALESCE($10, max_safe_amount),
+                global_cooldown_ms = COALESCE($11, global_cooldown_ms),
+                path_cooldown_ms = COALESCE($12, path_cooldown_ms),
+                pair_failure_cooldown_ms = COALESCE($13, pair_failure_cooldown_ms),
+                leg_depth_levels = COALESCE($14, leg_depth_levels),
+                max_leg_slippage_bps = COALESCE($15, max_leg_slippage_bps),
+                currency_reserves = COALESCE($16, currency_reserves),
+                max_notional_per_hour = COALESCE($17, max_notional_per_hour),
+                max_notional_per_day = COALESCE($18, max_notional_per_day),
+                execution_disabled_pairs = COALESCE($19, execution_disabled_pairs),
+                leg_profit_thresholds = COALESCE($20, leg_profit_thresholds),
+                daily_reset_timezone = COALESCE($21, daily_reset_timezone),
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = 1
             RETURNING
                 id, is_enabled, trade_amount, min_profit_threshold,
                 max_daily_loss, max_total_loss, start_currency, custom_currencies,
                 max_pairs, min_volume_24h_usd, max_cost_min,
+                sizing_tiers, max_safe_amount,
+                global_cooldown_ms, path_cooldown_ms, pair_failure_cooldown_ms,
+                leg_depth_levels, max_leg_slippage_bps, currency_reserves,
+                max_notional_per_hour, max_notional_per_day, execution_disabled_pairs, leg_profit_thresholds,
+                daily_reset_timezone,
                 created_at, updated_at, enabled_at, disabled_at
             "#
         )
@@ -104,14 +172,30 @@ impl Database {
         .bind(updates.max_pairs)
         .bind(updates.min_volume_24h_usd)
         .bind(updates.max_cost_min)
+        .bind(updates.sizing_tiers)
+        .bind(updates.max_safe_amount)
+        .bind(updates.global_cooldown_ms)
+        .bind(updates.path_cooldown_ms)
+        .bind(updates.pair_failure_cooldown_ms)
+        .bind(updates.leg_depth_levels)
+        .bind(updates.max_leg_slippage_bps)
+        .bind(updates.currency_reserves)
+        .bind(updates.max_notional_per_hour)
+        .bind(updates.max_notional_per_day)
+        .bind(updates.execution_disabled_pairs)
+        .bind(updates.leg_profit_thresholds)
+        .bind(updates.daily_reset_timezone)
         .fetch_one(self.pool())
         .await?;
 
+        self.mark_write();
         Ok(LiveTradingConfig::from_row(&row)?)
     }
 
-    /// Enable trading
+    /// Enable trading and open a new trading session with the current config
+    /// (a session still open is ended first)
     pub async fn enable_trading(&self) -> Result<LiveTradingConfig, DbError> {
+        let mut tx = self.pool().begin().await?;
         let row = sqlx::query(
             r#"
             UPDATE live_trading_config
@@ -124,17 +208,36 @@ impl Database {
                 id, is_enabled, trade_amount, min_profit_threshold,
                 max_daily_loss, max_total_loss, start_currency, custom_currencies,
                 max_pairs, min_volume_24h_usd, max_cost_min,
+                sizing_tiers, max_safe_amount,
+                global_cooldown_ms, path_cooldown_ms, pair_failure_cooldown_ms,
+                leg_depth_levels, max_leg_slippage_bps, currency_reserves,
+                max_notional_per_hour, max_notional_per_day, execution_disabled_pairs, leg_profit_thresholds,
+                daily_reset_timezone,
                 created_at, updated_at, enabled_at, disabled_at
             "#
         )
-        .fetch_one(self.pool())
+        .fetch_one(&mut *tx)
         .await?;
 
+        Self::end_session(&mut tx, "Superseded by a new session").await?;
+        sqlx::query(
+            r#"
+            INSERT INTO trading_sessions (session_id, started_at, config)
+            SELECT $1, CURRENT_TIMESTAMP, to_jsonb(c) FROM live_trading_config c WHERE c.id = 1
+            "#
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.mark_write();
         Ok(LiveTradingConfig::from_row(&row)?)
     }
 
-    /// Disable trading
-    pub async fn disable_trading(&self, _reason: &str) -> Result<LiveTradingConfig, DbError> {
+    /// Disable trading, ending the open trading session with `reason`
+    pub async fn disable_trading(&self, reason: &str) -> Result<LiveTradingConfig, DbError> {
+        let mut tx = self.pool().begin().await?;
         let row = sqlx::query(
             r#"
             UPDATE live_trading_config
@@ -147,15 +250,38 @@ impl Database {
                 id, is_enabled, trade_amount, min_profit_threshold,
                 max_daily_loss, max_total_loss, start_currency, custom_currencies,
                 max_pairs, min_volume_24h_usd, max_cost_min,
+                sizing_tiers, max_safe_amount,
+                global_cooldown_ms, path_cooldown_ms, pair_failure_cooldown_ms,
+                leg_depth_levels, max_leg_slippage_bps, currency_reserves,
+                max_notional_per_hour, max_notional_per_day, execution_disabled_pairs, leg_profit_thresholds,
+                daily_reset_timezone,
                 created_at, updated_at, enabled_at, disabled_at
             "#
         )
-        .fetch_one(self.pool())
+        .fetch_one(&mut *tx)
         .await?;
 
+        Self::end_session(&mut tx, reason).await?;
+        tx.commit().await?;
+
+        self.mark_write();
         Ok(LiveTradingConfig::from_row(&row)?)
     }
 
+    async fn end_session(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, reason: &str) -> Result<(), DbError> {
+        sqlx::query(
+            r#"
+            UPDATE trading_sessions
+            SET ended_at = CURRENT_TIMESTAMP, end_reason = $1
+            WHERE ended_at IS NULL
+            "#
+        )
+        .bind(reason)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
     // ==========================================
     // State Operations
     // ==========================================
@@ -216,6 +342,7 @@ impl Database {
         .fetch_one(self.pool())
         .await?;
 
+        self.mark_write();
         Ok(LiveTradingState::from_row(&row)?)
     }
 
@@ -246,6 +373,7 @@ impl Database {
         .fetch_one(self.pool())
         .await?;
 
+        self.mark_write();
         Ok(LiveTradingState::from_row(&row)?)
     }
 
@@ -278,6 +406,7 @@ impl Database {
         .fetch_one(self.pool())
         .await?;
 
+        self.mark_write();
         Ok(LiveTradingState::from_row(&row)?)
     }
 
@@ -331,6 +460,8 @@ impl Database {
             .execute(self.pool())
             .await?;
         }
+
+        self.mark_write();
         Ok(())
     }
 
@@ -347,9 +478,15 @@ impl Database {
                 profit_loss, profit_loss_pct, status, current_leg,
                 error_message, held_currency, held_amount, held_value_usd,
                 order_ids, leg_fills, started_at, completed_at,
-                total_execution_ms, opportunity_profit_pct, created_at
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id, created_at
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, COALESCE($16, NOW()), $17, $18, $19, $20,
+                $21, $22, $23, $24, $25,
+                (SELECT session_id FROM trading_sessions WHERE ended_at IS NULL ORDER BY started_at DESC LIMIT 1),
+                NOW()
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, COALESCE($16, NOW()), $17, $18, $19, NOW())
             RETURNING
                 id, trade_id, path, legs, amount_in, amount_out,
                 profit_loss, profit_loss_pct, status, current_leg,
@@ -359,7 +496,9 @@ impl Database {
                 order_ids, leg_fills,
                 started_at AT TIME ZONE 'UTC' as started_at,
                 completed_at AT TIME ZONE 'UTC' as completed_at,
-                total_execution_ms, opportunity_profit_pct,
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at AT TIME ZONE 'UTC' as notes_updated_at,
                 created_at AT TIME ZONE 'UTC' as created_at
             "#
         )
@@ -382,9 +521,16 @@ impl Database {
         .bind(trade.completed_at)
         .bind(trade.total_execution_ms)
         .bind(trade.opportunity_profit_pct)
+        .bind(&trade.strategy)
+        .bind(&trade.tags)
+        .bind(&trade.pnl_currency)
+        .bind(trade.pnl_reporting)
+        .bind(&trade.reporting_currency)
+        .bind(trade.fx_rate)
         .fetch_one(self.pool())
         .await?;
 
+        self.mark_write();
         Ok(LiveTrade::from_row(&row)?)
     }
 
@@ -401,7 +547,9 @@ impl Database {
                 order_ids, leg_fills,
                 started_at AT TIME ZONE 'UTC' as started_at,
                 completed_at AT TIME ZONE 'UTC' as completed_at,
-                total_execution_ms, opportunity_profit_pct,
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at AT TIME ZONE 'UTC' as notes_updated_at,
                 created_at AT TIME ZONE 'UTC' as created_at
             FROM live_trades
             WHERE
@@ -424,8 +572,60 @@ impl Database {
         Ok(trades)
     }
 
+    /// Trades with id > `after_id` in a time window, oldest first (for export)
+    pub async fn get_trades_page(
+        &self,
+        after_id: i32,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<LiveTrade>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                id, trade_id, path, legs, amount_in, amount_out,
+                profit_loss, profit_loss_pct, status, current_leg,
+                error_message, held_currency, held_amount, held_value_usd,
+                resolved_at AT TIME ZONE 'UTC' as resolved_at,
+                resolved_amount_usd, resolution_trade_id,
+                order_ids, leg_fills,
+                started_at AT TIME ZONE 'UTC' as started_at,
+                completed_at AT TIME ZONE 'UTC' as completed_at,
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at AT TIME ZONE 'UTC' as notes_updated_at,
+                created_at AT TIME ZONE 'UTC' as created_at
+            FROM live_trades
+            WHERE
+                id > $1
+                AND ($2::timestamptz IS NULL OR created_at >= $2 AT TIME ZONE 'UTC')
+                AND ($3::timestamptz IS NULL OR created_at < $3 AT TIME ZONE 'UTC')
+            ORDER BY id ASC
+            LIMIT $4
+            "#
+        )
+        .bind(after_id)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        let mut trades = Vec::with_capacity(rows.len());
+        for row in rows {
+            trades.push(LiveTrade::from_row(&row)?);
+        }
+        Ok(trades)
+    }
+
     /// Get trades count for pagination
-    pub async fn get_trades_count(&self, status: Option<&str>, hours: i32) -> Result<i64, DbError> {
+    pub async fn get_trades_count(
+        &self,
+        status: Option<&str>,
+        hours: i32,
+        strategy: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<i64, DbError> {
         let row: (i64,) = sqlx::query_as(
             r#"
             SELECT COUNT(*)
@@ -433,18 +633,30 @@ impl Database {
             WHERE
                 ($1::text IS NULL OR status = $1)
                 AND (created_at IS NULL OR created_at > NOW() - make_interval(hours => $2))
+                AND ($3::text IS NULL OR strategy = $3)
+                AND ($4::text IS NULL OR $4 = ANY(tags))
             "#
         )
         .bind(status)
         .bind(hours)
+        .bind(strategy)
+        .bind(tag)
         .fetch_one(self.pool())
         .await?;
 
         Ok(row.0)
     }
 
-    /// Get trades with pagination (limit + offset)
-    pub async fn get_trades_paginated(&self, limit: i64, offset: i64, status: Option<&str>, hours: i32) -> Result<Vec<LiveTrade>, DbError> {
+    /// Get trades with pagination (limit + offset), optionally filtered by strategy or tag
+    pub async fn get_trades_paginated(
+        &self,
+        limit: i64,
+        offset: i64,
+        status: Option<&str>,
+        hours: i32,
+        strategy: Option<&str>,
+        tag: Option<&str>,
+    ) -> Result<Vec<LiveTrade>, DbError> {
         let rows = sqlx::query(
             r#"
             SELECT
@@ -456,12 +668,16 @@ impl Database {
                 order_ids, leg_fills,
                 started_at AT TIME ZONE 'UTC' as started_at,
                 completed_at AT TIME ZONE 'UTC' as completed_at,
-                total_execution_ms, opportunity_profit_pct,
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at AT TIME ZONE 'UTC' as notes_updated_at,
                 created_at AT TIME ZONE 'UTC' as created_at
             FROM live_trades
             WHERE
                 ($1::text IS NULL OR status = $1)
                 AND (created_at IS NULL OR created_at > NOW() - make_interval(hours => $2))
+                AND ($5::text IS NULL OR strategy = $5)
+                AND ($6::text IS NULL OR $6 = ANY(tags))
             ORDER BY id DESC
             LIMIT $3 OFFSET $4
             "#
@@ -470,6 +686,8 @@ impl Database {
         .bind(hours)
         .bind(limit)
         .bind(offset)
+        .bind(strategy)
+        .bind(tag)
         .fetch_all(self.pool())
         .await?;
 
@@ -490,7 +708,9 @@ impl Database {
                 error_message, held_currency, held_amount, held_value_usd,
                 resolved_at, resolved_amount_usd, resolution_trade_id,
                 order_ids, leg_fills, started_at, completed_at,
-                total_execution_ms, opportunity_profit_pct, created_at
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at, created_at
             FROM live_trades
             WHERE trade_id = $1
             "#
@@ -526,7 +746,9 @@ impl Database {
                 error_message, held_currency, held_amount, held_value_usd,
                 resolved_at, resolved_amount_usd, resolution_trade_id,
                 order_ids, leg_fills, started_at, completed_at,
-                total_execution_ms, opportunity_profit_pct, created_at
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at, created_at
             "#
         )
         .bind(trade_id)
@@ -535,6 +757,7 @@ impl Database {
         .fetch_one(self.pool())
         .await?;
 
+        self.mark_write();
         Ok(LiveTrade::from_row(&row)?)
     }
 
@@ -571,7 +794,9 @@ impl Database {
                 error_message, held_currency, held_amount, held_value_usd,
                 resolved_at, resolved_amount_usd, resolution_trade_id,
                 order_ids, leg_fills, started_at, completed_at,
-                total_execution_ms, opportunity_profit_pct, created_at
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at, created_at
             "#
         )
         .bind(trade_id)
@@ -608,9 +833,47 @@ impl Database {
         .execute(self.pool())
         .await?;
 
+        self.mark_write();
         Ok(LiveTrade::from_row(&row)?)
     }
 
+    /// Set a trade's operator notes and labels (None leaves that field as
+    /// it is, an empty note clears it). Returns None for an unknown trade.
+    pub async fn set_trade_notes(
+        &self,
+        trade_id: &str,
+        notes: Option<&str>,
+        labels: Option<&[String]>,
+    ) -> Result<Option<LiveTrade>, DbError> {
+        let row = sqlx::query(
+            r#"
+            UPDATE live_trades
+            SET
+                notes = CASE WHEN $2::text IS NULL THEN notes ELSE NULLIF($2, '') END,
+                labels = COALESCE($3, labels),
+                notes_updated_at = CURRENT_TIMESTAMP
+            WHERE trade_id = $1
+            RETURNING
+                id, trade_id, path, legs, amount_in, amount_out,
+                profit_loss, profit_loss_pct, status, current_leg,
+                error_message, held_currency, held_amount, held_value_usd,
+                resolved_at, resolved_amount_usd, resolution_trade_id,
+                order_ids, leg_fills, started_at, completed_at,
+                total_execution_ms, opportunity_profit_pct, strategy, tags,
+                pnl_currency, pnl_reporting, reporting_currency, fx_rate, session_id,
+                notes, labels, notes_updated_at, created_at
+            "#
+        )
+        .bind(trade_id)
+        .bind(notes)
+        .bind(labels)
+        .fetch_optional(self.pool())
+        .await?;
+
+        self.mark_write();
+        Ok(row.map(|row| LiveTrade::from_row(&row)).transpose()?)
+    }
+
     // ==========================================
     // Opportunity Operations
     // ==========================================
@@ -627,7 +890,7 @@ impl Database {
             RETURNING 
                 id, found_at, path, legs, expected_profit_pct, expected_profit_usd,
                 trade_amount, status, status_reason, trade_id, pairs_scanned, paths_found,
-                created_at, updated_at
+                notes, labels, notes_updated_at, created_at, updated_at
             "#
         )
         .bind(&opp.path)
@@ -652,7 +915,7 @@ impl Database {
             SELECT 
                 id, found_at, path, legs, expected_profit_pct, expected_profit_usd,
                 trade_amount, status, status_reason, trade_id, pairs_scanned, paths_found,
-                created_at, updated_at
+                notes, labels, notes_updated_at, created_at, updated_at
             FROM live_opportunities
             WHERE 
                 ($1::text IS NULL OR status = $1)
@@ -674,6 +937,43 @@ impl Database {
         Ok(opportunities)
     }
 
+    /// Opportunities with id > `after_id` in a time window, oldest first (for export)
+    pub async fn get_opportunities_page(
+        &self,
+        after_id: i32,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<LiveOpportunity>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                id, found_at, path, legs, expected_profit_pct, expected_profit_usd,
+                trade_amount, status, status_reason, trade_id, pairs_scanned, paths_found,
+                notes, labels, notes_updated_at, created_at, updated_at
+            FROM live_opportunities
+            WHERE
+                id > $1
+                AND ($2::timestamptz IS NULL OR found_at >= $2 AT TIME ZONE 'UTC')
+                AND ($3::timestamptz IS NULL OR found_at < $3 AT TIME ZONE 'UTC')
+            ORDER BY id ASC
+            LIMIT $4
+            "#
+        )
+        .bind(after_id)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        let mut opportunities = Vec::with_capacity(rows.len());
+        for row in rows {
+            opportunities.push(LiveOpportunity::from_row(&row)?);
+        }
+        Ok(opportunities)
+    }
+
     /// Update opportunity status (e.g., when executed)
     pub async fn update_opportunity_status(
         &self,
@@ -703,18 +1003,35 @@ impl Database {
         Ok(())
     }
 
-    /// Clean old opportunities (keep last 7 days)
-    pub async fn clean_old_opportunities(&self) -> Result<u64, DbError> {
-        let result = sqlx::query(
+    /// Set an opportunity's operator notes and labels, like `set_trade_notes`
+    pub async fn set_opportunity_notes(
+        &self,
+        opp_id: i32,
+        notes: Option<&str>,
+        labels: Option<&[String]>,
+    ) -> Result<Option<LiveOpportunity>, DbError> {
+        let row = sqlx::query(
             r#"
-            DELETE FROM live_opportunities
-            WHERE found_at < NOW() - INTERVAL '7 days'
+            UPDATE live_opportunities
+            SET
+                notes = CASE WHEN $2::text IS NULL THEN notes ELSE NULLIF($2, '') END,
+                labels = COALESCE($3, labels),
+                notes_updated_at = CURRENT_TIMESTAMP,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            RETURNING
+                id, found_at, path, legs, expected_profit_pct, expected_profit_usd,
+                trade_amount, status, status_reason, trade_id, pairs_scanned, paths_found,
+                notes, labels, notes_updated_at, created_at, updated_at
             "#
         )
-        .execute(self.pool())
+        .bind(opp_id)
+        .bind(notes)
+        .bind(labels)
+        .fetch_optional(self.pool())
         .await?;
 
-        Ok(result.rows_affected())
+        Ok(row.map(|row| LiveOpportunity::from_row(&row)).transpose()?)
     }
 
     // ==========================================
@@ -809,4 +1126,341 @@ impl Database {
         let fee_config = self.get_fee_configuration().await?;
         Ok(fee_config.fee_source != "pending")
     }
-}
\ No newline at end of file
+
+    /// Fee changes, newest first. The table is filled by a trigger on
+    /// fee_configuration, so every writer is covered.
+    pub async fn get_fee_history(&self, limit: i64) -> Result<Vec<FeeConfigChange>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                id, changed_at, fee_source, old_maker_fee, old_taker_fee,
+                new_maker_fee, new_taker_fee, volume_tier, thirty_day_volume
+            FROM fee_configuration_history
+            ORDER BY changed_at DESC, id DESC
+            LIMIT $1
+            "#
+        )
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        let mut changes = Vec::new();
+        for row in rows {
+            changes.push(FeeConfigChange::from_row(&row)?);
+        }
+        Ok(changes)
+    }
+
+    // ==========================================
+    // Audit Log Operations
+    // ==========================================
+
+    /// Append an audit entry (the table rejects UPDATE/DELETE).
+    /// Does not bump the write generation - no cached response reads it.
+    pub async fn insert_audit_entry(&self, entry: &NewAuditEntry) -> Result<(), DbError> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (occurred_at, actor, category, action, details)
+            VALUES ($1, $2, $3, $4, $5)
+            "#
+        )
+        .bind(entry.occurred_at)
+        .bind(&entry.actor)
+        .bind(&entry.category)
+        .bind(&entry.action)
+        .bind(&entry.details)
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get audit entries, newest first
+    pub async fn get_audit_entries(
+        &self,
+        limit: i64,
+        offset: i64,
+        category: Option<&str>,
+        actor: Option<&str>,
+        action: Option<&str>,
+        hours: i32,
+    ) -> Result<Vec<AuditEntry>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                id, occurred_at AT TIME ZONE 'UTC' as occurred_at,
+                actor, category, action, details
+            FROM audit_log
+            WHERE
+                ($3::text IS NULL OR category = $3)
+                AND ($4::text IS NULL OR actor = $4)
+                AND ($6::text IS NULL OR action = $6)
+                AND occurred_at > NOW() - make_interval(hours => $5)
+            ORDER BY id DESC
+            LIMIT $1 OFFSET $2
+            "#
+        )
+        .bind(limit)
+        .bind(offset)
+        .bind(category)
+        .bind(actor)
+        .bind(hours)
+        .bind(action)
+        .fetch_all(self.pool())
+        .await?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(AuditEntry::from_row(&row)?);
+        }
+        Ok(entries)
+    }
+
+    // ==========================================
+    // Fill Journal Operations
+    // ==========================================
+
+    /// Append a fill journal event
+    pub async fn insert_order_fill(&self, fill: &OrderFill) -> Result<(), DbError> {
+        sqlx::query(
+            r#"
+            INSERT INTO order_fills (
+                received_at, order_id, cl_ord_id, exec_id, exec_type, order_status, symbol, side,
+                last_qty, last_price, cum_qty, cum_cost, avg_price, fee_usd, matched, duplicate
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            "#
+        )
+        .bind(fill.received_at)
+        .bind(&fill.order_id)
+        .bind(&fill.cl_ord_id)
+        .bind(&fill.exec_id)
+        .bind(&fill.exec_type)
+        .bind(&fill.order_status)
+        .bind(&fill.symbol)
+        .bind(&fill.side)
+        .bind(fill.last_qty)
+        .bind(fill.last_price)
+        .bind(fill.cum_qty)
+        .bind(fill.cum_cost)
+        .bind(fill.avg_price)
+        .bind(fill.fee_usd)
+        .bind(fill.matched)
+        .bind(fill.duplicate)
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Journaled events for one order, oldest first
+    pub async fn get_order_fills(&self, order_id: &str) -> Result<Vec<OrderFill>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                received_at AT TIME ZONE 'UTC' as received_at,
+                order_id, cl_ord_id, exec_id, exec_type, order_status, symbol, side,
+                last_qty, last_price, cum_qty, cum_cost, avg_price, fee_usd, matched, duplicate
+            FROM order_fills
+            WHERE order_id = $1 OR cl_ord_id = $1
+            ORDER BY id
+            "#
+        )
+        .bind(order_id)
+        .fetch_all(self.pool())
+        .await?;
+
+        let mut fills = Vec::new();
+        for row in rows {
+            fills.push(OrderFill::from_row(&row)?);
+        }
+        Ok(fills)
+    }
+
+    /// Append a stats history sample
+    pub async fn insert_stats_sample(&self, sample: &StatsSample) -> Result<(), DbError> {
+        sqlx::query(
+            r#"
+            INSERT INTO stats_history (
+                sampled_at, is_running, pairs_monitored, cycles_completed, events_received,
+                opportunities_found, paths_filtered_liquidity, trades_executed, trades_successful, trades_failed,
+                total_profit, total_loss, daily_profit, daily_loss,
+                orders_sent, orders_filled, orders_failed, ws_wire_bytes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            "#
+        )
+        .bind(sample.sampled_at)
+        .bind(sample.is_running)
+        .bind(sample.pairs_monitored)
+        .bind(sample.cycles_completed)
+        .bind(sample.events_received)
+        .bind(sample.opportunities_found)
+        .bind(sample.paths_filtered_liquidity)
+        .bind(sample.trades_executed)
+        .bind(sample.trades_successful)
+        .bind(sample.trades_failed)
+        .bind(sample.total_profit)
+        .bind(sample.total_loss)
+        .bind(sample.daily_profit)
+        .bind(sample.daily_loss)
+        .bind(sample.orders_sent)
+        .bind(sample.orders_filled)
+        .bind(sample.orders_failed)
+        .bind(sample.ws_wire_bytes)
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stats history samples taken at or after `since`, oldest first
+    pub async fn get_stats_samples(&self, since: DateTime<Utc>) -> Result<Vec<StatsSample>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                sampled_at AT TIME ZONE 'UTC' as sampled_at,
+                is_running, pairs_monitored, cycles_completed, events_received,
+                opportunities_found, paths_filtered_liquidity, trades_executed, trades_successful, trades_failed,
+                total_profit, total_loss, daily_profit, daily_loss,
+                orders_sent, orders_filled, orders_failed, ws_wire_bytes
+            FROM stats_history
+            WHERE sampled_at >= $1
+            ORDER BY sampled_at
+            "#
+        )
+        .bind(since)
+        .fetch_all(self.pool())
+        .await?;
+
+        let mut samples = Vec::new();
+        for row in rows {
+            samples.push(StatsSample::from_row(&row)?);
+        }
+        Ok(samples)
+    }
+
+    // ==========================================
+    // Session Operations
+    // ==========================================
+
+    /// The latest `limit` trading sessions with their trades and breaker
+    /// events, newest first
+    pub async fn get_session_summaries(&self, limit: i64) -> Result<Vec<SessionSummary>, DbError> {
+        // Audit entries are written in the background: one recorded as the
+        // session ended can land just after ended_at
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                s.session_id,
+                s.started_at AT TIME ZONE 'UTC' as started_at,
+                s.ended_at AT TIME ZONE 'UTC' as ended_at,
+                s.end_reason,
+                EXTRACT(EPOCH FROM (COALESCE(s.ended_at, CURRENT_TIMESTAMP) - s.started_at))::float8 as duration_secs,
+                s.config,
+                COUNT(t.id) as trades,
+                COUNT(t.id) FILTER (WHERE t.status = 'COMPLETED') as trades_completed,
+                COUNT(t.id) FILTER (WHERE t.status = 'FAILED') as trades_failed,
+                COUNT(t.id) FILTER (WHERE t.status IN ('PARTIAL', 'RESOLVED')) as trades_partial,
+                COUNT(t.id) FILTER (WHERE t.profit_loss > 0) as wins,
+                COALESCE(SUM(COALESCE(t.pnl_reporting, t.profit_loss)), 0)::float8 as pnl,
+                COALESCE(SUM(t.amount_in), 0)::float8 as volume,
+                (
+                    SELECT COUNT(*) FROM audit_log a
+                    WHERE a.category = 'breaker'
+                        AND a.action IN ('circuit_breaker_tripped', 'dead_man_tripped', 'quick_disable')
+                        AND a.occurred_at >= s.started_at
+                        AND a.occurred_at <= COALESCE(s.ended_at, CURRENT_TIMESTAMP) + INTERVAL '5 seconds'
+                ) as breaker_trips,
+                (
+                    SELECT COUNT(*) FROM audit_log a
+                    WHERE a.category = 'breaker'
+                        AND a.action = 'circuit_breaker_reset'
+                        AND a.occurred_at >= s.started_at
+                        AND a.occurred_at <= COALESCE(s.ended_at, CURRENT_TIMESTAMP) + INTERVAL '5 seconds'
+                ) as breaker_resets
+            FROM trading_sessions s
+            LEFT JOIN live_trades t ON t.session_id = s.session_id
+            GROUP BY s.id
+            ORDER BY s.started_at DESC
+            LIMIT $1
+            "#
+        )
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(SessionSummary::from_row(&row)?);
+        }
+        Ok(sessions)
+    }
+
+    // ==========================================
+    // Retention Operations
+    // ==========================================
+
+    /// Up to `limit` rows of `table` past `days` of retention with an id
+    /// above `after_id`, as (id, row as JSON) in id order
+    pub async fn get_expired_rows(
+        &self,
+        table: RetentionTable,
+        days: u32,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<(i64, String)>, DbError> {
+        let sql = format!(
+            "SELECT id::bigint AS id, row_to_json(t)::text AS row FROM {} t WHERE {} AND id > $2 ORDER BY id LIMIT $3",
+            table.table_name(),
+            table.expired_filter()
+        );
+        let rows = sqlx::query(&sql)
+            .bind(days as i32)
+            .bind(after_id)
+            .bind(limit)
+            .fetch_all(self.pool())
+            .await?;
+
+        let mut expired = Vec::with_capacity(rows.len());
+        for row in rows {
+            expired.push((row.try_get("id")?, row.try_get("row")?));
+        }
+        Ok(expired)
+    }
+
+    /// Delete up to `limit` rows of `table` past `days` of retention, only
+    /// among `ids` when given (rows annotated since they were read stay)
+    pub async fn delete_expired_rows(
+        &self,
+        table: RetentionTable,
+        days: u32,
+        ids: Option<&[i64]>,
+        limit: i64,
+    ) -> Result<u64, DbError> {
+        let sql = format!(
+            "DELETE FROM {table} WHERE id IN (
+                SELECT id FROM {table} WHERE {filter} AND ($2::bigint[] IS NULL OR id = ANY($2)) ORDER BY id LIMIT $3
+            )",
+            table = table.table_name(),
+            filter = table.expired_filter()
+        );
+        let mut tx = self.pool().begin().await?;
+        if table == RetentionTable::Audit {
+            // The append-only trigger lets the retention job through
+            sqlx::query("SET LOCAL audit_log.retention = 'on'").execute(&mut *tx).await?;
+        }
+        let result = sqlx::query(&sql)
+            .bind(days as i32)
+            .bind(ids)
+            .bind(limit)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        if table == RetentionTable::Trades && result.rows_affected() > 0 {
+            self.mark_write();
+        }
+        Ok(result.rows_affected())
+    }
+}
@@ -22,6 +22,29 @@ pub struct LiveTradingConfig {
     pub max_pairs: Option<i32>,
     pub min_volume_24h_usd: Option<f64>,
     pub max_cost_min: Option<f64>,
+    // Profit-tiered sizing (optional - falls back to trade_amount)
+    /// JSON array of {"min_profit_pct": 0.2, "amount": 50.0}
+    pub sizing_tiers: Option<serde_json::Value>,
+    /// Hard cap on any auto-execution amount
+    pub max_safe_amount: Option<f64>,
+    // Cooldowns in ms (optional - NULL or 0 = off)
+    pub global_cooldown_ms: Option<i32>,
+    pub path_cooldown_ms: Option<i32>,
+    pub pair_failure_cooldown_ms: Option<i32>,
+    // Per-leg liquidity filter (optional - both NULL disables it)
+    pub leg_depth_levels: Option<i32>,
+    pub max_leg_slippage_bps: Option<f64>,
+    // Per-currency reserves kept untouched (JSON object {currency: amount})
+    pub currency_reserves: Option<serde_json::Value>,
+    // USD notional filled across all legs, rolling windows (NULL = no limit)
+    pub max_notional_per_hour: Option<f64>,
+    pub max_notional_per_day: Option<f64>,
+    // Pairs scanned but never executed (JSON array ["BASE/QUOTE", ...])
+    pub execution_disabled_pairs: Option<serde_json::Value>,
+    // Min net profit by path length (JSON object {"<legs>": threshold})
+    pub leg_profit_thresholds: Option<serde_json::Value>,
+    // IANA timezone whose midnight rolls the daily counters (NULL = UTC)
+    pub daily_reset_timezone: Option<String>,
     // Timestamps
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -46,6 +69,19 @@ impl Default for LiveTradingConfig {
             max_pairs: None,
             min_volume_24h_usd: None,
             max_cost_min: None,
+            sizing_tiers: None,
+            max_safe_amount: None,
+            global_cooldown_ms: None,
+            path_cooldown_ms: None,
+            pair_failure_cooldown_ms: None,
+            leg_depth_levels: None,
+            max_leg_slippage_bps: None,
+            currency_reserves: None,
+            max_notional_per_hour: None,
+            max_notional_per_day: None,
+            execution_disabled_pairs: None,
+            leg_profit_thresholds: None,
+            daily_reset_timezone: None,
             created_at: None,
             updated_at: None,
             enabled_at: None,
@@ -68,6 +104,19 @@ impl<'r> FromRow<'r, PgRow> for LiveTradingConfig {
             max_pairs: row.try_get("max_pairs").ok(),
             min_volume_24h_usd: row.try_get("min_volume_24h_usd").ok(),
             max_cost_min: row.try_get("max_cost_min").ok(),
+            sizing_tiers: row.try_get("sizing_tiers").ok(),
+            max_safe_amount: row.try_get("max_safe_amount").ok(),
+            global_cooldown_ms: row.try_get("global_cooldown_ms").ok(),
+            path_cooldown_ms: row.try_get("path_cooldown_ms").ok(),
+            pair_failure_cooldown_ms: row.try_get("pair_failure_cooldown_ms").ok(),
+            leg_depth_levels: row.try_get("leg_depth_levels").ok(),
+            max_leg_slippage_bps: row.try_get("max_leg_slippage_bps").ok(),
+            currency_reserves: row.try_get("currency_reserves").ok(),
+            max_notional_per_hour: row.try_get("max_notional_per_hour").ok(),
+            max_notional_per_day: row.try_get("max_notional_per_day").ok(),
+            execution_disabled_pairs: row.try_get("execution_disabled_pairs").ok(),
+            leg_profit_thresholds: row.try_get("leg_profit_thresholds").ok(),
+            daily_reset_timezone: row.try_get("daily_reset_timezone").ok(),
             created_at: row.try_get("created_at").ok(),
             updated_at: row.try_get("updated_at").ok(),
             enabled_at: row.try_get("enabled_at").ok(),
@@ -77,7 +126,7 @@ impl<'r> FromRow<'r, PgRow> for LiveTradingConfig {
 }
 
 /// Config update request (all fields optional)
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConfigUpdate {
     pub trade_amount: Option<f64>,
     pub min_profit_threshold: Option<f64>,
@@ -91,6 +140,27 @@ pub struct ConfigUpdate {
     pub max_pairs: Option<i32>,
     pub min_volume_24h_usd: Option<f64>,
     pub max_cost_min: Option<f64>,
+    // Profit-tiered sizing
+    pub sizing_tiers: Option<serde_json::Value>,
+    pub max_safe_amount: Option<f64>,
+    // Cooldowns (ms)
+    pub global_cooldown_ms: Option<i32>,
+    pub path_cooldown_ms: Option<i32>,
+    pub pair_failure_cooldown_ms: Option<i32>,
+    // Per-leg liquidity filter
+    pub leg_depth_levels: Option<i32>,
+    pub max_leg_slippage_bps: Option<f64>,
+    // Per-currency reserves
+    pub currency_reserves: Option<serde_json::Value>,
+    // Traded notional limits (USD)
+    pub max_notional_per_hour: Option<f64>,
+    pub max_notional_per_day: Option<f64>,
+    // Scan-only pairs
+    pub execution_disabled_pairs: Option<serde_json::Value>,
+    // Thresholds per path length
+    pub leg_profit_thresholds: Option<serde_json::Value>,
+    // Daily counter rollover timezone (e.g. "America/New_York")
+    pub daily_reset_timezone: Option<String>,
 }
 
 /// Live trading state (circuit breaker, stats)
@@ -190,8 +260,16 @@ pub struct LiveTrade {
     pub legs: i32,
     pub amount_in: f64,
     pub amount_out: Option<f64>,
+    /// In the trade's start currency
     pub profit_loss: Option<f64>,
     pub profit_loss_pct: Option<f64>,
+    /// Currency profit_loss is in
+    pub pnl_currency: Option<String>,
+    /// profit_loss in reporting_currency at the completion rate
+    pub pnl_reporting: Option<f64>,
+    pub reporting_currency: Option<String>,
+    /// Reporting currency per unit of pnl_currency when the trade completed
+    pub fx_rate: Option<f64>,
     pub status: String,
     pub current_leg: Option<i32>,
     pub error_message: Option<String>,
@@ -207,6 +285,15 @@ pub struct LiveTrade {
     pub completed_at: Option<DateTime<Utc>>,
     pub total_execution_ms: Option<f64>,
     pub opportunity_profit_pct: Option<f64>,
+    /// Detection strategy that produced the trade (triangular, cross_pair, manual, stablecoin)
+    pub strategy: Option<String>,
+    pub tags: Vec<String>,
+    /// Trading session open when the trade was saved
+    pub session_id: Option<String>,
+    /// Operator annotations, e.g. "Kraken outage"
+    pub notes: Option<String>,
+    pub labels: Vec<String>,
+    pub notes_updated_at: Option<DateTime<Utc>>,
     pub created_at: Option<DateTime<Utc>>,
 }
 
@@ -221,6 +308,10 @@ impl<'r> FromRow<'r, PgRow> for LiveTrade {
             amount_out: row.try_get("amount_out").ok(),
             profit_loss: row.try_get("profit_loss").ok(),
             profit_loss_pct: row.try_get("profit_loss_pct").ok(),
+            pnl_currency: row.try_get("pnl_currency").ok(),
+            pnl_reporting: row.try_get("pnl_reporting").ok(),
+            reporting_currency: row.try_get("reporting_currency").ok(),
+            fx_rate: row.try_get("fx_rate").ok(),
             status: row.try_get("status")?,
             current_leg: row.try_get("current_leg").ok(),
             error_message: row.try_get("error_message").ok(),
@@ -236,6 +327,12 @@ impl<'r> FromRow<'r, PgRow> for LiveTrade {
             completed_at: row.try_get("completed_at").ok(),
             total_execution_ms: row.try_get("total_execution_ms").ok(),
             opportunity_profit_pct: row.try_get("opportunity_profit_pct").ok(),
+            strategy: row.try_get("strategy").ok(),
+            tags: row.try_get("tags").unwrap_or_default(),
+            session_id: row.try_get("session_id").ok(),
+            notes: row.try_get("notes").ok(),
+            labels: row.try_get("labels").unwrap_or_default(),
+            notes_updated_at: row.try_get("notes_updated_at").ok(),
             created_at: row.try_get("created_at").ok(),
         })
     }
@@ -249,8 +346,16 @@ pub struct NewLiveTrade {
     pub legs: i32,
     pub amount_in: f64,
     pub amount_out: Option<f64>,
+    /// In the trade's start currency
     pub profit_loss: Option<f64>,
     pub profit_loss_pct: Option<f64>,
+    /// Currency profit_loss is in
+    pub pnl_currency: Option<String>,
+    /// profit_loss in reporting_currency at the completion rate
+    pub pnl_reporting: Option<f64>,
+    pub reporting_currency: Option<String>,
+    /// Reporting currency per unit of pnl_currency when the trade completed
+    pub fx_rate: Option<f64>,
     pub status: String,
     pub current_leg: Option<i32>,
     pub error_message: Option<String>,
@@ -263,6 +368,8 @@ pub struct NewLiveTrade {
     pub completed_at: Option<DateTime<Utc>>,
     pub total_execution_ms: Option<f64>,
     pub opportunity_profit_pct: Option<f64>,
+    pub strategy: Option<String>,
+    pub tags: Vec<String>,
 }
 
 /// Live opportunity record (saved to database)
@@ -280,6 +387,10 @@ pub struct LiveOpportunity {
     pub trade_id: Option<String>,
     pub pairs_scanned: Option<i32>,
     pub paths_found: Option<i32>,
+    /// Operator annotations
+    pub notes: Option<String>,
+    pub labels: Vec<String>,
+    pub notes_updated_at: Option<DateTime<Utc>>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -299,6 +410,9 @@ impl<'r> FromRow<'r, PgRow> for LiveOpportunity {
             trade_id: row.try_get("trade_id").ok(),
             pairs_scanned: row.try_get("pairs_scanned").ok(),
             paths_found: row.try_get("paths_found").ok(),
+            notes: row.try_get("notes").ok(),
+            labels: row.try_get("labels").unwrap_or_default(),
+            notes_updated_at: row.try_get("notes_updated_at").ok(),
             created_at: row.try_get("created_at").ok(),
             updated_at: row.try_get("updated_at").ok(),
         })
@@ -367,6 +481,38 @@ impl<'r> FromRow<'r, PgRow> for FeeConfiguration {
     }
 }
 
+/// One recorded change to the fee configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeConfigChange {
+    pub id: i64,
+    pub changed_at: DateTime<Utc>,
+    /// Source of the new values ("kraken_api" or "manual")
+    pub fee_source: String,
+    /// None for the first configuration
+    pub old_maker_fee: Option<f64>,
+    pub old_taker_fee: Option<f64>,
+    pub new_maker_fee: f64,
+    pub new_taker_fee: f64,
+    pub volume_tier: Option<String>,
+    pub thirty_day_volume: Option<f64>,
+}
+
+impl<'r> FromRow<'r, PgRow> for FeeConfigChange {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            changed_at: row.try_get("changed_at")?,
+            fee_source: row.try_get("fee_source")?,
+            old_maker_fee: row.try_get("old_maker_fee")?,
+            old_taker_fee: row.try_get("old_taker_fee")?,
+            new_maker_fee: row.try_get("new_maker_fee")?,
+            new_taker_fee: row.try_get("new_taker_fee")?,
+            volume_tier: row.try_get("volume_tier")?,
+            thirty_day_volume: row.try_get("thirty_day_volume")?,
+        })
+    }
+}
+
 /// Fee configuration update request
 #[derive(Debug, Clone, Deserialize)]
 pub struct FeeConfigurationUpdate {
@@ -375,4 +521,232 @@ pub struct FeeConfigurationUpdate {
     pub fee_source: Option<String>,
     pub volume_tier: Option<String>,
     pub thirty_day_volume: Option<f64>,
-}
\ No newline at end of file
+}
+
+/// Append-only audit log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub occurred_at: Option<DateTime<Utc>>,
+    /// Who caused it: auto, api, system
+    pub actor: String,
+    /// order, guard, config, breaker
+    pub category: String,
+    pub action: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl<'r> FromRow<'r, PgRow> for AuditEntry {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            occurred_at: row.try_get("occurred_at").ok(),
+            actor: row.try_get("actor")?,
+            category: row.try_get("category")?,
+            action: row.try_get("action")?,
+            details: row.try_get("details").ok(),
+        })
+    }
+}
+
+/// New audit entry to append
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub occurred_at: DateTime<Utc>,
+    pub actor: String,
+    pub category: String,
+    pub action: String,
+    pub details: serde_json::Value,
+}
+
+/// One executions-channel message for an order (fill journal)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub received_at: DateTime<Utc>,
+    pub order_id: String,
+    pub cl_ord_id: Option<String>,
+    pub exec_id: Option<String>,
+    pub exec_type: String,
+    pub order_status: String,
+    pub symbol: Option<String>,
+    pub side: Option<String>,
+    pub last_qty: f64,
+    pub last_price: f64,
+    pub cum_qty: f64,
+    pub cum_cost: f64,
+    pub avg_price: f64,
+    pub fee_usd: f64,
+    /// A pending order was waiting for this message
+    pub matched: bool,
+    /// Same execution already journaled (redelivered)
+    pub duplicate: bool,
+}
+
+impl<'r> FromRow<'r, PgRow> for OrderFill {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            received_at: row.try_get("received_at")?,
+            order_id: row.try_get("order_id")?,
+            cl_ord_id: row.try_get("cl_ord_id").ok(),
+            exec_id: row.try_get("exec_id").ok(),
+            exec_type: row.try_get("exec_type")?,
+            order_status: row.try_get("order_status")?,
+            symbol: row.try_get("symbol").ok(),
+            side: row.try_get("side").ok(),
+            last_qty: row.try_get("last_qty")?,
+            last_price: row.try_get("last_price")?,
+            cum_qty: row.try_get("cum_qty")?,
+            cum_cost: row.try_get("cum_cost")?,
+            avg_price: row.try_get("avg_price")?,
+            fee_usd: row.try_get("fee_usd")?,
+            matched: row.try_get("matched")?,
+            duplicate: row.try_get("duplicate")?,
+        })
+    }
+}
+
+/// A trading session (enable to disable) and what was traded in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    /// None while the session is open
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Why trading was disabled
+    pub end_reason: Option<String>,
+    /// Up to now for an open session
+    pub duration_secs: f64,
+    /// live_trading_config as it was when the session started
+    pub config: Option<serde_json::Value>,
+    pub trades: i64,
+    pub trades_completed: i64,
+    pub trades_failed: i64,
+    /// Partial trades, resolved or not
+    pub trades_partial: i64,
+    pub wins: i64,
+    /// In the reporting currency (profit_loss where a trade has no conversion)
+    pub pnl: f64,
+    /// Sum of the trades' amount_in
+    pub volume: f64,
+    /// Breaker trips (manual, loss limit, dead man's switch, quick disable)
+    pub breaker_trips: i64,
+    pub breaker_resets: i64,
+}
+
+impl<'r> FromRow<'r, PgRow> for SessionSummary {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            session_id: row.try_get("session_id")?,
+            started_at: row.try_get("started_at")?,
+            ended_at: row.try_get("ended_at").ok(),
+            end_reason: row.try_get("end_reason").ok(),
+            duration_secs: row.try_get("duration_secs")?,
+            config: row.try_get("config").ok(),
+            trades: row.try_get("trades")?,
+            trades_completed: row.try_get("trades_completed")?,
+            trades_failed: row.try_get("trades_failed")?,
+            trades_partial: row.try_get("trades_partial")?,
+            wins: row.try_get("wins")?,
+            pnl: row.try_get("pnl")?,
+            volume: row.try_get("volume")?,
+            breaker_trips: row.try_get("breaker_trips")?,
+            breaker_resets: row.try_get("breaker_resets")?,
+        })
+    }
+}
+
+/// One periodic sample of engine, scanner and executor counters (stats history)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsSample {
+    pub sampled_at: DateTime<Utc>,
+    pub is_running: bool,
+    pub pairs_monitored: i64,
+    pub cycles_completed: i64,
+    pub events_received: i64,
+    pub opportunities_found: i64,
+    pub paths_filtered_liquidity: i64,
+    pub trades_executed: i64,
+    pub trades_successful: i64,
+    pub trades_failed: i64,
+    pub total_profit: f64,
+    pub total_loss: f64,
+    pub daily_profit: f64,
+    pub daily_loss: f64,
+    pub orders_sent: i64,
+    pub orders_filled: i64,
+    pub orders_failed: i64,
+    pub ws_wire_bytes: i64,
+}
+
+impl<'r> FromRow<'r, PgRow> for StatsSample {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            sampled_at: row.try_get("sampled_at")?,
+            is_running: row.try_get("is_running")?,
+            pairs_monitored: row.try_get("pairs_monitored")?,
+            cycles_completed: row.try_get("cycles_completed")?,
+            events_received: row.try_get("events_received")?,
+            opportunities_found: row.try_get("opportunities_found")?,
+            paths_filtered_liquidity: row.try_get("paths_filtered_liquidity")?,
+            trades_executed: row.try_get("trades_executed")?,
+            trades_successful: row.try_get("trades_successful")?,
+            trades_failed: row.try_get("trades_failed")?,
+            total_profit: row.try_get("total_profit")?,
+            total_loss: row.try_get("total_loss")?,
+            daily_profit: row.try_get("daily_profit")?,
+            daily_loss: row.try_get("daily_loss")?,
+            orders_sent: row.try_get("orders_sent")?,
+            orders_filled: row.try_get("orders_filled")?,
+            orders_failed: row.try_get("orders_failed")?,
+            ws_wire_bytes: row.try_get("ws_wire_bytes")?,
+        })
+    }
+}
+
+/// A table the retention job cleans up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionTable {
+    Opportunities,
+    Trades,
+    Fills,
+    Stats,
+    Audit,
+}
+
+impl RetentionTable {
+    pub const ALL: [RetentionTable; 5] = [
+        RetentionTable::Opportunities,
+        RetentionTable::Trades,
+        RetentionTable::Fills,
+        RetentionTable::Stats,
+        RetentionTable::Audit,
+    ];
+
+    pub fn table_name(self) -> &'static str {
+        match self {
+            RetentionTable::Opportunities => "live_opportunities",
+            RetentionTable::Trades => "live_trades",
+            RetentionTable::Fills => "order_fills",
+            RetentionTable::Stats => "stats_history",
+            RetentionTable::Audit => "audit_log",
+        }
+    }
+
+    /// Rows past retention (`$1` is the retention in days). Annotated rows
+    /// are kept, and so are trades that haven't reached a final status.
+    pub(crate) fn expired_filter(self) -> &'static str {
+        match self {
+            RetentionTable::Opportunities => {
+                "found_at < NOW() - make_interval(days => $1) AND notes IS NULL AND labels = '{}'"
+            }
+            RetentionTable::Trades => {
+                "created_at < NOW() - make_interval(days => $1) AND status IN ('COMPLETED', 'FAILED', 'RESOLVED') \
+                 AND notes IS NULL AND labels = '{}'"
+            }
+            RetentionTable::Fills => "received_at < NOW() - make_interval(days => $1)",
+            RetentionTable::Stats => "sampled_at < NOW() - make_interval(days => $1)",
+            RetentionTable::Audit => "occurred_at < NOW() - make_interval(days => $1)",
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Dead-Man's Switch
+//!
+//! Optional safety mode: auto-execution only stays on while an operator (or an
+//! external supervisor) keeps calling `POST /api/live/heartbeat`. If no
+//! heartbeat arrives within the configured timeout, auto-execution is blocked
+//! (safe mode) and open orders are cancelled; streaming and scanning keep
+//! running. Confirming resume clears the trip and restarts the countdown.
+//!
+//! Default comes from DEAD_MAN_SWITCH_SECS (unset or 0 = disabled) and can be
+//! changed at runtime via `PUT /api/live/dead-man-switch`.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How often the watcher checks for a missed heartbeat
+pub const WATCH_INTERVAL_MS: u64 = 1000;
+
+/// Shortest allowed heartbeat timeout
+pub const MIN_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug)]
+struct SwitchState {
+    enabled: bool,
+    timeout: Duration,
+    last_heartbeat: Instant,
+    last_heartbeat_at: Option<DateTime<Utc>>,
+    tripped_at: Option<DateTime<Utc>>,
+    trip_reason: Option<String>,
+}
+
+/// Snapshot for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadManStatus {
+    pub enabled: bool,
+    pub timeout_secs: u64,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    /// Seconds left before the switch trips (None when disabled)
+    pub remaining_secs: Option<f64>,
+    pub tripped_at: Option<DateTime<Utc>>,
+    pub trip_reason: Option<String>,
+}
+
+pub struct DeadManSwitch {
+    state: RwLock<SwitchState>,
+}
+
+impl DeadManSwitch {
+    pub fn new(timeout_secs: Option<u64>) -> Self {
+        let timeout_secs = timeout_secs.filter(|&s| s > 0);
+        Self {
+            state: RwLock::new(SwitchState {
+                enabled: timeout_secs.is_some(),
+                timeout: Duration::from_secs(timeout_secs.unwrap_or(60).max(MIN_TIMEOUT_SECS)),
+                last_heartbeat: Instant::now(),
+                last_heartbeat_at: None,
+                tripped_at: None,
+                trip_reason: None,
+            }),
+        }
+    }
+
+    /// Create from DEAD_MAN_SWITCH_SECS
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("DEAD_MAN_SWITCH_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+        )
+    }
+
+    /// Record an operator heartbeat
+    pub fn heartbeat(&self) {
+        let mut state = self.state.write();
+        state.last_heartbeat = Instant::now();
+        state.last_heartbeat_at = Some(Utc::now());
+    }
+
+    /// Enable/disable and set the timeout. Enabling counts as a heartbeat so
+    /// the switch doesn't trip on a stale timestamp.
+    pub fn configure(&self, enabled: bool, timeout_secs: Option<u64>) -> Result<(), String> {
+        if let Some(secs) = timeout_secs {
+            if secs < MIN_TIMEOUT_SECS {
+                return Err(format!("timeout_secs must be at least {}", MIN_TIMEOUT_SECS));
+            }
+        }
+
+        let mut state = self.state.write();
+        if let Some(secs) = timeout_secs {
+            state.timeout = Duration::from_secs(secs);
+        }
+        if enabled && !state.enabled {
+            state.last_heartbeat = Instant::now();
+            state.last_heartbeat_at = Some(Utc::now());
+        }
+        state.enabled = enabled;
+        Ok(())
+    }
+
+    /// Clear a previous trip and restart the countdown (engine started or
+    /// operator confirmed resume)
+    pub fn rearm(&self) {
+        let mut state = self.state.write();
+        state.last_heartbeat = Instant::now();
+        state.tripped_at = None;
+        state.trip_reason = None;
+    }
+
+    /// Check for a missed heartbeat. Returns the trip reason the first time
+    /// the timeout is exceeded; later calls return None until rearmed.
+    pub fn check(&self) -> Option<String> {
+        let mut state = self.state.write();
+        if !state.enabled || state.tripped_at.is_some() {
+            return None;
+        }
+
+        let silent_for = state.last_heartbeat.elapsed();
+        if silent_for < state.timeout {
+            return None;
+        }
+
+        let reason = format!(
+            "Dead-man's switch: no heartbeat for {}s (timeout {}s)",
+            silent_for.as_secs(),
+            state.timeout.as_secs()
+        );
+        state.tripped_at = Some(Utc::now());
+        state.trip_reason = Some(reason.clone());
+        Some(reason)
+    }
+
+    pub fn status(&self) -> DeadManStatus {
+        let state = self.state.read();
+        DeadManStatus {
+            enabled: state.enabled,
+            timeout_secs: state.timeout.as_secs(),
+            last_heartbeat_at: state.last_heartbeat_at,
+            remaining_secs: state.enabled.then(|| {
+                state.timeout.saturating_sub(state.last_heartbeat.elapsed()).as_secs_f64()
+            }),
+            tripped_at: state.tripped_at,
+            trip_reason: state.trip_reason.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_once_after_timeout() {
+        let switch = DeadManSwitch::new(None);
+        assert!(!switch.status().enabled);
+        assert!(switch.check().is_none());
+
+        switch.configure(true, Some(MIN_TIMEOUT_SECS)).unwrap();
+        assert!(switch.check().is_none());
+        assert!(switch.configure(true, Some(1)).is_err());
+
+        // Pretend the last heartbeat was long ago
+        switch.state.write().last_heartbeat = Instant::now() - Duration::from_secs(MIN_TIMEOUT_SECS + 1);
+        assert!(switch.check().is_some());
+        assert!(switch.check().is_none(), "should only trip once");
+        assert!(switch.status().tripped_at.is_some());
+
+        switch.rearm();
+        assert!(switch.status().tripped_at.is_none());
+        assert!(switch.check().is_none());
+    }
+}
@@ -0,0 +1,240 @@
+//! Subscription Depth Tiers
+//!
+//! Every pair used to be subscribed at the same book depth, though most of
+//! the scan's cycles run through a handful of pairs. With BOOK_DEPTH_TIERS
+//! set, each subscribed pair's depth follows the number of BASE → A → B →
+//! BASE triangles it belongs to among the subscribed pairs:
+//! - tiers are "min_cycles:depth" entries, e.g. "6:25,1:10,0:0"
+//! - a pair gets the depth of the highest tier it reaches, or the default
+//!   depth when it reaches none
+//! - depth 0 subscribes the ticker only: best bid/ask keep the pair priced,
+//!   but nothing deeper is known for slippage or liquidity checks
+//!
+//! Tiers are assigned before the socket subscribes and re-evaluated every
+//! BOOK_DEPTH_TIER_SECS (and after a pair set refresh), so pairs move
+//! between tiers as pairs are listed, delisted, subscribed and dropped.
+//! Moving a pair resubscribes its book at the new depth.
+#![allow(dead_code)]
+
+use crate::pair_ranking::triangle_membership;
+use crate::pair_registry::PairMeta;
+use crate::ws_v2::supported_book_depth;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DepthTier {
+    /// Triangles a pair must belong to
+    pub min_cycles: usize,
+    /// Book depth (0 = ticker only)
+    pub depth: usize,
+}
+
+/// Parse "min_cycles:depth" entries, highest tier first. Depths round up
+/// to one Kraken accepts.
+pub fn parse_tiers(value: &str) -> Result<Vec<DepthTier>, String> {
+    let mut tiers = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (cycles, depth) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid depth tier '{}': use min_cycles:depth", entry))?;
+        let min_cycles = cycles.trim().parse().map_err(|_| format!("Invalid cycle count in '{}'", entry))?;
+        let depth: usize = depth.trim().parse().map_err(|_| format!("Invalid depth in '{}'", entry))?;
+        let depth = if depth == 0 { 0 } else { supported_book_depth(depth) };
+        tiers.push(DepthTier { min_cycles, depth });
+    }
+    tiers.sort_by_key(|t| std::cmp::Reverse(t.min_cycles));
+    Ok(tiers)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DepthTierPolicy {
+    /// Highest first (empty = every pair at the default depth)
+    pub tiers: Vec<DepthTier>,
+    /// How often tiers are re-evaluated (0 = only at start and refreshes)
+    pub interval_secs: u64,
+}
+
+impl Default for DepthTierPolicy {
+    fn default() -> Self {
+        Self { tiers: Vec::new(), interval_secs: 60 }
+    }
+}
+
+impl DepthTierPolicy {
+    /// Create from BOOK_DEPTH_TIERS and BOOK_DEPTH_TIER_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let tiers = match std::env::var("BOOK_DEPTH_TIERS") {
+            Ok(value) => parse_tiers(&value).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring BOOK_DEPTH_TIERS: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            tiers,
+            interval_secs: std::env::var("BOOK_DEPTH_TIER_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.tiers.is_empty()
+    }
+
+    /// Depth for a pair in `cycles` triangles (None = the default depth)
+    pub fn depth_for(&self, cycles: usize) -> Option<usize> {
+        self.tiers.iter().find(|t| cycles >= t.min_cycles).map(|t| t.depth)
+    }
+}
+
+/// A subscribed pair's tier
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairDepth {
+    pub pair: String,
+    pub cycles: usize,
+    /// None = the default depth
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DepthTierStatus {
+    pub policy: DepthTierPolicy,
+    pub assigned_at: Option<DateTime<Utc>>,
+    /// Pairs per depth ("default" for pairs left at the default depth)
+    pub pairs_by_depth: HashMap<String, usize>,
+    /// Pairs moved to another depth while running
+    pub moves: u64,
+}
+
+/// Tier assignment of the subscribed pairs
+pub struct DepthTiers {
+    policy: DepthTierPolicy,
+    base_currencies: RwLock<Vec<String>>,
+    assigned: RwLock<Vec<PairDepth>>,
+    assigned_at: RwLock<Option<DateTime<Utc>>>,
+    moves: AtomicU64,
+}
+
+impl DepthTiers {
+    pub fn new(policy: DepthTierPolicy) -> Self {
+        Self {
+            policy,
+            base_currencies: RwLock::new(Vec::new()),
+            assigned: RwLock::new(Vec::new()),
+            assigned_at: RwLock::new(None),
+            moves: AtomicU64::new(0),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(DepthTierPolicy::from_env())
+    }
+
+    pub fn policy(&self) -> &DepthTierPolicy {
+        &self.policy
+    }
+
+    /// The engine (re)started scanning from `base_currencies`
+    pub fn reset(&self, base_currencies: Vec<String>) {
+        *self.base_currencies.write() = base_currencies;
+        self.assigned.write().clear();
+        *self.assigned_at.write() = None;
+    }
+
+    /// Tier of every pair in `pairs` (the subscribed set), by pair name
+    pub fn assign(&self, pairs: &[PairMeta]) -> Vec<PairDepth> {
+        let cycles = triangle_membership(
+            pairs.iter().map(|p| (p.pair_name.as_str(), p.base.as_str(), p.quote.as_str())),
+            &self.base_currencies.read(),
+        );
+        let mut assigned: Vec<PairDepth> = pairs
+            .iter()
+            .map(|p| {
+                let cycles = cycles.get(&p.pair_name).copied().unwrap_or(0);
+                PairDepth { pair: p.pair_name.clone(), cycles, depth: self.policy.depth_for(cycles) }
+            })
+            .collect();
+        assigned.sort_by(|a, b| a.pair.cmp(&b.pair));
+        *self.assigned.write() = assigned.clone();
+        *self.assigned_at.write() = Some(Utc::now());
+        assigned
+    }
+
+    pub fn record_moves(&self, moved: usize) {
+        self.moves.fetch_add(moved as u64, Ordering::Relaxed);
+    }
+
+    pub fn assigned(&self) -> Vec<PairDepth> {
+        self.assigned.read().clone()
+    }
+
+    pub fn status(&self) -> DepthTierStatus {
+        let mut pairs_by_depth = HashMap::new();
+        for pair in self.assigned.read().iter() {
+            let key = pair.depth.map_or_else(|| "default".to_string(), |d| d.to_string());
+            *pairs_by_depth.entry(key).or_insert(0) += 1;
+        }
+        DepthTierStatus {
+            policy: self.policy.clone(),
+            assigned_at: *self.assigned_at.read(),
+            pairs_by_depth,
+            moves: self.moves.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for DepthTiers {
+    fn default() -> Self {
+        Self::new(DepthTierPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pair_registry::STATUS_ONLINE;
+
+    fn meta(pair: &str) -> PairMeta {
+        let (base, quote) = pair.split_once('/').unwrap();
+        PairMeta {
+            pair_name: pair.to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            kraken_id: pair.replace('/', ""),
+            ws_name: pair.to_string(),
+            altname: pair.replace('/', ""),
+            status: STATUS_ONLINE.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tiers_follow_cycle_membership() {
+        assert!(parse_tiers("6").is_err());
+        let tiers = parse_tiers("1:10, 2:20, 0:0").unwrap();
+        // Sorted highest first, 20 rounded up to 25
+        assert_eq!(tiers, vec![
+            DepthTier { min_cycles: 2, depth: 25 },
+            DepthTier { min_cycles: 1, depth: 10 },
+            DepthTier { min_cycles: 0, depth: 0 },
+        ]);
+
+        let tiers = DepthTiers::new(DepthTierPolicy { tiers, interval_secs: 60 });
+        tiers.reset(vec!["USD".to_string()]);
+        // Triangles USD-BTC-ETH and USD-BTC-SOL share BTC/USD; DOT/EUR is in none
+        let pairs: Vec<PairMeta> = ["BTC/USD", "ETH/USD", "ETH/BTC", "SOL/USD", "SOL/BTC", "DOT/EUR"].into_iter().map(meta).collect();
+        let depths: HashMap<String, Option<usize>> = tiers.assign(&pairs).into_iter().map(|p| (p.pair, p.depth)).collect();
+        assert_eq!(depths["BTC/USD"], Some(25));
+        assert_eq!(depths["ETH/BTC"], Some(10));
+        assert_eq!(depths["DOT/EUR"], Some(0));
+
+        let status = tiers.status();
+        assert_eq!((status.pairs_by_depth["25"], status.pairs_by_depth["10"], status.pairs_by_depth["0"]), (1, 4, 1));
+    }
+}
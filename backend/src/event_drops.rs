@@ -0,0 +1,345 @@
+//! Event Channel Drop Diagnostics
+//!
+//! Book updates reach the HFT loop through a bounded channel, and an update
+//! arriving while it's full loses its event (the book itself is still
+//! updated). The socket only counted drops, and nothing read the counts.
+//! Drops are now counted per pair, and every minute the sent and dropped
+//! totals are sampled into the last hour of per-minute counts (a minute
+//! with drops is logged).
+//!
+//! Optionally, a sustained drop rate sheds load: after
+//! EVENT_DROP_ESCALATE_MINUTES (default 3) consecutive minutes dropping at
+//! least EVENT_DROP_ESCALATE_PCT (0 = never) of events, the
+//! EVENT_DROP_ESCALATE_PAIRS (default 3) pairs that dropped the most over
+//! those minutes are unsubscribed. They stay unsubscribed until the engine
+//! restarts or they're subscribed again.
+#![allow(dead_code)]
+
+use crate::ws_v2::EventChannelStats;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// Per-minute samples kept (one hour)
+const MINUTES_KEPT: usize = 60;
+/// Escalations kept (oldest dropped first)
+const ESCALATIONS_KEPT: usize = 20;
+/// Pairs listed with each minute
+const TOP_PAIRS_PER_MINUTE: usize = 3;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EventDropPolicy {
+    /// Drop rate (%) that counts towards escalation (0 = never escalate)
+    pub escalate_pct: f64,
+    /// Consecutive minutes at that rate before pairs are shed
+    pub escalate_minutes: u32,
+    /// Pairs unsubscribed per escalation
+    pub escalate_pairs: usize,
+}
+
+impl Default for EventDropPolicy {
+    fn default() -> Self {
+        Self { escalate_pct: 0.0, escalate_minutes: 3, escalate_pairs: 3 }
+    }
+}
+
+impl EventDropPolicy {
+    /// Create from the EVENT_DROP_ESCALATE_* variables
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            escalate_pct: env("EVENT_DROP_ESCALATE_PCT").unwrap_or(defaults.escalate_pct),
+            escalate_minutes: env::<u32>("EVENT_DROP_ESCALATE_MINUTES").unwrap_or(defaults.escalate_minutes).max(1),
+            escalate_pairs: env("EVENT_DROP_ESCALATE_PAIRS").unwrap_or(defaults.escalate_pairs),
+        }
+    }
+
+    pub fn escalates(&self) -> bool {
+        self.escalate_pct > 0.0 && self.escalate_pairs > 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairDrops {
+    pub pair: String,
+    pub dropped: u64,
+}
+
+/// Events sent and dropped during one minute
+#[derive(Debug, Clone, Serialize)]
+pub struct MinuteDrops {
+    /// End of the minute
+    pub at: DateTime<Utc>,
+    pub sent: u64,
+    pub dropped: u64,
+    pub drop_pct: f64,
+    /// Pairs that dropped the most that minute
+    pub top_pairs: Vec<PairDrops>,
+}
+
+/// Pairs shed after a sustained drop rate
+#[derive(Debug, Clone, Serialize)]
+pub struct DropEscalation {
+    pub at: DateTime<Utc>,
+    /// Minutes the rate was sustained
+    pub minutes: u32,
+    /// Drop rate over those minutes
+    pub drop_pct: f64,
+    pub pairs: Vec<PairDrops>,
+}
+
+/// What one sample found
+#[derive(Debug, Clone)]
+pub struct DropSample {
+    pub minute: MinuteDrops,
+    /// Set when this minute completed a sustained run
+    pub escalation: Option<DropEscalation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventChannelReport {
+    pub policy: EventDropPolicy,
+    /// Whether a channel is being watched (the engine has started)
+    pub attached: bool,
+    /// Since the channel was created
+    pub events_sent: u64,
+    pub events_dropped: u64,
+    pub drop_pct: f64,
+    /// Every pair that dropped, most drops first
+    pub pairs: Vec<PairDrops>,
+    /// Newest first
+    pub minutes: Vec<MinuteDrops>,
+    /// Consecutive minutes at or above the escalation rate so far
+    pub sustained_minutes: u32,
+    /// Pairs shed since the channel was created
+    pub shed_pairs: Vec<String>,
+    /// Newest first
+    pub escalations: Vec<DropEscalation>,
+}
+
+#[derive(Default)]
+struct SampleState {
+    /// Totals at the previous sample
+    sent: u64,
+    dropped: u64,
+    by_pair: HashMap<String, u64>,
+    minutes: VecDeque<MinuteDrops>,
+    /// Current run of minutes at the escalation rate, and its counts
+    streak: u32,
+    streak_sent: u64,
+    streak_dropped: u64,
+    streak_by_pair: HashMap<String, u64>,
+    shed: HashSet<String>,
+    escalations: VecDeque<DropEscalation>,
+}
+
+fn drop_pct(sent: u64, dropped: u64) -> f64 {
+    let total = sent + dropped;
+    if total == 0 {
+        0.0
+    } else {
+        dropped as f64 / total as f64 * 100.0
+    }
+}
+
+/// Most drops first (then by name), at most `limit`
+fn ranked(counts: &HashMap<String, u64>, limit: usize) -> Vec<PairDrops> {
+    let mut pairs: Vec<PairDrops> = counts
+        .iter()
+        .filter(|(_, dropped)| **dropped > 0)
+        .map(|(pair, dropped)| PairDrops { pair: pair.clone(), dropped: *dropped })
+        .collect();
+    pairs.sort_by(|a, b| b.dropped.cmp(&a.dropped).then_with(|| a.pair.cmp(&b.pair)));
+    pairs.truncate(limit);
+    pairs
+}
+
+pub struct EventDropMonitor {
+    policy: EventDropPolicy,
+    channel: RwLock<Option<Arc<EventChannelStats>>>,
+    state: Mutex<SampleState>,
+}
+
+impl EventDropMonitor {
+    pub fn new(policy: EventDropPolicy) -> Self {
+        Self { policy, channel: RwLock::new(None), state: Mutex::new(SampleState::default()) }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(EventDropPolicy::from_env())
+    }
+
+    pub fn policy(&self) -> &EventDropPolicy {
+        &self.policy
+    }
+
+    /// Watch a newly created channel (its counts start from zero; the
+    /// per-minute history is kept)
+    pub fn attach(&self, stats: Arc<EventChannelStats>) {
+        *self.channel.write() = Some(stats);
+        let mut state = self.state.lock();
+        let minutes = std::mem::take(&mut state.minutes);
+        let escalations = std::mem::take(&mut state.escalations);
+        *state = SampleState { minutes, escalations, ..Default::default() };
+    }
+
+    /// Count the minute since the previous sample (None before a channel
+    /// is attached)
+    pub fn sample(&self, now: DateTime<Utc>) -> Option<DropSample> {
+        let channel = self.channel.read().clone()?;
+        let sent = channel.events_sent.load(Ordering::Relaxed);
+        let dropped = channel.events_dropped.load(Ordering::Relaxed);
+        let by_pair = channel.dropped_by_pair();
+
+        let mut state = self.state.lock();
+        let minute_sent = sent.saturating_sub(state.sent);
+        let minute_dropped = dropped.saturating_sub(state.dropped);
+        let minute_by_pair: HashMap<String, u64> = by_pair
+            .iter()
+            .map(|(pair, n)| (pair.clone(), n.saturating_sub(state.by_pair.get(pair).copied().unwrap_or(0))))
+            .collect();
+        state.sent = sent;
+        state.dropped = dropped;
+        state.by_pair = by_pair;
+
+        let minute = MinuteDrops {
+            at: now,
+            sent: minute_sent,
+            dropped: minute_dropped,
+            drop_pct: drop_pct(minute_sent, minute_dropped),
+            top_pairs: ranked(&minute_by_pair, TOP_PAIRS_PER_MINUTE),
+        };
+        if state.minutes.len() >= MINUTES_KEPT {
+            state.minutes.pop_back();
+        }
+        state.minutes.push_front(minute.clone());
+
+        let sustained = self.policy.escalates() && minute_dropped > 0 && minute.drop_pct >= self.policy.escalate_pct;
+        if !sustained {
+            state.streak = 0;
+            state.streak_sent = 0;
+            state.streak_dropped = 0;
+            state.streak_by_pair.clear();
+            return Some(DropSample { minute, escalation: None });
+        }
+        state.streak += 1;
+        state.streak_sent += minute_sent;
+        state.streak_dropped += minute_dropped;
+        for (pair, n) in minute_by_pair {
+            *state.streak_by_pair.entry(pair).or_insert(0) += n;
+        }
+        if state.streak < self.policy.escalate_minutes {
+            return Some(DropSample { minute, escalation: None });
+        }
+
+        let shed = &state.shed;
+        let candidates: HashMap<String, u64> = state
+            .streak_by_pair
+            .iter()
+            .filter(|(pair, _)| !shed.contains(*pair))
+            .map(|(pair, n)| (pair.clone(), *n))
+            .collect();
+        let escalation = DropEscalation {
+            at: now,
+            minutes: state.streak,
+            drop_pct: drop_pct(state.streak_sent, state.streak_dropped),
+            pairs: ranked(&candidates, self.policy.escalate_pairs),
+        };
+        state.streak = 0;
+        state.streak_sent = 0;
+        state.streak_dropped = 0;
+        state.streak_by_pair.clear();
+        if escalation.pairs.is_empty() {
+            return Some(DropSample { minute, escalation: None });
+        }
+        for shed in &escalation.pairs {
+            state.shed.insert(shed.pair.clone());
+        }
+        if state.escalations.len() >= ESCALATIONS_KEPT {
+            state.escalations.pop_back();
+        }
+        state.escalations.push_front(escalation.clone());
+        Some(DropSample { minute, escalation: Some(escalation) })
+    }
+
+    pub fn report(&self) -> EventChannelReport {
+        let channel = self.channel.read().clone();
+        let (events_sent, events_dropped, by_pair) = match &channel {
+            Some(c) => (c.events_sent.load(Ordering::Relaxed), c.events_dropped.load(Ordering::Relaxed), c.dropped_by_pair()),
+            None => (0, 0, HashMap::new()),
+        };
+        let state = self.state.lock();
+        let mut shed_pairs: Vec<String> = state.shed.iter().cloned().collect();
+        shed_pairs.sort();
+        EventChannelReport {
+            policy: self.policy,
+            attached: channel.is_some(),
+            events_sent,
+            events_dropped,
+            drop_pct: drop_pct(events_sent, events_dropped),
+            pairs: ranked(&by_pair, usize::MAX),
+            minutes: state.minutes.iter().cloned().collect(),
+            sustained_minutes: state.streak,
+            shed_pairs,
+            escalations: state.escalations.iter().cloned().collect(),
+        }
+    }
+}
+
+impl Default for EventDropMonitor {
+    fn default() -> Self {
+        Self::new(EventDropPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sustained_drops_shed_top_pairs() {
+        let monitor = EventDropMonitor::new(EventDropPolicy { escalate_pct: 10.0, escalate_minutes: 2, escalate_pairs: 1 });
+        assert!(monitor.sample(Utc::now()).is_none());
+        let stats = Arc::new(EventChannelStats::default());
+        monitor.attach(Arc::clone(&stats));
+
+        let minute = |sent: u64, drops: &[(&str, u64)]| {
+            stats.events_sent.fetch_add(sent, Ordering::Relaxed);
+            for (pair, n) in drops {
+                for _ in 0..*n {
+                    stats.record_drop(pair);
+                }
+            }
+            monitor.sample(Utc::now()).unwrap()
+        };
+
+        // 5% then 20%: only the second minute counts
+        let quiet = minute(95, &[("BTC/USD", 5)]);
+        assert_eq!((quiet.minute.dropped, quiet.minute.drop_pct), (5, 5.0));
+        assert!(minute(80, &[("ETH/USD", 15), ("BTC/USD", 5)]).escalation.is_none());
+        let busy = minute(70, &[("ETH/USD", 10), ("SOL/USD", 20)]);
+        let escalation = busy.escalation.unwrap();
+        assert_eq!(escalation.minutes, 2);
+        assert_eq!(escalation.drop_pct, 25.0);
+        assert_eq!(escalation.pairs, vec![PairDrops { pair: "ETH/USD".to_string(), dropped: 25 }]);
+        assert_eq!(busy.minute.top_pairs[0], PairDrops { pair: "SOL/USD".to_string(), dropped: 20 });
+
+        // A shed pair isn't picked again
+        minute(70, &[("ETH/USD", 30)]);
+        let next = minute(70, &[("ETH/USD", 20), ("SOL/USD", 10)]).escalation.unwrap();
+        assert_eq!(next.pairs[0].pair, "SOL/USD");
+
+        let report = monitor.report();
+        assert_eq!((report.events_sent, report.events_dropped), (385, 115));
+        assert_eq!(report.pairs[0], PairDrops { pair: "ETH/USD".to_string(), dropped: 75 });
+        assert_eq!(report.minutes.len(), 5);
+        assert_eq!(report.shed_pairs, vec!["ETH/USD".to_string(), "SOL/USD".to_string()]);
+        assert_eq!(report.escalations.len(), 2);
+    }
+}
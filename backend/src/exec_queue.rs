@@ -0,0 +1,234 @@
+//! Execution Queue
+//!
+//! A scan can produce more than one tradeable opportunity at once (the main
+//! scan's find, execute-mode universe finds, a sweep's pick), and before
+//! this they were taken in the order they turned up. They now go through a
+//! small priority queue in front of execution:
+//! - priority is the net profit scaled down linearly with age, reaching zero
+//!   at EXEC_QUEUE_MAX_AGE_MS after detection
+//! - anything older than that is dropped unexecuted
+//! - a path queued again replaces its older entry
+//! - with EXEC_QUEUE_CAPACITY entries queued, the lowest priority one is
+//!   displaced by a better newcomer (or the newcomer is dropped)
+//!
+//! Each hot path cycle takes the best entry; the rest wait for the next cycle
+//! and go stale if they wait too long.
+#![allow(dead_code)]
+
+use crate::types::Opportunity;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ExecQueuePolicy {
+    /// Opportunities older than this are dropped, in milliseconds
+    pub max_age_ms: u64,
+    /// Most opportunities waiting at once
+    pub capacity: usize,
+}
+
+impl Default for ExecQueuePolicy {
+    fn default() -> Self {
+        Self { max_age_ms: 250, capacity: 8 }
+    }
+}
+
+impl ExecQueuePolicy {
+    /// Create from EXEC_QUEUE_MAX_AGE_MS and EXEC_QUEUE_CAPACITY
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            max_age_ms: env::<u64>("EXEC_QUEUE_MAX_AGE_MS").filter(|v| *v > 0).unwrap_or(defaults.max_age_ms),
+            capacity: env::<usize>("EXEC_QUEUE_CAPACITY").filter(|v| *v > 0).unwrap_or(defaults.capacity),
+        }
+    }
+
+    /// Net profit scaled by freshness (None once too old)
+    pub fn priority(&self, opp: &Opportunity, now: DateTime<Utc>) -> Option<f64> {
+        let age_ms = (now - opp.detected_at).num_milliseconds().max(0) as f64;
+        let max_age_ms = self.max_age_ms as f64;
+        (age_ms < max_age_ms).then(|| opp.net_profit_pct * (1.0 - age_ms / max_age_ms))
+    }
+}
+
+/// A waiting opportunity, for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedOpportunity {
+    pub path: String,
+    pub net_profit_pct: f64,
+    pub age_ms: i64,
+    pub priority: f64,
+}
+
+/// Queue state for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecQueueStatus {
+    pub policy: ExecQueuePolicy,
+    /// Best first
+    pub waiting: Vec<QueuedOpportunity>,
+    pub queued: u64,
+    /// Taken for execution
+    pub dispatched: u64,
+    /// Dropped for age
+    pub expired: u64,
+    /// Dropped for a better opportunity while full
+    pub displaced: u64,
+}
+
+pub struct ExecutionQueue {
+    policy: ExecQueuePolicy,
+    entries: Mutex<Vec<Opportunity>>,
+    queued: AtomicU64,
+    dispatched: AtomicU64,
+    expired: AtomicU64,
+    displaced: AtomicU64,
+}
+
+impl ExecutionQueue {
+    pub fn new(policy: ExecQueuePolicy) -> Self {
+        Self {
+            policy,
+            entries: Mutex::new(Vec::new()),
+            queued: AtomicU64::new(0),
+            dispatched: AtomicU64::new(0),
+            expired: AtomicU64::new(0),
+            displaced: AtomicU64::new(0),
+        }
+    }
+
+    pub fn policy(&self) -> &ExecQueuePolicy {
+        &self.policy
+    }
+
+    /// Drop entries too old to execute
+    fn expire(&self, entries: &mut Vec<Opportunity>, now: DateTime<Utc>) {
+        let before = entries.len();
+        entries.retain(|o| self.policy.priority(o, now).is_some());
+        self.expired.fetch_add((before - entries.len()) as u64, Ordering::Relaxed);
+    }
+
+    /// Queue `opp`. Returns false when it was dropped (already stale, or the
+    /// queue is full of better ones).
+    pub fn push(&self, opp: Opportunity, now: DateTime<Utc>) -> bool {
+        let Some(priority) = self.policy.priority(&opp, now) else {
+            self.expired.fetch_add(1, Ordering::Relaxed);
+            return false;
+        };
+        let mut entries = self.entries.lock();
+        self.expire(&mut entries, now);
+        entries.retain(|o| o.path != opp.path);
+
+        if entries.len() >= self.policy.capacity {
+            let worst = entries
+                .iter()
+                .enumerate()
+                .filter_map(|(i, o)| Some((i, self.policy.priority(o, now)?)))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match worst {
+                Some((i, worst)) if worst < priority => {
+                    entries.swap_remove(i);
+                }
+                _ => {
+                    self.displaced.fetch_add(1, Ordering::Relaxed);
+                    return false;
+                }
+            }
+            self.displaced.fetch_add(1, Ordering::Relaxed);
+        }
+        entries.push(opp);
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    /// Take the highest priority opportunity still fresh enough
+    pub fn pop(&self, now: DateTime<Utc>) -> Option<Opportunity> {
+        let mut entries = self.entries.lock();
+        self.expire(&mut entries, now);
+        let best = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, o)| Some((i, self.policy.priority(o, now)?)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)?;
+        self.dispatched.fetch_add(1, Ordering::Relaxed);
+        Some(entries.swap_remove(best))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> ExecQueueStatus {
+        let mut waiting: Vec<QueuedOpportunity> = self
+            .entries
+            .lock()
+            .iter()
+            .filter_map(|o| {
+                Some(QueuedOpportunity {
+                    path: o.path.clone(),
+                    net_profit_pct: o.net_profit_pct,
+                    age_ms: (now - o.detected_at).num_milliseconds(),
+                    priority: self.policy.priority(o, now)?,
+                })
+            })
+            .collect();
+        waiting.sort_by(|a, b| b.priority.total_cmp(&a.priority));
+        ExecQueueStatus {
+            policy: self.policy,
+            waiting,
+            queued: self.queued.load(Ordering::Relaxed),
+            dispatched: self.dispatched.load(Ordering::Relaxed),
+            expired: self.expired.load(Ordering::Relaxed),
+            displaced: self.displaced.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for ExecutionQueue {
+    fn default() -> Self {
+        Self::new(ExecQueuePolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opp(path: &str, net_profit_pct: f64, detected_at: DateTime<Utc>) -> Opportunity {
+        Opportunity { detected_at, ..Opportunity::for_test(path, net_profit_pct) }
+    }
+
+    #[test]
+    fn test_priority_preemption_and_expiry() {
+        let queue = ExecutionQueue::new(ExecQueuePolicy { max_age_ms: 100, capacity: 2 });
+        let now = Utc::now();
+        let ago = |ms: i64| now - chrono::Duration::milliseconds(ms);
+
+        // An older, more profitable find loses to a fresh one once decayed
+        assert!(queue.push(opp("USD → BTC → ETH → USD", 0.5, ago(80)), now));
+        assert!(queue.push(opp("USD → SOL → BTC → USD", 0.2, ago(0)), now));
+        // Full: a worse newcomer is dropped, a better one displaces the worst
+        assert!(!queue.push(opp("USD → XRP → BTC → USD", 0.05, ago(0)), now));
+        assert!(queue.push(opp("USD → ADA → BTC → USD", 0.15, ago(0)), now));
+        assert!(!queue.push(opp("USD → DOT → BTC → USD", 0.9, ago(150)), now));
+
+        let status = queue.status(now);
+        assert_eq!((status.queued, status.displaced, status.expired), (3, 2, 1));
+        assert_eq!(status.waiting.iter().map(|w| w.path.as_str()).collect::<Vec<_>>(), vec!["USD → SOL → BTC → USD", "USD → ADA → BTC → USD"]);
+
+        assert_eq!(queue.pop(now).unwrap().path, "USD → SOL → BTC → USD");
+        // Left waiting past its age limit
+        assert!(queue.pop(now + chrono::Duration::milliseconds(100)).is_none());
+        assert_eq!(queue.status(now).expired, 2);
+        assert_eq!(queue.status(now).dispatched, 1);
+    }
+}
@@ -0,0 +1,668 @@
+//! Scripted Execution Backend
+//!
+//! Deterministic stand-in for the Kraken private WebSocket so the execution
+//! path can be tested without API keys. Tests queue one outcome per expected
+//! order; `ExecutionEngine::with_fake_backend` then answers every order from
+//! the queue, in order:
+//! - fill at a price (fee taken from what the leg receives, or charged in
+//!   the quote currency as Kraken does by default)
+//! - partial fill (order ends canceled with only part of the quantity done)
+//! - rejection with an exchange error message
+//! - timeout
+//!
+//! A price-capped order (sent with a limit) whose scripted fill price is
+//! past the limit ends canceled with nothing filled, as an immediate-or-cancel
+//! order would, and a fill-or-kill order scripted to fill partly fills
+//! nothing. Each outcome can carry a latency so leg timings are exercised too. Orders
+//! that arrive after the script ran out are rejected, never filled.
+//! Balances can be set for the executor's insufficient-funds resize; they
+//! are not debited by fills.
+
+use crate::executor::{ExecutionError, OrderResponse, OrderSide, ORDER_TIMEOUT_MS};
+use crate::kraken_proto::v2::TimeInForce;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// What the fake exchange does with the next order
+#[derive(Debug, Clone)]
+pub enum ScriptedOutcome {
+    /// Fill the whole quantity at `price` (quote per base)
+    Fill { price: f64 },
+    /// Fill `fraction` of the quantity at `price`, then cancel the rest
+    PartialFill { price: f64, fraction: f64 },
+    /// Reject with this error
+    Reject(String),
+    /// Never answer
+    Timeout,
+}
+
+/// An order the backend received
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedOrder {
+    pub pair: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub time_in_force: TimeInForce,
+}
+
+/// Queue of scripted order outcomes plus a record of what was sent
+pub struct FakeExecutionBackend {
+    fee_rate: f64,
+    fee_in_quote: bool,
+    script: Mutex<VecDeque<(ScriptedOutcome, Duration)>>,
+    placed: Mutex<Vec<PlacedOrder>>,
+    next_order_id: Mutex<u64>,
+    balances: Mutex<HashMap<String, f64>>,
+}
+
+impl FakeExecutionBackend {
+    /// Create with the fee rate charged on every fill (e.g. 0.0026)
+    pub fn new(fee_rate: f64) -> Self {
+        Self {
+            fee_rate,
+            fee_in_quote: false,
+            script: Mutex::new(VecDeque::new()),
+            placed: Mutex::new(Vec::new()),
+            next_order_id: Mutex::new(1),
+            balances: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Charge fees in the quote currency, whichever side of it the order is
+    pub fn with_fees_in_quote(mut self) -> Self {
+        self.fee_in_quote = true;
+        self
+    }
+
+    /// Queue an outcome answered after `latency_ms`
+    pub fn push(&self, outcome: ScriptedOutcome, latency_ms: u64) -> &Self {
+        self.script.lock().push_back((outcome, Duration::from_millis(latency_ms)));
+        self
+    }
+
+    pub fn fill(&self, price: f64) -> &Self {
+        self.push(ScriptedOutcome::Fill { price }, 0)
+    }
+
+    pub fn partial_fill(&self, price: f64, fraction: f64) -> &Self {
+        self.push(ScriptedOutcome::PartialFill { price, fraction }, 0)
+    }
+
+    pub fn reject(&self, error: &str) -> &Self {
+        self.push(ScriptedOutcome::Reject(error.to_string()), 0)
+    }
+
+    pub fn timeout(&self) -> &Self {
+        self.push(ScriptedOutcome::Timeout, 0)
+    }
+
+    /// Report `amount` of `currency` as available
+    pub fn set_balance(&self, currency: &str, amount: f64) -> &Self {
+        self.balances.lock().insert(currency.to_string(), amount);
+        self
+    }
+
+    pub fn balance(&self, currency: &str) -> Option<f64> {
+        self.balances.lock().get(currency).copied()
+    }
+
+    /// Orders received so far, in order
+    pub fn placed(&self) -> Vec<PlacedOrder> {
+        self.placed.lock().clone()
+    }
+
+    /// Outcomes not consumed yet
+    pub fn remaining(&self) -> usize {
+        self.script.lock().len()
+    }
+
+    /// Answer one order. Quantities follow place_order: quote amount for
+    /// buys, base amount for sells. A completed order comes back as it would
+    /// from the executions channel, error and all.
+    pub async fn place_order(
+        &self,
+        pair: &str,
+        side: OrderSide,
+        quantity: f64,
+        limit_price: Option<f64>,
+        time_in_force: TimeInForce,
+    ) -> Result<OrderResponse, ExecutionError> {
+        self.placed.lock().push(PlacedOrder { pair: pair.to_string(), side, quantity, time_in_force });
+
+        let next = self.script.lock().pop_front();
+        let (outcome, latency) = match next {
+            Some(next) => next,
+            None => return Err(ExecutionError::OrderRejected("No scripted response".to_string())),
+        };
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        let order_id = {
+            let mut id = self.next_order_id.lock();
+            *id += 1;
+            format!("FAKE-{}", *id - 1)
+        };
+
+        let (price, mut fraction) = match outcome {
+            ScriptedOutcome::Fill { price } => (price, 1.0),
+            ScriptedOutcome::PartialFill { price, fraction } => (price, fraction.clamp(0.0, 1.0)),
+            ScriptedOutcome::Reject(error) => return Err(ExecutionError::OrderRejected(error)),
+            ScriptedOutcome::Timeout => return Err(ExecutionError::Timeout(ORDER_TIMEOUT_MS)),
+        };
+
+        let past_limit = limit_price.is_some_and(|limit| match side {
+            OrderSide::Buy => price > limit,
+            OrderSide::Sell => price < limit,
+        });
+        if past_limit || (time_in_force == TimeInForce::Fok && fraction < 1.0) {
+            fraction = 0.0;
+        }
+
+        // filled_qty is always base, cum_cost always quote
+        let (filled_qty, cum_cost) = match side {
+            OrderSide::Buy => (quantity * fraction / price, quantity * fraction),
+            OrderSide::Sell => (quantity * fraction, quantity * fraction * price),
+        };
+        // Fee is charged in the currency received, or always in the quote
+        let (base, quote) = pair.split_once('/').unwrap_or((pair, ""));
+        let (fee_native, fee_currency) = match side {
+            _ if self.fee_in_quote => (cum_cost * self.fee_rate, quote),
+            OrderSide::Buy => (filled_qty * self.fee_rate, base),
+            OrderSide::Sell => (cum_cost * self.fee_rate, quote),
+        };
+        let status = if fraction < 1.0 { "canceled" } else { "filled" };
+
+        Ok(OrderResponse {
+            order_id,
+            status: status.to_string(),
+            filled_qty,
+            avg_price: price,
+            cum_cost,
+            fee: cum_cost * self.fee_rate,
+            fee_native,
+            fee_currency: Some(fee_currency.to_string()),
+            error: (fraction < 1.0).then(|| format!("Order {}", status)),
+            price_improvement_bps: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{blended_improvement_bps, blended_price, market_order_size, parse_disabled_pairs, BuySizing, ErrorClass, ExecutionEngine, FillSegment, FreshnessPolicy, FundsResizePolicy, MarketTifPolicy, OrderSize, PrefundPolicy, PriceCapPolicy, PriceImprovementStats, RetryPolicy, RetryRule, SignalAction, SignalGatePolicy};
+    use std::collections::HashSet;
+    use crate::order_book::{OrderBookCache, PairInfo};
+    use crate::trade_feed::{TradeFeed, TradeFeedPolicy, TradePrint};
+    use crate::types::{Opportunity, OrderBookLevel, Strategy};
+    use std::sync::Arc;
+
+    fn cache_with(pairs: &[(&str, &str, f64)]) -> Arc<OrderBookCache> {
+        let cache = Arc::new(OrderBookCache::new());
+        for &(base, quote, mid) in pairs {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            cache.update_snapshot(
+                &pair,
+                vec![OrderBookLevel { price: mid * 0.9999, qty: 10.0 }],
+                vec![OrderBookLevel { price: mid * 1.0001, qty: 10.0 }],
+                1,
+            );
+        }
+        cache
+    }
+
+    #[tokio::test]
+    async fn test_scripted_cycle_fills_and_failures() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend));
+        let opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+
+        // Full cycle: 100 USD -> 0.002 BTC -> 0.04 ETH -> 102 USD
+        backend.fill(50_000.0).fill(0.05).push(ScriptedOutcome::Fill { price: 2_550.0 }, 5);
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.legs.len(), 3);
+        assert!((result.end_amount - 102.0).abs() < 1e-9);
+        assert!(result.legs[2].duration_ms >= 5);
+        let sides: Vec<OrderSide> = backend.placed().iter().map(|o| o.side).collect();
+        assert_eq!(sides, vec![OrderSide::Buy, OrderSide::Buy, OrderSide::Sell]);
+
+        // Rejection on leg 2 stops the cycle holding BTC
+        backend.fill(50_000.0).reject("EOrder:Insufficient funds");
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.legs.len(), 2);
+        assert!(result.legs[0].success && !result.legs[1].success);
+        assert!(result.error.unwrap().contains("Insufficient funds"));
+
+        // A partial fill counts as a failed leg
+        backend.partial_fill(50_000.0, 0.5);
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.legs[0].error.as_deref(), Some("Order rejected: Order canceled"));
+
+        backend.timeout();
+        let result = engine.execute_single_leg("USD", "BTC", 10.0).await.unwrap();
+        assert!(!result.success);
+
+        // Script exhausted: rejected, never filled
+        let result = engine.execute_single_leg("USD", "BTC", 10.0).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(backend.remaining(), 0);
+        let stats = engine.get_stats();
+        assert_eq!((stats.orders_filled, stats.orders_timed_out), (4, 1));
+    }
+
+    #[tokio::test]
+    async fn test_fee_in_spent_currency_not_taken_from_output() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.001).with_fees_in_quote());
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend));
+        let opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+
+        // Buys pay their fee in what they spend (0.1 USD, then 0.000002 BTC),
+        // the sell in what it receives (0.102 USD)
+        backend.fill(50_000.0).fill(0.05).fill(2_550.0);
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.legs[0].fee_currency.as_deref(), Some("USD"));
+        assert!((result.legs[0].output_amount - 0.002).abs() < 1e-12);
+        assert!((backend.placed()[1].quantity - 0.002).abs() < 1e-12);
+        assert_eq!(result.legs[1].fee_currency.as_deref(), Some("BTC"));
+        assert!((result.legs[1].fee_amount - 0.000002).abs() < 1e-15);
+        assert!((result.end_amount - 101.898).abs() < 1e-9);
+        // Both buy fees come off the profit, in USD: 101.898 - 100 - 0.1 - 0.1
+        assert!((result.profit_amount - 1.698).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_ioc_legs_trade_what_filled() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let policy = MarketTifPolicy { triangular: TimeInForce::Ioc, cross_pair: TimeInForce::Fok, ..MarketTifPolicy::default() };
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend)).with_market_tif(policy);
+        let mut opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+
+        // Leg 1 spends 60 of 100 USD: the cycle runs on 60, leg 2 sells the BTC bought
+        backend.partial_fill(50_000.0, 0.6).fill(0.05).fill(2_550.0);
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(result.success);
+        assert!((result.legs[0].unfilled_amount.unwrap() - 40.0).abs() < 1e-9);
+        assert!((result.start_amount - 60.0).abs() < 1e-9);
+        assert!((backend.placed()[1].quantity - 0.0012).abs() < 1e-12);
+        assert!((result.end_amount - 61.2).abs() < 1e-9);
+        assert!(result.legs[1].unfilled_amount.is_none());
+        assert_eq!(backend.placed()[0].time_in_force, TimeInForce::Ioc);
+        assert_eq!(engine.get_stats().orders_partially_filled, 1);
+
+        // Fill-or-kill: a leg that can't fill whole fills nothing and fails
+        opp.strategy = Strategy::CrossPair;
+        backend.partial_fill(50_000.0, 0.6);
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(!result.success && result.legs[0].unfilled_amount.is_none());
+        assert_eq!(backend.placed()[3].time_in_force, TimeInForce::Fok);
+        assert_eq!(backend.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_funds_resizes_leg() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let minimums = HashMap::from([("ETH/BTC".to_string(), (0.01, 0.0))]);
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_resize_policy(FundsResizePolicy { enabled: true, max_shrink_pct: 1.0 })
+            .with_order_minimums(minimums);
+        let opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+
+        // Leg 2 is short 0.5%: retried with what the account holds
+        backend.set_balance("BTC", 0.00199);
+        backend.fill(50_000.0).reject("EOrder:Insufficient funds").fill(0.05).fill(2_550.0);
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.legs[1].resized_from, Some(0.002));
+        assert_eq!(result.legs[1].input_amount, 0.00199);
+        assert!(result.legs[0].resized_from.is_none());
+        assert!((result.end_amount - 0.00199 / 0.05 * 2_550.0).abs() < 1e-9);
+        assert_eq!(backend.placed()[2].quantity, 0.00199);
+
+        // Leg 1 resized: the cycle just starts smaller
+        backend.set_balance("USD", 99.5);
+        backend.reject("EOrder:Insufficient funds").fill(50_000.0).fill(0.05).fill(2_550.0);
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(result.success && result.start_amount == 99.5);
+        assert!(result.profit_pct > 1.9);
+
+        // Too far short, or short for another reason: the leg fails as before
+        backend.set_balance("BTC", 0.0019);
+        backend.fill(50_000.0).reject("EOrder:Insufficient funds");
+        let result = engine.execute_opportunity(&opp, 100.0).await.unwrap();
+        assert!(!result.success && result.legs[1].resized_from.is_none());
+        backend.set_balance("BTC", 0.00199);
+        backend.fill(50_000.0).reject("EOrder:Invalid price");
+        assert!(!engine.execute_opportunity(&opp, 100.0).await.unwrap().success);
+
+        // Below the pair's order minimum even though within the shrink limit
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_resize_policy(FundsResizePolicy { enabled: true, max_shrink_pct: 1.0 })
+            .with_order_minimums(HashMap::from([("ETH/BTC".to_string(), (0.05, 0.0))]));
+        backend.fill(50_000.0).reject("EOrder:Insufficient funds");
+        assert!(!engine.execute_opportunity(&opp, 100.0).await.unwrap().success);
+        assert_eq!(backend.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_prefund_check_refuses_short_first_leg() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_prefund_check(PrefundPolicy { enabled: true, fee_buffer_pct: 0.5 });
+
+        // 100 USD covers the order but not its fee buffer: nothing is sent
+        backend.set_balance("USD", 100.0);
+        let err = engine.execute_opportunity(&Opportunity::for_test("USD → BTC → ETH → USD", 0.2), 100.0).await.unwrap_err();
+        match err {
+            ExecutionError::InsufficientFunds { currency, needed, available, needed_usd, .. } => {
+                assert_eq!(currency, "USD");
+                assert!((needed - 100.5).abs() < 1e-9 && available == 100.0);
+                assert_eq!(needed_usd, Some(needed));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(backend.placed().is_empty());
+
+        // Starting from BTC the order is a sell: no buffer, priced in USD
+        backend.set_balance("BTC", 0.001);
+        let err = engine.execute_opportunity(&Opportunity::for_test("BTC → USD → ETH → BTC", 0.2), 0.002).await.unwrap_err();
+        let ExecutionError::InsufficientFunds { needed, available_usd, .. } = err else { panic!() };
+        assert_eq!(needed, 0.002);
+        assert!((available_usd.unwrap() - 50.0).abs() < 0.01);
+
+        backend.set_balance("USD", 101.0);
+        backend.fill(50_000.0).fill(0.05).fill(2_550.0);
+        assert!(engine.execute_opportunity(&Opportunity::for_test("USD → BTC → ETH → USD", 0.2), 100.0).await.unwrap().success);
+    }
+
+    #[tokio::test]
+    async fn test_price_cap_stops_fill_past_limit() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let policy = PriceCapPolicy { max_deviation_bps: 20.0 };
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_price_cap(policy, HashMap::from([("BTC/USD".to_string(), (1, 8))]));
+
+        // Ask + 20 bps on a 0.1 tick; the buy is sized in base at the limit
+        let buy = policy.cap(OrderSide::Buy, 49_995.0, 50_005.0, 100.0, 1, 8);
+        assert!((buy.limit_price - 50_105.0).abs() < 1e-6);
+        assert!(buy.order_qty * buy.limit_price <= 100.0);
+        let sell = policy.cap(OrderSide::Sell, 49_995.0, 50_005.0, 0.002, 1, 8);
+        assert!((sell.limit_price - 49_895.1).abs() < 1e-6 && sell.order_qty == 0.002);
+
+        backend.fill(50_100.0);
+        assert!(engine.execute_single_leg("USD", "BTC", 100.0).await.unwrap().success);
+
+        // The book gapped past the limit: nothing fills and the cap is counted
+        backend.fill(50_200.0);
+        let result = engine.execute_single_leg("USD", "BTC", 100.0).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Price cap on BTC/USD"));
+        assert_eq!(engine.get_stats().orders_price_capped, 1);
+
+        // Unknown precision: sent at market as before
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_price_cap(policy, HashMap::new());
+        backend.fill(50_200.0);
+        assert!(engine.execute_single_leg("USD", "BTC", 100.0).await.unwrap().success);
+    }
+
+    #[test]
+    fn test_market_order_size_by_side() {
+        // Buys are handed quote: spent as is, or converted at the ask and
+        // rounded down to the lot so they never spend more than that
+        assert_eq!(market_order_size(OrderSide::Buy, 100.0, BuySizing::Cost, Some(50_005.0), Some(8)), OrderSize::Quote(100.0));
+        let OrderSize::Base(qty) = market_order_size(OrderSide::Buy, 100.0, BuySizing::Base, Some(50_005.0), Some(8)) else {
+            panic!("buy not sized in base");
+        };
+        assert_eq!(qty, 0.00199980);
+        assert!(qty * 50_005.0 <= 100.0);
+        assert_eq!(market_order_size(OrderSide::Buy, 100.0, BuySizing::Base, Some(0.0), Some(8)), OrderSize::Quote(100.0));
+        assert_eq!(market_order_size(OrderSide::Buy, 100.0, BuySizing::Base, None, None), OrderSize::Quote(100.0));
+
+        // Sells are handed base already
+        assert_eq!(market_order_size(OrderSide::Sell, 0.002, BuySizing::Base, Some(50_005.0), Some(8)), OrderSize::Base(0.002));
+        assert_eq!(market_order_size(OrderSide::Sell, 0.002, BuySizing::Cost, None, None), OrderSize::Base(0.002));
+    }
+
+    #[tokio::test]
+    async fn test_price_improvement_by_pair_and_order_type() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend));
+
+        // Buys measure against the ask (50_005), sells against the bid (49_995)
+        backend.fill(49_990.0).fill(50_015.0).fill(50_000.0);
+        let better = engine.execute_single_leg("USD", "BTC", 100.0).await.unwrap();
+        assert!((better.legs[0].price_improvement_bps.unwrap() - 15.0 / 50_005.0 * 10_000.0).abs() < 1e-9);
+        engine.execute_single_leg("USD", "BTC", 100.0).await.unwrap();
+        engine.execute_single_leg("BTC", "USD", 0.001).await.unwrap();
+
+        let stats = engine.get_stats().price_improvement;
+        assert_eq!(stats.len(), 1);
+        let market = &stats[0];
+        assert_eq!((market.pair.as_str(), market.order_type.as_str(), market.fills), ("BTC/USD", "market", 3));
+        assert!((market.best_bps - 15.0 / 50_005.0 * 10_000.0).abs() < 1e-9);
+        assert!((market.worst_bps + 10.0 / 50_005.0 * 10_000.0).abs() < 1e-9);
+        let by_type = PriceImprovementStats::by_order_type(&stats);
+        assert_eq!(by_type.len(), 1);
+        assert!((by_type[0].avg_bps - market.avg_bps).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_split_leg_blends_segments() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend));
+
+        // A single-venue leg is its own one segment, measured as the leg was
+        backend.fill(49_990.0);
+        let mut leg = engine.execute_single_leg("USD", "BTC", 100.0).await.unwrap().legs.remove(0);
+        assert!(leg.fills.is_empty());
+        let segments = leg.segments();
+        assert_eq!((segments.len(), segments[0].venue.as_str()), (1, "kraken"));
+        assert!((segments[0].volume - 100.0 / 49_990.0).abs() < 1e-12);
+        assert_eq!(segments[0].fee_currency, leg.fee_currency);
+        assert!((blended_price(&segments).unwrap() - 49_990.0).abs() < 1e-6);
+        assert!((segments[0].expected_price.unwrap() - 50_005.0).abs() < 1e-6);
+        assert!((leg.improvement_bps().unwrap() - leg.price_improvement_bps.unwrap()).abs() < 1e-9);
+
+        // A buy split 3:1 across two books is measured against the better ask
+        let segment = |venue: &str, volume: f64, price: f64, expected: f64| FillSegment {
+            venue: venue.to_string(),
+            order_id: format!("{}-1", venue),
+            volume,
+            input_amount: volume * price,
+            output_amount: volume,
+            avg_price: price,
+            fee: 0.0,
+            fee_amount: 0.0,
+            fee_currency: Some("USD".to_string()),
+            expected_price: Some(expected),
+        };
+        let split = [segment("a", 0.03, 50_000.0, 50_000.0), segment("b", 0.01, 50_040.0, 50_030.0)];
+        assert!((blended_price(&split).unwrap() - 50_010.0).abs() < 1e-6);
+        let bps = blended_improvement_bps(OrderSide::Buy, &split).unwrap();
+        assert!((bps + 10.0 / 50_000.0 * 10_000.0).abs() < 1e-6);
+        assert!(blended_improvement_bps(OrderSide::Buy, &split[..0]).is_none());
+        leg.fills = split.to_vec();
+        assert!((leg.improvement_bps().unwrap() - bps).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_execution_disabled_pair_refuses_path() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend));
+        let opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+
+        let pairs = parse_disabled_pairs(&serde_json::json!(["eth/btc", "ETH/BTC"])).unwrap();
+        assert_eq!(pairs, vec!["ETH/BTC".to_string()]);
+        assert!(parse_disabled_pairs(&serde_json::json!(["ETHBTC"])).is_err());
+        engine.set_execution_disabled(pairs.into_iter().collect());
+
+        // Refused before any order, naming the pair
+        assert_eq!(engine.disabled_pair_on(&opp.path).as_deref(), Some("ETH/BTC"));
+        let err = engine.execute_opportunity(&opp, 100.0).await.unwrap_err();
+        assert!(matches!(err, ExecutionError::PairDisabled { ref pair } if pair == "ETH/BTC"));
+        assert!(backend.placed().is_empty());
+
+        // Paths avoiding it still trade
+        assert!(engine.disabled_pair_on("USD → ETH → USD").is_none());
+        backend.fill(50_000.0);
+        assert!(engine.execute_single_leg("USD", "BTC", 10.0).await.unwrap().success);
+        engine.set_execution_disabled(HashSet::new());
+        assert!(engine.disabled_pair_on(&opp.path).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_volatile_pair_refuses_path() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let feed = Arc::new(TradeFeed::new(TradeFeedPolicy { max_vol_bps: 50.0, ..TradeFeedPolicy::default() }));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend)).with_trade_feed(Arc::clone(&feed));
+        let opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+
+        // ETH/BTC swinging 1% print to print: about 173bps over the window
+        let now = chrono::Utc::now();
+        feed.record("ETH/BTC", [0.050, 0.0505, 0.050, 0.0505].map(|price| TradePrint { price, qty: 1.0, side: "buy".to_string(), at: now, trade_id: None }));
+        let err = engine.execute_opportunity(&opp, 100.0).await.unwrap_err();
+        assert!(matches!(err, ExecutionError::TooVolatile { ref pair, vol_bps } if pair == "ETH/BTC" && vol_bps > 170.0));
+        assert!(backend.placed().is_empty());
+
+        // Unwinding a single leg is never refused
+        backend.fill(0.05);
+        assert!(engine.execute_single_leg("BTC", "ETH", 0.002).await.unwrap().success);
+    }
+
+    #[tokio::test]
+    async fn test_signal_gate_skips_first_leg_and_delays_later_ones() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        // Bids dwarf asks on BTC/USD: buying BTC now means chasing the price up
+        cache.update_snapshot(
+            "BTC/USD",
+            vec![OrderBookLevel { price: 49_995.0, qty: 100.0 }],
+            vec![OrderBookLevel { price: 50_005.0, qty: 1.0 }],
+            2,
+        );
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+        let policy = SignalGatePolicy { enabled: true, threshold: 0.4, delay_ms: 1, action: SignalAction::Skip };
+
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_signal_gate(policy);
+        let err = engine.execute_opportunity(&opp, 100.0).await.unwrap_err();
+        assert!(matches!(err, ExecutionError::AdverseSignal { ref pair, .. } if pair == "BTC/USD"));
+        assert!(backend.placed().is_empty());
+        assert_eq!(engine.get_stats().signal_skips, 1);
+
+        // Selling BTC into the same book is fine; only the buy leg is held
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_signal_gate(SignalGatePolicy { action: SignalAction::Delay, ..policy });
+        backend.fill(50_000.0).fill(0.05).fill(2_550.0);
+        assert!(engine.execute_opportunity(&opp, 100.0).await.unwrap().success);
+        assert_eq!(engine.get_stats().signal_delays, 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_opportunity_dropped_before_first_order() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_freshness(FreshnessPolicy { max_age_ms: 500 });
+        let mut opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+        opp.detected_at = chrono::Utc::now() - chrono::Duration::seconds(2);
+
+        let err = engine.execute_opportunity(&opp, 100.0).await.unwrap_err();
+        assert!(matches!(err, ExecutionError::StaleOpportunity { age_ms, max_age_ms: 500 } if age_ms >= 2_000));
+        assert!(backend.placed().is_empty());
+        assert_eq!(engine.get_stats().stale_opportunities, 1);
+
+        // The operator's own paths go through at any age
+        opp.strategy = Strategy::Manual;
+        backend.fill(50_000.0).fill(0.05).fill(2_550.0);
+        assert!(engine.execute_opportunity(&opp, 100.0).await.unwrap().success);
+        assert_eq!(engine.get_stats().stale_opportunities, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_by_error_class() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let policy = RetryPolicy {
+            enabled: true,
+            rate_limit: RetryRule { max_retries: 2, backoff_ms: 1 },
+            jitter_pct: 0.0,
+            ..RetryPolicy::default()
+        };
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend))
+            .with_retry_policy(policy);
+
+        // Timeout: one retry, which fills
+        backend.timeout().fill(50_000.0);
+        assert!(engine.execute_single_leg("USD", "BTC", 10.0).await.unwrap().success);
+
+        // Rate limit: backs off twice, then gives up
+        backend.reject("EOrder:Rate limit exceeded").reject("EOrder:Rate limit exceeded").reject("EOrder:Rate limit exceeded");
+        assert!(!engine.execute_single_leg("USD", "BTC", 10.0).await.unwrap().success);
+
+        // Insufficient funds: never retried
+        backend.reject("EOrder:Insufficient funds").fill(50_000.0);
+        assert!(!engine.execute_single_leg("USD", "BTC", 10.0).await.unwrap().success);
+        assert_eq!(backend.remaining(), 1);
+        assert_eq!(backend.placed().len(), 6);
+
+        let retries = engine.get_stats().retries;
+        assert_eq!((retries[&ErrorClass::Timeout].retries, retries[&ErrorClass::Timeout].recovered), (1, 1));
+        assert_eq!((retries[&ErrorClass::RateLimit].retries, retries[&ErrorClass::RateLimit].exhausted), (2, 1));
+        assert!(!retries.contains_key(&ErrorClass::InsufficientFunds));
+    }
+
+    #[tokio::test]
+    async fn test_abort_trade_unwinds_acquired_leg() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)]);
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend));
+        let opp = Opportunity::for_test("USD → BTC → ETH → USD", 0.2);
+
+        // Aborted while leg 1 is out: no leg 2, the BTC is sold back to USD
+        backend.push(ScriptedOutcome::Fill { price: 50_000.0 }, 50);
+        backend.fill(49_000.0);
+        let abort = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let trade_id = engine.in_flight_trades()[0].trade_id.clone();
+            engine.abort_trade(&trade_id).await.unwrap()
+        };
+        let (result, abort) = tokio::join!(engine.execute_opportunity(&opp, 100.0), abort);
+        let result = result.unwrap();
+
+        assert_eq!((abort.leg, abort.cancel_sent), (1, false));
+        assert!(!result.success);
+        assert!(result.error.unwrap().starts_with("Aborted by operator at leg 2"));
+        assert_eq!(result.legs.len(), 2);
+        assert_eq!((result.legs[1].pair.as_str(), result.legs[1].side.as_str()), ("BTC/USD", "sell"));
+        assert!((result.end_amount - 98.0).abs() < 1e-9);
+        assert_eq!(backend.placed().len(), 2);
+
+        assert!(engine.in_flight_trades().is_empty());
+        assert!(engine.abort_trade(&result.id).await.is_none());
+    }
+}
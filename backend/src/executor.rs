@@ -3,21 +3,36 @@
 //! Executes arbitrage trades via Kraken WebSocket v2 private channels.
 //! Designed for async Rust web servers (Axum), not Python bindings.
 
+use crate::audit::{AuditActor, AuditCategory, AuditLog};
 use crate::auth::KrakenAuth;
+use crate::chaos::ChaosMonkey;
+use crate::runtimes::spawn_on;
+use crate::supervisor::{spawn_supervised, TaskSupervisor};
+#[cfg(test)]
+use crate::execution_sim::FakeExecutionBackend;
+use crate::fill_journal::{fill_from_exec, FillJournal};
+use crate::kraken_pairs::normalize_currency;
+use crate::kraken_proto::v2;
 use crate::order_book::OrderBookCache;
-use crate::types::Opportunity;
+use crate::private_transport::{Frame, FrameStream, PrivateTransport, TungsteniteTransport};
+use crate::reconnect::{ReconnectPolicy, ReconnectTracker};
+use crate::trade_feed::TradeFeed;
+use crate::trade_wal::{TradeWal, WalRecord};
+use crate::types::{Opportunity, Strategy};
+use crate::valuation::{PricingSource, Valuator};
 use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use thiserror::Error;
+use tokio::runtime::Handle;
 use tokio::sync::{mpsc, oneshot, RwLock};
 use tokio::time::timeout;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
@@ -27,7 +42,635 @@ fn get_kraken_ws_private_url() -> String {
         .unwrap_or_else(|_| "wss://ws-auth.kraken.com/v2".to_string())
 }
 
-const ORDER_TIMEOUT_MS: u64 = 5000;  // 5 seconds for HFT (was 30s)
+pub(crate) const ORDER_TIMEOUT_MS: u64 = 5000;  // 5 seconds for HFT (was 30s)
+
+/// Tag recorded on trades that sell off a partial trade's held currency
+pub const PARTIAL_RESOLUTION_TAG: &str = "partial_resolution";
+
+/// Pending orders older than this have no caller left waiting (each one
+/// gives up after ORDER_TIMEOUT_MS) and are dropped by the cleanup task
+const STALE_ORDER_MS: u64 = 2 * ORDER_TIMEOUT_MS;
+
+/// How often the cleanup task looks for stale pending orders
+const CLEANUP_INTERVAL_MS: u64 = 5000;
+
+/// Time to wait for an amend_order acknowledgement
+const AMEND_TIMEOUT_MS: u64 = 5000;
+
+/// Resolves a pending amend_order (Err carries Kraken's rejection message)
+type AmendAck = oneshot::Sender<Result<(), String>>;
+
+/// Kraken batch_add accepts 2-15 orders, all for the same symbol
+const MIN_BATCH_ORDERS: usize = 2;
+const MAX_BATCH_ORDERS: usize = 15;
+
+/// Kraken's rejection text when the account can't cover an order
+const INSUFFICIENT_FUNDS: &str = "Insufficient funds";
+
+/// What to do when a cycle leg is rejected for insufficient funds
+#[derive(Debug, Clone, Copy)]
+pub struct FundsResizePolicy {
+    /// Retry once with the available balance instead of failing the leg
+    pub enabled: bool,
+    /// Largest cut to the requested amount a retry may make (percent).
+    /// On legs after the first the shortfall comes out of the cycle's
+    /// return, so this is also the most profit a resize may give up.
+    pub max_shrink_pct: f64,
+}
+
+impl Default for FundsResizePolicy {
+    fn default() -> Self {
+        Self { enabled: false, max_shrink_pct: 1.0 }
+    }
+}
+
+impl FundsResizePolicy {
+    /// Create from INSUFFICIENT_FUNDS_RESIZE (default off) and
+    /// INSUFFICIENT_FUNDS_MAX_SHRINK_PCT (default 1.0)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("INSUFFICIENT_FUNDS_RESIZE")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            max_shrink_pct: std::env::var("INSUFFICIENT_FUNDS_MAX_SHRINK_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0 && *v < 100.0)
+                .unwrap_or(defaults.max_shrink_pct),
+        }
+    }
+}
+
+/// Balance check before a cycle's first order is sent
+#[derive(Debug, Clone, Copy)]
+pub struct PrefundPolicy {
+    /// Look up the spendable balance before leg 1 (one REST call per trade)
+    pub enabled: bool,
+    /// Headroom required on buys, whose fee is charged in the quote currency
+    /// on top of the amount spent (percent)
+    pub fee_buffer_pct: f64,
+}
+
+impl Default for PrefundPolicy {
+    fn default() -> Self {
+        Self { enabled: false, fee_buffer_pct: 0.5 }
+    }
+}
+
+impl PrefundPolicy {
+    /// Create from PREFUND_CHECK (default off) and PREFUND_FEE_BUFFER_PCT (default 0.5)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("PREFUND_CHECK")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            fee_buffer_pct: std::env::var("PREFUND_FEE_BUFFER_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| (0.0..10.0).contains(v))
+                .unwrap_or(defaults.fee_buffer_pct),
+        }
+    }
+}
+
+/// Resting post-only orders for legs that aren't racing a price (partial
+/// trade resolution), repriced in place with amend_order
+#[derive(Debug, Clone, Copy)]
+pub struct MakerPolicy {
+    /// Rest single legs at the touch before sending them at market
+    pub enabled: bool,
+    /// How long an order rests at one price before it follows the touch
+    pub reprice_ms: u64,
+    /// Reprices before the rest is cancelled and sent at market
+    pub max_reprices: u32,
+}
+
+impl Default for MakerPolicy {
+    fn default() -> Self {
+        Self { enabled: false, reprice_ms: 1000, max_reprices: 3 }
+    }
+}
+
+impl MakerPolicy {
+    /// Create from MAKER_LEGS (default off), MAKER_REPRICE_MS (default 1000)
+    /// and MAKER_MAX_REPRICES (default 3). Both limits keep a resting order
+    /// well inside the pending-order sweep.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("MAKER_LEGS")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            reprice_ms: std::env::var("MAKER_REPRICE_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| (100..=2000).contains(v))
+                .unwrap_or(defaults.reprice_ms),
+            max_reprices: std::env::var("MAKER_MAX_REPRICES")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|v| *v <= 3)
+                .unwrap_or(defaults.max_reprices),
+        }
+    }
+}
+
+/// Price bound on orders that would otherwise go out as market orders
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PriceCapPolicy {
+    /// Furthest a fill may land from the best price when the order is sent,
+    /// in basis points (0 = plain market orders)
+    pub max_deviation_bps: f64,
+}
+
+impl Default for PriceCapPolicy {
+    fn default() -> Self {
+        Self { max_deviation_bps: 0.0 }
+    }
+}
+
+impl PriceCapPolicy {
+    /// Create from ORDER_MAX_DEVIATION_BPS (default 0 = off)
+    pub fn from_env() -> Self {
+        Self {
+            max_deviation_bps: std::env::var("ORDER_MAX_DEVIATION_BPS")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(Self::default().max_deviation_bps),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_deviation_bps > 0.0
+    }
+
+    /// Marketable limit for `quantity` (quote for buys, base for sells):
+    /// best ask + bps for buys, best bid - bps for sells, rounded inwards
+    /// to the pair's tick but never past the best price. Buys are sized in
+    /// base at the limit, so they spend at most `quantity`.
+    pub fn cap(&self, side: OrderSide, bid: f64, ask: f64, quantity: f64, price_decimals: u32, lot_decimals: u32) -> OrderCap {
+        let deviation = self.max_deviation_bps / 10_000.0;
+        let tick = 10f64.powi(price_decimals as i32);
+        match side {
+            OrderSide::Buy => {
+                let limit_price = ((ask * (1.0 + deviation) * tick).floor() / tick).max(ask);
+                let lot = 10f64.powi(lot_decimals as i32);
+                OrderCap { limit_price, order_qty: (quantity / limit_price * lot).floor() / lot }
+            }
+            OrderSide::Sell => {
+                let limit_price = ((bid * (1.0 - deviation) * tick).ceil() / tick).min(bid);
+                OrderCap { limit_price, order_qty: quantity }
+            }
+        }
+    }
+}
+
+/// An order sent as an immediate-or-cancel limit instead of at market
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderCap {
+    pub limit_price: f64,
+    /// Base amount
+    pub order_qty: f64,
+}
+
+/// How a market buy is sized. A leg hands a buy the quote amount to spend;
+/// Kraken takes that as is (cash_order_qty) or, like any other order, a
+/// base quantity (order_qty). Either way the leg's input is reconciled to
+/// the order's cum_cost once it fills.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuySizing {
+    /// cash_order_qty of the quote amount
+    #[default]
+    Cost,
+    /// order_qty of the quote amount at the best ask, rounded down to the lot
+    Base,
+}
+
+impl BuySizing {
+    /// Create from BUY_ORDER_SIZING (cost|base, default cost)
+    pub fn from_env() -> Self {
+        match std::env::var("BUY_ORDER_SIZING").map(|v| v.to_lowercase()).as_deref() {
+            Ok("base") => BuySizing::Base,
+            _ => BuySizing::default(),
+        }
+    }
+}
+
+/// Size of an uncapped market order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderSize {
+    /// order_qty, in base
+    Base(f64),
+    /// cash_order_qty, in quote (buys only)
+    Quote(f64),
+}
+
+/// Size a market order for `amount` of the leg's input currency: quote for
+/// buys, base for sells. A buy sized in base needs the best ask (without
+/// one it falls back to its cost); `lot_decimals` is the pair's precision.
+pub fn market_order_size(side: OrderSide, amount: f64, sizing: BuySizing, ask: Option<f64>, lot_decimals: Option<u32>) -> OrderSize {
+    match (side, sizing, ask.filter(|a| *a > 0.0)) {
+        (OrderSide::Sell, _, _) => OrderSize::Base(amount),
+        (OrderSide::Buy, BuySizing::Base, Some(ask)) => {
+            let qty = amount / ask;
+            OrderSize::Base(match lot_decimals {
+                Some(decimals) => {
+                    let lot = 10f64.powi(decimals as i32);
+                    (qty * lot).floor() / lot
+                }
+                None => qty,
+            })
+        }
+        (OrderSide::Buy, _, _) => OrderSize::Quote(amount),
+    }
+}
+
+impl OrderSize {
+    fn params(self, side: OrderSide, cl_ord_id: String) -> v2::OrderParams {
+        match (self, side) {
+            // Spend this much quote currency (e.g., $10 USD)
+            (OrderSize::Quote(cost), _) => v2::OrderParams::market_buy_cost(cost, cl_ord_id),
+            (OrderSize::Base(qty), OrderSide::Buy) => v2::OrderParams::market_buy(qty, cl_ord_id),
+            // Sell this much base currency (e.g., 0.003 ETH)
+            (OrderSize::Base(qty), OrderSide::Sell) => v2::OrderParams::market_sell(qty, cl_ord_id),
+        }
+    }
+}
+
+/// Time in force of cycle legs, by the strategy of the trade. Gtc sends
+/// plain market orders; ioc lets a leg fill what it can at once, and the
+/// next leg trades what actually filled; fok fills the whole leg or fails it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MarketTifPolicy {
+    pub triangular: v2::TimeInForce,
+    pub cross_pair: v2::TimeInForce,
+    pub manual: v2::TimeInForce,
+    pub stablecoin: v2::TimeInForce,
+    pub plugin: v2::TimeInForce,
+}
+
+impl Default for MarketTifPolicy {
+    fn default() -> Self {
+        let gtc = v2::TimeInForce::Gtc;
+        Self { triangular: gtc, cross_pair: gtc, manual: gtc, stablecoin: gtc, plugin: gtc }
+    }
+}
+
+impl MarketTifPolicy {
+    /// Create from MARKET_TIF (every strategy, default gtc) and
+    /// MARKET_TIF_<STRATEGY> for TRIANGULAR, CROSS_PAIR, MANUAL and
+    /// STABLECOIN: gtc, ioc or fok
+    pub fn from_env() -> Self {
+        fn tif(key: &str) -> Option<v2::TimeInForce> {
+            match std::env::var(key).ok()?.trim().to_lowercase().as_str() {
+                "gtc" => Some(v2::TimeInForce::Gtc),
+                "ioc" => Some(v2::TimeInForce::Ioc),
+                "fok" => Some(v2::TimeInForce::Fok),
+                other => {
+                    warn!("Ignoring {}={}: expected gtc, ioc or fok", key, other);
+                    None
+                }
+            }
+        }
+        let all = tif("MARKET_TIF").unwrap_or(v2::TimeInForce::Gtc);
+        let strategy = |name: &str| tif(&format!("MARKET_TIF_{}", name)).unwrap_or(all);
+        Self {
+            triangular: strategy("TRIANGULAR"),
+            cross_pair: strategy("CROSS_PAIR"),
+            manual: strategy("MANUAL"),
+            stablecoin: strategy("STABLECOIN"),
+            plugin: strategy("PLUGIN"),
+        }
+    }
+
+    pub fn for_strategy(&self, strategy: Strategy) -> v2::TimeInForce {
+        match strategy {
+            Strategy::Triangular => self.triangular,
+            Strategy::CrossPair => self.cross_pair,
+            Strategy::Manual => self.manual,
+            Strategy::Stablecoin => self.stablecoin,
+            Strategy::Plugin => self.plugin,
+        }
+    }
+}
+
+/// Part of a leg's input an IOC order left unfilled (None when it all
+/// filled): base for sells, quote for buys
+fn leg_unfilled(side: OrderSide, requested: f64, response: &OrderResponse) -> Option<f64> {
+    if response.status == "filled" {
+        return None;
+    }
+    let used = match side {
+        OrderSide::Buy => response.cum_cost,
+        OrderSide::Sell => response.filled_qty,
+    };
+    Some((requested - used).max(0.0))
+}
+
+/// Kraken's rejection texts when orders come too fast
+const RATE_LIMITED: [&str; 2] = ["Rate limit exceeded", "Too many requests"];
+
+/// Failure kinds a leg may be retried for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// No answer within ORDER_TIMEOUT_MS
+    Timeout,
+    RateLimit,
+    /// Never retried here; see FundsResizePolicy
+    InsufficientFunds,
+    /// Socket down or closed while waiting
+    Connection,
+    /// Any other exchange rejection
+    Rejected,
+}
+
+impl ErrorClass {
+    /// Class of an order failure (None for errors raised before anything
+    /// was sent, like a bad path or a disabled pair)
+    pub fn of(error: &ExecutionError) -> Option<Self> {
+        match error {
+            ExecutionError::Timeout(_) => Some(ErrorClass::Timeout),
+            ExecutionError::NotConnected | ExecutionError::WebSocketError(_) => Some(ErrorClass::Connection),
+            ExecutionError::OrderRejected(msg) if msg.contains(INSUFFICIENT_FUNDS) => Some(ErrorClass::InsufficientFunds),
+            ExecutionError::OrderRejected(msg) if RATE_LIMITED.iter().any(|m| msg.contains(m)) => Some(ErrorClass::RateLimit),
+            ExecutionError::OrderRejected(_) => Some(ErrorClass::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// Retries allowed for one error class
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RetryRule {
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after it
+    pub backoff_ms: u64,
+}
+
+/// Retrying failed legs by error class
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RetryPolicy {
+    pub enabled: bool,
+    pub timeout: RetryRule,
+    pub rate_limit: RetryRule,
+    pub connection: RetryRule,
+    pub rejected: RetryRule,
+    /// Random spread applied to each wait, in percent
+    pub jitter_pct: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: RetryRule { max_retries: 1, backoff_ms: 0 },
+            rate_limit: RetryRule { max_retries: 2, backoff_ms: 500 },
+            connection: RetryRule { max_retries: 1, backoff_ms: 250 },
+            rejected: RetryRule { max_retries: 0, backoff_ms: 0 },
+            jitter_pct: 20.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create from EXECUTION_RETRY (default off) and EXECUTION_RETRY_<CLASS>_MAX /
+    /// EXECUTION_RETRY_<CLASS>_BACKOFF_MS for TIMEOUT, RATE_LIMIT, CONNECTION
+    /// and REJECTED, plus EXECUTION_RETRY_JITTER_PCT
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let rule = |class: &str, default: RetryRule| RetryRule {
+            max_retries: env(&format!("EXECUTION_RETRY_{}_MAX", class)).unwrap_or(default.max_retries),
+            backoff_ms: env(&format!("EXECUTION_RETRY_{}_BACKOFF_MS", class)).unwrap_or(default.backoff_ms),
+        };
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("EXECUTION_RETRY")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            timeout: rule("TIMEOUT", defaults.timeout),
+            rate_limit: rule("RATE_LIMIT", defaults.rate_limit),
+            connection: rule("CONNECTION", defaults.connection),
+            rejected: rule("REJECTED", defaults.rejected),
+            jitter_pct: env("EXECUTION_RETRY_JITTER_PCT")
+                .filter(|v: &f64| (0.0..=100.0).contains(v))
+                .unwrap_or(defaults.jitter_pct),
+        }
+    }
+
+    /// Rule for `class` (insufficient funds never retries)
+    pub fn rule(&self, class: ErrorClass) -> RetryRule {
+        match class {
+            ErrorClass::Timeout => self.timeout,
+            ErrorClass::RateLimit => self.rate_limit,
+            ErrorClass::Connection => self.connection,
+            ErrorClass::Rejected => self.rejected,
+            ErrorClass::InsufficientFunds => RetryRule { max_retries: 0, backoff_ms: 0 },
+        }
+    }
+
+    /// Jittered wait before retry `attempt` (1-based) of `class`
+    pub fn delay(&self, class: ErrorClass, attempt: u32) -> Duration {
+        let base = self.rule(class).backoff_ms.saturating_mul(1u64 << attempt.saturating_sub(1).min(16));
+        let unit: f64 = rand::thread_rng().gen_range(-1.0..=1.0);
+        Duration::from_millis((base as f64 * (1.0 + unit * self.jitter_pct / 100.0)).max(0.0) as u64)
+    }
+}
+
+/// How far fills on one pair with one order type landed from the best
+/// price when they were sent, in bps (positive = better than expected)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PriceImprovementStats {
+    pub pair: String,
+    /// market, market_ioc, market_fok, limit_ioc or limit_fok
+    pub order_type: String,
+    pub fills: u64,
+    pub avg_bps: f64,
+    pub best_bps: f64,
+    pub worst_bps: f64,
+}
+
+impl PriceImprovementStats {
+    fn record(&mut self, bps: f64) {
+        if self.fills == 0 {
+            self.best_bps = bps;
+            self.worst_bps = bps;
+        }
+        self.fills += 1;
+        self.avg_bps += (bps - self.avg_bps) / self.fills as f64;
+        self.best_bps = self.best_bps.max(bps);
+        self.worst_bps = self.worst_bps.min(bps);
+    }
+
+    /// The same figures per order type across every pair
+    pub fn by_order_type(stats: &[PriceImprovementStats]) -> Vec<PriceImprovementStats> {
+        let mut merged: BTreeMap<&str, PriceImprovementStats> = BTreeMap::new();
+        for s in stats {
+            let total = merged.entry(s.order_type.as_str()).or_insert_with(|| PriceImprovementStats {
+                pair: "all".to_string(),
+                order_type: s.order_type.clone(),
+                best_bps: s.best_bps,
+                worst_bps: s.worst_bps,
+                ..Default::default()
+            });
+            let fills = total.fills + s.fills;
+            if fills > 0 {
+                total.avg_bps = (total.avg_bps * total.fills as f64 + s.avg_bps * s.fills as f64) / fills as f64;
+            }
+            total.fills = fills;
+            total.best_bps = total.best_bps.max(s.best_bps);
+            total.worst_bps = total.worst_bps.min(s.worst_bps);
+        }
+        merged.into_values().collect()
+    }
+}
+
+/// Fill price against the best price at submission: below the ask for a
+/// buy, above the bid for a sell counts as improvement
+fn price_improvement_bps(side: OrderSide, expected: f64, fill: f64) -> f64 {
+    let improvement = match side {
+        OrderSide::Buy => expected - fill,
+        OrderSide::Sell => fill - expected,
+    };
+    improvement / expected * 10_000.0
+}
+
+/// The best price at submission that `bps` of improvement was measured
+/// against (inverse of price_improvement_bps)
+fn expected_price(side: OrderSide, fill: f64, bps: f64) -> f64 {
+    match side {
+        OrderSide::Buy => fill / (1.0 - bps / 10_000.0),
+        OrderSide::Sell => fill / (1.0 + bps / 10_000.0),
+    }
+}
+
+/// Name of the order type a leg goes out as, for price improvement stats
+fn order_type_label(capped: bool, time_in_force: v2::TimeInForce) -> &'static str {
+    match (capped, time_in_force) {
+        (true, v2::TimeInForce::Fok) => "limit_fok",
+        (true, _) => "limit_ioc",
+        (false, v2::TimeInForce::Ioc) => "market_ioc",
+        (false, v2::TimeInForce::Fok) => "market_fok",
+        (false, _) => "market",
+    }
+}
+
+/// Retry outcomes for one error class
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct RetryClassStats {
+    /// Retries sent
+    pub retries: u64,
+    /// Legs a retry filled
+    pub recovered: u64,
+    /// Legs that still failed after every retry allowed
+    pub exhausted: u64,
+}
+
+/// What the signal gate does with a leg facing adverse book pressure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignalAction {
+    /// Wait delay_ms, then place the leg anyway
+    Delay,
+    /// Abandon the trade if it's the first leg (later legs are delayed,
+    /// since skipping them would leave the cycle half done)
+    Skip,
+}
+
+/// Hold back legs when imbalance/microprice say the price is about to
+/// move against them
+#[derive(Debug, Clone, Copy)]
+pub struct SignalGatePolicy {
+    pub enabled: bool,
+    /// Adverse pressure (0-1) at or above which a leg is held back
+    pub threshold: f64,
+    pub delay_ms: u64,
+    pub action: SignalAction,
+}
+
+impl Default for SignalGatePolicy {
+    fn default() -> Self {
+        Self { enabled: false, threshold: 0.6, delay_ms: 25, action: SignalAction::Delay }
+    }
+}
+
+impl SignalGatePolicy {
+    /// Create from BOOK_SIGNAL_GATE (default off), BOOK_SIGNAL_THRESHOLD
+    /// (default 0.6), BOOK_SIGNAL_DELAY_MS (default 25) and
+    /// BOOK_SIGNAL_ACTION (delay|skip, default delay)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("BOOK_SIGNAL_GATE")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            threshold: std::env::var("BOOK_SIGNAL_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0 && *v <= 1.0)
+                .unwrap_or(defaults.threshold),
+            delay_ms: std::env::var("BOOK_SIGNAL_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.delay_ms),
+            action: match std::env::var("BOOK_SIGNAL_ACTION").map(|v| v.to_lowercase()).as_deref() {
+                Ok("skip") => SignalAction::Skip,
+                _ => defaults.action,
+            },
+        }
+    }
+}
+
+/// Age limit on an opportunity when its first order is about to go out
+#[derive(Debug, Clone, Copy)]
+pub struct FreshnessPolicy {
+    /// Milliseconds from detection to the first order (0 = no limit)
+    pub max_age_ms: u64,
+}
+
+impl Default for FreshnessPolicy {
+    fn default() -> Self {
+        Self { max_age_ms: 1_000 }
+    }
+}
+
+impl FreshnessPolicy {
+    /// Create from EXEC_MAX_OPPORTUNITY_AGE_MS (default 1000, 0 = off)
+    pub fn from_env() -> Self {
+        Self {
+            max_age_ms: std::env::var("EXEC_MAX_OPPORTUNITY_AGE_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(Self::default().max_age_ms),
+        }
+    }
+}
+
+/// Parse and validate the execution_disabled_pairs JSON from
+/// live_trading_config, e.g. ["DOGE/BTC"]. Pairs are uppercased, sorted and
+/// deduplicated.
+pub fn parse_disabled_pairs(value: &Value) -> Result<Vec<String>, String> {
+    let raw: Vec<String> = serde_json::from_value(value.clone())
+        .map_err(|e| format!("execution_disabled_pairs must be an array of pair names: {}", e))?;
+
+    let mut pairs = Vec::with_capacity(raw.len());
+    for pair in raw {
+        let pair = pair.trim().to_uppercase();
+        match pair.split_once('/') {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() => pairs.push(pair),
+            _ => return Err(format!("execution_disabled_pairs: '{}' is not a BASE/QUOTE pair", pair)),
+        }
+    }
+    pairs.sort();
+    pairs.dedup();
+    Ok(pairs)
+}
 
 // ==========================================
 // Error Types
@@ -52,6 +695,25 @@ pub enum ExecutionError {
     WebSocketError(String),
     #[error("Invalid path format: {0}")]
     InvalidPath(String),
+    #[error("Execution disabled for {pair} (scan-only)")]
+    PairDisabled { pair: String },
+    #[error("Skipped {pair}: adverse book pressure {pressure:.2}")]
+    AdverseSignal { pair: String, pressure: f64 },
+    #[error("Stale opportunity: detected {age_ms}ms before its first order (limit {max_age_ms}ms)")]
+    StaleOpportunity { age_ms: i64, max_age_ms: u64 },
+    #[error("Refused: {pair} realized volatility {vol_bps:.1}bps over the limit")]
+    TooVolatile { pair: String, vol_bps: f64 },
+    #[error("Price cap on {pair}: limit {limit_price} reached with {filled_qty} filled")]
+    PriceCapped { pair: String, limit_price: f64, filled_qty: f64 },
+    #[error("Insufficient {currency}: need {needed:.8}, have {available:.8}")]
+    InsufficientFunds {
+        currency: String,
+        needed: f64,
+        available: f64,
+        /// Both amounts in USD at live rates (None when unpriced)
+        needed_usd: Option<f64>,
+        available_usd: Option<f64>,
+    },
 }
 
 // ==========================================
@@ -65,6 +727,77 @@ pub enum OrderSide {
     Sell,
 }
 
+impl From<OrderSide> for v2::Side {
+    fn from(side: OrderSide) -> Self {
+        match side {
+            OrderSide::Buy => v2::Side::Buy,
+            OrderSide::Sell => v2::Side::Sell,
+        }
+    }
+}
+
+/// Pair and side the scanner recorded for leg `leg`, when the opportunity
+/// carries one entry per leg (manual paths don't)
+fn recorded_leg(opportunity: &Opportunity, leg: usize, legs: usize) -> Option<(String, OrderSide)> {
+    if opportunity.legs_detail.len() != legs {
+        return None;
+    }
+    let detail = &opportunity.legs_detail[leg];
+    let side = match detail.action.as_str() {
+        "buy" => OrderSide::Buy,
+        "sell" => OrderSide::Sell,
+        _ => return None,
+    };
+    Some((detail.pair.clone(), side))
+}
+
+/// A filled leg's proceeds, its fee taken from the side it was charged on
+#[derive(Debug, Clone, PartialEq)]
+struct LegFill {
+    gross_output: f64,
+    /// What the leg delivered: gross_output, less the fee when it was
+    /// charged in the currency received
+    output_amount: f64,
+    fee_currency: String,
+    /// Fee charged in the currency spent, on top of the amount sent
+    input_fee: f64,
+}
+
+/// Split a filled order on `pair` into proceeds and fee. Kraken charges
+/// the fee in the quote currency unless the order asked otherwise, so a
+/// buy's fee comes out of the quote balance, not out of the base received.
+fn leg_fill(pair: &str, side: OrderSide, response: &OrderResponse) -> LegFill {
+    // BUY: We receive base currency (filled_qty)
+    // SELL: We receive quote currency (cum_cost)
+    let gross_output = match side {
+        OrderSide::Buy => response.filled_qty,
+        OrderSide::Sell => {
+            if response.cum_cost > 0.0 {
+                response.cum_cost
+            } else {
+                response.filled_qty * response.avg_price
+            }
+        }
+    };
+
+    let (base, quote) = pair.split_once('/').unwrap_or((pair, ""));
+    let (spent, received) = match side {
+        OrderSide::Buy => (quote, base),
+        OrderSide::Sell => (base, quote),
+    };
+    let fee_currency = response.fee_currency.clone().unwrap_or_else(|| quote.to_string());
+    let mut fill = LegFill { gross_output, output_amount: gross_output, fee_currency, input_fee: 0.0 };
+    if fill.fee_currency == received {
+        fill.output_amount -= response.fee_native;
+    } else if fill.fee_currency == spent {
+        fill.input_fee = response.fee_native;
+    } else if response.fee_native > 0.0 {
+        warn!("{} {} fee of {:.8} {} is in neither currency: counted in USD only",
+            side, pair, response.fee_native, fill.fee_currency);
+    }
+    fill
+}
+
 impl std::fmt::Display for OrderSide {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -75,6 +808,16 @@ impl std::fmt::Display for OrderSide {
 }
 
 
+/// A leg that doesn't depend on the output of another leg in its group
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct GroupLeg {
+    pub leg_index: usize,
+    pub from_currency: String,
+    pub to_currency: String,
+    pub amount: f64,
+}
+
 // ==========================================
 // Result Types
 // ==========================================
@@ -88,10 +831,119 @@ pub struct LegResult {
     pub input_amount: f64,
     pub output_amount: f64,
     pub avg_price: f64,
+    /// USD equivalent of the fee
     pub fee: f64,
+    /// The fee in the currency it was charged in
+    #[serde(default)]
+    pub fee_amount: f64,
+    /// Currency the fee was charged in: the one received comes out of
+    /// output_amount, the one spent was paid on top of input_amount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_currency: Option<String>,
     pub duration_ms: u64,
     pub success: bool,
     pub error: Option<String>,
+    /// Amount originally requested when the leg was retried smaller after
+    /// an insufficient-funds rejection (input_amount is what was sent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resized_from: Option<f64>,
+    /// Part of input_amount an IOC leg left unfilled; it stays in the
+    /// leg's from currency and the next leg trades only what filled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unfilled_amount: Option<f64>,
+    /// avg_price against the best price when the order was sent, in bps
+    /// (positive = filled better than expected)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_improvement_bps: Option<f64>,
+    /// Per-venue fills of a leg split across several books of its pair.
+    /// Empty for a single-venue leg: its totals are its one fill.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fills: Vec<FillSegment>,
+}
+
+/// Venue of every order until more than one exchange is supported
+pub const DEFAULT_VENUE: &str = "kraken";
+
+/// One venue's part of a leg. Amounts are in the leg's currencies; the
+/// leg's input, output and fee are the sums over its segments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillSegment {
+    pub venue: String,
+    pub order_id: String,
+    /// Base quantity filled
+    pub volume: f64,
+    pub input_amount: f64,
+    pub output_amount: f64,
+    pub avg_price: f64,
+    /// USD equivalent of the fee
+    pub fee: f64,
+    /// The fee in the currency it was charged in
+    #[serde(default)]
+    pub fee_amount: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_currency: Option<String>,
+    /// Best price on this venue when the order was sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_price: Option<f64>,
+}
+
+impl LegResult {
+    fn order_side(&self) -> OrderSide {
+        if self.side == "buy" { OrderSide::Buy } else { OrderSide::Sell }
+    }
+
+    /// The leg's fills, a single-venue leg as one segment on DEFAULT_VENUE
+    pub fn segments(&self) -> Vec<FillSegment> {
+        if !self.fills.is_empty() {
+            return self.fills.clone();
+        }
+        let side = self.order_side();
+        let volume = match side {
+            OrderSide::Buy if self.avg_price > 0.0 => self.input_amount / self.avg_price,
+            OrderSide::Buy => 0.0,
+            OrderSide::Sell => self.input_amount,
+        };
+        vec![FillSegment {
+            venue: DEFAULT_VENUE.to_string(),
+            order_id: self.order_id.clone(),
+            volume,
+            input_amount: self.input_amount,
+            output_amount: self.output_amount,
+            avg_price: self.avg_price,
+            fee: self.fee,
+            fee_amount: self.fee_amount,
+            fee_currency: self.fee_currency.clone(),
+            expected_price: self.price_improvement_bps.map(|bps| expected_price(side, self.avg_price, bps)),
+        }]
+    }
+
+    /// Fill against the best price when the orders were sent, in bps,
+    /// across all of the leg's segments
+    pub fn improvement_bps(&self) -> Option<f64> {
+        blended_improvement_bps(self.order_side(), &self.segments())
+    }
+}
+
+/// Volume-weighted price across `segments` (None when nothing filled)
+pub fn blended_price(segments: &[FillSegment]) -> Option<f64> {
+    let volume: f64 = segments.iter().filter(|s| s.avg_price > 0.0).map(|s| s.volume).sum();
+    if volume <= 0.0 {
+        return None;
+    }
+    let notional: f64 = segments.iter().filter(|s| s.avg_price > 0.0).map(|s| s.avg_price * s.volume).sum();
+    Some(notional / volume)
+}
+
+/// Slippage of a split leg: its blended price against the best price on
+/// any of its venues when the orders were sent, so routing to a worse book
+/// counts against it. With one segment this is `price_improvement_bps`.
+pub fn blended_improvement_bps(side: OrderSide, segments: &[FillSegment]) -> Option<f64> {
+    let expected = segments.iter().filter_map(|s| s.expected_price).filter(|p| *p > 0.0);
+    let best = match side {
+        OrderSide::Buy => expected.reduce(f64::min),
+        OrderSide::Sell => expected.reduce(f64::max),
+    }?;
+    Some(price_improvement_bps(side, best, blended_price(segments)?))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +960,8 @@ pub struct TradeResult {
     pub success: bool,
     pub error: Option<String>,
     pub executed_at: DateTime<Utc>,
+    pub strategy: Strategy,
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -120,26 +974,128 @@ pub struct OrderResponse {
     pub cum_cost: f64,  // Cumulative cost (quote currency spent for BUY orders)
     pub fee: f64,       // Fee in USD equivalent (for tracking/reporting)
     pub fee_native: f64, // Fee in native currency (for amount adjustment)
+    /// Currency fee_native is in (None = not reported, the quote currency)
+    pub fee_currency: Option<String>,
     #[allow(dead_code)]
     pub error: Option<String>,
+    /// Fill against the best price when the order was sent, in bps
+    /// (positive = better; None when there was no price or no fill)
+    pub price_improvement_bps: Option<f64>,
+}
+
+/// Order and amend counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExecutionStats {
+    pub orders_sent: u64,
+    pub orders_filled: u64,
+    pub orders_failed: u64,
+    pub orders_timed_out: u64,
+    pub amends_sent: u64,
+    pub amends_succeeded: u64,
+    pub amends_failed: u64,
+    /// amends_succeeded / (amends_succeeded + amends_failed), None before any amend completed
+    pub amend_success_rate: Option<f64>,
+    /// Post-only orders sent, those that filled completely, and rejected
+    /// amends that fell back to cancel + replace
+    pub maker_orders_attempted: u64,
+    pub maker_orders_filled: u64,
+    pub maker_replacements: u64,
+    /// Legs held back / trades abandoned by the book signal gate
+    pub signal_delays: u64,
+    pub signal_skips: u64,
+    /// Trades abandoned because the opportunity was too old for its first order
+    pub stale_opportunities: u64,
+    /// Capped orders that expired unfilled or partly filled at their limit
+    pub orders_price_capped: u64,
+    /// IOC legs that completed with part of their size
+    pub orders_partially_filled: u64,
+    /// Leg retries by error class (only classes that were retried)
+    pub retries: HashMap<ErrorClass, RetryClassStats>,
+    /// Fill price against the best price at submission, by pair and order type
+    pub price_improvement: Vec<PriceImprovementStats>,
+    /// Pending orders dropped by the cleanup task after nobody waited for them
+    pub orders_swept: u64,
 }
 
 // ==========================================
 // Internal Types
 // ==========================================
 
-#[allow(dead_code)]
+/// Shared state the private message handler updates
+struct MessageContext {
+    pending_orders: Arc<RwLock<HashMap<String, PendingOrder>>>,
+    pending_amends: Arc<RwLock<HashMap<u64, AmendAck>>>,
+    is_connected: Arc<AtomicBool>,
+    orders_filled: Arc<AtomicU64>,
+    orders_failed: Arc<AtomicU64>,
+    amends_succeeded: Arc<AtomicU64>,
+    amends_failed: Arc<AtomicU64>,
+    fill_journal: Arc<FillJournal>,
+    chaos: Arc<ChaosMonkey>,
+}
+
 struct PendingOrder {
     order_id: String,
     client_id: String,
     response_tx: oneshot::Sender<OrderResponse>,
-    created_at: Instant,
+    /// Tokio's clock, so tests can age orders with paused time
+    created_at: tokio::time::Instant,
 }
 
 // ==========================================
 // Execution Engine
 // ==========================================
 
+/// A trade between the start of its first leg and the end of its last
+struct InFlightTrade {
+    path: String,
+    started_at: DateTime<Utc>,
+    /// Leg being placed (0-based)
+    leg: AtomicUsize,
+    /// cl_ord_id of the order the leg is waiting on
+    open_order: parking_lot::Mutex<Option<String>>,
+    aborted: AtomicBool,
+    /// Time in force its legs are sent with
+    time_in_force: v2::TimeInForce,
+}
+
+/// An in-flight trade for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct InFlightStatus {
+    pub trade_id: String,
+    pub path: String,
+    /// Leg being placed (1-based)
+    pub leg: usize,
+    pub started_at: DateTime<Utc>,
+    pub aborting: bool,
+}
+
+/// What an abort request did
+#[derive(Debug, Clone, Serialize)]
+pub struct AbortRequest {
+    pub trade_id: String,
+    pub path: String,
+    /// Leg the trade was on (1-based)
+    pub leg: usize,
+    /// cancel_order went out for the leg's open order
+    pub cancel_sent: bool,
+    /// The trade had already been asked to abort
+    pub already_aborting: bool,
+}
+
+/// Removes a trade from the in-flight list however execute_legs returns
+struct InFlightGuard<'a> {
+    trades: &'a parking_lot::Mutex<HashMap<String, Arc<InFlightTrade>>>,
+    trade_id: String,
+    trade: Arc<InFlightTrade>,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.trades.lock().remove(&self.trade_id);
+    }
+}
+
 pub struct ExecutionEngine {
     auth: Arc<KrakenAuth>,
     cache: Arc<OrderBookCache>,
@@ -150,6 +1106,9 @@ pub struct ExecutionEngine {
     
     // Pending orders - using tokio async locks
     pending_orders: Arc<RwLock<HashMap<String, PendingOrder>>>,
+
+    // Pending amend_order acknowledgements by req_id (Err = rejection message)
+    pending_amends: Arc<RwLock<HashMap<u64, AmendAck>>>,
     
     // Request ID counter (atomic - no lock needed)
     req_id_counter: AtomicU64,
@@ -159,6 +1118,80 @@ pub struct ExecutionEngine {
     orders_filled: Arc<AtomicU64>,
     orders_failed: Arc<AtomicU64>,
     orders_timed_out: Arc<AtomicU64>,
+    orders_swept: Arc<AtomicU64>,
+    amends_sent: AtomicU64,
+    amends_succeeded: Arc<AtomicU64>,
+    amends_failed: Arc<AtomicU64>,
+    // Resting post-only single legs (off by default)
+    maker: MakerPolicy,
+    maker_orders_attempted: AtomicU64,
+    maker_orders_filled: AtomicU64,
+    maker_replacements: AtomicU64,
+
+    // Order request/response trail (None = not audited)
+    audit: Option<AuditLog>,
+
+    // Every executions-channel message, matched to a pending order or not
+    fill_journal: Arc<FillJournal>,
+    // Leg intents and fills, replayed after a crash (disabled by default)
+    trade_wal: Arc<TradeWal>,
+
+    // Retry-smaller behaviour for insufficient-funds rejections
+    resize_policy: FundsResizePolicy,
+    // Exchange minimums by pair: (ordermin in base, costmin in quote)
+    order_minimums: HashMap<String, (f64, f64)>,
+    // Leg timing by book imbalance/microprice
+    signal_gate: SignalGatePolicy,
+    prefund: PrefundPolicy,
+    // Marketable limits instead of market orders (off by default)
+    price_cap: PriceCapPolicy,
+    // Market buys in quote (cash_order_qty) or base
+    buy_sizing: BuySizing,
+    // Tick and lot precision by pair: (price decimals, lot decimals)
+    order_precision: HashMap<String, (u32, u32)>,
+    orders_price_capped: AtomicU64,
+    // Recent prints per pair: volatility limits and caps (None = unused)
+    trade_feed: Option<Arc<TradeFeed>>,
+    // Time in force of cycle legs by strategy
+    market_tif: MarketTifPolicy,
+    orders_partially_filled: AtomicU64,
+    signal_delays: AtomicU64,
+    signal_skips: AtomicU64,
+    // Opportunity age limit at the first order
+    freshness: FreshnessPolicy,
+    stale_opportunities: AtomicU64,
+    // Retrying failed legs by error class
+    retry_policy: RetryPolicy,
+    retry_stats: parking_lot::Mutex<HashMap<ErrorClass, RetryClassStats>>,
+    // Fill vs best price at submission by (pair, order type)
+    price_improvement: parking_lot::Mutex<BTreeMap<(String, &'static str), PriceImprovementStats>>,
+    // Pairs that are scanned but never traded
+    execution_disabled: parking_lot::RwLock<HashSet<String>>,
+    // Trades being executed, by trade id, so an operator can abort one
+    in_flight: parking_lot::Mutex<HashMap<String, Arc<InFlightTrade>>>,
+
+    // Opens the private socket (tokio-tungstenite outside tests)
+    transport: Arc<dyn PrivateTransport>,
+    // Backoff for reconnecting the private socket
+    reconnect: Arc<ReconnectTracker>,
+    // Set on drop so the reconnect supervisor stops
+    closed: Arc<AtomicBool>,
+    // Fault injection for chaos testing (disabled by default)
+    chaos: Arc<ChaosMonkey>,
+    // Runtime the socket tasks run on (None = the caller's)
+    runtime: Option<Handle>,
+    // Restarts the socket reader after a panic (None = a panic ends it)
+    supervisor: Option<Arc<TaskSupervisor>>,
+
+    // Scripted exchange answering orders instead of the WebSocket
+    #[cfg(test)]
+    fake_backend: Option<Arc<FakeExecutionBackend>>,
+}
+
+impl Drop for ExecutionEngine {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
 }
 
 // Ensure ExecutionEngine is Send + Sync for async handlers
@@ -174,100 +1207,423 @@ impl ExecutionEngine {
             is_connected: Arc::new(AtomicBool::new(false)),
             ws_tx: Arc::new(RwLock::new(None)),
             pending_orders: Arc::new(RwLock::new(HashMap::new())),
+            pending_amends: Arc::new(RwLock::new(HashMap::new())),
             req_id_counter: AtomicU64::new(1),
             orders_sent: Arc::new(AtomicU64::new(0)),
             orders_filled: Arc::new(AtomicU64::new(0)),
             orders_failed: Arc::new(AtomicU64::new(0)),
             orders_timed_out: Arc::new(AtomicU64::new(0)),
+            orders_swept: Arc::new(AtomicU64::new(0)),
+            amends_sent: AtomicU64::new(0),
+            amends_succeeded: Arc::new(AtomicU64::new(0)),
+            amends_failed: Arc::new(AtomicU64::new(0)),
+            maker: MakerPolicy::default(),
+            maker_orders_attempted: AtomicU64::new(0),
+            maker_orders_filled: AtomicU64::new(0),
+            maker_replacements: AtomicU64::new(0),
+            audit: None,
+            fill_journal: Arc::new(FillJournal::new(None)),
+            trade_wal: Arc::new(TradeWal::disabled()),
+            resize_policy: FundsResizePolicy::default(),
+            order_minimums: HashMap::new(),
+            signal_gate: SignalGatePolicy::default(),
+            prefund: PrefundPolicy::default(),
+            price_cap: PriceCapPolicy::default(),
+            buy_sizing: BuySizing::default(),
+            order_precision: HashMap::new(),
+            orders_price_capped: AtomicU64::new(0),
+            trade_feed: None,
+            market_tif: MarketTifPolicy::default(),
+            orders_partially_filled: AtomicU64::new(0),
+            signal_delays: AtomicU64::new(0),
+            signal_skips: AtomicU64::new(0),
+            freshness: FreshnessPolicy::default(),
+            stale_opportunities: AtomicU64::new(0),
+            retry_policy: RetryPolicy::default(),
+            retry_stats: parking_lot::Mutex::new(HashMap::new()),
+            price_improvement: parking_lot::Mutex::new(BTreeMap::new()),
+            execution_disabled: parking_lot::RwLock::new(HashSet::new()),
+            in_flight: parking_lot::Mutex::new(HashMap::new()),
+            transport: Arc::new(TungsteniteTransport),
+            reconnect: Arc::new(ReconnectTracker::new("private", ReconnectPolicy::default())),
+            closed: Arc::new(AtomicBool::new(false)),
+            chaos: Arc::new(ChaosMonkey::default()),
+            runtime: None,
+            supervisor: None,
+            #[cfg(test)]
+            fake_backend: None,
         }
     }
-    
-    /// Check if connected
-    pub fn is_connected(&self) -> bool {
-        self.is_connected.load(Ordering::Relaxed)
+
+    /// Create an engine whose orders are answered by a scripted backend
+    #[cfg(test)]
+    pub fn with_fake_backend(cache: Arc<OrderBookCache>, backend: Arc<FakeExecutionBackend>) -> Self {
+        let mut engine = Self::new(Arc::new(KrakenAuth::new_public_only()), cache);
+        engine.is_connected.store(true, Ordering::Relaxed);
+        engine.fake_backend = Some(backend);
+        engine
     }
-    
-    /// Get next request ID
-    fn next_req_id(&self) -> u64 {
-        self.req_id_counter.fetch_add(1, Ordering::Relaxed)
+
+    /// Open the private socket through `transport` (before connect)
+    #[cfg(test)]
+    pub fn with_transport(mut self, transport: Arc<dyn PrivateTransport>) -> Self {
+        self.transport = transport;
+        self
     }
 
-    /// Connect to Kraken WebSocket
+    /// Record every order request and response in the audit log
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Journal executions into a shared fill journal (before connect)
+    pub fn with_fill_journal(mut self, journal: Arc<FillJournal>) -> Self {
+        self.fill_journal = journal;
+        self
+    }
+
+    /// Journal each trade's legs to a write-ahead log
+    pub fn with_trade_wal(mut self, wal: Arc<TradeWal>) -> Self {
+        self.trade_wal = wal;
+        self
+    }
+
+    /// Retry legs rejected for insufficient funds with the available balance
+    pub fn with_resize_policy(mut self, policy: FundsResizePolicy) -> Self {
+        self.resize_policy = policy;
+        self
+    }
+
+    /// Exchange minimums a resized order must still meet
+    pub fn with_order_minimums(mut self, minimums: HashMap<String, (f64, f64)>) -> Self {
+        self.order_minimums = minimums;
+        self
+    }
+
+    /// Delay or skip legs facing adverse book pressure
+    pub fn with_signal_gate(mut self, policy: SignalGatePolicy) -> Self {
+        self.signal_gate = policy;
+        self
+    }
+
+    /// Abandon auto-executed opportunities too old to trade by their first order
+    pub fn with_freshness(mut self, policy: FreshnessPolicy) -> Self {
+        self.freshness = policy;
+        self
+    }
+
+    /// Check the balance covers the first leg before sending it
+    pub fn with_prefund_check(mut self, policy: PrefundPolicy) -> Self {
+        self.prefund = policy;
+        self
+    }
+
+    /// Rest single legs as post-only orders before going to market
+    pub fn with_maker_policy(mut self, policy: MakerPolicy) -> Self {
+        self.maker = policy;
+        self
+    }
+
+    /// Send orders as marketable limits no further than `policy` allows from
+    /// the best price; pairs without a known precision still go at market
+    pub fn with_price_cap(mut self, policy: PriceCapPolicy, precision: HashMap<String, (u32, u32)>) -> Self {
+        self.price_cap = policy;
+        self.order_precision = precision;
+        self
+    }
+
+    /// Size market buys in quote (cash_order_qty) or in base at the best ask
+    pub fn with_buy_sizing(mut self, sizing: BuySizing) -> Self {
+        self.buy_sizing = sizing;
+        self
+    }
+
+    /// Send cycle legs with the time in force `policy` gives their strategy
+    pub fn with_market_tif(mut self, policy: MarketTifPolicy) -> Self {
+        self.market_tif = policy;
+        self
+    }
+
+    /// Refuse or cap legs on pairs by their realized volatility, as the
+    /// feed's policy says
+    pub fn with_trade_feed(mut self, feed: Arc<TradeFeed>) -> Self {
+        self.trade_feed = Some(feed);
+        self
+    }
+
+    /// Retry failed legs according to `policy`
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Replace the set of scan-only pairs (takes effect for the next trade)
+    pub fn set_execution_disabled(&self, pairs: HashSet<String>) {
+        *self.execution_disabled.write() = pairs;
+    }
+
+    /// First leg pair of `path` that is execution-disabled
+    pub fn disabled_pair_on(&self, path: &str) -> Option<String> {
+        let disabled = self.execution_disabled.read();
+        if disabled.is_empty() {
+            return None;
+        }
+        let currencies: Vec<&str> = path.split(" → ").collect();
+        currencies
+            .windows(2)
+            .filter_map(|leg| self.determine_pair_and_side(leg[0], leg[1]).ok())
+            .map(|(pair, _)| pair)
+            .find(|pair| disabled.contains(pair))
+    }
+
+    /// First leg pair of `path` more volatile than the trade feed allows,
+    /// with its realized volatility in bps
+    pub fn volatile_pair_on(&self, path: &str) -> Option<(String, f64)> {
+        let feed = self.trade_feed.as_ref().filter(|f| f.policy().max_vol_bps > 0.0)?;
+        let now = Utc::now();
+        let currencies: Vec<&str> = path.split(" → ").collect();
+        currencies
+            .windows(2)
+            .filter_map(|leg| self.determine_pair_and_side(leg[0], leg[1]).ok())
+            .find_map(|(pair, _)| {
+                let vol_bps = feed.realized_vol(&pair, now)?.vol_bps;
+                (vol_bps > feed.policy().max_vol_bps).then_some((pair, vol_bps))
+            })
+    }
+
+    /// Use a shared reconnect tracker (before connect)
+    pub fn with_reconnect(mut self, tracker: Arc<ReconnectTracker>) -> Self {
+        self.reconnect = tracker;
+        self
+    }
+    
+    /// Inject faults from a shared chaos layer (before connect)
+    pub fn with_chaos(mut self, chaos: Arc<ChaosMonkey>) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    /// Run the socket reader and sender on `runtime` (before connect)
+    pub fn with_runtime(mut self, runtime: Handle) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Restart the socket reader under `supervisor` when it panics (before connect)
+    pub fn with_supervisor(mut self, supervisor: Arc<TaskSupervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+    
+    /// Check if connected
+    pub fn is_connected(&self) -> bool {
+        self.is_connected.load(Ordering::Relaxed)
+    }
+    
+    /// Get next request ID
+    fn next_req_id(&self) -> u64 {
+        self.req_id_counter.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Connect to Kraken WebSocket
+    ///
+    /// The first connect must succeed. After that a supervisor task
+    /// reconnects whenever the socket drops, following the ReconnectPolicy.
     pub async fn connect(&self) -> Result<(), ExecutionError> {
+        let read = Self::open_session(&*self.transport, &self.auth, &self.ws_tx, &self.is_connected, self.runtime.as_ref()).await?;
+        self.reconnect.connected();
+
+        let ctx = Arc::new(MessageContext {
+            pending_orders: Arc::clone(&self.pending_orders),
+            pending_amends: Arc::clone(&self.pending_amends),
+            is_connected: Arc::clone(&self.is_connected),
+            orders_filled: Arc::clone(&self.orders_filled),
+            orders_failed: Arc::clone(&self.orders_failed),
+            amends_succeeded: Arc::clone(&self.amends_succeeded),
+            amends_failed: Arc::clone(&self.amends_failed),
+            fill_journal: Arc::clone(&self.fill_journal),
+            chaos: Arc::clone(&self.chaos),
+        });
+        self.spawn_cleanup();
+        let first_session = Arc::new(parking_lot::Mutex::new(Some(read)));
+        let transport = Arc::clone(&self.transport);
+        let auth = Arc::clone(&self.auth);
+        let ws_tx = Arc::clone(&self.ws_tx);
+        let reconnect = Arc::clone(&self.reconnect);
+        let closed = Arc::clone(&self.closed);
+        let runtime = self.runtime.clone();
+
+        spawn_supervised(self.supervisor.as_ref(), self.runtime.as_ref(), "private_ws_reader", move || {
+            let first_session = Arc::clone(&first_session);
+            let ctx = Arc::clone(&ctx);
+            let transport = Arc::clone(&transport);
+            let auth = Arc::clone(&auth);
+            let ws_tx = Arc::clone(&ws_tx);
+            let reconnect = Arc::clone(&reconnect);
+            let closed = Arc::clone(&closed);
+            let runtime = runtime.clone();
+            async move {
+                // A restart after a panic opens a fresh session
+                let mut read = first_session.lock().take();
+                let mut reason = "reader restarted after a panic".to_string();
+                loop {
+                    if let Some(session) = read.take() {
+                        reason = Self::read_messages(session, &ctx).await;
+                    }
+
+                    // Reconnect until a session opens, the policy gives up or the engine is dropped
+                    read = Some(loop {
+                        if closed.load(Ordering::SeqCst) {
+                            return;
+                        }
+                        let Some(delay) = reconnect.disconnected(&reason) else {
+                            error!("Private WebSocket giving up after repeated reconnect failures");
+                            return;
+                        };
+                        warn!("Private WebSocket disconnected ({}), reconnecting in {}ms...", reason, delay.as_millis());
+                        tokio::time::sleep(delay).await;
+
+                        match Self::open_session(&*transport, &auth, &ws_tx, &ctx.is_connected, runtime.as_ref()).await {
+                            Ok(read) => {
+                                reconnect.connected();
+                                break read;
+                            }
+                            Err(e) => reason = e.to_string(),
+                        }
+                    });
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Drop pending orders nobody waits for any more (a caller that was
+    /// cancelled mid-order leaves its entry behind) until the engine is dropped
+    fn spawn_cleanup(&self) {
+        let pending_orders = Arc::clone(&self.pending_orders);
+        let orders_swept = Arc::clone(&self.orders_swept);
+        let closed = Arc::clone(&self.closed);
+        spawn_on(self.runtime.as_ref(), async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(CLEANUP_INTERVAL_MS));
+            loop {
+                interval.tick().await;
+                if closed.load(Ordering::SeqCst) {
+                    return;
+                }
+                let swept = sweep_stale_orders(&pending_orders, Duration::from_millis(STALE_ORDER_MS)).await;
+                if !swept.is_empty() {
+                    orders_swept.fetch_add(swept.len() as u64, Ordering::Relaxed);
+                    warn!("Dropped {} stale pending orders: {:?}", swept.len(), swept);
+                }
+            }
+        });
+    }
+
+    /// Open the private socket, subscribe to executions and start the
+    /// sender task. Returns the read half for the message handler.
+    async fn open_session(
+        transport: &dyn PrivateTransport,
+        auth: &KrakenAuth,
+        ws_tx: &RwLock<Option<mpsc::UnboundedSender<String>>>,
+        is_connected: &Arc<AtomicBool>,
+        runtime: Option<&Handle>,
+    ) -> Result<FrameStream, ExecutionError> {
         info!("Connecting to Kraken private WebSocket...");
         
-        let token = self.auth
+        let token = auth
             .get_ws_token()
             .await
             .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
         
-        let (ws_stream, _) = connect_async(get_kraken_ws_private_url())
+        let session = transport
+            .connect(&get_kraken_ws_private_url())
             .await
-            .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
-        
-        let (mut write, mut read) = ws_stream.split();
+            .map_err(ExecutionError::WebSocketError)?;
+        let (mut write, read) = (session.sink, session.stream);
         
         // Create channel for sending messages
         let (tx, mut rx) = mpsc::unbounded_channel::<String>();
         
         // Store sender
-        *self.ws_tx.write().await = Some(tx);
+        *ws_tx.write().await = Some(tx);
         
         // Authenticate
-        let auth_msg = json!({
-            "method": "subscribe",
-            "params": {
-                "channel": "executions",
-                "token": token,
-                "snap_trades": false
-            }
-        });
+        let auth_msg = v2::Request::new(v2::Method::Subscribe(v2::SubscribeParams::executions(token)));
         
-        write.send(Message::Text(auth_msg.to_string()))
+        write.send(auth_msg.to_json())
             .await
-            .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
+            .map_err(ExecutionError::WebSocketError)?;
         
-        self.is_connected.store(true, Ordering::SeqCst);
+        is_connected.store(true, Ordering::SeqCst);
         info!("Connected to Kraken private WebSocket");
         
-        // Spawn message handler
-        let pending_orders = Arc::clone(&self.pending_orders);
-        let is_connected = Arc::clone(&self.is_connected);
-        let orders_filled = Arc::clone(&self.orders_filled);
-        let orders_failed = Arc::clone(&self.orders_failed);
+        // Spawn sender task
+        let is_connected_sender = Arc::clone(is_connected);
+        spawn_on(runtime, async move {
+            while let Some(msg) = rx.recv().await {
+                if write.send(msg).await.is_err() {
+                    is_connected_sender.store(false, Ordering::SeqCst);
+                    break;
+                }
+            }
+        });
         
-        tokio::spawn(async move {
-            while let Some(msg) = read.next().await {
-                match msg {
-                    Ok(Message::Text(text)) => {
-                        // Log all private WS messages for debugging
-                        debug!("Private WS received: {}", text);
-
-                        if let Ok(json) = serde_json::from_str::<Value>(&text) {
-                            // Log important messages
-                            if let Some(method) = json.get("method").and_then(|m| m.as_str()) {
-                                if method == "subscribe" {
-                                    if json.get("success").and_then(|s| s.as_bool()) == Some(true) {
-                                        info!("Subscribed to executions channel");
-                                    } else {
-                                        warn!("Failed to subscribe to executions: {:?}", json);
-                                    }
+        Ok(read)
+    }
+
+    /// Handle private socket messages until the connection drops.
+    /// Returns the disconnect reason.
+    async fn read_messages(mut read: FrameStream, ctx: &MessageContext) -> String {
+        let MessageContext {
+            pending_orders,
+            pending_amends,
+            is_connected,
+            orders_filled,
+            orders_failed,
+            amends_succeeded,
+            amends_failed,
+            fill_journal,
+            chaos,
+        } = ctx;
+
+        while let Some(msg) = read.next().await {
+            match msg {
+                Ok(Frame::Text(text)) => {
+                    if let Some(delay) = chaos.ack_delay() {
+                        debug!("Chaos: holding private message back {}ms", delay.as_millis());
+                        tokio::time::sleep(delay).await;
+                    }
+
+                    // Log all private WS messages for debugging
+                    debug!("Private WS received: {}", text);
+
+                    if let Ok(json) = serde_json::from_str::<Value>(&text) {
+                        match v2::MethodResponse::from_value(&json) {
+                            Some(ack) if ack.method == "subscribe" => {
+                                if ack.success {
+                                    info!("Subscribed to executions channel");
+                                } else {
+                                    warn!("Failed to subscribe to executions: {:?}", json);
                                 }
                             }
 
                             // Handle add_order responses
-                            if json.get("method").and_then(|m| m.as_str()) == Some("add_order") {
-                                if json.get("success").and_then(|s| s.as_bool()) == Some(true) {
-                                    info!("Order placed: {:?}", json.get("result"));
+                            Some(ack) if ack.method == "add_order" => {
+                                if ack.success {
+                                    info!("Order placed: {:?}", ack.result);
+                                    if let Some(req_id) = ack.req_id {
+                                        let order_id = ack.result.as_ref().and_then(|r| r.get("order_id")).and_then(|v| v.as_str());
+                                        record_order_id(pending_orders, &format!("arb_{}", req_id), order_id).await;
+                                    }
                                 } else {
                                     // Order rejected - complete pending order immediately
-                                    let error_msg = json.get("error")
-                                        .and_then(|e| e.as_str())
-                                        .unwrap_or("Order rejected");
+                                    let error_msg = ack.error_or("Order rejected");
                                     warn!("Order rejected: {}", error_msg);
 
                                     // Find the pending order by req_id and complete it with error
-                                    if let Some(req_id) = json.get("req_id").and_then(|r| r.as_u64()) {
+                                    if let Some(req_id) = ack.req_id {
                                         let client_id = format!("arb_{}", req_id);
                                         let mut orders = pending_orders.write().await;
                                         if let Some(pending) = orders.remove(&client_id) {
@@ -280,7 +1636,9 @@ impl ExecutionEngine {
                                                 cum_cost: 0.0,
                                                 fee: 0.0,
                                                 fee_native: 0.0,
+                                                fee_currency: None,
                                                 error: Some(error_msg.to_string()),
+                                                price_improvement_bps: None,
                                             };
                                             let _ = pending.response_tx.send(response);
                                         }
@@ -288,235 +1646,1206 @@ impl ExecutionEngine {
                                 }
                             }
 
-                            // Handle execution updates
-                            if json.get("channel").and_then(|c| c.as_str()) == Some("executions") {
-                                info!("Raw execution data: {}", serde_json::to_string(&json).unwrap_or_default());
-                                if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-                                    for exec in data {
-                                        let order_id = exec.get("order_id")
-                                            .and_then(|o| o.as_str())
-                                            .unwrap_or("");
-                                        let cl_ord_id = exec.get("cl_ord_id")
-                                            .and_then(|o| o.as_str())
-                                            .unwrap_or("");
-                                        let status = exec.get("order_status")
-                                            .and_then(|s| s.as_str())
-                                            .unwrap_or("");
-                                        let exec_type = exec.get("exec_type")
-                                            .and_then(|e| e.as_str())
-                                            .unwrap_or("");
-
-                                        // Helper to parse value as f64 (handles both string and number)
-                                        fn parse_f64(v: &serde_json::Value) -> f64 {
-                                            v.as_f64()
-                                                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
-                                                .unwrap_or(0.0)
+                            // Handle batch_add responses - a rejected batch fails every order in it
+                            Some(ack) if ack.method == "batch_add" => {
+                                if ack.success {
+                                    info!("Batch placed: {:?}", ack.result);
+                                    for placed in ack.result.as_ref().and_then(|r| r.as_array()).into_iter().flatten() {
+                                        if let Some(client_id) = placed.get("cl_ord_id").and_then(|v| v.as_str()) {
+                                            record_order_id(pending_orders, client_id, placed.get("order_id").and_then(|v| v.as_str())).await;
                                         }
+                                    }
+                                } else if let Some(req_id) = ack.req_id {
+                                    let error_msg = ack.error_or("Batch rejected");
+                                    warn!("Batch {} rejected: {}", req_id, error_msg);
 
-                                        // Parse quantity - cum_qty is cumulative filled quantity
-                                        let cum_qty = exec.get("cum_qty")
-                                            .map(parse_f64)
-                                            .unwrap_or(0.0);
-
-                                        // Parse avg_price for overall order
-                                        let avg_price = exec.get("avg_price")
-                                            .map(parse_f64)
-                                            .unwrap_or(0.0);
-
-                                        // Parse cumulative cost (quote currency spent for BUY orders)
-                                        let cum_cost = exec.get("cum_cost")
-                                            .map(parse_f64)
-                                            .unwrap_or(0.0);
-
-                                        // Parse fees - Kraken v2 uses fee_usd_equiv for total USD fees
-                                        let fee = exec.get("fee_usd_equiv")
-                                            .map(parse_f64)
-                                            .unwrap_or(0.0);
-
-                                        // Parse native currency fee from fees array
-                                        // This is needed to calculate NET amounts for each leg
-                                        let fee_native = exec.get("fees")
-                                            .and_then(|f| f.as_array())
-                                            .map(|fees| {
-                                                fees.iter()
-                                                    .filter_map(|fee_item| {
-                                                        fee_item.get("qty").map(parse_f64)
-                                                    })
-                                                    .sum()
-                                            })
-                                            .unwrap_or(0.0);
-
-                                        // For individual trade events, also track last fill
-                                        let last_qty = exec.get("last_qty")
-                                            .map(parse_f64)
-                                            .unwrap_or(0.0);
-                                        let last_price = exec.get("last_price")
-                                            .map(parse_f64)
-                                            .unwrap_or(0.0);
-
-                                        info!("Execution update: order={}, cl_ord={}, status={}, exec_type={}, cum_qty={}, cum_cost={}, avg_price={}, fee={}, last_qty={}, last_price={}",
-                                              order_id, cl_ord_id, status, exec_type, cum_qty, cum_cost, avg_price, fee, last_qty, last_price);
-
-                                        // Check if order is complete (filled, canceled, or expired)
-                                        if status == "filled" || status == "canceled" || status == "expired" {
-                                            let mut orders = pending_orders.write().await;
-                                            if let Some(pending) = orders.remove(cl_ord_id) {
-                                                let response = OrderResponse {
-                                                    order_id: order_id.to_string(),
-                                                    status: status.to_string(),
-                                                    filled_qty: cum_qty,
-                                                    avg_price,
-                                                    cum_cost,
-                                                    fee,
-                                                    fee_native,
-                                                    error: if status != "filled" {
-                                                        Some(format!("Order {}", status))
-                                                    } else {
-                                                        None
-                                                    },
-                                                };
-
-                                                if status == "filled" {
-                                                    orders_filled.fetch_add(1, Ordering::Relaxed);
+                                    let prefix = format!("arb_{}_", req_id);
+                                    let mut orders = pending_orders.write().await;
+                                    let batch_ids: Vec<String> = orders.keys()
+                                        .filter(|id| id.starts_with(&prefix))
+                                        .cloned()
+                                        .collect();
+                                    for client_id in batch_ids {
+                                        if let Some(pending) = orders.remove(&client_id) {
+                                            orders_failed.fetch_add(1, Ordering::Relaxed);
+                                            let _ = pending.response_tx.send(OrderResponse {
+                                                order_id: String::new(),
+                                                status: "rejected".to_string(),
+                                                filled_qty: 0.0,
+                                                avg_price: 0.0,
+                                                cum_cost: 0.0,
+                                                fee: 0.0,
+                                                fee_native: 0.0,
+                                                fee_currency: None,
+                                                error: Some(error_msg.to_string()),
+                                                price_improvement_bps: None,
+                                            });
+                                        }
+                                    }
+                                }
+                            }
+
+                            // Handle amend_order acknowledgements
+                            Some(ack) if ack.method == "amend_order" => {
+                                if let Some(req_id) = ack.req_id {
+                                    let result = if ack.success {
+                                        amends_succeeded.fetch_add(1, Ordering::Relaxed);
+                                        debug!("Order amended: {:?}", ack.result);
+                                        Ok(())
+                                    } else {
+                                        amends_failed.fetch_add(1, Ordering::Relaxed);
+                                        let error_msg = ack.error_or("Amend rejected").to_string();
+                                        warn!("Amend {} rejected: {}", req_id, error_msg);
+                                        Err(error_msg)
+                                    };
+                                    if let Some(tx) = pending_amends.write().await.remove(&req_id) {
+                                        let _ = tx.send(result);
+                                    }
+                                }
+                            }
+
+                            _ => {}
+                        }
+
+                        // Handle execution updates
+                        if json.get("channel").and_then(|c| c.as_str()) == Some("executions") {
+                            info!("Raw execution data: {}", serde_json::to_string(&json).unwrap_or_default());
+                            if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
+                                for exec in data {
+                                    let order_id = exec.get("order_id")
+                                        .and_then(|o| o.as_str())
+                                        .unwrap_or("");
+                                    let cl_ord_id = exec.get("cl_ord_id")
+                                        .and_then(|o| o.as_str())
+                                        .unwrap_or("");
+                                    let status = exec.get("order_status")
+                                        .and_then(|s| s.as_str())
+                                        .unwrap_or("");
+                                    let exec_type = exec.get("exec_type")
+                                        .and_then(|e| e.as_str())
+                                        .unwrap_or("");
+
+                                    // Helper to parse value as f64 (handles both string and number)
+                                    fn parse_f64(v: &serde_json::Value) -> f64 {
+                                        v.as_f64()
+                                            .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+                                            .unwrap_or(0.0)
+                                    }
+
+                                    // Parse quantity - cum_qty is cumulative filled quantity
+                                    let cum_qty = exec.get("cum_qty")
+                                        .map(parse_f64)
+                                        .unwrap_or(0.0);
+
+                                    // Parse avg_price for overall order
+                                    let avg_price = exec.get("avg_price")
+                                        .map(parse_f64)
+                                        .unwrap_or(0.0);
+
+                                    // Parse cumulative cost (quote currency spent for BUY orders)
+                                    let cum_cost = exec.get("cum_cost")
+                                        .map(parse_f64)
+                                        .unwrap_or(0.0);
+
+                                    // Parse fees - Kraken v2 uses fee_usd_equiv for total USD fees
+                                    let fee = exec.get("fee_usd_equiv")
+                                        .map(parse_f64)
+                                        .unwrap_or(0.0);
+
+                                    // Parse native currency fee from fees array
+                                    // This is needed to calculate NET amounts for each leg
+                                    let fee_native = exec.get("fees")
+                                        .and_then(|f| f.as_array())
+                                        .map(|fees| {
+                                            fees.iter()
+                                                .filter_map(|fee_item| {
+                                                    fee_item.get("qty").map(parse_f64)
+                                                })
+                                                .sum()
+                                        })
+                                        .unwrap_or(0.0);
+                                    // ... and the currency it's in (one per order)
+                                    let fee_currency = exec.get("fees")
+                                        .and_then(|f| f.as_array())
+                                        .and_then(|fees| fees.first())
+                                        .and_then(|fee_item| fee_item.get("asset"))
+                                        .and_then(|a| a.as_str())
+                                        .map(normalize_currency);
+
+                                    // For individual trade events, also track last fill
+                                    let last_qty = exec.get("last_qty")
+                                        .map(parse_f64)
+                                        .unwrap_or(0.0);
+                                    let last_price = exec.get("last_price")
+                                        .map(parse_f64)
+                                        .unwrap_or(0.0);
+
+                                    info!("Execution update: order={}, cl_ord={}, status={}, exec_type={}, cum_qty={}, cum_cost={}, avg_price={}, fee={}, last_qty={}, last_price={}",
+                                          order_id, cl_ord_id, status, exec_type, cum_qty, cum_cost, avg_price, fee, last_qty, last_price);
+
+                                    // Match by our cl_ord_id, or by Kraken's order_id
+                                    // when the update doesn't carry it
+                                    let pending_key = {
+                                        let orders = pending_orders.read().await;
+                                        if orders.contains_key(cl_ord_id) {
+                                            Some(cl_ord_id.to_string())
+                                        } else if !order_id.is_empty() {
+                                            orders.iter().find(|(_, p)| p.order_id == order_id).map(|(id, _)| id.clone())
+                                        } else {
+                                            None
+                                        }
+                                    };
+
+                                    // Journal every execution, waited for or not
+                                    if let Some(fill) = fill_from_exec(exec, pending_key.is_some()) {
+                                        fill_journal.record(fill);
+                                    }
+
+                                    // Check if order is complete (filled, canceled, or expired)
+                                    if status == "filled" || status == "canceled" || status == "expired" {
+                                        let mut orders = pending_orders.write().await;
+                                        if let Some(pending) = pending_key.and_then(|id| orders.remove(&id)) {
+                                            let response = OrderResponse {
+                                                order_id: order_id.to_string(),
+                                                status: status.to_string(),
+                                                filled_qty: cum_qty,
+                                                avg_price,
+                                                cum_cost,
+                                                fee,
+                                                fee_native,
+                                                fee_currency,
+                                                error: if status != "filled" {
+                                                    Some(format!("Order {}", status))
                                                 } else {
-                                                    orders_failed.fetch_add(1, Ordering::Relaxed);
-                                                }
+                                                    None
+                                                },
+                                                price_improvement_bps: None,
+                                            };
 
-                                                let _ = pending.response_tx.send(response);
+                                            if status == "filled" {
+                                                orders_filled.fetch_add(1, Ordering::Relaxed);
+                                            } else {
+                                                orders_failed.fetch_add(1, Ordering::Relaxed);
                                             }
+
+                                            let _ = pending.response_tx.send(response);
                                         }
                                     }
                                 }
                             }
                         }
                     }
-                    Ok(Message::Ping(_data)) => {
-                        // Pong is handled automatically by tungstenite
-                    }
-                    Ok(Message::Close(_)) => {
-                        info!("WebSocket closed");
-                        is_connected.store(false, Ordering::SeqCst);
-                        break;
-                    }
-                    Err(e) => {
-                        error!("WebSocket error: {}", e);
-                        is_connected.store(false, Ordering::SeqCst);
-                        break;
-                    }
-                    _ => {}
+                }
+                Ok(Frame::Close) => {
+                    info!("WebSocket closed");
+                    is_connected.store(false, Ordering::SeqCst);
+                    return "closed by server".to_string();
+                }
+                Err(e) => {
+                    error!("WebSocket error: {}", e);
+                    is_connected.store(false, Ordering::SeqCst);
+                    return e;
                 }
             }
-        });
+        }
+
+        is_connected.store(false, Ordering::SeqCst);
+        "stream ended".to_string()
+    }
+    
+    /// Place a market order (an immediate-or-cancel limit when price-capped)
+    pub async fn place_order(
+        &self,
+        pair: &str,
+        side: OrderSide,
+        quantity: f64,
+    ) -> Result<OrderResponse, ExecutionError> {
+        self.place_order_for(None, pair, side, quantity).await
+    }
+
+    /// Place an order, remembering it as the open order of in-flight trade
+    /// `trade_id` so an abort can cancel it
+    async fn place_order_for(
+        &self,
+        trade_id: Option<&str>,
+        pair: &str,
+        side: OrderSide,
+        quantity: f64,
+    ) -> Result<OrderResponse, ExecutionError> {
+        if !self.is_connected() {
+            return Err(ExecutionError::NotConnected);
+        }
+        let cap = self.order_cap(pair, side, quantity);
+        let trade = trade_id.and_then(|id| self.in_flight.lock().get(id).cloned());
+        let time_in_force = trade.as_ref().map_or(v2::TimeInForce::Gtc, |t| t.time_in_force);
+        let order_type = order_type_label(cap.is_some(), time_in_force);
+        let expected_price = self.best_price(pair, side);
+
+        #[cfg(test)]
+        if let Some(backend) = &self.fake_backend {
+            self.orders_sent.fetch_add(1, Ordering::Relaxed);
+            let result = backend.place_order(pair, side, quantity, cap.map(|c| c.limit_price), time_in_force).await;
+            match &result {
+                Ok(response) if response.error.is_none() => self.orders_filled.fetch_add(1, Ordering::Relaxed),
+                Err(ExecutionError::Timeout(_)) => self.orders_timed_out.fetch_add(1, Ordering::Relaxed),
+                _ => self.orders_failed.fetch_add(1, Ordering::Relaxed),
+            };
+            let response = self.settle_order(pair, result?, cap, time_in_force)?;
+            return Ok(self.record_price_improvement(pair, side, expected_price, order_type, response));
+        }
         
-        // Spawn sender task
-        let is_connected_sender = Arc::clone(&self.is_connected);
-        tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                if write.send(Message::Text(msg)).await.is_err() {
-                    is_connected_sender.store(false, Ordering::SeqCst);
-                    break;
+        let token = self.auth
+            .get_ws_token()
+            .await
+            .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
+        
+        let req_id = self.next_req_id();
+        let client_id = format!("arb_{}", req_id);
+        
+        // Create response channel
+        let (tx, rx) = oneshot::channel();
+        
+        // Register pending order
+        {
+            let mut orders = self.pending_orders.write().await;
+            orders.insert(client_id.clone(), PendingOrder {
+                order_id: String::new(),
+                client_id: client_id.clone(),
+                response_tx: tx,
+                created_at: tokio::time::Instant::now(),
+            });
+        }
+        if let Some(trade) = &trade {
+            *trade.open_order.lock() = Some(client_id.clone());
+        }
+        
+        // Build order message
+        // For BUY orders: quantity is quote (cash_order_qty, or base at the ask)
+        // For SELL orders: use order_qty (base currency amount, e.g., ETH)
+        // Capped orders are limits sized in base either way
+        let params = match cap {
+            Some(cap) => v2::OrderParams::ioc_limit(side.into(), cap.order_qty, cap.limit_price, client_id.clone()),
+            None => self.market_order_size(pair, side, quantity).params(side, client_id.clone()),
+        };
+        // Capped orders are already IOC; fok tightens them, gtc is the default
+        let params = match time_in_force {
+            v2::TimeInForce::Gtc => params,
+            v2::TimeInForce::Ioc if cap.is_some() => params,
+            tif => params.time_in_force(tif),
+        };
+        let order_msg = v2::Request::new(v2::Method::AddOrder(params.on(pair, token))).with_req_id(req_id);
+        
+        // Send order
+        {
+            let ws_tx = self.ws_tx.read().await;
+            if let Some(tx) = ws_tx.as_ref() {
+                tx.send(order_msg.to_json())
+                    .map_err(|_| ExecutionError::NotConnected)?;
+                self.orders_sent.fetch_add(1, Ordering::Relaxed);
+            } else {
+                return Err(ExecutionError::NotConnected);
+            }
+        }
+
+        let response = self.await_response(&client_id, rx).await;
+        if let Some(trade) = &trade {
+            *trade.open_order.lock() = None;
+        }
+        let response = self.settle_order(pair, response?, cap, time_in_force)?;
+        Ok(self.record_price_improvement(pair, side, expected_price, order_type, response))
+    }
+
+    /// Market order for `quantity` (quote for buys, base for sells) as
+    /// buy_sizing says
+    fn market_order_size(&self, pair: &str, side: OrderSide, quantity: f64) -> OrderSize {
+        let lot_decimals = self.order_precision.get(pair).map(|&(_, lot)| lot);
+        market_order_size(side, quantity, self.buy_sizing, self.best_price(pair, OrderSide::Buy), lot_decimals)
+    }
+
+    /// Price an order on `pair` would fill at right now: the ask for a buy,
+    /// the bid for a sell
+    fn best_price(&self, pair: &str, side: OrderSide) -> Option<f64> {
+        let edge = self.cache.get_price(pair)?;
+        let price = match side {
+            OrderSide::Buy => edge.ask,
+            OrderSide::Sell => edge.bid,
+        };
+        (price > 0.0).then_some(price)
+    }
+
+    /// Measure a fill against the best price when its order was sent and add
+    /// it to the stats for the pair and order type
+    fn record_price_improvement(
+        &self,
+        pair: &str,
+        side: OrderSide,
+        expected_price: Option<f64>,
+        order_type: &'static str,
+        mut response: OrderResponse,
+    ) -> OrderResponse {
+        let Some(expected) = expected_price.filter(|_| response.avg_price > 0.0 && response.filled_qty > 0.0) else {
+            return response;
+        };
+        let bps = price_improvement_bps(side, expected, response.avg_price);
+        self.price_improvement
+            .lock()
+            .entry((pair.to_string(), order_type))
+            .or_insert_with(|| PriceImprovementStats {
+                pair: pair.to_string(),
+                order_type: order_type.to_string(),
+                ..Default::default()
+            })
+            .record(bps);
+        response.price_improvement_bps = Some(bps);
+        response
+    }
+
+    /// Limit for an order on `pair` under the price cap, or, with the cap
+    /// off, no further than its realized volatility when the pair is
+    /// volatile enough for the trade feed to cap it (None = send at market)
+    fn order_cap(&self, pair: &str, side: OrderSide, quantity: f64) -> Option<OrderCap> {
+        let policy = if self.price_cap.is_enabled() {
+            self.price_cap
+        } else {
+            PriceCapPolicy { max_deviation_bps: self.volatility_cap_bps(pair)? }
+        };
+        let &(price_decimals, lot_decimals) = self.order_precision.get(pair)?;
+        let edge = self.cache.get_price(pair).filter(|e| e.bid > 0.0 && e.ask > 0.0)?;
+        Some(policy.cap(side, edge.bid, edge.ask, quantity, price_decimals, lot_decimals))
+    }
+
+    /// Realized volatility of `pair` when at or above the trade feed's cap
+    /// threshold
+    fn volatility_cap_bps(&self, pair: &str) -> Option<f64> {
+        let feed = self.trade_feed.as_ref().filter(|f| f.policy().cap_above_bps > 0.0)?;
+        let vol_bps = feed.realized_vol(pair, Utc::now())?.vol_bps;
+        (vol_bps >= feed.policy().cap_above_bps).then_some(vol_bps)
+    }
+
+    /// Result of a completed order: an IOC order that filled part of its
+    /// size completes with that part, a capped order that otherwise ended
+    /// canceled or expired ran into its limit, anything else with an error
+    /// was rejected
+    fn settle_order(
+        &self,
+        pair: &str,
+        response: OrderResponse,
+        cap: Option<OrderCap>,
+        time_in_force: v2::TimeInForce,
+    ) -> Result<OrderResponse, ExecutionError> {
+        let Some(error) = &response.error else {
+            return Ok(response);
+        };
+        let ended = response.status == "canceled" || response.status == "expired";
+        if time_in_force == v2::TimeInForce::Ioc && ended && response.filled_qty > 0.0 {
+            self.orders_partially_filled.fetch_add(1, Ordering::Relaxed);
+            warn!("IOC order on {} {} with {} filled - continuing with the filled part", pair, response.status, response.filled_qty);
+            return Ok(response);
+        }
+        match cap {
+            Some(cap) if response.status == "canceled" || response.status == "expired" => {
+                self.orders_price_capped.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Price cap stopped {} at {} ({} of {} filled)",
+                    pair, cap.limit_price, response.filled_qty, cap.order_qty
+                );
+                Err(ExecutionError::PriceCapped {
+                    pair: pair.to_string(),
+                    limit_price: cap.limit_price,
+                    filled_qty: response.filled_qty,
+                })
+            }
+            _ => Err(ExecutionError::OrderRejected(error.clone())),
+        }
+    }
+
+    /// Cancel every open order on the account (cancel_all)
+    pub async fn cancel_all_orders(&self) -> Result<(), ExecutionError> {
+        if !self.is_connected() {
+            return Err(ExecutionError::NotConnected);
+        }
+
+        let token = self.auth
+            .get_ws_token()
+            .await
+            .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
+
+        let cancel_msg = v2::Request::new(v2::Method::CancelAll(v2::TokenParams { token }))
+            .with_req_id(self.next_req_id());
+
+        let ws_tx = self.ws_tx.read().await;
+        let tx = ws_tx.as_ref().ok_or(ExecutionError::NotConnected)?;
+        tx.send(cancel_msg.to_json())
+            .map_err(|_| ExecutionError::NotConnected)?;
+        warn!("cancel_all sent - all open orders will be cancelled");
+        Ok(())
+    }
+
+    /// Reprice and/or resize a resting limit order in place (amend_order)
+    ///
+    /// Keeps the order's queue priority where Kraken allows it and saves the
+    /// round trip of cancel + add_order. Returns once Kraken acknowledges the
+    /// amend; the new state arrives on the executions channel as usual.
+    pub async fn amend_order(
+        &self,
+        order_id: &str,
+        limit_price: Option<f64>,
+        order_qty: Option<f64>,
+    ) -> Result<(), ExecutionError> {
+        if limit_price.is_none() && order_qty.is_none() {
+            return Err(ExecutionError::OrderRejected("Amend needs a limit_price or order_qty".to_string()));
+        }
+        if !self.is_connected() {
+            return Err(ExecutionError::NotConnected);
+        }
+
+        let token = self.auth
+            .get_ws_token()
+            .await
+            .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
+
+        let req_id = self.next_req_id();
+        let amend_msg = v2::Request::new(v2::Method::AmendOrder(v2::AmendParams {
+            order_id: order_id.to_string(),
+            limit_price,
+            order_qty,
+            token,
+        }))
+        .with_req_id(req_id);
+
+        let (tx, rx) = oneshot::channel();
+        self.pending_amends.write().await.insert(req_id, tx);
+
+        {
+            let ws_tx = self.ws_tx.read().await;
+            let sent = ws_tx.as_ref().map(|tx| tx.send(amend_msg.to_json()).is_ok());
+            if sent != Some(true) {
+                self.pending_amends.write().await.remove(&req_id);
+                return Err(ExecutionError::NotConnected);
+            }
+            self.amends_sent.fetch_add(1, Ordering::Relaxed);
+        }
+
+        match timeout(Duration::from_millis(AMEND_TIMEOUT_MS), rx).await {
+            Ok(Ok(Ok(()))) => Ok(()),
+            Ok(Ok(Err(error))) => Err(ExecutionError::OrderRejected(error)),
+            Ok(Err(_)) => Err(ExecutionError::WebSocketError("Channel closed".to_string())),
+            Err(_) => {
+                self.pending_amends.write().await.remove(&req_id);
+                self.amends_failed.fetch_add(1, Ordering::Relaxed);
+                Err(ExecutionError::Timeout(AMEND_TIMEOUT_MS))
+            }
+        }
+    }
+
+    /// Rest `amount` (quote for buys, base for sells) as a post-only limit at
+    /// the touch, following the touch with amend_order every reprice_ms
+    ///
+    /// A rejected amend falls back to cancel + replace: the order is
+    /// cancelled and what it didn't fill goes out again at the new price.
+    /// After max_reprices the order is cancelled. Returns everything filled,
+    /// with the status of the last order ("filled" when all of it was).
+    pub async fn chase_maker_order(
+        &self,
+        pair: &str,
+        side: OrderSide,
+        amount: f64,
+    ) -> Result<OrderResponse, ExecutionError> {
+        if !self.is_connected() {
+            return Err(ExecutionError::NotConnected);
+        }
+        let mut price = self.maker_price(pair, side)
+            .ok_or_else(|| ExecutionError::OrderRejected(format!("No price for {}", pair)))?;
+        let lot_decimals = self.order_precision.get(pair).map(|&(_, lot)| lot);
+        let order_qty = match side {
+            OrderSide::Buy => round_lot(amount / price, lot_decimals),
+            OrderSide::Sell => round_lot(amount, lot_decimals),
+        };
+
+        let (mut client_id, mut rx) = self.send_maker_order(pair, side, order_qty, price).await?;
+        let mut done: Option<OrderResponse> = None;
+        let reprice = Duration::from_millis(self.maker.reprice_ms);
+        let mut reprices = 0;
+
+        let last = loop {
+            if let Ok(response) = timeout(reprice, &mut rx).await {
+                break response.map_err(|_| ExecutionError::WebSocketError("Channel closed".to_string()))?;
+            }
+            // Out of reprices: take what filled
+            if reprices == self.maker.max_reprices {
+                let _ = self.cancel_client_order(&client_id).await;
+                break self.await_response(&client_id, rx).await?;
+            }
+            reprices += 1;
+
+            let order_id = self.pending_orders.read().await
+                .get(&client_id)
+                .map(|p| p.order_id.clone())
+                .unwrap_or_default();
+            let touch = self.maker_price(pair, side);
+            let Some(new_price) = touch.filter(|p| *p != price && !order_id.is_empty()) else {
+                continue;
+            };
+
+            match self.amend_order(&order_id, Some(new_price), None).await {
+                Ok(()) => price = new_price,
+                Err(ExecutionError::OrderRejected(e)) => {
+                    debug!("Amend of {} rejected ({}) - cancelling and replacing", order_id, e);
+                    let _ = self.cancel_client_order(&client_id).await;
+                    let cancelled = self.await_response(&client_id, rx).await?;
+                    let filled = match done.take() {
+                        Some(done) => combine_fills(&done, cancelled),
+                        None => cancelled,
+                    };
+                    let remaining = round_lot(order_qty - filled.filled_qty, lot_decimals);
+                    if remaining <= 0.0 {
+                        break filled;
+                    }
+                    done = Some(filled);
+                    self.maker_replacements.fetch_add(1, Ordering::Relaxed);
+                    (client_id, rx) = self.send_maker_order(pair, side, remaining, new_price).await?;
+                    price = new_price;
+                }
+                Err(e) => {
+                    let _ = self.cancel_client_order(&client_id).await;
+                    return Err(e);
                 }
             }
+        };
+
+        if last.status == "filled" {
+            self.maker_orders_filled.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(match done {
+            Some(done) => combine_fills(&done, last),
+            None => last,
+        })
+    }
+
+    /// Send a post-only limit without waiting for it, returning its client
+    /// id and where its final state arrives
+    async fn send_maker_order(
+        &self,
+        pair: &str,
+        side: OrderSide,
+        order_qty: f64,
+        limit_price: f64,
+    ) -> Result<(String, oneshot::Receiver<OrderResponse>), ExecutionError> {
+        let token = self.auth
+            .get_ws_token()
+            .await
+            .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
+
+        let req_id = self.next_req_id();
+        let client_id = format!("arb_{}", req_id);
+        let (tx, rx) = oneshot::channel();
+        self.pending_orders.write().await.insert(client_id.clone(), PendingOrder {
+            order_id: String::new(),
+            client_id: client_id.clone(),
+            response_tx: tx,
+            created_at: tokio::time::Instant::now(),
         });
-        
-        Ok(())
+
+        let params = v2::OrderParams::post_only_limit(side.into(), order_qty, limit_price, client_id.clone());
+        let order_msg = v2::Request::new(v2::Method::AddOrder(params.on(pair, token))).with_req_id(req_id);
+        let ws_tx = self.ws_tx.read().await;
+        let sent = ws_tx.as_ref().map(|tx| tx.send(order_msg.to_json()).is_ok());
+        if sent != Some(true) {
+            self.pending_orders.write().await.remove(&client_id);
+            return Err(ExecutionError::NotConnected);
+        }
+        self.orders_sent.fetch_add(1, Ordering::Relaxed);
+        self.maker_orders_attempted.fetch_add(1, Ordering::Relaxed);
+        Ok((client_id, rx))
     }
-    
-    /// Place a market order
-    pub async fn place_order(
+
+    /// Where a resting order on `pair` joins the book: the bid for a buy,
+    /// the ask for a sell
+    fn maker_price(&self, pair: &str, side: OrderSide) -> Option<f64> {
+        let edge = self.cache.get_price(pair)?;
+        let price = match side {
+            OrderSide::Buy => edge.bid,
+            OrderSide::Sell => edge.ask,
+        };
+        (price > 0.0).then_some(price)
+    }
+
+    /// Client ids of orders still waiting for a final status, with their age
+    pub async fn pending_orders(&self) -> Vec<(String, Duration)> {
+        self.pending_orders.read().await
+            .values()
+            .map(|p| (p.client_id.clone(), p.created_at.elapsed()))
+            .collect()
+    }
+
+    /// Order and amend counters since connect
+    pub fn get_stats(&self) -> ExecutionStats {
+        let amends_succeeded = self.amends_succeeded.load(Ordering::Relaxed);
+        let amends_failed = self.amends_failed.load(Ordering::Relaxed);
+        let amends_completed = amends_succeeded + amends_failed;
+        ExecutionStats {
+            orders_sent: self.orders_sent.load(Ordering::Relaxed),
+            orders_filled: self.orders_filled.load(Ordering::Relaxed),
+            orders_failed: self.orders_failed.load(Ordering::Relaxed),
+            orders_timed_out: self.orders_timed_out.load(Ordering::Relaxed),
+            amends_sent: self.amends_sent.load(Ordering::Relaxed),
+            amends_succeeded,
+            amends_failed,
+            maker_orders_attempted: self.maker_orders_attempted.load(Ordering::Relaxed),
+            maker_orders_filled: self.maker_orders_filled.load(Ordering::Relaxed),
+            maker_replacements: self.maker_replacements.load(Ordering::Relaxed),
+            amend_success_rate: (amends_completed > 0).then(|| amends_succeeded as f64 / amends_completed as f64),
+            signal_delays: self.signal_delays.load(Ordering::Relaxed),
+            signal_skips: self.signal_skips.load(Ordering::Relaxed),
+            stale_opportunities: self.stale_opportunities.load(Ordering::Relaxed),
+            orders_price_capped: self.orders_price_capped.load(Ordering::Relaxed),
+            orders_partially_filled: self.orders_partially_filled.load(Ordering::Relaxed),
+            retries: self.retry_stats.lock().clone(),
+            price_improvement: self.price_improvement.lock().values().cloned().collect(),
+            orders_swept: self.orders_swept.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Place an order, auditing the request and its outcome
+    async fn place_audited_order(
         &self,
+        actor: AuditActor,
+        trade_id: &str,
         pair: &str,
         side: OrderSide,
         quantity: f64,
     ) -> Result<OrderResponse, ExecutionError> {
+        let audit = match &self.audit {
+            Some(audit) => audit,
+            None => return self.place_order_for(Some(trade_id), pair, side, quantity).await,
+        };
+
+        audit.record(actor, AuditCategory::Order, "order_request", json!({
+            "trade_id": trade_id,
+            "pair": pair,
+            "side": side.to_string(),
+            "quantity": quantity,
+        }));
+
+        let result = self.place_order_for(Some(trade_id), pair, side, quantity).await;
+        match &result {
+            Ok(response) => audit.record(actor, AuditCategory::Order, "order_response", json!({
+                "trade_id": trade_id,
+                "pair": pair,
+                "side": side.to_string(),
+                "order_id": response.order_id,
+                "filled_qty": response.filled_qty,
+                "avg_price": response.avg_price,
+                "cum_cost": response.cum_cost,
+                "fee": response.fee,
+            })),
+            Err(ExecutionError::PriceCapped { limit_price, filled_qty, .. }) => {
+                audit.record(actor, AuditCategory::Order, "order_price_capped", json!({
+                    "trade_id": trade_id,
+                    "pair": pair,
+                    "side": side.to_string(),
+                    "quantity": quantity,
+                    "limit_price": limit_price,
+                    "filled_qty": filled_qty,
+                    "max_deviation_bps": self.price_cap.max_deviation_bps,
+                }))
+            }
+            Err(e) => audit.record(actor, AuditCategory::Order, "order_error", json!({
+                "trade_id": trade_id,
+                "pair": pair,
+                "side": side.to_string(),
+                "error": e.to_string(),
+            })),
+        }
+        result
+    }
+
+    /// Wait for a pending order to complete (filled, canceled, expired or rejected)
+    async fn await_order(
+        &self,
+        client_id: &str,
+        rx: oneshot::Receiver<OrderResponse>,
+    ) -> Result<OrderResponse, ExecutionError> {
+        let response = self.await_response(client_id, rx).await?;
+        // Check if the response contains an error (order rejected)
+        if let Some(error) = &response.error {
+            Err(ExecutionError::OrderRejected(error.clone()))
+        } else {
+            Ok(response)
+        }
+    }
+
+    /// Wait for a pending order's final state, whatever it is
+    async fn await_response(
+        &self,
+        client_id: &str,
+        rx: oneshot::Receiver<OrderResponse>,
+    ) -> Result<OrderResponse, ExecutionError> {
+        match timeout(Duration::from_millis(ORDER_TIMEOUT_MS), rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(ExecutionError::WebSocketError("Channel closed".to_string())),
+            Err(_) => {
+                // Remove from pending
+                self.pending_orders.write().await.remove(client_id);
+                self.orders_timed_out.fetch_add(1, Ordering::Relaxed);
+                self.chaos.order_timed_out();
+                Err(ExecutionError::Timeout(ORDER_TIMEOUT_MS))
+            }
+        }
+    }
+
+    /// Place several market orders on one pair in a single batch_add message
+    ///
+    /// Each order gets its own cl_ord_id (`arb_<req_id>_<n>`) so fills are
+    /// tracked per order by the executions channel. Results are returned in
+    /// input order; a rejected batch fails every order in it.
+    #[allow(dead_code)]
+    pub async fn place_order_batch(
+        &self,
+        pair: &str,
+        orders: &[(OrderSide, f64)],
+    ) -> Result<Vec<Result<OrderResponse, ExecutionError>>, ExecutionError> {
+        if orders.len() < MIN_BATCH_ORDERS {
+            // Nothing to batch
+            let mut results = Vec::with_capacity(orders.len());
+            for &(side, quantity) in orders {
+                results.push(self.place_order(pair, side, quantity).await);
+            }
+            return Ok(results);
+        }
+        if orders.len() > MAX_BATCH_ORDERS {
+            return Err(ExecutionError::OrderRejected(format!(
+                "Batch of {} orders exceeds Kraken limit of {}", orders.len(), MAX_BATCH_ORDERS
+            )));
+        }
         if !self.is_connected() {
             return Err(ExecutionError::NotConnected);
         }
-        
+
         let token = self.auth
             .get_ws_token()
             .await
             .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
-        
+
         let req_id = self.next_req_id();
-        let client_id = format!("arb_{}", req_id);
-        
-        // Create response channel
-        let (tx, rx) = oneshot::channel();
-        
-        // Register pending order
+        let mut receivers = Vec::with_capacity(orders.len());
+        let mut order_params = Vec::with_capacity(orders.len());
+
         {
-            let mut orders = self.pending_orders.write().await;
-            orders.insert(client_id.clone(), PendingOrder {
-                order_id: String::new(),
-                client_id: client_id.clone(),
-                response_tx: tx,
-                created_at: Instant::now(),
-            });
+            let mut pending = self.pending_orders.write().await;
+            for (n, &(side, quantity)) in orders.iter().enumerate() {
+                let client_id = format!("arb_{}_{}", req_id, n);
+                let (tx, rx) = oneshot::channel();
+                pending.insert(client_id.clone(), PendingOrder {
+                    order_id: String::new(),
+                    client_id: client_id.clone(),
+                    response_tx: tx,
+                    created_at: tokio::time::Instant::now(),
+                });
+
+                // Same quantity convention as place_order
+                order_params.push(self.market_order_size(pair, side, quantity).params(side, client_id.clone()));
+                receivers.push((client_id, rx));
+            }
         }
-        
-        // Build order message
-        // For BUY orders: use cash_order_qty (quote currency amount, e.g., USD)
-        // For SELL orders: use order_qty (base currency amount, e.g., ETH)
-        let order_msg = match side {
-            OrderSide::Buy => json!({
-                "method": "add_order",
-                "params": {
-                    "order_type": "market",
-                    "side": "buy",
-                    "symbol": pair,
-                    "cash_order_qty": quantity,  // Spend this much quote currency (e.g., $10 USD)
-                    "cl_ord_id": client_id,
-                    "token": token
-                },
-                "req_id": req_id
-            }),
-            OrderSide::Sell => json!({
-                "method": "add_order",
-                "params": {
-                    "order_type": "market",
-                    "side": "sell",
-                    "symbol": pair,
-                    "order_qty": quantity,  // Sell this much base currency (e.g., 0.003 ETH)
-                    "cl_ord_id": client_id,
-                    "token": token
-                },
-                "req_id": req_id
-            }),
-        };
-        
-        // Send order
+
+        let batch_msg = v2::Request::new(v2::Method::BatchAdd(v2::BatchAddParams {
+            symbol: pair.to_string(),
+            orders: order_params,
+            token,
+        }))
+        .with_req_id(req_id);
+
         {
             let ws_tx = self.ws_tx.read().await;
-            if let Some(tx) = ws_tx.as_ref() {
-                tx.send(order_msg.to_string())
-                    .map_err(|_| ExecutionError::NotConnected)?;
-                self.orders_sent.fetch_add(1, Ordering::Relaxed);
-            } else {
+            let sent = ws_tx.as_ref().map(|tx| tx.send(batch_msg.to_json()).is_ok()).unwrap_or(false);
+            if !sent {
+                let mut pending = self.pending_orders.write().await;
+                for (client_id, _) in &receivers {
+                    pending.remove(client_id);
+                }
                 return Err(ExecutionError::NotConnected);
             }
+            self.orders_sent.fetch_add(orders.len() as u64, Ordering::Relaxed);
         }
-        
-        // Wait for response with timeout
-        match timeout(Duration::from_millis(ORDER_TIMEOUT_MS), rx).await {
-            Ok(Ok(response)) => {
-                // Check if the response contains an error (order rejected)
-                if let Some(error) = &response.error {
-                    Err(ExecutionError::OrderRejected(error.clone()))
-                } else {
-                    Ok(response)
-                }
+
+        Ok(futures_util::future::join_all(
+            receivers.into_iter().map(|(client_id, rx)| async move {
+                self.await_order(&client_id, rx).await
+            })
+        ).await)
+    }
+
+    /// Execute a group of independent legs concurrently
+    ///
+    /// Legs on the same pair go out as one batch_add (Kraken only batches
+    /// within a symbol); the remaining legs are sent individually at the same
+    /// time. Results are returned in input order.
+    ///
+    /// Cycle legs in execute_opportunity stay sequential - each one spends the
+    /// previous leg's output - so this is only for legs that are truly independent.
+    #[allow(dead_code)]
+    pub async fn execute_leg_group(&self, legs: &[GroupLeg]) -> Vec<LegResult> {
+        let group_start = Instant::now();
+
+        // pair -> [(position in `legs`, side, amount)]
+        let mut by_pair: HashMap<String, Vec<(usize, OrderSide, f64)>> = HashMap::new();
+        let mut results: Vec<Option<LegResult>> = vec![None; legs.len()];
+
+        for (pos, leg) in legs.iter().enumerate() {
+            match self.determine_pair_and_side(&leg.from_currency, &leg.to_currency) {
+                Ok((pair, side)) => by_pair.entry(pair).or_default().push((pos, side, leg.amount)),
+                Err(e) => results[pos] = Some(failed_leg(leg, "", "", 0, &e)),
             }
-            Ok(Err(_)) => Err(ExecutionError::WebSocketError("Channel closed".to_string())),
-            Err(_) => {
-                // Remove from pending
-                self.pending_orders.write().await.remove(&client_id);
-                self.orders_timed_out.fetch_add(1, Ordering::Relaxed);
-                Err(ExecutionError::Timeout(ORDER_TIMEOUT_MS))
+        }
+
+        let submissions = by_pair.into_iter().map(|(pair, entries)| async move {
+            let orders: Vec<(OrderSide, f64)> = entries.iter().map(|&(_, side, amount)| (side, amount)).collect();
+            let responses = match self.place_order_batch(&pair, &orders).await {
+                Ok(responses) => responses,
+                Err(e) => entries.iter().map(|_| Err(e.clone())).collect(),
+            };
+            (pair, entries, responses)
+        });
+
+        for (pair, entries, responses) in futures_util::future::join_all(submissions).await {
+            let duration_ms = group_start.elapsed().as_millis() as u64;
+            for ((pos, side, _), response) in entries.into_iter().zip(responses) {
+                let leg = &legs[pos];
+                results[pos] = Some(match response {
+                    Ok(response) => {
+                        let fill = leg_fill(&pair, side, &response);
+                        LegResult {
+                            leg_index: leg.leg_index,
+                            pair: pair.clone(),
+                            side: side.to_string(),
+                            order_id: response.order_id,
+                            input_amount: leg.amount,
+                            output_amount: fill.output_amount,
+                            avg_price: response.avg_price,
+                            fee: response.fee,
+                            fee_amount: response.fee_native,
+                            fee_currency: Some(fill.fee_currency),
+                            duration_ms,
+                            success: true,
+                            error: None,
+                            resized_from: None,
+                            unfilled_amount: None,
+                            price_improvement_bps: response.price_improvement_bps,
+                            fills: Vec::new(),
+                        }
+                    }
+                    Err(e) => failed_leg(leg, &pair, &side.to_string(), duration_ms, &e),
+                });
             }
         }
+
+        results.into_iter().flatten().collect()
     }
     
+    /// Spendable balance of a currency from REST /0/private/Balance
+    /// (None when it can't be fetched)
+    async fn available_balance(&self, currency: &str) -> Option<f64> {
+        #[cfg(test)]
+        if let Some(backend) = &self.fake_backend {
+            return backend.balance(currency);
+        }
+
+        if !self.auth.is_configured() {
+            return None;
+        }
+        let path = "/0/private/Balance";
+        let nonce = self.auth.next_nonce();
+        let post_data = format!("nonce={}", nonce);
+        let signature = self.auth.sign_request(path, nonce, &post_data).ok()?;
+
+        let response = self.auth
+            .client()
+            .post(format!("https://api.kraken.com{}", path))
+            .header("API-Key", self.auth.api_key())
+            .header("API-Sign", signature)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(post_data)
+            .send()
+            .await
+            .map_err(|e| warn!("Balance lookup failed: {}", e))
+            .ok()?;
+        let json: Value = response.json().await.ok()?;
+        if json.get("error").and_then(|e| e.as_array()).is_some_and(|e| !e.is_empty()) {
+            warn!("Balance lookup rejected: {}", json["error"]);
+            return None;
+        }
+
+        let balances = json.get("result")?.as_object()?;
+        let total = balances
+            .iter()
+            .filter(|(asset, _)| normalize_currency(asset) == currency)
+            .filter_map(|(_, v)| v.as_str().and_then(|s| s.parse::<f64>().ok()))
+            .sum();
+        Some(total)
+    }
+
+    /// Amount to retry a leg with after an insufficient-funds rejection:
+    /// the available balance, if resizing is enabled, the cut is within the
+    /// policy and the order still meets the pair's minimums
+    async fn resized_amount(
+        &self,
+        error: &ExecutionError,
+        pair: &str,
+        side: OrderSide,
+        from_currency: &str,
+        requested: f64,
+    ) -> Option<f64> {
+        let policy = self.resize_policy;
+        match error {
+            ExecutionError::OrderRejected(msg) if policy.enabled && msg.contains(INSUFFICIENT_FUNDS) => {}
+            _ => return None,
+        }
+
+        let available = self.available_balance(from_currency).await?;
+        if available <= 0.0 || available >= requested {
+            return None;
+        }
+        let shrink_pct = (requested - available) / requested * 100.0;
+        if shrink_pct > policy.max_shrink_pct {
+            info!("Not resizing {} {}: {:.8} {} available is {:.3}% short (max {:.3}%)",
+                side, pair, available, from_currency, shrink_pct, policy.max_shrink_pct);
+            return None;
+        }
+
+        // Quantities are quote for buys, base for sells
+        if let Some(&(ordermin, costmin)) = self.order_minimums.get(pair) {
+            let price = self.cache.get_price(pair).map(|e| (e.bid + e.ask) / 2.0).filter(|p| *p > 0.0)?;
+            let (base_qty, cost) = match side {
+                OrderSide::Buy => (available / price, available),
+                OrderSide::Sell => (available, available * price),
+            };
+            if base_qty < ordermin || cost < costmin {
+                info!("Not resizing {} {}: {:.8} {} is below the pair minimums", side, pair, available, from_currency);
+                return None;
+            }
+        }
+        Some(available)
+    }
+
+    /// Refuse the first leg when the account can't cover it: buys need the
+    /// amount plus the fee buffer, sells just the amount (their fee comes out
+    /// of the proceeds). An unavailable balance lets the order through.
+    async fn check_funding(&self, pair: &str, side: OrderSide, from_currency: &str, amount: f64) -> Result<(), ExecutionError> {
+        if !self.prefund.enabled {
+            return Ok(());
+        }
+        let needed = match side {
+            OrderSide::Buy => amount * (1.0 + self.prefund.fee_buffer_pct / 100.0),
+            OrderSide::Sell => amount,
+        };
+        let Some(available) = self.available_balance(from_currency).await else {
+            warn!("Funding check skipped for {} {}: balance unavailable", side, pair);
+            return Ok(());
+        };
+        if available >= needed {
+            return Ok(());
+        }
+
+        let usd_rate = Valuator::new(Arc::clone(&self.cache), PricingSource::Mid)
+            .usd_rate(from_currency)
+            .map(|r| r.rate);
+        warn!("Refusing {} {}: {:.8} {} available, {:.8} needed", side, pair, available, from_currency, needed);
+        Err(ExecutionError::InsufficientFunds {
+            currency: from_currency.to_string(),
+            needed,
+            available,
+            needed_usd: usd_rate.map(|r| needed * r),
+            available_usd: usd_rate.map(|r| available * r),
+        })
+    }
+
+    /// Whether the executions channel reported an order on `pair`/`side`
+    /// since `since`
+    fn order_seen_since(&self, pair: &str, side: OrderSide, since: DateTime<Utc>) -> bool {
+        let side = side.to_string();
+        self.fill_journal.recent_orders(50).iter().any(|o| {
+            o.symbol.as_deref() == Some(pair) && o.side.as_deref() == Some(side.as_str()) && o.last_at >= since
+        })
+    }
+
+    /// Refuse an opportunity detected more than max_age_ms ago: after a stall
+    /// its prices are long gone. Operator-submitted paths aren't checked.
+    fn check_freshness(&self, opportunity: &Opportunity) -> Result<(), ExecutionError> {
+        let max_age_ms = self.freshness.max_age_ms;
+        if max_age_ms == 0 || opportunity.strategy == Strategy::Manual {
+            return Ok(());
+        }
+        let age_ms = (Utc::now() - opportunity.detected_at).num_milliseconds();
+        if age_ms <= max_age_ms as i64 {
+            return Ok(());
+        }
+        self.stale_opportunities.fetch_add(1, Ordering::Relaxed);
+        warn!("Dropping stale opportunity {}: detected {}ms ago (limit {}ms)", opportunity.path, age_ms, max_age_ms);
+        Err(ExecutionError::StaleOpportunity { age_ms, max_age_ms })
+    }
+
+    /// Hold a leg back while the book leans against it: buys fear upward
+    /// pressure, sells downward. Err only when the first leg is skipped.
+    async fn gate_leg(&self, leg: usize, pair: &str, side: OrderSide) -> Result<(), ExecutionError> {
+        let policy = self.signal_gate;
+        if !policy.enabled {
+            return Ok(());
+        }
+        let Some(signals) = self.cache.get_signals(pair) else {
+            return Ok(());
+        };
+        let pressure = match side {
+            OrderSide::Buy => signals.pressure(),
+            OrderSide::Sell => -signals.pressure(),
+        };
+        if pressure < policy.threshold {
+            return Ok(());
+        }
+
+        if policy.action == SignalAction::Skip && leg == 0 {
+            self.signal_skips.fetch_add(1, Ordering::Relaxed);
+            info!("Skipping {} {}: adverse pressure {:.2} (microprice {:+.2}bps)",
+                side, pair, pressure, signals.microprice_offset_bps);
+            return Err(ExecutionError::AdverseSignal { pair: pair.to_string(), pressure });
+        }
+
+        self.signal_delays.fetch_add(1, Ordering::Relaxed);
+        debug!("Delaying {} {} {}ms: adverse pressure {:.2} (microprice {:+.2}bps)",
+            side, pair, policy.delay_ms, pressure, signals.microprice_offset_bps);
+        tokio::time::sleep(Duration::from_millis(policy.delay_ms)).await;
+        Ok(())
+    }
+
+    /// Place one leg, retrying once with the available balance when the
+    /// exchange rejects it for insufficient funds. Returns the result, the
+    /// amount actually sent and the originally requested amount if resized.
+    async fn place_leg_order(
+        &self,
+        actor: AuditActor,
+        trade_id: &str,
+        pair: &str,
+        side: OrderSide,
+        from_currency: &str,
+        amount: f64,
+    ) -> (Result<OrderResponse, ExecutionError>, f64, Option<f64>) {
+        let result = self.place_with_retries(actor, trade_id, pair, side, amount).await;
+        let resized = match &result {
+            Err(e) => self.resized_amount(e, pair, side, from_currency, amount).await,
+            Ok(_) => None,
+        };
+        let Some(resized) = resized else {
+            return (result, amount, None);
+        };
+
+        warn!("Insufficient funds for {} {} {:.8} {} - retrying with available {:.8}",
+            side, pair, amount, from_currency, resized);
+        if let Some(audit) = &self.audit {
+            audit.record(actor, AuditCategory::Order, "order_resize", json!({
+                "trade_id": trade_id,
+                "pair": pair,
+                "side": side.to_string(),
+                "requested": amount,
+                "available": resized,
+            }));
+        }
+        let retry = self.place_with_retries(actor, trade_id, pair, side, resized).await;
+        (retry, resized, Some(amount))
+    }
+
+    /// A single leg's order: rested at the touch first when the maker policy
+    /// is on, with whatever that leaves sent like any other leg
+    async fn place_single_leg_order(
+        &self,
+        trade_id: &str,
+        pair: &str,
+        side: OrderSide,
+        from_currency: &str,
+        amount: f64,
+    ) -> (Result<OrderResponse, ExecutionError>, f64, Option<f64>) {
+        if !self.maker.enabled {
+            return self.place_leg_order(AuditActor::Api, trade_id, pair, side, from_currency, amount).await;
+        }
+        let rested = match self.chase_maker_order(pair, side, amount).await {
+            Ok(rested) if rested.status == "filled" => return (Ok(rested), amount, None),
+            Ok(rested) => rested,
+            Err(e) => {
+                warn!("Maker order on {} failed ({}) - sending at market", pair, e);
+                return self.place_leg_order(AuditActor::Api, trade_id, pair, side, from_currency, amount).await;
+            }
+        };
+        let rested_amount = match side {
+            OrderSide::Buy => rested.cum_cost,
+            OrderSide::Sell => rested.filled_qty,
+        };
+        let (result, taken, resized_from) = self
+            .place_leg_order(AuditActor::Api, trade_id, pair, side, from_currency, amount - rested_amount)
+            .await;
+        let result = result.map(|taken| if rested_amount > 0.0 { combine_fills(&rested, taken) } else { taken });
+        (result, rested_amount + taken, resized_from.map(|_| amount))
+    }
+
+    /// Place an order, retrying failures as the retry policy allows for
+    /// their error class, with jittered exponential backoff
+    async fn place_with_retries(
+        &self,
+        actor: AuditActor,
+        trade_id: &str,
+        pair: &str,
+        side: OrderSide,
+        amount: f64,
+    ) -> Result<OrderResponse, ExecutionError> {
+        let started = Utc::now();
+        let mut attempt = 0u32;
+        // Class of the failure last retried (counted as recovered or exhausted)
+        let mut retried: Option<ErrorClass> = None;
+        loop {
+            let result = self.place_audited_order(actor, trade_id, pair, side, amount).await;
+            let class = match &result {
+                Ok(_) => {
+                    if let Some(class) = retried {
+                        self.retry_stats.lock().entry(class).or_default().recovered += 1;
+                    }
+                    return result;
+                }
+                Err(e) => ErrorClass::of(e),
+            };
+            let retryable = class.filter(|c| self.retry_policy.enabled && attempt < self.retry_policy.rule(*c).max_retries);
+            let Some(class) = retryable else {
+                if let Some(class) = retried {
+                    self.retry_stats.lock().entry(class).or_default().exhausted += 1;
+                }
+                return result;
+            };
+            // A market order that timed out may still have filled; one the
+            // executions channel has since reported must not be sent twice
+            if class == ErrorClass::Timeout && self.order_seen_since(pair, side, started) {
+                warn!("Not retrying {} {}: the timed-out order reached the exchange", side, pair);
+                return result;
+            }
+
+            attempt += 1;
+            retried = Some(class);
+            let delay = self.retry_policy.delay(class, attempt);
+            self.retry_stats.lock().entry(class).or_default().retries += 1;
+            warn!("Retrying {} {} ({:?}, attempt {}) in {}ms: {}",
+                side, pair, class, attempt, delay.as_millis(), result.as_ref().err().map(|e| e.to_string()).unwrap_or_default());
+            if let Some(audit) = &self.audit {
+                audit.record(actor, AuditCategory::Order, "order_retry", json!({
+                    "trade_id": trade_id,
+                    "pair": pair,
+                    "side": side.to_string(),
+                    "class": class,
+                    "attempt": attempt,
+                    "delay_ms": delay.as_millis() as u64,
+                }));
+            }
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
     /// Execute an arbitrage opportunity
     pub async fn execute_opportunity(
         &self,
@@ -524,6 +2853,31 @@ impl ExecutionEngine {
         start_amount: f64,
     ) -> Result<TradeResult, ExecutionError> {
         let trade_id = Uuid::new_v4().to_string();
+        self.trade_wal.append(WalRecord::Begin {
+            trade_id: trade_id.clone(),
+            path: opportunity.path.clone(),
+            start_amount,
+            at: Utc::now(),
+        }).await;
+
+        let result = self.execute_legs(&trade_id, opportunity, start_amount).await;
+
+        // The caller records whatever came back; nothing left to replay
+        self.trade_wal.append(WalRecord::End {
+            trade_id,
+            success: matches!(&result, Ok(trade) if trade.success),
+            at: Utc::now(),
+        }).await;
+        result
+    }
+
+    async fn execute_legs(
+        &self,
+        trade_id: &str,
+        opportunity: &Opportunity,
+        mut start_amount: f64,
+    ) -> Result<TradeResult, ExecutionError> {
+        let trade_id = trade_id.to_string();
         let start_time = Instant::now();
         let executed_at = Utc::now();
         
@@ -534,55 +2888,131 @@ impl ExecutionEngine {
         if currencies.len() < 3 {
             return Err(ExecutionError::InvalidPath(opportunity.path.clone()));
         }
+
+        // Refuse the whole path up front rather than stop halfway through it
+        if let Some(pair) = self.disabled_pair_on(&opportunity.path) {
+            warn!("Refusing {}: execution disabled for {}", opportunity.path, pair);
+            return Err(ExecutionError::PairDisabled { pair });
+        }
+        if let Some((pair, vol_bps)) = self.volatile_pair_on(&opportunity.path) {
+            warn!("Refusing {}: {} realized volatility {:.1}bps", opportunity.path, pair, vol_bps);
+            return Err(ExecutionError::TooVolatile { pair, vol_bps });
+        }
         
         let mut current_amount = start_amount;
         let mut leg_results = Vec::new();
         let mut total_fees = 0.0;
+        // Fees charged in a leg's spent currency, in the start currency
+        let mut input_fees = 0.0;
+
+        // Operator-submitted paths come through the API, everything else is auto-execution
+        let actor = if opportunity.strategy == Strategy::Manual { AuditActor::Api } else { AuditActor::Auto };
+
+        let in_flight = self.track_trade(&trade_id, &opportunity.path, self.market_tif.for_strategy(opportunity.strategy));
+        // Leg the operator aborted the trade at
+        let mut aborted_at = None;
         
         // Execute each leg
         for i in 0..currencies.len() - 1 {
             let from_currency = currencies[i];
             let to_currency = currencies[i + 1];
+
+            in_flight.trade.leg.store(i, Ordering::SeqCst);
+            if in_flight.trade.aborted.load(Ordering::SeqCst) {
+                aborted_at = Some(i);
+                break;
+            }
             
             let leg_start = Instant::now();
             
             // Determine pair and side
-            let (pair, side) = self.determine_pair_and_side(from_currency, to_currency)?;
+            let (pair, side) = match recorded_leg(opportunity, i, currencies.len() - 1) {
+                Some(leg) => leg,
+                None => self.determine_pair_and_side(from_currency, to_currency)?,
+            };
             
             info!("Leg {}: {} {} {} (amount: {:.6})", 
                 i + 1, side, pair, from_currency, current_amount);
+
+            self.gate_leg(i, &pair, side).await?;
+            if i == 0 {
+                self.check_funding(&pair, side, from_currency, current_amount).await?;
+                self.check_freshness(opportunity)?;
+            }
+
+            self.trade_wal.append(WalRecord::Intent {
+                trade_id: trade_id.clone(),
+                leg: i,
+                pair: pair.clone(),
+                side: side.to_string(),
+                from_currency: from_currency.to_string(),
+                to_currency: to_currency.to_string(),
+                amount: current_amount,
+                at: Utc::now(),
+            }).await;
             
             // Place order
-            let result = self.place_order(&pair, side, current_amount).await;
+            let (result, sent_amount, resized_from) = self
+                .place_leg_order(actor, &trade_id, &pair, side, from_currency, current_amount)
+                .await;
+            if resized_from.is_some() {
+                // A smaller first leg is a smaller cycle, not a loss
+                if i == 0 {
+                    start_amount = sent_amount;
+                }
+                current_amount = sent_amount;
+            }
             
             let leg_duration = leg_start.elapsed().as_millis() as u64;
             
             match result {
                 Ok(response) => {
-                    // Calculate GROSS output amount based on order side
-                    // BUY: We receive base currency (filled_qty)
-                    // SELL: We receive quote currency (cum_cost)
-                    let gross_output = match side {
-                        OrderSide::Buy => response.filled_qty,
-                        OrderSide::Sell => {
-                            if response.cum_cost > 0.0 {
-                                response.cum_cost
-                            } else {
-                                response.filled_qty * response.avg_price
-                            }
+                    let fill = leg_fill(&pair, side, &response);
+                    let output_amount = fill.output_amount;
+                    let unfilled_amount = leg_unfilled(side, current_amount, &response);
+                    if let Some(unfilled) = unfilled_amount {
+                        warn!("Leg {} partly filled: {:.8} of {:.8} {} left unfilled, next leg trades {:.8}",
+                            i + 1, unfilled, current_amount, from_currency, output_amount);
+                        // Unfilled start currency never left: the cycle just ran smaller
+                        if i == 0 {
+                            start_amount = current_amount - unfilled;
                         }
-                    };
-
-                    // Calculate NET output by deducting native currency fee
-                    // Fee is charged on what we RECEIVE:
-                    // - BUY: fee in base currency (deduct from filled_qty)
-                    // - SELL: fee in quote currency (deduct from cum_cost)
-                    let output_amount = gross_output - response.fee_native;
+                    } else if side == OrderSide::Buy && response.cum_cost > 0.0 && response.cum_cost != current_amount {
+                        // A buy sized in base (at the ask or a limit) spends
+                        // what it cost, not exactly the quote it was sized from
+                        debug!("Leg {} cost {:.8} {} of {:.8} sized", i + 1, response.cum_cost, from_currency, current_amount);
+                        if i == 0 {
+                            start_amount = response.cum_cost;
+                        }
+                        current_amount = response.cum_cost;
+                    }
 
-                    info!("⚡ Leg {} completed: {} {} | in={:.8} gross={:.8} net={:.8} | price={:.6} fee={:.6} (native={:.8}) | {}ms",
-                          i + 1, side, pair, current_amount, gross_output, output_amount, response.avg_price, response.fee, response.fee_native, leg_duration);
+                    info!("⚡ Leg {} completed: {} {} | in={:.8} gross={:.8} net={:.8} | price={:.6} fee={:.6} (native={:.8} {}) | {}ms",
+                          i + 1, side, pair, current_amount, fill.gross_output, output_amount, response.avg_price, response.fee, response.fee_native, fill.fee_currency, leg_duration);
 
                     total_fees += response.fee;
+                    // A fee paid on top of what the leg spent never passes
+                    // through the cycle: count it in the start currency, at
+                    // the rate the cycle reached this leg's currency
+                    if fill.input_fee > 0.0 {
+                        let spent = current_amount - unfilled_amount.unwrap_or(0.0);
+                        input_fees += if i == 0 {
+                            fill.input_fee
+                        } else if spent > 0.0 {
+                            fill.input_fee * start_amount / spent
+                        } else {
+                            0.0
+                        };
+                    }
+
+                    self.trade_wal.append(WalRecord::Fill {
+                        trade_id: trade_id.clone(),
+                        leg: i,
+                        order_id: response.order_id.clone(),
+                        output_amount,
+                        avg_price: response.avg_price,
+                        at: Utc::now(),
+                    }).await;
 
                     leg_results.push(LegResult {
                         leg_index: i,
@@ -593,14 +3023,27 @@ impl ExecutionEngine {
                         output_amount,
                         avg_price: response.avg_price,
                         fee: response.fee,
+                        fee_amount: response.fee_native,
+                        fee_currency: Some(fill.fee_currency),
                         duration_ms: leg_duration,
                         success: true,
                         error: None,
+                        resized_from,
+                        unfilled_amount,
+                        price_improvement_bps: response.price_improvement_bps,
+                        fills: Vec::new(),
                     });
 
                     current_amount = output_amount;
                 }
                 Err(e) => {
+                    self.trade_wal.append(WalRecord::LegFailed {
+                        trade_id: trade_id.clone(),
+                        leg: i,
+                        error: e.to_string(),
+                        at: Utc::now(),
+                    }).await;
+
                     leg_results.push(LegResult {
                         leg_index: i,
                         pair: pair.clone(),
@@ -610,10 +3053,22 @@ impl ExecutionEngine {
                         output_amount: 0.0,
                         avg_price: 0.0,
                         fee: 0.0,
+                        fee_amount: 0.0,
+                        fee_currency: None,
                         duration_ms: leg_duration,
                         success: false,
                         error: Some(e.to_string()),
+                        resized_from,
+                        unfilled_amount: None,
+                        price_improvement_bps: None,
+                        fills: Vec::new(),
                     });
+
+                    // The abort cancelled this leg: unwind what the earlier ones bought
+                    if in_flight.trade.aborted.load(Ordering::SeqCst) {
+                        aborted_at = Some(i);
+                        break;
+                    }
                     
                     let total_duration = start_time.elapsed().as_millis() as u64;
                     
@@ -630,17 +3085,63 @@ impl ExecutionEngine {
                         success: false,
                         error: Some(format!("Leg {} failed: {}", i + 1, e)),
                         executed_at,
+                        strategy: opportunity.strategy,
+                        tags: opportunity.tags.clone(),
                     });
                 }
             }
         }
         
+        if let Some(leg) = aborted_at {
+            let held = currencies[leg];
+            let start_currency = currencies[0];
+            let mut error = format!("Aborted by operator at leg {}", leg + 1);
+            if leg > 0 && held != start_currency {
+                let unwind = self
+                    .unwind_leg(actor, &trade_id, leg_results.len(), held, start_currency, current_amount)
+                    .await;
+                if unwind.success {
+                    error.push_str(&format!("; unwound {:.8} {} to {}", current_amount, held, start_currency));
+                    // A fee in the held currency is valued at the unwind's own rate
+                    if unwind.fee_currency.as_deref() == Some(held) && unwind.input_amount > 0.0 {
+                        input_fees += unwind.fee_amount * unwind.output_amount / unwind.input_amount;
+                    }
+                    current_amount = unwind.output_amount;
+                    total_fees += unwind.fee;
+                } else {
+                    error.push_str(&format!(
+                        "; unwind of {} failed: {}",
+                        held, unwind.error.as_deref().unwrap_or("unknown error")
+                    ));
+                }
+                leg_results.push(unwind);
+            }
+            warn!("Trade {} {}", trade_id, error);
+
+            return Ok(TradeResult {
+                id: trade_id,
+                path: opportunity.path.clone(),
+                legs: leg_results,
+                start_amount,
+                end_amount: current_amount,
+                profit_amount: current_amount - start_amount - input_fees,
+                profit_pct: ((current_amount - start_amount - input_fees) / start_amount) * 100.0,
+                total_fees,
+                total_duration_ms: start_time.elapsed().as_millis() as u64,
+                success: false,
+                error: Some(error),
+                executed_at,
+                strategy: opportunity.strategy,
+                tags: opportunity.tags.clone(),
+            });
+        }
+
         let total_duration = start_time.elapsed().as_millis() as u64;
 
         // Calculate NET profit
-        // Since we now track NET amounts through each leg (deducting native fees),
-        // current_amount is already the NET end amount - no need to subtract fees again
-        let profit_amount = current_amount - start_amount;
+        // current_amount is already net of fees charged in a leg's received
+        // currency; fees charged on top of a leg's spent currency aren't
+        let profit_amount = current_amount - start_amount - input_fees;
         let profit_pct = (profit_amount / start_amount) * 100.0;
 
         info!("Trade {} completed: ${:.2} -> ${:.2} (net after ${:.4} fees) = {:+.4}% in {}ms",
@@ -659,41 +3160,166 @@ impl ExecutionEngine {
             success: true,
             error: None,
             executed_at,
+            strategy: opportunity.strategy,
+            tags: opportunity.tags.clone(),
         })
     }
     
+    /// List `trade_id` as in flight until the guard drops
+    fn track_trade(&self, trade_id: &str, path: &str, time_in_force: v2::TimeInForce) -> InFlightGuard<'_> {
+        let trade = Arc::new(InFlightTrade {
+            path: path.to_string(),
+            started_at: Utc::now(),
+            leg: AtomicUsize::new(0),
+            open_order: parking_lot::Mutex::new(None),
+            aborted: AtomicBool::new(false),
+            time_in_force,
+        });
+        self.in_flight.lock().insert(trade_id.to_string(), Arc::clone(&trade));
+        InFlightGuard { trades: &self.in_flight, trade_id: trade_id.to_string(), trade }
+    }
+
+    /// Trades currently executing
+    pub fn in_flight_trades(&self) -> Vec<InFlightStatus> {
+        let mut trades: Vec<InFlightStatus> = self.in_flight.lock()
+            .iter()
+            .map(|(trade_id, trade)| InFlightStatus {
+                trade_id: trade_id.clone(),
+                path: trade.path.clone(),
+                leg: trade.leg.load(Ordering::SeqCst) + 1,
+                started_at: trade.started_at,
+                aborting: trade.aborted.load(Ordering::SeqCst),
+            })
+            .collect();
+        trades.sort_by_key(|t| t.started_at);
+        trades
+    }
+
+    /// Stop an in-flight trade: no further legs are placed, the open order
+    /// (if any) is cancelled, and whatever the trade already acquired is
+    /// sold back into its start currency. None when the trade isn't in flight.
+    pub async fn abort_trade(&self, trade_id: &str) -> Option<AbortRequest> {
+        let trade = self.in_flight.lock().get(trade_id).cloned()?;
+        let already_aborting = trade.aborted.swap(true, Ordering::SeqCst);
+        let open_order = trade.open_order.lock().clone();
+
+        let mut cancel_sent = false;
+        if let (false, Some(client_id)) = (already_aborting, open_order) {
+            match self.cancel_client_order(&client_id).await {
+                Ok(()) => cancel_sent = true,
+                // Usually the order filled first; the leg then completes and the unwind follows
+                Err(e) => warn!("Could not cancel {} for aborted trade {}: {}", client_id, trade_id, e),
+            }
+        }
+        let leg = trade.leg.load(Ordering::SeqCst) + 1;
+        warn!("Abort requested for trade {} ({}) at leg {}", trade_id, trade.path, leg);
+
+        Some(AbortRequest {
+            trade_id: trade_id.to_string(),
+            path: trade.path.clone(),
+            leg,
+            cancel_sent,
+            already_aborting,
+        })
+    }
+
+    /// Cancel one of our orders by its client order id (cancel_order)
+    async fn cancel_client_order(&self, client_id: &str) -> Result<(), ExecutionError> {
+        if !self.is_connected() {
+            return Err(ExecutionError::NotConnected);
+        }
+
+        let token = self.auth
+            .get_ws_token()
+            .await
+            .map_err(|e| ExecutionError::WebSocketError(e.to_string()))?;
+
+        let cancel_msg = v2::Request::new(v2::Method::CancelOrder(v2::CancelOrderParams {
+            cl_ord_id: vec![client_id.to_string()],
+            token,
+        }))
+        .with_req_id(self.next_req_id());
+
+        let ws_tx = self.ws_tx.read().await;
+        let tx = ws_tx.as_ref().ok_or(ExecutionError::NotConnected)?;
+        tx.send(cancel_msg.to_json())
+            .map_err(|_| ExecutionError::NotConnected)
+    }
+
+    /// Sell `amount` of `from` held by an aborted trade back into `to`
+    async fn unwind_leg(
+        &self,
+        actor: AuditActor,
+        trade_id: &str,
+        leg_index: usize,
+        from: &str,
+        to: &str,
+        amount: f64,
+    ) -> LegResult {
+        let started = Instant::now();
+        let mut leg = LegResult {
+            leg_index,
+            pair: format!("{}/{}", from, to),
+            side: String::new(),
+            order_id: String::new(),
+            input_amount: amount,
+            output_amount: 0.0,
+            avg_price: 0.0,
+            fee: 0.0,
+            fee_amount: 0.0,
+            fee_currency: None,
+            duration_ms: 0,
+            success: false,
+            error: None,
+            resized_from: None,
+            unfilled_amount: None,
+            price_improvement_bps: None,
+            fills: Vec::new(),
+        };
+        let (pair, side) = match self.determine_pair_and_side(from, to) {
+            Ok(found) => found,
+            Err(e) => {
+                leg.error = Some(e.to_string());
+                return leg;
+            }
+        };
+        info!("Unwinding trade {}: {} {} {:.8} {}", trade_id, side, pair, amount, from);
+
+        let (result, sent_amount, resized_from) = self.place_leg_order(actor, trade_id, &pair, side, from, amount).await;
+        leg.pair = pair;
+        leg.side = side.to_string();
+        leg.input_amount = sent_amount;
+        leg.resized_from = resized_from;
+        leg.duration_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok(response) => {
+                let fill = leg_fill(&leg.pair, side, &response);
+                leg.output_amount = fill.output_amount;
+                leg.avg_price = response.avg_price;
+                leg.fee = response.fee;
+                leg.fee_amount = response.fee_native;
+                leg.fee_currency = Some(fill.fee_currency);
+                leg.price_improvement_bps = response.price_improvement_bps;
+                leg.order_id = response.order_id;
+                leg.success = true;
+            }
+            Err(e) => leg.error = Some(e.to_string()),
+        }
+        leg
+    }
+
     /// Determine trading pair and side from currencies
     fn determine_pair_and_side(
         &self,
         from: &str,
         to: &str,
     ) -> Result<(String, OrderSide), ExecutionError> {
-        // Common quote currencies
-        let quote_currencies = ["USD", "USDT", "EUR", "BTC", "ETH"];
-        
-        // Check if direct pair exists (from/to)
-        let direct_pair = format!("{}/{}", from, to);
-        let reverse_pair = format!("{}/{}", to, from);
-        
-        // Try to get price to see which pair exists
-        if self.cache.get_price(&direct_pair).is_some() {
-            // from/to exists - we're selling from to get to
-            return Ok((direct_pair, OrderSide::Sell));
-        }
-        
-        if self.cache.get_price(&reverse_pair).is_some() {
-            // to/from exists - we're buying to with from
-            return Ok((reverse_pair, OrderSide::Buy));
-        }
-        
-        // Fallback: guess based on quote currency conventions
-        if quote_currencies.contains(&to) {
-            Ok((format!("{}/{}", from, to), OrderSide::Sell))
-        } else if quote_currencies.contains(&from) {
-            Ok((format!("{}/{}", to, from), OrderSide::Buy))
-        } else {
-            Err(ExecutionError::InvalidPath(format!("Cannot determine pair for {} -> {}", from, to)))
-        }
+        // from/to is sold for to, to/from is bought with from
+        let route = self.cache.find_pair(from, to).ok_or_else(|| {
+            ExecutionError::InvalidPath(format!("No registered pair for {} -> {}", from, to))
+        })?;
+        let side = if route.sells_base { OrderSide::Sell } else { OrderSide::Buy };
+        Ok((route.pair, side))
     }
     
     /// Execute a single leg trade (for resolving partial trades)
@@ -712,32 +3338,26 @@ impl ExecutionEngine {
         
         // Determine pair and side
         let (pair, side) = self.determine_pair_and_side(from_currency, to_currency)?;
+        if self.execution_disabled.read().contains(&pair) {
+            warn!("Refusing single leg {} -> {}: execution disabled for {}", from_currency, to_currency, pair);
+            return Err(ExecutionError::PairDisabled { pair });
+        }
         
         info!("Single leg: {} {} {} (amount: {:.6})", side, pair, from_currency, amount);
         
         // Place order
-        let result = self.place_order(&pair, side, amount).await;
+        let (result, amount, resized_from) = self
+            .place_single_leg_order(&trade_id, &pair, side, from_currency, amount)
+            .await;
         let total_duration = start_time.elapsed().as_millis() as u64;
         
         match result {
             Ok(response) => {
-                // Calculate GROSS output amount based on order side
-                let gross_output = match side {
-                    OrderSide::Buy => response.filled_qty,
-                    OrderSide::Sell => {
-                        if response.cum_cost > 0.0 {
-                            response.cum_cost
-                        } else {
-                            response.filled_qty * response.avg_price
-                        }
-                    }
-                };
-
-                // Calculate NET output by deducting native currency fee
-                let output_amount = gross_output - response.fee_native;
+                let fill = leg_fill(&pair, side, &response);
+                let output_amount = fill.output_amount;
 
-                info!("Single leg completed: {} {} | in={:.8} gross={:.8} net={:.8} | price={:.6} fee={:.6} (native={:.8})",
-                      side, pair, amount, gross_output, output_amount, response.avg_price, response.fee, response.fee_native);
+                info!("Single leg completed: {} {} | in={:.8} gross={:.8} net={:.8} | price={:.6} fee={:.6} (native={:.8} {})",
+                      side, pair, amount, fill.gross_output, output_amount, response.avg_price, response.fee, response.fee_native, fill.fee_currency);
 
                 // Profit is simply NET output - input (fee already deducted from output)
                 let profit_amount = output_amount - amount;
@@ -752,9 +3372,15 @@ impl ExecutionEngine {
                     output_amount,
                     avg_price: response.avg_price,
                     fee: response.fee,
+                    fee_amount: response.fee_native,
+                    fee_currency: Some(fill.fee_currency),
                     duration_ms: total_duration,
                     success: true,
                     error: None,
+                    resized_from,
+                    unfilled_amount: None,
+                    price_improvement_bps: response.price_improvement_bps,
+                    fills: Vec::new(),
                 };
 
                 Ok(TradeResult {
@@ -770,6 +3396,8 @@ impl ExecutionEngine {
                     success: true,
                     error: None,
                     executed_at,
+                    strategy: Strategy::Manual,
+                    tags: vec![PARTIAL_RESOLUTION_TAG.to_string()],
                 })
             }
             Err(e) => {
@@ -782,9 +3410,15 @@ impl ExecutionEngine {
                     output_amount: 0.0,
                     avg_price: 0.0,
                     fee: 0.0,
+                    fee_amount: 0.0,
+                    fee_currency: None,
                     duration_ms: total_duration,
                     success: false,
                     error: Some(e.to_string()),
+                    resized_from,
+                    unfilled_amount: None,
+                    price_improvement_bps: None,
+                    fills: Vec::new(),
                 };
                 
                 Ok(TradeResult {
@@ -800,8 +3434,83 @@ impl ExecutionEngine {
                     success: false,
                     error: Some(e.to_string()),
                     executed_at,
+                    strategy: Strategy::Manual,
+                    tags: vec![PARTIAL_RESOLUTION_TAG.to_string()],
                 })
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Note Kraken's order_id on a pending order once add_order is acknowledged
+/// Fills of two orders on one pair as one (with the later order's id and status)
+fn combine_fills(first: &OrderResponse, later: OrderResponse) -> OrderResponse {
+    let filled_qty = first.filled_qty + later.filled_qty;
+    let cum_cost = first.cum_cost + later.cum_cost;
+    OrderResponse {
+        filled_qty,
+        cum_cost,
+        avg_price: if filled_qty > 0.0 { cum_cost / filled_qty } else { 0.0 },
+        fee: first.fee + later.fee,
+        fee_native: first.fee_native + later.fee_native,
+        fee_currency: later.fee_currency.or_else(|| first.fee_currency.clone()),
+        ..later
+    }
+}
+
+/// `qty` rounded down to the pair's lot size (as-is when unknown)
+fn round_lot(qty: f64, lot_decimals: Option<u32>) -> f64 {
+    match lot_decimals {
+        Some(decimals) => {
+            let lot = 10f64.powi(decimals as i32);
+            (qty * lot).floor() / lot
+        }
+        None => qty,
+    }
+}
+
+async fn record_order_id(pending_orders: &RwLock<HashMap<String, PendingOrder>>, client_id: &str, order_id: Option<&str>) {
+    let Some(order_id) = order_id else {
+        return;
+    };
+    if let Some(pending) = pending_orders.write().await.get_mut(client_id) {
+        pending.order_id = order_id.to_string();
+    }
+}
+
+/// Remove pending orders older than `max_age`, returning their client ids
+async fn sweep_stale_orders(pending_orders: &RwLock<HashMap<String, PendingOrder>>, max_age: Duration) -> Vec<String> {
+    let mut orders = pending_orders.write().await;
+    let stale: Vec<String> = orders.iter()
+        .filter(|(_, p)| p.created_at.elapsed() > max_age)
+        .map(|(id, _)| id.clone())
+        .collect();
+    for id in &stale {
+        orders.remove(id);
+    }
+    stale
+}
+
+/// LegResult for a group leg that never filled
+#[allow(dead_code)]
+fn failed_leg(leg: &GroupLeg, pair: &str, side: &str, duration_ms: u64, error: &ExecutionError) -> LegResult {
+    LegResult {
+        leg_index: leg.leg_index,
+        pair: pair.to_string(),
+        side: side.to_string(),
+        order_id: String::new(),
+        input_amount: leg.amount,
+        output_amount: 0.0,
+        avg_price: 0.0,
+        fee: 0.0,
+        fee_amount: 0.0,
+        fee_currency: None,
+        duration_ms,
+        success: false,
+        error: Some(error.to_string()),
+        resized_from: None,
+        unfilled_amount: None,
+        price_improvement_bps: None,
+        fills: Vec::new(),
+    }
+}
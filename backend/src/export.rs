@@ -0,0 +1,344 @@
+//! Trade / Opportunity Export
+//!
+//! Streams historical rows from the database as CSV for analysis in pandas
+//! or DuckDB. Rows are read in pages of `PAGE_SIZE` ordered by id, so an
+//! export of any size never holds more than one page in memory.
+//!
+//! Trades are flattened to one row each with `leg{N}_*` columns for up to
+//! four legs, taken from the stored leg fills (auto-executed trades record
+//! timings only; manual ones also record prices, amounts and fees, the rest
+//! is left empty).
+//!
+//! CSV is the only format. `format` is still a parameter so another one can
+//! be added without changing the endpoints; anything but csv is rejected.
+
+use crate::db::{Database, LiveOpportunity, LiveTrade};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::stream::{self, Stream};
+use serde_json::Value;
+
+/// Rows fetched per database round trip
+const PAGE_SIZE: i64 = 1_000;
+
+/// Legs flattened into trade columns (the scanner stops at 4)
+const EXPORT_LEGS: usize = 4;
+
+/// Per-leg columns, in order
+const LEG_FIELDS: [&str; 9] = [
+    "pair", "side", "success", "duration_ms", "avg_price", "input_amount", "output_amount", "fee", "error",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            other => Err(format!("Unknown export format '{}' (only csv is supported)", other)),
+        }
+    }
+}
+
+/// Inclusive-from, exclusive-to time window (either end open)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl ExportRange {
+    /// Parse `from`/`to` given as RFC 3339 timestamps or YYYY-MM-DD dates (UTC midnight)
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> Result<Self, String> {
+        let range = Self {
+            from: from.map(|v| parse_time("from", v)).transpose()?,
+            to: to.map(|v| parse_time("to", v)).transpose()?,
+        };
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            if from >= to {
+                return Err("from must be before to".to_string());
+            }
+        }
+        Ok(range)
+    }
+}
+
+fn parse_time(name: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+        .ok_or_else(|| format!("{} must be an RFC 3339 timestamp or YYYY-MM-DD date", name))
+}
+
+/// A row type that can be written as CSV
+trait CsvRecord {
+    fn header() -> String;
+    fn id(&self) -> i32;
+    fn write_row(&self, out: &mut String);
+}
+
+/// Quote a field when it contains a delimiter, quote or line break (RFC 4180)
+fn push_field(out: &mut String, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&value.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(value);
+    }
+}
+
+fn push_row(out: &mut String, fields: &[String]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        push_field(out, field);
+    }
+    out.push('\n');
+}
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn time(value: &Option<DateTime<Utc>>) -> String {
+    value.map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+/// JSON scalar as a CSV cell (strings unquoted, null empty)
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+impl CsvRecord for LiveTrade {
+    fn header() -> String {
+        let mut columns: Vec<String> = [
+            "id", "trade_id", "created_at", "started_at", "completed_at", "path", "legs", "status",
+            "strategy", "tags", "session_id", "amount_in", "amount_out", "profit_loss", "profit_loss_pct",
+            "pnl_currency", "pnl_reporting", "reporting_currency", "fx_rate",
+            "opportunity_profit_pct", "total_execution_ms", "current_leg", "error_message",
+            "held_currency", "held_amount", "held_value_usd", "resolved_at", "resolved_amount_usd",
+            "resolution_trade_id", "order_ids", "notes", "labels",
+        ].iter().map(|c| c.to_string()).collect();
+        for leg in 1..=EXPORT_LEGS {
+            columns.extend(LEG_FIELDS.iter().map(|f| format!("leg{}_{}", leg, f)));
+        }
+        let mut out = String::new();
+        push_row(&mut out, &columns);
+        out
+    }
+
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn write_row(&self, out: &mut String) {
+        let mut fields = vec![
+            self.id.to_string(),
+            self.trade_id.clone(),
+            time(&self.created_at),
+            time(&self.started_at),
+            time(&self.completed_at),
+            self.path.clone(),
+            self.legs.to_string(),
+            self.status.clone(),
+            opt(&self.strategy),
+            self.tags.join(";"),
+            opt(&self.session_id),
+            self.amount_in.to_string(),
+            opt(&self.amount_out),
+            opt(&self.profit_loss),
+            opt(&self.profit_loss_pct),
+            opt(&self.pnl_currency),
+            opt(&self.pnl_reporting),
+            opt(&self.reporting_currency),
+            opt(&self.fx_rate),
+            opt(&self.opportunity_profit_pct),
+            opt(&self.total_execution_ms),
+            opt(&self.current_leg),
+            opt(&self.error_message),
+            opt(&self.held_currency),
+            opt(&self.held_amount),
+            opt(&self.held_value_usd),
+            time(&self.resolved_at),
+            opt(&self.resolved_amount_usd),
+            opt(&self.resolution_trade_id),
+            self.order_ids.as_ref().map(|v| v.to_string()).unwrap_or_default(),
+            opt(&self.notes),
+            self.labels.join(";"),
+        ];
+
+        let legs = self.leg_fills.as_ref().and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        for i in 0..EXPORT_LEGS {
+            let leg = legs.get(i);
+            fields.extend(LEG_FIELDS.iter().map(|f| cell(leg.and_then(|l| l.get(*f)))));
+        }
+        push_row(out, &fields);
+    }
+}
+
+impl CsvRecord for LiveOpportunity {
+    fn header() -> String {
+        let mut out = String::new();
+        let columns: Vec<String> = [
+            "id", "found_at", "path", "legs", "expected_profit_pct", "expected_profit_usd",
+            "trade_amount", "status", "status_reason", "trade_id", "pairs_scanned", "paths_found",
+            "notes", "labels",
+        ].iter().map(|c| c.to_string()).collect();
+        push_row(&mut out, &columns);
+        out
+    }
+
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn write_row(&self, out: &mut String) {
+        push_row(out, &[
+            self.id.to_string(),
+            time(&self.found_at),
+            self.path.clone(),
+            self.legs.to_string(),
+            self.expected_profit_pct.to_string(),
+            opt(&self.expected_profit_usd),
+            opt(&self.trade_amount),
+            self.status.clone(),
+            opt(&self.status_reason),
+            opt(&self.trade_id),
+            opt(&self.pairs_scanned),
+            opt(&self.paths_found),
+            opt(&self.notes),
+            self.labels.join(";"),
+        ]);
+    }
+}
+
+/// Render one page; the header goes with the first. Returns the chunk and
+/// the cursor for the next page (None when this page was the last).
+fn page_chunk<T: CsvRecord>(rows: &[T], first: bool) -> (String, Option<i32>) {
+    let mut chunk = if first { T::header() } else { String::new() };
+    for row in rows {
+        row.write_row(&mut chunk);
+    }
+    let next = if rows.len() as i64 == PAGE_SIZE { rows.last().map(|r| r.id()) } else { None };
+    (chunk, next)
+}
+
+/// What to export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    Trades,
+    Opportunities,
+}
+
+impl ExportKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportKind::Trades => "trades",
+            ExportKind::Opportunities => "opportunities",
+        }
+    }
+}
+
+/// CSV body stream: header first, then one chunk per page
+pub fn csv_stream(
+    db: Database,
+    kind: ExportKind,
+    range: ExportRange,
+) -> impl Stream<Item = Result<String, std::io::Error>> {
+    stream::unfold(Some((0i32, true)), move |cursor| {
+        let db = db.clone();
+        async move {
+            let (after_id, first) = cursor?;
+            let page = match kind {
+                ExportKind::Trades => db.get_trades_page(after_id, range.from, range.to, PAGE_SIZE).await
+                    .map(|rows| page_chunk(&rows, first)),
+                ExportKind::Opportunities => db.get_opportunities_page(after_id, range.from, range.to, PAGE_SIZE).await
+                    .map(|rows| page_chunk(&rows, first)),
+            };
+            match page {
+                Ok((chunk, next)) => Some((Ok(chunk), next.map(|id| (id, false)))),
+                Err(e) => Some((Err(std::io::Error::other(e.to_string())), None)),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trade_row_flattens_legs() {
+        let trade = LiveTrade {
+            id: 7,
+            trade_id: "t-7".to_string(),
+            path: "USD → BTC → ETH → USD".to_string(),
+            legs: 3,
+            amount_in: 100.0,
+            amount_out: None,
+            profit_loss: None,
+            profit_loss_pct: None,
+            pnl_currency: None,
+            pnl_reporting: None,
+            reporting_currency: None,
+            fx_rate: None,
+            status: "PARTIAL".to_string(),
+            current_leg: Some(2),
+            error_message: Some("EOrder:Insufficient funds, \"retry\"".to_string()),
+            held_currency: Some("BTC".to_string()),
+            held_amount: Some(0.002),
+            held_value_usd: None,
+            resolved_at: None,
+            resolved_amount_usd: None,
+            resolution_trade_id: None,
+            order_ids: None,
+            leg_fills: Some(serde_json::json!([
+                {"leg": 1, "pair": "BTC/USD", "side": "buy", "duration_ms": 40, "success": true, "error": null},
+                {"leg": 2, "pair": "ETH/BTC", "side": "buy", "duration_ms": 35, "success": false, "error": "rejected"}
+            ])),
+            started_at: None,
+            completed_at: None,
+            total_execution_ms: Some(75.0),
+            opportunity_profit_pct: Some(0.2),
+            strategy: Some("triangular".to_string()),
+            tags: vec!["a".to_string(), "b".to_string()],
+            session_id: None,
+            notes: Some("Kraken outage".to_string()),
+            labels: vec!["incident".to_string()],
+            notes_updated_at: None,
+            created_at: None,
+        };
+
+        let header = LiveTrade::header();
+        let columns = header.trim_end().split(',').count();
+        assert_eq!(columns, 32 + EXPORT_LEGS * LEG_FIELDS.len());
+        assert!(header.contains("leg4_error"));
+
+        let (chunk, next) = page_chunk(std::slice::from_ref(&trade), false);
+        assert_eq!(next, None);
+        assert!(chunk.contains(",\"EOrder:Insufficient funds, \"\"retry\"\"\","));
+        assert!(chunk.contains(",a;b,"));
+        assert!(chunk.contains(",Kraken outage,incident,"));
+        assert!(chunk.contains("BTC/USD,buy,true,40,,,,,,ETH/BTC,buy,false,35,,,,,rejected,"));
+        assert!(chunk.ends_with(",,,,,,,,\n"));
+
+        assert!(ExportRange::parse(Some("2026-01-02"), Some("2026-01-01")).is_err());
+        let range = ExportRange::parse(Some("2026-01-01"), Some("2026-01-02T12:00:00Z")).unwrap();
+        assert_eq!(range.from.unwrap().to_rfc3339(), "2026-01-01T00:00:00+00:00");
+        assert_eq!(ExportFormat::parse("CSV"), Ok(ExportFormat::Csv));
+        assert!(ExportFormat::parse("xlsx").is_err());
+        assert!(ExportFormat::parse("parquet").is_err());
+    }
+}
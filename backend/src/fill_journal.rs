@@ -0,0 +1,284 @@
+//! Order Fill Journal
+//!
+//! Every message on the executions channel is recorded here by order_id,
+//! whether or not a pending order is still waiting for it. Pending-order
+//! matching only hands the final status to the order that asked; fills that
+//! arrive after that order timed out, or are redelivered after a reconnect,
+//! would otherwise leave no trace. The journal keeps them for reconciliation.
+//!
+//! Each event is flagged:
+//! - matched: a pending order was waiting when it arrived
+//! - duplicate: the same exec_id (or, without one, the same exec_type /
+//!   status / cum_qty) was already journaled for the order
+//!
+//! The last `MAX_ORDERS` orders stay in memory. With FILL_JOURNAL_DB=true
+//! events are also written to `order_fills` by a background task, through a
+//! bounded channel like the audit log (full channel = event dropped, counted).
+#![allow(dead_code)]
+
+use crate::db::{Database, OrderFill};
+use chrono::Utc;
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Orders kept in memory (oldest dropped first)
+const MAX_ORDERS: usize = 1_000;
+
+/// Events buffered for the DB writer before new ones are dropped
+const CHANNEL_CAPACITY: usize = 10_000;
+
+/// Number or numeric string (Kraken v2 sends both)
+fn parse_f64(value: Option<&Value>) -> f64 {
+    value
+        .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+        .unwrap_or(0.0)
+}
+
+fn text(exec: &Value, key: &str) -> Option<String> {
+    exec.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// Build a journal event from one entry of an executions message
+/// (None without an order_id)
+pub fn fill_from_exec(exec: &Value, matched: bool) -> Option<OrderFill> {
+    Some(OrderFill {
+        received_at: Utc::now(),
+        order_id: text(exec, "order_id")?,
+        cl_ord_id: text(exec, "cl_ord_id"),
+        exec_id: text(exec, "exec_id"),
+        exec_type: text(exec, "exec_type").unwrap_or_default(),
+        order_status: text(exec, "order_status").unwrap_or_default(),
+        symbol: text(exec, "symbol"),
+        side: text(exec, "side"),
+        last_qty: parse_f64(exec.get("last_qty")),
+        last_price: parse_f64(exec.get("last_price")),
+        cum_qty: parse_f64(exec.get("cum_qty")),
+        cum_cost: parse_f64(exec.get("cum_cost")),
+        avg_price: parse_f64(exec.get("avg_price")),
+        fee_usd: parse_f64(exec.get("fee_usd_equiv")),
+        matched,
+        duplicate: false,
+    })
+}
+
+/// Identity of an execution within its order
+fn exec_key(fill: &OrderFill) -> String {
+    match &fill.exec_id {
+        Some(id) => id.clone(),
+        None => format!("{}:{}:{}", fill.exec_type, fill.order_status, fill.cum_qty),
+    }
+}
+
+/// Latest state of one journaled order
+#[derive(Debug, Clone, Serialize)]
+pub struct FillOrderSummary {
+    pub order_id: String,
+    pub cl_ord_id: Option<String>,
+    pub symbol: Option<String>,
+    pub side: Option<String>,
+    pub last_status: String,
+    pub cum_qty: f64,
+    pub avg_price: f64,
+    pub events: usize,
+    /// Events that arrived with no pending order waiting (late or unsolicited)
+    pub unmatched: usize,
+    pub duplicates: usize,
+    pub last_at: chrono::DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FillJournalStats {
+    pub persisted: bool,
+    pub orders_tracked: usize,
+    pub events_recorded: u64,
+    pub unmatched: u64,
+    pub duplicates: u64,
+    /// Events not written to the DB because the writer fell behind
+    pub db_dropped: u64,
+}
+
+#[derive(Default)]
+struct JournalState {
+    by_order: HashMap<String, Vec<OrderFill>>,
+    /// order_ids oldest first, for eviction
+    order: VecDeque<String>,
+}
+
+pub struct FillJournal {
+    state: RwLock<JournalState>,
+    db_tx: Option<mpsc::Sender<OrderFill>>,
+    recorded: AtomicU64,
+    unmatched: AtomicU64,
+    duplicates: AtomicU64,
+    db_dropped: AtomicU64,
+}
+
+impl FillJournal {
+    /// Memory-only journal, or memory + `order_fills` when given a database
+    /// (spawns the writer task)
+    pub fn new(db: Option<Database>) -> Self {
+        let db_tx = db.map(|db| {
+            let (tx, mut rx) = mpsc::channel::<OrderFill>(CHANNEL_CAPACITY);
+            tokio::spawn(async move {
+                while let Some(fill) = rx.recv().await {
+                    if let Err(e) = db.insert_order_fill(&fill).await {
+                        warn!("Failed to journal fill for order {}: {}", fill.order_id, e);
+                    }
+                }
+            });
+            tx
+        });
+
+        Self {
+            state: RwLock::new(JournalState::default()),
+            db_tx,
+            recorded: AtomicU64::new(0),
+            unmatched: AtomicU64::new(0),
+            duplicates: AtomicU64::new(0),
+            db_dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Create from FILL_JOURNAL_DB (default off: memory only)
+    pub fn from_env(db: Database) -> Self {
+        let persist = std::env::var("FILL_JOURNAL_DB")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        Self::new(persist.then_some(db))
+    }
+
+    pub fn is_persisted(&self) -> bool {
+        self.db_tx.is_some()
+    }
+
+    /// Journal an event, flagging it as a duplicate if already seen.
+    /// Returns the stored event.
+    pub fn record(&self, mut fill: OrderFill) -> OrderFill {
+        {
+            let mut state = self.state.write();
+            let key = exec_key(&fill);
+            if !state.by_order.contains_key(&fill.order_id) {
+                if state.order.len() == MAX_ORDERS {
+                    if let Some(oldest) = state.order.pop_front() {
+                        state.by_order.remove(&oldest);
+                    }
+                }
+                state.order.push_back(fill.order_id.clone());
+            }
+            let events = state.by_order.entry(fill.order_id.clone()).or_default();
+            fill.duplicate = events.iter().any(|e| exec_key(e) == key);
+            events.push(fill.clone());
+        }
+
+        self.recorded.fetch_add(1, Ordering::Relaxed);
+        if !fill.matched {
+            self.unmatched.fetch_add(1, Ordering::Relaxed);
+        }
+        if fill.duplicate {
+            self.duplicates.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(tx) = &self.db_tx {
+            if tx.try_send(fill.clone()).is_err() {
+                let dropped = self.db_dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    warn!("Fill journal backlog full - {} events not persisted so far", dropped);
+                }
+            }
+        }
+        fill
+    }
+
+    /// Events for an order (by order_id or cl_ord_id), oldest first
+    pub fn get_fills(&self, order_id: &str) -> Vec<OrderFill> {
+        let state = self.state.read();
+        if let Some(events) = state.by_order.get(order_id) {
+            return events.clone();
+        }
+        state.by_order
+            .values()
+            .find(|events| events.iter().any(|e| e.cl_ord_id.as_deref() == Some(order_id)))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Most recently journaled orders, newest first
+    pub fn recent_orders(&self, limit: usize) -> Vec<FillOrderSummary> {
+        let state = self.state.read();
+        state.order
+            .iter()
+            .rev()
+            .filter_map(|id| state.by_order.get(id))
+            .filter_map(|events| {
+                let last = events.last()?;
+                Some(FillOrderSummary {
+                    order_id: last.order_id.clone(),
+                    cl_ord_id: events.iter().find_map(|e| e.cl_ord_id.clone()),
+                    symbol: events.iter().find_map(|e| e.symbol.clone()),
+                    side: events.iter().find_map(|e| e.side.clone()),
+                    last_status: last.order_status.clone(),
+                    cum_qty: events.iter().map(|e| e.cum_qty).fold(0.0, f64::max),
+                    avg_price: last.avg_price,
+                    events: events.len(),
+                    unmatched: events.iter().filter(|e| !e.matched).count(),
+                    duplicates: events.iter().filter(|e| e.duplicate).count(),
+                    last_at: last.received_at,
+                })
+            })
+            .take(limit)
+            .collect()
+    }
+
+    pub fn stats(&self) -> FillJournalStats {
+        FillJournalStats {
+            persisted: self.is_persisted(),
+            orders_tracked: self.state.read().order.len(),
+            events_recorded: self.recorded.load(Ordering::Relaxed),
+            unmatched: self.unmatched.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            db_dropped: self.db_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_journal_flags_late_and_duplicate_fills() {
+        let journal = FillJournal::new(None);
+        let exec = |exec_id: &str, status: &str, cum_qty: &str| json!({
+            "order_id": "OABC-1", "cl_ord_id": "arb_7", "exec_id": exec_id,
+            "exec_type": "trade", "order_status": status, "symbol": "BTC/USD", "side": "buy",
+            "last_qty": "0.001", "last_price": 50_000.0, "cum_qty": cum_qty, "avg_price": "50000",
+        });
+
+        journal.record(fill_from_exec(&exec("E1", "partially_filled", "0.001"), true).unwrap());
+        // Order timed out before the rest arrived; then the same exec is redelivered
+        let late = journal.record(fill_from_exec(&exec("E2", "filled", "0.002"), false).unwrap());
+        let dup = journal.record(fill_from_exec(&exec("E2", "filled", "0.002"), false).unwrap());
+        assert!(!late.duplicate && !late.matched);
+        assert!(dup.duplicate);
+        assert!(fill_from_exec(&json!({"exec_type": "new"}), false).is_none());
+
+        let fills = journal.get_fills("OABC-1");
+        assert_eq!(fills.len(), 3);
+        assert_eq!(fills[0].last_qty, 0.001);
+        assert_eq!(journal.get_fills("arb_7").len(), 3);
+
+        let summary = &journal.recent_orders(10)[0];
+        assert_eq!((summary.events, summary.unmatched, summary.duplicates), (3, 2, 1));
+        assert_eq!(summary.last_status, "filled");
+        assert_eq!(summary.cum_qty, 0.002);
+
+        let stats = journal.stats();
+        assert_eq!((stats.events_recorded, stats.unmatched, stats.duplicates), (3, 2, 1));
+        assert!(!stats.persisted);
+    }
+}
@@ -4,6 +4,7 @@
 //! - Graph structure is built once during initialization
 //! - Only edge weights are updated when order books change
 //! - Tracks which pairs have changed for targeted scanning
+//! - Leaves out restricted currencies, rebuilding when the blocked set changes
 //!
 //! Performance benefits:
 //! - Full rebuild: ~50ms for 300 pairs
@@ -11,7 +12,7 @@
 #![allow(dead_code)]
 
 use crate::order_book::OrderBookCache;
-use crate::types::{EngineConfig, LegDetail, Opportunity, OrderBookHealth};
+use crate::types::{EngineConfig, LegDetail, Opportunity, OrderBookHealth, Strategy};
 use chrono::Utc;
 use parking_lot::RwLock;
 use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
@@ -58,6 +59,15 @@ pub struct PersistentGraph {
 
     /// Order book health stats
     health: RwLock<OrderBookHealth>,
+
+    /// Cache restrictions version the structure was built for
+    restrictions_version: u64,
+
+    /// Cache topology version (registered pairs) the structure was built for
+    topology_version: u64,
+
+    /// Edges not added because a currency on them is restricted
+    excluded_edges: usize,
 }
 
 impl PersistentGraph {
@@ -72,6 +82,9 @@ impl PersistentGraph {
             build_count: AtomicU64::new(0),
             update_count: AtomicU64::new(0),
             health: RwLock::new(OrderBookHealth::default()),
+            restrictions_version: 0,
+            topology_version: 0,
+            excluded_edges: 0,
         }
     }
 
@@ -85,33 +98,58 @@ impl PersistentGraph {
         self.node_map.clear();
         self.edge_map.clear();
         self.last_update.clear();
+        self.restrictions_version = cache.restrictions_version();
+        self.topology_version = cache.topology_version();
 
-        // Add nodes for all currencies
+        // Add nodes for all currencies but restricted ones
         let currencies = cache.get_currencies();
-        for currency in &currencies {
+        for currency in currencies.iter().filter(|c| !cache.is_currency_blocked(c)) {
             let idx = self.graph.add_node(currency.clone());
             self.node_map.insert(currency.clone(), idx);
         }
 
-        // Add placeholder edges for all pairs
-        let pairs = cache.get_all_pairs();
-        let prices = cache.get_all_prices();
-
-        for pair in &pairs {
-            if let Some(edge) = prices.get(pair) {
-                self.add_pair_edges(pair, &edge.base, &edge.quote);
+        // Add placeholder edges for all pairs (base and quote from the
+        // registry, so pairs still waiting for a price get their edges too)
+        self.excluded_edges = 0;
+        for meta in cache.pair_registry().all() {
+            if cache.is_currency_blocked(&meta.base) || cache.is_currency_blocked(&meta.quote) {
+                self.excluded_edges += 2;
+                continue;
             }
+            self.add_pair_edges(&meta.pair_name, &meta.base, &meta.quote);
         }
+        self.health.write().excluded_restricted_edges = self.excluded_edges as u32;
 
         self.build_count.fetch_add(1, Ordering::Relaxed);
         info!(
-            "PersistentGraph initialized: {} currencies, {} pairs in {:?}",
-            currencies.len(),
-            pairs.len(),
+            "PersistentGraph initialized: {} currencies, {} pairs ({} edges restricted) in {:?}",
+            self.node_map.len(),
+            self.edge_map.len(),
+            self.excluded_edges,
             start.elapsed()
         );
     }
 
+    /// Rebuild the structure when the cache's blocked currencies or its
+    /// registered pairs changed since it was built. Returns whether it rebuilt.
+    pub fn rebuild_if_changed(&mut self, cache: &Arc<OrderBookCache>) -> bool {
+        if self.topology_version != cache.topology_version() {
+            info!("Registered pairs changed, rebuilding graph");
+        } else if self.restrictions_version != cache.restrictions_version() {
+            info!("Restricted currencies changed, rebuilding graph");
+        } else {
+            return false;
+        }
+        self.initialize(cache);
+        self.update_all(cache);
+        true
+    }
+
+    /// Edges left out of the graph because a currency on them is restricted
+    pub fn excluded_edges(&self) -> usize {
+        self.excluded_edges
+    }
+
     /// Add edges for a trading pair (bidirectional)
     fn add_pair_edges(&mut self, pair: &str, base: &str, quote: &str) {
         let base_idx = match self.node_map.get(base) {
@@ -157,6 +195,12 @@ impl PersistentGraph {
         cache: &Arc<OrderBookCache>,
         pair: &str,
     ) -> bool {
+        // A changed blocked set or pair set rebuilds first; the rebuild
+        // updates every pair
+        if self.rebuild_if_changed(cache) {
+            return self.edge_map.contains_key(pair);
+        }
+
         let edge_indices = match self.edge_map.get(pair) {
             Some(indices) => indices.clone(),
             None => return false,
@@ -175,7 +219,7 @@ impl PersistentGraph {
                 let book_bid = book.bids.first().map(|l| l.price).unwrap_or(0.0);
                 let book_ask = book.asks.first().map(|l| l.price).unwrap_or(0.0);
                 let spread_pct = if book_bid > 0.0 { (book_ask - book_bid) / book_bid * 100.0 } else { 100.0 };
-                let reasonable_spread = spread_pct >= 0.0 && spread_pct < crate::types::MAX_SPREAD_PCT;
+                let reasonable_spread = (0.0..crate::types::MAX_SPREAD_PCT).contains(&spread_pct);
 
                 if has_depth && is_fresh && reasonable_spread && book_bid > 0.0 && book_ask > 0.0 {
                     (book_bid, book_ask, true)
@@ -350,6 +394,7 @@ impl PersistentGraph {
 
     /// Iterative DFS to find all cycles back to start
     /// Uses explicit stack to avoid stack overflow with large graphs
+    #[allow(clippy::too_many_arguments)]
     fn dfs_find_cycles(
         &self,
         start: NodeIndex,
@@ -414,7 +459,7 @@ impl PersistentGraph {
             frame.edge_iter_index += 1;
 
             // Check constraints
-            if currencies.len() >= max_legs + 1 {
+            if currencies.len() > max_legs {
                 continue;
             }
 
@@ -517,7 +562,7 @@ impl PersistentGraph {
             return None;
         }
 
-        let is_profitable = net_profit_pct > config.min_profit_threshold * 100.0;
+        let is_profitable = net_profit_pct > config.threshold_for(total_legs) * 100.0;
         let path_str = path.currencies.join(" → ");
 
         // Build legs detail for price snapshot
@@ -543,6 +588,9 @@ impl PersistentGraph {
             fee_rate: config.fee_rate,
             fee_source: config.fee_source.clone(),
             legs_detail,
+            strategy: Strategy::for_legs(total_legs),
+            tags: Vec::new(),
+            inverse: None,
         })
     }
 
@@ -731,4 +779,44 @@ mod tests {
         assert_eq!(graph.node_map.len(), 0);
         assert_eq!(graph.edge_map.len(), 0);
     }
+
+    #[test]
+    fn test_restricted_currencies_left_out() {
+        use crate::order_book::PairInfo;
+        use crate::types::OrderBookLevel;
+
+        let cache = Arc::new(OrderBookCache::new());
+        for (base, quote, mid) in [("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_500.0), ("XMR", "USD", 150.0)] {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            cache.update_snapshot(
+                &pair,
+                vec![OrderBookLevel { price: mid * 0.9999, qty: 10.0 }],
+                vec![OrderBookLevel { price: mid * 1.0001, qty: 10.0 }],
+                1,
+            );
+        }
+
+        cache.set_blocked_currencies(HashSet::from(["xmr".to_string()]));
+        let mut graph = PersistentGraph::new();
+        graph.initialize(&cache);
+        assert!(!graph.node_map.contains_key("XMR"));
+        assert_eq!((graph.edge_map.len(), graph.excluded_edges()), (3, 2));
+        assert_eq!(graph.get_health().excluded_restricted_edges, 2);
+        assert_eq!(cache.restricted_pairs(), vec!["XMR/USD".to_string()]);
+
+        // Blocking ETH as well is picked up by the next update
+        assert!(!cache.set_blocked_currencies(HashSet::from(["XMR".to_string()])));
+        cache.set_blocked_currencies(HashSet::from(["XMR".to_string(), "ETH".to_string()]));
+        assert!(!graph.update_pair(&cache, "ETH/USD"));
+        assert_eq!((graph.edge_map.len(), graph.excluded_edges()), (1, 6));
+        assert!(graph.get_connected_currencies("BTC").iter().all(|c| c == "USD"));
+    }
 }
@@ -0,0 +1,260 @@
+//! gRPC Server (feature "grpc")
+//!
+//! The operations Go tools need from the Axum API, over tonic: engine
+//! status, cached opportunities, manual execution and the live trading
+//! config. Definitions are in proto/trading.proto (package trading.v1).
+//! Calls go through the same engine and DB methods as the HTTP handlers, so
+//! a trade executed here is guarded, recorded and audited the same way.
+//!
+//! Served on GRPC_ADDR (e.g. 0.0.0.0:50051) when it's set; unset = off.
+#![allow(dead_code)]
+
+use crate::audit::{AuditActor, AuditCategory};
+use crate::config_schema::{ConfigError, ConfigPatch};
+use crate::db::ConfigUpdate;
+use crate::executor::TradeResult;
+use crate::opportunity_cache::OpportunityWithAge;
+use crate::trading::EngineError;
+use crate::AppState;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+pub mod proto {
+    tonic::include_proto!("trading.v1");
+}
+
+use proto::trading_engine_server::{TradingEngine, TradingEngineServer};
+
+/// Address to serve on, from GRPC_ADDR (None = server off)
+pub fn addr_from_env() -> Option<SocketAddr> {
+    let raw = std::env::var("GRPC_ADDR").ok().filter(|v| !v.trim().is_empty())?;
+    match raw.trim().parse() {
+        Ok(addr) => Some(addr),
+        Err(e) => {
+            warn!("Ignoring GRPC_ADDR={}: {}", raw, e);
+            None
+        }
+    }
+}
+
+/// Serve the gRPC API on `addr` until `shutdown` completes
+pub async fn serve(state: Arc<AppState>, addr: SocketAddr, shutdown: impl std::future::Future<Output = ()>) {
+    info!("Starting gRPC server on {}", addr);
+    let result = tonic::transport::Server::builder()
+        .add_service(TradingEngineServer::new(GrpcService { state }))
+        .serve_with_shutdown(addr, shutdown)
+        .await;
+    if let Err(e) = result {
+        warn!("gRPC server stopped: {}", e);
+    }
+}
+
+pub struct GrpcService {
+    state: Arc<AppState>,
+}
+
+fn engine_status(e: EngineError) -> Status {
+    match e {
+        EngineError::InsufficientFunds { .. } | EngineError::Execution(_) | EngineError::Config(_) => {
+            Status::failed_precondition(e.to_string())
+        }
+        EngineError::NotInitialized => Status::unavailable(e.to_string()),
+        e => Status::internal(e.to_string()),
+    }
+}
+
+fn config_status(e: ConfigError) -> Status {
+    match e {
+        ConfigError::Invalid(_) => Status::invalid_argument(e.to_string()),
+        ConfigError::Database(_) => Status::internal(e.to_string()),
+    }
+}
+
+impl From<OpportunityWithAge> for proto::Opportunity {
+    fn from(o: OpportunityWithAge) -> Self {
+        let opp = o.opportunity;
+        Self {
+            id: opp.id,
+            path: opp.path,
+            legs: opp.legs as u32,
+            gross_profit_pct: opp.gross_profit_pct,
+            fees_pct: opp.fees_pct,
+            net_profit_pct: opp.net_profit_pct,
+            detected_at_ms: opp.detected_at.timestamp_millis(),
+            age_ms: o.age_ms,
+            max_move_bps: o.max_move_bps,
+            strategy: opp.strategy.as_str().to_string(),
+            legs_detail: opp
+                .legs_detail
+                .into_iter()
+                .map(|leg| proto::Leg { pair: leg.pair, action: leg.action, rate: leg.rate })
+                .collect(),
+        }
+    }
+}
+
+impl From<TradeResult> for proto::TradeReply {
+    fn from(t: TradeResult) -> Self {
+        Self {
+            trade_id: t.id,
+            path: t.path,
+            success: t.success,
+            error: t.error.unwrap_or_default(),
+            start_amount: t.start_amount,
+            end_amount: t.end_amount,
+            profit_amount: t.profit_amount,
+            profit_pct: t.profit_pct,
+            total_fees: t.total_fees,
+            duration_ms: t.total_duration_ms,
+            legs: t
+                .legs
+                .into_iter()
+                .map(|leg| proto::LegResult {
+                    pair: leg.pair,
+                    side: leg.side,
+                    order_id: leg.order_id,
+                    input_amount: leg.input_amount,
+                    output_amount: leg.output_amount,
+                    avg_price: leg.avg_price,
+                    fee: leg.fee,
+                    duration_ms: leg.duration_ms,
+                    success: leg.success,
+                    error: leg.error.unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl TradingEngine for GrpcService {
+    async fn get_status(&self, _: Request<proto::GetStatusRequest>) -> Result<Response<proto::StatusReply>, Status> {
+        let engine = &self.state.engine;
+        let stats = engine.get_stats().await;
+        let config = self.state.db.get_config().await.unwrap_or_default();
+        let db_state = self.state.db.get_state().await.unwrap_or_default();
+        Ok(Response::new(proto::StatusReply {
+            is_running: stats.is_running,
+            status: if !stats.is_running { "stopped" } else if stats.degraded { "degraded" } else { "running" }.to_string(),
+            trading_enabled: config.is_enabled,
+            auto_execution_enabled: engine.is_auto_execution_enabled(),
+            pairs_monitored: stats.pairs_monitored as u64,
+            currencies_tracked: stats.currencies_tracked as u64,
+            opportunities_found: stats.opportunities_found,
+            uptime_seconds: stats.uptime_seconds,
+            circuit_broken: db_state.is_circuit_broken,
+            circuit_broken_reason: db_state.circuit_broken_reason.unwrap_or_default(),
+            daily_profit: db_state.daily_profit,
+            daily_loss: db_state.daily_loss,
+            total_profit: db_state.total_profit,
+            total_loss: db_state.total_loss,
+        }))
+    }
+
+    async fn list_opportunities(
+        &self,
+        request: Request<proto::ListOpportunitiesRequest>,
+    ) -> Result<Response<proto::ListOpportunitiesReply>, Status> {
+        let limit = request.into_inner().limit as usize;
+        let mut opportunities = self.state.engine.get_cached_opportunities_with_age();
+        if limit > 0 {
+            opportunities.truncate(limit);
+        }
+        Ok(Response::new(proto::ListOpportunitiesReply {
+            opportunities: opportunities.into_iter().map(Into::into).collect(),
+            invalidated: self.state.engine.opportunities_invalidated(),
+        }))
+    }
+
+    async fn execute_trade(&self, request: Request<proto::ExecuteTradeRequest>) -> Result<Response<proto::TradeReply>, Status> {
+        let req = request.into_inner();
+        let config = self.state.db.get_config().await.unwrap_or_default();
+        let amount = req.amount.unwrap_or_else(|| config.trade_amount.unwrap_or(0.0));
+        if amount <= 0.0 {
+            return Err(Status::failed_precondition("Trade amount not configured. Please set from the dashboard."));
+        }
+        match self.state.engine.execute_trade(&req.path, amount, req.tags).await {
+            Ok(result) => Ok(Response::new(result.into())),
+            Err(e) => {
+                self.state.engine.audit().record(AuditActor::Api, AuditCategory::Guard, "manual_trade_rejected", serde_json::json!({
+                    "path": req.path,
+                    "amount": amount,
+                    "error": e.to_string(),
+                }));
+                Err(engine_status(e))
+            }
+        }
+    }
+
+    async fn get_config(&self, _: Request<proto::GetConfigRequest>) -> Result<Response<proto::ConfigReply>, Status> {
+        let config = self.state.db.get_config().await.map_err(|e| Status::internal(e.to_string()))?;
+        let config_json = serde_json::to_string(&config).map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(proto::ConfigReply { config_json, changes_json: String::new() }))
+    }
+
+    async fn update_config(&self, request: Request<proto::UpdateConfigRequest>) -> Result<Response<proto::ConfigReply>, Status> {
+        let updates: ConfigUpdate = serde_json::from_str(&request.into_inner().updates_json)
+            .map_err(|e| Status::invalid_argument(format!("Invalid updates_json: {}", e)))?;
+        let patch = ConfigPatch { trading: Some(updates), fees: None };
+        let applied = self
+            .state
+            .engine
+            .apply_config(patch, AuditActor::Api, "grpc")
+            .await
+            .map_err(config_status)?;
+        Ok(Response::new(proto::ConfigReply {
+            config_json: serde_json::to_string(&applied.config).map_err(|e| Status::internal(e.to_string()))?,
+            changes_json: serde_json::to_string(&applied.changes).map_err(|e| Status::internal(e.to_string()))?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{LegDetail, Opportunity, Strategy};
+    use chrono::Utc;
+
+    #[test]
+    fn test_opportunity_and_errors_to_proto() {
+        let detected_at = Utc::now();
+        let opp = OpportunityWithAge {
+            opportunity: Opportunity {
+                id: "opp-1".to_string(),
+                path: "USD → BTC → ETH → USD".to_string(),
+                legs: 3,
+                gross_profit_pct: 1.0,
+                fees_pct: 0.78,
+                net_profit_pct: 0.22,
+                is_profitable: true,
+                detected_at,
+                fee_rate: 0.0026,
+                fee_source: "default".to_string(),
+                legs_detail: vec![LegDetail { pair: "BTC/USD".to_string(), action: "buy".to_string(), rate: 50_000.0 }],
+                strategy: Strategy::CrossPair,
+                tags: vec![],
+                inverse: None,
+            },
+            age_ms: 120,
+            max_move_bps: 1.5,
+            legs_updated: 1,
+        };
+        let reply = proto::Opportunity::from(opp);
+        assert_eq!((reply.legs, reply.strategy.as_str()), (3, "cross_pair"));
+        assert_eq!(reply.detected_at_ms, detected_at.timestamp_millis());
+        assert_eq!(reply.legs_detail[0].pair, "BTC/USD");
+
+        let funds = EngineError::InsufficientFunds {
+            currency: "USD".to_string(),
+            needed: 100.0,
+            available: 20.0,
+            needed_usd: None,
+            available_usd: None,
+        };
+        assert_eq!(engine_status(funds).code(), tonic::Code::FailedPrecondition);
+        assert_eq!(engine_status(EngineError::Database("down".to_string())).code(), tonic::Code::Internal);
+        assert_eq!(config_status(ConfigError::Invalid(vec![])).code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,95 @@
+//! Guard Verdicts
+//!
+//! The hot path stops at the first guard that blocks an opportunity and only
+//! reports that one, which makes a quiet loop hard to read: fixing the
+//! cooldown just uncovers the reserve. A verdict evaluates every guard the
+//! hot path would apply to an opportunity, in the hot path's order, and
+//! records each one's outcome with the values it compared:
+//! - running: the loop is up and its circuit breaker hasn't tripped
+//! - loss_limits: daily and total losses under their limits
+//! - profit_threshold: net profit above the threshold for its leg count
+//! - staleness: young enough for the execution queue
+//! - cooldown: no global, path or pair cooldown in effect
+//! - warmup, safe_mode, throttle
+//! - execution_engine: connected and no leg on a scan-only pair
+//! - balance: something left to spend above the start currency's reserve
+//! - exposure: the trade fits the notional limits
+//!
+//! Evaluating a guard has no side effects (strategy plugins are not asked,
+//! since asking counts as a decision).
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// One guard's outcome
+#[derive(Debug, Clone, Serialize)]
+pub struct GuardCheck {
+    pub guard: &'static str,
+    pub passed: bool,
+    /// Values the guard compared
+    pub detail: serde_json::Value,
+}
+
+/// Every guard evaluated for one opportunity
+#[derive(Debug, Clone, Serialize)]
+pub struct GuardVerdict {
+    pub id: String,
+    pub path: String,
+    pub net_profit_pct: f64,
+    pub age_ms: i64,
+    pub checked_at: DateTime<Utc>,
+    /// Every guard passed: the hot path would trade it
+    pub passed: bool,
+    /// First failing guard, the one the hot path would report
+    pub blocked_by: Option<&'static str>,
+    pub checks: Vec<GuardCheck>,
+}
+
+impl GuardVerdict {
+    pub fn new(id: &str, path: &str, net_profit_pct: f64, age_ms: i64) -> Self {
+        Self {
+            id: id.to_string(),
+            path: path.to_string(),
+            net_profit_pct,
+            age_ms,
+            checked_at: Utc::now(),
+            passed: true,
+            blocked_by: None,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Record a guard's outcome
+    pub fn check(&mut self, guard: &'static str, passed: bool, detail: serde_json::Value) {
+        if !passed {
+            self.passed = false;
+            self.blocked_by.get_or_insert(guard);
+        }
+        self.checks.push(GuardCheck { guard, passed, detail });
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &GuardCheck> {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verdict_keeps_every_check() {
+        let mut verdict = GuardVerdict::new("opp-1", "USD → BTC → ETH → USD", 0.3, 40);
+        verdict.check("profit_threshold", true, json!({"net_profit_pct": 0.3, "threshold_pct": 0.1}));
+        verdict.check("cooldown", false, json!({"scope": "path"}));
+        verdict.check("warmup", true, json!({}));
+        verdict.check("balance", false, json!({"spendable": 0.0}));
+
+        assert!(!verdict.passed);
+        assert_eq!(verdict.blocked_by, Some("cooldown"));
+        assert_eq!(verdict.checks.len(), 4);
+        assert_eq!(verdict.failed().map(|c| c.guard).collect::<Vec<_>>(), vec!["cooldown", "balance"]);
+    }
+}
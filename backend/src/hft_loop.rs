@@ -7,27 +7,48 @@
 //! 4. COLD PATH: All validation happens AFTER trade completes
 //!
 //! State Machine:
-//! IDLE → [event trigger] → HOT_PATH → [complete/fail] → COLD_PATH → IDLE
+//! IDLE → [event or sweep] → HOT_PATH → [complete/fail] → COLD_PATH → IDLE
 //!                                                              ↓
 //!                                                          [circuit break]
 //!                                                              ↓
 //!                                                           STOPPED
 #![allow(dead_code)]
 
+use crate::anomaly::{AnomalyDetector, AnomalyReason};
+use crate::audit::{AuditActor, AuditCategory, AuditLog};
 use crate::config_manager::ConfigManager;
 use crate::db::{Database, NewLiveTrade};
-use crate::executor::ExecutionEngine;
+use crate::exec_queue::ExecutionQueue;
+use crate::executor::{ExecutionEngine, ExecutionError, ExecutionStats};
+use crate::guards::GuardVerdict;
+use crate::notional::{NotionalBlock, NotionalHeadroom, NotionalLimits, NotionalTracker};
+use crate::opportunity_cache::OpportunityCache;
 use crate::order_book::OrderBookCache;
-use crate::scanner::Scanner;
-use crate::types::Opportunity;
-
+use crate::scan_profile::ScanProfiler;
+use crate::scan_fairness::ScanFairness;
+use crate::scanner::{LiquidityRequirement, Scanner, MIN_BOOK_LEVELS};
+use crate::stablecoin::StablecoinPolicy;
+use crate::strategy_plugin::{PluginDecision, StrategyPlugins};
+use crate::types::{BookDelta, Opportunity, Strategy};
+use crate::universe::{UniverseTrigger, Universes};
+use crate::valuation::{ReportedPnl, Valuator};
+use crate::safe_mode::SafeMode;
+use crate::throttle::{PerformanceThrottle, ThrottleBlock};
+use crate::trade_lifecycle::{filled_notional, TradeLifecycle, TradeRecord};
+use crate::supervisor::{spawn_supervised, Heartbeat, TaskSupervisor, HEARTBEAT_INTERVAL};
+use crate::webhook::{OpportunityWebhook, WebhookConfig};
+
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
 use tokio::sync::{mpsc, RwLock};
-use tracing::{debug, info, warn};
+use tracing::{info, warn};
 
 /// HFT Loop State
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HftState {
     /// Waiting for order book update event
     Idle,
@@ -48,6 +69,15 @@ pub struct LegTiming {
     pub duration_ms: u64,
     pub success: bool,
     pub error: Option<String>,
+    /// Requested amount when the leg was retried smaller for insufficient funds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resized_from: Option<f64>,
+    /// Input an IOC leg left unfilled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unfilled_amount: Option<f64>,
+    /// Fill against the best price when the order was sent, in bps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_improvement_bps: Option<f64>,
 }
 
 /// Result of a single trading cycle
@@ -55,20 +85,104 @@ pub struct LegTiming {
 pub enum CycleResult {
     /// No opportunity found, back to IDLE
     NoOpportunity,
+    /// Opportunity (or the whole loop) is cooling down after a recent trade
+    CoolingDown {
+        scope: CooldownScope,
+        key: String,
+        remaining_ms: u64,
+    },
+    /// Opportunity found but the engine hasn't finished warming up
+    WarmingUp,
+    /// Start currency has nothing left above its configured reserve
+    ReserveBlocked {
+        currency: String,
+        balance: Option<f64>,
+        reserve: f64,
+    },
+    /// Trade would take traded notional past an hourly/daily limit
+    NotionalBlocked(NotionalBlock),
+    /// Net profit doesn't beat the threshold configured for the path length
+    BelowLegThreshold {
+        path: String,
+        legs: usize,
+        net_profit_pct: f64,
+        threshold_pct: f64,
+    },
+    /// A leg of the path goes through a scan-only pair
+    PairDisabled {
+        path: String,
+        pair: String,
+    },
+    /// Safe mode is on: nothing executes until an operator confirms
+    SafeModeBlocked {
+        path: String,
+    },
+    /// Recent trades realized well below expectations and the throttle is engaged
+    Throttled {
+        path: String,
+        block: ThrottleBlock,
+    },
+    /// First leg skipped: imbalance/microprice pointed against it
+    SignalSkipped {
+        path: String,
+        pair: String,
+        pressure: f64,
+    },
+    /// Opportunity too old by its first order (after a stall or a backlog)
+    StaleOpportunity {
+        path: String,
+        age_ms: i64,
+        max_age_ms: u64,
+    },
+    /// Implausibly profitable, or through a pair blamed for such an opportunity
+    AnomalyQuarantined {
+        path: String,
+        pair: String,
+        reason: AnomalyReason,
+        net_profit_pct: f64,
+    },
+    /// A leg can't absorb the tier-sized trade amount within the depth limits
+    Illiquid {
+        path: String,
+        trade_amount: f64,
+    },
+    /// A strategy plugin vetoed the trade
+    PluginSkipped {
+        path: String,
+        plugin: String,
+        reason: String,
+    },
     /// Trade executed successfully
     TradeSuccess {
+        trade_id: String,
         path: String,
+        trade_amount: f64,
         profit_pct: f64,
+        /// Net profit the scanner expected
+        expected_profit_pct: f64,
+        /// In the start currency
         profit_amount: f64,
+        /// profit_amount with its reporting-currency conversion at completion
+        pnl: ReportedPnl,
         duration_ms: u64,
         leg_timings: Vec<LegTiming>,
+        /// USD spent by the legs that filled
+        notional_usd: f64,
+        strategy: Strategy,
+        tags: Vec<String>,
     },
     /// Trade failed (partial or error)
     TradeFailed {
+        trade_id: String,
         path: String,
+        trade_amount: f64,
         error: String,
         is_partial: bool,
         leg_timings: Vec<LegTiming>,
+        /// USD spent by the legs that filled
+        notional_usd: f64,
+        strategy: Strategy,
+        tags: Vec<String>,
     },
     /// Circuit breaker tripped
     CircuitBroken {
@@ -86,7 +200,7 @@ pub enum ColdPathDecision {
 }
 
 /// HFT Loop Statistics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct HftStats {
     pub cycles_completed: u64,
     pub opportunities_found: u64,
@@ -94,47 +208,416 @@ pub struct HftStats {
     pub trades_successful: u64,
     pub trades_failed: u64,
     pub trades_partial: u64,
+    /// P&L totals are in the reporting currency
     pub total_profit: f64,
     pub total_loss: f64,
     pub daily_profit: f64,
     pub daily_loss: f64,
     pub events_received: u64,
+    /// Book updates below the levels any scan reads (no scan triggered)
+    pub events_skipped_deep: u64,
     pub events_ignored_in_hot_path: u64,
+    pub skipped_cooldown: u64,
+    /// Profitable paths dropped by the scanner's per-leg liquidity requirement
+    pub skipped_illiquid: u64,
+    /// Opportunities not executed because warm-up wasn't complete
+    pub skipped_warmup: u64,
+    /// Opportunities not executed because the start currency was at its reserve
+    pub skipped_reserve: u64,
+    /// Opportunities not executed because a notional limit had no room
+    pub skipped_notional: u64,
+    /// Opportunities not executed because a leg's pair is scan-only
+    pub skipped_disabled_pair: u64,
+    /// Opportunities not executed because they missed their path length's threshold
+    pub skipped_leg_threshold: u64,
+    /// Opportunities not executed because the engine was in safe mode
+    pub skipped_safe_mode: u64,
+    /// Opportunities not executed because the performance throttle was engaged
+    pub skipped_throttle: u64,
+    /// Opportunities abandoned because the first leg's book leaned against it
+    pub skipped_signal: u64,
+    /// Opportunities a strategy plugin vetoed
+    pub skipped_plugin: u64,
+    /// Opportunities dropped for being too old when their first order was due
+    pub skipped_stale: u64,
+    /// Opportunities quarantined by the profit anomaly detector
+    pub skipped_anomaly: u64,
 }
 
 /// Configuration for HFT Loop
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct HftConfig {
     /// Minimum profit threshold (from user, can be negative for test mode)
     pub min_profit_threshold: f64,
     /// Trade amount in USD
     pub trade_amount: f64,
-    /// Maximum daily loss before circuit break
+    /// Maximum daily loss before circuit break (reporting currency)
     pub max_daily_loss: f64,
-    /// Maximum total loss before circuit break
+    /// Maximum total loss before circuit break (reporting currency)
     pub max_total_loss: f64,
+    /// Currency P&L is reported and loss limits are counted in
+    pub reporting_currency: String,
     /// Base currencies to scan (USD, EUR, etc.)
     pub base_currencies: Vec<String>,
+    /// Profit-tiered sizing, sorted by min_profit_pct (empty = always trade_amount)
+    pub sizing_tiers: Vec<SizingTier>,
+    /// Hard cap on any auto-execution amount
+    pub max_safe_amount: Option<f64>,
+    /// Pause lengths between trades
+    pub cooldowns: CooldownConfig,
+    /// Per-leg depth/slippage limit applied while scanning (None = off)
+    pub leg_liquidity: Option<LiquidityRequirement>,
+    /// Balance per currency that auto-execution never spends (e.g. USD: 500)
+    pub reserves: HashMap<String, f64>,
+    /// Fiat/stablecoin-only cycles (off unless STABLECOIN_CYCLES=true)
+    pub stablecoins: StablecoinPolicy,
+    /// USD traded across all legs per rolling hour / day
+    pub notional_limits: NotionalLimits,
+    /// Rolling quality score (0-100) a pair needs to be scanned (0 = off)
+    pub min_pair_quality: f64,
+}
+
+/// Loop state, stats and config read at the same instant
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HftSnapshot {
+    pub state: HftState,
+    pub stats: HftStats,
+    pub config: HftConfig,
+}
+
+/// Cooldowns applied by the HFT loop (milliseconds, 0 = off; all off unless configured)
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct CooldownConfig {
+    /// After any trade, before the next one
+    pub global_ms: u64,
+    /// Before the same path is traded again
+    pub path_ms: u64,
+    /// Before a pair whose leg failed is used again
+    pub pair_failure_ms: u64,
+}
+
+/// What a cooldown applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CooldownScope {
+    Global,
+    Path,
+    Pair,
+}
+
+/// An active cooldown with the time it has left
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ActiveCooldown {
+    pub scope: CooldownScope,
+    pub key: String,
+    pub remaining_ms: u64,
+}
+
+/// Tracks when the loop, paths and pairs may trade again
+#[derive(Debug, Default)]
+pub struct CooldownTracker {
+    global_until: Option<Instant>,
+    paths: HashMap<String, Instant>,
+    pairs: HashMap<String, Instant>,
+}
+
+impl CooldownTracker {
+    /// Start cooldowns after a trade (failed_pairs = pairs of legs that failed)
+    pub fn record_trade(&mut self, config: &CooldownConfig, path: &str, failed_pairs: &[&str], now: Instant) {
+        if config.global_ms > 0 {
+            self.global_until = Some(now + Duration::from_millis(config.global_ms));
+        }
+        if config.path_ms > 0 {
+            self.paths.insert(path.to_string(), now + Duration::from_millis(config.path_ms));
+        }
+        if config.pair_failure_ms > 0 {
+            for pair in failed_pairs {
+                self.pairs.insert(pair.to_string(), now + Duration::from_millis(config.pair_failure_ms));
+            }
+        }
+    }
+
+    /// Remaining global cooldown, if any
+    pub fn global_remaining(&self, now: Instant) -> Option<u64> {
+        self.global_until
+            .filter(|until| *until > now)
+            .map(|until| (until - now).as_millis() as u64)
+    }
+
+    /// First cooldown that blocks this opportunity (path first, then its pairs)
+    pub fn blocking(&self, opp: &Opportunity, now: Instant) -> Option<ActiveCooldown> {
+        if let Some(until) = self.paths.get(&opp.path).filter(|until| **until > now) {
+            return Some(ActiveCooldown {
+                scope: CooldownScope::Path,
+                key: opp.path.clone(),
+                remaining_ms: (*until - now).as_millis() as u64,
+            });
+        }
+        opp.legs_detail.iter().find_map(|leg| {
+            self.pairs.get(&leg.pair).filter(|until| **until > now).map(|until| ActiveCooldown {
+                scope: CooldownScope::Pair,
+                key: leg.pair.clone(),
+                remaining_ms: (*until - now).as_millis() as u64,
+            })
+        })
+    }
+
+    /// All active cooldowns (expired entries are dropped)
+    pub fn active(&mut self, now: Instant) -> Vec<ActiveCooldown> {
+        self.paths.retain(|_, until| *until > now);
+        self.pairs.retain(|_, until| *until > now);
+
+        let mut active: Vec<ActiveCooldown> = self.global_remaining(now)
+            .map(|remaining_ms| ActiveCooldown { scope: CooldownScope::Global, key: "*".to_string(), remaining_ms })
+            .into_iter()
+            .collect();
+        for (scope, entries) in [(CooldownScope::Path, &self.paths), (CooldownScope::Pair, &self.pairs)] {
+            active.extend(entries.iter().map(|(key, until)| ActiveCooldown {
+                scope,
+                key: key.clone(),
+                remaining_ms: (*until - now).as_millis() as u64,
+            }));
+        }
+        active.sort_by_key(|c| std::cmp::Reverse(c.remaining_ms));
+        active
+    }
+}
+
+/// One rung of the sizing ladder: opportunities at or above min_profit_pct trade `amount`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SizingTier {
+    /// Net profit in percent (0.2 = 0.2%), same unit as Opportunity::net_profit_pct
+    pub min_profit_pct: f64,
+    /// Trade amount in start currency
+    pub amount: f64,
+}
+
+impl SizingTier {
+    /// Parse and validate the sizing_tiers JSON from live_trading_config
+    /// Returns tiers sorted ascending by min_profit_pct
+    pub fn parse_tiers(value: &serde_json::Value) -> Result<Vec<SizingTier>, String> {
+        let mut tiers: Vec<SizingTier> = serde_json::from_value(value.clone())
+            .map_err(|e| format!("sizing_tiers must be a list of {{min_profit_pct, amount}}: {}", e))?;
+
+        for tier in &tiers {
+            if !tier.min_profit_pct.is_finite() {
+                return Err("sizing_tiers: min_profit_pct must be a number".to_string());
+            }
+            if !tier.amount.is_finite() || tier.amount <= 0.0 {
+                return Err(format!("sizing_tiers: amount must be > 0 (got {})", tier.amount));
+            }
+        }
+
+        tiers.sort_by(|a, b| a.min_profit_pct.partial_cmp(&b.min_profit_pct).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(tiers)
+    }
+}
+
+/// Parse and validate the currency_reserves JSON from live_trading_config,
+/// e.g. {"USD": 500}. Currency codes are uppercased.
+pub fn parse_reserves(value: &serde_json::Value) -> Result<HashMap<String, f64>, String> {
+    let raw: HashMap<String, f64> = serde_json::from_value(value.clone())
+        .map_err(|e| format!("currency_reserves must be an object of {{currency: amount}}: {}", e))?;
+
+    let mut reserves = HashMap::with_capacity(raw.len());
+    for (currency, amount) in raw {
+        let currency = currency.trim().to_uppercase();
+        if currency.is_empty() {
+            return Err("currency_reserves: currency must not be empty".to_string());
+        }
+        if !amount.is_finite() || amount < 0.0 {
+            return Err(format!("currency_reserves: {} reserve must be >= 0 (got {})", currency, amount));
+        }
+        reserves.insert(currency, amount);
+    }
+    Ok(reserves)
+}
+
+impl HftConfig {
+    /// Trade amount for an opportunity: highest tier reached, else trade_amount,
+    /// always capped by max_safe_amount
+    pub fn trade_amount_for(&self, net_profit_pct: f64) -> f64 {
+        let amount = self.sizing_tiers
+            .iter()
+            .rev()
+            .find(|t| net_profit_pct >= t.min_profit_pct)
+            .map(|t| t.amount)
+            .unwrap_or(self.trade_amount);
+
+        match self.max_safe_amount {
+            Some(cap) if cap > 0.0 => amount.min(cap),
+            _ => amount,
+        }
+    }
+
+    /// How much of `currency` auto-execution may spend: balance minus reserve.
+    /// None when the currency has no reserve (no limit). Without a known
+    /// balance nothing is spendable, since the reserve can't be verified.
+    pub fn spendable(&self, currency: &str, balances: Option<&HashMap<String, f64>>) -> Option<f64> {
+        let reserve = self.reserves.get(currency).copied().filter(|r| *r > 0.0)?;
+        let balance = balances.and_then(|b| b.get(currency)).copied().unwrap_or(0.0);
+        Some((balance - reserve).max(0.0))
+    }
+}
+
+/// Conditions that must hold before auto-execution may trade after a start
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct WarmupPolicy {
+    /// Time since the loop was created
+    pub min_uptime_secs: u64,
+    /// Share of pairs with a fresh two-sided book (0-100)
+    pub min_fresh_pairs_pct: f64,
+    /// Hot-path scans performed
+    pub min_scans: u64,
+}
+
+impl Default for WarmupPolicy {
+    fn default() -> Self {
+        Self {
+            min_uptime_secs: 30,
+            min_fresh_pairs_pct: 80.0,
+            min_scans: 100,
+        }
+    }
+}
+
+impl WarmupPolicy {
+    /// Defaults overridden by WARMUP_MIN_UPTIME_SECS, WARMUP_MIN_FRESH_PAIRS_PCT
+    /// and WARMUP_MIN_SCANS (all 0 = no warm-up)
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            min_uptime_secs: env("WARMUP_MIN_UPTIME_SECS").unwrap_or(defaults.min_uptime_secs),
+            min_fresh_pairs_pct: env("WARMUP_MIN_FRESH_PAIRS_PCT").unwrap_or(defaults.min_fresh_pairs_pct),
+            min_scans: env("WARMUP_MIN_SCANS").unwrap_or(defaults.min_scans),
+        }
+    }
+}
+
+/// Warm-up progress for the status API
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WarmupProgress {
+    pub complete: bool,
+    pub uptime_secs: u64,
+    pub fresh_pairs_pct: f64,
+    pub scans: u64,
+    pub policy: WarmupPolicy,
+}
+
+/// Blocks execution until the warm-up policy is met. Once met it stays
+/// complete for the lifetime of the loop - a later dip in book freshness is
+/// the scanner's problem, not warm-up's.
+#[derive(Debug)]
+pub struct WarmupGate {
+    policy: WarmupPolicy,
+    started_at: Instant,
+    scans: AtomicU64,
+    complete: AtomicBool,
+}
+
+impl WarmupGate {
+    pub fn new(policy: WarmupPolicy) -> Self {
+        Self {
+            policy,
+            started_at: Instant::now(),
+            scans: AtomicU64::new(0),
+            complete: AtomicBool::new(false),
+        }
+    }
+
+    pub fn record_scan(&self) {
+        self.scans.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// True once every warm-up condition has held at least once
+    pub fn is_complete(&self, cache: &OrderBookCache) -> bool {
+        if self.complete.load(Ordering::Relaxed) {
+            return true;
+        }
+        let progress = self.progress(cache);
+        if progress.complete {
+            self.complete.store(true, Ordering::Relaxed);
+            info!(
+                "Warm-up complete after {}s and {} scans ({:.0}% of books fresh) - auto-execution allowed",
+                progress.uptime_secs, progress.scans, progress.fresh_pairs_pct
+            );
+        }
+        progress.complete
+    }
+
+    pub fn progress(&self, cache: &OrderBookCache) -> WarmupProgress {
+        let uptime_secs = self.started_at.elapsed().as_secs();
+        let fresh_pairs_pct = cache.fresh_book_ratio(crate::types::MAX_ORDERBOOK_STALENESS_MS) * 100.0;
+        let scans = self.scans.load(Ordering::Relaxed);
+        let complete = self.complete.load(Ordering::Relaxed)
+            || (uptime_secs >= self.policy.min_uptime_secs
+                && fresh_pairs_pct >= self.policy.min_fresh_pairs_pct
+                && scans >= self.policy.min_scans);
+
+        WarmupProgress {
+            complete,
+            uptime_secs,
+            fresh_pairs_pct,
+            scans,
+            policy: self.policy,
+        }
+    }
 }
 
 /// Unified HFT Trading Loop
 pub struct HftLoop {
     // State
     state: Arc<RwLock<HftState>>,
+    /// Why the breaker is tripped (None while trading)
+    breaker: Arc<parking_lot::Mutex<Option<String>>>,
     stats: Arc<RwLock<HftStats>>,
     config: Arc<RwLock<HftConfig>>,
+    cooldowns: Arc<RwLock<CooldownTracker>>,
+    warmup: Arc<WarmupGate>,
+    notional: Arc<NotionalTracker>,
 
     // Core components
     cache: Arc<OrderBookCache>,
     config_manager: Arc<ConfigManager>,
     execution_engine: Arc<RwLock<Option<ExecutionEngine>>>,
-    db: Database,
+    /// Records executed trades, the loop's and manual ones alike
+    lifecycle: Arc<TradeLifecycle>,
+    audit: AuditLog,
+    opportunities: Arc<OpportunityCache>,
+    /// Detected opportunities forwarded to an external endpoint (off by default)
+    webhook: Arc<OpportunityWebhook>,
+    /// Execution is blocked while active (never entered by default)
+    safe_mode: Arc<SafeMode>,
+    /// Raises the threshold or pauses after realized profit falls short (off by default)
+    throttle: Arc<PerformanceThrottle>,
+    /// Runtime the loop runs on (None = the caller's)
+    runtime: Option<Handle>,
+    /// Restarts the loop after a panic (None = a panic ends it)
+    supervisor: Option<Arc<TaskSupervisor>>,
+    scan_profiler: Arc<ScanProfiler>,
+    valuator: Arc<Valuator>,
+    /// Named currency subgraphs scanned alongside the main scan (none by default)
+    universes: Arc<Universes>,
+    /// Sweeps every cycle when no scan has covered them in time (off by default)
+    fairness: Arc<ScanFairness>,
+    /// Opportunities waiting for execution, best first
+    exec_queue: Arc<ExecutionQueue>,
+    /// Custom detection and execution hooks (none by default)
+    plugins: Arc<StrategyPlugins>,
+    /// Holds back implausibly profitable opportunities and their suspect pairs
+    anomalies: Arc<AnomalyDetector>,
+    /// Last known exchange balances (None until the first refresh)
+    balances: Arc<RwLock<Option<HashMap<String, f64>>>>,
 
     // Control flags
     is_running: Arc<AtomicBool>,
 
     // Counters
     cycle_count: Arc<AtomicU64>,
+    liquidity_filtered: Arc<AtomicU64>,
 }
 
 impl HftLoop {
@@ -142,31 +625,151 @@ impl HftLoop {
         cache: Arc<OrderBookCache>,
         config_manager: Arc<ConfigManager>,
         db: Database,
+        audit: AuditLog,
+        opportunities: Arc<OpportunityCache>,
+        scan_profiler: Arc<ScanProfiler>,
+        valuator: Arc<Valuator>,
     ) -> Self {
+        let stats = Arc::new(RwLock::new(HftStats::default()));
+        let config = Arc::new(RwLock::new(HftConfig {
+            min_profit_threshold: 0.0,
+            trade_amount: 10.0,
+            max_daily_loss: 100.0,
+            max_total_loss: 500.0,
+            reporting_currency: "USD".to_string(),
+            base_currencies: vec!["USD".to_string()],
+            sizing_tiers: Vec::new(),
+            max_safe_amount: None,
+            cooldowns: CooldownConfig::default(),
+            leg_liquidity: None,
+            reserves: HashMap::new(),
+            stablecoins: StablecoinPolicy::default(),
+            notional_limits: NotionalLimits::default(),
+            min_pair_quality: 0.0,
+        }));
+        let cooldowns = Arc::new(RwLock::new(CooldownTracker::default()));
+        let notional = Arc::new(NotionalTracker::new());
+        let lifecycle = Arc::new(TradeLifecycle::new(
+            Arc::clone(&stats),
+            Arc::clone(&config),
+            Arc::clone(&cooldowns),
+            Arc::clone(&notional),
+            db,
+        ));
         Self {
             state: Arc::new(RwLock::new(HftState::Idle)),
-            stats: Arc::new(RwLock::new(HftStats::default())),
-            config: Arc::new(RwLock::new(HftConfig {
-                min_profit_threshold: 0.0,
-                trade_amount: 10.0,
-                max_daily_loss: 100.0,
-                max_total_loss: 500.0,
-                base_currencies: vec!["USD".to_string()],
-            })),
+            breaker: Arc::new(parking_lot::Mutex::new(None)),
+            stats,
+            config,
+            cooldowns,
+            warmup: Arc::new(WarmupGate::new(WarmupPolicy::from_env())),
+            notional,
             cache,
             config_manager,
             execution_engine: Arc::new(RwLock::new(None)),
-            db,
+            lifecycle,
+            audit,
+            opportunities,
+            webhook: OpportunityWebhook::new(WebhookConfig::default()),
+            safe_mode: Arc::new(SafeMode::default()),
+            throttle: Arc::new(PerformanceThrottle::default()),
+            runtime: None,
+            supervisor: None,
+            scan_profiler,
+            valuator,
+            universes: Arc::new(Universes::default()),
+            fairness: Arc::new(ScanFairness::default()),
+            exec_queue: Arc::new(ExecutionQueue::default()),
+            plugins: Arc::new(StrategyPlugins::default()),
+            anomalies: Arc::new(AnomalyDetector::default()),
+            balances: Arc::new(RwLock::new(None)),
             is_running: Arc::new(AtomicBool::new(false)),
             cycle_count: Arc::new(AtomicU64::new(0)),
+            liquidity_filtered: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Replace the warm-up policy (before the event channel is created)
+    pub fn with_warmup(mut self, policy: WarmupPolicy) -> Self {
+        self.warmup = Arc::new(WarmupGate::new(policy));
+        self
+    }
+
+    /// Forward detected opportunities to `webhook`
+    pub fn with_webhook(mut self, webhook: Arc<OpportunityWebhook>) -> Self {
+        self.webhook = webhook;
+        self
+    }
+
+    /// Block execution whenever `safe_mode` is active
+    pub fn with_safe_mode(mut self, safe_mode: Arc<SafeMode>) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+
+    /// Compare realized with expected profit and throttle execution on a shortfall
+    pub fn with_throttle(mut self, throttle: Arc<PerformanceThrottle>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Also scan the universes in `universes` on every triggered scan
+    pub fn with_universes(mut self, universes: Arc<Universes>) -> Self {
+        self.universes = universes;
+        self
+    }
+
+    /// Scan every cycle at least as often as `fairness` requires, events or not
+    pub fn with_fairness(mut self, fairness: Arc<ScanFairness>) -> Self {
+        self.fairness = fairness;
+        self
+    }
+
+    /// Execute what scans find in `exec_queue`'s priority order
+    pub fn with_exec_queue(mut self, exec_queue: Arc<ExecutionQueue>) -> Self {
+        self.exec_queue = exec_queue;
+        self
+    }
+
+    /// Run `plugins`' hooks on book updates, finds and trades
+    pub fn with_plugins(mut self, plugins: Arc<StrategyPlugins>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Quarantine opportunities `anomalies` finds implausible
+    pub fn with_anomalies(mut self, anomalies: Arc<AnomalyDetector>) -> Self {
+        self.anomalies = anomalies;
+        self
+    }
+
+    /// Run the loop (scans and order placement) on `runtime`
+    pub fn with_runtime(mut self, runtime: Handle) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Restart the loop under `supervisor` when it panics
+    pub fn with_supervisor(mut self, supervisor: Arc<TaskSupervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
     /// Update configuration from database
     pub async fn update_config(&self, config: HftConfig) {
         *self.config.write().await = config;
     }
 
+    /// Whether any currency has a reserve to protect
+    pub async fn has_reserves(&self) -> bool {
+        self.config.read().await.reserves.values().any(|r| *r > 0.0)
+    }
+
+    /// Replace the balance snapshot used to protect reserves
+    pub async fn set_balances(&self, balances: HashMap<String, f64>) {
+        *self.balances.write().await = Some(balances);
+    }
+
     /// Set execution engine
     pub async fn set_execution_engine(&self, engine: ExecutionEngine) {
         *self.execution_engine.write().await = Some(engine);
@@ -179,62 +782,189 @@ impl HftLoop {
 
     /// Get statistics
     pub async fn get_stats(&self) -> HftStats {
-        self.stats.read().await.clone()
+        let mut stats = self.stats.read().await.clone();
+        stats.skipped_illiquid = self.liquidity_filtered.load(Ordering::Relaxed);
+        stats
+    }
+
+    /// State, stats and config with all three locks held together, so they
+    /// describe the same instant
+    pub async fn snapshot(&self) -> HftSnapshot {
+        let state = self.state.read().await;
+        let stats = self.stats.read().await;
+        let config = self.config.read().await;
+        let mut stats = stats.clone();
+        stats.skipped_illiquid = self.liquidity_filtered.load(Ordering::Relaxed);
+        HftSnapshot { state: *state, stats, config: config.clone() }
     }
 
     /// Create event channel for order book updates
-    pub fn create_event_channel(&mut self) -> mpsc::Sender<String> {
+    pub fn create_event_channel(&mut self) -> mpsc::Sender<BookDelta> {
         let (tx, rx) = mpsc::channel(1000);
 
         // Spawn the main loop
         let state = Arc::clone(&self.state);
+        let breaker = Arc::clone(&self.breaker);
         let stats = Arc::clone(&self.stats);
         let config = Arc::clone(&self.config);
+        let cooldowns = Arc::clone(&self.cooldowns);
+        let warmup = Arc::clone(&self.warmup);
+        let notional = Arc::clone(&self.notional);
         let cache = Arc::clone(&self.cache);
         let config_manager = Arc::clone(&self.config_manager);
         let execution_engine = Arc::clone(&self.execution_engine);
         let is_running = Arc::clone(&self.is_running);
         let cycle_count = Arc::clone(&self.cycle_count);
-        let db = self.db.clone();
-
-        tokio::spawn(async move {
-            Self::run_loop(
-                rx,
-                state,
-                stats,
-                config,
-                cache,
-                config_manager,
-                execution_engine,
-                is_running,
-                cycle_count,
-                db,
-            ).await;
+        let liquidity_filtered = Arc::clone(&self.liquidity_filtered);
+        let audit = self.audit.clone();
+        let opportunities = Arc::clone(&self.opportunities);
+        let webhook = Arc::clone(&self.webhook);
+        let safe_mode = Arc::clone(&self.safe_mode);
+        let throttle = Arc::clone(&self.throttle);
+        let balances = Arc::clone(&self.balances);
+        let scan_profiler = Arc::clone(&self.scan_profiler);
+        let valuator = Arc::clone(&self.valuator);
+        let universes = Arc::clone(&self.universes);
+        let fairness = Arc::clone(&self.fairness);
+        let exec_queue = Arc::clone(&self.exec_queue);
+        let plugins = Arc::clone(&self.plugins);
+        let anomalies = Arc::clone(&self.anomalies);
+        let lifecycle = Arc::clone(&self.lifecycle);
+        let heartbeat = self.supervisor.as_ref().map_or_else(Heartbeat::default, |s| s.heartbeat("hft_loop"));
+
+        // A restart after a panic resumes on the same receiver
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+        spawn_supervised(self.supervisor.as_ref(), self.runtime.as_ref(), "hft_loop", move || {
+            let state = Arc::clone(&state);
+            let breaker = Arc::clone(&breaker);
+            let stats = Arc::clone(&stats);
+            let config = Arc::clone(&config);
+            let cooldowns = Arc::clone(&cooldowns);
+            let warmup = Arc::clone(&warmup);
+            let notional = Arc::clone(&notional);
+            let cache = Arc::clone(&cache);
+            let config_manager = Arc::clone(&config_manager);
+            let execution_engine = Arc::clone(&execution_engine);
+            let is_running = Arc::clone(&is_running);
+            let cycle_count = Arc::clone(&cycle_count);
+            let liquidity_filtered = Arc::clone(&liquidity_filtered);
+            let lifecycle = Arc::clone(&lifecycle);
+            let audit = audit.clone();
+            let opportunities = Arc::clone(&opportunities);
+            let webhook = Arc::clone(&webhook);
+            let safe_mode = Arc::clone(&safe_mode);
+            let throttle = Arc::clone(&throttle);
+            let balances = Arc::clone(&balances);
+            let scan_profiler = Arc::clone(&scan_profiler);
+            let valuator = Arc::clone(&valuator);
+            let universes = Arc::clone(&universes);
+            let fairness = Arc::clone(&fairness);
+            let exec_queue = Arc::clone(&exec_queue);
+            let plugins = Arc::clone(&plugins);
+            let anomalies = Arc::clone(&anomalies);
+            let heartbeat = heartbeat.clone();
+            let rx = Arc::clone(&rx);
+            async move {
+                Self::run_loop(
+                    rx,
+                    state,
+                    breaker,
+                    stats,
+                    config,
+                    cooldowns,
+                    warmup,
+                    notional,
+                    cache,
+                    config_manager,
+                    execution_engine,
+                    is_running,
+                    cycle_count,
+                    liquidity_filtered,
+                    lifecycle,
+                    audit,
+                    opportunities,
+                    webhook,
+                    safe_mode,
+                    throttle,
+                    balances,
+                    scan_profiler,
+                    valuator,
+                    universes,
+                    fairness,
+                    exec_queue,
+                    plugins,
+                    anomalies,
+                    heartbeat,
+                ).await;
+            }
         });
 
         tx
     }
 
     /// Main HFT loop - processes events and executes trades
+    #[allow(clippy::too_many_arguments)]
     async fn run_loop(
-        mut event_rx: mpsc::Receiver<String>,
+        event_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<BookDelta>>>,
         state: Arc<RwLock<HftState>>,
+        breaker: Arc<parking_lot::Mutex<Option<String>>>,
         stats: Arc<RwLock<HftStats>>,
         config: Arc<RwLock<HftConfig>>,
+        cooldowns: Arc<RwLock<CooldownTracker>>,
+        warmup: Arc<WarmupGate>,
+        notional: Arc<NotionalTracker>,
         cache: Arc<OrderBookCache>,
         config_manager: Arc<ConfigManager>,
         execution_engine: Arc<RwLock<Option<ExecutionEngine>>>,
         is_running: Arc<AtomicBool>,
         cycle_count: Arc<AtomicU64>,
-        db: Database,
+        liquidity_filtered: Arc<AtomicU64>,
+        lifecycle: Arc<TradeLifecycle>,
+        audit: AuditLog,
+        opportunities: Arc<OpportunityCache>,
+        webhook: Arc<OpportunityWebhook>,
+        safe_mode: Arc<SafeMode>,
+        throttle: Arc<PerformanceThrottle>,
+        balances: Arc<RwLock<Option<HashMap<String, f64>>>>,
+        scan_profiler: Arc<ScanProfiler>,
+        valuator: Arc<Valuator>,
+        universes: Arc<Universes>,
+        fairness: Arc<ScanFairness>,
+        exec_queue: Arc<ExecutionQueue>,
+        plugins: Arc<StrategyPlugins>,
+        anomalies: Arc<AnomalyDetector>,
+        heartbeat: Heartbeat,
     ) {
         info!("HFT Loop started");
         is_running.store(true, Ordering::SeqCst);
+        let mut event_rx = event_rx.lock().await;
+
+        // A run that panicked mid-cycle leaves its state behind; don't
+        // resume a hot path for an event that's gone
+        let mut leftover = state.write().await;
+        if matches!(*leftover, HftState::HotPath | HftState::ColdPath) {
+            warn!("HFT loop resuming from {:?} after a restart", *leftover);
+            *leftover = HftState::Idle;
+        }
+        drop(leftover);
+
+        // Last path/pair cooldown block written to the audit log, so a blocked
+        // opportunity seen on every book update is only recorded once
+        let mut last_guard_key: Option<String> = None;
+        // This pass is a fairness sweep rather than an event-triggered scan
+        let mut sweep = false;
 
         while is_running.load(Ordering::SeqCst) {
+            heartbeat.beat();
             // Wait for event (only when IDLE)
             let current_state = *state.read().await;
 
+            // A breaker tripped from outside the loop takes effect before the next cycle
+            if current_state != HftState::Stopped && breaker.lock().is_some() {
+                *state.write().await = HftState::Stopped;
+                continue;
+            }
+
             match current_state {
                 HftState::Stopped => {
                     // Circuit breaker tripped - wait for manual reset
@@ -242,15 +972,46 @@ impl HftLoop {
                     continue;
                 }
                 HftState::Idle => {
-                    // Wait for order book update event
-                    match event_rx.recv().await {
-                        Some(_pair) => {
-                            stats.write().await.events_received += 1;
+                    // Wait for order book update event, or until a sweep is due
+                    let received = match fairness.sweep_due_in(Instant::now()) {
+                        // Overdue: don't let a steady stream of events put it off
+                        Some(due_in) if due_in.is_zero() => None,
+                        Some(due_in) => tokio::time::timeout(due_in, Self::recv_beating(&mut event_rx, &heartbeat)).await.ok(),
+                        None => Some(Self::recv_beating(&mut event_rx, &heartbeat).await),
+                    };
+                    match received {
+                        // Nothing covered every cycle in time: sweep them all
+                        None => {
+                            fairness.record_sweep(Instant::now());
+                            sweep = true;
+                            *state.write().await = HftState::HotPath;
+                        }
+                        Some(Some(delta)) => {
+                            // Deep-level churn can't move prices, depth checks or the minimum book size
+                            let window = config.read().await.leg_liquidity
+                                .map_or(MIN_BOOK_LEVELS, |l| l.depth_levels.max(MIN_BOOK_LEVELS));
+                            // Plugin finds are queued for the hot path like the scanner's
+                            let mut plugin_found = false;
+                            if !plugins.is_empty() {
+                                let now = chrono::Utc::now();
+                                for opp in plugins.on_orderbook_update(&delta, &cache) {
+                                    plugins.on_opportunity(&opp);
+                                    opportunities.insert(&opp, &cache);
+                                    plugin_found |= exec_queue.push(opp, now);
+                                }
+                            }
+                            let mut stats_guard = stats.write().await;
+                            stats_guard.events_received += 1;
+                            if !delta.can_affect_cycles(window) && !plugin_found {
+                                stats_guard.events_skipped_deep += 1;
+                                continue;
+                            }
+                            drop(stats_guard);
 
                             // Transition to HOT_PATH
                             *state.write().await = HftState::HotPath;
                         }
-                        None => {
+                        Some(None) => {
                             // Channel closed
                             info!("Event channel closed, stopping HFT loop");
                             break;
@@ -281,22 +1042,169 @@ impl HftLoop {
                 &config_manager,
                 &execution_engine,
                 &config,
+                &cooldowns,
+                &warmup,
+                &notional,
+                &liquidity_filtered,
+                &opportunities,
+                &webhook,
+                &safe_mode,
+                &throttle,
+                &balances,
+                &scan_profiler,
+                &valuator,
+                &universes,
+                &fairness,
+                &exec_queue,
+                &plugins,
+                &anomalies,
+                std::mem::take(&mut sweep),
             ).await;
 
             cycle_count.fetch_add(1, Ordering::Relaxed);
 
+            match &cycle_result {
+                CycleResult::CoolingDown { scope, key, remaining_ms }
+                    if *scope != CooldownScope::Global && last_guard_key.as_ref() != Some(key) =>
+                {
+                    audit.record(AuditActor::Auto, AuditCategory::Guard, "cooldown_block", serde_json::json!({
+                        "scope": scope,
+                        "key": key,
+                        "remaining_ms": remaining_ms,
+                    }));
+                    last_guard_key = Some(key.clone());
+                }
+                CycleResult::ReserveBlocked { currency, balance, reserve } => {
+                    let key = format!("reserve:{}", currency);
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "reserve_block", serde_json::json!({
+                            "currency": currency,
+                            "balance": balance,
+                            "reserve": reserve,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::NotionalBlocked(block) => {
+                    let key = format!("notional:{}", block.window.as_str());
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "notional_block", serde_json::json!(block));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::BelowLegThreshold { path, legs, net_profit_pct, threshold_pct } => {
+                    let key = format!("leg_threshold:{}", path);
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "leg_threshold_block", serde_json::json!({
+                            "path": path,
+                            "legs": legs,
+                            "net_profit_pct": net_profit_pct,
+                            "threshold_pct": threshold_pct,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::PairDisabled { path, pair } => {
+                    let key = format!("disabled:{}", pair);
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "pair_disabled_block", serde_json::json!({
+                            "path": path,
+                            "pair": pair,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::SafeModeBlocked { path } => {
+                    let key = "safe_mode".to_string();
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "safe_mode_block", serde_json::json!({
+                            "path": path,
+                            "reason": safe_mode.status().reason,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::Throttled { path, block } => {
+                    let key = "throttle".to_string();
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "throttle_block", serde_json::json!({
+                            "path": path,
+                            "block": block,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::SignalSkipped { path, pair, pressure } => {
+                    let key = format!("signal:{}", pair);
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "signal_skip", serde_json::json!({
+                            "path": path,
+                            "pair": pair,
+                            "pressure": pressure,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::PluginSkipped { path, plugin, reason } => {
+                    let key = format!("plugin:{}:{}", plugin, path);
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "plugin_skip", serde_json::json!({
+                            "path": path,
+                            "plugin": plugin,
+                            "reason": reason,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::StaleOpportunity { path, age_ms, max_age_ms } => {
+                    let key = "stale".to_string();
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "stale_opportunity", serde_json::json!({
+                            "path": path,
+                            "age_ms": age_ms,
+                            "max_age_ms": max_age_ms,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::AnomalyQuarantined { path, pair, reason, net_profit_pct } => {
+                    let key = format!("anomaly:{}", pair);
+                    if last_guard_key.as_ref() != Some(&key) {
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "anomaly_quarantine", serde_json::json!({
+                            "path": path,
+                            "pair": pair,
+                            "reason": reason,
+                            "net_profit_pct": net_profit_pct,
+                        }));
+                        last_guard_key = Some(key);
+                    }
+                }
+                CycleResult::TradeSuccess { path, profit_pct, expected_profit_pct, .. } => {
+                    if let Some(shortfall) = throttle.record(*expected_profit_pct, *profit_pct, Instant::now()) {
+                        let status = throttle.status(Instant::now());
+                        warn!(
+                            "Performance throttle engaged: last {} trades realized {:.3} points below expected ({:?} for {}s)",
+                            status.policy.window, shortfall, status.policy.mode, status.remaining_secs
+                        );
+                        audit.record(AuditActor::Auto, AuditCategory::Guard, "throttle_engaged", serde_json::json!({
+                            "path": path,
+                            "shortfall_pct": shortfall,
+                            "status": status,
+                        }));
+                    }
+                    last_guard_key = None;
+                }
+                CycleResult::TradeFailed { .. } => last_guard_key = None,
+                _ => {}
+            }
+
             // ============================================
             // COLD PATH - Validation and decision
             // ============================================
 
             *state.write().await = HftState::ColdPath;
 
-            let decision = Self::execute_cold_path(
-                &cycle_result,
-                &stats,
-                &config,
-                &db,
-            ).await;
+            let decision = Self::execute_cold_path(&cycle_result, &stats, &lifecycle).await;
 
             // Update state based on decision
             match decision {
@@ -305,6 +1213,10 @@ impl HftLoop {
                 }
                 ColdPathDecision::Stop { reason } => {
                     warn!("Circuit breaker tripped: {}", reason);
+                    audit.record(AuditActor::Auto, AuditCategory::Breaker, "circuit_breaker_tripped", serde_json::json!({
+                        "reason": reason,
+                    }));
+                    *breaker.lock() = Some(reason);
                     *state.write().await = HftState::Stopped;
                 }
             }
@@ -314,43 +1226,172 @@ impl HftLoop {
         is_running.store(false, Ordering::SeqCst);
     }
 
+    /// Next event, beating while none arrives so a quiet market doesn't look
+    /// like a hung loop to the watchdog
+    async fn recv_beating(event_rx: &mut mpsc::Receiver<BookDelta>, heartbeat: &Heartbeat) -> Option<BookDelta> {
+        loop {
+            if let Ok(event) = tokio::time::timeout(HEARTBEAT_INTERVAL, event_rx.recv()).await {
+                return event;
+            }
+            heartbeat.beat();
+        }
+    }
+
     /// HOT PATH: Scan → Find First → Execute
     /// SPEED CRITICAL - No extra checks, no delays
+    #[allow(clippy::too_many_arguments)]
     async fn execute_hot_path(
         cache: &Arc<OrderBookCache>,
         config_manager: &Arc<ConfigManager>,
         execution_engine: &Arc<RwLock<Option<ExecutionEngine>>>,
         hft_config: &Arc<RwLock<HftConfig>>,
+        cooldowns: &Arc<RwLock<CooldownTracker>>,
+        warmup: &WarmupGate,
+        notional: &NotionalTracker,
+        liquidity_filtered: &Arc<AtomicU64>,
+        opportunities: &OpportunityCache,
+        webhook: &OpportunityWebhook,
+        safe_mode: &SafeMode,
+        throttle: &PerformanceThrottle,
+        balances: &RwLock<Option<HashMap<String, f64>>>,
+        scan_profiler: &Arc<ScanProfiler>,
+        valuator: &Valuator,
+        universes: &Universes,
+        fairness: &ScanFairness,
+        exec_queue: &ExecutionQueue,
+        plugins: &StrategyPlugins,
+        anomalies: &AnomalyDetector,
+        sweep: bool,
     ) -> CycleResult {
         let hot_path_start = std::time::Instant::now();
 
+        // Global cooldown: don't even scan
+        if let Some(remaining_ms) = cooldowns.read().await.global_remaining(hot_path_start) {
+            return CycleResult::CoolingDown { scope: CooldownScope::Global, key: "*".to_string(), remaining_ms };
+        }
+
         let config = hft_config.read().await;
         let engine_config = config_manager.get_config();
 
         // Step 1: Create scanner and find FIRST profitable opportunity
         let scan_start = std::time::Instant::now();
-        let scanner = Scanner::new(Arc::clone(cache), engine_config);
+        let mut scanner = Scanner::new(Arc::clone(cache), engine_config.clone())
+            .with_profiler(Arc::clone(scan_profiler))
+            .with_stablecoins(config.stablecoins)
+            .with_min_quality(config.min_pair_quality);
+        if let Some(requirement) = config.leg_liquidity {
+            // Check depth for the smallest amount an opportunity at threshold would get;
+            // the one picked is re-checked at its tier size before it trades
+            let lowest = engine_config.leg_thresholds.values().fold(config.min_profit_threshold, |a, b| a.min(*b));
+            let amount = config.trade_amount_for(lowest * 100.0);
+            scanner = scanner.with_liquidity(requirement, amount, Arc::clone(liquidity_filtered));
+        }
 
-        // Scan - but we only care about the FIRST opportunity that meets threshold
-        let opportunity = Self::find_first_opportunity(
-            &scanner,
-            &config.base_currencies,
-            config.min_profit_threshold,
-        );
+        // Scan - but we only care about the FIRST opportunity that meets threshold.
+        // A sweep evaluates every cycle instead and takes the best one not cooling down.
+        let mut opportunity = if sweep {
+            Self::sweep_opportunities(&scanner, &config.base_currencies, config.min_profit_threshold, cooldowns, opportunities, cache).await
+        } else {
+            None
+        };
+        if opportunity.is_none() {
+            opportunity = Self::find_first_opportunity(
+                &scanner,
+                &config.base_currencies,
+                config.min_profit_threshold,
+            );
+        }
+        // Finding nothing means every cycle was evaluated
+        if sweep || opportunity.is_none() {
+            fairness.record_full_pass(std::time::Instant::now());
+        }
+
+        // Universes run their own thresholds; execute-mode finds are queued
+        // with the main scan's, observe-mode ones are just cached
+        let found_at = chrono::Utc::now();
+        if let Some(opp) = opportunity {
+            plugins.on_opportunity(&opp);
+            opportunities.insert(&opp, cache);
+            exec_queue.push(opp, found_at);
+        }
+        if !universes.is_empty() {
+            let found = universes.scan(cache, &engine_config, config.stablecoins, &config.base_currencies);
+            for (trigger, opp) in found {
+                plugins.on_opportunity(&opp);
+                opportunities.insert(&opp, cache);
+                if trigger == UniverseTrigger::Execute {
+                    exec_queue.push(opp, found_at);
+                }
+            }
+        }
         let scan_ms = scan_start.elapsed().as_micros() as f64 / 1000.0;
+        warmup.record_scan();
 
-        let opp = match opportunity {
+        // The best of what this and earlier scans found, while still fresh
+        let opp = match exec_queue.pop(chrono::Utc::now()) {
             Some(o) => o,
             None => {
                 // Log every 100th scan to avoid spam
                 static SCAN_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
                 let count = SCAN_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                if count % 100 == 0 {
+                if count.is_multiple_of(100) {
                     info!("📊 Scanned {} times, no opportunity above threshold (scan: {:.2}ms)", count + 1, scan_ms);
                 }
                 return CycleResult::NoOpportunity;
             }
         };
+        // Never executed, and kept from the webhook: most likely a bad quote
+        if let Some(anomaly) = anomalies.check(&opp, cache, chrono::Utc::now()) {
+            return CycleResult::AnomalyQuarantined {
+                path: opp.path,
+                pair: anomaly.pair,
+                reason: anomaly.reason,
+                net_profit_pct: opp.net_profit_pct,
+            };
+        }
+        universes.record_selected(&opp);
+        if opportunities.filter().matches(&opp) {
+            webhook.publish(&opp, cache);
+        }
+
+        // Longer paths must clear their own threshold (stable cycles have theirs)
+        if opp.strategy != Strategy::Stablecoin {
+            if let Some(threshold) = engine_config.leg_threshold(opp.legs) {
+                if opp.net_profit_pct <= threshold * 100.0 {
+                    return CycleResult::BelowLegThreshold {
+                        path: opp.path,
+                        legs: opp.legs,
+                        net_profit_pct: opp.net_profit_pct,
+                        threshold_pct: threshold * 100.0,
+                    };
+                }
+            }
+        }
+
+        if let Some(cooldown) = cooldowns.read().await.blocking(&opp, std::time::Instant::now()) {
+            return CycleResult::CoolingDown {
+                scope: cooldown.scope,
+                key: cooldown.key,
+                remaining_ms: cooldown.remaining_ms,
+            };
+        }
+
+        // Books are thin and stats unreliable right after a start
+        if !warmup.is_complete(cache) {
+            return CycleResult::WarmingUp;
+        }
+
+        // After a crash or breaker trip, keep scanning but wait for the operator
+        if safe_mode.is_active() {
+            safe_mode.record_block();
+            return CycleResult::SafeModeBlocked { path: opp.path };
+        }
+
+        // While recent trades fall short of their expected profit, demand more (or wait)
+        let threshold_pct = engine_config.leg_threshold(opp.legs).unwrap_or(config.min_profit_threshold) * 100.0;
+        if let Err(block) = throttle.check(opp.net_profit_pct, threshold_pct, std::time::Instant::now()) {
+            return CycleResult::Throttled { path: opp.path, block };
+        }
 
         info!("🎯 Found opportunity: {} | {:.3}% | scan: {:.2}ms", opp.path, opp.net_profit_pct, scan_ms);
 
@@ -361,15 +1402,69 @@ impl HftLoop {
             None => {
                 warn!("Execution engine not available");
                 return CycleResult::TradeFailed {
+                    trade_id: uuid::Uuid::new_v4().to_string(),
                     path: opp.path,
+                    trade_amount: 0.0,
                     error: "Execution engine not available".to_string(),
                     is_partial: false,
                     leg_timings: vec![],
+                    notional_usd: 0.0,
+                    strategy: opp.strategy,
+                    tags: opp.tags,
                 };
             }
         };
 
-        let trade_amount = config.trade_amount;
+        // Scan-only pairs are reported but never traded
+        if let Some(pair) = engine.disabled_pair_on(&opp.path) {
+            return CycleResult::PairDisabled { path: opp.path, pair };
+        }
+
+        // Plugins may veto the trade or scale it down
+        let plugin_factor = match plugins.select_action(&opp, cache) {
+            PluginDecision::Execute { factor } => factor,
+            PluginDecision::Skip { plugin, reason } => {
+                return CycleResult::PluginSkipped { path: opp.path, plugin, reason };
+            }
+        };
+
+        // Size by profit tier (falls back to trade_amount, capped by max_safe_amount)
+        let mut trade_amount = config.trade_amount_for(opp.net_profit_pct) * plugin_factor;
+
+        // Sizes are in USD: a cycle starting elsewhere trades the equivalent
+        // (taken as-is when nothing prices the start currency)
+        let start_currency = opp.path.split(" → ").next().unwrap_or_default().to_string();
+        let start_rate = valuator.usd_rate(&start_currency).map(|usd| usd.rate).unwrap_or(1.0);
+        trade_amount /= start_rate;
+
+        // Never spend into the start currency's reserve
+        let (spendable, balance) = {
+            let snapshot = balances.read().await;
+            let balance = snapshot.as_ref().and_then(|b| b.get(&start_currency)).copied();
+            (config.spendable(&start_currency, snapshot.as_ref()), balance)
+        };
+        if let Some(spendable) = spendable {
+            if spendable <= 0.0 {
+                return CycleResult::ReserveBlocked {
+                    reserve: config.reserves.get(&start_currency).copied().unwrap_or(0.0),
+                    currency: start_currency,
+                    balance,
+                };
+            }
+            trade_amount = trade_amount.min(spendable);
+        }
+
+        // A higher profit tier trades more than the scan checked the depth for
+        if !scanner.opportunity_fills(&opp, trade_amount) {
+            return CycleResult::Illiquid { path: opp.path, trade_amount };
+        }
+
+        // Every leg trades roughly the cycle's size
+        let projected_notional = trade_amount * start_rate * opp.legs as f64;
+        if let Err(block) = notional.check(&config.notional_limits, projected_notional, std::time::Instant::now()) {
+            return CycleResult::NotionalBlocked(block);
+        }
+        let reporting_currency = config.reporting_currency.clone();
         drop(config); // Release lock before async call
 
         // Execute the trade
@@ -379,6 +1474,25 @@ impl HftLoop {
 
         let total_hot_path_ms = hot_path_start.elapsed().as_millis() as u64;
 
+        // Our own fills are the freshest trade prints we have
+        if let Ok(trade_result) = &result {
+            for leg in trade_result.legs.iter().filter(|l| l.success) {
+                valuator.record_last_trade(&leg.pair, leg.avg_price);
+            }
+        }
+
+        // Keep the snapshot roughly right until the next refresh:
+        // a completed cycle returns with its P&L, a broken one left the funds elsewhere
+        if let Ok(trade_result) = &result {
+            if let Some(balance) = balances.write().await.as_mut().and_then(|b| b.get_mut(&start_currency)) {
+                if trade_result.success {
+                    *balance += trade_result.profit_amount;
+                } else if trade_result.legs.iter().any(|l| l.success) {
+                    *balance -= trade_amount;
+                }
+            }
+        }
+
         match result {
             Ok(trade_result) => {
                 // Build leg timings and log string in single pass (post-execution, not time-critical)
@@ -394,6 +1508,9 @@ impl HftLoop {
                         duration_ms: l.duration_ms,
                         success: l.success,
                         error: l.error.clone(),
+                        resized_from: l.resized_from,
+                        unfilled_amount: l.unfilled_amount,
+                        price_improvement_bps: l.improvement_bps(),
                     });
                     if l.success {
                         completed_legs += 1;
@@ -411,14 +1528,22 @@ impl HftLoop {
                         scan_ms, leg_times_str, duration_ms, total_hot_path_ms
                     );
                     CycleResult::TradeSuccess {
+                        notional_usd: filled_notional(valuator, &trade_result.legs, trade_amount * start_rate),
+                        trade_id: trade_result.id,
                         path: trade_result.path,
+                        trade_amount,
                         profit_pct: trade_result.profit_pct,
+                        expected_profit_pct: opp.net_profit_pct,
                         profit_amount: trade_result.profit_amount,
+                        pnl: valuator.report_pnl(&start_currency, trade_result.profit_amount, &reporting_currency),
                         duration_ms,
                         leg_timings,
+                        strategy: trade_result.strategy,
+                        tags: trade_result.tags,
                     }
                 } else {
-                    let is_partial = completed_legs > 0 && completed_legs < trade_result.legs.len();
+                    // Something is still held unless the last leg (an abort's unwind included) filled
+                    let is_partial = completed_legs > 0 && trade_result.legs.last().is_some_and(|l| !l.success);
 
                     warn!(
                         "❌ Trade FAILED: {} | {} | scan: {:.2}ms | legs: [{}] | exec: {}ms | total: {}ms",
@@ -428,21 +1553,37 @@ impl HftLoop {
                     );
 
                     CycleResult::TradeFailed {
+                        notional_usd: filled_notional(valuator, &trade_result.legs, trade_amount * start_rate),
+                        trade_id: trade_result.id,
                         path: trade_result.path,
+                        trade_amount,
                         error: trade_result.error.unwrap_or_else(|| "Unknown error".to_string()),
                         is_partial,
                         leg_timings,
+                        strategy: trade_result.strategy,
+                        tags: trade_result.tags,
                     }
                 }
             }
+            Err(ExecutionError::AdverseSignal { pair, pressure }) => {
+                CycleResult::SignalSkipped { path: opp.path, pair, pressure }
+            }
+            Err(ExecutionError::StaleOpportunity { age_ms, max_age_ms }) => {
+                CycleResult::StaleOpportunity { path: opp.path, age_ms, max_age_ms }
+            }
             Err(e) => {
                 warn!("❌ Execution error: {} | {} | exec: {}ms | total: {}ms (scan: {:.2}ms)",
                     opp.path, e, duration_ms, total_hot_path_ms, scan_ms);
                 CycleResult::TradeFailed {
+                    trade_id: uuid::Uuid::new_v4().to_string(),
                     path: opp.path,
+                    trade_amount,
                     error: e.to_string(),
                     is_partial: false,
                     leg_timings: vec![],
+                    notional_usd: 0.0,
+                    strategy: opp.strategy,
+                    tags: opp.tags,
                 }
             }
         }
@@ -460,18 +1601,42 @@ impl HftLoop {
         scanner.scan_first(base_currencies, min_threshold)
     }
 
-    /// COLD PATH: Validate results, update stats, check circuit breakers
+    /// Evaluate every cycle, cache each one that clears the threshold and
+    /// return the most profitable that isn't cooling down (falling back to
+    /// the most profitable, which the cooldown check then reports)
+    async fn sweep_opportunities(
+        scanner: &Scanner,
+        base_currencies: &[String],
+        min_threshold: f64,
+        cooldowns: &RwLock<CooldownTracker>,
+        opportunities: &OpportunityCache,
+        cache: &OrderBookCache,
+    ) -> Option<Opportunity> {
+        let mut found = scanner.scan_filtered(base_currencies, min_threshold * 100.0);
+        if found.is_empty() {
+            return None;
+        }
+        let now = std::time::Instant::now();
+        let pick = {
+            let cooldowns = cooldowns.read().await;
+            found.iter().position(|o| cooldowns.blocking(o, now).is_none()).unwrap_or(0)
+        };
+        // The pick is cached by the hot path like any other find
+        let best = found.swap_remove(pick);
+        for opp in &found {
+            opportunities.insert(opp, cache);
+        }
+        Some(best)
+    }
+
+    /// COLD PATH: Count the cycle, record any trade, check circuit breakers
     async fn execute_cold_path(
         cycle_result: &CycleResult,
         stats: &Arc<RwLock<HftStats>>,
-        config: &Arc<RwLock<HftConfig>>,
-        db: &Database,
+        lifecycle: &TradeLifecycle,
     ) -> ColdPathDecision {
-        // Read config once at the start (before acquiring stats lock)
-        let config_snapshot = config.read().await.clone();
-
         // Update stats (short critical section)
-        let (daily_loss, total_loss) = {
+        {
             let mut stats_guard = stats.write().await;
             stats_guard.cycles_completed += 1;
 
@@ -479,49 +1644,96 @@ impl HftLoop {
                 CycleResult::NoOpportunity => {
                     return ColdPathDecision::Continue;
                 }
-                CycleResult::TradeSuccess { profit_amount, .. } => {
-                    stats_guard.opportunities_found += 1;
-                    stats_guard.trades_executed += 1;
-                    stats_guard.trades_successful += 1;
-
-                    if *profit_amount >= 0.0 {
-                        stats_guard.total_profit += profit_amount;
-                        stats_guard.daily_profit += profit_amount;
-                    } else {
-                        stats_guard.total_loss += profit_amount.abs();
-                        stats_guard.daily_loss += profit_amount.abs();
-                    }
+                CycleResult::CoolingDown { .. } => {
+                    stats_guard.skipped_cooldown += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::WarmingUp => {
+                    stats_guard.skipped_warmup += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::ReserveBlocked { .. } => {
+                    stats_guard.skipped_reserve += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::NotionalBlocked(_) => {
+                    stats_guard.skipped_notional += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::PairDisabled { .. } => {
+                    stats_guard.skipped_disabled_pair += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::BelowLegThreshold { .. } => {
+                    stats_guard.skipped_leg_threshold += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::SafeModeBlocked { .. } => {
+                    stats_guard.skipped_safe_mode += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::Throttled { .. } => {
+                    stats_guard.skipped_throttle += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::SignalSkipped { .. } => {
+                    stats_guard.skipped_signal += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::PluginSkipped { .. } => {
+                    stats_guard.skipped_plugin += 1;
+                    return ColdPathDecision::Continue;
+                }
+                // Counted by the scanner's liquidity_filtered
+                CycleResult::Illiquid { .. } => {
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::StaleOpportunity { .. } => {
+                    stats_guard.skipped_stale += 1;
+                    return ColdPathDecision::Continue;
+                }
+                CycleResult::AnomalyQuarantined { .. } => {
+                    stats_guard.skipped_anomaly += 1;
+                    return ColdPathDecision::Continue;
                 }
-                CycleResult::TradeFailed { is_partial, .. } => {
+                CycleResult::TradeSuccess { .. } | CycleResult::TradeFailed { .. } => {
                     stats_guard.opportunities_found += 1;
-                    stats_guard.trades_executed += 1;
-                    stats_guard.trades_failed += 1;
-                    if *is_partial {
-                        stats_guard.trades_partial += 1;
-                    }
                 }
                 CycleResult::CircuitBroken { reason } => {
                     return ColdPathDecision::Stop { reason: reason.clone() };
                 }
             }
+        } // Stats lock released here
 
-            (stats_guard.daily_loss, stats_guard.total_loss)
-        }; // Stats lock released here
+        // Cooldowns, trade counters, P&L and the DB, like any other trade
+        let Some(record) = Self::trade_record(cycle_result) else {
+            return ColdPathDecision::Continue;
+        };
+        match lifecycle.record(&record).await.loss_limit_breach {
+            Some(reason) => ColdPathDecision::Stop { reason },
+            None => ColdPathDecision::Continue,
+        }
+    }
 
-        // Save to database (no locks held)
+    /// The trade a cycle executed, as the lifecycle records it
+    fn trade_record(cycle_result: &CycleResult) -> Option<TradeRecord> {
         match cycle_result {
-            CycleResult::TradeSuccess { path, profit_pct, profit_amount, duration_ms, leg_timings } => {
+            CycleResult::TradeSuccess { trade_id, path, trade_amount, profit_pct, expected_profit_pct, profit_amount, pnl, duration_ms, leg_timings, notional_usd, strategy, tags } => {
                 // Serialize leg timings to JSON
                 let leg_fills_json = serde_json::to_value(leg_timings).ok();
 
-                let new_trade = NewLiveTrade {
-                    trade_id: uuid::Uuid::new_v4().to_string(),
+                let trade = NewLiveTrade {
+                    trade_id: trade_id.clone(),
                     path: path.clone(),
                     legs: path.matches(" → ").count() as i32 + 1,
-                    amount_in: config_snapshot.trade_amount,
-                    amount_out: Some(config_snapshot.trade_amount + profit_amount),
+                    amount_in: *trade_amount,
+                    amount_out: Some(trade_amount + profit_amount),
                     profit_loss: Some(*profit_amount),
                     profit_loss_pct: Some(*profit_pct),
+                    pnl_currency: Some(pnl.currency.clone()),
+                    pnl_reporting: pnl.reporting_amount,
+                    reporting_currency: Some(pnl.reporting_currency.clone()),
+                    fx_rate: pnl.fx_rate,
                     status: "COMPLETED".to_string(),
                     current_leg: None,
                     error_message: None,
@@ -533,39 +1745,21 @@ impl HftLoop {
                     started_at: Some(chrono::Utc::now()),
                     completed_at: Some(chrono::Utc::now()),
                     total_execution_ms: Some(*duration_ms as f64),
-                    opportunity_profit_pct: Some(*profit_pct),
+                    opportunity_profit_pct: Some(*expected_profit_pct),
+                    strategy: Some(strategy.as_str().to_string()),
+                    tags: tags.clone(),
                 };
 
-                if let Err(e) = db.save_trade(&new_trade).await {
-                    warn!("Failed to save trade to DB: {}", e);
-                }
-
-                // Update trading state with trade result
-                let is_win = *profit_amount > 0.0;
-                if let Err(e) = db.record_trade_result(*profit_amount, config_snapshot.trade_amount, is_win).await {
-                    warn!("Failed to update trading state: {}", e);
-                }
-
-                // Check circuit breakers (using snapshot values)
-                if daily_loss > config_snapshot.max_daily_loss {
-                    return ColdPathDecision::Stop {
-                        reason: format!(
-                            "Daily loss limit exceeded: ${:.2} > ${:.2}",
-                            daily_loss, config_snapshot.max_daily_loss
-                        ),
-                    };
-                }
-                if total_loss > config_snapshot.max_total_loss {
-                    return ColdPathDecision::Stop {
-                        reason: format!(
-                            "Total loss limit exceeded: ${:.2} > ${:.2}",
-                            total_loss, config_snapshot.max_total_loss
-                        ),
-                    };
-                }
+                Some(TradeRecord {
+                    trade,
+                    pnl: Some(pnl.clone()),
+                    is_partial: false,
+                    failed_pairs: Vec::new(),
+                    notional_usd: *notional_usd,
+                })
             }
 
-            CycleResult::TradeFailed { path, error, is_partial, leg_timings } => {
+            CycleResult::TradeFailed { trade_id, path, trade_amount, error, is_partial, leg_timings, notional_usd, strategy, tags } => {
                 // Serialize leg timings to JSON (even partial data is useful)
                 let leg_fills_json = if leg_timings.is_empty() {
                     None
@@ -573,14 +1767,18 @@ impl HftLoop {
                     serde_json::to_value(leg_timings).ok()
                 };
 
-                let new_trade = NewLiveTrade {
-                    trade_id: uuid::Uuid::new_v4().to_string(),
+                let trade = NewLiveTrade {
+                    trade_id: trade_id.clone(),
                     path: path.clone(),
                     legs: path.matches(" → ").count() as i32 + 1,
-                    amount_in: config_snapshot.trade_amount,
+                    amount_in: *trade_amount,
                     amount_out: None,
                     profit_loss: None,
                     profit_loss_pct: None,
+                    pnl_currency: None,
+                    pnl_reporting: None,
+                    reporting_currency: None,
+                    fx_rate: None,
                     status: if *is_partial { "PARTIAL".to_string() } else { "FAILED".to_string() },
                     current_leg: None,
                     error_message: Some(error.clone()),
@@ -593,28 +1791,80 @@ impl HftLoop {
                     completed_at: Some(chrono::Utc::now()),
                     total_execution_ms: None,
                     opportunity_profit_pct: None,
+                    strategy: Some(strategy.as_str().to_string()),
+                    tags: tags.clone(),
                 };
 
-                if let Err(e) = db.save_trade(&new_trade).await {
-                    warn!("Failed to save failed trade to DB: {}", e);
-                }
+                Some(TradeRecord {
+                    trade,
+                    pnl: None,
+                    is_partial: *is_partial,
+                    // Pairs whose leg failed cool down on their own
+                    failed_pairs: leg_timings.iter().filter(|l| !l.success).map(|l| l.pair.clone()).collect(),
+                    notional_usd: *notional_usd,
+                })
             }
 
-            // NoOpportunity and CircuitBroken are handled in stats update block above
-            _ => {}
+            _ => None,
         }
-
-        ColdPathDecision::Continue
     }
 
     /// Stop the HFT loop
     pub fn stop(&self) {
         self.is_running.store(false, Ordering::SeqCst);
+        if let Some(ref supervisor) = self.supervisor {
+            supervisor.unwatch("hft_loop");
+        }
         info!("HFT Loop stop requested");
     }
 
+    /// Orders the execution engine is still waiting on (empty when not connected)
+    pub async fn pending_orders(&self) -> Vec<(String, Duration)> {
+        match *self.execution_engine.read().await {
+            Some(ref engine) => engine.pending_orders().await,
+            None => Vec::new(),
+        }
+    }
+
+    /// Where trades executed outside the loop are recorded
+    pub fn lifecycle(&self) -> Arc<TradeLifecycle> {
+        Arc::clone(&self.lifecycle)
+    }
+
+    /// Current balance snapshot (None before the first refresh)
+    pub async fn get_balances(&self) -> Option<HashMap<String, f64>> {
+        self.balances.read().await.clone()
+    }
+
+    /// Order/amend counters from the execution engine (zeros when not connected)
+    pub async fn get_execution_stats(&self) -> ExecutionStats {
+        self.execution_engine.read().await
+            .as_ref()
+            .map(|engine| engine.get_stats())
+            .unwrap_or_default()
+    }
+
+    /// Cancel all open orders through the execution engine (if connected)
+    pub async fn cancel_open_orders(&self) -> Result<(), String> {
+        match *self.execution_engine.read().await {
+            Some(ref engine) => engine.cancel_all_orders().await.map_err(|e| e.to_string()),
+            None => Err("Execution engine not connected".to_string()),
+        }
+    }
+
+    /// Why the breaker is tripped (None while trading)
+    pub fn breaker_reason(&self) -> Option<String> {
+        self.breaker.lock().clone()
+    }
+
+    /// Stop trading until the breaker is reset (the loop keeps running)
+    pub fn trip_circuit_breaker(&self, reason: &str) {
+        *self.breaker.lock() = Some(reason.to_string());
+    }
+
     /// Reset circuit breaker and resume trading
     pub async fn reset_circuit_breaker(&self) {
+        *self.breaker.lock() = None;
         let mut state = self.state.write().await;
         if *state == HftState::Stopped {
             *state = HftState::Idle;
@@ -634,4 +1884,241 @@ impl HftLoop {
     pub fn is_running(&self) -> bool {
         self.is_running.load(Ordering::Relaxed)
     }
+
+    /// Active cooldowns with remaining time
+    pub async fn get_cooldowns(&self) -> Vec<ActiveCooldown> {
+        self.cooldowns.write().await.active(Instant::now())
+    }
+
+    /// Notional used and left in the hourly/daily windows
+    pub async fn get_notional_headroom(&self) -> NotionalHeadroom {
+        let limits = self.config.read().await.notional_limits;
+        self.notional.headroom(&limits, Instant::now())
+    }
+
+    /// Warm-up progress towards allowing auto-execution
+    pub fn get_warmup(&self) -> WarmupProgress {
+        self.warmup.progress(&self.cache)
+    }
+
+    /// Evaluate every guard the hot path applies to `opp` (found `age_ms`
+    /// ago), without stopping at the first one that blocks it
+    pub async fn explain_guards(&self, opp: &Opportunity, age_ms: i64) -> GuardVerdict {
+        let now = Instant::now();
+        let mut verdict = GuardVerdict::new(&opp.id, &opp.path, opp.net_profit_pct, age_ms);
+        let config = self.config.read().await.clone();
+        let engine_config = self.config_manager.get_config();
+
+        let state = *self.state.read().await;
+        verdict.check("running", self.is_running() && state != HftState::Stopped, serde_json::json!({
+            "running": self.is_running(),
+            "state": state,
+        }));
+
+        let (daily_loss, total_loss) = {
+            let stats = self.stats.read().await;
+            (stats.daily_loss, stats.total_loss)
+        };
+        verdict.check(
+            "loss_limits",
+            daily_loss <= config.max_daily_loss && total_loss <= config.max_total_loss,
+            serde_json::json!({
+                "daily_loss": daily_loss,
+                "max_daily_loss": config.max_daily_loss,
+                "total_loss": total_loss,
+                "max_total_loss": config.max_total_loss,
+                "currency": config.reporting_currency,
+            }),
+        );
+
+        // Stable cycles have their own threshold, longer paths may too
+        let threshold_pct = if opp.strategy == Strategy::Stablecoin {
+            config.stablecoins.min_profit_pct
+        } else {
+            engine_config.leg_threshold(opp.legs).unwrap_or(config.min_profit_threshold) * 100.0
+        };
+        verdict.check("profit_threshold", opp.net_profit_pct > threshold_pct, serde_json::json!({
+            "net_profit_pct": opp.net_profit_pct,
+            "threshold_pct": threshold_pct,
+            "legs": opp.legs,
+        }));
+
+        let max_age_ms = self.exec_queue.policy().max_age_ms;
+        verdict.check("staleness", age_ms < max_age_ms as i64, serde_json::json!({
+            "age_ms": age_ms,
+            "max_age_ms": max_age_ms,
+        }));
+
+        let cooldown = {
+            let cooldowns = self.cooldowns.read().await;
+            cooldowns
+                .global_remaining(now)
+                .map(|remaining_ms| ActiveCooldown { scope: CooldownScope::Global, key: "*".to_string(), remaining_ms })
+                .or_else(|| cooldowns.blocking(opp, now))
+        };
+        verdict.check("cooldown", cooldown.is_none(), serde_json::json!({ "active": cooldown }));
+
+        let warmup = self.warmup.progress(&self.cache);
+        verdict.check("warmup", warmup.complete, serde_json::json!(warmup));
+
+        let safe_mode = self.safe_mode.status();
+        verdict.check("safe_mode", !self.safe_mode.is_active(), serde_json::json!(safe_mode));
+
+        let threshold = engine_config.leg_threshold(opp.legs).unwrap_or(config.min_profit_threshold) * 100.0;
+        let throttle = self.throttle.check(opp.net_profit_pct, threshold, now).err();
+        verdict.check("throttle", throttle.is_none(), serde_json::json!({ "block": throttle }));
+
+        let (connected, disabled_pair) = match *self.execution_engine.read().await {
+            Some(ref engine) => (true, engine.disabled_pair_on(&opp.path)),
+            None => (false, None),
+        };
+        verdict.check("execution_engine", connected && disabled_pair.is_none(), serde_json::json!({
+            "connected": connected,
+            "disabled_pair": disabled_pair,
+        }));
+
+        let trade_amount_usd = config.trade_amount_for(opp.net_profit_pct);
+        let start_currency = opp.path.split(" → ").next().unwrap_or_default().to_string();
+        let start_rate = self.valuator.usd_rate(&start_currency).map(|usd| usd.rate).unwrap_or(1.0);
+        let (spendable, balance) = {
+            let snapshot = self.balances.read().await;
+            let balance = snapshot.as_ref().and_then(|b| b.get(&start_currency)).copied();
+            (config.spendable(&start_currency, snapshot.as_ref()), balance)
+        };
+        verdict.check("balance", spendable.is_none_or(|s| s > 0.0), serde_json::json!({
+            "currency": start_currency,
+            "balance": balance,
+            "reserve": config.reserves.get(&start_currency),
+            "spendable": spendable,
+        }));
+
+        let trade_amount = spendable.map_or(trade_amount_usd / start_rate, |s| (trade_amount_usd / start_rate).min(s));
+        let projected = trade_amount * start_rate * opp.legs as f64;
+        let exposure = self.notional.check(&config.notional_limits, projected, now).err();
+        verdict.check("exposure", exposure.is_none(), serde_json::json!({
+            "projected_usd": projected,
+            "limits": config.notional_limits,
+            "block": exposure,
+        }));
+
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trade_amount_for_tiers() {
+        let tiers = SizingTier::parse_tiers(&serde_json::json!([
+            {"min_profit_pct": 0.5, "amount": 200.0},
+            {"min_profit_pct": 0.2, "amount": 50.0}
+        ])).unwrap();
+
+        let mut config = HftConfig {
+            min_profit_threshold: 0.0,
+            trade_amount: 10.0,
+            max_daily_loss: 100.0,
+            max_total_loss: 500.0,
+            reporting_currency: "USD".to_string(),
+            base_currencies: vec!["USD".to_string()],
+            sizing_tiers: tiers,
+            max_safe_amount: Some(150.0),
+            cooldowns: CooldownConfig::default(),
+            leg_liquidity: None,
+            reserves: HashMap::new(),
+            stablecoins: StablecoinPolicy::default(),
+            notional_limits: NotionalLimits::default(),
+            min_pair_quality: 0.0,
+        };
+
+        assert_eq!(config.trade_amount_for(0.1), 10.0);
+        assert_eq!(config.trade_amount_for(0.3), 50.0);
+        assert_eq!(config.trade_amount_for(0.8), 150.0); // capped
+
+        config.max_safe_amount = None;
+        assert_eq!(config.trade_amount_for(0.8), 200.0);
+    }
+
+    #[test]
+    fn test_reserves_limit_spendable() {
+        assert!(parse_reserves(&serde_json::json!({"USD": -1})).is_err());
+        assert!(parse_reserves(&serde_json::json!([500])).is_err());
+
+        let config = HftConfig {
+            min_profit_threshold: 0.0,
+            trade_amount: 10.0,
+            max_daily_loss: 100.0,
+            max_total_loss: 500.0,
+            reporting_currency: "USD".to_string(),
+            base_currencies: vec!["USD".to_string()],
+            sizing_tiers: Vec::new(),
+            max_safe_amount: None,
+            cooldowns: CooldownConfig::default(),
+            leg_liquidity: None,
+            reserves: parse_reserves(&serde_json::json!({"usd": 500, "EUR": 0})).unwrap(),
+            stablecoins: StablecoinPolicy::default(),
+            notional_limits: NotionalLimits::default(),
+            min_pair_quality: 0.0,
+        };
+        let balances: HashMap<String, f64> = [("USD".to_string(), 520.0), ("EUR".to_string(), 5.0)].into();
+
+        assert_eq!(config.spendable("USD", Some(&balances)), Some(20.0));
+        assert_eq!(config.spendable("USD", None), Some(0.0)); // unknown balance: nothing
+        assert_eq!(config.spendable("EUR", Some(&balances)), None); // zero reserve: no limit
+        assert_eq!(config.spendable("BTC", None), None);
+
+        let low: HashMap<String, f64> = [("USD".to_string(), 400.0)].into();
+        assert_eq!(config.spendable("USD", Some(&low)), Some(0.0));
+    }
+
+    #[test]
+    fn test_cooldown_tracker() {
+        let config = CooldownConfig { global_ms: 1_000, path_ms: 5_000, pair_failure_ms: 10_000 };
+        let opp = Opportunity {
+            legs_detail: ["BTC/USD", "ETH/BTC", "ETH/USD"].iter().map(|p| crate::types::LegDetail {
+                pair: p.to_string(),
+                action: "buy".to_string(),
+                rate: 1.0,
+            }).collect(),
+            ..Opportunity::for_test("USD → BTC → ETH → USD", 0.2)
+        };
+
+        let now = Instant::now();
+        let mut tracker = CooldownTracker::default();
+        tracker.record_trade(&config, "USD → SOL → BTC → USD", &["SOL/BTC"], now);
+
+        assert_eq!(tracker.global_remaining(now), Some(1_000));
+        assert!(tracker.blocking(&opp, now).is_none());
+
+        // Failed leg on a pair this path also uses
+        tracker.record_trade(&config, "USD → XRP → ETH → USD", &["ETH/USD"], now);
+        let blocked = tracker.blocking(&opp, now + Duration::from_millis(2_000)).unwrap();
+        assert_eq!(blocked.scope, CooldownScope::Pair);
+        assert_eq!(blocked.remaining_ms, 8_000);
+
+        let later = now + Duration::from_millis(6_000);
+        assert_eq!(tracker.global_remaining(later), None);
+        let active = tracker.active(later);
+        assert_eq!(active.len(), 2); // both failed pairs; path cooldowns expired
+        assert!(active.iter().all(|c| c.scope == CooldownScope::Pair));
+    }
+
+    #[test]
+    fn test_warmup_gate() {
+        let cache = OrderBookCache::new();
+        let gate = WarmupGate::new(WarmupPolicy { min_uptime_secs: 0, min_fresh_pairs_pct: 50.0, min_scans: 2 });
+
+        // No books and no scans yet
+        assert!(!gate.is_complete(&cache));
+        gate.record_scan();
+        gate.record_scan();
+        assert!(!gate.is_complete(&cache), "no fresh books");
+        assert_eq!(gate.progress(&cache).scans, 2);
+
+        let lenient = WarmupGate::new(WarmupPolicy { min_uptime_secs: 0, min_fresh_pairs_pct: 0.0, min_scans: 0 });
+        assert!(lenient.is_complete(&cache));
+        assert!(lenient.progress(&cache).complete);
+    }
 }
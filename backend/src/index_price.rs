@@ -0,0 +1,409 @@
+//! External Index Price Check
+//!
+//! Optional sanity check of Kraken quotes against a secondary price source
+//! (Coinbase exchange rates by default). Every poll compares each cached
+//! pair's mid with the index price for the same pair; pairs that deviate by
+//! more than `max_deviation_pct` are marked anomalous in the OrderBookCache
+//! and the scanner leaves them out of the graph until the next poll agrees.
+//!
+//! The index is never used for pricing. Pairs a source doesn't cover are
+//! not checked, and when every source fails the anomalies are released
+//! rather than kept blocking on old data.
+//!
+//! Sources implement `IndexPriceSource`; further feeds can be added with
+//! `IndexPriceMonitor::add_source` and are consulted in order (the first
+//! source quoting a pair wins).
+#![allow(dead_code)]
+
+use crate::order_book::OrderBookCache;
+use crate::types::PriceEdge;
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{info, warn};
+
+const COINBASE_RATES_URL: &str = "https://api.coinbase.com/v2/exchange-rates?currency=USD";
+
+#[derive(Error, Debug)]
+pub enum IndexPriceError {
+    #[error("Request failed: {0}")]
+    Request(String),
+    #[error("Unexpected response: {0}")]
+    Parse(String),
+}
+
+/// A secondary price feed. `fetch` returns an index price per pair
+/// ("BTC/USD" → quote currency per base); uncovered pairs are left out.
+pub trait IndexPriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn fetch<'a>(&'a self, pairs: &'a [String]) -> BoxFuture<'a, Result<HashMap<String, f64>, IndexPriceError>>;
+}
+
+/// Coinbase USD exchange rates: one request covers every currency, and any
+/// pair between two covered currencies is derived from them
+pub struct CoinbaseRatesSource {
+    client: Client,
+}
+
+impl CoinbaseRatesSource {
+    pub fn new() -> Self {
+        Self {
+            client: Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default(),
+        }
+    }
+
+    /// Pair prices from "units of X per USD" rates
+    fn cross_prices(rates: &HashMap<String, f64>, pairs: &[String]) -> HashMap<String, f64> {
+        pairs
+            .iter()
+            .filter_map(|pair| {
+                let (base, quote) = pair.split_once('/')?;
+                let base_rate = rates.get(base).filter(|r| **r > 0.0)?;
+                let quote_rate = rates.get(quote).filter(|r| **r > 0.0)?;
+                Some((pair.clone(), quote_rate / base_rate))
+            })
+            .collect()
+    }
+}
+
+impl Default for CoinbaseRatesSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IndexPriceSource for CoinbaseRatesSource {
+    fn name(&self) -> &str {
+        "coinbase"
+    }
+
+    fn fetch<'a>(&'a self, pairs: &'a [String]) -> BoxFuture<'a, Result<HashMap<String, f64>, IndexPriceError>> {
+        Box::pin(async move {
+            let body: serde_json::Value = self.client
+                .get(COINBASE_RATES_URL)
+                .send()
+                .await
+                .map_err(|e| IndexPriceError::Request(e.to_string()))?
+                .json()
+                .await
+                .map_err(|e| IndexPriceError::Parse(e.to_string()))?;
+
+            let rates = body["data"]["rates"]
+                .as_object()
+                .ok_or_else(|| IndexPriceError::Parse("missing data.rates".to_string()))?
+                .iter()
+                .filter_map(|(currency, rate)| Some((currency.clone(), rate.as_str()?.parse::<f64>().ok()?)))
+                .collect();
+
+            Ok(Self::cross_prices(&rates, pairs))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct IndexPolicy {
+    pub enabled: bool,
+    /// Max deviation of the Kraken mid from the index (percent)
+    pub max_deviation_pct: f64,
+    /// Seconds between polls
+    pub poll_interval_secs: u64,
+}
+
+impl Default for IndexPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_deviation_pct: 3.0,
+            poll_interval_secs: 30,
+        }
+    }
+}
+
+impl IndexPolicy {
+    /// Create from INDEX_PRICE_CHECK (default off), INDEX_MAX_DEVIATION_PCT
+    /// and INDEX_POLL_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("INDEX_PRICE_CHECK")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            max_deviation_pct: std::env::var("INDEX_MAX_DEVIATION_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+                .unwrap_or(defaults.max_deviation_pct),
+            poll_interval_secs: std::env::var("INDEX_POLL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.poll_interval_secs),
+        }
+    }
+}
+
+/// A pair whose Kraken mid strays from the index
+#[derive(Debug, Clone, Serialize)]
+pub struct AnomalousPair {
+    pub pair: String,
+    pub kraken_mid: f64,
+    pub index_price: f64,
+    pub deviation_pct: f64,
+    pub source: String,
+}
+
+/// Result of the latest poll
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexReport {
+    pub enabled: bool,
+    pub sources: Vec<String>,
+    pub max_deviation_pct: f64,
+    /// Pairs both Kraken and a source quoted
+    pub pairs_checked: usize,
+    pub anomalous: Vec<AnomalousPair>,
+    pub last_check: Option<String>,
+    /// Failures of the latest poll, one per source
+    pub errors: Vec<String>,
+}
+
+/// Background poller that marks pairs deviating from the index as anomalous
+pub struct IndexPriceMonitor {
+    cache: Arc<OrderBookCache>,
+    policy: IndexPolicy,
+    sources: RwLock<Vec<Arc<dyn IndexPriceSource>>>,
+    report: RwLock<IndexReport>,
+    is_running: Arc<AtomicBool>,
+}
+
+impl IndexPriceMonitor {
+    pub fn new(cache: Arc<OrderBookCache>, policy: IndexPolicy) -> Self {
+        Self {
+            cache,
+            policy,
+            sources: RwLock::new(Vec::new()),
+            report: RwLock::new(IndexReport { enabled: policy.enabled, max_deviation_pct: policy.max_deviation_pct, ..Default::default() }),
+            is_running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Policy from the environment, with the Coinbase source when enabled
+    pub fn from_env(cache: Arc<OrderBookCache>) -> Self {
+        let monitor = Self::new(cache, IndexPolicy::from_env());
+        if monitor.policy.enabled {
+            monitor.add_source(Arc::new(CoinbaseRatesSource::new()));
+        }
+        monitor
+    }
+
+    /// Consult `source` after the ones already added
+    pub fn add_source(&self, source: Arc<dyn IndexPriceSource>) {
+        self.sources.write().push(source);
+    }
+
+    /// Spawn the poll task (no-op when disabled or already running)
+    pub fn start(self: &Arc<Self>) {
+        if !self.policy.enabled || self.is_running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let monitor = Arc::clone(self);
+        tokio::spawn(async move {
+            info!(
+                "Index price check started ({:.1}% max deviation, every {}s)",
+                monitor.policy.max_deviation_pct, monitor.policy.poll_interval_secs
+            );
+            let mut interval = tokio::time::interval(Duration::from_secs(monitor.policy.poll_interval_secs));
+
+            while monitor.is_running.load(Ordering::SeqCst) {
+                interval.tick().await;
+                monitor.check_once().await;
+            }
+
+            info!("Index price check stopped");
+        });
+    }
+
+    /// Stop polling and release all anomalous pairs
+    pub fn stop(&self) {
+        self.is_running.store(false, Ordering::SeqCst);
+        self.cache.set_anomalous(HashMap::new());
+    }
+
+    /// Poll every source once and apply the result
+    pub async fn check_once(&self) -> IndexReport {
+        let sources: Vec<Arc<dyn IndexPriceSource>> = self.sources.read().clone();
+        let prices = self.cache.get_all_prices();
+        let pairs: Vec<String> = prices.keys().cloned().collect();
+
+        // pair -> (index price, source name); the first source to quote a pair wins
+        let mut index: HashMap<String, (f64, String)> = HashMap::new();
+        let mut errors = Vec::new();
+        for source in &sources {
+            match source.fetch(&pairs).await {
+                Ok(quoted) => {
+                    for (pair, price) in quoted {
+                        if price > 0.0 && price.is_finite() {
+                            index.entry(pair).or_insert_with(|| (price, source.name().to_string()));
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Index price source {} failed: {}", source.name(), e);
+                    errors.push(format!("{}: {}", source.name(), e));
+                }
+            }
+        }
+
+        let (pairs_checked, anomalous) = evaluate(&prices, &index, self.policy.max_deviation_pct);
+
+        // Log only changes to the anomalous set
+        let previous = self.cache.get_anomalous();
+        for pair in &anomalous {
+            if !previous.contains_key(&pair.pair) {
+                warn!(
+                    "Blocking {}: Kraken mid {} is {:.2}% off the {} index ({})",
+                    pair.pair, pair.kraken_mid, pair.deviation_pct, pair.source, pair.index_price
+                );
+            }
+        }
+        for pair in previous.keys() {
+            if !anomalous.iter().any(|a| &a.pair == pair) {
+                info!("Releasing {} - back in line with the index", pair);
+            }
+        }
+        self.cache.set_anomalous(anomalous.iter().map(|a| (a.pair.clone(), a.deviation_pct)).collect());
+
+        let report = IndexReport {
+            enabled: self.policy.enabled,
+            sources: sources.iter().map(|s| s.name().to_string()).collect(),
+            max_deviation_pct: self.policy.max_deviation_pct,
+            pairs_checked,
+            anomalous,
+            last_check: Some(Utc::now().to_rfc3339()),
+            errors,
+        };
+        *self.report.write() = report.clone();
+        report
+    }
+
+    pub fn get_report(&self) -> IndexReport {
+        self.report.read().clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::SeqCst)
+    }
+}
+
+/// Compare every quoted pair's mid with its index price
+fn evaluate(
+    prices: &HashMap<String, PriceEdge>,
+    index: &HashMap<String, (f64, String)>,
+    max_deviation_pct: f64,
+) -> (usize, Vec<AnomalousPair>) {
+    let mut checked = 0;
+    let mut anomalous = Vec::new();
+
+    for (pair, edge) in prices {
+        let Some((index_price, source)) = index.get(pair) else { continue };
+        if edge.bid <= 0.0 || edge.ask <= 0.0 {
+            continue;
+        }
+        checked += 1;
+
+        let mid = (edge.bid + edge.ask) / 2.0;
+        let deviation_pct = (mid / index_price - 1.0).abs() * 100.0;
+        if deviation_pct > max_deviation_pct {
+            anomalous.push(AnomalousPair {
+                pair: pair.clone(),
+                kraken_mid: mid,
+                index_price: *index_price,
+                deviation_pct,
+                source: source.clone(),
+            });
+        }
+    }
+
+    anomalous.sort_by(|a, b| a.pair.cmp(&b.pair));
+    (checked, anomalous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::PairInfo;
+    use crate::types::OrderBookLevel;
+
+    struct FixedSource(&'static str, Option<HashMap<String, f64>>);
+
+    impl IndexPriceSource for FixedSource {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn fetch<'a>(&'a self, _pairs: &'a [String]) -> BoxFuture<'a, Result<HashMap<String, f64>, IndexPriceError>> {
+            let result = self.1.clone().ok_or_else(|| IndexPriceError::Request("offline".to_string()));
+            Box::pin(async move { result })
+        }
+    }
+
+    fn cache_with(pairs: &[(&str, &str, f64)]) -> Arc<OrderBookCache> {
+        let cache = Arc::new(OrderBookCache::new());
+        for &(base, quote, mid) in pairs {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            let level = |price: f64| vec![OrderBookLevel { price, qty: 1.0 }];
+            cache.update_snapshot(&pair, level(mid * 0.9999), level(mid * 1.0001), 1);
+        }
+        cache
+    }
+
+    #[tokio::test]
+    async fn test_deviating_pairs_marked_anomalous() {
+        let cache = cache_with(&[("BTC", "USD", 50_000.0), ("ETH", "USD", 2_700.0), ("SOL", "USD", 100.0)]);
+        let policy = IndexPolicy { enabled: true, max_deviation_pct: 3.0, poll_interval_secs: 30 };
+        let monitor = IndexPriceMonitor::new(Arc::clone(&cache), policy);
+
+        // The first source has no ETH quote, so the second one's is used
+        let primary: HashMap<String, f64> = [("BTC/USD".to_string(), 50_400.0)].into_iter().collect();
+        let secondary: HashMap<String, f64> = [("BTC/USD".to_string(), 40_000.0), ("ETH/USD".to_string(), 2_500.0)].into_iter().collect();
+        monitor.add_source(Arc::new(FixedSource("primary", Some(primary))));
+        monitor.add_source(Arc::new(FixedSource("secondary", Some(secondary))));
+
+        let report = monitor.check_once().await;
+        assert_eq!(report.pairs_checked, 2);
+        assert_eq!(report.anomalous.len(), 1);
+        assert_eq!((report.anomalous[0].pair.as_str(), report.anomalous[0].source.as_str()), ("ETH/USD", "secondary"));
+        assert!((report.anomalous[0].deviation_pct - 8.0).abs() < 0.01);
+        assert!(cache.is_anomalous("ETH/USD"));
+        assert!(!cache.is_anomalous("BTC/USD") && !cache.is_anomalous("SOL/USD"));
+
+        // With every source down nothing stays blocked
+        let offline = IndexPriceMonitor::new(Arc::clone(&cache), policy);
+        offline.add_source(Arc::new(FixedSource("primary", None)));
+        let report = offline.check_once().await;
+        assert_eq!(report.errors.len(), 1);
+        assert!(!cache.is_anomalous("ETH/USD"));
+
+        // Crosses come from USD rates
+        let rates: HashMap<String, f64> = [("BTC".to_string(), 0.00002), ("EUR".to_string(), 0.9)].into_iter().collect();
+        let prices = CoinbaseRatesSource::cross_prices(&rates, &["BTC/EUR".to_string(), "DOT/EUR".to_string()]);
+        assert!((prices["BTC/EUR"] - 45_000.0).abs() < 1e-6);
+        assert!(!prices.contains_key("DOT/EUR"));
+    }
+}
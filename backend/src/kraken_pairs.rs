@@ -12,6 +12,7 @@
 //! 6. Validated for triangular arbitrage paths
 #![allow(dead_code)]
 
+use crate::pair_registry::PairMeta;
 use crate::restrictions::RestrictionsManager;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -240,12 +241,21 @@ pub struct SelectedPair {
     pub kraken_id: String,
     /// WebSocket pair name (e.g., "XBT/USD")
     pub ws_name: String,
+    /// REST pair name (e.g., "XBTUSD")
+    pub altname: String,
+    /// AssetPairs status (selection keeps only "online" pairs)
+    pub status: String,
     /// 24-hour volume in USD equivalent
     pub volume_24h_usd: f64,
     /// Minimum order size
     pub ordermin: f64,
     /// Minimum order cost in quote currency
     pub costmin: f64,
+    /// Decimal places Kraken accepts in prices / base volumes (None when missing)
+    pub pair_decimals: Option<u32>,
+    pub lot_decimals: Option<u32>,
+    /// Last traded price from the ticker at selection time
+    pub last_price: f64,
 }
 
 /// Kraken pair selector for HFT arbitrage
@@ -273,14 +283,33 @@ impl KrakenPairSelector {
     /// Select the best trading pairs for arbitrage
     /// FAILS if configuration is incomplete
     pub async fn select_pairs(&self) -> Result<Vec<SelectedPair>, PairSelectionError> {
-        // Validate configuration first - fail early if not configured
-        self.config.validate().map_err(|e| PairSelectionError::ApiError(e))?;
-
         let max_pairs = self.config.get_max_pairs()
-            .map_err(|e| PairSelectionError::ApiError(e))?;
+            .map_err(PairSelectionError::ApiError)?;
 
-        info!("Starting pair selection (max: {}, quote currencies: {:?})",
-              max_pairs, self.config.allowed_quote_currencies);
+        // Step 6: Take top N pairs
+        let final_pairs: Vec<SelectedPair> = self.select_candidates()
+            .await?
+            .into_iter()
+            .take(max_pairs)
+            .collect();
+
+        info!("Selected {} pairs for trading:", final_pairs.len());
+        for (i, pair) in final_pairs.iter().enumerate() {
+            info!("  {}. {} - ${:.0} 24h volume", i + 1, pair.pair_name, pair.volume_24h_usd);
+        }
+
+        Ok(final_pairs)
+    }
+
+    /// Every pair that passes the filters and closes a triangle, highest
+    /// volume first, before the max_pairs cap (for ranking by other signals)
+    /// FAILS if configuration is incomplete
+    pub async fn select_candidates(&self) -> Result<Vec<SelectedPair>, PairSelectionError> {
+        // Validate configuration first - fail early if not configured
+        self.config.validate().map_err(PairSelectionError::ApiError)?;
+
+        info!("Starting pair selection (max: {:?}, quote currencies: {:?})",
+              self.config.max_pairs, self.config.allowed_quote_currencies);
 
         // Step 1: Fetch all asset pairs from Kraken
         let all_pairs = self.fetch_asset_pairs().await?;
@@ -302,22 +331,29 @@ impl KrakenPairSelector {
         let mut sorted_pairs = pairs_with_volume;
         sorted_pairs.sort_by(|a, b| b.volume_24h_usd.partial_cmp(&a.volume_24h_usd).unwrap());
 
-        // Step 5: Validate triangular paths and select final pairs
+        // Step 5: Validate triangular paths
         let validated_pairs = self.validate_triangular_paths(sorted_pairs);
         info!("After triangular validation: {} pairs", validated_pairs.len());
 
-        // Step 6: Take top N pairs
-        let final_pairs: Vec<SelectedPair> = validated_pairs
-            .into_iter()
-            .take(max_pairs)
-            .collect();
-
-        info!("Selected {} pairs for trading:", final_pairs.len());
-        for (i, pair) in final_pairs.iter().enumerate() {
-            info!("  {}. {} - ${:.0} 24h volume", i + 1, pair.pair_name, pair.volume_24h_usd);
-        }
+        Ok(validated_pairs)
+    }
 
-        Ok(final_pairs)
+    /// Every pair AssetPairs lists right now, unfiltered and whatever its
+    /// status (for spotting listings and delistings while running)
+    pub async fn fetch_listing(&self) -> Result<Vec<PairMeta>, PairSelectionError> {
+        let pairs = self.fetch_asset_pairs().await?;
+        Ok(pairs
+            .into_iter()
+            .map(|p| PairMeta {
+                pair_name: format!("{}/{}", p.base, p.quote),
+                base: p.base,
+                quote: p.quote,
+                kraken_id: p.kraken_id,
+                ws_name: p.ws_name,
+                altname: p.altname,
+                status: p.status,
+            })
+            .collect())
     }
 
     /// Fetch all asset pairs from Kraken REST API
@@ -368,16 +404,20 @@ impl KrakenPairSelector {
             .and_then(|v| v.as_str())
             .and_then(|s| s.parse::<f64>().ok())
             .unwrap_or(0.0);
+        let pair_decimals = info.get("pair_decimals").and_then(|v| v.as_u64()).map(|v| v as u32);
+        let lot_decimals = info.get("lot_decimals").and_then(|v| v.as_u64()).map(|v| v as u32);
 
         Some(RawPairInfo {
             kraken_id: kraken_id.to_string(),
             altname: altname.to_string(),
             ws_name: wsname.to_string(),
-            base: self.normalize_currency(base_raw),
-            quote: self.normalize_currency(quote_raw),
+            base: normalize_currency(base_raw),
+            quote: normalize_currency(quote_raw),
             status: status.to_string(),
             ordermin,
             costmin,
+            pair_decimals,
+            lot_decimals,
         })
     }
 
@@ -385,7 +425,7 @@ impl KrakenPairSelector {
     /// Returns error if configuration is incomplete
     fn apply_filters(&self, pairs: Vec<RawPairInfo>) -> Result<Vec<RawPairInfo>, PairSelectionError> {
         let max_cost_min = self.config.get_max_cost_min()
-            .map_err(|e| PairSelectionError::ApiError(e))?;
+            .map_err(PairSelectionError::ApiError)?;
 
         let filtered: Vec<RawPairInfo> = pairs
             .into_iter()
@@ -432,7 +472,7 @@ impl KrakenPairSelector {
         let mut result = Vec::new();
 
         let min_volume = self.config.get_min_volume()
-            .map_err(|e| PairSelectionError::ApiError(e))?;
+            .map_err(PairSelectionError::ApiError)?;
 
         // Get EUR/USD rate for volume conversion - REQUIRED, no fallback
         let eur_usd_rate = self.fetch_eur_usd_rate().await?;
@@ -488,9 +528,14 @@ impl KrakenPairSelector {
                                 quote: pair_info.quote.clone(),
                                 kraken_id: pair_info.kraken_id.clone(),
                                 ws_name,
+                                altname: pair_info.altname.clone(),
+                                status: pair_info.status.clone(),
                                 volume_24h_usd: volume_usd,
                                 ordermin: pair_info.ordermin,
                                 costmin: pair_info.costmin,
+                                pair_decimals: pair_info.pair_decimals,
+                                lot_decimals: pair_info.lot_decimals,
+                                last_price,
                             });
                         }
                     }
@@ -621,34 +666,35 @@ impl KrakenPairSelector {
             .collect()
     }
 
-    /// Normalize Kraken currency symbols to standard format
-    fn normalize_currency(&self, symbol: &str) -> String {
-        match symbol {
-            "XXBT" | "XBT" => "BTC".to_string(),
-            "XETH" => "ETH".to_string(),
-            "ZUSD" => "USD".to_string(),
-            "ZEUR" => "EUR".to_string(),
-            "ZCAD" => "CAD".to_string(),
-            "ZGBP" => "GBP".to_string(),
-            "ZJPY" => "JPY".to_string(),
-            "XXRP" => "XRP".to_string(),
-            "XXLM" => "XLM".to_string(),
-            "XLTC" => "LTC".to_string(),
-            "XXMR" => "XMR".to_string(),
-            "XXDG" | "XDG" => "DOGE".to_string(),
-            "XETC" => "ETC".to_string(),
-            "XZEC" => "ZEC".to_string(),
-            s if s.starts_with('X') || s.starts_with('Z') => s[1..].to_string(),
-            s => s.to_string(),
-        }
-    }
-
     /// Get the current configuration
     pub fn config(&self) -> &PairSelectionConfig {
         &self.config
     }
 }
 
+/// Normalize a Kraken asset code (AssetPairs base/quote, Balance keys, fee
+/// assets) to the name used in pairs: XXBT -> BTC, ZUSD -> USD, XXDG -> DOGE
+pub fn normalize_currency(symbol: &str) -> String {
+    match symbol {
+        "XXBT" | "XBT" => "BTC".to_string(),
+        "XETH" => "ETH".to_string(),
+        "ZUSD" => "USD".to_string(),
+        "ZEUR" => "EUR".to_string(),
+        "ZCAD" => "CAD".to_string(),
+        "ZGBP" => "GBP".to_string(),
+        "ZJPY" => "JPY".to_string(),
+        "XXRP" => "XRP".to_string(),
+        "XXLM" => "XLM".to_string(),
+        "XLTC" => "LTC".to_string(),
+        "XXMR" => "XMR".to_string(),
+        "XXDG" | "XDG" => "DOGE".to_string(),
+        "XETC" => "ETC".to_string(),
+        "XZEC" => "ZEC".to_string(),
+        s if s.starts_with('X') || s.starts_with('Z') => s[1..].to_string(),
+        s => s.to_string(),
+    }
+}
+
 /// Internal struct for raw pair info before volume filtering
 #[derive(Debug)]
 struct RawPairInfo {
@@ -660,4 +706,18 @@ struct RawPairInfo {
     status: String,
     ordermin: f64,
     costmin: f64,
+    pair_decimals: Option<u32>,
+    lot_decimals: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_currency() {
+        for (code, name) in [("XXBT", "BTC"), ("XBT", "BTC"), ("ZUSD", "USD"), ("XXRP", "XRP"), ("XXDG", "DOGE"), ("XLTC", "LTC"), ("XMLN", "MLN"), ("USDT", "USDT"), ("SOL", "SOL")] {
+            assert_eq!(normalize_currency(code), name, "{}", code);
+        }
+    }
 }
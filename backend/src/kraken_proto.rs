@@ -0,0 +1,341 @@
+//! Kraken WebSocket Protocol
+//!
+//! Typed requests and method responses for Kraken's WebSocket API, so the
+//! public market-data socket (`ws_v2`) and the private execution socket
+//! (`executor`) build and read the same messages instead of each assembling
+//! its own JSON. Types live under a module per protocol version; only v2 is
+//! spoken today.
+//!
+//! Requests serialize as `{"method": ..., "params": {...}, "req_id": ...}`.
+//! Channel data (book, ticker, executions) is still read by the modules
+//! that consume it: the book path has its own zero-copy parser.
+#![allow(dead_code)]
+
+pub mod v2 {
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    /// One request frame
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct Request {
+        #[serde(flatten)]
+        pub method: Method,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub req_id: Option<u64>,
+    }
+
+    impl Request {
+        pub fn new(method: Method) -> Self {
+            Self { method, req_id: None }
+        }
+
+        pub fn with_req_id(mut self, req_id: u64) -> Self {
+            self.req_id = Some(req_id);
+            self
+        }
+
+        /// Frame text to send
+        pub fn to_json(&self) -> String {
+            serde_json::to_string(self).expect("protocol requests always serialize")
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    #[serde(tag = "method", content = "params", rename_all = "snake_case")]
+    pub enum Method {
+        Subscribe(SubscribeParams),
+        Unsubscribe(SubscribeParams),
+        AddOrder(OrderParams),
+        BatchAdd(BatchAddParams),
+        AmendOrder(AmendParams),
+        CancelOrder(CancelOrderParams),
+        CancelAll(TokenParams),
+        Ping,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Channel {
+        Book,
+        Ticker,
+        /// Public trade prints
+        Trade,
+        Executions,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct SubscribeParams {
+        pub channel: Channel,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub symbol: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub depth: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub snap_trades: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub token: Option<String>,
+    }
+
+    impl SubscribeParams {
+        pub fn book(symbols: Vec<String>, depth: usize) -> Self {
+            Self { channel: Channel::Book, symbol: Some(symbols), depth: Some(depth), snap_trades: None, token: None }
+        }
+
+        pub fn ticker(symbols: Vec<String>) -> Self {
+            Self::symbols(Channel::Ticker, symbols)
+        }
+
+        /// `channel` for `symbols` with no options (what unsubscribe takes)
+        pub fn symbols(channel: Channel, symbols: Vec<String>) -> Self {
+            Self { channel, symbol: Some(symbols), depth: None, snap_trades: None, token: None }
+        }
+
+        /// Own executions, without replaying past trades
+        pub fn executions(token: String) -> Self {
+            Self { channel: Channel::Executions, symbol: None, depth: None, snap_trades: Some(false), token: Some(token) }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Side {
+        Buy,
+        Sell,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum OrderType {
+        Market,
+        Limit,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum TimeInForce {
+        Gtc,
+        Gtd,
+        Ioc,
+        /// Fill the whole quantity at once or cancel it all
+        Fok,
+    }
+
+    /// add_order params, also one entry of a batch_add (which carries the
+    /// symbol and token once for the whole batch)
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct OrderParams {
+        pub order_type: OrderType,
+        pub side: Side,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub symbol: Option<String>,
+        /// Base currency amount
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub order_qty: Option<f64>,
+        /// Quote currency amount (market buys only)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub cash_order_qty: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub limit_price: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub time_in_force: Option<TimeInForce>,
+        /// Rejected rather than filled if it would take liquidity
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub post_only: Option<bool>,
+        pub cl_ord_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub token: Option<String>,
+    }
+
+    impl OrderParams {
+        /// Market buy spending `cost` of the quote currency
+        pub fn market_buy_cost(cost: f64, cl_ord_id: String) -> Self {
+            Self::base(OrderType::Market, Side::Buy, cl_ord_id).cash_qty(cost)
+        }
+
+        /// Market buy of `qty` base
+        pub fn market_buy(qty: f64, cl_ord_id: String) -> Self {
+            Self::base(OrderType::Market, Side::Buy, cl_ord_id).qty(qty)
+        }
+
+        /// Market sell of `qty` base
+        pub fn market_sell(qty: f64, cl_ord_id: String) -> Self {
+            Self::base(OrderType::Market, Side::Sell, cl_ord_id).qty(qty)
+        }
+
+        /// Immediate-or-cancel limit for `qty` base at `limit_price`
+        pub fn ioc_limit(side: Side, qty: f64, limit_price: f64, cl_ord_id: String) -> Self {
+            let mut params = Self::base(OrderType::Limit, side, cl_ord_id).qty(qty);
+            params.limit_price = Some(limit_price);
+            params.time_in_force = Some(TimeInForce::Ioc);
+            params
+        }
+
+        /// Resting (gtc) limit for `qty` base at `limit_price` that only
+        /// ever adds liquidity
+        pub fn post_only_limit(side: Side, qty: f64, limit_price: f64, cl_ord_id: String) -> Self {
+            let mut params = Self::base(OrderType::Limit, side, cl_ord_id).qty(qty);
+            params.limit_price = Some(limit_price);
+            params.post_only = Some(true);
+            params
+        }
+
+        fn base(order_type: OrderType, side: Side, cl_ord_id: String) -> Self {
+            Self {
+                order_type,
+                side,
+                symbol: None,
+                order_qty: None,
+                cash_order_qty: None,
+                limit_price: None,
+                time_in_force: None,
+                post_only: None,
+                cl_ord_id,
+                token: None,
+            }
+        }
+
+        /// Send with `time_in_force` (Kraken defaults to gtc when absent)
+        pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+            self.time_in_force = Some(time_in_force);
+            self
+        }
+
+        fn qty(mut self, qty: f64) -> Self {
+            self.order_qty = Some(qty);
+            self
+        }
+
+        fn cash_qty(mut self, cost: f64) -> Self {
+            self.cash_order_qty = Some(cost);
+            self
+        }
+
+        /// Standalone add_order on `symbol`
+        pub fn on(mut self, symbol: &str, token: String) -> Self {
+            self.symbol = Some(symbol.to_string());
+            self.token = Some(token);
+            self
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct BatchAddParams {
+        pub symbol: String,
+        pub orders: Vec<OrderParams>,
+        pub token: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct AmendParams {
+        pub order_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub limit_price: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub order_qty: Option<f64>,
+        pub token: String,
+    }
+
+    /// Orders to cancel by our client order id
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct CancelOrderParams {
+        pub cl_ord_id: Vec<String>,
+        pub token: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct TokenParams {
+        pub token: String,
+    }
+
+    /// Acknowledgement of a request (subscribe, add_order, batch_add, ...)
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct MethodResponse {
+        pub method: String,
+        #[serde(default)]
+        pub success: bool,
+        #[serde(default)]
+        pub result: Option<Value>,
+        #[serde(default)]
+        pub error: Option<String>,
+        #[serde(default)]
+        pub req_id: Option<u64>,
+    }
+
+    impl MethodResponse {
+        /// The acknowledgement in a parsed frame (None for channel data)
+        pub fn from_value(frame: &Value) -> Option<Self> {
+            frame.get("method")?;
+            Self::deserialize(frame).ok()
+        }
+
+        /// Kraken's error text, or `fallback` when a failure came without one
+        pub fn error_or<'a>(&'a self, fallback: &'a str) -> &'a str {
+            self.error.as_deref().unwrap_or(fallback)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::v2::*;
+    use serde_json::{json, Value};
+
+    // Frames as sent and received on the live API
+    const ADD_ORDER: &str = r#"{"method":"add_order","params":{"order_type":"limit","side":"buy","symbol":"BTC/USD","order_qty":0.0002,"limit_price":50010.5,"time_in_force":"ioc","cl_ord_id":"arb_7","token":"tok"},"req_id":7}"#;
+    const BOOK_SUBSCRIBE: &str = r#"{"method":"subscribe","params":{"channel":"book","symbol":["BTC/USD","ETH/USD"],"depth":25},"req_id":1}"#;
+    const ADD_ORDER_REJECTED: &str = r#"{"error":"EOrder:Insufficient funds","method":"add_order","req_id":7,"success":false,"time_in":"2024-05-01T12:00:00.000000Z","time_out":"2024-05-01T12:00:00.000100Z"}"#;
+    const BATCH_ACK: &str = r#"{"method":"batch_add","req_id":9,"result":[{"order_id":"OA1"},{"order_id":"OA2"}],"success":true,"time_in":"2024-05-01T12:00:00.000000Z","time_out":"2024-05-01T12:00:00.000100Z"}"#;
+
+    fn parsed(text: &str) -> Value {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_requests_match_recorded_frames() {
+        let order = Request::new(Method::AddOrder(
+            OrderParams::ioc_limit(Side::Buy, 0.0002, 50010.5, "arb_7".to_string()).on("BTC/USD", "tok".to_string()),
+        ))
+        .with_req_id(7);
+        assert_eq!(parsed(&order.to_json()), parsed(ADD_ORDER));
+
+        let subscribe = Request::new(Method::Subscribe(SubscribeParams::book(
+            vec!["BTC/USD".to_string(), "ETH/USD".to_string()],
+            25,
+        )))
+        .with_req_id(1);
+        assert_eq!(parsed(&subscribe.to_json()), parsed(BOOK_SUBSCRIBE));
+
+        let buy = OrderParams::market_buy_cost(10.0, "arb_8_0".to_string());
+        assert_eq!(
+            serde_json::to_value(&buy).unwrap(),
+            json!({"order_type": "market", "side": "buy", "cash_order_qty": 10.0, "cl_ord_id": "arb_8_0"})
+        );
+        let base_buy = OrderParams::market_buy(0.0002, "arb_8_1".to_string());
+        assert_eq!(
+            serde_json::to_value(&base_buy).unwrap(),
+            json!({"order_type": "market", "side": "buy", "order_qty": 0.0002, "cl_ord_id": "arb_8_1"})
+        );
+        let fok = OrderParams::market_sell(0.5, "arb_9".to_string()).time_in_force(TimeInForce::Fok);
+        assert_eq!(serde_json::to_value(&fok).unwrap()["time_in_force"], "fok");
+        let maker = OrderParams::post_only_limit(Side::Sell, 0.01, 50005.0, "arb_10".to_string());
+        assert_eq!(
+            serde_json::to_value(&maker).unwrap(),
+            json!({"order_type": "limit", "side": "sell", "order_qty": 0.01, "limit_price": 50005.0, "post_only": true, "cl_ord_id": "arb_10"})
+        );
+        assert_eq!(parsed(&Request::new(Method::Ping).to_json()), json!({"method": "ping"}));
+    }
+
+    #[test]
+    fn test_method_responses_parse() {
+        let rejected = MethodResponse::from_value(&parsed(ADD_ORDER_REJECTED)).unwrap();
+        assert_eq!((rejected.method.as_str(), rejected.success, rejected.req_id), ("add_order", false, Some(7)));
+        assert_eq!(rejected.error_or("Order rejected"), "EOrder:Insufficient funds");
+
+        let batch = MethodResponse::from_value(&parsed(BATCH_ACK)).unwrap();
+        assert!(batch.success);
+        assert_eq!(batch.result.unwrap().as_array().unwrap().len(), 2);
+
+        let data = parsed(r#"{"channel":"executions","type":"update","data":[]}"#);
+        assert!(MethodResponse::from_value(&data).is_none());
+    }
+}
@@ -0,0 +1,380 @@
+//! Order Book Load Generator
+//!
+//! Measures the book pipeline without connecting to Kraken. A run
+//! synthesizes `updates_per_sec` incremental book updates spread over
+//! `pairs` synthetic pairs (USD, EUR, BTC and ETH crossed with each other,
+//! then ALT<n> coins quoted in all four), applies them to an OrderBookCache
+//! and sends each resulting delta down an event channel as large as the HFT
+//! loop's. A consumer drains the channel the way the hot path does: updates
+//! that can affect a cycle trigger a `scan_first` from USD.
+//!
+//! A run has its own cache and channel, so it can run beside live trading;
+//! it competes for CPU on the market-data runtime, not for books. Reported:
+//! - target and achieved update rates
+//! - events sent, dropped on a full channel and consumed, and the deepest
+//!   queue seen
+//! - cache write latency: scans hold the books' read locks, so the tail of
+//!   this is lock contention
+//! - scan latency, scans per second and opportunities found
+//!
+//! Started with POST /api/loadgen; the latest run is served with
+//! GET /api/snapshot.
+#![allow(dead_code)]
+
+use crate::order_book::{OrderBookCache, PairInfo};
+use crate::runtimes::spawn_on;
+use crate::scanner::Scanner;
+use crate::types::{BookDelta, EngineConfig, OrderBookLevel, ParseLatencySnapshot};
+use crate::ws_v2::ParseTimings;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tracing::info;
+
+/// Same capacity as the HFT loop's event channel
+const EVENT_CHANNEL_CAPACITY: usize = 1000;
+
+/// Levels per side of each synthetic book
+const BOOK_LEVELS: usize = 10;
+
+/// Book levels the consumer's scans read (updates below them aren't scanned)
+const SCAN_DEPTH_LEVELS: usize = 1;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadGenConfig {
+    pub updates_per_sec: u64,
+    pub pairs: usize,
+    pub duration_secs: u64,
+}
+
+impl Default for LoadGenConfig {
+    fn default() -> Self {
+        Self { updates_per_sec: 5_000, pairs: 100, duration_secs: 10 }
+    }
+}
+
+impl LoadGenConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=1_000_000).contains(&self.updates_per_sec) {
+            return Err("updates_per_sec must be between 1 and 1000000".to_string());
+        }
+        if !(6..=10_000).contains(&self.pairs) {
+            return Err("pairs must be between 6 and 10000".to_string());
+        }
+        if !(1..=600).contains(&self.duration_secs) {
+            return Err("duration_secs must be between 1 and 600".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// `count` synthetic pairs as (base, quote, mid), the majors first
+pub fn synthetic_pairs(count: usize) -> Vec<(String, String, f64)> {
+    let usd_value = |c: &str| match c {
+        "USD" => 1.0,
+        "EUR" => 1.08,
+        "BTC" => 50_000.0,
+        "ETH" => 2_500.0,
+        alt => 1.0 + alt[3..].parse::<f64>().unwrap_or(0.0),
+    };
+    let majors = ["USD", "EUR", "BTC", "ETH"];
+    let mut pairs: Vec<(String, String)> = [("EUR", "USD"), ("BTC", "USD"), ("ETH", "USD"), ("BTC", "EUR"), ("ETH", "EUR"), ("ETH", "BTC")]
+        .iter()
+        .map(|(b, q)| (b.to_string(), q.to_string()))
+        .collect();
+    let mut alt = 0;
+    while pairs.len() < count {
+        for quote in majors {
+            pairs.push((format!("ALT{}", alt), quote.to_string()));
+        }
+        alt += 1;
+    }
+    pairs.truncate(count);
+    pairs
+        .into_iter()
+        .enumerate()
+        // Small deterministic mispricings so some cycles pay
+        .map(|(n, (base, quote))| {
+            let mid = usd_value(&base) / usd_value(&quote) * (1.0 + 0.0005 * ((n * 7) % 11) as f64);
+            (base, quote, mid)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadGenReport {
+    pub config: LoadGenConfig,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub running: bool,
+    pub updates_applied: u64,
+    /// Updates applied per second of the run so far
+    pub updates_per_sec: f64,
+    pub events_sent: u64,
+    /// Dropped because the channel was full
+    pub events_dropped: u64,
+    pub events_consumed: u64,
+    pub drop_rate_pct: f64,
+    pub max_queue_depth: usize,
+    pub scans: u64,
+    pub scans_per_sec: f64,
+    pub opportunities_found: u64,
+    /// Time to apply an update to the cache
+    pub update_latency: ParseLatencySnapshot,
+    pub scan_latency: ParseLatencySnapshot,
+}
+
+/// One run's counters, shared by its producer and consumer
+struct LoadRun {
+    config: LoadGenConfig,
+    started_at: DateTime<Utc>,
+    started: Instant,
+    /// Run time, fixed when the run ends
+    elapsed: RwLock<Option<Duration>>,
+    finished_at: RwLock<Option<DateTime<Utc>>>,
+    updates_applied: AtomicU64,
+    events_sent: AtomicU64,
+    events_dropped: AtomicU64,
+    events_consumed: AtomicU64,
+    max_queue_depth: AtomicUsize,
+    scans: AtomicU64,
+    opportunities_found: AtomicU64,
+    update_latency: ParseTimings,
+    scan_latency: ParseTimings,
+}
+
+fn nanos(since: Instant) -> u32 {
+    since.elapsed().as_nanos().min(u32::MAX as u128) as u32
+}
+
+impl LoadRun {
+    fn new(config: LoadGenConfig) -> Self {
+        Self {
+            config,
+            started_at: Utc::now(),
+            started: Instant::now(),
+            elapsed: RwLock::new(None),
+            finished_at: RwLock::new(None),
+            updates_applied: AtomicU64::new(0),
+            events_sent: AtomicU64::new(0),
+            events_dropped: AtomicU64::new(0),
+            events_consumed: AtomicU64::new(0),
+            max_queue_depth: AtomicUsize::new(0),
+            scans: AtomicU64::new(0),
+            opportunities_found: AtomicU64::new(0),
+            update_latency: ParseTimings::default(),
+            scan_latency: ParseTimings::default(),
+        }
+    }
+
+    async fn execute(self: Arc<Self>, engine_config: EngineConfig) {
+        let cache = Arc::new(OrderBookCache::new());
+        let pairs = synthetic_pairs(self.config.pairs);
+        for (base, quote, mid) in &pairs {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.clone(),
+                quote: quote.clone(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            cache.update_snapshot(&pair, ladder(*mid, -1.0), ladder(*mid, 1.0), 1);
+        }
+
+        let (tx, mut rx) = mpsc::channel::<BookDelta>(EVENT_CHANNEL_CAPACITY);
+        let run = Arc::clone(&self);
+        let scan_cache = Arc::clone(&cache);
+        let consumer = tokio::spawn(async move {
+            let scanner = Scanner::new(scan_cache, engine_config.clone());
+            let bases = ["USD".to_string()];
+            while let Some(delta) = rx.recv().await {
+                run.events_consumed.fetch_add(1, Ordering::Relaxed);
+                if !delta.can_affect_cycles(SCAN_DEPTH_LEVELS) {
+                    continue;
+                }
+                let scan_start = Instant::now();
+                let found = scanner.scan_first(&bases, engine_config.min_profit_threshold);
+                run.scan_latency.record(nanos(scan_start));
+                run.scans.fetch_add(1, Ordering::Relaxed);
+                if found.is_some() {
+                    run.opportunities_found.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+
+        let names: Vec<String> = pairs.iter().map(|(b, q, _)| format!("{}/{}", b, q)).collect();
+        let mut sequences = vec![1u64; names.len()];
+        let mut rng = StdRng::from_entropy();
+        let duration = Duration::from_secs(self.config.duration_secs);
+        let mut ticker = tokio::time::interval(Duration::from_millis(1));
+        let mut applied = 0u64;
+        loop {
+            ticker.tick().await;
+            let elapsed = self.started.elapsed();
+            if elapsed >= duration {
+                break;
+            }
+            let due = (self.config.updates_per_sec as f64 * elapsed.as_secs_f64()) as u64;
+            while applied < due {
+                applied += 1;
+                let n = rng.gen_range(0..names.len());
+                sequences[n] += 1;
+                // Resize one existing level, so books stay uncrossed
+                let level = rng.gen_range(0..BOOK_LEVELS);
+                let side = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+                let update = vec![OrderBookLevel { price: level_price(pairs[n].2, side, level), qty: rng.gen_range(0.1..100.0) }];
+                let (bids, asks) = if side < 0.0 { (update, Vec::new()) } else { (Vec::new(), update) };
+
+                let update_start = Instant::now();
+                let delta = cache.update_incremental(&names[n], bids, asks, sequences[n]);
+                self.update_latency.record(nanos(update_start));
+                self.updates_applied.fetch_add(1, Ordering::Relaxed);
+
+                let Some(delta) = delta else { continue };
+                match tx.try_send(delta) {
+                    Ok(()) => {
+                        self.events_sent.fetch_add(1, Ordering::Relaxed);
+                        self.max_queue_depth.fetch_max(EVENT_CHANNEL_CAPACITY - tx.capacity(), Ordering::Relaxed);
+                    }
+                    Err(_) => {
+                        self.events_dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+        drop(tx);
+        let _ = consumer.await;
+
+        *self.elapsed.write() = Some(self.started.elapsed());
+        *self.finished_at.write() = Some(Utc::now());
+        let report = self.report();
+        info!(
+            "Load run done: {:.0} updates/s, {} of {} events dropped, {} scans (p99 {:.0}us)",
+            report.updates_per_sec, report.events_dropped, report.events_sent + report.events_dropped, report.scans, report.scan_latency.p99_us
+        );
+    }
+
+    fn report(&self) -> LoadGenReport {
+        let elapsed = self.elapsed.read().unwrap_or_else(|| self.started.elapsed()).as_secs_f64().max(f64::EPSILON);
+        let sent = self.events_sent.load(Ordering::Relaxed);
+        let dropped = self.events_dropped.load(Ordering::Relaxed);
+        let scans = self.scans.load(Ordering::Relaxed);
+        let updates_applied = self.updates_applied.load(Ordering::Relaxed);
+        let finished_at = *self.finished_at.read();
+        LoadGenReport {
+            config: self.config,
+            started_at: self.started_at,
+            finished_at,
+            running: finished_at.is_none(),
+            updates_applied,
+            updates_per_sec: updates_applied as f64 / elapsed,
+            events_sent: sent,
+            events_dropped: dropped,
+            events_consumed: self.events_consumed.load(Ordering::Relaxed),
+            drop_rate_pct: if sent + dropped > 0 { dropped as f64 / (sent + dropped) as f64 * 100.0 } else { 0.0 },
+            max_queue_depth: self.max_queue_depth.load(Ordering::Relaxed),
+            scans,
+            scans_per_sec: scans as f64 / elapsed,
+            opportunities_found: self.opportunities_found.load(Ordering::Relaxed),
+            update_latency: self.update_latency.snapshot(),
+            scan_latency: self.scan_latency.snapshot(),
+        }
+    }
+}
+
+/// Price of level `level` on the bid (-1) or ask (+1) side, 1bp apart
+fn level_price(mid: f64, side: f64, level: usize) -> f64 {
+    mid * (1.0 + side * 0.0001 * (level + 1) as f64)
+}
+
+fn ladder(mid: f64, side: f64) -> Vec<OrderBookLevel> {
+    (0..BOOK_LEVELS).map(|level| OrderBookLevel { price: level_price(mid, side, level), qty: 10.0 }).collect()
+}
+
+/// Runs one load test at a time and keeps the latest
+#[derive(Default)]
+pub struct LoadGenerator {
+    running: Arc<AtomicBool>,
+    last_run: RwLock<Option<Arc<LoadRun>>>,
+}
+
+impl LoadGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a run on `runtime` (the caller's when None)
+    pub fn start(&self, config: LoadGenConfig, engine_config: EngineConfig, runtime: Option<&Handle>) -> Result<(), String> {
+        config.validate()?;
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err("A load run is already going".to_string());
+        }
+        info!(
+            "Load run started: {} updates/s over {} pairs for {}s",
+            config.updates_per_sec, config.pairs, config.duration_secs
+        );
+        let run = Arc::new(LoadRun::new(config));
+        *self.last_run.write() = Some(Arc::clone(&run));
+        let running = Arc::clone(&self.running);
+        spawn_on(runtime, async move {
+            run.execute(engine_config).await;
+            running.store(false, Ordering::SeqCst);
+        });
+        Ok(())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// The latest run, live while it's going
+    pub fn report(&self) -> Option<LoadGenReport> {
+        self.last_run.read().as_ref().map(|run| run.report())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_run_feeds_cache_and_scanner() {
+        let pairs = synthetic_pairs(14);
+        assert_eq!(pairs.len(), 14);
+        assert_eq!((pairs[6].0.as_str(), pairs[6].1.as_str()), ("ALT0", "USD"));
+        assert!(LoadGenConfig { pairs: 3, ..Default::default() }.validate().is_err());
+
+        let generator = LoadGenerator::new();
+        let config = LoadGenConfig { updates_per_sec: 2_000, pairs: 14, duration_secs: 1 };
+        let engine_config = EngineConfig {
+            min_profit_threshold: 0.001,
+            fee_rate: 0.0026,
+            fee_source: "test".to_string(),
+            leg_thresholds: std::collections::BTreeMap::new(),
+        };
+        generator.start(config, engine_config.clone(), None).unwrap();
+        assert!(generator.start(config, engine_config, None).is_err());
+        while generator.is_running() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let report = generator.report().unwrap();
+        assert!(!report.running);
+        // Roughly the target rate (the run stops on the first tick past its duration)
+        assert!(report.updates_applied >= 1_500, "{}", report.updates_applied);
+        assert_eq!(report.events_sent, report.events_consumed);
+        assert_eq!(report.events_sent + report.events_dropped, report.updates_applied);
+        assert!(report.scans > 0 && report.scans <= report.events_consumed);
+        assert_eq!(report.update_latency.samples, report.updates_applied);
+    }
+}
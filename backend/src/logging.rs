@@ -0,0 +1,325 @@
+//! Logging Setup
+//!
+//! Installs the global tracing subscriber from `LoggingConfig`: verbosity as
+//! an EnvFilter directive (RUST_LOG syntax, e.g. "info,sqlx=warn"), plain
+//! text or one JSON object per line, and optionally a second copy of every
+//! line in a file that rolls over hourly or daily
+//! (`logs/backend.log` → `logs/backend.log.2026-10-14`).
+//!
+//! If the process already has a global subscriber (an embedding host or a
+//! test harness installed one) it is left alone and `configure_logging`
+//! reports that nothing was installed.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+#[derive(Error, Debug)]
+pub enum LoggingError {
+    #[error("Invalid log level '{0}': {1}")]
+    Level(String, String),
+    #[error("Cannot open log file {0}: {1}")]
+    File(PathBuf, io::Error),
+    #[error("Cannot install subscriber: {0}")]
+    Install(String),
+}
+
+/// When the log file starts a new segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Never,
+    Hourly,
+    Daily,
+}
+
+impl Rotation {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "never" => Ok(Rotation::Never),
+            "hourly" => Ok(Rotation::Hourly),
+            "daily" => Ok(Rotation::Daily),
+            other => Err(format!("Unknown log rotation '{}' (expected never, hourly or daily)", other)),
+        }
+    }
+
+    /// File name suffix for the segment containing `at` (None = no rotation)
+    fn suffix(&self, at: DateTime<Utc>) -> Option<String> {
+        match self {
+            Rotation::Never => None,
+            Rotation::Hourly => Some(at.format("%Y-%m-%d-%H").to_string()),
+            Rotation::Daily => Some(at.format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoggingConfig {
+    /// EnvFilter directive, e.g. "info" or "info,rust_backend::executor=debug"
+    pub level: String,
+    /// One JSON object per line instead of text
+    pub json: bool,
+    /// Also write to this file (None = stdout only)
+    pub file_path: Option<PathBuf>,
+    pub rotation: Rotation,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            json: false,
+            file_path: None,
+            rotation: Rotation::Daily,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Create from RUST_LOG, LOG_FORMAT (text or json), LOG_FILE and
+    /// LOG_ROTATION (never, hourly or daily; default daily)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            level: var("RUST_LOG").unwrap_or(defaults.level),
+            json: var("LOG_FORMAT").is_some_and(|v| v.eq_ignore_ascii_case("json")),
+            file_path: var("LOG_FILE").map(PathBuf::from),
+            rotation: var("LOG_ROTATION")
+                .and_then(|v| Rotation::parse(&v).ok())
+                .unwrap_or(defaults.rotation),
+        }
+    }
+}
+
+/// Install the global subscriber. Returns false (and changes nothing) when
+/// one is already installed.
+pub fn configure_logging(config: &LoggingConfig) -> Result<bool, LoggingError> {
+    if tracing::dispatcher::has_been_set() {
+        return Ok(false);
+    }
+
+    let filter = EnvFilter::try_new(&config.level)
+        .map_err(|e| LoggingError::Level(config.level.clone(), e.to_string()))?;
+
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = vec![output_layer(config.json, io::stdout, true)];
+    if let Some(path) = &config.file_path {
+        let file = RollingFile::open(path, config.rotation)?;
+        layers.push(output_layer(config.json, file, false));
+    }
+
+    let subscriber = Registry::default().with(layers).with(filter);
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| LoggingError::Install(e.to_string()))?;
+    Ok(true)
+}
+
+/// Text (as before: no target, with thread ids) or JSON lines to `writer`
+fn output_layer<W>(json: bool, writer: W, ansi: bool) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+    if json {
+        layer.event_format(JsonLines).boxed()
+    } else {
+        layer.with_ansi(ansi).with_target(false).with_thread_ids(true).boxed()
+    }
+}
+
+/// One JSON object per event: timestamp, level, target, thread, the
+/// enclosing span names and the event's fields (message included)
+pub struct JsonLines;
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::from(format!("{:?}", value)));
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+
+        let mut line = Map::new();
+        line.insert("timestamp".to_string(), Value::from(Utc::now().to_rfc3339()));
+        line.insert("level".to_string(), Value::from(metadata.level().as_str()));
+        line.insert("target".to_string(), Value::from(metadata.target()));
+        line.insert("thread".to_string(), Value::from(format!("{:?}", std::thread::current().id())));
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope.from_root().map(|span| Value::from(span.name())).collect();
+            line.insert("spans".to_string(), Value::Array(spans));
+        }
+        line.insert("fields".to_string(), Value::Object(fields.0));
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+struct Segment {
+    suffix: Option<String>,
+    file: File,
+}
+
+/// Append-only log file that switches to a new segment when the rotation
+/// period changes
+#[derive(Clone)]
+pub struct RollingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    segment: Arc<Mutex<Segment>>,
+}
+
+impl RollingFile {
+    pub fn open(path: &Path, rotation: Rotation) -> Result<Self, LoggingError> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| LoggingError::File(dir.to_path_buf(), e))?;
+        }
+        let suffix = rotation.suffix(Utc::now());
+        let file = Self::open_segment(path, suffix.as_deref())?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            rotation,
+            segment: Arc::new(Mutex::new(Segment { suffix, file })),
+        })
+    }
+
+    /// Path of the segment with `suffix` (the plain path without rotation)
+    pub fn segment_path(path: &Path, suffix: Option<&str>) -> PathBuf {
+        match suffix {
+            Some(suffix) => {
+                let mut name = path.as_os_str().to_owned();
+                name.push(".");
+                name.push(suffix);
+                PathBuf::from(name)
+            }
+            None => path.to_path_buf(),
+        }
+    }
+
+    fn open_segment(path: &Path, suffix: Option<&str>) -> Result<File, LoggingError> {
+        let segment = Self::segment_path(path, suffix);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&segment)
+            .map_err(|e| LoggingError::File(segment, e))
+    }
+
+    /// Write to the segment for `at`, rolling over first if its period ended
+    fn write_at(&self, buf: &[u8], at: DateTime<Utc>) -> io::Result<usize> {
+        let mut segment = self.segment.lock();
+        let suffix = self.rotation.suffix(at);
+        if suffix != segment.suffix {
+            match Self::open_segment(&self.path, suffix.as_deref()) {
+                Ok(file) => *segment = Segment { suffix, file },
+                // Keep writing to the old segment rather than lose lines
+                Err(e) => eprintln!("Log rotation failed: {}", e),
+            }
+        }
+        segment.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_at(buf, Utc::now())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.segment.lock().file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RollingFile {
+    type Writer = RollingFile;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_json_lines_into_rolling_file() {
+        let dir = std::env::temp_dir().join(format!("logging-test-{}", std::process::id()));
+        let path = dir.join("backend.log");
+        let file = RollingFile::open(&path, Rotation::Never).unwrap();
+
+        let subscriber = Registry::default()
+            .with(output_layer(true, file.clone(), false))
+            .with(EnvFilter::new("info"));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("hot_path");
+            let _guard = span.enter();
+            tracing::info!(pair = "BTC/USD", legs = 3u64, "trade done");
+            tracing::debug!("filtered out");
+        });
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["spans"], serde_json::json!(["hot_path"]));
+        assert_eq!(lines[0]["fields"]["message"], "trade done");
+        assert_eq!(lines[0]["fields"]["legs"], 3);
+
+        // Each hour gets its own segment
+        let hourly = RollingFile::open(&path, Rotation::Hourly).unwrap();
+        let later = Utc.with_ymd_and_hms(2030, 1, 2, 3, 0, 0).unwrap();
+        hourly.write_at(b"rolled\n", later).unwrap();
+        let rolled = dir.join("backend.log.2030-01-02-03");
+        assert_eq!(std::fs::read_to_string(&rolled).unwrap(), "rolled\n");
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        assert!(Rotation::parse("weekly").is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
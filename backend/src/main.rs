@@ -3,38 +3,95 @@
 //! Complete replacement for Python backend.
 
 mod api;
+mod audit;
 mod db;
 mod trading;
 
 // Trading engine modules
+mod analytics;
+mod anomaly;
 mod auth;
+mod breaker_sync;
+mod chaos;
 mod config_manager;
+mod config_schema;
+mod consistency;
+mod dead_man;
+mod depth_tiers;
+mod event_drops;
+mod exec_queue;
 mod executor;
+#[cfg(test)]
+mod execution_sim;
+mod export;
+mod fill_journal;
 mod graph_manager;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod guards;
 mod hft_loop;
+mod index_price;
 mod kraken_pairs;
+mod kraken_proto;
+mod loadgen;
+mod logging;
+#[cfg(test)]
+mod mock_kraken;
+mod notifications;
+mod notional;
+mod opportunity_cache;
+mod opportunity_filter;
 mod order_book;
+mod pair_quality;
+mod pair_ranking;
+mod pair_refresh;
+mod pair_registry;
+mod private_transport;
+mod reconcile;
+mod reconnect;
+mod rest_bootstrap;
 mod restrictions;
+mod retention;
+mod runtimes;
+mod safe_mode;
+mod scan_fairness;
+mod scan_profile;
 mod scanner;
+mod stablecoin;
+mod stats_history;
+mod strategy_plugin;
+mod supervisor;
+mod throttle;
+mod trade_feed;
+mod trade_lifecycle;
+mod trade_wal;
+mod trading_day;
 mod types;
+mod universe;
+mod valuation;
+mod webhook;
 mod ws_v2;
 
-use crate::api::create_router;
+use crate::api::{create_router, ReadCache};
+use crate::config_schema::StartupConfigPolicy;
 use crate::db::Database;
+use crate::logging::LoggingConfig;
 use crate::restrictions::RestrictionsManager;
 use crate::trading::TradingEngine;
 
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::signal;
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use tracing::{info, warn};
+
+const RESTRICTIONS_FILE: &str = "config/canada_restrictions.json";
 
 /// Application state shared across all handlers
 pub struct AppState {
     pub db: Database,
     pub engine: Arc<TradingEngine>,
     pub restrictions: Arc<RestrictionsManager>,
+    pub read_cache: ReadCache,
 }
 
 #[tokio::main]
@@ -43,12 +100,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
 
     // Initialize logging
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .with_target(false)
-        .with_thread_ids(true)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber)?;
+    logging::configure_logging(&LoggingConfig::from_env())?;
+    // Record panics in spawned tasks instead of losing them with the task
+    supervisor::install_panic_hook();
 
     info!("╔══════════════════════════════════════════════════════════╗");
     info!("║   LimogiAICryptoX - HFT Trading Backend v1.1.0          ║");
@@ -70,9 +124,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::new(&database_url).await?;
     info!("Database connected");
 
+    // Apply embedded schema migrations when enabled
+    let auto_migrate = std::env::var("DB_AUTO_MIGRATE")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false);
+    if auto_migrate {
+        info!("Running database migrations...");
+        db.migrate().await?;
+    }
+
+    // With STARTUP_REQUIRE_CONFIG on, incomplete settings stop the server here
+    let startup_policy = StartupConfigPolicy::from_env();
+
     // Initialize restrictions manager (loads from config/canada_restrictions.json)
     info!("Initializing restrictions manager...");
-    let restrictions = Arc::new(RestrictionsManager::new(Some("config/canada_restrictions.json")));
+    let restrictions = if startup_policy.require_complete {
+        Arc::new(RestrictionsManager::load_or_error(RESTRICTIONS_FILE)?)
+    } else {
+        Arc::new(RestrictionsManager::new(Some(RESTRICTIONS_FILE)))
+    };
     info!("Restrictions manager initialized - {} blocked currencies",
           restrictions.get_blocked_currencies().len());
 
@@ -84,16 +154,45 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         api_secret,
         db.clone(),
     ).await?);
+    engine.load_startup_config(startup_policy).await?;
     info!("Trading engine initialized (STOPPED - waiting for user to configure and start)");
+    engine.start_dead_man_watch();
+    engine.start_watchdog();
+    engine.start_balance_refresh();
+    engine.start_reconciliation();
+    engine.start_stats_history();
+    engine.start_daily_reset();
+    engine.start_fee_refresh();
+    engine.start_pair_refresh();
+    engine.start_depth_tiers();
+    engine.start_retention();
+    engine.start_breaker_sync();
+    engine.start_event_drop_watch();
+    engine.start_restrictions_watch(Arc::clone(&restrictions));
+
+    // After a crash the engine comes back scanning, but in safe mode
+    {
+        let engine = Arc::clone(&engine);
+        tokio::spawn(async move { engine.recover_after_restart().await });
+    }
 
     // NOTE: Engine is NOT auto-started!
     // User must:
     // 1. Configure settings via dashboard (start currency, trade amount, etc.)
     // 2. Call POST /api/engine/start to start the engine
     // This ensures user consciously starts trading with their intended configuration.
+    // The one exception is recovery after a crash (below): streaming and scanning
+    // restart, but nothing executes until POST /api/live/confirm-resume.
 
     // Create application state
-    let state = Arc::new(AppState { db, engine, restrictions });
+    let read_cache = ReadCache::from_env();
+    let state = Arc::new(AppState { db, engine: Arc::clone(&engine), restrictions, read_cache });
+
+    // Same operations over gRPC, when built with it and GRPC_ADDR is set
+    #[cfg(feature = "grpc")]
+    if let Some(addr) = grpc::addr_from_env() {
+        tokio::spawn(grpc::serve(Arc::clone(&state), addr, shutdown_signal()));
+    }
 
     // Create router with all API endpoints
     let app = create_router(state);
@@ -108,23 +207,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_graceful_shutdown(shutdown_signal())
         .await?;
 
+    // Halt trading and drain in-flight orders before the runtime drops the
+    // engine's tasks mid-order
+    engine.close().await;
     info!("Server shutdown complete");
     Ok(())
 }
 
 async fn shutdown_signal() {
+    // A handler that can't be installed leaves that signal to its default
+    // action rather than panicking the server
     let ctrl_c = async {
-        signal::ctrl_c()
-            .await
-            .expect("failed to install Ctrl+C handler");
+        if let Err(e) = signal::ctrl_c().await {
+            warn!("Failed to install Ctrl+C handler: {}", e);
+            std::future::pending::<()>().await;
+        }
     };
 
     #[cfg(unix)]
     let terminate = async {
-        signal::unix::signal(signal::unix::SignalKind::terminate())
-            .expect("failed to install signal handler")
-            .recv()
-            .await;
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to install SIGTERM handler: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
     };
 
     #[cfg(not(unix))]
@@ -0,0 +1,592 @@
+//! Mock Kraken v2 Exchange
+//!
+//! In-process stand-in for Kraken so the whole engine (public book stream →
+//! scanner → HFT loop → private order socket → trade recording) can be
+//! exercised end to end without network access or API keys. It serves, on
+//! random local ports:
+//! - the public v2 socket: acks `book`/`ticker` subscriptions, sends a book
+//!   snapshot per subscribed symbol and then every delta pushed with
+//!   `update_book`
+//! - the private v2 socket: acks the `executions` subscription, answers
+//!   `add_order` (market orders fill in full at the current best level, or
+//!   are rejected with a queued error) and streams the resulting execution
+//! - the REST API, as far as GetWebSocketsToken
+//!
+//! `MockKraken::point_env` sets KRAKEN_WS_V2_PUBLIC, KRAKEN_WS_V2_PRIVATE and
+//! KRAKEN_REST_URL so the real clients connect to it. Fills don't consume
+//! the book and balances aren't tracked.
+//!
+//! The end-to-end test checks the recorded trade rows only when
+//! TEST_DATABASE_URL points at a Postgres it may migrate.
+
+use crate::types::OrderBookLevel;
+use crate::ws_v2::calculate_book_checksum;
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+/// An order as the mock exchange filled it
+#[derive(Debug, Clone)]
+pub struct MockOrder {
+    pub symbol: String,
+    pub side: String,
+    /// cash_order_qty for buys (quote), order_qty for sells (base)
+    pub requested: f64,
+    pub filled_qty: f64,
+    pub price: f64,
+}
+
+/// (price, qty) decimals the mock formats book checksums at
+const BOOK_PRECISION: (u32, u32) = (5, 8);
+
+#[derive(Default)]
+struct MockBook {
+    bids: Vec<OrderBookLevel>,
+    asks: Vec<OrderBookLevel>,
+}
+
+impl MockBook {
+    fn apply(levels: &mut Vec<OrderBookLevel>, updates: &[OrderBookLevel], descending: bool) {
+        for update in updates {
+            levels.retain(|l| l.price != update.price);
+            if update.qty > 0.0 {
+                levels.push(update.clone());
+            }
+        }
+        levels.sort_by(|a, b| {
+            let order = a.price.partial_cmp(&b.price).unwrap_or(std::cmp::Ordering::Equal);
+            if descending { order.reverse() } else { order }
+        });
+    }
+
+    fn checksum(&self) -> u32 {
+        let top = |levels: &[OrderBookLevel]| levels.iter().take(10).cloned().collect::<Vec<_>>();
+        calculate_book_checksum(&top(&self.bids), &top(&self.asks), BOOK_PRECISION.0, BOOK_PRECISION.1)
+    }
+}
+
+struct MockState {
+    books: Mutex<HashMap<String, MockBook>>,
+    /// (symbol, frame) for every book delta
+    updates: broadcast::Sender<(String, String)>,
+    orders: Mutex<Vec<MockOrder>>,
+    rejections: Mutex<VecDeque<String>>,
+    /// Fee taken from what each fill receives (decimal)
+    fee_rate: f64,
+    token_requests: AtomicU64,
+    next_order_id: AtomicU64,
+}
+
+pub struct MockKraken {
+    pub public_url: String,
+    pub private_url: String,
+    pub rest_url: String,
+    state: Arc<MockState>,
+}
+
+/// Three levels a side around the best bid/ask, 10 units each
+pub fn ladder(best_bid: f64, best_ask: f64) -> (Vec<OrderBookLevel>, Vec<OrderBookLevel>) {
+    let tick = (best_ask - best_bid).max(best_ask * 1e-5);
+    let bids = (0..3).map(|i| OrderBookLevel { price: best_bid - tick * i as f64, qty: 10.0 }).collect();
+    let asks = (0..3).map(|i| OrderBookLevel { price: best_ask + tick * i as f64, qty: 10.0 }).collect();
+    (bids, asks)
+}
+
+impl MockKraken {
+    /// Bind all three servers on 127.0.0.1 and start accepting
+    pub async fn start(fee_rate: f64) -> Self {
+        let (updates, _) = broadcast::channel(1024);
+        let state = Arc::new(MockState {
+            books: Mutex::new(HashMap::new()),
+            updates,
+            orders: Mutex::new(Vec::new()),
+            rejections: Mutex::new(VecDeque::new()),
+            fee_rate,
+            token_requests: AtomicU64::new(0),
+            next_order_id: AtomicU64::new(1),
+        });
+
+        let public = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let private = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rest = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mock = Self {
+            public_url: format!("ws://{}", public.local_addr().unwrap()),
+            private_url: format!("ws://{}", private.local_addr().unwrap()),
+            rest_url: format!("http://{}", rest.local_addr().unwrap()),
+            state: Arc::clone(&state),
+        };
+
+        Self::accept(public, Arc::clone(&state), |stream, state| tokio::spawn(serve_public(stream, state)));
+        Self::accept(private, Arc::clone(&state), |stream, state| tokio::spawn(serve_private(stream, state)));
+        Self::accept(rest, state, |stream, state| tokio::spawn(serve_rest(stream, state)));
+        mock
+    }
+
+    fn accept<F>(listener: TcpListener, state: Arc<MockState>, serve: F)
+    where
+        F: Fn(TcpStream, Arc<MockState>) -> tokio::task::JoinHandle<()> + Send + 'static,
+    {
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                serve(stream, Arc::clone(&state));
+            }
+        });
+    }
+
+    /// Point the Kraken clients at this mock (process-wide)
+    pub fn point_env(&self) {
+        std::env::set_var("KRAKEN_WS_V2_PUBLIC", &self.public_url);
+        std::env::set_var("KRAKEN_WS_V2_PRIVATE", &self.private_url);
+        std::env::set_var("KRAKEN_REST_URL", &self.rest_url);
+    }
+
+    /// Replace a book (sent as the snapshot to later subscribers)
+    pub fn set_book(&self, symbol: &str, (bids, asks): (Vec<OrderBookLevel>, Vec<OrderBookLevel>)) {
+        let mut book = MockBook::default();
+        MockBook::apply(&mut book.bids, &bids, true);
+        MockBook::apply(&mut book.asks, &asks, false);
+        self.state.books.lock().insert(symbol.to_string(), book);
+    }
+
+    /// Apply level updates (qty 0 removes a level) and stream them as a delta
+    pub fn update_book(&self, symbol: &str, bids: &[OrderBookLevel], asks: &[OrderBookLevel]) {
+        let checksum = {
+            let mut books = self.state.books.lock();
+            let book = books.entry(symbol.to_string()).or_default();
+            MockBook::apply(&mut book.bids, bids, true);
+            MockBook::apply(&mut book.asks, asks, false);
+            book.checksum()
+        };
+        let frame = book_frame("update", symbol, bids, asks, checksum);
+        let _ = self.state.updates.send((symbol.to_string(), frame));
+    }
+
+    /// Reject the next add_order with `error`
+    pub fn reject_next(&self, error: &str) {
+        self.state.rejections.lock().push_back(error.to_string());
+    }
+
+    pub fn orders(&self) -> Vec<MockOrder> {
+        self.state.orders.lock().clone()
+    }
+
+    pub fn token_requests(&self) -> u64 {
+        self.state.token_requests.load(Ordering::Relaxed)
+    }
+}
+
+fn levels_json(levels: &[OrderBookLevel]) -> Value {
+    Value::Array(levels.iter().map(|l| json!({ "price": l.price, "qty": l.qty })).collect())
+}
+
+fn book_frame(kind: &str, symbol: &str, bids: &[OrderBookLevel], asks: &[OrderBookLevel], checksum: u32) -> String {
+    json!({
+        "channel": "book",
+        "type": kind,
+        "data": [{
+            "symbol": symbol,
+            "bids": levels_json(bids),
+            "asks": levels_json(asks),
+            "checksum": checksum,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }]
+    })
+    .to_string()
+}
+
+fn symbols_of(params: &Value) -> Vec<String> {
+    params["symbol"]
+        .as_array()
+        .map(|s| s.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+async fn serve_public(stream: TcpStream, state: Arc<MockState>) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else { return };
+    let (mut write, mut read) = ws.split();
+    let mut updates = state.updates.subscribe();
+    let mut subscribed: HashSet<String> = HashSet::new();
+
+    loop {
+        tokio::select! {
+            msg = read.next() => {
+                let Some(Ok(Message::Text(text))) = msg else { break };
+                let Ok(request) = serde_json::from_str::<Value>(&text) else { continue };
+                let method = request["method"].as_str().unwrap_or_default();
+                let channel = request["params"]["channel"].as_str().unwrap_or_default().to_string();
+                let symbols = symbols_of(&request["params"]);
+
+                let mut replies = Vec::new();
+                for symbol in &symbols {
+                    replies.push(json!({
+                        "method": method,
+                        "success": true,
+                        "result": { "channel": channel, "symbol": symbol },
+                        "req_id": request["req_id"],
+                    }).to_string());
+                }
+                if method == "subscribe" && channel == "book" {
+                    let books = state.books.lock();
+                    for symbol in &symbols {
+                        subscribed.insert(symbol.clone());
+                        if let Some(book) = books.get(symbol) {
+                            replies.push(book_frame("snapshot", symbol, &book.bids, &book.asks, book.checksum()));
+                        }
+                    }
+                } else if method == "unsubscribe" && channel == "book" {
+                    for symbol in &symbols {
+                        subscribed.remove(symbol);
+                    }
+                }
+                for reply in replies {
+                    if write.send(Message::Text(reply)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            update = updates.recv() => {
+                let Ok((symbol, frame)) = update else { break };
+                if subscribed.contains(&symbol) && write.send(Message::Text(frame)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Fill a market order at the best level, or the queued rejection
+fn fill_order(state: &MockState, params: &Value) -> Result<(MockOrder, String), String> {
+    if let Some(error) = state.rejections.lock().pop_front() {
+        return Err(error);
+    }
+    let symbol = params["symbol"].as_str().unwrap_or_default().to_string();
+    let side = params["side"].as_str().unwrap_or_default().to_string();
+    let books = state.books.lock();
+    let book = books.get(&symbol).ok_or_else(|| "EQuery:Unknown asset pair".to_string())?;
+
+    let (requested, filled_qty, price) = if side == "buy" {
+        let price = book.asks.first().ok_or("EOrder:Insufficient liquidity")?.price;
+        let cash = params["cash_order_qty"].as_f64().ok_or("EGeneral:Invalid arguments:cash_order_qty")?;
+        (cash, cash / price, price)
+    } else {
+        let price = book.bids.first().ok_or("EOrder:Insufficient liquidity")?.price;
+        let qty = params["order_qty"].as_f64().ok_or("EGeneral:Invalid arguments:order_qty")?;
+        (qty, qty, price)
+    };
+    let order_id = format!("OMOCK-{:05}", state.next_order_id.fetch_add(1, Ordering::Relaxed));
+    Ok((MockOrder { symbol, side, requested, filled_qty, price }, order_id))
+}
+
+fn execution_frame(order: &MockOrder, order_id: &str, cl_ord_id: &Value, fee_rate: f64) -> String {
+    let cost = order.filled_qty * order.price;
+    // The fee comes out of what the order receives
+    let received = if order.side == "buy" { order.filled_qty } else { cost };
+    json!({
+        "channel": "executions",
+        "type": "update",
+        "data": [{
+            "order_id": order_id,
+            "cl_ord_id": cl_ord_id,
+            "exec_id": format!("{}-1", order_id),
+            "exec_type": "trade",
+            "order_status": "filled",
+            "symbol": order.symbol,
+            "side": order.side,
+            "last_qty": order.filled_qty,
+            "last_price": order.price,
+            "cum_qty": order.filled_qty,
+            "cum_cost": cost,
+            "avg_price": order.price,
+            "fee_usd_equiv": 0.0,
+            "fees": [{ "asset": "mock", "qty": received * fee_rate }],
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }]
+    })
+    .to_string()
+}
+
+async fn serve_private(stream: TcpStream, state: Arc<MockState>) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else { return };
+    let (mut write, mut read) = ws.split();
+
+    while let Some(Ok(msg)) = read.next().await {
+        let Message::Text(text) = msg else { continue };
+        let Ok(request) = serde_json::from_str::<Value>(&text) else { continue };
+        let params = &request["params"];
+
+        let replies = match request["method"].as_str().unwrap_or_default() {
+            "subscribe" => vec![json!({
+                "method": "subscribe",
+                "success": true,
+                "result": { "channel": params["channel"] },
+            }).to_string()],
+            "add_order" => match fill_order(&state, params) {
+                Ok((order, order_id)) => {
+                    let ack = json!({
+                        "method": "add_order",
+                        "success": true,
+                        "result": { "order_id": order_id, "cl_ord_id": params["cl_ord_id"] },
+                        "req_id": request["req_id"],
+                    });
+                    let execution = execution_frame(&order, &order_id, &params["cl_ord_id"], state.fee_rate);
+                    state.orders.lock().push(order);
+                    vec![ack.to_string(), execution]
+                }
+                Err(error) => vec![json!({
+                    "method": "add_order",
+                    "success": false,
+                    "error": error,
+                    "req_id": request["req_id"],
+                }).to_string()],
+            },
+            _ => Vec::new(),
+        };
+        for reply in replies {
+            if write.send(Message::Text(reply)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Minimal HTTP/1.1: one request per connection
+async fn serve_rest(mut stream: TcpStream, state: Arc<MockState>) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let Ok(n) = stream.read(&mut buf).await else { return };
+        if n == 0 {
+            return;
+        }
+        request.extend_from_slice(&buf[..n]);
+        if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+    let content_length = head
+        .lines()
+        .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+        .unwrap_or(0);
+    while request.len() < header_end + content_length {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    let path = head.split_whitespace().nth(1).unwrap_or_default();
+    let body = if path == "/0/private/GetWebSocketsToken" {
+        state.token_requests.fetch_add(1, Ordering::Relaxed);
+        json!({ "error": [], "result": { "token": "mock-ws-token", "expires": 900 } })
+    } else {
+        json!({ "error": ["EGeneral:Unknown method"] })
+    }
+    .to_string();
+
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditLog;
+    use crate::auth::KrakenAuth;
+    use crate::config_manager::ConfigManager;
+    use crate::db::Database;
+    use crate::executor::ExecutionEngine;
+    use crate::hft_loop::{CooldownConfig, HftConfig, HftLoop, WarmupPolicy};
+    use crate::kraken_pairs::SelectedPair;
+    use crate::notional::NotionalLimits;
+    use crate::opportunity_cache::OpportunityCache;
+    use crate::order_book::OrderBookCache;
+    use crate::scan_profile::ScanProfiler;
+    use crate::stablecoin::StablecoinPolicy;
+    use crate::types::{EngineConfig, Opportunity, Strategy};
+    use crate::valuation::{PricingSource, Valuator};
+    use crate::ws_v2::KrakenWebSocketV2;
+    use base64::Engine;
+    use std::time::Duration;
+
+    const PAIRS: [(&str, &str); 3] = [("BTC", "USD"), ("ETH", "BTC"), ("ETH", "USD")];
+
+    fn selected(base: &str, quote: &str) -> SelectedPair {
+        let pair = format!("{}/{}", base, quote);
+        SelectedPair {
+            pair_name: pair.clone(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            kraken_id: pair.replace('/', ""),
+            altname: pair.replace('/', ""),
+            ws_name: pair,
+            status: "online".to_string(),
+            volume_24h_usd: 1_000_000.0,
+            ordermin: 0.0,
+            costmin: 0.0,
+            pair_decimals: Some(BOOK_PRECISION.0),
+            lot_decimals: Some(BOOK_PRECISION.1),
+            last_price: 0.0,
+        }
+    }
+
+    async fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+        for _ in 0..200 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("timed out waiting for {}", what);
+    }
+
+    /// TEST_DATABASE_URL (migrated here) for asserting rows; otherwise a
+    /// pool to nowhere and the recording step only logs its failures
+    async fn test_database() -> (Database, bool) {
+        match std::env::var("TEST_DATABASE_URL") {
+            Ok(url) => {
+                let db = Database::new(&url).await.expect("TEST_DATABASE_URL unreachable");
+                db.migrate().await.unwrap();
+                (db, true)
+            }
+            Err(_) => (Database::connect_lazy("postgres://mock@127.0.0.1:1/none", Duration::from_millis(200)).unwrap(), false),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_stream_detect_execute_record() {
+        let mock = MockKraken::start(0.0).await;
+        mock.point_env();
+        mock.set_book("BTC/USD", ladder(49_990.0, 50_000.0));
+        mock.set_book("ETH/BTC", ladder(0.04999, 0.05));
+        mock.set_book("ETH/USD", ladder(2_499.0, 2_500.0));
+
+        // Wired the way TradingEngine::start does it
+        let (db, check_rows) = test_database().await;
+        let cache = Arc::new(OrderBookCache::new());
+        let config_manager = Arc::new(ConfigManager::new(EngineConfig::unconfigured()));
+        config_manager.update_fee_rate(0.0026, "test");
+        let mut hft_loop = HftLoop::new(
+            Arc::clone(&cache),
+            config_manager,
+            db.clone(),
+            AuditLog::new(db.clone()),
+            Arc::new(OpportunityCache::new(0.0)),
+            Arc::new(ScanProfiler::new(false)),
+            Arc::new(Valuator::new(Arc::clone(&cache), PricingSource::Mid)),
+        )
+        .with_warmup(WarmupPolicy { min_uptime_secs: 0, min_fresh_pairs_pct: 0.0, min_scans: 0 });
+        hft_loop
+            .update_config(HftConfig {
+                min_profit_threshold: 0.001,
+                trade_amount: 100.0,
+                max_daily_loss: 100.0,
+                max_total_loss: 500.0,
+                reporting_currency: "USD".to_string(),
+                base_currencies: vec!["USD".to_string()],
+                sizing_tiers: Vec::new(),
+                max_safe_amount: None,
+                cooldowns: CooldownConfig::default(),
+                leg_liquidity: None,
+                reserves: HashMap::new(),
+                stablecoins: StablecoinPolicy::default(),
+                notional_limits: NotionalLimits::default(),
+                min_pair_quality: 0.0,
+            })
+            .await;
+
+        let secret = base64::engine::general_purpose::STANDARD.encode(b"mock-secret");
+        let auth = Arc::new(KrakenAuth::new("mock-key".to_string(), secret).unwrap());
+        let exec_engine = ExecutionEngine::new(Arc::clone(&auth), Arc::clone(&cache));
+        exec_engine.connect().await.unwrap();
+        hft_loop.set_execution_engine(exec_engine).await;
+
+        let hft_tx = hft_loop.create_event_channel();
+        let mut ws = KrakenWebSocketV2::new(Arc::clone(&cache));
+        let (mut ws_rx, _) = ws.create_event_channel();
+        tokio::spawn(async move {
+            while let Some(pair) = ws_rx.recv().await {
+                if hft_tx.send(pair).await.is_err() {
+                    break;
+                }
+            }
+        });
+        ws.initialize_with_pairs(PAIRS.iter().map(|(b, q)| selected(b, q)).collect());
+        ws.start(PAIRS.len(), 10).await.unwrap();
+
+        // Initialize + stream: snapshots land in the cache, nothing is profitable
+        wait_for("book snapshots", || PAIRS.iter().all(|(b, q)| cache.get_order_book(&format!("{}/{}", b, q)).is_some())).await;
+        assert_eq!(cache.get_order_book("ETH/BTC").unwrap().asks[0].price, 0.05);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(mock.orders().is_empty());
+
+        // ETH/USD bid jumps ~2.4%: USD → BTC → ETH → USD clears fees
+        let touch = |symbol: &str, bid: f64| mock.update_book(symbol, &[OrderBookLevel { price: bid, qty: 11.0 }], &[]);
+        touch("BTC/USD", 49_990.0);
+        touch("ETH/BTC", 0.04999);
+        let (bids, mut asks) = ladder(2_560.0, 2_561.0);
+        asks.extend((0..3).map(|i| OrderBookLevel { price: 2_500.0 + i as f64, qty: 0.0 }));
+        mock.update_book("ETH/USD", &bids, &asks);
+
+        // Detect + execute over the private socket
+        let mut stats = hft_loop.get_stats().await;
+        for _ in 0..200 {
+            if stats.trades_executed > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+            stats = hft_loop.get_stats().await;
+        }
+        hft_loop.stop();
+        ws.stop().await;
+
+        assert!(stats.trades_executed >= 1, "no trade executed: {:?}", stats);
+        assert_eq!(stats.trades_successful, stats.trades_executed);
+        assert!(stats.total_profit > 2.0 * stats.trades_executed as f64 - 1e-6);
+        let snapshot = hft_loop.snapshot().await;
+        assert_eq!(snapshot.config.trade_amount, 100.0);
+        assert!(snapshot.stats.trades_executed >= stats.trades_executed);
+        let orders = mock.orders();
+        let legs: Vec<(&str, &str)> = orders.iter().take(3).map(|o| (o.symbol.as_str(), o.side.as_str())).collect();
+        assert_eq!(legs, vec![("BTC/USD", "buy"), ("ETH/BTC", "buy"), ("ETH/USD", "sell")]);
+        assert_eq!(orders[0].requested, 100.0);
+        assert!((orders[1].requested - orders[0].filled_qty).abs() < 1e-12);
+        assert!((orders[2].requested - orders[1].filled_qty).abs() < 1e-12);
+        assert_eq!(orders[2].price, 2_560.0);
+        assert!(mock.token_requests() >= 1);
+
+        // Record
+        if check_rows {
+            let trades = db.get_trades(10, Some("COMPLETED"), 1).await.unwrap();
+            let trade = trades.iter().find(|t| t.path == "USD → BTC → ETH → USD").expect("trade row");
+            assert_eq!(trade.amount_in, 100.0);
+            assert!(trade.profit_loss.unwrap() > 2.0);
+        }
+
+        // A rejection on the private socket fails the leg with Kraken's error
+        let manual = ExecutionEngine::new(auth, Arc::clone(&cache));
+        manual.connect().await.unwrap();
+        mock.reject_next("EOrder:Insufficient funds");
+        let opp = Opportunity {
+            strategy: Strategy::Manual,
+            ..Opportunity::for_test("USD → BTC → ETH → USD", 0.0)
+        };
+        let result = manual.execute_opportunity(&opp, 50.0).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Insufficient funds"));
+        assert_eq!(mock.orders().len(), orders.len());
+    }
+}
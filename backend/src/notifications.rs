@@ -0,0 +1,86 @@
+//! Operator Notifications
+//!
+//! Short in-memory feed of things an operator should look at (e.g. orders
+//! the exchange and the engine disagree about). Every notification is also
+//! logged at its severity. The newest `MAX_NOTIFICATIONS` are kept and
+//! served by GET /api/notifications.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{error, info, warn};
+
+/// Notifications kept (oldest dropped first)
+const MAX_NOTIFICATIONS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub id: u64,
+    pub at: DateTime<Utc>,
+    pub severity: Severity,
+    /// Component that raised it, e.g. "reconciliation"
+    pub source: &'static str,
+    pub title: String,
+    pub details: Value,
+}
+
+pub struct Notifications {
+    entries: RwLock<VecDeque<Notification>>,
+    next_id: AtomicU64,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Raise a notification; returns its id
+    pub fn push(&self, severity: Severity, source: &'static str, title: String, details: Value) -> u64 {
+        match severity {
+            Severity::Info => info!("[{}] {}", source, title),
+            Severity::Warning => warn!("[{}] {}", source, title),
+            Severity::Critical => error!("[{}] {}", source, title),
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut entries = self.entries.write();
+        if entries.len() == MAX_NOTIFICATIONS {
+            entries.pop_front();
+        }
+        entries.push_back(Notification { id, at: Utc::now(), severity, source, title, details });
+        id
+    }
+
+    /// Newest first, only those after `since_id` when given
+    pub fn recent(&self, since_id: Option<u64>, limit: usize) -> Vec<Notification> {
+        self.entries
+            .read()
+            .iter()
+            .rev()
+            .filter(|n| since_id.is_none_or(|since| n.id > since))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,166 @@
+//! Traded Notional Limits
+//!
+//! Caps how much the HFT loop may trade per rolling hour and rolling 24
+//! hours, independent of P&L. Notional is accounted from leg fills: every
+//! filled leg adds the USD value of what it spent, so a $100 three-leg
+//! cycle counts as about $300. Before executing, a cycle's projected
+//! notional (USD size × legs) must fit in both windows or it is skipped.
+//!
+//! Limits come from live_trading_config (max_notional_per_hour,
+//! max_notional_per_day; NULL = no limit). Headroom is served with the
+//! circuit-breaker state.
+#![allow(dead_code)]
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const HOUR: Duration = Duration::from_secs(3_600);
+const DAY: Duration = Duration::from_secs(86_400);
+
+/// USD limits per window (None = unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct NotionalLimits {
+    pub per_hour: Option<f64>,
+    pub per_day: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotionalWindow {
+    Hour,
+    Day,
+}
+
+impl NotionalWindow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotionalWindow::Hour => "hour",
+            NotionalWindow::Day => "day",
+        }
+    }
+
+    fn length(&self) -> Duration {
+        match self {
+            NotionalWindow::Hour => HOUR,
+            NotionalWindow::Day => DAY,
+        }
+    }
+}
+
+/// A trade that doesn't fit in a window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotionalBlock {
+    pub window: NotionalWindow,
+    pub used: f64,
+    pub limit: f64,
+    pub projected: f64,
+}
+
+/// Usage of one window
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowHeadroom {
+    pub limit: Option<f64>,
+    pub used: f64,
+    /// None when unlimited
+    pub remaining: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotionalHeadroom {
+    pub hour: WindowHeadroom,
+    pub day: WindowHeadroom,
+}
+
+/// Filled leg notionals over the last 24 hours
+#[derive(Default)]
+pub struct NotionalTracker {
+    fills: Mutex<VecDeque<(Instant, f64)>>,
+}
+
+impl NotionalTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Account one filled leg
+    pub fn record(&self, usd: f64, at: Instant) {
+        if usd > 0.0 && usd.is_finite() {
+            self.fills.lock().push_back((at, usd));
+        }
+    }
+
+    /// USD filled within `window` before `now`
+    pub fn used(&self, window: NotionalWindow, now: Instant) -> f64 {
+        let mut fills = self.fills.lock();
+        while fills.front().is_some_and(|(at, _)| now.saturating_duration_since(*at) >= DAY) {
+            fills.pop_front();
+        }
+        fills
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) < window.length())
+            .map(|(_, usd)| usd)
+            .sum()
+    }
+
+    /// Err with the first window `projected` more USD would overrun
+    pub fn check(&self, limits: &NotionalLimits, projected: f64, now: Instant) -> Result<(), NotionalBlock> {
+        for (window, limit) in [(NotionalWindow::Hour, limits.per_hour), (NotionalWindow::Day, limits.per_day)] {
+            let Some(limit) = limit else { continue };
+            let used = self.used(window, now);
+            if used + projected > limit {
+                return Err(NotionalBlock { window, used, limit, projected });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn headroom(&self, limits: &NotionalLimits, now: Instant) -> NotionalHeadroom {
+        let window = |window: NotionalWindow, limit: Option<f64>| {
+            let used = self.used(window, now);
+            WindowHeadroom { limit, used, remaining: limit.map(|l| (l - used).max(0.0)) }
+        };
+        NotionalHeadroom {
+            hour: window(NotionalWindow::Hour, limits.per_hour),
+            day: window(NotionalWindow::Day, limits.per_day),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_hour_and_day_limits() {
+        let tracker = NotionalTracker::new();
+        let limits = NotionalLimits { per_hour: Some(1_000.0), per_day: Some(1_500.0) };
+        let start = Instant::now();
+
+        // Three legs of a $300 cycle
+        for _ in 0..3 {
+            tracker.record(300.0, start);
+        }
+        assert!(tracker.check(&limits, 100.0, start).is_ok());
+        let block = tracker.check(&limits, 300.0, start).unwrap_err();
+        assert_eq!((block.window, block.used, block.limit), (NotionalWindow::Hour, 900.0, 1_000.0));
+
+        // An hour later the hour window is clear, the day still counts it
+        let later = start + HOUR;
+        tracker.record(500.0, later);
+        assert_eq!(tracker.used(NotionalWindow::Hour, later), 500.0);
+        let block = tracker.check(&limits, 200.0, later).unwrap_err();
+        assert_eq!((block.window, block.used), (NotionalWindow::Day, 1_400.0));
+
+        let headroom = tracker.headroom(&limits, later);
+        assert_eq!(headroom.hour.remaining, Some(500.0));
+        assert_eq!(headroom.day.remaining, Some(100.0));
+
+        // After a day only the later fill is left; no limits never block
+        let next_day = start + DAY;
+        assert_eq!(tracker.used(NotionalWindow::Day, next_day), 500.0);
+        assert!(tracker.check(&NotionalLimits::default(), 1e9, next_day).is_ok());
+        assert!(tracker.headroom(&NotionalLimits::default(), next_day).hour.remaining.is_none());
+    }
+}
@@ -0,0 +1,333 @@
+//! Recently Found Opportunities
+//!
+//! The HFT loop executes (or skips) an opportunity within the same cycle, so
+//! nothing scanned stays around on its own. This cache keeps the latest ones
+//! for the dashboard together with the mid price of every leg's pair at
+//! detection time.
+//!
+//! An entry stays current only while its books agree with what it was
+//! computed from: once any leg's mid has moved more than `max_move_bps` (or
+//! the pair lost its quote) the entry is invalidated and dropped on the next
+//! read, instead of lingering until the next scan replaces it.
+//!
+//! Only finds passing the `OpportunityFilter` are cached.
+//!
+//! For consumers polling the set at high frequency the current entries can
+//! also be packed as fixed-width little-endian records (`pack_records`),
+//! laid out as described by `RECORD_FIELDS` so they load with a single
+//! `numpy.frombuffer(body, dtype=...)` instead of parsing JSON objects.
+#![allow(dead_code)]
+
+use crate::opportunity_filter::OpportunityFilter;
+use crate::order_book::OrderBookCache;
+use crate::types::Opportunity;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default mid move (bps) on any leg that invalidates an opportunity
+pub const DEFAULT_INVALIDATE_MOVE_BPS: f64 = 10.0;
+
+/// Entries kept (oldest dropped first)
+const MAX_CACHED: usize = 50;
+
+/// Bytes of the path field (longer paths are truncated at a character)
+pub const RECORD_PATH_BYTES: usize = 96;
+
+/// Bytes of the strategy field
+pub const RECORD_STRATEGY_BYTES: usize = 12;
+
+/// Packed record layout as numpy (name, format) pairs, in order.
+/// Strings are UTF-8, NUL-padded.
+pub const RECORD_FIELDS: [(&str, &str); 12] = [
+    ("detected_at_ms", "<i8"),
+    ("age_ms", "<i8"),
+    ("gross_profit_pct", "<f8"),
+    ("fees_pct", "<f8"),
+    ("net_profit_pct", "<f8"),
+    ("fee_rate", "<f8"),
+    ("max_move_bps", "<f8"),
+    ("legs", "<u2"),
+    ("legs_updated", "<u2"),
+    ("is_profitable", "|b1"),
+    ("strategy", "|S12"),
+    ("path", "|S96"),
+];
+
+/// Bytes per packed record
+pub const RECORD_SIZE: usize = 7 * 8 + 2 * 2 + 1 + RECORD_STRATEGY_BYTES + RECORD_PATH_BYTES;
+
+/// Price of one leg's pair when the opportunity was found
+#[derive(Debug, Clone)]
+struct LegReference {
+    pair: String,
+    mid: f64,
+    last_update: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedOpportunity {
+    opportunity: Opportunity,
+    legs: Vec<LegReference>,
+}
+
+/// A cached opportunity with how far its books have drifted since detection
+#[derive(Debug, Clone, Serialize)]
+pub struct OpportunityWithAge {
+    #[serde(flatten)]
+    pub opportunity: Opportunity,
+    pub age_ms: i64,
+    /// Largest mid move across the legs since detection
+    pub max_move_bps: f64,
+    /// Legs whose book has updated since detection
+    pub legs_updated: usize,
+}
+
+pub struct OpportunityCache {
+    max_move_bps: f64,
+    entries: RwLock<VecDeque<CachedOpportunity>>,
+    invalidated: AtomicU64,
+    filter: OpportunityFilter,
+}
+
+impl OpportunityCache {
+    pub fn new(max_move_bps: f64) -> Self {
+        Self {
+            max_move_bps,
+            entries: RwLock::new(VecDeque::new()),
+            invalidated: AtomicU64::new(0),
+            filter: OpportunityFilter::default(),
+        }
+    }
+
+    /// Create from OPPORTUNITY_INVALIDATE_BPS and OPPORTUNITY_FILTER_*
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("OPPORTUNITY_INVALIDATE_BPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_INVALIDATE_MOVE_BPS),
+        )
+        .with_filter(OpportunityFilter::from_env())
+    }
+
+    pub fn with_filter(mut self, filter: OpportunityFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// What gets cached (and sent to the webhook)
+    pub fn filter(&self) -> &OpportunityFilter {
+        &self.filter
+    }
+
+    /// Remember an opportunity with its legs' current prices, unless the
+    /// filter keeps it out. A newer opportunity on the same path replaces
+    /// the old one.
+    pub fn insert(&self, opportunity: &Opportunity, cache: &OrderBookCache) {
+        if !self.filter.admit(opportunity) {
+            return;
+        }
+        let legs = opportunity.legs_detail
+            .iter()
+            .filter_map(|leg| {
+                let edge = cache.get_price(&leg.pair)?;
+                Some(LegReference {
+                    pair: leg.pair.clone(),
+                    mid: (edge.bid + edge.ask) / 2.0,
+                    last_update: edge.last_update,
+                })
+            })
+            .collect();
+
+        let mut entries = self.entries.write();
+        entries.retain(|e| e.opportunity.path != opportunity.path);
+        if entries.len() == MAX_CACHED {
+            entries.pop_front();
+        }
+        entries.push_back(CachedOpportunity { opportunity: opportunity.clone(), legs });
+    }
+
+    /// Current opportunities (newest first) with their freshness.
+    /// Entries whose books moved past the threshold are dropped.
+    pub fn get_with_age(&self, cache: &OrderBookCache) -> Vec<OpportunityWithAge> {
+        let mut fresh = Vec::new();
+        let mut entries = self.entries.write();
+        let before = entries.len();
+
+        entries.retain(|entry| {
+            let mut max_move_bps: f64 = 0.0;
+            let mut legs_updated = 0;
+            for leg in &entry.legs {
+                let edge = match cache.get_price(&leg.pair) {
+                    Some(e) if e.bid > 0.0 && e.ask > 0.0 => e,
+                    _ => return false,
+                };
+                let mid = (edge.bid + edge.ask) / 2.0;
+                max_move_bps = max_move_bps.max((mid / leg.mid - 1.0).abs() * 10_000.0);
+                if edge.last_update > leg.last_update {
+                    legs_updated += 1;
+                }
+            }
+            if max_move_bps > self.max_move_bps {
+                return false;
+            }
+            fresh.push(OpportunityWithAge {
+                age_ms: entry.opportunity.age_ms(),
+                opportunity: entry.opportunity.clone(),
+                max_move_bps,
+                legs_updated,
+            });
+            true
+        });
+
+        self.invalidated.fetch_add((before - entries.len()) as u64, Ordering::Relaxed);
+        fresh.reverse();
+        fresh
+    }
+
+    /// Entries held (ones invalidated since the last read included)
+    pub fn len(&self) -> usize {
+        self.entries.read().len()
+    }
+
+    /// Opportunities invalidated by book moves so far
+    pub fn invalidated(&self) -> u64 {
+        self.invalidated.load(Ordering::Relaxed)
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().clear();
+    }
+}
+
+/// Entries as consecutive `RECORD_FIELDS` records, in the given order
+pub fn pack_records(entries: &[OpportunityWithAge]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(entries.len() * RECORD_SIZE);
+    for entry in entries {
+        let opp = &entry.opportunity;
+        out.extend_from_slice(&opp.detected_at.timestamp_millis().to_le_bytes());
+        out.extend_from_slice(&entry.age_ms.to_le_bytes());
+        for value in [opp.gross_profit_pct, opp.fees_pct, opp.net_profit_pct, opp.fee_rate, entry.max_move_bps] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out.extend_from_slice(&(opp.legs.min(u16::MAX as usize) as u16).to_le_bytes());
+        out.extend_from_slice(&(entry.legs_updated.min(u16::MAX as usize) as u16).to_le_bytes());
+        out.push(opp.is_profitable as u8);
+        push_fixed(&mut out, opp.strategy.as_str(), RECORD_STRATEGY_BYTES);
+        push_fixed(&mut out, &opp.path, RECORD_PATH_BYTES);
+    }
+    out
+}
+
+/// `text` NUL-padded to `width` bytes, cut at the last whole character
+fn push_fixed(out: &mut Vec<u8>, text: &str, width: usize) {
+    let mut end = text.len().min(width);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    out.extend_from_slice(&text.as_bytes()[..end]);
+    out.resize(out.len() + width - end, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::PairInfo;
+    use crate::types::{LegDetail, OrderBookLevel, Strategy};
+
+    fn quote(cache: &OrderBookCache, pair: &str, mid: f64) {
+        cache.update_snapshot(
+            pair,
+            vec![OrderBookLevel { price: mid * 0.9999, qty: 1.0 }],
+            vec![OrderBookLevel { price: mid * 1.0001, qty: 1.0 }],
+            1,
+        );
+    }
+
+    #[test]
+    fn test_invalidated_when_leg_moves() {
+        let cache = OrderBookCache::new();
+        for (base, quote_ccy) in [("BTC", "USD"), ("ETH", "BTC"), ("ETH", "USD")] {
+            let pair = format!("{}/{}", base, quote_ccy);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote_ccy.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair,
+                volume_24h: 0.0,
+            });
+        }
+        quote(&cache, "BTC/USD", 50_000.0);
+        quote(&cache, "ETH/BTC", 0.05);
+        quote(&cache, "ETH/USD", 2_510.0);
+
+        let opp = Opportunity {
+            legs_detail: ["BTC/USD", "ETH/BTC", "ETH/USD"].iter().map(|p| LegDetail {
+                pair: p.to_string(),
+                action: "buy".to_string(),
+                rate: 1.0,
+            }).collect(),
+            ..Opportunity::for_test("USD → BTC → ETH → USD", 0.1)
+        };
+
+        let opportunities = OpportunityCache::new(10.0);
+        opportunities.insert(&opp, &cache);
+
+        // 5 bps move: still current, leg marked as updated
+        quote(&cache, "ETH/USD", 2_510.0 * 1.0005);
+        let current = opportunities.get_with_age(&cache);
+        assert_eq!(current.len(), 1);
+        assert!((current[0].max_move_bps - 5.0).abs() < 0.01);
+
+        // 20 bps move: invalidated
+        quote(&cache, "BTC/USD", 50_000.0 * 1.002);
+        assert!(opportunities.get_with_age(&cache).is_empty());
+        assert_eq!(opportunities.invalidated(), 1);
+    }
+
+    #[test]
+    fn test_packed_records_layout() {
+        let long_path = vec!["USDT"; 30].join(" → ");
+        let entries: Vec<OpportunityWithAge> = [("USD → BTC → ETH → USD", 0.12), (long_path.as_str(), -0.5)]
+            .iter()
+            .map(|(path, net)| OpportunityWithAge {
+                opportunity: Opportunity {
+                    detected_at: DateTime::from_timestamp_millis(1_700_000_000_123).unwrap(),
+                    strategy: Strategy::CrossPair,
+                    ..Opportunity::for_test(path, *net)
+                },
+                age_ms: 42,
+                max_move_bps: 1.5,
+                legs_updated: 2,
+            })
+            .collect();
+
+        let bytes = pack_records(&entries);
+        assert_eq!(bytes.len(), 2 * RECORD_SIZE);
+        let widths: usize = RECORD_FIELDS
+            .iter()
+            .map(|(_, format)| format[2..].parse::<usize>().unwrap())
+            .sum();
+        assert_eq!(widths, RECORD_SIZE);
+
+        let first = &bytes[..RECORD_SIZE];
+        assert_eq!(i64::from_le_bytes(first[0..8].try_into().unwrap()), 1_700_000_000_123);
+        assert_eq!(i64::from_le_bytes(first[8..16].try_into().unwrap()), 42);
+        assert_eq!(f64::from_le_bytes(first[32..40].try_into().unwrap()), 0.12);
+        assert_eq!(u16::from_le_bytes(first[58..60].try_into().unwrap()), 2);
+        assert_eq!(first[60], 1);
+        assert_eq!(&first[61..71], b"cross_pair");
+        assert_eq!(first[71..73], [0, 0]);
+        assert!(first[73..].starts_with("USD → BTC".as_bytes()));
+
+        // Cut before the arrow that would straddle the field end
+        let path = &bytes[RECORD_SIZE + 73..2 * RECORD_SIZE];
+        let text = std::str::from_utf8(path).unwrap().trim_end_matches('\0');
+        assert!(long_path.starts_with(text) && text.len() <= RECORD_PATH_BYTES);
+        assert_eq!(bytes[RECORD_SIZE + 60], 0);
+    }
+}
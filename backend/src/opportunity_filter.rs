@@ -0,0 +1,188 @@
+//! Surfaced Opportunity Filter
+//!
+//! The loop can find thousands of marginal paths a minute, and every one of
+//! them used to land in the cached set and go out on the webhook, so a
+//! client polling `/api/opportunities` or listening on the webhook paid for
+//! deserializing paths it throws away. This filter narrows what's surfaced:
+//! - min_net_profit_pct: net profit (percent) an opportunity must reach
+//! - base_currencies: currencies a path may start from (empty = any)
+//! - max_legs: longest path surfaced (None = any)
+//!
+//! Only surfacing is filtered. Execution, guards and plugins still see every
+//! find. Set from OPPORTUNITY_FILTER_* and replaced at runtime through
+//! PUT /api/opportunities/filter.
+#![allow(dead_code)]
+
+use crate::types::Opportunity;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilterConfig {
+    #[serde(default)]
+    pub min_net_profit_pct: Option<f64>,
+    #[serde(default)]
+    pub base_currencies: Vec<String>,
+    #[serde(default)]
+    pub max_legs: Option<usize>,
+}
+
+impl FilterConfig {
+    /// Create from OPPORTUNITY_FILTER_MIN_PROFIT_PCT, OPPORTUNITY_FILTER_BASES
+    /// (comma-separated) and OPPORTUNITY_FILTER_MAX_LEGS
+    pub fn from_env() -> Self {
+        let config = Self {
+            min_net_profit_pct: std::env::var("OPPORTUNITY_FILTER_MIN_PROFIT_PCT").ok().and_then(|v| v.parse().ok()),
+            base_currencies: std::env::var("OPPORTUNITY_FILTER_BASES")
+                .map(|v| v.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            max_legs: std::env::var("OPPORTUNITY_FILTER_MAX_LEGS").ok().and_then(|v| v.parse().ok()),
+        }
+        .normalized();
+        match config.validate() {
+            Ok(()) => config,
+            Err(e) => {
+                tracing::warn!("Ignoring OPPORTUNITY_FILTER_*: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Currencies upper-cased and trimmed, blanks dropped
+    pub fn normalized(mut self) -> Self {
+        self.base_currencies = self
+            .base_currencies
+            .iter()
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty())
+            .collect();
+        self.base_currencies.dedup();
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(min) = self.min_net_profit_pct {
+            if !min.is_finite() {
+                return Err(format!("min_net_profit_pct must be a number, got {}", min));
+            }
+        }
+        if let Some(legs) = self.max_legs {
+            if legs < 2 {
+                return Err(format!("max_legs must be at least 2, got {}", legs));
+            }
+        }
+        Ok(())
+    }
+
+    /// Does `opportunity` pass every set condition
+    pub fn matches(&self, opportunity: &Opportunity) -> bool {
+        if self.min_net_profit_pct.is_some_and(|min| opportunity.net_profit_pct < min) {
+            return false;
+        }
+        if self.max_legs.is_some_and(|max| opportunity.legs > max) {
+            return false;
+        }
+        if !self.base_currencies.is_empty() {
+            let start = opportunity.path.split(" → ").next().unwrap_or_default();
+            if !self.base_currencies.iter().any(|c| c == start) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterStatus {
+    pub config: FilterConfig,
+    /// Finds surfaced since the process started
+    pub passed: u64,
+    /// Finds kept out of the cached set
+    pub filtered: u64,
+}
+
+pub struct OpportunityFilter {
+    config: RwLock<FilterConfig>,
+    passed: AtomicU64,
+    filtered: AtomicU64,
+}
+
+impl OpportunityFilter {
+    pub fn new(config: FilterConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            passed: AtomicU64::new(0),
+            filtered: AtomicU64::new(0),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(FilterConfig::from_env())
+    }
+
+    /// Check `opportunity` and count the outcome
+    pub fn admit(&self, opportunity: &Opportunity) -> bool {
+        let pass = self.matches(opportunity);
+        let counter = if pass { &self.passed } else { &self.filtered };
+        counter.fetch_add(1, Ordering::Relaxed);
+        pass
+    }
+
+    /// Check `opportunity` without counting it
+    pub fn matches(&self, opportunity: &Opportunity) -> bool {
+        self.config.read().matches(opportunity)
+    }
+
+    pub fn set(&self, config: FilterConfig) -> Result<FilterConfig, String> {
+        let config = config.normalized();
+        config.validate()?;
+        *self.config.write() = config.clone();
+        Ok(config)
+    }
+
+    pub fn status(&self) -> FilterStatus {
+        FilterStatus {
+            config: self.config.read().clone(),
+            passed: self.passed.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for OpportunityFilter {
+    fn default() -> Self {
+        Self::new(FilterConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_conditions() {
+        let filter = OpportunityFilter::default();
+        let usd = Opportunity::for_test("USD → BTC → ETH → USD", 0.05);
+        let eur = Opportunity::for_test("EUR → BTC → SOL → ETH → EUR", 0.4);
+        assert!(filter.admit(&usd) && filter.admit(&eur));
+
+        let config = FilterConfig {
+            min_net_profit_pct: Some(0.1),
+            base_currencies: vec![" eur".to_string(), "".to_string()],
+            max_legs: None,
+        };
+        assert_eq!(filter.set(config).unwrap().base_currencies, vec!["EUR"]);
+        assert!(!filter.admit(&usd));
+        assert!(filter.admit(&eur));
+
+        let config = FilterConfig { max_legs: Some(3), ..filter.status().config };
+        filter.set(config).unwrap();
+        assert!(!filter.admit(&eur));
+        assert!(filter.set(FilterConfig { max_legs: Some(1), ..Default::default() }).is_err());
+
+        let status = filter.status();
+        assert_eq!((status.passed, status.filtered), (3, 2));
+        assert_eq!(status.config.max_legs, Some(3));
+    }
+}
@@ -1,12 +1,34 @@
 //! In-memory order book cache with lock-free reads
+//!
+//! Readers get a copy of a book. With the level pool enabled
+//! (BOOK_LEVEL_POOL=true) the scanner's copies come from a pool of
+//! recycled books whose level buffers are reused, and snapshots are copied
+//! into the existing buffers with headroom so deltas rarely regrow them.
+//! Allocation counters are kept either way so the two modes can be compared.
+//!
+//! Each pair also carries top-of-book pressure signals (depth imbalance,
+//! microprice and a decayed order flow imbalance) for execution timing, and
+//! a rolling quality score (spread, update rate, gaps) over the last hour.
+//!
+//! The cache is held to a budget (BOOK_CACHE_MAX_BOOKS, BOOK_CACHE_MAX_MB).
+//! A pair dropped from the subscription is released: its levels are freed
+//! at once, and when the cache is over budget the least recently released
+//! books are evicted outright. Registration is kept, so an evicted pair can
+//! be subscribed again. Subscribed pairs are never evicted.
 #![allow(dead_code)]
 
-use crate::types::{OrderBook, OrderBookLevel, PriceEdge};
+use crate::pair_quality::{PairQuality, QualityTracker};
+use crate::pair_registry::{PairMeta, PairRegistry, PairRoute};
+use crate::types::{BookDelta, CacheMemorySnapshot, OrderBook, OrderBookLevel, PriceEdge};
 use chrono::Utc;
 use dashmap::DashMap;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Thread-safe order book cache
 pub struct OrderBookCache {
@@ -21,9 +43,59 @@ pub struct OrderBookCache {
     
     /// Pair info mapping
     pair_info: DashMap<String, PairInfo>,
+
+    /// AssetPairs metadata, indexed by currency pair
+    registry: PairRegistry,
+
+    /// Pairs excluded from scanning by the consistency monitor (pair -> reason)
+    quarantined: DashMap<String, String>,
+
+    /// Pairs whose mid strays from an external index (pair -> deviation %)
+    anomalous: DashMap<String, f64>,
+
+    /// Currencies no cycle may pass through (from the restrictions config;
+    /// kept across clear() like the config it mirrors)
+    blocked_currencies: RwLock<HashSet<String>>,
+
+    /// Bumped whenever blocked_currencies changes, so graphs know to rebuild
+    restrictions_version: AtomicU64,
+
+    /// Bumped whenever a pair is added or removed, so graphs know to rebuild
+    topology_version: AtomicU64,
+
+    /// Books bootstrapped over REST, waiting for their first WebSocket
+    /// snapshot (pair -> when fetched)
+    rest_sourced: DashMap<String, chrono::DateTime<Utc>>,
+
+    /// Order flow imbalance at the touch, updated on every delta
+    flow: DashMap<String, FlowState>,
+
+    /// Spread, update rate and gaps over the last hour
+    quality: QualityTracker,
     
     /// Statistics
     stats: Arc<RwLock<CacheStats>>,
+
+    /// Recycled book copies and allocation counters
+    pool: BookPool,
+
+    /// Books held and estimated bytes the cache may grow to
+    budget: CacheBudget,
+
+    /// Pairs dropped from the subscription, by when (eviction order)
+    released: DashMap<String, Instant>,
+
+    /// Books evicted to stay within the budget
+    evictions: AtomicU64,
+}
+
+/// Restricted currencies and the registered pairs they keep out of the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct RestrictedGraph {
+    pub currencies: Vec<String>,
+    pub pairs: Vec<String>,
+    /// Two per pair (buy and sell)
+    pub excluded_edges: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +108,80 @@ pub struct PairInfo {
     pub volume_24h: f64,
 }
 
+/// Levels per side summed for the depth imbalance
+pub const SIGNAL_DEPTH_LEVELS: usize = 5;
+
+/// Weight the order flow imbalance keeps on each update
+const FLOW_DECAY: f64 = 0.9;
+
+/// Top-of-book pressure signals for one pair
+#[derive(Debug, Clone, Serialize)]
+pub struct BookSignals {
+    pub pair: String,
+    pub mid: f64,
+    /// Size-weighted mid: leans toward the side with less resting size
+    pub microprice: f64,
+    /// (microprice - mid) / mid in basis points
+    pub microprice_offset_bps: f64,
+    /// (bid qty - ask qty) / (bid qty + ask qty) over the top levels, -1..1
+    pub depth_imbalance: f64,
+    /// Decayed order flow imbalance at the touch, normalized to -1..1
+    pub order_flow_imbalance: f64,
+}
+
+impl BookSignals {
+    /// Upward (+) or downward (-) price pressure, -1..1
+    pub fn pressure(&self) -> f64 {
+        (self.depth_imbalance + self.order_flow_imbalance) / 2.0
+    }
+}
+
+/// Decayed sum of touch order flow events and of their magnitudes
+#[derive(Debug, Default, Clone, Copy)]
+struct FlowState {
+    net: f64,
+    gross: f64,
+}
+
+impl FlowState {
+    fn record(&mut self, event: f64) {
+        self.net = self.net * FLOW_DECAY + event;
+        self.gross = self.gross * FLOW_DECAY + event.abs();
+    }
+
+    fn normalized(&self) -> f64 {
+        if self.gross > 0.0 { (self.net / self.gross).clamp(-1.0, 1.0) } else { 0.0 }
+    }
+}
+
+/// Touch spread in bps of mid (None unless both sides are quoted)
+fn spread_bps(book: &OrderBook) -> Option<f64> {
+    let (bid, ask) = (book.best_bid()?, book.best_ask()?);
+    let mid = (bid + ask) / 2.0;
+    (mid > 0.0).then(|| (ask - bid) / mid * 10_000.0)
+}
+
+/// Order flow contribution of one side's best level changing from
+/// `before` to `after` (price, qty). Positive = more buying pressure on
+/// bids or more selling pressure on asks.
+fn touch_flow(before: Option<(f64, f64)>, after: Option<(f64, f64)>, is_bid: bool) -> f64 {
+    match (before, after) {
+        (Some((old_price, old_qty)), Some((new_price, new_qty))) => {
+            let improved = if is_bid { new_price > old_price } else { new_price < old_price };
+            if improved {
+                new_qty
+            } else if new_price == old_price {
+                new_qty - old_qty
+            } else {
+                -old_qty
+            }
+        }
+        (None, Some((_, new_qty))) => new_qty,
+        (Some((_, old_qty)), None) => -old_qty,
+        (None, None) => 0.0,
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct CacheStats {
     pub updates_received: u64,
@@ -43,6 +189,201 @@ pub struct CacheStats {
     pub last_update: Option<chrono::DateTime<Utc>>,
 }
 
+/// Cache size summary from get_stats()
+#[derive(Debug, Clone)]
+pub struct CacheUsage {
+    pub pairs: usize,
+    pub currencies: usize,
+    pub avg_staleness_ms: f64,
+    pub memory: CacheMemorySnapshot,
+}
+
+/// How large the cache may grow before released books are evicted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheBudget {
+    /// Books held at most (0 = unbounded)
+    pub max_books: usize,
+    /// Estimated bytes at most (0 = unbounded)
+    pub max_bytes: usize,
+}
+
+impl Default for CacheBudget {
+    fn default() -> Self {
+        Self {
+            max_books: 600,
+            max_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+impl CacheBudget {
+    /// Create from BOOK_CACHE_MAX_BOOKS and BOOK_CACHE_MAX_MB
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let parse = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<usize>().ok());
+        Self {
+            max_books: parse("BOOK_CACHE_MAX_BOOKS").unwrap_or(defaults.max_books),
+            max_bytes: parse("BOOK_CACHE_MAX_MB").map_or(defaults.max_bytes, |mb| mb * 1024 * 1024),
+        }
+    }
+}
+
+/// Level buffer reuse settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelPoolPolicy {
+    pub enabled: bool,
+    /// Idle book copies kept for reuse
+    pub max_pooled: usize,
+    /// Spare levels reserved per side when a snapshot lands
+    pub headroom: usize,
+}
+
+impl Default for LevelPoolPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_pooled: 256,
+            headroom: 16,
+        }
+    }
+}
+
+impl LevelPoolPolicy {
+    /// Create from BOOK_LEVEL_POOL, BOOK_POOL_SIZE and BOOK_LEVEL_HEADROOM
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let parse = |key: &str, default: usize| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            enabled: std::env::var("BOOK_LEVEL_POOL").map(|v| v == "true").unwrap_or(defaults.enabled),
+            max_pooled: parse("BOOK_POOL_SIZE", defaults.max_pooled),
+            headroom: parse("BOOK_LEVEL_HEADROOM", defaults.headroom),
+        }
+    }
+}
+
+/// Allocation counters for book copies and level buffers
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocationStats {
+    pub pooled: bool,
+    /// Reader copies that needed new buffers
+    pub fresh_copies: u64,
+    /// Reader copies served from a recycled book without reallocating
+    pub reused_copies: u64,
+    /// Times a cached book's level buffer had to grow
+    pub level_growths: u64,
+    /// Snapshots that swapped in new buffers (freeing the old ones)
+    pub buffers_replaced: u64,
+    /// Recycled copies waiting for reuse
+    pub idle_copies: usize,
+}
+
+#[derive(Default)]
+struct AllocationCounters {
+    fresh_copies: AtomicU64,
+    reused_copies: AtomicU64,
+    level_growths: AtomicU64,
+    buffers_replaced: AtomicU64,
+}
+
+struct BookPool {
+    policy: LevelPoolPolicy,
+    idle: Mutex<Vec<OrderBook>>,
+    counters: AllocationCounters,
+}
+
+impl BookPool {
+    fn new(policy: LevelPoolPolicy) -> Self {
+        Self {
+            policy,
+            idle: Mutex::new(Vec::new()),
+            counters: AllocationCounters::default(),
+        }
+    }
+
+    /// Copy `source`, into a recycled book when one is idle
+    fn copy(&self, source: &OrderBook) -> OrderBook {
+        let shell = if self.policy.enabled { self.idle.lock().pop() } else { None };
+        let Some(mut book) = shell else {
+            self.counters.fresh_copies.fetch_add(1, Ordering::Relaxed);
+            return source.clone();
+        };
+
+        let capacity = (book.pair.capacity(), book.bids.capacity(), book.asks.capacity());
+        book.pair.clone_from(&source.pair);
+        book.bids.clone_from(&source.bids);
+        book.asks.clone_from(&source.asks);
+        book.sequence = source.sequence;
+        book.last_update = source.last_update;
+
+        let counter = if (book.pair.capacity(), book.bids.capacity(), book.asks.capacity()) == capacity {
+            &self.counters.reused_copies
+        } else {
+            &self.counters.fresh_copies
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        book
+    }
+
+    fn release(&self, book: OrderBook) {
+        if self.policy.enabled {
+            let mut idle = self.idle.lock();
+            if idle.len() < self.policy.max_pooled {
+                idle.push(book);
+            }
+        }
+    }
+
+    /// Replace a side's levels, reusing its buffer when pooling
+    fn fill_levels(&self, levels: &mut Vec<OrderBookLevel>, incoming: Vec<OrderBookLevel>) {
+        if !self.policy.enabled {
+            *levels = incoming;
+            self.counters.buffers_replaced.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        levels.clear();
+        if levels.capacity() < incoming.len() {
+            levels.reserve(incoming.len() + self.policy.headroom);
+            self.counters.level_growths.fetch_add(1, Ordering::Relaxed);
+        }
+        levels.extend_from_slice(&incoming);
+    }
+
+    fn stats(&self) -> AllocationStats {
+        AllocationStats {
+            pooled: self.policy.enabled,
+            fresh_copies: self.counters.fresh_copies.load(Ordering::Relaxed),
+            reused_copies: self.counters.reused_copies.load(Ordering::Relaxed),
+            level_growths: self.counters.level_growths.load(Ordering::Relaxed),
+            buffers_replaced: self.counters.buffers_replaced.load(Ordering::Relaxed),
+            idle_copies: self.idle.lock().len(),
+        }
+    }
+}
+
+/// A book copy that goes back to the pool when dropped
+pub struct PooledBook<'a> {
+    book: Option<OrderBook>,
+    pool: &'a BookPool,
+}
+
+impl Deref for PooledBook<'_> {
+    type Target = OrderBook;
+
+    fn deref(&self) -> &OrderBook {
+        self.book.as_ref().expect("book is only taken on drop")
+    }
+}
+
+impl Drop for PooledBook<'_> {
+    fn drop(&mut self) {
+        if let Some(book) = self.book.take() {
+            self.pool.release(book);
+        }
+    }
+}
+
 impl OrderBookCache {
     pub fn new() -> Self {
         Self {
@@ -50,10 +391,35 @@ impl OrderBookCache {
             prices: DashMap::new(),
             currencies: DashMap::new(),
             pair_info: DashMap::new(),
+            registry: PairRegistry::new(),
+            quarantined: DashMap::new(),
+            anomalous: DashMap::new(),
+            blocked_currencies: RwLock::new(HashSet::new()),
+            restrictions_version: AtomicU64::new(0),
+            topology_version: AtomicU64::new(0),
+            rest_sourced: DashMap::new(),
+            flow: DashMap::new(),
+            quality: QualityTracker::default(),
             stats: Arc::new(RwLock::new(CacheStats::default())),
+            pool: BookPool::new(LevelPoolPolicy::default()),
+            budget: CacheBudget::default(),
+            released: DashMap::new(),
+            evictions: AtomicU64::new(0),
         }
     }
 
+    /// Reuse level buffers per `policy` (before the cache is shared)
+    pub fn with_level_pool(mut self, policy: LevelPoolPolicy) -> Self {
+        self.pool = BookPool::new(policy);
+        self
+    }
+
+    /// Hold the cache to `budget` (before the cache is shared)
+    pub fn with_budget(mut self, budget: CacheBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
     /// Register a trading pair
     pub fn register_pair(&self, info: PairInfo) {
         // Add currencies
@@ -67,8 +433,38 @@ impl OrderBookCache {
             Arc::new(RwLock::new(order_book)),
         );
         
-        // Store pair info
-        self.pair_info.insert(info.pair_name.clone(), info);
+        // Store pair info (keeping AssetPairs metadata registered beforehand)
+        self.registry.register_if_absent(PairMeta::from(&info));
+        self.released.remove(&info.pair_name);
+        if self.pair_info.insert(info.pair_name.clone(), info).is_none() {
+            self.topology_version.fetch_add(1, Ordering::SeqCst);
+        }
+        self.enforce_budget();
+    }
+
+    /// Remove a pair altogether (delisted): its book, price, metadata and
+    /// any currency no other pair trades. Returns whether it was registered.
+    pub fn unregister_pair(&self, pair: &str) -> bool {
+        let Some((_, info)) = self.pair_info.remove(pair) else {
+            return false;
+        };
+        self.order_books.remove(pair);
+        self.prices.remove(pair);
+        self.rest_sourced.remove(pair);
+        self.flow.remove(pair);
+        self.quality.remove(pair);
+        self.released.remove(pair);
+        self.quarantined.remove(pair);
+        self.anomalous.remove(pair);
+        self.registry.remove(pair);
+        for currency in [&info.base, &info.quote] {
+            let traded = self.pair_info.iter().any(|p| p.base == *currency || p.quote == *currency);
+            if !traded {
+                self.currencies.remove(currency);
+            }
+        }
+        self.topology_version.fetch_add(1, Ordering::SeqCst);
+        true
     }
 
     /// Update order book from WebSocket snapshot
@@ -81,48 +477,126 @@ impl OrderBookCache {
     ) {
         if let Some(book_ref) = self.order_books.get(pair) {
             let mut book = book_ref.write();
-            book.bids = bids;
-            book.asks = asks;
+            // A snapshot over a live book is a resync (one over a REST
+            // bootstrap is just the socket catching up)
+            let bootstrapped = self.rest_sourced.remove(pair).is_some();
+            if !bootstrapped && (!book.bids.is_empty() || !book.asks.is_empty()) {
+                self.quality.record_gap(pair, Instant::now());
+            }
+            self.pool.fill_levels(&mut book.bids, bids);
+            self.pool.fill_levels(&mut book.asks, asks);
             book.sequence = sequence;
             book.last_update = Utc::now();
+            self.quality.record_update(pair, spread_bps(&book), Instant::now());
             
             // Update price edge
             self.update_price_from_book(pair, &book);
         }
+        self.flow.remove(pair);
         
         let mut stats = self.stats.write();
         stats.snapshots_received += 1;
         stats.last_update = Some(Utc::now());
     }
 
+    /// Fill a book from a REST Depth snapshot while the socket hasn't sent
+    /// one. Returns false (and leaves the book alone) once it has.
+    pub fn update_rest_snapshot(&self, pair: &str, bids: Vec<OrderBookLevel>, asks: Vec<OrderBookLevel>) -> bool {
+        let Some(book_ref) = self.order_books.get(pair) else {
+            return false;
+        };
+        let mut book = book_ref.write();
+        if (!book.bids.is_empty() || !book.asks.is_empty()) && !self.rest_sourced.contains_key(pair) {
+            return false;
+        }
+        self.pool.fill_levels(&mut book.bids, bids);
+        self.pool.fill_levels(&mut book.asks, asks);
+        book.sequence = 0;
+        book.last_update = Utc::now();
+        self.rest_sourced.insert(pair.to_string(), book.last_update);
+        self.update_price_from_book(pair, &book);
+        true
+    }
+
+    /// Whether the pair's book still comes from the REST bootstrap
+    pub fn is_rest_sourced(&self, pair: &str) -> bool {
+        self.rest_sourced.contains_key(pair)
+    }
+
+    /// Pairs still served from REST, sorted
+    pub fn get_rest_sourced(&self) -> Vec<String> {
+        let mut pairs: Vec<String> = self.rest_sourced.iter().map(|e| e.key().clone()).collect();
+        pairs.sort();
+        pairs
+    }
+
     /// Update order book from WebSocket incremental update
+    /// Returns what changed (None for an unknown pair or an out-of-sequence update)
     pub fn update_incremental(
         &self,
         pair: &str,
         bid_updates: Vec<OrderBookLevel>,
         ask_updates: Vec<OrderBookLevel>,
         sequence: u64,
-    ) {
+    ) -> Option<BookDelta> {
+        let mut delta = None;
         if let Some(book_ref) = self.order_books.get(pair) {
             let mut book = book_ref.write();
             
             // Skip if out of sequence (but allow sequence=0 to always update)
             if sequence != 0 && sequence <= book.sequence {
-                return;
+                self.quality.record_gap(pair, Instant::now());
+                return None;
             }
             
+            let capacity = (book.bids.capacity(), book.asks.capacity());
+            let top = (book.best_bid(), book.best_ask());
+            let touch = |levels: &[OrderBookLevel]| levels.first().map(|l| (l.price, l.qty));
+            let touch_before = (touch(&book.bids), touch(&book.asks));
+            let mut changes = BookDelta {
+                pair: pair.to_string(),
+                snapshot: false,
+                bids_changed: false,
+                asks_changed: false,
+                levels_changed: 0,
+                shallowest_level: usize::MAX,
+                top_moved: false,
+            };
+
             // Apply bid updates
             for update in bid_updates {
-                Self::apply_level_update(&mut book.bids, update, true);
+                if let Some(idx) = Self::apply_level_update(&mut book.bids, update, true) {
+                    changes.bids_changed = true;
+                    changes.levels_changed += 1;
+                    changes.shallowest_level = changes.shallowest_level.min(idx);
+                }
             }
             
             // Apply ask updates
             for update in ask_updates {
-                Self::apply_level_update(&mut book.asks, update, false);
+                if let Some(idx) = Self::apply_level_update(&mut book.asks, update, false) {
+                    changes.asks_changed = true;
+                    changes.levels_changed += 1;
+                    changes.shallowest_level = changes.shallowest_level.min(idx);
+                }
+            }
+            changes.top_moved = top != (book.best_bid(), book.best_ask());
+            delta = Some(changes);
+
+            let flow = touch_flow(touch_before.0, touch(&book.bids), true)
+                - touch_flow(touch_before.1, touch(&book.asks), false);
+            if flow != 0.0 {
+                self.flow.entry(pair.to_string()).or_default().record(flow);
+            }
+
+            let grown = (book.bids.capacity() > capacity.0) as u64 + (book.asks.capacity() > capacity.1) as u64;
+            if grown > 0 {
+                self.pool.counters.level_growths.fetch_add(grown, Ordering::Relaxed);
             }
             
             book.sequence = sequence;
             book.last_update = Utc::now();
+            self.quality.record_update(pair, spread_bps(&book), Instant::now());
             
             // Update price edge
             self.update_price_from_book(pair, &book);
@@ -131,10 +605,12 @@ impl OrderBookCache {
         let mut stats = self.stats.write();
         stats.updates_received += 1;
         stats.last_update = Some(Utc::now());
+        delta
     }
 
     /// Apply a single level update to bids or asks
-    fn apply_level_update(levels: &mut Vec<OrderBookLevel>, update: OrderBookLevel, is_bid: bool) {
+    /// Returns the position touched (None when removing a level that isn't there)
+    fn apply_level_update(levels: &mut Vec<OrderBookLevel>, update: OrderBookLevel, is_bid: bool) -> Option<usize> {
         // Find existing level at this price using relative comparison
         // Uses relative epsilon for better handling across different price ranges
         // (e.g., BTC at 50000 vs SHIB at 0.00001)
@@ -152,12 +628,13 @@ impl OrderBookCache {
         
         if update.qty == 0.0 {
             // Remove level
-            if let Some(idx) = pos {
-                levels.remove(idx);
-            }
+            let idx = pos?;
+            levels.remove(idx);
+            Some(idx)
         } else if let Some(idx) = pos {
             // Update existing level
             levels[idx].qty = update.qty;
+            Some(idx)
         } else {
             // Insert new level in sorted order
             let insert_pos = if is_bid {
@@ -168,6 +645,7 @@ impl OrderBookCache {
                 levels.iter().position(|l| l.price > update.price).unwrap_or(levels.len())
             };
             levels.insert(insert_pos, update);
+            Some(insert_pos)
         }
     }
 
@@ -227,10 +705,63 @@ impl OrderBookCache {
                 return None;
             }
             
+            self.pool.counters.fresh_copies.fetch_add(1, Ordering::Relaxed);
             Some(book)
         })
     }
 
+    /// Like get_order_book, but the copy is recycled when dropped (with the
+    /// level pool enabled). For short-lived reads on the scan path.
+    pub fn get_order_book_pooled(&self, pair: &str) -> Option<PooledBook<'_>> {
+        let entry = self.order_books.get(pair)?;
+        let source = entry.read();
+        if source.bids.is_empty() || source.asks.is_empty() {
+            tracing::debug!("Order book for {} has no real data, skipping", pair);
+            return None;
+        }
+        Some(PooledBook { book: Some(self.pool.copy(&source)), pool: &self.pool })
+    }
+
+    /// Imbalance and microprice for one pair (None without both sides)
+    pub fn get_signals(&self, pair: &str) -> Option<BookSignals> {
+        let entry = self.order_books.get(pair)?;
+        let book = entry.read();
+        let (bid, ask) = (book.bids.first()?, book.asks.first()?);
+        let mid = (bid.price + ask.price) / 2.0;
+        let touch_qty = bid.qty + ask.qty;
+        if mid <= 0.0 || touch_qty <= 0.0 {
+            return None;
+        }
+        let microprice = (bid.price * ask.qty + ask.price * bid.qty) / touch_qty;
+
+        let bid_depth: f64 = book.bids.iter().take(SIGNAL_DEPTH_LEVELS).map(|l| l.qty).sum();
+        let ask_depth: f64 = book.asks.iter().take(SIGNAL_DEPTH_LEVELS).map(|l| l.qty).sum();
+
+        Some(BookSignals {
+            pair: pair.to_string(),
+            mid,
+            microprice,
+            microprice_offset_bps: (microprice - mid) / mid * 10_000.0,
+            depth_imbalance: (bid_depth - ask_depth) / (bid_depth + ask_depth),
+            order_flow_imbalance: self.flow.get(pair).map(|f| f.normalized()).unwrap_or(0.0),
+        })
+    }
+
+    /// Signals for every pair with a two-sided book
+    pub fn get_all_signals(&self) -> Vec<BookSignals> {
+        let mut signals: Vec<BookSignals> = self.order_books
+            .iter()
+            .filter_map(|entry| self.get_signals(entry.key()))
+            .collect();
+        signals.sort_by(|a, b| a.pair.cmp(&b.pair));
+        signals
+    }
+
+    /// Allocation counters for book copies and level buffers
+    pub fn allocation_stats(&self) -> AllocationStats {
+        self.pool.stats()
+    }
+
     /// Get multiple order books
     pub fn get_order_books(&self, pairs: &[String]) -> HashMap<String, OrderBook> {
         pairs
@@ -267,6 +798,16 @@ impl OrderBookCache {
         self.pair_info.get(pair).map(|r| r.clone())
     }
 
+    /// Metadata of the registered pairs
+    pub fn pair_registry(&self) -> &PairRegistry {
+        &self.registry
+    }
+
+    /// Registered pair that trades `from` into `to`, and in which direction
+    pub fn find_pair(&self, from: &str, to: &str) -> Option<PairRoute> {
+        self.registry.find_pair(from, to)
+    }
+
     /// Get all pairs
     pub fn get_all_pairs(&self) -> Vec<String> {
         self.pair_info.iter().map(|r| r.key().clone()).collect()
@@ -288,7 +829,7 @@ impl OrderBookCache {
     }
 
     /// Get cache statistics
-    pub fn get_stats(&self) -> (usize, usize, f64) {
+    pub fn get_stats(&self) -> CacheUsage {
         let pairs = self.order_books.len();
         let currencies = self.currencies.len();
         
@@ -302,13 +843,118 @@ impl OrderBookCache {
             count += 1;
         }
         
-        let avg_staleness = if count > 0 {
+        let avg_staleness_ms = if count > 0 {
             total_staleness as f64 / count as f64
         } else {
             0.0
         };
         
-        (pairs, currencies, avg_staleness)
+        CacheUsage { pairs, currencies, avg_staleness_ms, memory: self.memory_usage() }
+    }
+
+    /// Estimated bytes held, against the budget. Levels count by capacity,
+    /// since that is what stays allocated.
+    pub fn memory_usage(&self) -> CacheMemorySnapshot {
+        let level = std::mem::size_of::<OrderBookLevel>();
+        let book_size = |book: &OrderBook| {
+            std::mem::size_of::<OrderBook>() + book.pair.capacity() + (book.bids.capacity() + book.asks.capacity()) * level
+        };
+        let book_bytes: usize = self.order_books.iter().map(|entry| book_size(&entry.read())).sum();
+        let price_bytes: usize = self.prices.iter()
+            .map(|e| std::mem::size_of::<PriceEdge>() + e.pair.capacity() + e.base.capacity() + e.quote.capacity())
+            .sum();
+        let quality_bytes = self.quality.memory_bytes();
+        let pooled_bytes: usize = self.pool.idle.lock().iter().map(book_size).sum();
+        CacheMemorySnapshot {
+            total_bytes: book_bytes + price_bytes + quality_bytes + pooled_bytes,
+            book_bytes,
+            price_bytes,
+            quality_bytes,
+            pooled_bytes,
+            books: self.order_books.len(),
+            max_books: self.budget.max_books,
+            max_bytes: self.budget.max_bytes,
+            released_pairs: self.released.len(),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// A pair left the subscription: free its levels and price, and make
+    /// its book evictable
+    pub fn release_pair(&self, pair: &str) {
+        let Some(book_ref) = self.order_books.get(pair) else {
+            return;
+        };
+        *book_ref.write() = OrderBook::new(pair.to_string());
+        drop(book_ref);
+        self.prices.remove(pair);
+        self.rest_sourced.remove(pair);
+        self.flow.remove(pair);
+        self.released.insert(pair.to_string(), Instant::now());
+        self.enforce_budget();
+    }
+
+    /// Evict released books, least recently released first, until the
+    /// cache is within budget. Returns the evicted pairs.
+    pub fn enforce_budget(&self) -> Vec<String> {
+        let over = |usage: &CacheMemorySnapshot| {
+            (usage.max_books > 0 && usage.books > usage.max_books)
+                || (usage.max_bytes > 0 && usage.total_bytes > usage.max_bytes)
+        };
+        if self.released.is_empty() {
+            return Vec::new();
+        }
+        let mut usage = self.memory_usage();
+        if !over(&usage) {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<(String, Instant)> = self.released.iter().map(|e| (e.key().clone(), *e.value())).collect();
+        candidates.sort_by_key(|(_, at)| *at);
+        let mut evicted = Vec::new();
+        for (pair, _) in candidates {
+            if !over(&usage) {
+                break;
+            }
+            self.evict_pair(&pair);
+            evicted.push(pair);
+            usage = self.memory_usage();
+        }
+        tracing::info!(
+            "Evicted {} released order books to stay within budget ({} books, {} bytes)",
+            evicted.len(), usage.books, usage.total_bytes
+        );
+        evicted
+    }
+
+    /// Drop everything held for a pair except its registration
+    fn evict_pair(&self, pair: &str) {
+        self.order_books.remove(pair);
+        self.prices.remove(pair);
+        self.rest_sourced.remove(pair);
+        self.flow.remove(pair);
+        self.quality.remove(pair);
+        self.released.remove(pair);
+        self.evictions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Share of registered pairs (0.0-1.0) whose book has both sides
+    /// populated and was updated within max_staleness_ms
+    pub fn fresh_book_ratio(&self, max_staleness_ms: i64) -> f64 {
+        let total = self.order_books.len();
+        if total == 0 {
+            return 0.0;
+        }
+
+        let fresh = self.order_books
+            .iter()
+            .filter(|entry| {
+                let book = entry.read();
+                !book.bids.is_empty() && !book.asks.is_empty() && book.staleness_ms() < max_staleness_ms
+            })
+            .count();
+
+        fresh as f64 / total as f64
     }
 
     /// Check if order book is fresh enough
@@ -326,12 +972,141 @@ impl OrderBookCache {
             .map(|r| r.read().staleness_ms())
     }
 
+    /// Rolling quality of one pair (None before its first update)
+    pub fn get_quality(&self, pair: &str) -> Option<PairQuality> {
+        self.quality.get(pair, Instant::now())
+    }
+
+    /// Rolling quality of every pair with history, best first
+    pub fn get_all_quality(&self) -> Vec<PairQuality> {
+        self.quality.all(Instant::now())
+    }
+
+    /// Replace the set of quarantined pairs (pair -> reason)
+    pub fn set_quarantined(&self, pairs: HashMap<String, String>) {
+        self.quarantined.retain(|pair, _| pairs.contains_key(pair));
+        for (pair, reason) in pairs {
+            self.quarantined.insert(pair, reason);
+        }
+    }
+
+    /// Check if a pair is excluded from scanning
+    pub fn is_quarantined(&self, pair: &str) -> bool {
+        self.quarantined.contains_key(pair)
+    }
+
+    /// Get quarantined pairs with their reasons
+    pub fn get_quarantined(&self) -> HashMap<String, String> {
+        self.quarantined
+            .iter()
+            .map(|r| (r.key().clone(), r.value().clone()))
+            .collect()
+    }
+
+    /// Replace the set of pairs flagged against the index (pair -> deviation %)
+    pub fn set_anomalous(&self, pairs: HashMap<String, f64>) {
+        self.anomalous.retain(|pair, _| pairs.contains_key(pair));
+        for (pair, deviation) in pairs {
+            self.anomalous.insert(pair, deviation);
+        }
+    }
+
+    /// Check if a pair deviates from the external index
+    pub fn is_anomalous(&self, pair: &str) -> bool {
+        self.anomalous.contains_key(pair)
+    }
+
+    /// Get anomalous pairs with their deviation from the index (%)
+    pub fn get_anomalous(&self) -> HashMap<String, f64> {
+        self.anomalous.iter().map(|r| (r.key().clone(), *r.value())).collect()
+    }
+
+    /// Replace the currencies graphs must leave out. Returns whether the set changed.
+    pub fn set_blocked_currencies(&self, currencies: HashSet<String>) -> bool {
+        let currencies: HashSet<String> = currencies.into_iter().map(|c| c.to_uppercase()).collect();
+        let mut blocked = self.blocked_currencies.write();
+        if *blocked == currencies {
+            return false;
+        }
+        *blocked = currencies;
+        self.restrictions_version.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+
+    pub fn is_currency_blocked(&self, currency: &str) -> bool {
+        let blocked = self.blocked_currencies.read();
+        !blocked.is_empty() && blocked.contains(currency)
+    }
+
+    pub fn get_blocked_currencies(&self) -> HashSet<String> {
+        self.blocked_currencies.read().clone()
+    }
+
+    /// Changes with every new blocked set
+    pub fn restrictions_version(&self) -> u64 {
+        self.restrictions_version.load(Ordering::SeqCst)
+    }
+
+    /// Changes whenever a pair is registered or unregistered
+    pub fn topology_version(&self) -> u64 {
+        self.topology_version.load(Ordering::SeqCst)
+    }
+
+    /// Registered pairs through a blocked currency (two graph edges each)
+    pub fn restricted_pairs(&self) -> Vec<String> {
+        let blocked = self.blocked_currencies.read();
+        if blocked.is_empty() {
+            return Vec::new();
+        }
+        let mut pairs: Vec<String> = self
+            .pair_info
+            .iter()
+            .filter(|r| blocked.contains(&r.base) || blocked.contains(&r.quote))
+            .map(|r| r.key().clone())
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// What the restrictions keep out of scanning
+    pub fn restricted_graph(&self) -> RestrictedGraph {
+        let mut currencies: Vec<String> = self.get_blocked_currencies().into_iter().collect();
+        currencies.sort();
+        let pairs = self.restricted_pairs();
+        RestrictedGraph { currencies, excluded_edges: pairs.len() * 2, pairs }
+    }
+
+    /// Drop one pair's book and price so it is rebuilt from a fresh snapshot
+    /// (registration is kept, and an evicted book is recreated). Returns
+    /// false if the pair is unknown.
+    pub fn reset_pair(&self, pair: &str) -> bool {
+        if !self.pair_info.contains_key(pair) {
+            return false;
+        }
+        self.released.remove(pair);
+        self.order_books
+            .entry(pair.to_string())
+            .and_modify(|book| *book.write() = OrderBook::new(pair.to_string()))
+            .or_insert_with(|| Arc::new(RwLock::new(OrderBook::new(pair.to_string()))));
+        self.prices.remove(pair);
+        self.rest_sourced.remove(pair);
+        self.flow.remove(pair);
+        true
+    }
+
     /// Clear all data (for reconnection with new settings)
     pub fn clear(&self) {
         self.order_books.clear();
         self.prices.clear();
         self.currencies.clear();
         self.pair_info.clear();
+        self.registry.clear();
+        self.quarantined.clear();
+        self.anomalous.clear();
+        self.rest_sourced.clear();
+        self.released.clear();
+        self.flow.clear();
+        self.quality.clear();
         
         // Reset stats
         let mut stats = self.stats.write();
@@ -389,5 +1164,188 @@ mod tests {
         let price = cache.get_price("BTC/USD").unwrap();
         assert_eq!(price.bid, 100000.0);
         assert_eq!(price.ask, 100001.0);
+
+        // Resubscription drops the book but keeps the pair registered
+        assert!(cache.reset_pair("BTC/USD"));
+        assert!(cache.get_order_book("BTC/USD").is_none());
+        assert!(cache.get_price("BTC/USD").is_none());
+        assert!(cache.get_pair_info("BTC/USD").is_some());
+        assert!(!cache.reset_pair("ETH/USD"));
+    }
+
+    #[test]
+    fn test_book_signals() {
+        let cache = OrderBookCache::new();
+        cache.register_pair(PairInfo {
+            pair_name: "BTC/USD".to_string(),
+            base: "BTC".to_string(),
+            quote: "USD".to_string(),
+            kraken_id: "XBTUSD".to_string(),
+            ws_name: "BTC/USD".to_string(),
+            volume_24h: 1000000.0,
+        });
+        cache.update_snapshot(
+            "BTC/USD",
+            vec![OrderBookLevel { price: 100.0, qty: 3.0 }],
+            vec![OrderBookLevel { price: 101.0, qty: 1.0 }],
+            1,
+        );
+
+        // Heavier bids pull the microprice toward the ask
+        let signals = cache.get_signals("BTC/USD").unwrap();
+        assert_eq!(signals.mid, 100.5);
+        assert_eq!(signals.microprice, 100.75);
+        assert_eq!(signals.depth_imbalance, 0.5);
+        assert_eq!(signals.order_flow_imbalance, 0.0);
+
+        // Bids growing and asks shrinking at the touch is buying flow
+        cache.update_incremental("BTC/USD", vec![OrderBookLevel { price: 100.0, qty: 4.0 }], vec![], 0);
+        cache.update_incremental("BTC/USD", vec![], vec![OrderBookLevel { price: 101.0, qty: 0.5 }], 0);
+        let signals = cache.get_signals("BTC/USD").unwrap();
+        assert_eq!(signals.order_flow_imbalance, 1.0);
+        assert!(signals.pressure() > 0.5);
+
+        // A fresh snapshot starts the flow over
+        cache.update_snapshot(
+            "BTC/USD",
+            vec![OrderBookLevel { price: 100.0, qty: 1.0 }],
+            vec![OrderBookLevel { price: 101.0, qty: 1.0 }],
+            2,
+        );
+        assert_eq!(cache.get_signals("BTC/USD").unwrap().pressure(), 0.0);
+        assert!(cache.get_signals("ETH/USD").is_none());
+    }
+
+    #[test]
+    fn test_level_pool_reuses_buffers() {
+        let run = |policy: LevelPoolPolicy| {
+            let cache = OrderBookCache::new().with_level_pool(policy);
+            cache.register_pair(PairInfo {
+                pair_name: "BTC/USD".to_string(),
+                base: "BTC".to_string(),
+                quote: "USD".to_string(),
+                kraken_id: "XBTUSD".to_string(),
+                ws_name: "BTC/USD".to_string(),
+                volume_24h: 1000000.0,
+            });
+            let levels = |from: f64, step: f64| (0..10).map(|i| OrderBookLevel { price: from + step * i as f64, qty: 1.0 }).collect();
+            cache.update_snapshot("BTC/USD", levels(99_999.0, -1.0), levels(100_001.0, 1.0), 1);
+            for i in 0..40 {
+                // Each delta adds a level a side
+                let bid = OrderBookLevel { price: 99_000.0 - i as f64, qty: 1.0 };
+                let ask = OrderBookLevel { price: 101_000.0 + i as f64, qty: 1.0 };
+                cache.update_incremental("BTC/USD", vec![bid], vec![ask], 0);
+                let book = cache.get_order_book_pooled("BTC/USD").unwrap();
+                assert_eq!(book.bids.len(), 11 + i);
+            }
+            cache.allocation_stats()
+        };
+
+        let plain = run(LevelPoolPolicy::default());
+        assert_eq!(plain.fresh_copies, 40);
+        assert_eq!(plain.reused_copies, 0);
+        assert_eq!(plain.buffers_replaced, 2);
+
+        let pooled = run(LevelPoolPolicy { enabled: true, max_pooled: 4, headroom: 16 });
+        assert_eq!(pooled.idle_copies, 1);
+        assert_eq!(pooled.buffers_replaced, 0);
+        assert!(pooled.reused_copies > 30, "{:?}", pooled);
+        assert_eq!(pooled.fresh_copies + pooled.reused_copies, 40);
+        assert!(pooled.level_growths < plain.level_growths, "{:?} vs {:?}", pooled, plain);
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_incremental_update_reports_delta() {
+        let cache = OrderBookCache::new();
+        cache.register_pair(PairInfo {
+            pair_name: "BTC/USD".to_string(),
+            base: "BTC".to_string(),
+            quote: "USD".to_string(),
+            kraken_id: "XBTUSD".to_string(),
+            ws_name: "BTC/USD".to_string(),
+            volume_24h: 1000000.0,
+        });
+        let levels = |from: f64, step: f64| (0..10).map(|i| OrderBookLevel { price: from + step * i as f64, qty: 1.0 }).collect();
+        cache.update_snapshot("BTC/USD", levels(99_999.0, -1.0), levels(100_001.0, 1.0), 1);
+        let level = |price: f64, qty: f64| OrderBookLevel { price, qty };
+
+        // Resizing the 8th bid leaves the top alone
+        let delta = cache.update_incremental("BTC/USD", vec![level(99_992.0, 3.0)], vec![], 0).unwrap();
+        assert!(delta.bids_changed && !delta.asks_changed);
+        assert_eq!((delta.levels_changed, delta.shallowest_level, delta.top_moved), (1, 7, false));
+        assert!(!delta.can_affect_cycles(3));
+        assert!(delta.can_affect_cycles(10));
+
+        // A new best ask moves the top; removing a missing level changes nothing
+        let delta = cache.update_incremental("BTC/USD", vec![level(50.0, 0.0)], vec![level(100_000.5, 1.0)], 0).unwrap();
+        assert!(!delta.bids_changed && delta.asks_changed);
+        assert_eq!((delta.levels_changed, delta.shallowest_level, delta.top_moved), (1, 0, true));
+        assert!(delta.can_affect_cycles(1));
+
+        assert!(cache.update_incremental("ETH/USD", vec![level(1.0, 1.0)], vec![], 0).is_none());
+        assert!(BookDelta::snapshot("BTC/USD").can_affect_cycles(0));
+    }
+
+    #[test]
+    fn test_released_books_evicted_over_budget() {
+        let cache = OrderBookCache::new().with_budget(CacheBudget { max_books: 2, max_bytes: 0 });
+        let register = |base: &str| {
+            let pair = format!("{}/USD", base);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: "USD".to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1000000.0,
+            });
+            let levels = |from: f64| (0..25).map(|i| OrderBookLevel { price: from + i as f64, qty: 1.0 }).collect();
+            cache.update_snapshot(&pair, levels(100.0), levels(200.0), 1);
+        };
+        register("BTC");
+        register("ETH");
+        register("SOL");
+        // Nothing released yet: subscribed books are never evicted
+        let usage = cache.get_stats();
+        assert_eq!((usage.pairs, usage.memory.evictions), (3, 0));
+        assert!(usage.memory.book_bytes >= 3 * 50 * std::mem::size_of::<OrderBookLevel>());
+
+        cache.release_pair("ETH/USD");
+        cache.release_pair("BTC/USD");
+        // Evicting ETH brought the cache back to two books; BTC stays released
+        let memory = cache.memory_usage();
+        assert_eq!((memory.books, memory.released_pairs, memory.evictions), (2, 1, 1));
+        assert!(cache.get_order_book("BTC/USD").is_none());
+        assert!(cache.get_price("BTC/USD").is_none());
+
+        // An evicted pair is still registered and gets a fresh book on resubscribe
+        assert!(cache.get_pair_info("ETH/USD").is_some());
+        assert!(cache.reset_pair("ETH/USD"));
+        assert_eq!(cache.memory_usage().books, 3);
+    }
+
+    #[test]
+    fn test_unregister_pair_drops_unused_currencies() {
+        let cache = OrderBookCache::new();
+        for (base, quote) in [("BTC", "USD"), ("ETH", "USD"), ("ETH", "BTC")] {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair,
+                volume_24h: 1000000.0,
+            });
+        }
+        let version = cache.topology_version();
+
+        assert!(cache.unregister_pair("ETH/BTC"));
+        assert!(cache.get_pair_info("ETH/BTC").is_none());
+        assert!(cache.find_pair("ETH", "BTC").is_none());
+        assert_eq!(cache.get_currencies().len(), 3);
+        assert!(cache.unregister_pair("BTC/USD"));
+        assert!(!cache.get_currencies().contains("BTC"));
+        assert!(!cache.unregister_pair("BTC/USD"));
+        assert_eq!(cache.topology_version(), version + 2);
+    }
+}
@@ -0,0 +1,212 @@
+//! Rolling Pair Quality
+//!
+//! Each book update is bucketed by minute over the last hour, per pair:
+//! - spread at the touch (bps), averaged over every update
+//! - update count, as updates per minute
+//! - gaps: a silence longer than MAX_ORDERBOOK_STALENESS_MS between updates,
+//!   an out-of-sequence update, or a fresh snapshot replacing a live book
+//!
+//! The three combine into a 0-100 score:
+//! `40 × 1/(1 + spread/20bps) + 30 × min(1, updates/min ÷ 60) + 30 × (1 - share of minutes with a gap)`.
+//! With PAIR_QUALITY_MIN set, the scanner leaves out pairs scoring under it
+//! (pairs with less than a minute of history are always kept).
+#![allow(dead_code)]
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// History kept per pair
+const WINDOW: Duration = Duration::from_secs(3_600);
+
+const BUCKET: Duration = Duration::from_secs(60);
+
+/// Spread at which the spread component is worth half
+const SPREAD_REF_BPS: f64 = 20.0;
+
+/// Updates per minute that earn the full frequency component
+const FREQ_REF_PER_MIN: f64 = 60.0;
+
+/// Minimum score a pair needs to be scanned, from PAIR_QUALITY_MIN (0 = off)
+pub fn min_score_from_env() -> f64 {
+    std::env::var("PAIR_QUALITY_MIN")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| (0.0..=100.0).contains(v))
+        .unwrap_or(0.0)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    started: Instant,
+    updates: u64,
+    gaps: u64,
+    spread_bps_sum: f64,
+    spread_samples: u64,
+}
+
+impl Bucket {
+    fn new(started: Instant) -> Self {
+        Self { started, updates: 0, gaps: 0, spread_bps_sum: 0.0, spread_samples: 0 }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    buckets: VecDeque<Bucket>,
+    last_update: Option<Instant>,
+}
+
+impl Window {
+    fn current(&mut self, now: Instant) -> &mut Bucket {
+        while self.buckets.front().is_some_and(|b| now.duration_since(b.started) > WINDOW) {
+            self.buckets.pop_front();
+        }
+        let fresh = self.buckets.back().is_none_or(|b| now.duration_since(b.started) >= BUCKET);
+        if fresh {
+            self.buckets.push_back(Bucket::new(now));
+        }
+        self.buckets.back_mut().expect("bucket just ensured")
+    }
+}
+
+/// One pair's quality over the window
+#[derive(Debug, Clone, Serialize)]
+pub struct PairQuality {
+    pub pair: String,
+    pub score: f64,
+    pub avg_spread_bps: f64,
+    pub updates_per_min: f64,
+    /// Share of observed minutes (0-1) with at least one gap
+    pub gap_rate: f64,
+    pub gaps: u64,
+    /// Minutes of history behind the score
+    pub minutes_observed: f64,
+}
+
+/// Per-pair rolling windows
+#[derive(Default)]
+pub struct QualityTracker {
+    windows: DashMap<String, Window>,
+}
+
+impl QualityTracker {
+    /// A book update with the resulting touch spread (None for a one-sided book)
+    pub fn record_update(&self, pair: &str, spread_bps: Option<f64>, now: Instant) {
+        let mut window = self.windows.entry(pair.to_string()).or_default();
+        let silent = window.last_update.map(|last| now.duration_since(last));
+        window.last_update = Some(now);
+        let bucket = window.current(now);
+        bucket.updates += 1;
+        if let Some(spread) = spread_bps.filter(|s| s.is_finite() && *s >= 0.0) {
+            bucket.spread_bps_sum += spread;
+            bucket.spread_samples += 1;
+        }
+        let max_silence = Duration::from_millis(crate::types::MAX_ORDERBOOK_STALENESS_MS as u64);
+        if silent.is_some_and(|s| s > max_silence) {
+            bucket.gaps += 1;
+        }
+    }
+
+    /// A missed or rejected update, or a resync snapshot
+    pub fn record_gap(&self, pair: &str, now: Instant) {
+        self.windows.entry(pair.to_string()).or_default().current(now).gaps += 1;
+    }
+
+    pub fn get(&self, pair: &str, now: Instant) -> Option<PairQuality> {
+        self.windows.get(pair).and_then(|w| quality(pair, &w, now))
+    }
+
+    /// Every pair with history, best first
+    pub fn all(&self, now: Instant) -> Vec<PairQuality> {
+        let mut scores: Vec<PairQuality> = self.windows.iter()
+            .filter_map(|w| quality(w.key(), w.value(), now))
+            .collect();
+        scores.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scores
+    }
+
+    /// Approximate bytes held by the windows
+    pub fn memory_bytes(&self) -> usize {
+        self.windows.iter()
+            .map(|w| w.key().capacity() + std::mem::size_of::<Window>() + w.buckets.capacity() * std::mem::size_of::<Bucket>())
+            .sum()
+    }
+
+    pub fn remove(&self, pair: &str) {
+        self.windows.remove(pair);
+    }
+
+    pub fn clear(&self) {
+        self.windows.clear();
+    }
+}
+
+fn quality(pair: &str, window: &Window, now: Instant) -> Option<PairQuality> {
+    let live: Vec<&Bucket> = window.buckets.iter()
+        .filter(|b| now.duration_since(b.started) <= WINDOW)
+        .collect();
+    let first = live.first()?;
+    let minutes = (now.duration_since(first.started).as_secs_f64() / 60.0).max(1.0);
+
+    let updates: u64 = live.iter().map(|b| b.updates).sum();
+    let gaps: u64 = live.iter().map(|b| b.gaps).sum();
+    let samples: u64 = live.iter().map(|b| b.spread_samples).sum();
+    let avg_spread_bps = if samples > 0 {
+        live.iter().map(|b| b.spread_bps_sum).sum::<f64>() / samples as f64
+    } else {
+        f64::INFINITY
+    };
+    let updates_per_min = updates as f64 / minutes;
+    let gap_rate = (live.iter().filter(|b| b.gaps > 0).count() as f64 / minutes.ceil()).min(1.0);
+
+    let spread_score = 1.0 / (1.0 + avg_spread_bps / SPREAD_REF_BPS);
+    let freq_score = (updates_per_min / FREQ_REF_PER_MIN).min(1.0);
+    let score = 100.0 * (0.4 * spread_score + 0.3 * freq_score + 0.3 * (1.0 - gap_rate));
+
+    Some(PairQuality {
+        pair: pair.to_string(),
+        score,
+        avg_spread_bps: if avg_spread_bps.is_finite() { avg_spread_bps } else { 0.0 },
+        updates_per_min,
+        gap_rate,
+        gaps,
+        minutes_observed: now.duration_since(first.started).as_secs_f64() / 60.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quality_score_components() {
+        let tracker = QualityTracker::default();
+        let start = Instant::now();
+        // Tight, busy and gap-free for five minutes
+        for s in 0..300 {
+            tracker.record_update("BTC/USD", Some(1.0), start + Duration::from_secs(s));
+        }
+        // Wide, slow, with a long silence and a resync
+        for s in (0..300).step_by(30) {
+            tracker.record_update("ALT/USD", Some(80.0), start + Duration::from_secs(s));
+        }
+        tracker.record_gap("ALT/USD", start + Duration::from_secs(150));
+
+        let now = start + Duration::from_secs(300);
+        let good = tracker.get("BTC/USD", now).unwrap();
+        let poor = tracker.get("ALT/USD", now).unwrap();
+        assert_eq!(good.gaps, 0);
+        assert!((good.avg_spread_bps - 1.0).abs() < 1e-9);
+        assert!((good.updates_per_min - 60.0).abs() < 1e-9);
+        assert!(good.score > 95.0);
+        assert!(poor.gap_rate > 0.9);
+        assert!(poor.score < 20.0, "{}", poor.score);
+        assert_eq!(tracker.all(now)[0].pair, "BTC/USD");
+
+        // Minutes older than the window no longer count
+        let later = start + WINDOW + Duration::from_secs(400);
+        assert!(tracker.get("BTC/USD", later).is_none());
+    }
+}
@@ -0,0 +1,269 @@
+//! Pair Ranking
+//!
+//! With max_pairs capped, the pairs kept used to be simply the highest-volume
+//! ones. Volume says little about whether a pair ever takes part in a
+//! profitable cycle, so candidates are now scored on three signals, each
+//! normalized to 0..1 across the candidate set:
+//!
+//! - volume: ln(1 + 24h USD volume), relative to the largest
+//! - opportunities: how often the pair was a leg of a profitable trade in
+//!   the last PAIR_RANK_HISTORY_HOURS
+//! - cycles: how many start-currency triangles the pair belongs to
+//!
+//! The weighted sum decides which pairs are selected and the order they are
+//! subscribed in. PAIR_RANKING=volume keeps the old volume order; the
+//! ranking is computed (and served by `GET /api/pairs/ranking`) either way.
+
+use crate::kraken_pairs::SelectedPair;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Weights of the three signals and the trade history considered
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RankingPolicy {
+    /// Select by score (false = by volume only)
+    pub enabled: bool,
+    pub volume_weight: f64,
+    pub opportunity_weight: f64,
+    pub cycle_weight: f64,
+    pub history_hours: i32,
+}
+
+impl Default for RankingPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume_weight: 1.0,
+            opportunity_weight: 2.0,
+            cycle_weight: 1.0,
+            history_hours: 720,
+        }
+    }
+}
+
+impl RankingPolicy {
+    /// Read PAIR_RANKING (score / volume) and the PAIR_RANK_* weights
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let weight = |key: &str, default: f64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(default)
+        };
+        Self {
+            enabled: !std::env::var("PAIR_RANKING").is_ok_and(|v| v.eq_ignore_ascii_case("volume")),
+            volume_weight: weight("PAIR_RANK_VOLUME_WEIGHT", defaults.volume_weight),
+            opportunity_weight: weight("PAIR_RANK_OPPORTUNITY_WEIGHT", defaults.opportunity_weight),
+            cycle_weight: weight("PAIR_RANK_CYCLE_WEIGHT", defaults.cycle_weight),
+            history_hours: std::env::var("PAIR_RANK_HISTORY_HOURS")
+                .ok()
+                .and_then(|v| v.parse::<i32>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.history_hours),
+        }
+    }
+}
+
+/// One candidate's score and its inputs
+#[derive(Debug, Clone, Serialize)]
+pub struct PairScore {
+    pub rank: usize,
+    pub pair: String,
+    pub score: f64,
+    pub volume_24h_usd: f64,
+    /// Profitable trades the pair was a leg of
+    pub opportunity_count: u64,
+    /// Start-currency triangles the pair belongs to
+    pub cycle_count: usize,
+    /// Within max_pairs (subscribed on the last start)
+    pub selected: bool,
+}
+
+/// Ranking computed on the last engine start
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PairRanking {
+    pub ranked_at: Option<DateTime<Utc>>,
+    /// "score" or "volume"
+    pub selection: String,
+    pub max_pairs: usize,
+    pub candidates: usize,
+    pub pairs: Vec<PairScore>,
+}
+
+/// Pair name for each unordered currency pair among the candidates
+fn pair_index(pairs: &[SelectedPair]) -> HashMap<(String, String), String> {
+    let mut index = HashMap::new();
+    for pair in pairs {
+        index.insert((pair.base.clone(), pair.quote.clone()), pair.pair_name.clone());
+        index.insert((pair.quote.clone(), pair.base.clone()), pair.pair_name.clone());
+    }
+    index
+}
+
+/// How often each candidate was a leg of the given paths ("USD → BTC → ETH → USD")
+pub fn path_participation<'a>(paths: impl IntoIterator<Item = &'a str>, pairs: &[SelectedPair]) -> HashMap<String, u64> {
+    let index = pair_index(pairs);
+    let mut counts = HashMap::new();
+    for path in paths {
+        let currencies: Vec<&str> = path.split(" → ").map(str::trim).collect();
+        for hop in currencies.windows(2) {
+            if let Some(pair) = index.get(&(hop[0].to_string(), hop[1].to_string())) {
+                *counts.entry(pair.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Number of BASE → A → B → BASE triangles each candidate belongs to
+pub fn cycle_membership(pairs: &[SelectedPair], base_currencies: &[String]) -> HashMap<String, usize> {
+    triangle_membership(
+        pairs.iter().map(|p| (p.pair_name.as_str(), p.base.as_str(), p.quote.as_str())),
+        base_currencies,
+    )
+}
+
+/// Number of BASE → A → B → BASE triangles each (pair, base, quote) belongs to
+pub fn triangle_membership<'a>(
+    pairs: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+    base_currencies: &[String],
+) -> HashMap<String, usize> {
+    let mut index: HashMap<(String, String), String> = HashMap::new();
+    let mut neighbours: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (pair, base, quote) in pairs {
+        index.insert((base.to_string(), quote.to_string()), pair.to_string());
+        index.insert((quote.to_string(), base.to_string()), pair.to_string());
+        neighbours.entry(base).or_default().insert(quote);
+        neighbours.entry(quote).or_default().insert(base);
+    }
+
+    let mut counts = HashMap::new();
+    for base in base_currencies {
+        let Some(first_hops) = neighbours.get(base.as_str()) else { continue };
+        for &a in first_hops {
+            for &b in first_hops {
+                // Each triangle once, whichever direction it's traded in
+                if a >= b || !neighbours.get(a).is_some_and(|n| n.contains(b)) {
+                    continue;
+                }
+                for (x, y) in [(base.as_str(), a), (a, b), (b, base.as_str())] {
+                    if let Some(pair) = index.get(&(x.to_string(), y.to_string())) {
+                        *counts.entry(pair.clone()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// Score every candidate, best first (volume breaks ties). The first
+/// `max_pairs` are marked selected, in score order when the policy is
+/// enabled or volume order otherwise.
+pub fn rank_pairs(
+    pairs: &[SelectedPair],
+    participation: &HashMap<String, u64>,
+    base_currencies: &[String],
+    policy: &RankingPolicy,
+    max_pairs: usize,
+) -> Vec<PairScore> {
+    let cycles = cycle_membership(pairs, base_currencies);
+    let max_volume = pairs.iter().map(|p| p.volume_24h_usd.max(0.0).ln_1p()).fold(0.0, f64::max);
+    let max_opps = participation.values().copied().max().unwrap_or(0);
+    let max_cycles = cycles.values().copied().max().unwrap_or(0);
+    let ratio = |value: f64, max: f64| if max > 0.0 { value / max } else { 0.0 };
+
+    let mut scores: Vec<PairScore> = pairs
+        .iter()
+        .map(|pair| {
+            let opportunity_count = participation.get(&pair.pair_name).copied().unwrap_or(0);
+            let cycle_count = cycles.get(&pair.pair_name).copied().unwrap_or(0);
+            let score = policy.volume_weight * ratio(pair.volume_24h_usd.max(0.0).ln_1p(), max_volume)
+                + policy.opportunity_weight * ratio(opportunity_count as f64, max_opps as f64)
+                + policy.cycle_weight * ratio(cycle_count as f64, max_cycles as f64);
+            PairScore {
+                rank: 0,
+                pair: pair.pair_name.clone(),
+                score,
+                volume_24h_usd: pair.volume_24h_usd,
+                opportunity_count,
+                cycle_count,
+                selected: false,
+            }
+        })
+        .collect();
+
+    let by_volume = |a: &PairScore, b: &PairScore| b.volume_24h_usd.total_cmp(&a.volume_24h_usd);
+    if policy.enabled {
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| by_volume(a, b)));
+    } else {
+        scores.sort_by(by_volume);
+    }
+    for (i, score) in scores.iter_mut().enumerate() {
+        score.rank = i + 1;
+        score.selected = i < max_pairs;
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(base: &str, quote: &str, volume: f64) -> SelectedPair {
+        SelectedPair {
+            pair_name: format!("{}/{}", base, quote),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            kraken_id: String::new(),
+            ws_name: format!("{}/{}", base, quote),
+            altname: format!("{}{}", base, quote),
+            status: "online".to_string(),
+            volume_24h_usd: volume,
+            ordermin: 0.0,
+            costmin: 0.0,
+            pair_decimals: None,
+            lot_decimals: None,
+            last_price: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_history_and_cycles_outrank_volume() {
+        let pairs = vec![
+            pair("BTC", "USD", 500_000_000.0),
+            pair("ETH", "USD", 200_000_000.0),
+            pair("ETH", "BTC", 50_000_000.0),
+            pair("SOL", "USD", 300_000_000.0),
+            pair("DOGE", "USD", 400_000_000.0),
+        ];
+        let bases = vec!["USD".to_string()];
+
+        // Only USD-BTC-ETH closes a triangle
+        let cycles = cycle_membership(&pairs, &bases);
+        assert_eq!(cycles.get("ETH/BTC"), Some(&1));
+        assert_eq!(cycles.get("SOL/USD"), None);
+
+        let history = ["USD → BTC → ETH → USD", "USD → ETH → BTC → USD", "USD → SOL → USD"];
+        let participation = path_participation(history, &pairs);
+        assert_eq!(participation.get("ETH/BTC"), Some(&2));
+        assert_eq!(participation.get("SOL/USD"), Some(&2));
+
+        let policy = RankingPolicy::default();
+        let ranked = rank_pairs(&pairs, &participation, &bases, &policy, 3);
+        let selected: Vec<&str> = ranked.iter().filter(|s| s.selected).map(|s| s.pair.as_str()).collect();
+        // DOGE has the second-highest volume but no history and no triangle
+        assert!(!selected.contains(&"DOGE/USD"));
+        assert!(selected.contains(&"ETH/BTC"));
+        assert_eq!(ranked.last().unwrap().pair, "DOGE/USD");
+
+        // Volume selection keeps the old order
+        let volume_only = RankingPolicy { enabled: false, ..policy };
+        let ranked = rank_pairs(&pairs, &participation, &bases, &volume_only, 3);
+        let order: Vec<&str> = ranked.iter().map(|s| s.pair.as_str()).collect();
+        assert_eq!(order, vec!["BTC/USD", "DOGE/USD", "SOL/USD", "ETH/USD", "ETH/BTC"]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Pair Set Refresh
+//!
+//! Pairs are picked from AssetPairs when the engine starts. Every
+//! PAIR_REFRESH_MINS while it runs, AssetPairs is read again and compared
+//! with what is registered:
+//! - a registered pair no longer listed, or listed but no longer online, is
+//!   unsubscribed and dropped from the cache, the pair registry and the graph
+//! - an online pair that wasn't listed at the previous check is run through
+//!   the start filters; those that pass become candidates and are subscribed
+//!   while the subscription has room under max_pairs
+//!
+//! The first check only records the listing, so listings are reported from
+//! the second check on. Checks that changed something are kept here for
+//! GET /api/pairs/refresh.
+#![allow(dead_code)]
+
+use crate::kraken_pairs::PairSelectionConfig;
+use crate::pair_registry::PairMeta;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+
+/// Changed pair sets kept for the API
+const HISTORY_LEN: usize = 20;
+
+/// A registered pair that can no longer be traded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DelistedPair {
+    pub pair: String,
+    /// AssetPairs status now (None = no longer listed)
+    pub status: Option<String>,
+}
+
+/// What one check found and did
+#[derive(Debug, Clone, Serialize)]
+pub struct PairSetDiff {
+    pub checked_at: DateTime<Utc>,
+    /// Online pairs that weren't listed at the previous check
+    pub listed: Vec<String>,
+    /// Newly listed pairs that pass the start filters
+    pub candidates: Vec<String>,
+    pub delisted: Vec<DelistedPair>,
+    pub subscribed: Vec<String>,
+    pub unsubscribed: Vec<String>,
+}
+
+impl PairSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.listed.is_empty() && self.delisted.is_empty()
+    }
+}
+
+/// Compare a fresh AssetPairs listing with the registered pairs and the
+/// online pairs of the previous listing (None = first check, nothing counts
+/// as newly listed)
+pub fn diff_listing(previous: Option<&HashSet<String>>, registered: &[PairMeta], listing: &[PairMeta]) -> PairSetDiff {
+    let registered_names: HashSet<&str> = registered.iter().map(|p| p.pair_name.as_str()).collect();
+
+    let delisted = registered
+        .iter()
+        .filter_map(|meta| match listing.iter().find(|l| l.pair_name == meta.pair_name) {
+            Some(listed) if listed.is_online() => None,
+            Some(listed) => Some(DelistedPair { pair: meta.pair_name.clone(), status: Some(listed.status.clone()) }),
+            None => Some(DelistedPair { pair: meta.pair_name.clone(), status: None }),
+        })
+        .collect();
+
+    let mut listed: Vec<String> = match previous {
+        Some(previous) => listing
+            .iter()
+            .filter(|l| l.is_online() && !previous.contains(&l.pair_name) && !registered_names.contains(l.pair_name.as_str()))
+            .map(|l| l.pair_name.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+    listed.sort();
+    listed.dedup();
+
+    PairSetDiff {
+        checked_at: Utc::now(),
+        listed,
+        candidates: Vec::new(),
+        delisted,
+        subscribed: Vec::new(),
+        unsubscribed: Vec::new(),
+    }
+}
+
+/// Listing state between checks
+#[derive(Default)]
+pub struct PairSetRefresh {
+    /// Filters the engine started with (None until it started)
+    selection: RwLock<Option<PairSelectionConfig>>,
+    /// Online pairs at the previous check
+    last_listing: Mutex<Option<HashSet<String>>>,
+    /// Checks that changed something, newest last
+    history: Mutex<VecDeque<PairSetDiff>>,
+}
+
+impl PairSetRefresh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The engine (re)started with `config`: forget the previous listing
+    pub fn reset(&self, config: PairSelectionConfig) {
+        *self.selection.write() = Some(config);
+        *self.last_listing.lock() = None;
+    }
+
+    pub fn selection(&self) -> Option<PairSelectionConfig> {
+        self.selection.read().clone()
+    }
+
+    /// Diff `listing` against the registered pairs and remember it for the next check
+    pub fn compare(&self, registered: &[PairMeta], listing: &[PairMeta]) -> PairSetDiff {
+        let mut last = self.last_listing.lock();
+        let diff = diff_listing(last.as_ref(), registered, listing);
+        *last = Some(listing.iter().filter(|l| l.is_online()).map(|l| l.pair_name.clone()).collect());
+        diff
+    }
+
+    pub fn record(&self, diff: PairSetDiff) {
+        let mut history = self.history.lock();
+        if history.len() == HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(diff);
+    }
+
+    /// Changed pair sets, newest first
+    pub fn history(&self) -> Vec<PairSetDiff> {
+        self.history.lock().iter().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pair_registry::STATUS_ONLINE;
+
+    fn meta(pair: &str, status: &str) -> PairMeta {
+        let (base, quote) = pair.split_once('/').unwrap();
+        PairMeta {
+            pair_name: pair.to_string(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            kraken_id: pair.replace('/', ""),
+            ws_name: pair.to_string(),
+            altname: pair.replace('/', ""),
+            status: status.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_listings_and_delistings() {
+        let refresh = PairSetRefresh::new();
+        let registered = vec![meta("BTC/USD", STATUS_ONLINE), meta("ETH/USD", STATUS_ONLINE), meta("XRP/USD", STATUS_ONLINE)];
+
+        // First check: XRP is gone, ETH halted; SOL was listed all along
+        let listing = vec![meta("BTC/USD", STATUS_ONLINE), meta("ETH/USD", "cancel_only"), meta("SOL/USD", STATUS_ONLINE)];
+        let diff = refresh.compare(&registered, &listing);
+        assert!(diff.listed.is_empty());
+        assert_eq!(diff.delisted, vec![
+            DelistedPair { pair: "ETH/USD".to_string(), status: Some("cancel_only".to_string()) },
+            DelistedPair { pair: "XRP/USD".to_string(), status: None },
+        ]);
+
+        // Next check: only pairs new since then count as listed
+        let registered = vec![meta("BTC/USD", STATUS_ONLINE)];
+        let mut listing = listing;
+        listing.push(meta("ADA/USD", STATUS_ONLINE));
+        listing.push(meta("DOT/USD", "post_only"));
+        let diff = refresh.compare(&registered, &listing);
+        assert_eq!(diff.listed, vec!["ADA/USD".to_string()]);
+        assert!(diff.delisted.is_empty());
+        assert!(refresh.compare(&registered, &listing).is_empty());
+
+        refresh.record(diff);
+        assert_eq!(refresh.history()[0].listed, vec!["ADA/USD".to_string()]);
+    }
+}
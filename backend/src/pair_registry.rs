@@ -0,0 +1,212 @@
+//! Pair Metadata Registry
+//!
+//! What Kraken's AssetPairs says about each registered pair (base, quote,
+//! wsname, altname, status), indexed by currency pair so code that holds a
+//! `from -> to` step can ask which pair trades it and in which direction
+//! instead of formatting "FROM/TO" and "TO/FROM" and probing the cache.
+//!
+//! The OrderBookCache owns the registry and adds a basic entry for every
+//! pair it registers; the WebSocket layer registers the full AssetPairs
+//! metadata first, so that is what the entry holds.
+#![allow(dead_code)]
+
+use crate::kraken_pairs::SelectedPair;
+use crate::order_book::PairInfo;
+use dashmap::DashMap;
+use serde::Serialize;
+
+/// AssetPairs status of a pair that accepts every order type
+pub const STATUS_ONLINE: &str = "online";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairMeta {
+    /// Normalized pair name (e.g., "BTC/USD")
+    pub pair_name: String,
+    pub base: String,
+    pub quote: String,
+    /// Kraken's internal ID (e.g., "XXBTZUSD")
+    pub kraken_id: String,
+    /// WebSocket v2 symbol (e.g., "BTC/USD")
+    pub ws_name: String,
+    /// REST name (e.g., "XBTUSD")
+    pub altname: String,
+    /// online, cancel_only, post_only, limit_only or reduce_only
+    pub status: String,
+}
+
+impl PairMeta {
+    pub fn is_online(&self) -> bool {
+        self.status == STATUS_ONLINE
+    }
+}
+
+impl From<&SelectedPair> for PairMeta {
+    fn from(pair: &SelectedPair) -> Self {
+        Self {
+            pair_name: pair.pair_name.clone(),
+            base: pair.base.clone(),
+            quote: pair.quote.clone(),
+            kraken_id: pair.kraken_id.clone(),
+            ws_name: pair.ws_name.clone(),
+            altname: pair.altname.clone(),
+            status: pair.status.clone(),
+        }
+    }
+}
+
+/// Entry for a pair registered without AssetPairs metadata (assumed online,
+/// altname taken from the WebSocket symbol)
+impl From<&PairInfo> for PairMeta {
+    fn from(info: &PairInfo) -> Self {
+        Self {
+            pair_name: info.pair_name.clone(),
+            base: info.base.clone(),
+            quote: info.quote.clone(),
+            kraken_id: info.kraken_id.clone(),
+            ws_name: info.ws_name.clone(),
+            altname: info.ws_name.replace('/', ""),
+            status: STATUS_ONLINE.to_string(),
+        }
+    }
+}
+
+/// The pair that converts `from` into `to`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairRoute {
+    pub pair: String,
+    /// Selling the base (`from` is the base) rather than buying it
+    pub sells_base: bool,
+}
+
+#[derive(Default)]
+pub struct PairRegistry {
+    /// Metadata by pair name
+    pairs: DashMap<String, PairMeta>,
+    /// (base, quote) -> pair name
+    by_currencies: DashMap<(String, String), String>,
+    /// Pair name, wsname, altname and Kraken ID -> pair name
+    by_symbol: DashMap<String, String>,
+}
+
+impl PairRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the metadata of a pair
+    pub fn register(&self, meta: PairMeta) {
+        if let Some(previous) = self.pairs.get(&meta.pair_name).map(|m| m.clone()) {
+            self.unindex(&previous);
+        }
+        self.by_currencies
+            .insert((meta.base.clone(), meta.quote.clone()), meta.pair_name.clone());
+        for symbol in [&meta.pair_name, &meta.ws_name, &meta.altname, &meta.kraken_id] {
+            self.by_symbol.insert(symbol.clone(), meta.pair_name.clone());
+        }
+        self.pairs.insert(meta.pair_name.clone(), meta);
+    }
+
+    /// Add `meta` unless the pair already has an entry
+    pub fn register_if_absent(&self, meta: PairMeta) {
+        if !self.pairs.contains_key(&meta.pair_name) {
+            self.register(meta);
+        }
+    }
+
+    /// Drop a pair's entry (delisted)
+    pub fn remove(&self, pair: &str) -> Option<PairMeta> {
+        let (_, meta) = self.pairs.remove(pair)?;
+        self.unindex(&meta);
+        Some(meta)
+    }
+
+    fn unindex(&self, meta: &PairMeta) {
+        self.by_currencies
+            .remove_if(&(meta.base.clone(), meta.quote.clone()), |_, pair| *pair == meta.pair_name);
+        for symbol in [&meta.pair_name, &meta.ws_name, &meta.altname, &meta.kraken_id] {
+            self.by_symbol.remove_if(symbol, |_, pair| *pair == meta.pair_name);
+        }
+    }
+
+    pub fn get(&self, pair: &str) -> Option<PairMeta> {
+        self.pairs.get(pair).map(|m| m.clone())
+    }
+
+    /// Pair name for any of Kraken's names for it (wsname, altname, ID)
+    pub fn resolve(&self, symbol: &str) -> Option<String> {
+        self.by_symbol.get(symbol).map(|p| p.clone())
+    }
+
+    /// The pair trading `from` into `to`: `from`/`to` is sold, `to`/`from`
+    /// is bought. None when no registered pair links the two.
+    pub fn find_pair(&self, from: &str, to: &str) -> Option<PairRoute> {
+        let key = |base: &str, quote: &str| (base.to_string(), quote.to_string());
+        if let Some(pair) = self.by_currencies.get(&key(from, to)) {
+            return Some(PairRoute { pair: pair.clone(), sells_base: true });
+        }
+        self.by_currencies
+            .get(&key(to, from))
+            .map(|pair| PairRoute { pair: pair.clone(), sells_base: false })
+    }
+
+    /// Every entry, by pair name
+    pub fn all(&self) -> Vec<PairMeta> {
+        let mut pairs: Vec<PairMeta> = self.pairs.iter().map(|m| m.value().clone()).collect();
+        pairs.sort_by(|a, b| a.pair_name.cmp(&b.pair_name));
+        pairs
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn clear(&self) {
+        self.pairs.clear();
+        self.by_currencies.clear();
+        self.by_symbol.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(base: &str, quote: &str, kraken_id: &str, altname: &str) -> PairMeta {
+        PairMeta {
+            pair_name: format!("{}/{}", base, quote),
+            base: base.to_string(),
+            quote: quote.to_string(),
+            kraken_id: kraken_id.to_string(),
+            ws_name: format!("{}/{}", base, quote),
+            altname: altname.to_string(),
+            status: STATUS_ONLINE.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_pair_by_currencies() {
+        let registry = PairRegistry::new();
+        registry.register(meta("BTC", "USD", "XXBTZUSD", "XBTUSD"));
+        registry.register(meta("ETH", "BTC", "XETHXXBT", "ETHXBT"));
+
+        let sell = registry.find_pair("BTC", "USD").unwrap();
+        assert_eq!((sell.pair.as_str(), sell.sells_base), ("BTC/USD", true));
+        let buy = registry.find_pair("BTC", "ETH").unwrap();
+        assert_eq!((buy.pair.as_str(), buy.sells_base), ("ETH/BTC", false));
+        // USD is a quote everywhere, but no pair links it to ETH
+        assert!(registry.find_pair("USD", "ETH").is_none());
+
+        assert_eq!(registry.resolve("XBTUSD").as_deref(), Some("BTC/USD"));
+        assert_eq!(registry.resolve("XETHXXBT").as_deref(), Some("ETH/BTC"));
+
+        // A basic entry doesn't replace the AssetPairs one
+        registry.register_if_absent(meta("BTC", "USD", "XXBTZUSD", "BTCUSD"));
+        assert_eq!(registry.get("BTC/USD").unwrap().altname, "XBTUSD");
+
+        let mut halted = meta("BTC", "USD", "XXBTZUSD", "XBTUSD");
+        halted.status = "cancel_only".to_string();
+        registry.register(halted);
+        assert!(!registry.get("BTC/USD").unwrap().is_online());
+        assert_eq!(registry.len(), 2);
+    }
+}
@@ -0,0 +1,388 @@
+//! Private WebSocket Transport
+//!
+//! The execution engine talks to the private socket through
+//! `PrivateTransport`, which opens a session as a sink of outgoing text
+//! frames and a stream of incoming ones. The live transport is
+//! tokio-tungstenite; tests use `MockTransport`, which hands each session's
+//! two ends to the test so it can read what the engine sent and answer with
+//! recorded Kraken frames (acks, executions, a close).
+#![allow(dead_code)]
+
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::{Sink, SinkExt, StreamExt};
+use std::pin::Pin;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// What arrives on the socket (pings are answered by the transport)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Text(String),
+    /// The server closed the connection
+    Close,
+}
+
+/// Outgoing text frames; an error means the connection is gone
+pub type FrameSink = Pin<Box<dyn Sink<String, Error = String> + Send>>;
+
+/// Incoming frames until the connection ends (Err = transport error)
+pub type FrameStream = BoxStream<'static, Result<Frame, String>>;
+
+pub struct TransportSession {
+    pub sink: FrameSink,
+    pub stream: FrameStream,
+}
+
+pub trait PrivateTransport: Send + Sync {
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<TransportSession, String>>;
+}
+
+/// The real socket
+#[derive(Default)]
+pub struct TungsteniteTransport;
+
+impl PrivateTransport for TungsteniteTransport {
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<TransportSession, String>> {
+        Box::pin(async move {
+            let (ws_stream, _) = connect_async(url).await.map_err(|e| e.to_string())?;
+            let (write, read) = ws_stream.split();
+            let sink = write
+                .with(|text: String| async move { Ok::<_, tokio_tungstenite::tungstenite::Error>(Message::Text(text)) })
+                .sink_map_err(|e| e.to_string());
+            let stream = read.filter_map(|message| async move {
+                match message {
+                    Ok(Message::Text(text)) => Some(Ok(Frame::Text(text))),
+                    Ok(Message::Close(_)) => Some(Ok(Frame::Close)),
+                    Ok(_) => None,
+                    Err(e) => Some(Err(e.to_string())),
+                }
+            });
+            Ok(TransportSession { sink: Box::pin(sink), stream: stream.boxed() })
+        })
+    }
+}
+
+/// Test end of one mock session
+#[cfg(test)]
+pub struct MockPeer {
+    /// Frames the engine sent, in order
+    pub sent: tokio::sync::mpsc::UnboundedReceiver<String>,
+    inbound: tokio::sync::mpsc::UnboundedSender<Result<Frame, String>>,
+}
+
+#[cfg(test)]
+impl MockPeer {
+    /// Deliver a text frame to the engine
+    pub fn send(&self, text: impl Into<String>) {
+        let _ = self.inbound.send(Ok(Frame::Text(text.into())));
+    }
+
+    /// Close the connection from the server side
+    pub fn close(&self) {
+        let _ = self.inbound.send(Ok(Frame::Close));
+    }
+
+    /// Next frame the engine sent, parsed
+    pub async fn next_sent(&mut self) -> serde_json::Value {
+        let text = tokio::time::timeout(std::time::Duration::from_secs(1), self.sent.recv())
+            .await
+            .expect("engine sent nothing")
+            .expect("session dropped");
+        serde_json::from_str(&text).expect("engine sent invalid JSON")
+    }
+}
+
+/// Sessions whose other end goes to the test, one per connect
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockTransport {
+    peers: parking_lot::Mutex<Vec<MockPeer>>,
+    connects: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(test)]
+impl MockTransport {
+    /// The test end of the next unclaimed session
+    pub fn take_peer(&self) -> Option<MockPeer> {
+        let mut peers = self.peers.lock();
+        (!peers.is_empty()).then(|| peers.remove(0))
+    }
+
+    pub fn connects(&self) -> usize {
+        self.connects.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+impl PrivateTransport for MockTransport {
+    fn connect<'a>(&'a self, _url: &'a str) -> BoxFuture<'a, Result<TransportSession, String>> {
+        Box::pin(async move {
+            let (sent_tx, sent) = tokio::sync::mpsc::unbounded_channel::<String>();
+            let (inbound, inbound_rx) = tokio::sync::mpsc::unbounded_channel();
+            self.peers.lock().push(MockPeer { sent, inbound });
+            self.connects.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+            let sink = futures_util::sink::unfold(sent_tx, |tx, text: String| async move {
+                tx.send(text).map_err(|_| "peer dropped".to_string())?;
+                Ok::<_, String>(tx)
+            });
+            let stream = futures_util::stream::unfold(inbound_rx, |mut rx| async move {
+                rx.recv().await.map(|frame| (frame, rx))
+            });
+            Ok(TransportSession { sink: Box::pin(sink), stream: stream.boxed() })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::KrakenAuth;
+    use crate::executor::{ExecutionEngine, ExecutionError, GroupLeg, MakerPolicy, OrderResponse, OrderSide};
+    use crate::order_book::{OrderBookCache, PairInfo};
+    use crate::types::OrderBookLevel;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+
+    async fn connected() -> (Arc<ExecutionEngine>, MockPeer) {
+        connected_with(Arc::new(OrderBookCache::new()), MakerPolicy::default()).await
+    }
+
+    async fn connected_with(cache: Arc<OrderBookCache>, maker: MakerPolicy) -> (Arc<ExecutionEngine>, MockPeer) {
+        let transport = Arc::new(MockTransport::default());
+        let engine = ExecutionEngine::new(Arc::new(KrakenAuth::with_cached_token("tok")), cache)
+            .with_transport(transport.clone())
+            .with_maker_policy(maker);
+        engine.connect().await.unwrap();
+        let mut peer = transport.take_peer().unwrap();
+        let subscribe = peer.next_sent().await;
+        assert_eq!(subscribe["params"], json!({"channel": "executions", "snap_trades": false, "token": "tok"}));
+        (Arc::new(engine), peer)
+    }
+
+    /// Place a sell in the background and return it with the add_order frame
+    async fn place(engine: &Arc<ExecutionEngine>, peer: &mut MockPeer) -> (JoinHandle<Result<OrderResponse, ExecutionError>>, Value) {
+        let engine = Arc::clone(engine);
+        let order = tokio::spawn(async move { engine.place_order("BTC/USD", OrderSide::Sell, 0.01).await });
+        let frame = peer.next_sent().await;
+        assert_eq!(frame["method"], "add_order");
+        (order, frame)
+    }
+
+    fn execution(order_id: &str, cl_ord_id: Option<&str>, status: &str) -> String {
+        let mut exec = json!({
+            "order_id": order_id, "order_status": status, "exec_type": "trade",
+            "cum_qty": "0.01", "avg_price": "50000.0", "cum_cost": "500.0", "fee_usd_equiv": "1.3",
+        });
+        if let Some(id) = cl_ord_id {
+            exec["cl_ord_id"] = json!(id);
+        }
+        json!({"channel": "executions", "type": "update", "data": [exec]}).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_fill_matched_by_order_id_after_ack() {
+        let (engine, mut peer) = connected().await;
+        let (order, frame) = place(&engine, &mut peer).await;
+        let client_id = frame["params"]["cl_ord_id"].as_str().unwrap().to_string();
+        assert_eq!(client_id, format!("arb_{}", frame["req_id"]));
+        assert_eq!(frame["params"]["order_qty"], json!(0.01));
+
+        // The ack carries Kraken's order_id; the execution only that
+        peer.send(json!({"method": "add_order", "success": true, "req_id": frame["req_id"], "result": {"order_id": "OABC", "cl_ord_id": client_id}}).to_string());
+        peer.send(execution("OUNRELATED", None, "filled"));
+        peer.send(execution("OABC", None, "filled"));
+
+        let response = order.await.unwrap().unwrap();
+        assert_eq!((response.order_id.as_str(), response.status.as_str()), ("OABC", "filled"));
+        assert_eq!((response.filled_qty, response.cum_cost, response.fee), (0.01, 500.0, 1.3));
+        assert!(engine.pending_orders().await.is_empty());
+        assert_eq!(engine.get_stats().orders_filled, 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejections_and_server_close() {
+        let (engine, mut peer) = connected().await;
+
+        let (order, frame) = place(&engine, &mut peer).await;
+        peer.send(json!({"method": "add_order", "success": false, "req_id": frame["req_id"], "error": "EOrder:Insufficient funds"}).to_string());
+        match order.await.unwrap() {
+            Err(ExecutionError::OrderRejected(e)) => assert_eq!(e, "EOrder:Insufficient funds"),
+            other => panic!("expected a rejection, got {:?}", other),
+        }
+
+        // Accepted, then canceled by the exchange (matched by cl_ord_id)
+        let (order, frame) = place(&engine, &mut peer).await;
+        let client_id = frame["params"]["cl_ord_id"].as_str().unwrap();
+        peer.send(execution("ODEF", Some(client_id), "canceled"));
+        match order.await.unwrap() {
+            Err(ExecutionError::OrderRejected(e)) => assert_eq!(e, "Order canceled"),
+            other => panic!("expected a cancel, got {:?}", other),
+        }
+        assert_eq!(engine.get_stats().orders_failed, 2);
+
+        peer.close();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while engine.is_connected() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("close should disconnect the engine");
+    }
+
+    #[tokio::test]
+    async fn test_leg_group_batches_same_pair_legs() {
+        let (engine, mut peer) = connected_with(btc_usd(), MakerPolicy::default()).await;
+        let leg = |leg_index: usize, from: &str, amount: f64| GroupLeg {
+            leg_index,
+            from_currency: from.to_string(),
+            to_currency: "USD".to_string(),
+            amount,
+        };
+        let group = {
+            let engine = Arc::clone(&engine);
+            tokio::spawn(async move { engine.execute_leg_group(&[leg(0, "DOGE", 5.0), leg(1, "BTC", 0.01), leg(2, "BTC", 0.02)]).await })
+        };
+
+        // Both BTC legs go out in one batch_add, one cl_ord_id per order
+        let batch = peer.next_sent().await;
+        assert_eq!(batch["method"], "batch_add");
+        assert_eq!((batch["params"]["symbol"].clone(), batch["params"]["token"].clone()), (json!("BTC/USD"), json!("tok")));
+        let orders = batch["params"]["orders"].as_array().unwrap();
+        let ids: Vec<String> = orders.iter().map(|o| o["cl_ord_id"].as_str().unwrap().to_string()).collect();
+        assert_eq!(ids, vec![format!("arb_{}_0", batch["req_id"]), format!("arb_{}_1", batch["req_id"])]);
+        assert_eq!(orders.iter().map(|o| o["order_qty"].clone()).collect::<Vec<_>>(), vec![json!(0.01), json!(0.02)]);
+        assert!(orders.iter().all(|o| o["side"] == "sell" && o["order_type"] == "market"));
+
+        // The ack maps each cl_ord_id to an order_id; fills arrive out of order
+        peer.send(json!({"method": "batch_add", "success": true, "req_id": batch["req_id"], "result": [
+            {"order_id": "OFIRST", "cl_ord_id": ids[0]},
+            {"order_id": "OSECOND", "cl_ord_id": ids[1]},
+        ]}).to_string());
+        peer.send(execution("OSECOND", None, "filled"));
+        peer.send(execution("OFIRST", None, "filled"));
+
+        let results = group.await.unwrap();
+        assert_eq!(results.iter().map(|r| r.leg_index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert!(!results[0].success && results[0].pair.is_empty());
+        assert_eq!((results[1].order_id.as_str(), results[2].order_id.as_str()), ("OFIRST", "OSECOND"));
+        assert!(results[1].success && results[2].success);
+        assert!(engine.pending_orders().await.is_empty());
+        assert_eq!(engine.get_stats().orders_filled, 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_batch_fails_every_order() {
+        let (engine, mut peer) = connected().await;
+        let batch = {
+            let engine = Arc::clone(&engine);
+            tokio::spawn(async move { engine.place_order_batch("BTC/USD", &[(OrderSide::Sell, 0.01), (OrderSide::Buy, 100.0)]).await })
+        };
+        let frame = peer.next_sent().await;
+        assert_eq!(frame["method"], "batch_add");
+        peer.send(json!({"method": "batch_add", "success": false, "req_id": frame["req_id"], "error": "EOrder:Insufficient funds"}).to_string());
+
+        let results = batch.await.unwrap().unwrap();
+        assert_eq!(results.len(), 2);
+        for result in results {
+            match result {
+                Err(ExecutionError::OrderRejected(e)) => assert_eq!(e, "EOrder:Insufficient funds"),
+                other => panic!("expected a rejection, got {:?}", other),
+            }
+        }
+        assert!(engine.pending_orders().await.is_empty());
+        assert_eq!(engine.get_stats().orders_failed, 2);
+    }
+
+    /// Cache with BTC/USD registered
+    fn btc_usd() -> Arc<OrderBookCache> {
+        let cache = Arc::new(OrderBookCache::new());
+        cache.register_pair(PairInfo {
+            pair_name: "BTC/USD".to_string(),
+            base: "BTC".to_string(),
+            quote: "USD".to_string(),
+            kraken_id: "XBTUSD".to_string(),
+            ws_name: "BTC/USD".to_string(),
+            volume_24h: 1_000_000.0,
+        });
+        cache
+    }
+
+    /// BTC/USD quoted at `bid` / `ask`
+    fn quote(cache: &OrderBookCache, bid: f64, ask: f64, seq: u64) {
+        cache.update_snapshot("BTC/USD", vec![OrderBookLevel { price: bid, qty: 1.0 }], vec![OrderBookLevel { price: ask, qty: 1.0 }], seq);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_maker_order_amended_then_replaced() {
+        let cache = btc_usd();
+        quote(&cache, 49_990.0, 50_010.0, 1);
+        let policy = MakerPolicy { enabled: true, reprice_ms: 500, max_reprices: 3 };
+        let (engine, mut peer) = connected_with(Arc::clone(&cache), policy).await;
+
+        let chase = {
+            let engine = Arc::clone(&engine);
+            tokio::spawn(async move { engine.chase_maker_order("BTC/USD", OrderSide::Sell, 0.01).await })
+        };
+        let first = peer.next_sent().await;
+        assert_eq!(first["params"]["post_only"], json!(true));
+        assert_eq!(first["params"]["limit_price"], json!(50_010.0));
+        let first_id = first["params"]["cl_ord_id"].as_str().unwrap().to_string();
+        peer.send(json!({"method": "add_order", "success": true, "req_id": first["req_id"], "result": {"order_id": "OMAKER", "cl_ord_id": first_id}}).to_string());
+
+        // The ask moves down: the resting order follows it in place
+        quote(&cache, 49_980.0, 50_000.0, 2);
+        let amend = peer.next_sent().await;
+        assert_eq!(amend["method"], "amend_order");
+        assert_eq!((amend["params"]["order_id"].clone(), amend["params"]["limit_price"].clone()), (json!("OMAKER"), json!(50_000.0)));
+        peer.send(json!({"method": "amend_order", "success": true, "req_id": amend["req_id"], "result": {"order_id": "OMAKER"}}).to_string());
+
+        // A rejected amend cancels the order and replaces what it didn't fill
+        quote(&cache, 49_970.0, 49_990.0, 3);
+        let amend = peer.next_sent().await;
+        peer.send(json!({"method": "amend_order", "success": false, "req_id": amend["req_id"], "error": "EOrder:Invalid price"}).to_string());
+        let cancel = peer.next_sent().await;
+        assert_eq!((cancel["method"].clone(), cancel["params"]["cl_ord_id"].clone()), (json!("cancel_order"), json!([first_id])));
+        peer.send(json!({"channel": "executions", "type": "update", "data": [{
+            "order_id": "OMAKER", "cl_ord_id": first_id, "order_status": "canceled", "exec_type": "canceled",
+            "cum_qty": "0.004", "avg_price": "50000.0", "cum_cost": "200.0", "fee_usd_equiv": "0.32",
+        }]}).to_string());
+
+        let replacement = peer.next_sent().await;
+        assert_eq!(replacement["method"], "add_order");
+        assert_eq!(replacement["params"]["post_only"], json!(true));
+        assert_eq!((replacement["params"]["order_qty"].clone(), replacement["params"]["limit_price"].clone()), (json!(0.006), json!(49_990.0)));
+        let second_id = replacement["params"]["cl_ord_id"].as_str().unwrap().to_string();
+        peer.send(json!({"channel": "executions", "type": "update", "data": [{
+            "order_id": "OMAKER2", "cl_ord_id": second_id, "order_status": "filled", "exec_type": "trade",
+            "cum_qty": "0.006", "avg_price": "49990.0", "cum_cost": "299.94", "fee_usd_equiv": "0.48",
+        }]}).to_string());
+
+        let response = chase.await.unwrap().unwrap();
+        assert_eq!((response.order_id.as_str(), response.status.as_str()), ("OMAKER2", "filled"));
+        assert!((response.filled_qty - 0.01).abs() < 1e-12);
+        assert!((response.cum_cost - 499.94).abs() < 1e-9 && (response.fee - 0.8).abs() < 1e-9);
+        assert!((response.avg_price - 49_994.0).abs() < 1e-6);
+        let stats = engine.get_stats();
+        assert_eq!((stats.amends_sent, stats.amends_succeeded, stats.amends_failed), (2, 1, 1));
+        assert_eq!((stats.maker_orders_attempted, stats.maker_orders_filled, stats.maker_replacements), (2, 1, 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cleanup_drops_abandoned_orders() {
+        let (engine, mut peer) = connected().await;
+        let (order, _) = place(&engine, &mut peer).await;
+        // The caller goes away before its own timeout can remove the entry
+        order.abort();
+        assert_eq!(engine.pending_orders().await.len(), 1);
+
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        assert_eq!(engine.pending_orders().await.len(), 1, "too young to sweep");
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(engine.pending_orders().await.is_empty());
+        assert_eq!(engine.get_stats().orders_swept, 1);
+    }
+}
@@ -0,0 +1,432 @@
+//! Exchange Reconciliation
+//!
+//! Periodically compares what the engine believes with what Kraken reports
+//! (OpenOrders, TradesHistory, Balance):
+//! - orphan order: open on the exchange, but neither pending in the
+//!   executor nor seen in the fill journal
+//! - phantom order: pending in the executor or still open by the journal's
+//!   last status, but not open on the exchange
+//! - untracked trade: an exchange trade for an order we never journaled
+//! - balance drift: exchange balance differs from the HFT loop's running
+//!   snapshot by more than the tolerance
+//!
+//! Orders and trades younger than `GRACE_MS` are left out on both sides,
+//! since their executions message may still be in flight. A discrepancy is notified once,
+//! when it first appears; it can be notified again after it has cleared.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default seconds between runs (0 = off)
+pub const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+/// Default relative balance difference tolerated before flagging drift
+pub const DEFAULT_BALANCE_TOLERANCE_PCT: f64 = 1.0;
+
+/// Orders this recent are still settling and never flagged
+pub const GRACE_MS: i64 = 10_000;
+
+/// Order states after which nothing is open any more
+const TERMINAL_STATUSES: [&str; 3] = ["filled", "canceled", "expired"];
+
+pub fn is_terminal(status: &str) -> bool {
+    TERMINAL_STATUSES.contains(&status)
+}
+
+/// An order Kraken lists as open
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeOrder {
+    pub order_id: String,
+    pub cl_ord_id: Option<String>,
+    pub pair: String,
+    pub side: String,
+    pub volume: f64,
+    pub volume_executed: f64,
+    pub opened_at: Option<DateTime<Utc>>,
+}
+
+/// A trade from Kraken's history
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeTrade {
+    pub trade_id: String,
+    pub order_id: String,
+    pub pair: String,
+    pub side: String,
+    pub volume: f64,
+    pub price: f64,
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// What Kraken reports
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeState {
+    pub open_orders: Vec<ExchangeOrder>,
+    pub trades: Vec<ExchangeTrade>,
+    pub balances: HashMap<String, f64>,
+}
+
+/// An order the engine considers open
+#[derive(Debug, Clone, Serialize)]
+pub struct InternalOrder {
+    pub order_id: Option<String>,
+    pub cl_ord_id: Option<String>,
+    pub status: String,
+    /// "pending" (executor waiting on it) or "journal"
+    pub source: &'static str,
+}
+
+/// What the engine believes
+#[derive(Debug, Clone, Default)]
+pub struct InternalState {
+    pub open_orders: Vec<InternalOrder>,
+    /// Every order_id / cl_ord_id the executor or journal knows about
+    pub known_ids: HashSet<String>,
+    /// HFT loop balance snapshot (None when it keeps none)
+    pub balances: Option<HashMap<String, f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    OrphanOrder {
+        order_id: String,
+        cl_ord_id: Option<String>,
+        pair: String,
+        side: String,
+        volume: f64,
+    },
+    PhantomOrder {
+        order_id: Option<String>,
+        cl_ord_id: Option<String>,
+        status: String,
+        source: &'static str,
+    },
+    UntrackedTrade {
+        trade_id: String,
+        order_id: String,
+        pair: String,
+        side: String,
+        volume: f64,
+        price: f64,
+    },
+    BalanceDrift {
+        currency: String,
+        expected: f64,
+        actual: f64,
+        diff_pct: f64,
+    },
+}
+
+impl Discrepancy {
+    /// Stable identity across runs (drift is keyed by currency only)
+    fn key(&self) -> String {
+        match self {
+            Discrepancy::OrphanOrder { order_id, .. } => format!("orphan:{}", order_id),
+            Discrepancy::PhantomOrder { order_id, cl_ord_id, .. } => {
+                format!("phantom:{}", order_id.as_deref().or(cl_ord_id.as_deref()).unwrap_or_default())
+            }
+            Discrepancy::UntrackedTrade { trade_id, .. } => format!("trade:{}", trade_id),
+            Discrepancy::BalanceDrift { currency, .. } => format!("drift:{}", currency),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Discrepancy::OrphanOrder { order_id, pair, side, volume, .. } => {
+                format!("Orphan order {}: {} {} {} open on Kraken, unknown to the engine", order_id, side, volume, pair)
+            }
+            Discrepancy::PhantomOrder { order_id, cl_ord_id, status, .. } => format!(
+                "Phantom order {}: engine has it {}, Kraken doesn't list it open",
+                order_id.as_deref().or(cl_ord_id.as_deref()).unwrap_or("?"),
+                status
+            ),
+            Discrepancy::UntrackedTrade { trade_id, order_id, pair, .. } => {
+                format!("Untracked trade {} on {} for order {} never seen on the executions channel", trade_id, pair, order_id)
+            }
+            Discrepancy::BalanceDrift { currency, expected, actual, diff_pct } => {
+                format!("{} balance drift {:.2}%: engine {:.8}, Kraken {:.8}", currency, diff_pct, expected, actual)
+            }
+        }
+    }
+}
+
+/// Compare engine and exchange state as of `now`
+pub fn compare(
+    internal: &InternalState,
+    exchange: &ExchangeState,
+    balance_tolerance_pct: f64,
+    now: DateTime<Utc>,
+) -> Vec<Discrepancy> {
+    let mut found = Vec::new();
+    let settled = |opened_at: Option<DateTime<Utc>>| opened_at.is_none_or(|t| (now - t).num_milliseconds() > GRACE_MS);
+
+    let exchange_open: HashSet<&str> = exchange.open_orders
+        .iter()
+        .flat_map(|o| std::iter::once(o.order_id.as_str()).chain(o.cl_ord_id.as_deref()))
+        .collect();
+
+    for order in exchange.open_orders.iter().filter(|o| settled(o.opened_at)) {
+        let known = internal.known_ids.contains(&order.order_id)
+            || order.cl_ord_id.as_ref().is_some_and(|c| internal.known_ids.contains(c));
+        if !known {
+            found.push(Discrepancy::OrphanOrder {
+                order_id: order.order_id.clone(),
+                cl_ord_id: order.cl_ord_id.clone(),
+                pair: order.pair.clone(),
+                side: order.side.clone(),
+                volume: order.volume - order.volume_executed,
+            });
+        }
+    }
+
+    for order in &internal.open_orders {
+        let listed = order.order_id.as_deref().is_some_and(|id| exchange_open.contains(id))
+            || order.cl_ord_id.as_deref().is_some_and(|id| exchange_open.contains(id));
+        if !listed {
+            found.push(Discrepancy::PhantomOrder {
+                order_id: order.order_id.clone(),
+                cl_ord_id: order.cl_ord_id.clone(),
+                status: order.status.clone(),
+                source: order.source,
+            });
+        }
+    }
+
+    for trade in exchange.trades.iter().filter(|t| settled(t.time)) {
+        if !internal.known_ids.contains(&trade.order_id) {
+            found.push(Discrepancy::UntrackedTrade {
+                trade_id: trade.trade_id.clone(),
+                order_id: trade.order_id.clone(),
+                pair: trade.pair.clone(),
+                side: trade.side.clone(),
+                volume: trade.volume,
+                price: trade.price,
+            });
+        }
+    }
+
+    if let Some(expected) = &internal.balances {
+        let mut currencies: Vec<&String> = expected.keys().chain(exchange.balances.keys()).collect();
+        currencies.sort();
+        currencies.dedup();
+        for currency in currencies {
+            let engine = expected.get(currency).copied().unwrap_or(0.0);
+            let actual = exchange.balances.get(currency).copied().unwrap_or(0.0);
+            let scale = engine.abs().max(actual.abs());
+            if scale < 1e-8 {
+                continue;
+            }
+            let diff_pct = (actual - engine).abs() / scale * 100.0;
+            if diff_pct > balance_tolerance_pct {
+                found.push(Discrepancy::BalanceDrift { currency: currency.clone(), expected: engine, actual, diff_pct });
+            }
+        }
+    }
+
+    found
+}
+
+/// Number or numeric string
+fn number(value: Option<&Value>) -> f64 {
+    value
+        .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+        .unwrap_or(0.0)
+}
+
+fn unix_time(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    let secs = number(value);
+    (secs > 0.0).then(|| DateTime::from_timestamp_millis((secs * 1000.0) as i64)).flatten()
+}
+
+/// Parse the `result` of Kraken's OpenOrders
+pub fn parse_open_orders(result: &Value) -> Vec<ExchangeOrder> {
+    let Some(open) = result.get("open").and_then(|o| o.as_object()) else {
+        return Vec::new();
+    };
+    open.iter()
+        .map(|(txid, order)| {
+            let descr = order.get("descr");
+            ExchangeOrder {
+                order_id: txid.clone(),
+                cl_ord_id: order.get("cl_ord_id").and_then(|c| c.as_str()).map(str::to_string),
+                pair: descr.and_then(|d| d.get("pair")).and_then(|p| p.as_str()).unwrap_or_default().to_string(),
+                side: descr.and_then(|d| d.get("type")).and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+                volume: number(order.get("vol")),
+                volume_executed: number(order.get("vol_exec")),
+                opened_at: unix_time(order.get("opentm")),
+            }
+        })
+        .collect()
+}
+
+/// Parse the `result` of Kraken's TradesHistory
+pub fn parse_trades(result: &Value) -> Vec<ExchangeTrade> {
+    let Some(trades) = result.get("trades").and_then(|t| t.as_object()) else {
+        return Vec::new();
+    };
+    trades.iter()
+        .map(|(txid, trade)| ExchangeTrade {
+            trade_id: txid.clone(),
+            order_id: trade.get("ordertxid").and_then(|o| o.as_str()).unwrap_or_default().to_string(),
+            pair: trade.get("pair").and_then(|p| p.as_str()).unwrap_or_default().to_string(),
+            side: trade.get("type").and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+            volume: number(trade.get("vol")),
+            price: number(trade.get("price")),
+            time: unix_time(trade.get("time")),
+        })
+        .collect()
+}
+
+/// Outcome of one run
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationReport {
+    pub ran_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub exchange_open_orders: usize,
+    pub exchange_trades: usize,
+    pub internal_open_orders: usize,
+    pub discrepancies: Vec<Discrepancy>,
+    /// Discrepancies not present in the previous run (these were notified)
+    pub new_discrepancies: usize,
+    /// Set when Kraken couldn't be queried (nothing compared)
+    pub error: Option<String>,
+}
+
+/// Reconciliation status for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationStatus {
+    pub interval_secs: u64,
+    pub runs: u64,
+    pub last: Option<ReconciliationReport>,
+}
+
+/// Keeps the last report and which discrepancies were already notified
+pub struct Reconciler {
+    interval_secs: u64,
+    balance_tolerance_pct: f64,
+    runs: AtomicU64,
+    last: RwLock<Option<ReconciliationReport>>,
+    open_keys: RwLock<HashSet<String>>,
+}
+
+impl Reconciler {
+    pub fn new(interval_secs: u64, balance_tolerance_pct: f64) -> Self {
+        Self {
+            interval_secs,
+            balance_tolerance_pct,
+            runs: AtomicU64::new(0),
+            last: RwLock::new(None),
+            open_keys: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Create from RECONCILE_INTERVAL_SECS and RECONCILE_BALANCE_TOLERANCE_PCT
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        Self::new(
+            env("RECONCILE_INTERVAL_SECS").unwrap_or(DEFAULT_INTERVAL_SECS),
+            env("RECONCILE_BALANCE_TOLERANCE_PCT").unwrap_or(DEFAULT_BALANCE_TOLERANCE_PCT),
+        )
+    }
+
+    pub fn interval_secs(&self) -> u64 {
+        self.interval_secs
+    }
+
+    pub fn balance_tolerance_pct(&self) -> f64 {
+        self.balance_tolerance_pct
+    }
+
+    /// Store a completed run, counting what is new since the previous one.
+    /// Returns the new discrepancies (the caller notifies those).
+    pub fn record(&self, report: &mut ReconciliationReport) -> Vec<Discrepancy> {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        let fresh = if report.error.is_some() {
+            Vec::new()
+        } else {
+            let keys: HashSet<String> = report.discrepancies.iter().map(|d| d.key()).collect();
+            let mut open = self.open_keys.write();
+            let fresh: Vec<Discrepancy> = report.discrepancies
+                .iter()
+                .filter(|d| !open.contains(&d.key()))
+                .cloned()
+                .collect();
+            *open = keys;
+            fresh
+        };
+        report.new_discrepancies = fresh.len();
+        *self.last.write() = Some(report.clone());
+        fresh
+    }
+
+    pub fn status(&self) -> ReconciliationStatus {
+        ReconciliationStatus {
+            interval_secs: self.interval_secs,
+            runs: self.runs.load(Ordering::Relaxed),
+            last: self.last.read().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_orphan_phantom_and_drift() {
+        let exchange = ExchangeState {
+            open_orders: parse_open_orders(&json!({"open": {
+                "OAAA-1": {"cl_ord_id": "arb_1", "descr": {"pair": "XBTUSD", "type": "buy"}, "vol": "0.01", "vol_exec": "0", "opentm": 1_700_000_000.5},
+                "OBBB-2": {"descr": {"pair": "ETHUSD", "type": "sell"}, "vol": "1.0", "vol_exec": "0.25"}
+            }})),
+            trades: parse_trades(&json!({"trades": {
+                "TX-1": {"ordertxid": "OAAA-1", "pair": "XXBTZUSD", "type": "buy", "vol": "0.005", "price": "50000", "time": 1_700_000_001.0},
+                "TX-2": {"ordertxid": "OZZZ-9", "pair": "XETHZUSD", "type": "sell", "vol": "0.5", "price": "2500"}
+            }})),
+            balances: [("USD".to_string(), 1_000.0), ("BTC".to_string(), 0.10)].into(),
+        };
+        assert_eq!(exchange.open_orders.len(), 2);
+        assert!(exchange.open_orders.iter().any(|o| o.opened_at.is_some()));
+
+        let internal = InternalState {
+            open_orders: vec![
+                InternalOrder { order_id: None, cl_ord_id: Some("arb_1".to_string()), status: "pending".to_string(), source: "pending" },
+                InternalOrder { order_id: Some("OCCC-3".to_string()), cl_ord_id: None, status: "new".to_string(), source: "journal" },
+            ],
+            known_ids: ["arb_1", "OAAA-1", "OCCC-3"].iter().map(|s| s.to_string()).collect(),
+            balances: Some([("USD".to_string(), 1_005.0), ("BTC".to_string(), 0.12)].into()),
+        };
+
+        let found = compare(&internal, &exchange, 1.0, Utc::now());
+        let kinds: Vec<String> = found.iter().map(|d| d.key()).collect();
+        assert_eq!(kinds, vec!["orphan:OBBB-2", "phantom:OCCC-3", "trade:TX-2", "drift:BTC"]);
+        assert!(matches!(&found[0], Discrepancy::OrphanOrder { volume, .. } if *volume == 0.75));
+
+        // Notified once while it persists, again after clearing
+        let reconciler = Reconciler::new(60, 1.0);
+        let report = |discrepancies: Vec<Discrepancy>| ReconciliationReport {
+            ran_at: Utc::now(),
+            duration_ms: 0,
+            exchange_open_orders: 0,
+            exchange_trades: 0,
+            internal_open_orders: 0,
+            discrepancies,
+            new_discrepancies: 0,
+            error: None,
+        };
+        assert_eq!(reconciler.record(&mut report(found.clone())).len(), 4);
+        assert_eq!(reconciler.record(&mut report(found[..2].to_vec())).len(), 0);
+        assert_eq!(reconciler.record(&mut report(found.clone())).len(), 2);
+        let status = reconciler.status();
+        assert_eq!(status.runs, 3);
+        assert_eq!(status.last.unwrap().new_discrepancies, 2);
+    }
+}
@@ -0,0 +1,231 @@
+//! WebSocket Reconnection Policy
+//!
+//! Shared by the public market-data socket (ws_v2) and the private execution
+//! socket (executor):
+//! - exponential backoff from `base_delay_ms` up to `max_delay_ms`
+//! - ± `jitter_pct` randomization so both sockets don't retry in lockstep
+//! - give up after `max_attempts` consecutive failures (0 = retry forever)
+//! - a connection that stayed up for `reset_after_secs` resets the backoff
+//!
+//! Each socket owns a `ReconnectTracker` that applies the policy and keeps a
+//! short history of disconnects for the status API.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use rand::Rng;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Disconnects kept per socket for the status API
+const HISTORY_LEN: usize = 20;
+
+/// Backoff settings
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconnectPolicy {
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Consecutive failed attempts before giving up (0 = never give up)
+    pub max_attempts: u32,
+    /// Random spread applied to each delay, in percent
+    pub jitter_pct: f64,
+    /// Uptime after which a connection counts as healthy and resets the backoff
+    pub reset_after_secs: u64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: 5_000,
+            max_delay_ms: 60_000,
+            max_attempts: 0,
+            jitter_pct: 20.0,
+            reset_after_secs: 60,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Create from WS_RECONNECT_* (unset values keep the defaults)
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            base_delay_ms: env("WS_RECONNECT_BASE_MS").unwrap_or(defaults.base_delay_ms),
+            max_delay_ms: env("WS_RECONNECT_MAX_MS").unwrap_or(defaults.max_delay_ms),
+            max_attempts: env("WS_RECONNECT_MAX_ATTEMPTS").unwrap_or(defaults.max_attempts),
+            jitter_pct: env("WS_RECONNECT_JITTER_PCT").unwrap_or(defaults.jitter_pct),
+            reset_after_secs: env("WS_RECONNECT_RESET_SECS").unwrap_or(defaults.reset_after_secs),
+        }
+    }
+
+    /// Delay before the given attempt (1-based) without jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(16);
+        let delay = self.base_delay_ms.saturating_mul(1u64 << exp).min(self.max_delay_ms.max(self.base_delay_ms));
+        Duration::from_millis(delay)
+    }
+
+    /// Randomly jittered delay before the given attempt (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.jittered(self.backoff(attempt), rand::thread_rng().gen_range(-1.0..=1.0))
+    }
+
+    /// Apply jitter; `unit` is a random value in [-1, 1]
+    fn jittered(&self, delay: Duration, unit: f64) -> Duration {
+        let factor = 1.0 + unit.clamp(-1.0, 1.0) * self.jitter_pct.max(0.0) / 100.0;
+        Duration::from_millis((delay.as_millis() as f64 * factor).max(0.0) as u64)
+    }
+}
+
+/// One disconnect and what the tracker decided
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconnectEvent {
+    pub at: DateTime<Utc>,
+    pub reason: String,
+    /// Consecutive failures including this one
+    pub attempt: u32,
+    /// Wait before the next attempt (None = gave up)
+    pub delay_ms: Option<u64>,
+}
+
+/// Snapshot for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconnectStats {
+    pub socket: &'static str,
+    pub policy: ReconnectPolicy,
+    pub connected: bool,
+    pub connects: u64,
+    pub disconnects: u64,
+    pub consecutive_failures: u32,
+    pub gave_up: bool,
+    pub last_connected_at: Option<DateTime<Utc>>,
+    pub history: Vec<ReconnectEvent>,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    connected_since: Option<Instant>,
+    last_connected_at: Option<DateTime<Utc>>,
+    connects: u64,
+    disconnects: u64,
+    consecutive_failures: u32,
+    gave_up: bool,
+    history: VecDeque<ReconnectEvent>,
+}
+
+/// Applies a ReconnectPolicy to one socket
+pub struct ReconnectTracker {
+    socket: &'static str,
+    policy: ReconnectPolicy,
+    state: RwLock<TrackerState>,
+}
+
+impl ReconnectTracker {
+    pub fn new(socket: &'static str, policy: ReconnectPolicy) -> Self {
+        Self {
+            socket,
+            policy,
+            state: RwLock::new(TrackerState::default()),
+        }
+    }
+
+    /// Record a successful connect
+    pub fn connected(&self) {
+        let mut state = self.state.write();
+        state.connected_since = Some(Instant::now());
+        state.last_connected_at = Some(Utc::now());
+        state.connects += 1;
+        state.gave_up = false;
+    }
+
+    /// Record a disconnect or failed connect attempt.
+    /// Returns how long to wait before retrying, or None to give up.
+    pub fn disconnected(&self, reason: &str) -> Option<Duration> {
+        let unit = rand::thread_rng().gen_range(-1.0..=1.0);
+        self.disconnected_with(reason, unit)
+    }
+
+    fn disconnected_with(&self, reason: &str, jitter_unit: f64) -> Option<Duration> {
+        let mut state = self.state.write();
+
+        // A connection that stayed up long enough starts the backoff over
+        if let Some(since) = state.connected_since.take() {
+            state.disconnects += 1;
+            if since.elapsed() >= Duration::from_secs(self.policy.reset_after_secs) {
+                state.consecutive_failures = 0;
+            }
+        }
+        state.consecutive_failures += 1;
+        let attempt = state.consecutive_failures;
+
+        let delay = if self.policy.max_attempts > 0 && attempt > self.policy.max_attempts {
+            state.gave_up = true;
+            None
+        } else {
+            Some(self.policy.jittered(self.policy.backoff(attempt), jitter_unit))
+        };
+
+        if state.history.len() == HISTORY_LEN {
+            state.history.pop_front();
+        }
+        state.history.push_back(ReconnectEvent {
+            at: Utc::now(),
+            reason: reason.to_string(),
+            attempt,
+            delay_ms: delay.map(|d| d.as_millis() as u64),
+        });
+        delay
+    }
+
+    pub fn stats(&self) -> ReconnectStats {
+        let state = self.state.read();
+        ReconnectStats {
+            socket: self.socket,
+            policy: self.policy.clone(),
+            connected: state.connected_since.is_some(),
+            connects: state.connects,
+            disconnects: state.disconnects,
+            consecutive_failures: state.consecutive_failures,
+            gave_up: state.gave_up,
+            last_connected_at: state.last_connected_at,
+            history: state.history.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_give_up_and_reset() {
+        let policy = ReconnectPolicy {
+            base_delay_ms: 1_000,
+            max_delay_ms: 5_000,
+            max_attempts: 4,
+            jitter_pct: 20.0,
+            reset_after_secs: 0,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(1_000));
+        assert_eq!(policy.backoff(3), Duration::from_millis(4_000));
+        assert_eq!(policy.backoff(10), Duration::from_millis(5_000));
+        assert_eq!(policy.jittered(Duration::from_millis(1_000), 1.0), Duration::from_millis(1_200));
+
+        let tracker = ReconnectTracker::new("test", policy);
+        let delays: Vec<Option<u64>> = (0..5)
+            .map(|_| tracker.disconnected_with("refused", 0.0).map(|d| d.as_millis() as u64))
+            .collect();
+        assert_eq!(delays, vec![Some(1_000), Some(2_000), Some(4_000), Some(5_000), None]);
+        assert!(tracker.stats().gave_up);
+
+        // Healthy connection (reset_after_secs = 0) starts over
+        tracker.connected();
+        assert_eq!(tracker.disconnected_with("closed", 0.0), Some(Duration::from_millis(1_000)));
+        let stats = tracker.stats();
+        assert_eq!((stats.connects, stats.disconnects, stats.history.len()), (1, 1, 6));
+    }
+}
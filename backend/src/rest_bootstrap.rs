@@ -0,0 +1,295 @@
+//! REST Order Book Bootstrap
+//!
+//! At startup the public socket subscribes every selected pair at once and
+//! Kraken can take several seconds to send all the book snapshots, during
+//! which the scanner has nothing to work with. When REST_BOOTSTRAP is on,
+//! pairs still without a book REST_BOOTSTRAP_DELAY_MS after subscribing
+//! (highest ranked first, at most REST_BOOTSTRAP_PAIRS of them) are fetched
+//! from the public Depth endpoint, REST_BOOTSTRAP_CONCURRENCY at a time.
+//!
+//! A REST book is marked as REST-sourced in the OrderBookCache until the
+//! first WebSocket snapshot for the pair replaces it. A REST response never
+//! overwrites a book the socket has already delivered.
+#![allow(dead_code)]
+
+use crate::order_book::OrderBookCache;
+use crate::types::{BookDelta, OrderBookLevel};
+use futures_util::StreamExt;
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Get Depth API path from environment or use default
+fn get_depth_path() -> String {
+    std::env::var("KRAKEN_DEPTH_PATH")
+        .unwrap_or_else(|_| "/0/public/Depth".to_string())
+}
+
+fn get_kraken_rest_url() -> String {
+    std::env::var("KRAKEN_REST_URL")
+        .unwrap_or_else(|_| "https://api.kraken.com".to_string())
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BootstrapPolicy {
+    pub enabled: bool,
+    /// Wait this long for WebSocket snapshots before fetching
+    pub delay_ms: u64,
+    /// Most pairs fetched over REST
+    pub max_pairs: usize,
+    /// Depth requests in flight at once
+    pub concurrency: usize,
+    /// Levels per side requested
+    pub depth: usize,
+}
+
+impl Default for BootstrapPolicy {
+    fn default() -> Self {
+        Self { enabled: true, delay_ms: 2_000, max_pairs: 30, concurrency: 4, depth: 25 }
+    }
+}
+
+impl BootstrapPolicy {
+    /// Create from REST_BOOTSTRAP, REST_BOOTSTRAP_DELAY_MS, REST_BOOTSTRAP_PAIRS
+    /// and REST_BOOTSTRAP_CONCURRENCY
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        Self {
+            enabled: std::env::var("REST_BOOTSTRAP")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            delay_ms: env("REST_BOOTSTRAP_DELAY_MS").unwrap_or(defaults.delay_ms),
+            max_pairs: env("REST_BOOTSTRAP_PAIRS").map_or(defaults.max_pairs, |v| v as usize),
+            concurrency: env("REST_BOOTSTRAP_CONCURRENCY").map_or(defaults.concurrency, |v| (v as usize).max(1)),
+            depth: defaults.depth,
+        }
+    }
+}
+
+/// Last bootstrap for the API
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BootstrapStatus {
+    /// Pairs still without a book when the delay ran out
+    pub pending: u64,
+    /// Books written from REST
+    pub fetched: u64,
+    /// Requests that failed or returned nothing usable
+    pub failed: u64,
+    /// REST books dropped because the socket snapshot arrived first
+    pub superseded: u64,
+    pub duration_ms: Option<u64>,
+    /// Pairs still served from REST, waiting for their WebSocket snapshot
+    pub rest_sourced: Vec<String>,
+}
+
+pub struct RestBootstrap {
+    policy: BootstrapPolicy,
+    cache: Arc<OrderBookCache>,
+    client: Client,
+    pending: AtomicU64,
+    fetched: AtomicU64,
+    failed: AtomicU64,
+    superseded: AtomicU64,
+    duration_ms: RwLock<Option<u64>>,
+}
+
+impl RestBootstrap {
+    pub fn new(cache: Arc<OrderBookCache>, policy: BootstrapPolicy) -> Self {
+        Self {
+            policy,
+            cache,
+            client: Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            pending: AtomicU64::new(0),
+            fetched: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            superseded: AtomicU64::new(0),
+            duration_ms: RwLock::new(None),
+        }
+    }
+
+    pub fn from_env(cache: Arc<OrderBookCache>) -> Self {
+        Self::new(cache, BootstrapPolicy::from_env())
+    }
+
+    pub fn policy(&self) -> BootstrapPolicy {
+        self.policy
+    }
+
+    /// After the delay, fetch books for the pairs in `priority` order that
+    /// the socket hasn't filled yet. Each book written is announced on
+    /// `events` like a socket snapshot so the loop scans it.
+    pub async fn run(&self, priority: Vec<String>, events: Option<mpsc::Sender<BookDelta>>) {
+        if !self.policy.enabled {
+            return;
+        }
+        for counter in [&self.pending, &self.fetched, &self.failed, &self.superseded] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        *self.duration_ms.write() = None;
+        tokio::time::sleep(Duration::from_millis(self.policy.delay_ms)).await;
+
+        let pending: Vec<String> = priority
+            .into_iter()
+            .filter(|pair| self.cache.get_order_book(pair).is_some_and(|b| b.bids.is_empty() && b.asks.is_empty()))
+            .take(self.policy.max_pairs)
+            .collect();
+        self.pending.store(pending.len() as u64, Ordering::Relaxed);
+        if pending.is_empty() {
+            debug!("REST bootstrap not needed, every book arrived over WebSocket");
+            return;
+        }
+
+        let started = Instant::now();
+        info!("Bootstrapping {} order books over REST ({} at a time)", pending.len(), self.policy.concurrency);
+        futures_util::stream::iter(pending)
+            .map(|pair| async move {
+                let result = self.fetch(&pair).await;
+                (pair, result)
+            })
+            .buffer_unordered(self.policy.concurrency)
+            .for_each(|(pair, result)| {
+                let events = events.clone();
+                async move {
+                    match result {
+                        Ok((bids, asks)) => {
+                            if self.cache.update_rest_snapshot(&pair, bids, asks) {
+                                self.fetched.fetch_add(1, Ordering::Relaxed);
+                                if let Some(tx) = events {
+                                    let _ = tx.send(BookDelta::snapshot(&pair)).await;
+                                }
+                            } else {
+                                self.superseded.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                        Err(e) => {
+                            self.failed.fetch_add(1, Ordering::Relaxed);
+                            warn!("REST bootstrap of {} failed: {}", pair, e);
+                        }
+                    }
+                }
+            })
+            .await;
+
+        let elapsed = started.elapsed().as_millis() as u64;
+        *self.duration_ms.write() = Some(elapsed);
+        info!(
+            "REST bootstrap finished in {}ms: {} fetched, {} superseded by WebSocket, {} failed",
+            elapsed,
+            self.fetched.load(Ordering::Relaxed),
+            self.superseded.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed)
+        );
+    }
+
+    async fn fetch(&self, pair: &str) -> Result<(Vec<OrderBookLevel>, Vec<OrderBookLevel>), String> {
+        let kraken_id = self.cache.get_pair_info(pair).ok_or("pair not registered")?.kraken_id;
+        let url = format!("{}{}", get_kraken_rest_url(), get_depth_path());
+        let body: Value = self.client
+            .get(&url)
+            .query(&[("pair", kraken_id.as_str()), ("count", &self.policy.depth.to_string())])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        parse_depth(&body)
+    }
+
+    pub fn status(&self) -> BootstrapStatus {
+        BootstrapStatus {
+            pending: self.pending.load(Ordering::Relaxed),
+            fetched: self.fetched.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            superseded: self.superseded.load(Ordering::Relaxed),
+            duration_ms: *self.duration_ms.read(),
+            rest_sourced: self.cache.get_rest_sourced(),
+        }
+    }
+}
+
+/// Bids and asks from a Depth response. Kraken keys the result by its own
+/// pair name, which may differ from the one requested, so the single entry
+/// is taken whatever its key.
+fn parse_depth(body: &Value) -> Result<(Vec<OrderBookLevel>, Vec<OrderBookLevel>), String> {
+    if let Some(errors) = body["error"].as_array().filter(|e| !e.is_empty()) {
+        return Err(format!("Kraken API error: {:?}", errors));
+    }
+    let book = body["result"]
+        .as_object()
+        .and_then(|r| r.values().next())
+        .ok_or("missing result")?;
+    let side = |key: &str| -> Vec<OrderBookLevel> {
+        book[key]
+            .as_array()
+            .map(|levels| {
+                levels
+                    .iter()
+                    .filter_map(|level| {
+                        let price = level.get(0)?.as_str()?.parse().ok()?;
+                        let qty = level.get(1)?.as_str()?.parse().ok()?;
+                        Some(OrderBookLevel { price, qty })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let (bids, asks) = (side("bids"), side("asks"));
+    if bids.is_empty() || asks.is_empty() {
+        return Err("empty book".to_string());
+    }
+    Ok((bids, asks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::PairInfo;
+    use serde_json::json;
+
+    #[test]
+    fn test_rest_book_replaced_by_first_ws_snapshot() {
+        let body = json!({
+            "error": [],
+            "result": {"XXBTZUSD": {
+                "bids": [["50000.1", "0.5", 1700000000], ["49999.0", "1.0", 1700000000]],
+                "asks": [["50001.2", "1.25", 1700000000]]
+            }}
+        });
+        let (bids, asks) = parse_depth(&body).unwrap();
+        assert_eq!(bids.len(), 2);
+        assert_eq!((asks[0].price, asks[0].qty), (50001.2, 1.25));
+        assert!(parse_depth(&json!({"error": ["EQuery:Unknown asset pair"]})).is_err());
+
+        let cache = OrderBookCache::new();
+        cache.register_pair(PairInfo {
+            pair_name: "BTC/USD".to_string(),
+            base: "BTC".to_string(),
+            quote: "USD".to_string(),
+            kraken_id: "XXBTZUSD".to_string(),
+            ws_name: "BTC/USD".to_string(),
+            volume_24h: 1_000_000.0,
+        });
+
+        assert!(cache.update_rest_snapshot("BTC/USD", bids.clone(), asks.clone()));
+        assert_eq!(cache.get_rest_sourced(), vec!["BTC/USD".to_string()]);
+        assert_eq!(cache.get_price("BTC/USD").unwrap().bid, 50000.1);
+
+        // The socket snapshot takes over, and a late REST response is dropped
+        let level = |price: f64| vec![OrderBookLevel { price, qty: 2.0 }];
+        cache.update_snapshot("BTC/USD", level(50010.0), level(50011.0), 7);
+        assert!(cache.get_rest_sourced().is_empty());
+        assert!(!cache.update_rest_snapshot("BTC/USD", bids, asks));
+        assert_eq!(cache.get_price("BTC/USD").unwrap().bid, 50010.0);
+    }
+}
@@ -2,20 +2,24 @@
 //!
 //! Manages trading restrictions based on jurisdiction (e.g., Canada).
 //! Loads from JSON config file and provides API endpoints for management.
+//! Edits to the file are picked up by `reload_if_changed`; the engine pushes
+//! the blocked currencies into the scanner graph so no path crosses them.
 #![allow(dead_code)]
 
 use chrono::Utc;
 use parking_lot::RwLock;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::Path;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 use tracing::{info, warn};
 
 const CONFIG_FILE_PATH: &str = "config/canada_restrictions.json";
 
 #[derive(Debug, Error)]
+#[allow(clippy::enum_variant_names)]
 pub enum RestrictionsError {
     #[error("Failed to read config file: {0}")]
     FileReadError(String),
@@ -71,6 +75,8 @@ impl Default for RestrictionsConfig {
 pub struct RestrictionsManager {
     config: RwLock<RestrictionsConfig>,
     config_path: String,
+    /// Modification time of the file when it was last read or written
+    file_mtime: RwLock<Option<SystemTime>>,
     client: Client,
     kraken_api_key: Option<String>,
     kraken_api_secret: Option<String>,
@@ -94,6 +100,7 @@ impl RestrictionsManager {
         let manager = Self {
             config: RwLock::new(RestrictionsConfig::default()),
             config_path: path.clone(),
+            file_mtime: RwLock::new(None),
             client,
             kraken_api_key,
             kraken_api_secret,
@@ -130,6 +137,7 @@ impl RestrictionsManager {
         let manager = Self {
             config: RwLock::new(RestrictionsConfig::default()),
             config_path: config_path.to_string(),
+            file_mtime: RwLock::new(None),
             client,
             kraken_api_key,
             kraken_api_secret,
@@ -166,9 +174,25 @@ impl RestrictionsManager {
         );
 
         *self.config.write() = config;
+        *self.file_mtime.write() = self.current_mtime();
         Ok(())
     }
 
+    /// Re-read the config file when it changed on disk since it was last
+    /// read or written. Returns whether it was reloaded.
+    pub fn reload_if_changed(&self) -> Result<bool, RestrictionsError> {
+        let mtime = self.current_mtime();
+        if mtime.is_none() || mtime == *self.file_mtime.read() {
+            return Ok(false);
+        }
+        self.load_from_file()?;
+        Ok(true)
+    }
+
+    fn current_mtime(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.config_path).and_then(|m| m.modified()).ok()
+    }
+
     /// Save current config to JSON file
     pub fn save_to_file(&self) -> Result<(), RestrictionsError> {
         let config = self.config.read().clone();
@@ -183,6 +207,7 @@ impl RestrictionsManager {
 
         std::fs::write(&self.config_path, content)
             .map_err(|e| RestrictionsError::FileWriteError(e.to_string()))?;
+        *self.file_mtime.write() = self.current_mtime();
 
         info!("Saved restrictions config to {}", self.config_path);
         Ok(())
@@ -198,6 +223,11 @@ impl RestrictionsManager {
         self.config.read().blocked_base_currencies.clone()
     }
 
+    /// Blocked currencies as a set (uppercase)
+    pub fn blocked_set(&self) -> HashSet<String> {
+        self.config.read().blocked_base_currencies.iter().map(|c| c.to_uppercase()).collect()
+    }
+
     /// Get allowed specified assets list
     pub fn get_allowed_assets(&self) -> Vec<String> {
         self.config.read().allowed_specified_assets.clone()
@@ -416,6 +446,14 @@ pub struct AddRemoveRequest {
 mod tests {
     use super::*;
 
+    /// Manager backed by a temp file that does not exist yet, so tests never
+    /// read or modify the real restrictions config
+    fn temp_manager(name: &str) -> RestrictionsManager {
+        let path = std::env::temp_dir().join(format!("restrictions_test_{}_{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        RestrictionsManager::new(Some(path.to_str().unwrap()))
+    }
+
     #[test]
     fn test_default_config_is_empty() {
         // Default config should have empty lists - no hardcoded values
@@ -428,7 +466,7 @@ mod tests {
     #[test]
     fn test_is_currency_blocked_empty_default() {
         // When no config file exists, manager starts with empty blocked list
-        let manager = RestrictionsManager::new(None);
+        let manager = temp_manager("empty_default");
         // With empty defaults, nothing should be blocked
         assert!(!manager.is_currency_blocked("USDT"), "No hardcoded blocks");
         assert!(!manager.is_currency_blocked("BTC"), "No hardcoded blocks");
@@ -436,7 +474,7 @@ mod tests {
 
     #[test]
     fn test_is_currency_blocked_case_insensitive() {
-        let manager = RestrictionsManager::new(None);
+        let manager = temp_manager("case_insensitive");
         // Manually add a blocked currency
         let _ = manager.add_blocked_currency("TEST");
         assert!(manager.is_currency_blocked("TEST"));
@@ -446,7 +484,7 @@ mod tests {
 
     #[test]
     fn test_add_remove_blocked_currency() {
-        let manager = RestrictionsManager::new(None);
+        let manager = temp_manager("add_remove");
         // Start empty
         assert!(!manager.is_currency_blocked("XYZ"));
         // Add
@@ -0,0 +1,328 @@
+//! Data Retention
+//!
+//! Only live_opportunities used to be cleaned (after 7 days), so trades,
+//! fills, stats samples and the audit log grew without bound. Every table
+//! now has a retention in days (0 = kept forever):
+//! - RETENTION_OPPORTUNITIES_DAYS (default 7)
+//! - RETENTION_TRADES_DAYS, RETENTION_FILLS_DAYS, RETENTION_STATS_DAYS and
+//!   RETENTION_AUDIT_DAYS (default 0)
+//!
+//! Annotated trades and opportunities are never deleted, and neither are
+//! trades that haven't reached a final status (an unresolved PARTIAL still
+//! holds a position).
+//!
+//! With RETENTION_ARCHIVE_DIR set, rows are exported before deletion to
+//! `<dir>/<table>-<timestamp>.jsonl.gz`, one JSON object per row. A batch
+//! is deleted only once it has been written and synced, so a failure leaves
+//! rows in the table (and possibly in the archive twice) rather than lost.
+//!
+//! The job runs every RETENTION_INTERVAL_SECS (default 6h, 0 = only on
+//! demand) and through POST /api/admin/cleanup; one run at a time.
+#![allow(dead_code)]
+
+use crate::db::{Database, RetentionTable};
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Rows archived or deleted per database round trip
+const BATCH_SIZE: i64 = 5_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPolicy {
+    pub opportunities_days: u32,
+    pub trades_days: u32,
+    pub fills_days: u32,
+    pub stats_days: u32,
+    pub audit_days: u32,
+    /// Export rows here before deleting them (None = delete only)
+    pub archive_dir: Option<PathBuf>,
+    /// Seconds between scheduled runs (0 = only on demand)
+    pub interval_secs: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            opportunities_days: 7,
+            trades_days: 0,
+            fills_days: 0,
+            stats_days: 0,
+            audit_days: 0,
+            archive_dir: None,
+            interval_secs: 6 * 3600,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Create from the RETENTION_* variables
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            opportunities_days: env("RETENTION_OPPORTUNITIES_DAYS").unwrap_or(defaults.opportunities_days),
+            trades_days: env("RETENTION_TRADES_DAYS").unwrap_or(defaults.trades_days),
+            fills_days: env("RETENTION_FILLS_DAYS").unwrap_or(defaults.fills_days),
+            stats_days: env("RETENTION_STATS_DAYS").unwrap_or(defaults.stats_days),
+            audit_days: env("RETENTION_AUDIT_DAYS").unwrap_or(defaults.audit_days),
+            archive_dir: std::env::var("RETENTION_ARCHIVE_DIR").ok().filter(|v| !v.trim().is_empty()).map(PathBuf::from),
+            interval_secs: env("RETENTION_INTERVAL_SECS").unwrap_or(defaults.interval_secs),
+        }
+    }
+
+    /// Days `table` is kept (0 = forever)
+    pub fn days(&self, table: RetentionTable) -> u32 {
+        match table {
+            RetentionTable::Opportunities => self.opportunities_days,
+            RetentionTable::Trades => self.trades_days,
+            RetentionTable::Fills => self.fills_days,
+            RetentionTable::Stats => self.stats_days,
+            RetentionTable::Audit => self.audit_days,
+        }
+    }
+}
+
+/// What a run did to one table
+#[derive(Debug, Clone, Serialize)]
+pub struct TableCleanup {
+    pub table: RetentionTable,
+    pub retention_days: u32,
+    pub deleted: u64,
+    pub archived: u64,
+    pub archive_file: Option<String>,
+    /// Why the table was left partly cleaned
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupReport {
+    /// "scheduled" or "manual"
+    pub trigger: &'static str,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Tables with a retention, in cleanup order
+    pub tables: Vec<TableCleanup>,
+}
+
+impl CleanupReport {
+    pub fn deleted(&self) -> u64 {
+        self.tables.iter().map(|t| t.deleted).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionStatus {
+    pub policy: RetentionPolicy,
+    pub running: bool,
+    pub runs: u64,
+    pub last_run: Option<CleanupReport>,
+}
+
+/// Gzipped JSON lines export of one table's rows
+struct Archive {
+    path: PathBuf,
+    encoder: GzEncoder<File>,
+}
+
+impl Archive {
+    fn create(dir: &Path, table: RetentionTable, at: DateTime<Utc>) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.jsonl.gz", table.table_name(), at.format("%Y%m%dT%H%M%SZ")));
+        let file = File::create(&path)?;
+        Ok(Self { path, encoder: GzEncoder::new(file, Compression::default()) })
+    }
+
+    /// Append rows and make sure they're on disk
+    fn write(&mut self, rows: &[(i64, String)]) -> std::io::Result<()> {
+        for (_, row) in rows {
+            self.encoder.write_all(row.as_bytes())?;
+            self.encoder.write_all(b"\n")?;
+        }
+        self.encoder.flush()?;
+        self.encoder.get_ref().sync_data()
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        self.encoder.finish()?.sync_all()
+    }
+}
+
+pub struct Retention {
+    policy: RetentionPolicy,
+    running: AtomicBool,
+    runs: AtomicU64,
+    last_run: RwLock<Option<CleanupReport>>,
+}
+
+impl Retention {
+    pub fn new(policy: RetentionPolicy) -> Self {
+        Self {
+            policy,
+            running: AtomicBool::new(false),
+            runs: AtomicU64::new(0),
+            last_run: RwLock::new(None),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(RetentionPolicy::from_env())
+    }
+
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    /// Clean every table with a retention. Errs when a run is already going.
+    pub async fn run(&self, db: &Database, trigger: &'static str) -> Result<CleanupReport, String> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err("A cleanup is already running".to_string());
+        }
+        let started = Instant::now();
+        let started_at = Utc::now();
+        let mut tables = Vec::new();
+        for table in RetentionTable::ALL {
+            let days = self.policy.days(table);
+            if days > 0 {
+                tables.push(self.clean_table(db, table, days, started_at).await);
+            }
+        }
+        self.running.store(false, Ordering::SeqCst);
+
+        let report = CleanupReport { trigger, started_at, duration_ms: started.elapsed().as_millis() as u64, tables };
+        info!("Retention cleanup ({}) deleted {} rows in {}ms", trigger, report.deleted(), report.duration_ms);
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        *self.last_run.write() = Some(report.clone());
+        Ok(report)
+    }
+
+    async fn clean_table(&self, db: &Database, table: RetentionTable, days: u32, at: DateTime<Utc>) -> TableCleanup {
+        let mut cleanup = TableCleanup {
+            table,
+            retention_days: days,
+            deleted: 0,
+            archived: 0,
+            archive_file: None,
+            error: None,
+        };
+        let result = match &self.policy.archive_dir {
+            Some(dir) => self.archive_and_delete(db, table, days, dir, at, &mut cleanup).await,
+            None => Self::delete(db, table, days, &mut cleanup).await,
+        };
+        if let Err(e) = result {
+            warn!("Retention cleanup of {} stopped: {}", table.table_name(), e);
+            cleanup.error = Some(e);
+        }
+        cleanup
+    }
+
+    async fn delete(db: &Database, table: RetentionTable, days: u32, cleanup: &mut TableCleanup) -> Result<(), String> {
+        loop {
+            let deleted = db.delete_expired_rows(table, days, None, BATCH_SIZE).await.map_err(|e| e.to_string())?;
+            cleanup.deleted += deleted;
+            if deleted < BATCH_SIZE as u64 {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn archive_and_delete(
+        &self,
+        db: &Database,
+        table: RetentionTable,
+        days: u32,
+        dir: &Path,
+        at: DateTime<Utc>,
+        cleanup: &mut TableCleanup,
+    ) -> Result<(), String> {
+        let mut archive: Option<Archive> = None;
+        let mut after_id = 0;
+        let result = loop {
+            let rows = match db.get_expired_rows(table, days, after_id, BATCH_SIZE).await {
+                Ok(rows) => rows,
+                Err(e) => break Err(e.to_string()),
+            };
+            let Some(&(last_id, _)) = rows.last() else { break Ok(()) };
+            if archive.is_none() {
+                match Archive::create(dir, table, at) {
+                    Ok(created) => {
+                        cleanup.archive_file = Some(created.path.display().to_string());
+                        archive = Some(created);
+                    }
+                    Err(e) => break Err(format!("Can't create archive in {}: {}", dir.display(), e)),
+                }
+            }
+            if let Err(e) = archive.as_mut().map_or(Ok(()), |a| a.write(&rows)) {
+                break Err(format!("Can't write archive: {}", e));
+            }
+            cleanup.archived += rows.len() as u64;
+
+            let ids: Vec<i64> = rows.iter().map(|(id, _)| *id).collect();
+            match db.delete_expired_rows(table, days, Some(&ids), BATCH_SIZE).await {
+                Ok(deleted) => cleanup.deleted += deleted,
+                Err(e) => break Err(e.to_string()),
+            }
+            if rows.len() < BATCH_SIZE as usize {
+                break Ok(());
+            }
+            after_id = last_id;
+        };
+        if let Some(archive) = archive {
+            archive.finish().map_err(|e| format!("Can't finish archive: {}", e))?;
+        }
+        result
+    }
+
+    pub fn status(&self) -> RetentionStatus {
+        RetentionStatus {
+            policy: self.policy.clone(),
+            running: self.running.load(Ordering::SeqCst),
+            runs: self.runs.load(Ordering::Relaxed),
+            last_run: self.last_run.read().clone(),
+        }
+    }
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self::new(RetentionPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let policy = RetentionPolicy { trades_days: 90, ..Default::default() };
+        let kept: Vec<u32> = RetentionTable::ALL.iter().map(|t| policy.days(*t)).collect();
+        assert_eq!(kept, vec![7, 90, 0, 0, 0]);
+
+        let dir = std::env::temp_dir().join(format!("retention-test-{}", uuid::Uuid::new_v4()));
+        let at = "2026-03-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut archive = Archive::create(&dir, RetentionTable::Trades, at).unwrap();
+        assert!(archive.path.ends_with("live_trades-20260301T120000Z.jsonl.gz"));
+        archive.write(&[(1, r#"{"id":1}"#.to_string()), (2, r#"{"id":2}"#.to_string())]).unwrap();
+        archive.write(&[(3, r#"{"id":3}"#.to_string())]).unwrap();
+        let path = archive.path.clone();
+        archive.finish().unwrap();
+
+        let mut text = String::new();
+        GzDecoder::new(File::open(&path).unwrap()).read_to_string(&mut text).unwrap();
+        assert_eq!(text.lines().collect::<Vec<_>>(), vec![r#"{"id":1}"#, r#"{"id":2}"#, r#"{"id":3}"#]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
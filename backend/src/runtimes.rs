@@ -0,0 +1,298 @@
+//! Dedicated Runtimes
+//!
+//! Book ingestion, scanning and order placement used to share the main
+//! tokio runtime, so a burst of book updates and scans could hold up the
+//! task waiting on an order ack. The engine now runs the public WebSocket
+//! on a market-data runtime and the HFT loop plus the private execution
+//! socket on an execution runtime, each with its own worker threads. The
+//! API server, DB pool and periodic jobs stay on the main runtime.
+//!
+//! Every runtime runs a probe that sleeps PROBE_INTERVAL and measures how
+//! late it wakes up: the time a ready task waited for a worker. Served by
+//! `GET /api/runtimes`.
+//!
+//! RUNTIME_SPLIT=false puts everything back on the main runtime (the
+//! probes still run there). MARKET_DATA_THREADS / EXECUTION_THREADS size
+//! the dedicated runtimes.
+//!
+//! A dedicated runtime stops, taking its tasks and sockets with it, on
+//! `shutdown` or when its `EngineRuntime` is dropped.
+
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Builder, Handle};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// How often each probe measures wake-up lateness
+const PROBE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Latencies at or above this count as a stall
+const STALL_THRESHOLD_US: u64 = 10_000;
+
+/// How long a stopping runtime waits for its blocking tasks
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RuntimePolicy {
+    /// Dedicated runtimes (false = everything on the main runtime)
+    pub split: bool,
+    pub market_data_threads: usize,
+    pub execution_threads: usize,
+}
+
+impl Default for RuntimePolicy {
+    fn default() -> Self {
+        Self {
+            split: true,
+            market_data_threads: 2,
+            execution_threads: 2,
+        }
+    }
+}
+
+impl RuntimePolicy {
+    /// Read RUNTIME_SPLIT, MARKET_DATA_THREADS and EXECUTION_THREADS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let threads = |key: &str, default: usize| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            split: !std::env::var("RUNTIME_SPLIT").is_ok_and(|v| matches!(v.to_lowercase().as_str(), "false" | "0" | "off")),
+            market_data_threads: threads("MARKET_DATA_THREADS", defaults.market_data_threads),
+            execution_threads: threads("EXECUTION_THREADS", defaults.execution_threads),
+        }
+    }
+}
+
+/// Wake-up lateness of one runtime's probe
+#[derive(Default)]
+pub struct QueueLatency {
+    samples: AtomicU64,
+    total_us: AtomicU64,
+    last_us: AtomicU64,
+    max_us: AtomicU64,
+    stalls: AtomicU64,
+}
+
+impl QueueLatency {
+    pub fn record(&self, latency: Duration) {
+        let us = latency.as_micros() as u64;
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(us, Ordering::Relaxed);
+        self.last_us.store(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+        if us >= STALL_THRESHOLD_US {
+            self.stalls.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self) -> (u64, f64, u64, u64, u64) {
+        let samples = self.samples.load(Ordering::Relaxed);
+        let mean = if samples > 0 { self.total_us.load(Ordering::Relaxed) as f64 / samples as f64 } else { 0.0 };
+        (
+            samples,
+            mean,
+            self.last_us.load(Ordering::Relaxed),
+            self.max_us.load(Ordering::Relaxed),
+            self.stalls.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Snapshot of one runtime for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeStats {
+    pub name: &'static str,
+    /// Own worker threads (false = running on the main runtime)
+    pub dedicated: bool,
+    pub workers: usize,
+    pub alive_tasks: usize,
+    pub global_queue_depth: usize,
+    pub probe_samples: u64,
+    pub queue_latency_last_us: u64,
+    pub queue_latency_mean_us: f64,
+    pub queue_latency_max_us: u64,
+    /// Probe wake-ups at least STALL_THRESHOLD_US late
+    pub stalls: u64,
+}
+
+/// A runtime the engine spawns a class of tasks on
+pub struct EngineRuntime {
+    name: &'static str,
+    dedicated: bool,
+    handle: Handle,
+    latency: Arc<QueueLatency>,
+    /// Dropping it lets a dedicated runtime's driver thread stop the runtime
+    stop: parking_lot::Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl EngineRuntime {
+    /// Start a dedicated multi-thread runtime. It lives on its own thread
+    /// until stopped, so it's never dropped from async context.
+    fn dedicated(name: &'static str, threads: usize) -> std::io::Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(threads)
+            .thread_name(name)
+            .enable_all()
+            .build()?;
+        let handle = runtime.handle().clone();
+        let (stop, stopped) = oneshot::channel::<()>();
+        std::thread::Builder::new()
+            .name(format!("{}-driver", name))
+            .spawn(move || {
+                let _ = runtime.block_on(stopped);
+                runtime.shutdown_timeout(SHUTDOWN_TIMEOUT);
+                info!("Stopped {} runtime", name);
+            })?;
+        info!("Started {} runtime ({} worker threads)", name, threads);
+        let runtime = Self::with_handle(name, true, handle);
+        *runtime.stop.lock() = Some(stop);
+        Ok(runtime)
+    }
+
+    /// Tasks go to the runtime `handle` belongs to
+    fn with_handle(name: &'static str, dedicated: bool, handle: Handle) -> Self {
+        let latency = Arc::new(QueueLatency::default());
+        let probe = Arc::clone(&latency);
+        handle.spawn(async move {
+            loop {
+                let due = Instant::now() + PROBE_INTERVAL;
+                tokio::time::sleep(PROBE_INTERVAL).await;
+                probe.record(Instant::now().saturating_duration_since(due));
+            }
+        });
+        Self { name, dedicated, handle, latency, stop: parking_lot::Mutex::new(None) }
+    }
+
+    /// Stop a dedicated runtime, aborting everything spawned on it. Does
+    /// nothing for the main runtime.
+    pub fn shutdown(&self) {
+        if self.stop.lock().take().is_some() {
+            info!("Stopping {} runtime", self.name);
+        }
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    pub fn stats(&self) -> RuntimeStats {
+        let metrics = self.handle.metrics();
+        let (probe_samples, mean, last, max, stalls) = self.latency.snapshot();
+        RuntimeStats {
+            name: self.name,
+            dedicated: self.dedicated,
+            workers: metrics.num_workers(),
+            alive_tasks: metrics.num_alive_tasks(),
+            global_queue_depth: metrics.global_queue_depth(),
+            probe_samples,
+            queue_latency_last_us: last,
+            queue_latency_mean_us: mean,
+            queue_latency_max_us: max,
+            stalls,
+        }
+    }
+}
+
+/// Spawn on `runtime`, or on the caller's runtime when None
+pub fn spawn_on<F>(runtime: Option<&Handle>, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match runtime {
+        Some(handle) => handle.spawn(future),
+        None => tokio::spawn(future),
+    }
+}
+
+/// Market-data and execution runtimes, plus the main one for comparison
+pub struct EngineRuntimes {
+    policy: RuntimePolicy,
+    main: EngineRuntime,
+    pub market_data: EngineRuntime,
+    pub execution: EngineRuntime,
+}
+
+impl EngineRuntimes {
+    /// Must be called from within the main runtime
+    pub fn new(policy: RuntimePolicy) -> Self {
+        let current = Handle::current();
+        let start = |name: &'static str, threads: usize| {
+            if policy.split {
+                match EngineRuntime::dedicated(name, threads) {
+                    Ok(runtime) => return runtime,
+                    Err(e) => warn!("Failed to start {} runtime, using the main runtime: {}", name, e),
+                }
+            }
+            EngineRuntime::with_handle(name, false, current.clone())
+        };
+        Self {
+            policy,
+            market_data: start("market-data", policy.market_data_threads),
+            execution: start("execution", policy.execution_threads),
+            main: EngineRuntime::with_handle("main", false, current.clone()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(RuntimePolicy::from_env())
+    }
+
+    pub fn policy(&self) -> RuntimePolicy {
+        self.policy
+    }
+
+    pub fn stats(&self) -> Vec<RuntimeStats> {
+        vec![self.market_data.stats(), self.execution.stats(), self.main.stats()]
+    }
+
+    /// Stop the market-data and execution runtimes
+    pub fn shutdown(&self) {
+        self.market_data.shutdown();
+        self.execution.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_split_runtimes_run_tasks_and_probe() {
+        let runtimes = EngineRuntimes::new(RuntimePolicy { split: true, market_data_threads: 1, execution_threads: 1 });
+        let name = runtimes
+            .execution
+            .handle()
+            .spawn(async { std::thread::current().name().map(str::to_string) })
+            .await
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("execution"));
+
+        tokio::time::sleep(PROBE_INTERVAL * 3).await;
+        let stats = runtimes.stats();
+        assert_eq!(stats.iter().map(|s| s.name).collect::<Vec<_>>(), vec!["market-data", "execution", "main"]);
+        assert!(stats[0].dedicated && !stats[2].dedicated);
+        assert_eq!(stats[1].workers, 1);
+        assert!(stats[0].probe_samples > 0);
+
+        let latency = QueueLatency::default();
+        latency.record(Duration::from_micros(500));
+        latency.record(Duration::from_millis(20));
+        assert_eq!(latency.snapshot(), (2, 10_250.0, 20_000, 20_000, 1));
+
+        let parked = runtimes.market_data.handle().spawn(std::future::pending::<()>());
+        runtimes.shutdown();
+        assert!(parked.await.unwrap_err().is_cancelled());
+    }
+}
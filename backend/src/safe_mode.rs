@@ -0,0 +1,208 @@
+//! Safe Mode
+//!
+//! After a crash or a circuit-breaker trip the engine comes back up with
+//! streaming and scanning running but execution blocked, even when the DB
+//! says trading is enabled. Nothing trades until an operator confirms with
+//! `POST /api/live/confirm-resume` (operator id + reason), which is audited.
+//!
+//! When safe mode is entered comes from SAFE_MODE_START:
+//! - auto (default): after an unclean shutdown (the previous run was still
+//!   enabled when the process died) or a circuit-breaker trip/reset
+//! - always: every start, including the first
+//! - off: never
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// When the engine enters safe mode on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafeModePolicy {
+    Auto,
+    Always,
+    Off,
+}
+
+impl SafeModePolicy {
+    /// Read SAFE_MODE_START (auto / always / off, default auto)
+    pub fn from_env() -> Self {
+        match std::env::var("SAFE_MODE_START").map(|v| v.to_lowercase()).as_deref() {
+            Ok("always") => Self::Always,
+            Ok("off") | Ok("false") | Ok("0") => Self::Off,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// An operator's confirmation that trading may resume
+#[derive(Debug, Clone, Serialize)]
+pub struct ResumeRecord {
+    pub operator_id: String,
+    pub reason: String,
+    pub resumed_at: DateTime<Utc>,
+    /// Why safe mode had been entered
+    pub safe_mode_reason: String,
+}
+
+/// Snapshot for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct SafeModeStatus {
+    pub policy: SafeModePolicy,
+    pub active: bool,
+    pub reason: Option<String>,
+    pub entered_at: Option<DateTime<Utc>>,
+    /// Opportunities not executed since safe mode was entered
+    pub blocked_opportunities: u64,
+    pub last_resume: Option<ResumeRecord>,
+}
+
+#[derive(Debug, Default)]
+struct SafeModeState {
+    reason: Option<String>,
+    entered_at: Option<DateTime<Utc>>,
+    last_resume: Option<ResumeRecord>,
+}
+
+pub struct SafeMode {
+    policy: SafeModePolicy,
+    /// Checked on the hot path without taking the lock
+    active: AtomicBool,
+    blocked: AtomicU64,
+    state: RwLock<SafeModeState>,
+}
+
+impl SafeMode {
+    pub fn new(policy: SafeModePolicy) -> Self {
+        Self {
+            policy,
+            active: AtomicBool::new(false),
+            blocked: AtomicU64::new(0),
+            state: RwLock::new(SafeModeState::default()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(SafeModePolicy::from_env())
+    }
+
+    pub fn policy(&self) -> SafeModePolicy {
+        self.policy
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Block execution until confirmed. Returns false when already active
+    /// (the original reason is kept).
+    pub fn enter(&self, reason: &str) -> bool {
+        let mut state = self.state.write();
+        if self.active.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        state.reason = Some(reason.to_string());
+        state.entered_at = Some(Utc::now());
+        self.blocked.store(0, Ordering::Relaxed);
+        true
+    }
+
+    /// Enter safe mode for an automatic trigger (crash, breaker), as the
+    /// policy allows. Returns whether it was entered.
+    pub fn enter_auto(&self, reason: &str) -> bool {
+        self.policy != SafeModePolicy::Off && self.enter(reason)
+    }
+
+    /// Enter safe mode for a start, when the policy demands it every time
+    pub fn enter_on_start(&self) -> bool {
+        self.policy == SafeModePolicy::Always && self.enter("SAFE_MODE_START=always")
+    }
+
+    /// Count an opportunity that safe mode kept from executing
+    pub fn record_block(&self) {
+        self.blocked.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Operator confirmation: leave safe mode and allow execution again
+    pub fn confirm_resume(&self, operator_id: &str, reason: &str) -> Result<ResumeRecord, String> {
+        let operator_id = operator_id.trim();
+        let reason = reason.trim();
+        if operator_id.is_empty() {
+            return Err("operator_id is required".to_string());
+        }
+        if reason.is_empty() {
+            return Err("reason is required".to_string());
+        }
+
+        let mut state = self.state.write();
+        if !self.active.load(Ordering::SeqCst) {
+            return Err("Engine is not in safe mode".to_string());
+        }
+        let record = ResumeRecord {
+            operator_id: operator_id.to_string(),
+            reason: reason.to_string(),
+            resumed_at: Utc::now(),
+            safe_mode_reason: state.reason.take().unwrap_or_default(),
+        };
+        state.entered_at = None;
+        state.last_resume = Some(record.clone());
+        self.active.store(false, Ordering::SeqCst);
+        Ok(record)
+    }
+
+    pub fn status(&self) -> SafeModeStatus {
+        let state = self.state.read();
+        SafeModeStatus {
+            policy: self.policy,
+            active: self.is_active(),
+            reason: state.reason.clone(),
+            entered_at: state.entered_at,
+            blocked_opportunities: self.blocked.load(Ordering::Relaxed),
+            last_resume: state.last_resume.clone(),
+        }
+    }
+}
+
+impl Default for SafeMode {
+    /// Inactive and never entered automatically
+    fn default() -> Self {
+        Self::new(SafeModePolicy::Off)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_mode_requires_operator_confirmation() {
+        let safe_mode = SafeMode::new(SafeModePolicy::Auto);
+        assert!(!safe_mode.enter_on_start());
+        assert!(safe_mode.confirm_resume("ops-1", "checked balances").is_err());
+
+        assert!(safe_mode.enter_auto("unclean shutdown"));
+        // A second trigger keeps the first reason
+        assert!(!safe_mode.enter_auto("circuit breaker reset"));
+        safe_mode.record_block();
+        let status = safe_mode.status();
+        assert!(status.active);
+        assert_eq!(status.reason.as_deref(), Some("unclean shutdown"));
+        assert_eq!(status.blocked_opportunities, 1);
+
+        assert_eq!(safe_mode.confirm_resume(" ", "ok").unwrap_err(), "operator_id is required");
+        assert_eq!(safe_mode.confirm_resume("ops-1", "").unwrap_err(), "reason is required");
+        assert!(safe_mode.is_active());
+
+        let record = safe_mode.confirm_resume("ops-1", "checked balances").unwrap();
+        assert_eq!(record.safe_mode_reason, "unclean shutdown");
+        assert!(!safe_mode.is_active());
+        assert_eq!(safe_mode.status().last_resume.unwrap().operator_id, "ops-1");
+
+        // Off never enters on its own
+        let off = SafeMode::new(SafeModePolicy::Off);
+        assert!(!off.enter_auto("unclean shutdown"));
+        assert!(off.enter("manual"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Scan Fairness
+//!
+//! The HFT loop only scans when a book update arrives, and its scan stops at
+//! the first profitable cycle in a fixed search order. Busy pairs therefore
+//! get looked at constantly while a cycle through quiet pairs is only
+//! evaluated when some other update happens to trigger a scan, and never
+//! when an earlier cycle (say, one cooling down) keeps winning the search.
+//!
+//! A scan that finds nothing has evaluated every candidate cycle. When none
+//! has done so for SCAN_FAIRNESS_SECS, the loop runs a sweep whether or not
+//! an event arrived: a full scan that evaluates every cycle, caches every
+//! qualifying one and picks the best tradeable one instead of the first.
+#![allow(dead_code)]
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FairnessPolicy {
+    /// Longest a cycle may go unevaluated, in seconds (0 = off)
+    pub max_scan_age_secs: u64,
+}
+
+impl Default for FairnessPolicy {
+    fn default() -> Self {
+        Self { max_scan_age_secs: 5 }
+    }
+}
+
+impl FairnessPolicy {
+    /// Create from SCAN_FAIRNESS_SECS
+    pub fn from_env() -> Self {
+        Self {
+            max_scan_age_secs: std::env::var("SCAN_FAIRNESS_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(Self::default().max_scan_age_secs),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_scan_age_secs > 0
+    }
+}
+
+/// Fairness state for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct FairnessStatus {
+    pub policy: FairnessPolicy,
+    /// Sweeps run because no scan had covered every cycle in time
+    pub sweeps: u64,
+    /// Scans that covered every cycle, sweeps included
+    pub full_passes: u64,
+    /// Since every cycle was last evaluated (None before the first full pass)
+    pub last_full_pass_age_ms: Option<u64>,
+}
+
+pub struct ScanFairness {
+    policy: FairnessPolicy,
+    /// When every cycle was last evaluated
+    last_full_pass: Mutex<Option<Instant>>,
+    /// The next sweep is due an interval after this: the first check, the
+    /// last full pass or the last sweep (which may have been cut short by a
+    /// global cooldown), whichever is latest
+    since: Mutex<Option<Instant>>,
+    sweeps: AtomicU64,
+    full_passes: AtomicU64,
+}
+
+impl Default for ScanFairness {
+    fn default() -> Self {
+        Self::new(FairnessPolicy { max_scan_age_secs: 0 })
+    }
+}
+
+impl ScanFairness {
+    pub fn new(policy: FairnessPolicy) -> Self {
+        Self {
+            policy,
+            last_full_pass: Mutex::new(None),
+            since: Mutex::new(None),
+            sweeps: AtomicU64::new(0),
+            full_passes: AtomicU64::new(0),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(FairnessPolicy::from_env())
+    }
+
+    pub fn policy(&self) -> FairnessPolicy {
+        self.policy
+    }
+
+    /// Time left before a sweep is due (None = fairness off). The clock
+    /// starts at the first call, so a fresh loop gets one interval of events
+    /// before its first sweep.
+    pub fn sweep_due_in(&self, now: Instant) -> Option<Duration> {
+        if !self.policy.is_enabled() {
+            return None;
+        }
+        let since = *self.since.lock().get_or_insert(now);
+        let due = since + Duration::from_secs(self.policy.max_scan_age_secs);
+        Some(due.saturating_duration_since(now))
+    }
+
+    /// A scan evaluated every cycle (it found nothing, or was a sweep)
+    pub fn record_full_pass(&self, now: Instant) {
+        *self.last_full_pass.lock() = Some(now);
+        *self.since.lock() = Some(now);
+        self.full_passes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A sweep is starting
+    pub fn record_sweep(&self, now: Instant) {
+        *self.since.lock() = Some(now);
+        self.sweeps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn status(&self, now: Instant) -> FairnessStatus {
+        FairnessStatus {
+            policy: self.policy,
+            sweeps: self.sweeps.load(Ordering::Relaxed),
+            full_passes: self.full_passes.load(Ordering::Relaxed),
+            last_full_pass_age_ms: self
+                .last_full_pass
+                .lock()
+                .map(|at| now.saturating_duration_since(at).as_millis() as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_due_after_interval_without_full_pass() {
+        let fairness = ScanFairness::new(FairnessPolicy { max_scan_age_secs: 5 });
+        let start = Instant::now();
+
+        assert_eq!(fairness.sweep_due_in(start), Some(Duration::from_secs(5)));
+        assert_eq!(fairness.sweep_due_in(start + Duration::from_secs(3)), Some(Duration::from_secs(2)));
+        assert_eq!(fairness.sweep_due_in(start + Duration::from_secs(9)), Some(Duration::ZERO));
+
+        // A scan that covered every cycle pushes the next sweep back
+        fairness.record_full_pass(start + Duration::from_secs(4));
+        assert_eq!(fairness.sweep_due_in(start + Duration::from_secs(6)), Some(Duration::from_secs(3)));
+        assert_eq!(fairness.status(start + Duration::from_secs(6)).last_full_pass_age_ms, Some(2_000));
+
+        // A sweep pushes it back even when cut short before scanning
+        fairness.record_sweep(start + Duration::from_secs(10));
+        assert_eq!(fairness.sweep_due_in(start + Duration::from_secs(11)), Some(Duration::from_secs(4)));
+        assert_eq!(fairness.status(start).sweeps, 1);
+
+        assert_eq!(ScanFairness::default().sweep_due_in(start), None);
+    }
+}
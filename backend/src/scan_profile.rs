@@ -0,0 +1,292 @@
+//! Scanner Profiling
+//!
+//! Optional timing breakdown of every scan, to see where scan time goes
+//! before optimizing it. Phases:
+//! - graph_update: reading the price cache and building the currency graph
+//! - cycle_enumeration: DFS over the graph (own time, excluding the phases below)
+//! - profit_evaluation: multiplying rates and applying fees for a closed cycle
+//! - filtering: liquidity checks, plus dedupe/sort in full scans
+//! - serialization: building the Opportunity (path string, leg details, id)
+//!
+//! Off by default (SCANNER_PROFILING=true or the API turns it on); when off
+//! the scanner takes no timestamps at all. Totals are also exposed as folded
+//! stacks ("scan;cycle_enumeration;filtering 1234") for flame graph tools.
+#![allow(dead_code)]
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Scan phases in report order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanPhase {
+    GraphUpdate,
+    CycleEnumeration,
+    ProfitEvaluation,
+    Filtering,
+    Serialization,
+}
+
+pub const PHASES: [ScanPhase; 5] = [
+    ScanPhase::GraphUpdate,
+    ScanPhase::CycleEnumeration,
+    ScanPhase::ProfitEvaluation,
+    ScanPhase::Filtering,
+    ScanPhase::Serialization,
+];
+
+impl ScanPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanPhase::GraphUpdate => "graph_update",
+            ScanPhase::CycleEnumeration => "cycle_enumeration",
+            ScanPhase::ProfitEvaluation => "profit_evaluation",
+            ScanPhase::Filtering => "filtering",
+            ScanPhase::Serialization => "serialization",
+        }
+    }
+
+    /// Frame stack for folded output (phases run inside the DFS nest under it)
+    fn stack(&self) -> &'static str {
+        match self {
+            ScanPhase::GraphUpdate => "scan;graph_update",
+            ScanPhase::CycleEnumeration => "scan;cycle_enumeration",
+            ScanPhase::ProfitEvaluation => "scan;cycle_enumeration;profit_evaluation",
+            ScanPhase::Filtering => "scan;cycle_enumeration;filtering",
+            ScanPhase::Serialization => "scan;cycle_enumeration;serialization",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Phase times collected during one scan (atomics: full scans run per base in parallel)
+#[derive(Debug, Default)]
+pub struct ScanTrace {
+    nanos: [AtomicU64; 5],
+    paths: AtomicU64,
+}
+
+impl ScanTrace {
+    pub fn add(&self, phase: ScanPhase, elapsed: Duration) {
+        self.nanos[phase.index()].fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Count a closed cycle handed to profit evaluation
+    pub fn add_path(&self) {
+        self.paths.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        for n in &self.nanos {
+            n.store(0, Ordering::Relaxed);
+        }
+        self.paths.store(0, Ordering::Relaxed);
+    }
+
+    fn get(&self, phase: ScanPhase) -> u64 {
+        self.nanos[phase.index()].load(Ordering::Relaxed)
+    }
+
+    /// Close the scan. DFS time includes the nested phases, so enumeration
+    /// keeps only what's left after subtracting them.
+    pub fn finish(&self, graph_update: Duration, search: Duration) -> ScanBreakdown {
+        let nested = self.get(ScanPhase::ProfitEvaluation) + self.get(ScanPhase::Filtering) + self.get(ScanPhase::Serialization);
+        let mut nanos = [0u64; 5];
+        for phase in PHASES {
+            nanos[phase.index()] = self.get(phase);
+        }
+        nanos[ScanPhase::GraphUpdate.index()] += graph_update.as_nanos() as u64;
+        nanos[ScanPhase::CycleEnumeration.index()] += (search.as_nanos() as u64).saturating_sub(nested);
+        ScanBreakdown {
+            nanos,
+            paths_evaluated: self.paths.load(Ordering::Relaxed),
+            at: Instant::now(),
+        }
+    }
+}
+
+/// One finished scan
+#[derive(Debug, Clone, Copy)]
+pub struct ScanBreakdown {
+    nanos: [u64; 5],
+    paths_evaluated: u64,
+    at: Instant,
+}
+
+impl ScanBreakdown {
+    fn total_nanos(&self) -> u64 {
+        self.nanos.iter().sum()
+    }
+}
+
+/// Accumulated time of one phase
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    pub phase: &'static str,
+    pub total_ms: f64,
+    pub avg_us: f64,
+    pub max_us: f64,
+    pub last_us: f64,
+    pub pct_of_scan: f64,
+}
+
+/// Profile snapshot for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProfile {
+    pub enabled: bool,
+    pub scans_profiled: u64,
+    pub avg_scan_us: f64,
+    pub max_scan_us: f64,
+    pub avg_paths_evaluated: f64,
+    pub phases: Vec<PhaseTiming>,
+    /// Folded stacks with total microseconds, for flamegraph.pl / speedscope
+    pub folded: Vec<String>,
+    pub last_scan_age_ms: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct ProfileTotals {
+    scans: u64,
+    nanos: [u64; 5],
+    max_nanos: [u64; 5],
+    max_scan_nanos: u64,
+    paths: u64,
+    last: Option<ScanBreakdown>,
+}
+
+/// Shared collector the scanners report to
+pub struct ScanProfiler {
+    enabled: AtomicBool,
+    totals: RwLock<ProfileTotals>,
+}
+
+impl ScanProfiler {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            totals: RwLock::new(ProfileTotals::default()),
+        }
+    }
+
+    /// Create from SCANNER_PROFILING (default off)
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("SCANNER_PROFILING")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(false),
+        )
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        *self.totals.write() = ProfileTotals::default();
+    }
+
+    pub fn record(&self, scan: ScanBreakdown) {
+        let mut totals = self.totals.write();
+        totals.scans += 1;
+        totals.paths += scan.paths_evaluated;
+        for i in 0..PHASES.len() {
+            totals.nanos[i] += scan.nanos[i];
+            totals.max_nanos[i] = totals.max_nanos[i].max(scan.nanos[i]);
+        }
+        totals.max_scan_nanos = totals.max_scan_nanos.max(scan.total_nanos());
+        totals.last = Some(scan);
+    }
+
+    pub fn snapshot(&self) -> ScanProfile {
+        let totals = self.totals.read();
+        let scans = totals.scans.max(1) as f64;
+        let all_nanos: u64 = totals.nanos.iter().sum();
+
+        let phases = PHASES.iter().map(|phase| {
+            let i = phase.index();
+            PhaseTiming {
+                phase: phase.as_str(),
+                total_ms: totals.nanos[i] as f64 / 1e6,
+                avg_us: totals.nanos[i] as f64 / scans / 1e3,
+                max_us: totals.max_nanos[i] as f64 / 1e3,
+                last_us: totals.last.map(|l| l.nanos[i] as f64 / 1e3).unwrap_or(0.0),
+                pct_of_scan: if all_nanos > 0 { totals.nanos[i] as f64 / all_nanos as f64 * 100.0 } else { 0.0 },
+            }
+        }).collect();
+
+        let folded = PHASES.iter()
+            .filter(|phase| totals.nanos[phase.index()] > 0)
+            .map(|phase| format!("{} {}", phase.stack(), totals.nanos[phase.index()] / 1_000))
+            .collect();
+
+        ScanProfile {
+            enabled: self.is_enabled(),
+            scans_profiled: totals.scans,
+            avg_scan_us: all_nanos as f64 / scans / 1e3,
+            max_scan_us: totals.max_scan_nanos as f64 / 1e3,
+            avg_paths_evaluated: totals.paths as f64 / scans,
+            phases,
+            folded,
+            last_scan_age_ms: totals.last.map(|l| l.at.elapsed().as_millis() as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::{OrderBookCache, PairInfo};
+    use crate::scanner::Scanner;
+    use crate::types::{EngineConfig, OrderBookLevel};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_profiled_scan_breakdown() {
+        let cache = Arc::new(OrderBookCache::new());
+        for (base, quote, mid) in [("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_550.0)] {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            let levels = |side: f64| (1..=3).map(|i| OrderBookLevel { price: mid * (1.0 + side * 0.0001 * i as f64), qty: 10.0 }).collect();
+            cache.update_snapshot(&pair, levels(-1.0), levels(1.0), 1);
+        }
+        let config = EngineConfig { min_profit_threshold: -1.0, fee_rate: 0.001, fee_source: "test".to_string(), leg_thresholds: Default::default() };
+
+        // Disabled: scanning records nothing
+        let profiler = Arc::new(ScanProfiler::new(false));
+        let scanner = Scanner::new(Arc::clone(&cache), config.clone()).with_profiler(Arc::clone(&profiler));
+        assert!(scanner.scan_first(&["USD".to_string()], -1.0).is_some());
+        assert_eq!(profiler.snapshot().scans_profiled, 0);
+
+        profiler.set_enabled(true);
+        let scanner = Scanner::new(Arc::clone(&cache), config).with_profiler(Arc::clone(&profiler));
+        assert!(scanner.scan_first(&["USD".to_string()], -1.0).is_some());
+        assert!(!scanner.scan(&["USD".to_string()]).is_empty());
+
+        let profile = profiler.snapshot();
+        assert_eq!(profile.scans_profiled, 2);
+        assert!(profile.avg_paths_evaluated >= 1.0);
+        assert_eq!(profile.phases.len(), 5);
+        assert!(profile.phases.iter().all(|p| p.max_us >= p.last_us));
+        let pct: f64 = profile.phases.iter().map(|p| p.pct_of_scan).sum();
+        assert!((pct - 100.0).abs() < 1e-6);
+        assert!(profile.folded.iter().any(|f| f.starts_with("scan;graph_update ")));
+
+        profiler.reset();
+        assert_eq!(profiler.snapshot().scans_profiled, 0);
+    }
+}
@@ -2,21 +2,170 @@
 #![allow(dead_code)]
 
 use crate::order_book::OrderBookCache;
-use crate::types::{EngineConfig, LegDetail, Opportunity, OrderBookHealth, PriceEdge};
-use chrono::Utc;
+use crate::scan_profile::{ScanPhase, ScanProfiler, ScanTrace};
+use crate::stablecoin::{is_stable, is_stable_cycle, StablecoinPolicy};
+use crate::types::{EngineConfig, InverseQuote, LegDetail, Opportunity, OrderBook, OrderBookHealth, PriceEdge, Strategy};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use parking_lot::RwLock;
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::EdgeRef;
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use uuid::Uuid;
 
+/// Levels each side a book needs before it is scanned
+pub const MIN_BOOK_LEVELS: usize = 3;
+
+/// Gross profit past which a cycle is taken as a data error
+const MAX_REALISTIC_PROFIT_PCT: f64 = 5.0;
+
 /// Arbitrage scanner using directed graph
 pub struct Scanner {
     cache: Arc<OrderBookCache>,
     config: EngineConfig,
     health: Arc<RwLock<OrderBookHealth>>,
+    liquidity: Option<LiquidityRequirement>,
+    /// Amount in the start currency pushed through each path by the liquidity check
+    liquidity_amount: f64,
+    /// Paths dropped because a leg couldn't absorb the trade amount
+    liquidity_filtered: Arc<AtomicU64>,
+    /// Phase timing, only set while profiling is enabled
+    profiler: Option<(Arc<ScanProfiler>, ScanTrace)>,
+    /// All-stable cycles: fees, threshold and whether they're scanned at all
+    stablecoins: StablecoinPolicy,
+    /// Currencies the graph is limited to (None = every cached currency)
+    currencies: Option<HashSet<String>>,
+    /// Rolling quality score a pair needs to be scanned (0 = off)
+    min_quality: f64,
+}
+
+/// Where the cycles found from the base currencies ended up
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CycleCounts {
+    /// Closed cycles found by the DFS
+    pub evaluated: u64,
+    /// All-stable cycles while stablecoin cycles are off
+    pub skipped_stable: u64,
+    /// Rejected while pricing (unrealistic gross profit, missing rate)
+    pub rejected_pricing: u64,
+    /// Net profit at or under the path length's threshold
+    pub below_threshold: u64,
+    /// Profitable but a leg couldn't absorb the trade amount
+    pub illiquid: u64,
+    pub profitable: u64,
+}
+
+impl CycleCounts {
+    fn add(&mut self, other: &CycleCounts) {
+        self.evaluated += other.evaluated;
+        self.skipped_stable += other.skipped_stable;
+        self.rejected_pricing += other.rejected_pricing;
+        self.below_threshold += other.below_threshold;
+        self.illiquid += other.illiquid;
+        self.profitable += other.profitable;
+    }
+}
+
+/// Everything one full scan looked at, for tuning thresholds
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanReport {
+    pub scanned_at: DateTime<Utc>,
+    pub base_currencies: Vec<String>,
+    /// Pairs with a cached price
+    pub pairs_considered: u32,
+    /// Pairs that made it into the graph, and why the others didn't
+    pub pairs: OrderBookHealth,
+    pub cycles: CycleCounts,
+    /// Best cycles by net profit, including ones that missed the threshold
+    /// or the liquidity check
+    pub top_opportunities: Vec<Opportunity>,
+    pub graph_ms: f64,
+    pub search_ms: f64,
+    pub total_ms: f64,
+}
+
+/// One leg of an inspected path
+#[derive(Debug, Clone, Serialize)]
+pub struct PathLegReport {
+    pub from: String,
+    pub to: String,
+    /// None when no registered pair links the two currencies
+    pub pair: Option<String>,
+    /// "buy" or "sell" (of the pair's base)
+    pub action: String,
+    /// Top-of-book price the scanner prices the leg at (quote per base)
+    pub best_price: Option<f64>,
+    /// Average fill price for the amount reaching this leg
+    pub avg_price: Option<f64>,
+    /// Average fill vs best price (basis points)
+    pub slippage_bps: Option<f64>,
+    /// Book levels the fill consumed
+    pub levels_used: usize,
+    pub amount_in: f64,
+    /// What the leg yields after its fee (None when the book can't absorb amount_in)
+    pub amount_out: Option<f64>,
+    pub fee_pct: f64,
+    pub book_levels: usize,
+    pub staleness_ms: Option<i64>,
+    /// Why the scanner's graph leaves this pair out (None when it's used)
+    pub excluded: Option<String>,
+}
+
+/// One path priced against the current books, with why the scanner
+/// would or wouldn't return it
+#[derive(Debug, Clone, Serialize)]
+pub struct PathReport {
+    pub path: String,
+    pub start_currency: String,
+    /// Start-currency amount walked through the books
+    pub amount: f64,
+    /// Top-of-book profit, as the scanner computes it (None when a leg has no price)
+    pub gross_profit_pct: Option<f64>,
+    pub fees_pct: f64,
+    pub net_profit_pct: Option<f64>,
+    pub threshold_pct: f64,
+    /// Net profit when `amount` walks the books, fees included
+    pub depth_net_profit_pct: Option<f64>,
+    pub amount_out: Option<f64>,
+    pub legs: Vec<PathLegReport>,
+    /// Scanner checks the path fails (empty when a scan would return it)
+    pub blocked_by: Vec<String>,
+}
+
+/// Which cycles a DFS closes: regular ones, or only all-stable ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanPass {
+    Regular,
+    Stable,
+}
+
+/// Per-leg liquidity requirement checked before a path is returned
+///
+/// Every leg must fill the amount flowing through it within the top
+/// `depth_levels` of its book, at an average price no more than
+/// `max_slippage_bps` away from the best level.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct LiquidityRequirement {
+    /// Order book levels a leg may consume
+    pub depth_levels: usize,
+    /// Max average-fill slippage vs top of book (basis points)
+    pub max_slippage_bps: f64,
+}
+
+/// Currency graph: nodes are currencies, edges carry (pair, rate, action)
+type PriceGraph = DiGraph<String, (String, f64, String)>;
+
+/// Profit of a closed cycle, before it's turned into an Opportunity
+#[derive(Debug, Clone, Copy)]
+struct PathProfit {
+    gross_profit_pct: f64,
+    fees_pct: f64,
+    net_profit_pct: f64,
+    is_profitable: bool,
 }
 
 /// Internal representation of an arbitrage path
@@ -28,13 +177,126 @@ struct ArbitragePath {
     rates: Vec<f64>,       // Exchange rates used
 }
 
+impl ArbitragePath {
+    /// The same cycle starting `offset` legs later
+    fn rotated(&self, offset: usize) -> ArbitragePath {
+        let legs = self.pairs.len();
+        let currencies: Vec<String> = (0..=legs).map(|i| self.currencies[(offset + i) % legs].clone()).collect();
+        let mut pairs = self.pairs.clone();
+        let mut actions = self.actions.clone();
+        let mut rates = self.rates.clone();
+        pairs.rotate_left(offset);
+        actions.rotate_left(offset);
+        rates.rotate_left(offset);
+        ArbitragePath { currencies, pairs, actions, rates }
+    }
+
+    /// The same cycle traded the other way round, priced from the graph's
+    /// opposite edges (None when a leg can't be traded that way)
+    fn reversed(&self, graph: &PriceGraph, node_map: &HashMap<String, NodeIndex>) -> Option<ArbitragePath> {
+        let currencies: Vec<String> = self.currencies.iter().rev().cloned().collect();
+        let node = |currency: &String| node_map.get(currency).copied();
+        let legs = self.pairs.len();
+        let (mut pairs, mut actions, mut rates) = (Vec::with_capacity(legs), Vec::with_capacity(legs), Vec::with_capacity(legs));
+        for (hop, pair) in currencies.windows(2).zip(self.pairs.iter().rev()) {
+            let (from, to) = (node(&hop[0])?, node(&hop[1])?);
+            let (_, rate, action) = graph.edges_connecting(from, to).map(|e| e.weight()).find(|(p, _, _)| p == pair)?;
+            pairs.push(pair.clone());
+            actions.push(action.clone());
+            rates.push(*rate);
+        }
+        Some(ArbitragePath { currencies, pairs, actions, rates })
+    }
+}
+
 impl Scanner {
     pub fn new(cache: Arc<OrderBookCache>, config: EngineConfig) -> Self {
         Self { 
             cache, 
             config,
             health: Arc::new(RwLock::new(OrderBookHealth::default())),
+            liquidity: None,
+            liquidity_amount: 0.0,
+            liquidity_filtered: Arc::new(AtomicU64::new(0)),
+            profiler: None,
+            stablecoins: StablecoinPolicy::default(),
+            currencies: None,
+            min_quality: 0.0,
+        }
+    }
+
+    /// Leave out pairs whose rolling quality score is under `min_score`
+    /// (pairs without a minute of history yet are kept)
+    pub fn with_min_quality(mut self, min_score: f64) -> Self {
+        self.min_quality = min_score;
+        self
+    }
+
+    /// Only scan cycles through `currencies` (a named universe's subgraph)
+    pub fn with_currencies(mut self, currencies: HashSet<String>) -> Self {
+        self.currencies = Some(currencies);
+        self
+    }
+
+    /// Fee, threshold and toggle for all-stable cycles (disabled by default)
+    pub fn with_stablecoins(mut self, policy: StablecoinPolicy) -> Self {
+        self.stablecoins = policy;
+        self
+    }
+
+    /// Report per-phase scan timings to `profiler` (no-op while it's disabled)
+    pub fn with_profiler(mut self, profiler: Arc<ScanProfiler>) -> Self {
+        if profiler.is_enabled() {
+            self.profiler = Some((profiler, ScanTrace::default()));
         }
+        self
+    }
+
+    /// Run `f`, charging its time to `phase` when profiling
+    #[inline]
+    fn timed<T>(&self, phase: ScanPhase, f: impl FnOnce() -> T) -> T {
+        match &self.profiler {
+            Some((_, trace)) => {
+                let start = Instant::now();
+                let out = f();
+                trace.add(phase, start.elapsed());
+                out
+            }
+            None => f(),
+        }
+    }
+
+    fn start_profile(&self) -> Option<Instant> {
+        self.profiler.as_ref().map(|(_, trace)| {
+            trace.reset();
+            Instant::now()
+        })
+    }
+
+    /// Record a finished scan: `started` from start_profile, `searching` when the DFS began
+    fn finish_profile(&self, started: Option<Instant>, searching: Option<Instant>) {
+        if let (Some((profiler, trace)), Some(started), Some(searching)) = (&self.profiler, started, searching) {
+            profiler.record(trace.finish(searching - started, searching.elapsed()));
+        }
+    }
+
+    /// Filter paths whose legs can't absorb `trade_amount` (start currency).
+    /// `filtered` is incremented for every rejected path.
+    pub fn with_liquidity(
+        mut self,
+        requirement: LiquidityRequirement,
+        trade_amount: f64,
+        filtered: Arc<AtomicU64>,
+    ) -> Self {
+        self.liquidity = Some(requirement);
+        self.liquidity_amount = trade_amount;
+        self.liquidity_filtered = filtered;
+        self
+    }
+
+    /// Number of paths rejected by the liquidity requirement
+    pub fn liquidity_filtered(&self) -> u64 {
+        self.liquidity_filtered.load(Ordering::Relaxed)
     }
 
     /// Get current order book health stats
@@ -44,36 +306,119 @@ impl Scanner {
 
     /// Scan for all arbitrage opportunities
     pub fn scan(&self, base_currencies: &[String]) -> Vec<Opportunity> {
+        let mut result: Vec<Opportunity> = self.scan_batch(base_currencies).into_values().flatten().collect();
+        result.sort_by(|a, b| b.net_profit_pct.total_cmp(&a.net_profit_pct));
+        result
+    }
+
+    /// Scan every base currency in one traversal, returning the profitable,
+    /// liquid opportunities per base (best first).
+    ///
+    /// A cycle through several bases is enumerated once, from the first of
+    /// them in `base_currencies`, priced once (profit doesn't depend on where
+    /// a cycle starts) and then rotated to start at each base it visits.
+    pub fn scan_batch(&self, base_currencies: &[String]) -> HashMap<String, Vec<Opportunity>> {
+        let profile_start = self.start_profile();
+        let mut by_base: HashMap<String, Vec<Opportunity>> = base_currencies
+            .iter()
+            .map(|base| (base.clone(), Vec::new()))
+            .collect();
         let prices = self.cache.get_all_prices();
-        
+
         if prices.is_empty() {
-            return vec![];
+            return by_base;
         }
-        
+
         // Build graph
         let (graph, node_map) = self.build_graph(&prices);
-        
-        // Find opportunities from each base currency in parallel
-        let opportunities: Vec<Opportunity> = base_currencies
+        let search_start = profile_start.map(|_| Instant::now());
+
+        // Bases in the graph, in order, without repeats
+        let mut bases: Vec<(&str, NodeIndex)> = Vec::with_capacity(base_currencies.len());
+        for base in base_currencies {
+            if let Some(&idx) = node_map.get(base) {
+                if !bases.iter().any(|&(_, i)| i == idx) {
+                    bases.push((base, idx));
+                }
+            }
+        }
+        let base_names: HashSet<&str> = bases.iter().map(|&(name, _)| name).collect();
+
+        // Each DFS skips the bases before it: cycles through them were already found
+        let found: Vec<(String, Opportunity)> = bases
             .par_iter()
-            .flat_map(|base| {
-                self.find_opportunities_from(&graph, &node_map, base, &prices)
+            .enumerate()
+            .flat_map_iter(|(i, &(base, start_idx))| {
+                let earlier: HashSet<NodeIndex> = bases[..i].iter().map(|&(_, idx)| idx).collect();
+                let mut paths = Vec::new();
+                self.dfs_find_cycles(
+                    &graph,
+                    start_idx,
+                    start_idx,
+                    &mut vec![base.to_string()],
+                    &mut vec![],
+                    &mut vec![],
+                    &mut vec![],
+                    &mut HashSet::new(),
+                    &earlier,
+                    4,
+                    &mut paths,
+                );
+                paths
+                    .into_iter()
+                    .flat_map(|path| self.evaluate_for_bases(&graph, &node_map, &path, &base_names))
+                    .collect::<Vec<_>>()
             })
             .collect();
-        
-        // Sort by profit and deduplicate
-        let mut unique: HashMap<String, Opportunity> = HashMap::new();
-        for opp in opportunities {
-            let key = opp.path.clone();
-            if !unique.contains_key(&key) || unique[&key].net_profit_pct < opp.net_profit_pct {
-                unique.insert(key, opp);
+
+        self.timed(ScanPhase::Filtering, || {
+            for (base, opp) in found {
+                by_base.entry(base).or_default().push(opp);
+            }
+            for opportunities in by_base.values_mut() {
+                opportunities.sort_by(|a, b| b.net_profit_pct.total_cmp(&a.net_profit_pct));
+                // A cycle found both ways comes back once, in its better direction
+                let mut seen = HashSet::new();
+                opportunities.retain(|o| seen.insert(o.path.clone()));
             }
+        });
+        self.finish_profile(profile_start, search_start);
+
+        by_base
+    }
+
+    /// Price a cycle once and keep it, rotated, for every base on it whose
+    /// trade amount the legs can absorb (in whichever direction nets more)
+    fn evaluate_for_bases(
+        &self,
+        graph: &PriceGraph,
+        node_map: &HashMap<String, NodeIndex>,
+        path: &ArbitragePath,
+        bases: &HashSet<&str>,
+    ) -> Vec<(String, Opportunity)> {
+        if !self.stablecoins.enabled && is_stable_cycle(&path.currencies) {
+            return Vec::new();
         }
-        
-        let mut result: Vec<Opportunity> = unique.into_values().collect();
-        result.sort_by(|a, b| b.net_profit_pct.partial_cmp(&a.net_profit_pct).unwrap());
-        
-        result
+        if let Some((_, trace)) = &self.profiler {
+            trace.add_path();
+        }
+        let profit = match self.timed(ScanPhase::ProfitEvaluation, || self.evaluate_path(path)) {
+            Some(profit) if profit.is_profitable => profit,
+            _ => return Vec::new(),
+        };
+
+        (0..path.pairs.len())
+            .filter(|&offset| bases.contains(path.currencies[offset].as_str()))
+            .filter_map(|offset| {
+                let rotated = if offset == 0 { path.clone() } else { path.rotated(offset) };
+                let opp = self.timed(ScanPhase::Serialization, || self.build_opportunity(&rotated, profit));
+                let (rotated, opp) = self.with_inverse(graph, node_map, &rotated, opp);
+                if !self.has_liquidity(&rotated) {
+                    return None;
+                }
+                Some((rotated.currencies[0].clone(), opp))
+            })
+            .collect()
     }
 
     /// Build directed graph from price data
@@ -81,7 +426,7 @@ impl Scanner {
     fn build_graph(
         &self,
         prices: &HashMap<String, PriceEdge>,
-    ) -> (DiGraph<String, (String, f64, String)>, HashMap<String, NodeIndex>) {
+    ) -> (PriceGraph, HashMap<String, NodeIndex>) {
         let mut graph = DiGraph::new();
         let mut node_map: HashMap<String, NodeIndex> = HashMap::new();
         
@@ -92,6 +437,9 @@ impl Scanner {
         let mut skipped_stale = 0u32;
         let mut skipped_bad_spread = 0u32;
         let mut skipped_no_price = 0u32;
+        let mut skipped_inconsistent = 0u32;
+        let mut skipped_low_quality = 0u32;
+        let mut excluded_restricted_edges = 0u32;
         let mut total_freshness_ms = 0.0f64;
         let mut total_spread_pct = 0.0f64;
         let mut total_depth = 0.0f64;
@@ -99,7 +447,10 @@ impl Scanner {
         
         // Add nodes for all currencies
         let currencies = self.cache.get_currencies();
-        for currency in currencies {
+        let allowed = |c: &String| {
+            self.currencies.as_ref().is_none_or(|set| set.contains(c)) && !self.cache.is_currency_blocked(c)
+        };
+        for currency in currencies.into_iter().filter(allowed) {
             let idx = graph.add_node(currency.clone());
             node_map.insert(currency, idx);
         }
@@ -108,6 +459,11 @@ impl Scanner {
         
         // Add edges for all pairs (bidirectional)
         for (pair, edge) in prices {
+            // Restricted currencies have no node; count what that leaves out
+            if self.cache.is_currency_blocked(&edge.base) || self.cache.is_currency_blocked(&edge.quote) {
+                excluded_restricted_edges += 2;
+                continue;
+            }
             let base_idx = match node_map.get(&edge.base) {
                 Some(idx) => *idx,
                 None => continue,
@@ -122,10 +478,25 @@ impl Scanner {
                 skipped_no_price += 1;
                 continue;
             }
+
+            // Skip pairs the consistency monitor flagged as broken or stale,
+            // or that the index price check found off the external market
+            if self.cache.is_quarantined(pair) || self.cache.is_anomalous(pair) {
+                skipped_inconsistent += 1;
+                continue;
+            }
+
+            if self.min_quality > 0.0 {
+                let quality = self.cache.get_quality(pair).filter(|q| q.minutes_observed >= 1.0);
+                if quality.is_some_and(|q| q.score < self.min_quality) {
+                    skipped_low_quality += 1;
+                    continue;
+                }
+            }
             
             // CRITICAL FIX: Skip pairs WITHOUT valid order book data
             // This prevents using stale ticker prices for illiquid pairs
-            let order_book = match self.cache.get_order_book(pair) {
+            let order_book = match self.cache.get_order_book_pooled(pair) {
                 Some(book) => book,
                 None => {
                     skipped_no_orderbook += 1;
@@ -134,7 +505,7 @@ impl Scanner {
             };
             
             // Validate order book has minimum depth (at least 3 levels each side)
-            if order_book.bids.len() < 3 || order_book.asks.len() < 3 {
+            if order_book.bids.len() < MIN_BOOK_LEVELS || order_book.asks.len() < MIN_BOOK_LEVELS {
                 skipped_thin_depth += 1;
                 continue;  // Too thin order book
             }
@@ -176,7 +547,7 @@ impl Scanner {
             
             // Final sanity check: spread should be reasonable (< 10%)
             let spread_pct = (ask - bid) / bid * 100.0;
-            if spread_pct > 10.0 || spread_pct < 0.0 {
+            if !(0.0..=10.0).contains(&spread_pct) {
                 skipped_bad_spread += 1;
                 continue;  // Unrealistic spread
             }
@@ -212,6 +583,9 @@ impl Scanner {
             health.skipped_stale = skipped_stale;
             health.skipped_bad_spread = skipped_bad_spread;
             health.skipped_no_price = skipped_no_price;
+            health.skipped_inconsistent = skipped_inconsistent;
+            health.skipped_low_quality = skipped_low_quality;
+            health.excluded_restricted_edges = excluded_restricted_edges;
             health.avg_freshness_ms = if freshness_count > 0 { 
                 total_freshness_ms / freshness_count as f64 
             } else { 
@@ -230,7 +604,8 @@ impl Scanner {
             health.last_update = Utc::now().to_rfc3339();
         }
         
-        let total_skipped = skipped_no_orderbook + skipped_thin_depth + skipped_stale + skipped_bad_spread + skipped_no_price;
+        let total_skipped = skipped_no_orderbook + skipped_thin_depth + skipped_stale + skipped_bad_spread
+            + skipped_no_price + skipped_inconsistent + skipped_low_quality;
         tracing::info!(
             "Graph built: {} pairs with valid order books, {} pairs skipped (no/stale/thin order book)",
             valid_pairs, total_skipped
@@ -239,13 +614,239 @@ impl Scanner {
         (graph, node_map)
     }
 
-    /// Find opportunities starting from a specific currency
-    fn find_opportunities_from(
+    /// Full scan that also reports what was filtered where, and the best
+    /// `top_n` cycles whether or not they cleared the threshold
+    pub fn scan_detailed(&self, base_currencies: &[String], top_n: usize) -> ScanReport {
+        let started = Instant::now();
+        let prices = self.cache.get_all_prices();
+        let (graph, node_map) = self.build_graph(&prices);
+        let graph_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        let search_start = Instant::now();
+        let per_base: Vec<(Vec<Opportunity>, CycleCounts)> = base_currencies
+            .par_iter()
+            .map(|base| {
+                let mut counts = CycleCounts::default();
+                let mut rejected = Vec::new();
+                let mut found = self.evaluate_cycles_from(&graph, &node_map, base, &mut counts, Some(&mut rejected));
+                found.append(&mut rejected);
+                (found, counts)
+            })
+            .collect();
+        let search_ms = search_start.elapsed().as_secs_f64() * 1000.0;
+
+        let mut cycles = CycleCounts::default();
+        let mut unique: HashMap<String, Opportunity> = HashMap::new();
+        for (found, counts) in per_base {
+            cycles.add(&counts);
+            for opp in found {
+                if unique.get(&opp.path).is_none_or(|o| o.net_profit_pct < opp.net_profit_pct) {
+                    unique.insert(opp.path.clone(), opp);
+                }
+            }
+        }
+        let mut top: Vec<Opportunity> = unique.into_values().collect();
+        top.sort_by(|a, b| b.net_profit_pct.total_cmp(&a.net_profit_pct));
+        top.truncate(top_n);
+
+        ScanReport {
+            scanned_at: Utc::now(),
+            base_currencies: base_currencies.to_vec(),
+            pairs_considered: prices.len() as u32,
+            pairs: self.get_health(),
+            cycles,
+            top_opportunities: top,
+            graph_ms,
+            search_ms,
+            total_ms: started.elapsed().as_secs_f64() * 1000.0,
+        }
+    }
+
+    /// Price one path ("USD → BTC → ETH → USD") against the current books,
+    /// walking `amount` of its start currency through each leg, and list
+    /// every scanner check it fails
+    pub fn inspect_path(&self, path: &str, amount: f64) -> Result<PathReport, String> {
+        let currencies: Vec<String> = path.split('→').map(|c| c.trim().to_uppercase()).collect();
+        if currencies.len() < 3 || currencies.iter().any(|c| c.is_empty()) {
+            return Err(format!("Invalid path '{}' (expected e.g. \"USD → BTC → ETH → USD\")", path));
+        }
+        if currencies.first() != currencies.last() {
+            return Err("Path must end in its start currency".to_string());
+        }
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err("Amount must be positive".to_string());
+        }
+
+        let mut blocked_by = Vec::new();
+        let mut legs = Vec::with_capacity(currencies.len() - 1);
+        let mut rate_product = Some(1.0);
+        let mut fees_pct = 0.0;
+        let mut top_amount = 1.0;
+        let mut flowing = Some(amount);
+
+        for (index, leg) in currencies.windows(2).enumerate() {
+            let (from, to) = (&leg[0], &leg[1]);
+            let fee = self.stablecoins.leg_fee(from, to, self.config.fee_rate);
+            fees_pct += fee * 100.0;
+            let mut report = PathLegReport {
+                from: from.clone(),
+                to: to.clone(),
+                pair: None,
+                action: String::new(),
+                best_price: None,
+                avg_price: None,
+                slippage_bps: None,
+                levels_used: 0,
+                amount_in: flowing.unwrap_or(0.0),
+                amount_out: None,
+                fee_pct: fee * 100.0,
+                book_levels: 0,
+                staleness_ms: None,
+                excluded: None,
+            };
+
+            let Some(route) = self.cache.find_pair(from, to) else {
+                blocked_by.push(format!("leg {}: no pair trades {} -> {}", index + 1, from, to));
+                rate_product = None;
+                flowing = None;
+                legs.push(report);
+                continue;
+            };
+            let is_sell = route.sells_base;
+            report.action = if is_sell { "sell" } else { "buy" }.to_string();
+            report.excluded = self.pair_exclusion(&route.pair).map(str::to_string);
+            if let Some(reason) = &report.excluded {
+                blocked_by.push(format!("leg {}: {} {}", index + 1, route.pair, reason));
+            }
+
+            match self.cache.get_order_book_pooled(&route.pair) {
+                Some(book) => {
+                    report.book_levels = book.bids.len().min(book.asks.len());
+                    report.staleness_ms = Some(book.staleness_ms());
+                    let best = if is_sell { book.bids.first() } else { book.asks.first() }.map(|l| l.price);
+                    report.best_price = best.filter(|p| *p > 0.0);
+                    match report.best_price {
+                        Some(price) => {
+                            let rate = if is_sell { price } else { 1.0 / price };
+                            rate_product = rate_product.map(|r| r * rate);
+                            top_amount *= rate * (1.0 - fee);
+                        }
+                        None => rate_product = None,
+                    }
+
+                    let fill = flowing.and_then(|amount_in| walk_book(&book, is_sell, amount_in, usize::MAX));
+                    if let Some(fill) = &fill {
+                        report.avg_price = Some(fill.avg_price);
+                        report.slippage_bps = Some(fill.slippage_bps);
+                        report.levels_used = fill.levels_used;
+                        report.amount_out = Some(fill.received * (1.0 - fee));
+                        if let Some(req) = &self.liquidity {
+                            if fill.levels_used > req.depth_levels || fill.slippage_bps > req.max_slippage_bps {
+                                blocked_by.push(format!(
+                                    "leg {}: {} fills {:.6} over {} levels / {:.1} bps (limit {} / {} bps)",
+                                    index + 1, route.pair, report.amount_in, fill.levels_used,
+                                    fill.slippage_bps, req.depth_levels, req.max_slippage_bps
+                                ));
+                            }
+                        }
+                    } else if flowing.is_some() {
+                        blocked_by.push(format!("leg {}: {} book can't absorb {:.6}", index + 1, route.pair, report.amount_in));
+                    }
+                    flowing = report.amount_out;
+                }
+                None => {
+                    rate_product = None;
+                    flowing = None;
+                }
+            }
+            report.pair = Some(route.pair);
+            legs.push(report);
+        }
+
+        let stable = is_stable_cycle(&currencies);
+        let threshold_pct = if stable {
+            self.stablecoins.min_profit_pct
+        } else {
+            self.config.threshold_for(legs.len()) * 100.0
+        };
+        let gross_profit_pct = rate_product.map(|r| (r - 1.0) * 100.0);
+        let net_profit_pct = gross_profit_pct.map(|_| (top_amount - 1.0) * 100.0);
+        if stable && !self.stablecoins.enabled {
+            blocked_by.push("stablecoin cycles are disabled".to_string());
+        }
+        if gross_profit_pct.is_some_and(|g| g.abs() > MAX_REALISTIC_PROFIT_PCT) {
+            blocked_by.push(format!("gross profit past the {}% sanity limit", MAX_REALISTIC_PROFIT_PCT));
+        }
+        if let Some(net) = net_profit_pct.filter(|net| *net <= threshold_pct) {
+            blocked_by.push(format!("net {:.4}% is not above the {:.4}% threshold", net, threshold_pct));
+        }
+
+        Ok(PathReport {
+            path: currencies.join(" → "),
+            start_currency: currencies[0].clone(),
+            amount,
+            gross_profit_pct,
+            fees_pct,
+            net_profit_pct,
+            threshold_pct,
+            depth_net_profit_pct: flowing.map(|out| (out / amount - 1.0) * 100.0),
+            amount_out: flowing,
+            legs,
+            blocked_by,
+        })
+    }
+
+    /// Why `build_graph` would leave `pair` out, checked the same way
+    fn pair_exclusion(&self, pair: &str) -> Option<&'static str> {
+        let Some(edge) = self.cache.get_price(pair) else {
+            return Some("has no price");
+        };
+        if self.cache.is_currency_blocked(&edge.base) || self.cache.is_currency_blocked(&edge.quote) {
+            return Some("trades a restricted currency");
+        }
+        if let Some(set) = &self.currencies {
+            if !set.contains(&edge.base) || !set.contains(&edge.quote) {
+                return Some("is outside the scanned universe");
+            }
+        }
+        if edge.bid <= 0.0 || edge.ask <= 0.0 {
+            return Some("has no price");
+        }
+        if self.cache.is_quarantined(pair) || self.cache.is_anomalous(pair) {
+            return Some("is quarantined or off the index price");
+        }
+        if self.min_quality > 0.0 {
+            let quality = self.cache.get_quality(pair).filter(|q| q.minutes_observed >= 1.0);
+            if quality.is_some_and(|q| q.score < self.min_quality) {
+                return Some("is below the minimum quality score");
+            }
+        }
+        let Some(book) = self.cache.get_order_book_pooled(pair) else {
+            return Some("has no order book");
+        };
+        if book.bids.len() < MIN_BOOK_LEVELS || book.asks.len() < MIN_BOOK_LEVELS {
+            return Some("has a thin book");
+        }
+        if book.staleness_ms() > crate::types::MAX_ORDERBOOK_STALENESS_MS {
+            return Some("has a stale book");
+        }
+        let (bid, ask) = (book.bids[0].price, book.asks[0].price);
+        if bid > 0.0 && ask > 0.0 && !(0.0..=10.0).contains(&((ask - bid) / bid * 100.0)) {
+            return Some("has an unrealistic spread");
+        }
+        None
+    }
+
+    /// Price every cycle from `start`, returning the profitable, liquid ones.
+    /// Outcomes are tallied in `counts`; priced cycles that missed the
+    /// threshold or the liquidity check go to `rejected` when given.
+    fn evaluate_cycles_from(
         &self,
-        graph: &DiGraph<String, (String, f64, String)>,
+        graph: &PriceGraph,
         node_map: &HashMap<String, NodeIndex>,
         start: &str,
-        _prices: &HashMap<String, PriceEdge>,
+        counts: &mut CycleCounts,
+        mut rejected: Option<&mut Vec<Opportunity>>,
     ) -> Vec<Opportunity> {
         let start_idx = match node_map.get(start) {
             Some(idx) => *idx,
@@ -266,26 +867,48 @@ impl Scanner {
             &mut vec![],
             &mut vec![],
             &mut HashSet::new(),
+            &HashSet::new(),
             max_legs,
             &mut paths,
         );
         
         // Convert paths to opportunities
+        counts.evaluated += paths.len() as u64;
         for path in paths {
-            if let Some(opp) = self.path_to_opportunity(&path, start) {
-                if opp.is_profitable {
+            if !self.stablecoins.enabled && is_stable_cycle(&path.currencies) {
+                counts.skipped_stable += 1;
+                continue;
+            }
+            let Some(opp) = self.path_to_opportunity(&path, start) else {
+                counts.rejected_pricing += 1;
+                continue;
+            };
+            let (path, opp) = if opp.is_profitable { self.with_inverse(graph, node_map, &path, opp) } else { (path, opp) };
+            if !opp.is_profitable {
+                counts.below_threshold += 1;
+            } else if !self.has_liquidity(&path) {
+                counts.illiquid += 1;
+            } else {
+                counts.profitable += 1;
+                // Both directions of a cycle come back as the better one
+                if !opportunities.iter().any(|o: &Opportunity| o.path == opp.path) {
                     opportunities.push(opp);
                 }
+                continue;
+            }
+            if let Some(rejected) = rejected.as_deref_mut() {
+                rejected.push(opp);
             }
         }
         
         opportunities
     }
 
-    /// DFS to find all cycles back to start
+    /// DFS to find all cycles back to start that avoid `excluded` currencies
+    #[allow(clippy::too_many_arguments)]
     fn dfs_find_cycles(
         &self,
-        graph: &DiGraph<String, (String, f64, String)>,
+        graph: &PriceGraph,
         start: NodeIndex,
         current: NodeIndex,
         currencies: &mut Vec<String>,
@@ -293,6 +916,7 @@ impl Scanner {
         actions: &mut Vec<String>,
         rates: &mut Vec<f64>,
         visited_pairs: &mut HashSet<String>,
+        excluded: &HashSet<NodeIndex>,
         max_legs: usize,
         results: &mut Vec<ArbitragePath>,
     ) {
@@ -323,7 +947,7 @@ impl Scanner {
             }
             
             // Don't revisit currencies except start
-            if target != start && currencies.contains(target_currency) {
+            if target != start && (currencies.contains(target_currency) || excluded.contains(&target)) {
                 continue;
             }
             
@@ -343,6 +967,7 @@ impl Scanner {
                 actions,
                 rates,
                 visited_pairs,
+                excluded,
                 max_legs,
                 results,
             );
@@ -357,6 +982,43 @@ impl Scanner {
 
     /// Convert a path to an Opportunity with profit calculations
     fn path_to_opportunity(&self, path: &ArbitragePath, _start: &str) -> Option<Opportunity> {
+        if let Some((_, trace)) = &self.profiler {
+            trace.add_path();
+        }
+        let profit = self.timed(ScanPhase::ProfitEvaluation, || self.evaluate_path(path))?;
+        Some(self.timed(ScanPhase::Serialization, || self.build_opportunity(path, profit)))
+    }
+
+    /// Price the reverse of `path` and keep the better direction, with the
+    /// other attached as its inverse. `opp` is `path`'s opportunity.
+    fn with_inverse(
+        &self,
+        graph: &PriceGraph,
+        node_map: &HashMap<String, NodeIndex>,
+        path: &ArbitragePath,
+        mut opp: Opportunity,
+    ) -> (ArbitragePath, Opportunity) {
+        let Some(reverse) = path.reversed(graph, node_map) else { return (path.clone(), opp) };
+        let Some(profit) = self.timed(ScanPhase::ProfitEvaluation, || self.evaluate_path(&reverse)) else {
+            return (path.clone(), opp);
+        };
+        if profit.net_profit_pct > opp.net_profit_pct {
+            let mut better = self.build_opportunity(&reverse, profit);
+            better.inverse = Some(InverseQuote::of(&opp));
+            return (reverse, better);
+        }
+        opp.inverse = Some(InverseQuote {
+            path: reverse.currencies.join(" → "),
+            gross_profit_pct: profit.gross_profit_pct,
+            fees_pct: profit.fees_pct,
+            net_profit_pct: profit.net_profit_pct,
+            is_profitable: profit.is_profitable,
+        });
+        (path.clone(), opp)
+    }
+
+    /// Net profit of a path after fees (None for empty or unrealistic paths)
+    fn evaluate_path(&self, path: &ArbitragePath) -> Option<PathProfit> {
         if path.rates.is_empty() {
             return None;
         }
@@ -369,14 +1031,12 @@ impl Scanner {
             amount *= rate;
         }
         
-        // Calculate fees (fee per leg)
-        let fee_per_leg = self.config.fee_rate;
-        let total_legs = path.pairs.len();
-        let fees_pct = fee_per_leg * 100.0 * total_legs as f64;
-        
-        // Apply fees
-        for _ in 0..total_legs {
-            amount *= 1.0 - fee_per_leg;
+        // Calculate fees per leg (stable-stable legs on the stablecoin schedule)
+        let mut fees_pct = 0.0;
+        for leg in path.currencies.windows(2) {
+            let fee = self.stablecoins.leg_fee(&leg[0], &leg[1], self.config.fee_rate);
+            fees_pct += fee * 100.0;
+            amount *= 1.0 - fee;
         }
         
         // Calculate profits
@@ -386,7 +1046,6 @@ impl Scanner {
         // SANITY CHECK: Reject unrealistic profits
         // Real arbitrage opportunities are typically 0.01% - 1%
         // Anything above 5% is almost certainly a data error
-        const MAX_REALISTIC_PROFIT_PCT: f64 = 5.0;
         if gross_profit_pct.abs() > MAX_REALISTIC_PROFIT_PCT {
             tracing::debug!(
                 "Rejecting unrealistic opportunity: {} with {:.2}% gross profit (max: {}%)",
@@ -397,8 +1056,20 @@ impl Scanner {
             return None;
         }
         
-        let is_profitable = net_profit_pct > self.config.min_profit_threshold * 100.0;
-        
+        let threshold_pct = if is_stable_cycle(&path.currencies) {
+            self.stablecoins.min_profit_pct
+        } else {
+            self.config.threshold_for(path.pairs.len()) * 100.0
+        };
+        let is_profitable = net_profit_pct > threshold_pct;
+
+        Some(PathProfit { gross_profit_pct, fees_pct, net_profit_pct, is_profitable })
+    }
+
+    fn build_opportunity(&self, path: &ArbitragePath, profit: PathProfit) -> Opportunity {
+        let total_legs = path.pairs.len();
+        let stable = is_stable_cycle(&path.currencies);
+
         // Build path string
         let path_str = path.currencies.join(" → ");
 
@@ -413,19 +1084,72 @@ impl Scanner {
             })
             .collect();
         
-        Some(Opportunity {
+        Opportunity {
             id: Uuid::new_v4().to_string(),
             path: path_str,
             legs: total_legs,
-            gross_profit_pct,
-            fees_pct,
-            net_profit_pct,
-            is_profitable,
+            gross_profit_pct: profit.gross_profit_pct,
+            fees_pct: profit.fees_pct,
+            net_profit_pct: profit.net_profit_pct,
+            is_profitable: profit.is_profitable,
             detected_at: Utc::now(),
-            fee_rate: self.config.fee_rate,
+            fee_rate: if stable { self.stablecoins.fee_rate } else { self.config.fee_rate },
             fee_source: self.config.fee_source.clone(),
             legs_detail,
-        })
+            strategy: if stable { Strategy::Stablecoin } else { Strategy::for_legs(total_legs) },
+            tags: Vec::new(),
+            inverse: None,
+        }
+    }
+
+    /// Check every leg of a path against the liquidity requirement (if any).
+    /// Only called for paths that already clear the profit threshold.
+    fn has_liquidity(&self, path: &ArbitragePath) -> bool {
+        let requirement = match &self.liquidity {
+            Some(r) => r,
+            None => return true,
+        };
+        let legs = path.pairs.iter().map(String::as_str).zip(path.actions.iter().map(String::as_str));
+        self.timed(ScanPhase::Filtering, || self.legs_fill(&path.currencies.join(" → "), legs, self.liquidity_amount, requirement))
+    }
+
+    /// Re-check an opportunity's legs at the amount it will actually trade
+    /// (in its start currency), once tier sizing has picked it
+    pub fn opportunity_fills(&self, opp: &Opportunity, amount: f64) -> bool {
+        let requirement = match &self.liquidity {
+            Some(r) => r,
+            None => return true,
+        };
+        let legs = opp.legs_detail.iter().map(|leg| (leg.pair.as_str(), leg.action.as_str()));
+        self.legs_fill(&opp.path, legs, amount, requirement)
+    }
+
+    fn legs_fill<'a>(
+        &self,
+        label: &str,
+        legs: impl Iterator<Item = (&'a str, &'a str)>,
+        mut amount: f64,
+        requirement: &LiquidityRequirement,
+    ) -> bool {
+        for (pair, action) in legs {
+            let filled = self.cache
+                .get_order_book_pooled(pair)
+                .and_then(|book| fill_within_depth(&book, action, amount, requirement));
+
+            match filled {
+                Some(out) => amount = out,
+                None => {
+                    self.liquidity_filtered.fetch_add(1, Ordering::Relaxed);
+                    tracing::debug!(
+                        "Filtering {}: {} {} can't absorb {:.6} within {} levels / {} bps",
+                        label, action, pair, amount,
+                        requirement.depth_levels, requirement.max_slippage_bps
+                    );
+                    return false;
+                }
+            }
+        }
+        true
     }
 
     /// Scan for opportunities with specific pairs only
@@ -458,6 +1182,7 @@ impl Scanner {
     /// HFT-optimized scan: returns FIRST opportunity above threshold
     /// NO sorting, NO collecting all paths - stops immediately on first match
     pub fn scan_first(&self, base_currencies: &[String], min_profit_threshold: f64) -> Option<Opportunity> {
+        let profile_start = self.start_profile();
         let prices = self.cache.get_all_prices();
 
         if prices.is_empty() {
@@ -466,29 +1191,45 @@ impl Scanner {
 
         // Build graph (same as regular scan)
         let (graph, node_map) = self.build_graph(&prices);
+        let search_start = profile_start.map(|_| Instant::now());
 
-        // Search each base currency SEQUENTIALLY (no parallel overhead for early exit)
-        for base in base_currencies {
-            if let Some(opp) = self.find_first_opportunity_from(
+        // Search each base currency SEQUENTIALLY (no parallel overhead for early exit).
+        // Stable cycles only get a look when no regular one qualified.
+        let found = base_currencies.iter().find_map(|base| {
+            self.find_first_opportunity_from(
                 &graph,
                 &node_map,
                 base,
-                min_profit_threshold
-            ) {
-                return Some(opp);
+                min_profit_threshold,
+                ScanPass::Regular,
+            )
+        }).or_else(|| {
+            if !self.stablecoins.enabled {
+                return None;
             }
-        }
+            base_currencies.iter().filter(|base| is_stable(base)).find_map(|base| {
+                self.find_first_opportunity_from(
+                    &graph,
+                    &node_map,
+                    base,
+                    self.stablecoins.min_profit_pct / 100.0,
+                    ScanPass::Stable,
+                )
+            })
+        });
+        self.finish_profile(profile_start, search_start);
 
-        None
+        found
     }
 
     /// Find FIRST opportunity from a base currency that meets threshold
     fn find_first_opportunity_from(
         &self,
-        graph: &DiGraph<String, (String, f64, String)>,
+        graph: &PriceGraph,
         node_map: &HashMap<String, NodeIndex>,
         start: &str,
         min_profit_threshold: f64,
+        pass: ScanPass,
     ) -> Option<Opportunity> {
         let start_idx = match node_map.get(start) {
             Some(idx) => *idx,
@@ -500,6 +1241,7 @@ impl Scanner {
         // DFS with early termination - returns first profitable path
         self.dfs_find_first(
             graph,
+            node_map,
             start_idx,
             start_idx,
             &mut vec![start.to_string()],
@@ -510,13 +1252,16 @@ impl Scanner {
             max_legs,
             start,
             min_profit_threshold,
+            pass,
         )
     }
 
     /// DFS that returns immediately on first profitable cycle
+    #[allow(clippy::too_many_arguments)]
     fn dfs_find_first(
         &self,
-        graph: &DiGraph<String, (String, f64, String)>,
+        graph: &PriceGraph,
+        node_map: &HashMap<String, NodeIndex>,
         start: NodeIndex,
         current: NodeIndex,
         currencies: &mut Vec<String>,
@@ -527,6 +1272,7 @@ impl Scanner {
         max_legs: usize,
         start_currency: &str,
         min_profit_threshold: f64,
+        pass: ScanPass,
     ) -> Option<Opportunity> {
         if currencies.len() > max_legs + 1 {
             return None;
@@ -534,6 +1280,10 @@ impl Scanner {
 
         // Check if we're back at start (and have at least 2 legs)
         if current == start && currencies.len() > 2 {
+            // All-stable cycles belong to the stable pass
+            if pass == ScanPass::Regular && is_stable_cycle(currencies) {
+                return None;
+            }
             let path = ArbitragePath {
                 currencies: currencies.clone(),
                 pairs: pairs.clone(),
@@ -545,9 +1295,17 @@ impl Scanner {
             // Note: min_profit_threshold is a decimal (e.g., -0.02 for -2%),
             // but net_profit_pct is a percentage (e.g., -2.0 for -2%)
             // So we multiply threshold by 100 for comparison
+            // Longer cycles may carry their own (stricter) threshold
+            let threshold = match pass {
+                ScanPass::Regular => self.config.leg_threshold(path.pairs.len()).unwrap_or(min_profit_threshold),
+                ScanPass::Stable => min_profit_threshold,
+            };
             if let Some(opp) = self.path_to_opportunity(&path, start_currency) {
-                if opp.net_profit_pct > min_profit_threshold * 100.0 {
-                    return Some(opp);  // EARLY EXIT - first profitable path wins
+                if opp.net_profit_pct > threshold * 100.0 {
+                    let (path, opp) = self.with_inverse(graph, node_map, &path, opp);
+                    if self.has_liquidity(&path) {
+                        return Some(opp);  // EARLY EXIT - first profitable path wins
+                    }
                 }
             }
             return None;
@@ -569,6 +1327,11 @@ impl Scanner {
                 continue;
             }
 
+            // The stable pass never leaves stable currencies
+            if pass == ScanPass::Stable && !is_stable(target_currency) {
+                continue;
+            }
+
             // Recurse
             currencies.push(target_currency.clone());
             pairs.push(pair.clone());
@@ -579,6 +1342,7 @@ impl Scanner {
             // If recursive call found something, return it immediately
             if let Some(opp) = self.dfs_find_first(
                 graph,
+                node_map,
                 start,
                 target,
                 currencies,
@@ -589,6 +1353,7 @@ impl Scanner {
                 max_legs,
                 start_currency,
                 min_profit_threshold,
+                pass,
             ) {
                 return Some(opp);  // PROPAGATE EARLY EXIT
             }
@@ -603,3 +1368,430 @@ impl Scanner {
         None
     }
 }
+
+/// Walk the top levels of a book for one leg.
+///
+/// `amount` is what the leg spends: base units for a sell (hits bids),
+/// quote units for a buy (lifts asks). Returns the amount received before
+/// fees, or None if the leg can't fill within the depth/slippage limits.
+fn fill_within_depth(book: &OrderBook, action: &str, amount: f64, requirement: &LiquidityRequirement) -> Option<f64> {
+    walk_book(book, action == "sell", amount, requirement.depth_levels)
+        .filter(|fill| fill.slippage_bps <= requirement.max_slippage_bps)
+        .map(|fill| fill.received)
+}
+
+/// `amount` pushed through the top `max_levels` of a book
+struct BookFill {
+    received: f64,
+    /// Quote per base
+    avg_price: f64,
+    /// Average price vs the best level
+    slippage_bps: f64,
+    levels_used: usize,
+}
+
+/// Fill `amount` (base when selling, quote when buying) against the bids or
+/// asks; None when the levels can't absorb all of it
+fn walk_book(book: &OrderBook, is_sell: bool, amount: f64, max_levels: usize) -> Option<BookFill> {
+    let levels = if is_sell { &book.bids } else { &book.asks };
+    let best = levels.first()?.price;
+    if best <= 0.0 || amount <= 0.0 {
+        return None;
+    }
+
+    let mut remaining = amount;
+    let mut received = 0.0;
+    let mut levels_used = 0;
+    for level in levels.iter().take(max_levels) {
+        levels_used += 1;
+        if is_sell {
+            let qty = remaining.min(level.qty);
+            received += qty * level.price;
+            remaining -= qty;
+        } else {
+            let notional = remaining.min(level.qty * level.price);
+            received += notional / level.price;
+            remaining -= notional;
+        }
+        if remaining <= f64::EPSILON * amount {
+            remaining = 0.0;
+            break;
+        }
+    }
+    if remaining > 0.0 || received <= 0.0 {
+        return None;
+    }
+
+    // Average fill price in quote per base, compared with the best level
+    let avg_price = if is_sell { received / amount } else { amount / received };
+    let slippage_bps = (avg_price - best).abs() / best * 10_000.0;
+    Some(BookFill { received, avg_price, slippage_bps, levels_used })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderBookLevel;
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> OrderBook {
+        let mut book = OrderBook::new("BTC/USD".to_string());
+        book.bids = bids.iter().map(|&(price, qty)| OrderBookLevel { price, qty }).collect();
+        book.asks = asks.iter().map(|&(price, qty)| OrderBookLevel { price, qty }).collect();
+        book
+    }
+
+    #[test]
+    fn test_fill_within_depth() {
+        let book = book(&[(100.0, 1.0), (99.0, 1.0), (90.0, 10.0)], &[(101.0, 1.0), (102.0, 1.0), (110.0, 10.0)]);
+        let req = LiquidityRequirement { depth_levels: 2, max_slippage_bps: 100.0 };
+
+        // Sell 1.5 BTC: 1 @ 100 + 0.5 @ 99, avg 99.67 (~33 bps)
+        assert_eq!(fill_within_depth(&book, "sell", 1.5, &req), Some(149.5));
+        // Deeper than 2 levels
+        assert_eq!(fill_within_depth(&book, "sell", 2.5, &req), None);
+        // Buy with 101 USD fills exactly the best ask
+        assert_eq!(fill_within_depth(&book, "buy", 101.0, &req), Some(1.0));
+
+        // Same sell with a tighter slippage budget
+        let tight = LiquidityRequirement { max_slippage_bps: 10.0, ..req };
+        assert_eq!(fill_within_depth(&book, "sell", 1.5, &tight), None);
+    }
+
+    #[test]
+    fn test_thresholds_per_path_length() {
+        use crate::config_manager::parse_leg_thresholds;
+        use crate::order_book::PairInfo;
+
+        // USD → BTC → ETH → USD nets ~0.42% after 3 × 0.26% fees
+        let cache = Arc::new(OrderBookCache::new());
+        for (base, quote, mid) in [("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_530.0)] {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            let levels = |side: f64| (1..=3).map(|i| OrderBookLevel { price: mid * (1.0 + side * 0.00001 * i as f64), qty: 1_000.0 }).collect();
+            cache.update_snapshot(&pair, levels(-1.0), levels(1.0), 1);
+        }
+        let usd = ["USD".to_string()];
+        let scan = |thresholds: serde_json::Value| {
+            let config = EngineConfig {
+                min_profit_threshold: 0.001,
+                fee_rate: 0.0026,
+                fee_source: "test".to_string(),
+                leg_thresholds: parse_leg_thresholds(&thresholds).unwrap(),
+            };
+            let scanner = Scanner::new(Arc::clone(&cache), config);
+            (scanner.scan_first(&usd, 0.001).is_some(), scanner.scan(&usd).iter().any(|o| o.is_profitable))
+        };
+
+        assert_eq!(scan(serde_json::json!({})), (true, true));
+        assert_eq!(scan(serde_json::json!({"3": 0.005})), (false, false));
+        assert_eq!(scan(serde_json::json!({"3": 0.003, "4": 0.01})), (true, true));
+        // The longest configured length not above the path's applies
+        assert_eq!(scan(serde_json::json!({"2": 0.006})), (false, false));
+        assert_eq!(scan(serde_json::json!({"4": 0.01})), (true, true));
+
+        assert!(parse_leg_thresholds(&serde_json::json!({"one": 0.1})).is_err());
+        assert!(parse_leg_thresholds(&serde_json::json!({"3": "x"})).is_err());
+
+        // The detailed scan accounts for every cycle and keeps the near misses
+        let config = EngineConfig {
+            min_profit_threshold: 0.001,
+            fee_rate: 0.0026,
+            fee_source: "test".to_string(),
+            leg_thresholds: std::collections::BTreeMap::new(),
+        };
+        let report = Scanner::new(Arc::clone(&cache), config).scan_detailed(&usd, 10);
+        let cycles = report.cycles;
+        assert_eq!(report.pairs.valid_pairs, 3);
+        assert_eq!(cycles.profitable, 1);
+        assert!(cycles.below_threshold >= 1);
+        assert_eq!(
+            cycles.evaluated,
+            cycles.skipped_stable + cycles.rejected_pricing + cycles.below_threshold + cycles.illiquid + cycles.profitable
+        );
+        assert_eq!(report.top_opportunities[0].path, "USD → BTC → ETH → USD");
+        assert!(report.top_opportunities.iter().skip(1).all(|o| !o.is_profitable));
+
+        // Each find carries its reverse, priced on the opposite sides, and its legs' sides
+        let best = &report.top_opportunities[0];
+        let inverse = best.inverse.as_ref().unwrap();
+        assert_eq!(inverse.path, "USD → ETH → BTC → USD");
+        assert!(!inverse.is_profitable && inverse.net_profit_pct < best.net_profit_pct);
+        let sides: Vec<&str> = best.legs_detail.iter().map(|l| l.action.as_str()).collect();
+        assert_eq!(sides, ["buy", "buy", "sell"]);
+        let first = Scanner::new(Arc::clone(&cache), EngineConfig {
+            min_profit_threshold: 0.001,
+            fee_rate: 0.0026,
+            fee_source: "test".to_string(),
+            leg_thresholds: std::collections::BTreeMap::new(),
+        })
+        .scan_first(&usd, 0.001)
+        .unwrap();
+        assert_eq!(first.inverse.map(|i| i.path).as_deref(), Some("USD → ETH → BTC → USD"));
+    }
+
+    #[tokio::test]
+    async fn test_reverse_direction_wins() {
+        use crate::execution_sim::FakeExecutionBackend;
+        use crate::executor::{ExecutionEngine, OrderSide};
+        use crate::order_book::PairInfo;
+
+        // USD → BTC → ETH → USD nets ~0.42%; its reverse loses
+        let cache = Arc::new(OrderBookCache::new());
+        for (base, quote, mid) in [("BTC", "USD", 50_000.0), ("ETH", "BTC", 0.05), ("ETH", "USD", 2_530.0)] {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            let levels = |side: f64| (1..=3).map(|i| OrderBookLevel { price: mid * (1.0 + side * 0.00001 * i as f64), qty: 1_000.0 }).collect();
+            cache.update_snapshot(&pair, levels(-1.0), levels(1.0), 1);
+        }
+        let scanner = Scanner::new(Arc::clone(&cache), EngineConfig {
+            min_profit_threshold: 0.001,
+            fee_rate: 0.0026,
+            fee_source: "test".to_string(),
+            leg_thresholds: std::collections::BTreeMap::new(),
+        });
+        let (graph, node_map) = scanner.build_graph(&cache.get_all_prices());
+        let mut paths = Vec::new();
+        let usd = node_map["USD"];
+        scanner.dfs_find_cycles(
+            &graph,
+            usd,
+            usd,
+            &mut vec!["USD".to_string()],
+            &mut vec![],
+            &mut vec![],
+            &mut vec![],
+            &mut HashSet::new(),
+            &HashSet::new(),
+            4,
+            &mut paths,
+        );
+        let losing = paths.into_iter().find(|p| p.currencies == ["USD", "ETH", "BTC", "USD"]).unwrap();
+        let opp = scanner.path_to_opportunity(&losing, "USD").unwrap();
+        assert!(!opp.is_profitable);
+
+        // The reverse is kept, with the losing direction as its inverse
+        let (path, opp) = scanner.with_inverse(&graph, &node_map, &losing, opp);
+        assert_eq!(path.currencies, ["USD", "BTC", "ETH", "USD"]);
+        assert_eq!(path.pairs, ["BTC/USD", "ETH/BTC", "ETH/USD"]);
+        assert!(opp.is_profitable);
+        assert_eq!(opp.path, "USD → BTC → ETH → USD");
+        assert_eq!(opp.inverse.as_ref().map(|i| i.path.as_str()), Some("USD → ETH → BTC → USD"));
+        let sides: Vec<&str> = opp.legs_detail.iter().map(|l| l.action.as_str()).collect();
+        assert_eq!(sides, ["buy", "buy", "sell"]);
+
+        // The executor trades the swapped legs on the sides recorded for them
+        let backend = Arc::new(FakeExecutionBackend::new(0.0));
+        let engine = ExecutionEngine::with_fake_backend(Arc::clone(&cache), Arc::clone(&backend));
+        backend.fill(50_000.0).fill(0.05).fill(2_530.0);
+        assert!(engine.execute_opportunity(&opp, 100.0).await.unwrap().success);
+        let placed: Vec<(String, OrderSide)> = backend.placed().into_iter().map(|o| (o.pair, o.side)).collect();
+        assert_eq!(
+            placed,
+            [
+                ("BTC/USD".to_string(), OrderSide::Buy),
+                ("ETH/BTC".to_string(), OrderSide::Buy),
+                ("ETH/USD".to_string(), OrderSide::Sell),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inspect_path_walks_books() {
+        use crate::order_book::PairInfo;
+
+        // A triangle netting ~0.5%, with one BTC per BTC/USD level
+        let cache = Arc::new(OrderBookCache::new());
+        for (base, quote, mid, qty) in [("BTC", "USD", 50_000.0, 1.0), ("ETH", "BTC", 0.05, 100.0), ("ETH", "USD", 2_540.0, 100.0)] {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            let levels = |side: f64| (1..=3).map(|i| OrderBookLevel { price: mid * (1.0 + side * 0.001 * i as f64), qty }).collect();
+            cache.update_snapshot(&pair, levels(-1.0), levels(1.0), 1);
+        }
+        let config = EngineConfig {
+            min_profit_threshold: 0.001,
+            fee_rate: 0.0026,
+            fee_source: "test".to_string(),
+            leg_thresholds: std::collections::BTreeMap::new(),
+        };
+        let scanner = Scanner::new(Arc::clone(&cache), config);
+
+        let report = scanner.inspect_path("usd → btc → eth → usd", 100.0).unwrap();
+        assert_eq!(report.path, "USD → BTC → ETH → USD");
+        let actions: Vec<&str> = report.legs.iter().map(|l| l.action.as_str()).collect();
+        assert_eq!(actions, ["buy", "buy", "sell"]);
+        assert_eq!(report.legs[0].pair.as_deref(), Some("BTC/USD"));
+        assert!((report.legs[0].best_price.unwrap() - 50_050.0).abs() < 1e-6);
+        // A small amount fills at the top, so depth and top-of-book agree
+        assert!((report.depth_net_profit_pct.unwrap() - report.net_profit_pct.unwrap()).abs() < 1e-9);
+        assert!(report.legs.iter().all(|l| l.levels_used == 1 && l.excluded.is_none()));
+        assert!(report.blocked_by.is_empty(), "{:?}", report.blocked_by);
+
+        let opp = scanner.scan(&["USD".to_string()]).into_iter()
+            .find(|o| o.path == "USD → BTC → ETH → USD")
+            .unwrap();
+
+        // 1.5 BTC worth walks a second level, past a one-level requirement
+        let strict = scanner.with_liquidity(LiquidityRequirement { depth_levels: 1, max_slippage_bps: 50.0 }, 0.0, Arc::new(AtomicU64::new(0)));
+        let deep = strict.inspect_path("USD → BTC → ETH → USD", 75_000.0).unwrap();
+        assert_eq!(deep.legs[0].levels_used, 2);
+        assert!(deep.legs[0].slippage_bps.unwrap() > 0.0);
+        assert!(deep.depth_net_profit_pct.unwrap() < deep.net_profit_pct.unwrap());
+        assert!(deep.blocked_by[0].starts_with("leg 1: BTC/USD fills"));
+
+        // Sized up after the scan, the same opportunity no longer fits the book
+        assert!(strict.opportunity_fills(&opp, 100.0));
+        assert!(!strict.opportunity_fills(&opp, 75_000.0));
+
+        // More than the book holds
+        let too_big = strict.inspect_path("USD → BTC → ETH → USD", 1_000_000.0).unwrap();
+        assert!(too_big.amount_out.is_none());
+        assert!(too_big.blocked_by.iter().any(|b| b.contains("can't absorb")));
+
+        let unknown = strict.inspect_path("USD → DOGE → BTC → USD", 100.0).unwrap();
+        assert_eq!(unknown.legs[0].pair, None);
+        assert!(unknown.gross_profit_pct.is_none());
+        assert!(strict.inspect_path("USD → BTC → ETH", 100.0).is_err());
+    }
+
+    /// Four majors plus `alts` coins quoted against each of them
+    fn dense_market(alts: usize) -> Arc<OrderBookCache> {
+        use crate::order_book::PairInfo;
+
+        let usd_value = |c: &str| match c {
+            "USD" => 1.0,
+            "EUR" => 1.08,
+            "BTC" => 50_000.0,
+            "ETH" => 2_500.0,
+            alt => 1.0 + alt[3..].parse::<f64>().unwrap(),
+        };
+        let majors = ["USD", "EUR", "BTC", "ETH"];
+        let mut pairs: Vec<(String, String)> = vec![
+            ("EUR".into(), "USD".into()), ("BTC".into(), "USD".into()), ("ETH".into(), "USD".into()),
+            ("BTC".into(), "EUR".into()), ("ETH".into(), "EUR".into()), ("ETH".into(), "BTC".into()),
+        ];
+        for i in 0..alts {
+            for quote in majors {
+                pairs.push((format!("ALT{}", i), quote.to_string()));
+            }
+        }
+
+        let cache = Arc::new(OrderBookCache::new());
+        for (n, (base, quote)) in pairs.iter().enumerate() {
+            let pair = format!("{}/{}", base, quote);
+            // Small deterministic mispricings so some cycles pay
+            let mid = usd_value(base) / usd_value(quote) * (1.0 + 0.0005 * ((n * 7) % 11) as f64);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.clone(),
+                quote: quote.clone(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            let levels = |side: f64| (1..=3).map(|i| OrderBookLevel { price: mid * (1.0 + side * 0.00001 * i as f64), qty: 1_000.0 }).collect();
+            cache.update_snapshot(&pair, levels(-1.0), levels(1.0), 1);
+        }
+        cache
+    }
+
+    #[test]
+    fn test_currency_subgraph() {
+        let cache = dense_market(3);
+        let majors: HashSet<String> = ["USD", "EUR", "BTC"].iter().map(|c| c.to_string()).collect();
+        let usd = ["USD".to_string()];
+
+        let all = Scanner::new(Arc::clone(&cache), batch_config()).scan(&usd);
+        assert!(all.iter().any(|o| o.path.contains("ALT")));
+        let limited = Scanner::new(cache, batch_config()).with_currencies(majors.clone()).scan(&usd);
+        assert!(!limited.is_empty());
+        assert!(limited.iter().all(|o| o.path.split(" → ").all(|c| majors.contains(c))));
+    }
+
+    fn batch_config() -> EngineConfig {
+        EngineConfig {
+            min_profit_threshold: 0.0,
+            fee_rate: 0.0,
+            fee_source: "test".to_string(),
+            leg_thresholds: std::collections::BTreeMap::new(),
+        }
+    }
+
+    /// The pre-batch scan: one DFS per base over a shared graph
+    fn per_base_paths(scanner: &Scanner, bases: &[String]) -> HashMap<String, HashSet<String>> {
+        let (graph, node_map) = scanner.build_graph(&scanner.cache.get_all_prices());
+        bases
+            .iter()
+            .map(|base| {
+                let found = scanner.evaluate_cycles_from(&graph, &node_map, base, &mut CycleCounts::default(), None);
+                (base.clone(), found.into_iter().map(|o| o.path).collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_batch_matches_per_base_scan() {
+        let scanner = Scanner::new(dense_market(6), batch_config());
+        let bases: Vec<String> = ["USD", "EUR", "BTC", "XYZ"].iter().map(|b| b.to_string()).collect();
+
+        let batch = scanner.scan_batch(&bases);
+        let expected = per_base_paths(&scanner, &bases);
+        assert_eq!(batch.len(), 4);
+        assert!(batch["XYZ"].is_empty());
+        for base in &bases {
+            let paths: HashSet<String> = batch[base].iter().map(|o| o.path.clone()).collect();
+            assert_eq!(paths, expected[base], "{}", base);
+            assert!(batch[base].iter().all(|o| o.path.starts_with(base.as_str())));
+            assert!(batch[base].windows(2).all(|w| w[0].net_profit_pct >= w[1].net_profit_pct));
+        }
+        assert!(!batch["USD"].is_empty());
+
+        // A rotated cycle keeps its profit
+        let usd = batch["USD"].iter().find(|o| o.path.contains("EUR")).unwrap();
+        let legs: Vec<&str> = usd.path.split(" → ").collect();
+        let eur_start = legs.iter().position(|c| *c == "EUR").unwrap();
+        let rotated: Vec<&str> = legs[eur_start..legs.len() - 1].iter().chain(legs[..=eur_start].iter()).copied().collect();
+        let eur = batch["EUR"].iter().find(|o| o.path == rotated.join(" → ")).unwrap();
+        assert!((eur.net_profit_pct - usd.net_profit_pct).abs() < 1e-9);
+    }
+
+    /// cargo test --release bench_scan_batch -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_scan_batch() {
+        let scanner = Scanner::new(dense_market(40), batch_config());
+        for count in [1, 3, 4] {
+            let bases: Vec<String> = ["USD", "EUR", "BTC", "ETH"][..count].iter().map(|b| b.to_string()).collect();
+            let time = |f: &dyn Fn()| {
+                let start = Instant::now();
+                for _ in 0..5 {
+                    f();
+                }
+                start.elapsed().as_secs_f64() * 1000.0 / 5.0
+            };
+            let per_base = time(&|| { per_base_paths(&scanner, &bases); });
+            let batch = time(&|| { scanner.scan_batch(&bases); });
+            println!("{} bases: per-base {:.1}ms, batch {:.1}ms ({:.2}x)", count, per_base, batch, per_base / batch);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Stablecoin Conversion Cycles
+//!
+//! Cycles made only of stable currencies (fiat and fiat-pegged coins, e.g.
+//! USD → USDT → USDC → USD or EUR → EURT → USDT → EUR) behave differently
+//! from regular triangular cycles: spreads are a few basis points, Kraken
+//! charges its stablecoin/FX fee schedule on pairs between them, and the
+//! edge is small enough that the profit threshold has to be finer too.
+//!
+//! They are a separate strategy (`Strategy::Stablecoin`), off by default.
+//! When enabled the scanner looks for them only after a scan found no
+//! regular opportunity, so a steady stream of tiny stable cycles never
+//! crowds out triangular ones. When disabled, all-stable cycles are not
+//! returned at all. While enabled, the stablecoin fee applies to every
+//! stable-stable leg, including ones inside regular cycles (e.g. the USD/USDT
+//! leg of BTC → USD → USDT → BTC); while disabled every leg is priced at the
+//! regular fee, as before the strategy existed.
+#![allow(dead_code)]
+
+use serde::Serialize;
+
+/// Fiat currencies and coins pegged to them
+const STABLE_CURRENCIES: [&str; 20] = [
+    "USD", "EUR", "GBP", "CAD", "CHF", "AUD", "JPY",
+    "USDT", "USDC", "DAI", "PYUSD", "USDG", "RLUSD", "TUSD", "USDS", "USDQ",
+    "EURT", "EURC", "EURR", "EURQ",
+];
+
+/// Kraken's starting stablecoin/FX fee (decimal)
+const DEFAULT_FEE_RATE: f64 = 0.002;
+
+/// Default threshold for stable cycles (percent)
+const DEFAULT_MIN_PROFIT_PCT: f64 = 0.01;
+
+/// Whether `currency` is fiat or a fiat-pegged stablecoin
+pub fn is_stable(currency: &str) -> bool {
+    STABLE_CURRENCIES.contains(&currency)
+}
+
+/// Whether every currency of a cycle is stable
+pub fn is_stable_cycle<S: AsRef<str>>(currencies: &[S]) -> bool {
+    !currencies.is_empty() && currencies.iter().all(|c| is_stable(c.as_ref()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StablecoinPolicy {
+    /// Scan for (and execute) all-stable cycles
+    pub enabled: bool,
+    /// Fee per stable-stable leg (decimal, e.g. 0.002 = 0.2%)
+    pub fee_rate: f64,
+    /// Net profit a stable cycle needs (percent, e.g. 0.01 = 0.01%)
+    pub min_profit_pct: f64,
+}
+
+impl Default for StablecoinPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fee_rate: DEFAULT_FEE_RATE,
+            min_profit_pct: DEFAULT_MIN_PROFIT_PCT,
+        }
+    }
+}
+
+impl StablecoinPolicy {
+    /// Create from STABLECOIN_CYCLES (default off), STABLECOIN_FEE_RATE and
+    /// STABLECOIN_MIN_PROFIT_PCT
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<f64>().ok());
+        Self {
+            enabled: std::env::var("STABLECOIN_CYCLES")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.enabled),
+            fee_rate: number("STABLECOIN_FEE_RATE")
+                .filter(|v| (0.0..0.1).contains(v))
+                .unwrap_or(defaults.fee_rate),
+            min_profit_pct: number("STABLECOIN_MIN_PROFIT_PCT").unwrap_or(defaults.min_profit_pct),
+        }
+    }
+
+    /// Fee for a leg between two currencies (`regular` unless the policy is
+    /// enabled and both are stable)
+    pub fn leg_fee(&self, from: &str, to: &str, regular: f64) -> f64 {
+        if self.enabled && is_stable(from) && is_stable(to) { self.fee_rate } else { regular }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::{OrderBookCache, PairInfo};
+    use crate::scanner::Scanner;
+    use crate::types::{EngineConfig, OrderBookLevel, Strategy};
+    use std::sync::Arc;
+
+    fn cache_with(pairs: &[(&str, &str, f64)]) -> Arc<OrderBookCache> {
+        let cache = Arc::new(OrderBookCache::new());
+        for &(base, quote, mid) in pairs {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 1_000_000.0,
+            });
+            let levels = |side: f64| (1..=3).map(|i| OrderBookLevel { price: mid * (1.0 + side * 0.00001 * i as f64), qty: 1_000.0 }).collect();
+            cache.update_snapshot(&pair, levels(-1.0), levels(1.0), 1);
+        }
+        cache
+    }
+
+    #[test]
+    fn test_stable_cycles_are_a_separate_pass() {
+        // USD → USDC → USDT → USD gains ~0.3% gross; the BTC cycle ~0.5%
+        let cache = cache_with(&[
+            ("USDT", "USD", 1.0),
+            ("USDC", "USDT", 1.003),
+            ("USDC", "USD", 1.0),
+        ]);
+        let config = EngineConfig { min_profit_threshold: 0.0, fee_rate: 0.0026, fee_source: "test".to_string(), leg_thresholds: Default::default() };
+        let usd = ["USD".to_string()];
+
+        // Disabled: never returned, even though it clears the threshold with stable fees
+        let scanner = Scanner::new(Arc::clone(&cache), config.clone());
+        assert!(scanner.scan_first(&usd, -1.0).is_none());
+        assert!(scanner.scan(&usd).is_empty());
+
+        let policy = StablecoinPolicy { enabled: true, fee_rate: 0.0005, min_profit_pct: 0.05 };
+        let scanner = Scanner::new(Arc::clone(&cache), config.clone()).with_stablecoins(policy);
+        let opp = scanner.scan_first(&usd, 0.0).unwrap();
+        assert_eq!(opp.strategy, Strategy::Stablecoin);
+        assert!((opp.fees_pct - 0.15).abs() < 1e-9);
+        assert_eq!(opp.fee_rate, 0.0005);
+        assert!(opp.net_profit_pct > 0.05);
+
+        // Too thin for the stable threshold
+        let strict = StablecoinPolicy { min_profit_pct: 0.5, ..policy };
+        assert!(Scanner::new(Arc::clone(&cache), config.clone()).with_stablecoins(strict).scan_first(&usd, 0.0).is_none());
+
+        // A regular cycle is found first even though the stable one exists
+        let cache = cache_with(&[
+            ("USDT", "USD", 1.0),
+            ("USDC", "USDT", 1.003),
+            ("USDC", "USD", 1.0),
+            ("BTC", "USD", 50_000.0),
+            ("ETH", "BTC", 0.05),
+            ("ETH", "USD", 2_530.0),
+        ]);
+        let scanner = Scanner::new(Arc::clone(&cache), config).with_stablecoins(policy);
+        let opp = scanner.scan_first(&usd, 0.0).unwrap();
+        assert_eq!(opp.strategy, Strategy::Triangular);
+        let all = scanner.scan(&usd);
+        assert!(all.iter().any(|o| o.strategy == Strategy::Stablecoin));
+
+        assert!(is_stable_cycle(&["EUR", "EURT", "USDT", "EUR"]));
+        assert!(!is_stable_cycle(&["USD", "BTC", "USD"]));
+        assert_eq!(policy.leg_fee("USD", "USDT", 0.0026), 0.0005);
+        assert_eq!(policy.leg_fee("USD", "BTC", 0.0026), 0.0026);
+        // Off, stable legs of regular cycles keep the regular fee
+        assert_eq!(StablecoinPolicy::default().leg_fee("USD", "USDT", 0.0026), 0.0026);
+    }
+}
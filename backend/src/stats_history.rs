@@ -0,0 +1,207 @@
+//! Engine Stats History
+//!
+//! `get_stats()` and friends only report the current counters, so anything
+//! that happens between two dashboard polls is lost. The engine samples its
+//! engine / scanner / executor counters every STATS_SAMPLE_SECS into a ring
+//! buffer here (STATS_HISTORY_SIZE samples, oldest dropped first), and with
+//! STATS_HISTORY_DB=true also into `stats_history`, so charts survive a
+//! restart and can reach further back than the buffer.
+//!
+//! Samples are taken whether or not the engine is running: a stopped engine
+//! shows up as a flat line with `is_running = false` rather than as a gap.
+//! Counters are cumulative; rates are left to the consumer.
+
+use crate::db::{Database, StatsSample};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Samples buffered for the DB writer before new ones are dropped
+const CHANNEL_CAPACITY: usize = 1_000;
+
+/// Sampling interval and retention
+#[derive(Debug, Clone, Copy)]
+pub struct StatsHistoryPolicy {
+    /// Seconds between samples (0 = sampling off)
+    pub sample_secs: u64,
+    /// Samples kept in memory
+    pub capacity: usize,
+    /// Also write samples to the stats_history table
+    pub persist: bool,
+}
+
+impl Default for StatsHistoryPolicy {
+    fn default() -> Self {
+        // A day of 10s samples
+        Self { sample_secs: 10, capacity: 8_640, persist: false }
+    }
+}
+
+impl StatsHistoryPolicy {
+    /// Read STATS_SAMPLE_SECS, STATS_HISTORY_SIZE and STATS_HISTORY_DB
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            sample_secs: std::env::var("STATS_SAMPLE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.sample_secs),
+            capacity: std::env::var("STATS_HISTORY_SIZE")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(defaults.capacity),
+            persist: std::env::var("STATS_HISTORY_DB")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(defaults.persist),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsHistoryStats {
+    pub sample_secs: u64,
+    pub capacity: usize,
+    pub persisted: bool,
+    pub samples_buffered: usize,
+    pub samples_recorded: u64,
+    pub oldest_sample: Option<DateTime<Utc>>,
+    /// Samples not written to the DB because the writer fell behind
+    pub db_dropped: u64,
+}
+
+pub struct StatsHistory {
+    policy: StatsHistoryPolicy,
+    samples: RwLock<VecDeque<StatsSample>>,
+    db_tx: Option<mpsc::Sender<StatsSample>>,
+    recorded: AtomicU64,
+    db_dropped: AtomicU64,
+}
+
+impl StatsHistory {
+    /// Memory-only history, or memory + `stats_history` when given a database
+    /// (spawns the writer task)
+    pub fn new(policy: StatsHistoryPolicy, db: Option<Database>) -> Self {
+        let db_tx = db.map(|db| {
+            let (tx, mut rx) = mpsc::channel::<StatsSample>(CHANNEL_CAPACITY);
+            tokio::spawn(async move {
+                while let Some(sample) = rx.recv().await {
+                    if let Err(e) = db.insert_stats_sample(&sample).await {
+                        warn!("Failed to persist stats sample: {}", e);
+                    }
+                }
+            });
+            tx
+        });
+
+        Self {
+            policy,
+            samples: RwLock::new(VecDeque::with_capacity(policy.capacity.min(CHANNEL_CAPACITY))),
+            db_tx,
+            recorded: AtomicU64::new(0),
+            db_dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Create from the environment (persisting only with STATS_HISTORY_DB=true)
+    pub fn from_env(db: Database) -> Self {
+        let policy = StatsHistoryPolicy::from_env();
+        Self::new(policy, policy.persist.then_some(db))
+    }
+
+    pub fn policy(&self) -> StatsHistoryPolicy {
+        self.policy
+    }
+
+    pub fn is_persisted(&self) -> bool {
+        self.db_tx.is_some()
+    }
+
+    /// Append a sample, evicting the oldest when the buffer is full
+    pub fn record(&self, sample: StatsSample) {
+        {
+            let mut samples = self.samples.write();
+            if samples.len() == self.policy.capacity {
+                samples.pop_front();
+            }
+            samples.push_back(sample.clone());
+        }
+        self.recorded.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(tx) = &self.db_tx {
+            if tx.try_send(sample).is_err() {
+                let dropped = self.db_dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    warn!("Stats history backlog full - {} samples not persisted so far", dropped);
+                }
+            }
+        }
+    }
+
+    /// Buffered samples taken at or after `since`, oldest first
+    pub fn since(&self, since: DateTime<Utc>) -> Vec<StatsSample> {
+        let samples = self.samples.read();
+        let start = samples.partition_point(|s| s.sampled_at < since);
+        samples.range(start..).cloned().collect()
+    }
+
+    /// Whether the buffer reaches back to `since` (nothing older was evicted)
+    pub fn covers(&self, since: DateTime<Utc>) -> bool {
+        let samples = self.samples.read();
+        let evicted = self.recorded.load(Ordering::Relaxed) > samples.len() as u64;
+        !evicted || samples.front().is_some_and(|s| s.sampled_at <= since)
+    }
+
+    pub fn stats(&self) -> StatsHistoryStats {
+        let samples = self.samples.read();
+        StatsHistoryStats {
+            sample_secs: self.policy.sample_secs,
+            capacity: self.policy.capacity,
+            persisted: self.is_persisted(),
+            samples_buffered: samples.len(),
+            samples_recorded: self.recorded.load(Ordering::Relaxed),
+            oldest_sample: samples.front().map(|s| s.sampled_at),
+            db_dropped: self.db_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_ring_buffer_window_and_eviction() {
+        let policy = StatsHistoryPolicy { sample_secs: 10, capacity: 3, persist: false };
+        let history = StatsHistory::new(policy, None);
+        let t0 = Utc::now() - Duration::minutes(10);
+        let at = |minutes: i64| t0 + Duration::minutes(minutes);
+
+        for (minute, trades) in [(0, 1), (2, 2)] {
+            history.record(StatsSample { sampled_at: at(minute), trades_executed: trades, ..Default::default() });
+        }
+        // Nothing evicted yet: the buffer covers any window
+        assert!(history.covers(t0 - Duration::hours(1)));
+        assert_eq!(history.since(at(1)).len(), 1);
+
+        for minute in [4, 6] {
+            history.record(StatsSample { sampled_at: at(minute), trades_executed: 3, ..Default::default() });
+        }
+        let stats = history.stats();
+        assert_eq!(stats.samples_buffered, 3);
+        assert_eq!(stats.samples_recorded, 4);
+        assert_eq!(stats.oldest_sample, Some(at(2)));
+
+        // The minute-0 sample is gone, so windows reaching before minute 2 aren't covered
+        assert!(!history.covers(at(1)));
+        assert!(history.covers(at(3)));
+        let window: Vec<i64> = history.since(at(2)).iter().map(|s| s.trades_executed).collect();
+        assert_eq!(window, vec![2, 3, 3]);
+        assert!(history.since(at(7)).is_empty());
+    }
+}
@@ -0,0 +1,311 @@
+//! Strategy Plugins
+//!
+//! Custom detection and execution logic hooks into the HFT loop through
+//! `StrategyPlugin` instead of a fork of the scanner or the loop:
+//! - `on_orderbook_update` sees every book update and may return
+//!   opportunities of its own, which join the scanner's in the execution
+//!   queue (tagged `plugin:<name>`, strategy "plugin")
+//! - `on_opportunity` sees every opportunity found, whoever found it
+//! - `select_action` is asked before any opportunity is traded and may skip
+//!   it or scale its size down
+//!
+//! Every hook has a default, so a plugin implements only what it needs.
+//! Plugins are registered statically or at startup:
+//! - compiled-in plugins are listed in `BUILTIN_PLUGINS` by name, and
+//!   STRATEGY_PLUGINS picks which of them the engine loads
+//! - code embedding the engine calls `TradingEngine::register_strategy_plugin`
+//!   before starting it
+//!
+//! Hooks run on the hot path, so they must return quickly and never block.
+#![allow(dead_code)]
+
+use crate::order_book::OrderBookCache;
+use crate::types::{BookDelta, Opportunity, Strategy};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// What a plugin wants done with an opportunity about to be traded
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginAction {
+    Execute,
+    /// Don't trade it
+    Skip { reason: String },
+    /// Trade it at `factor` (0-1) of the size it would get
+    Resize { factor: f64 },
+}
+
+pub trait StrategyPlugin: Send + Sync {
+    /// Unique name, used in STRATEGY_PLUGINS and in tags
+    fn name(&self) -> &str;
+
+    /// A book changed. Returns any opportunities the plugin found.
+    fn on_orderbook_update(&self, _delta: &BookDelta, _cache: &OrderBookCache) -> Vec<Opportunity> {
+        Vec::new()
+    }
+
+    /// An opportunity was found, by the scanner or a plugin
+    fn on_opportunity(&self, _opp: &Opportunity) {}
+
+    /// Whether to trade `opp`, which passed every other check
+    fn select_action(&self, _opp: &Opportunity, _cache: &OrderBookCache) -> PluginAction {
+        PluginAction::Execute
+    }
+}
+
+/// Creates a compiled-in plugin
+pub type PluginFactory = fn() -> Arc<dyn StrategyPlugin>;
+
+/// Compiled-in plugins, loadable by name through STRATEGY_PLUGINS
+pub const BUILTIN_PLUGINS: &[(&str, PluginFactory)] = &[("max_spread", max_spread)];
+
+fn max_spread() -> Arc<dyn StrategyPlugin> {
+    Arc::new(MaxSpreadPlugin::from_env())
+}
+
+/// Outcome of asking every plugin about an opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginDecision {
+    /// Trade at `factor` of the size (1.0 unless a plugin scaled it)
+    Execute { factor: f64 },
+    Skip { plugin: String, reason: String },
+}
+
+/// Counters of one plugin, for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginStatus {
+    pub name: String,
+    /// Opportunities it found
+    pub found: u64,
+    /// Opportunities it vetoed
+    pub skipped: u64,
+    /// Opportunities it scaled down
+    pub resized: u64,
+}
+
+struct Registered {
+    plugin: Arc<dyn StrategyPlugin>,
+    found: AtomicU64,
+    skipped: AtomicU64,
+    resized: AtomicU64,
+}
+
+#[derive(Default)]
+pub struct StrategyPlugins {
+    plugins: RwLock<Vec<Registered>>,
+}
+
+impl StrategyPlugins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the compiled-in plugins named in STRATEGY_PLUGINS (comma-separated)
+    pub fn from_env() -> Self {
+        let plugins = Self::new();
+        let names = std::env::var("STRATEGY_PLUGINS").unwrap_or_default();
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match BUILTIN_PLUGINS.iter().find(|(builtin, _)| *builtin == name) {
+                Some((_, factory)) => plugins.register(factory()),
+                None => warn!("Ignoring unknown strategy plugin '{}' in STRATEGY_PLUGINS", name),
+            }
+        }
+        plugins
+    }
+
+    /// Add a plugin (one with the same name is replaced)
+    pub fn register(&self, plugin: Arc<dyn StrategyPlugin>) {
+        info!("Strategy plugin '{}' registered", plugin.name());
+        let mut plugins = self.plugins.write();
+        plugins.retain(|r| r.plugin.name() != plugin.name());
+        plugins.push(Registered {
+            plugin,
+            found: AtomicU64::new(0),
+            skipped: AtomicU64::new(0),
+            resized: AtomicU64::new(0),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.read().is_empty()
+    }
+
+    /// Let every plugin look at a book update; returns what they found,
+    /// tagged with the plugin that found it
+    pub fn on_orderbook_update(&self, delta: &BookDelta, cache: &OrderBookCache) -> Vec<Opportunity> {
+        let mut found = Vec::new();
+        for registered in self.plugins.read().iter() {
+            let name = registered.plugin.name();
+            for mut opp in registered.plugin.on_orderbook_update(delta, cache) {
+                opp.strategy = Strategy::Plugin;
+                opp.tags.push(format!("plugin:{}", name));
+                registered.found.fetch_add(1, Ordering::Relaxed);
+                found.push(opp);
+            }
+        }
+        found
+    }
+
+    pub fn on_opportunity(&self, opp: &Opportunity) {
+        for registered in self.plugins.read().iter() {
+            registered.plugin.on_opportunity(opp);
+        }
+    }
+
+    /// Ask every plugin in registration order: the first skip wins, resize
+    /// factors multiply
+    pub fn select_action(&self, opp: &Opportunity, cache: &OrderBookCache) -> PluginDecision {
+        let mut factor = 1.0;
+        for registered in self.plugins.read().iter() {
+            match registered.plugin.select_action(opp, cache) {
+                PluginAction::Execute => {}
+                PluginAction::Skip { reason } => {
+                    registered.skipped.fetch_add(1, Ordering::Relaxed);
+                    return PluginDecision::Skip { plugin: registered.plugin.name().to_string(), reason };
+                }
+                PluginAction::Resize { factor: f } => {
+                    registered.resized.fetch_add(1, Ordering::Relaxed);
+                    factor *= f.clamp(0.0, 1.0);
+                }
+            }
+        }
+        PluginDecision::Execute { factor }
+    }
+
+    pub fn status(&self) -> Vec<PluginStatus> {
+        self.plugins
+            .read()
+            .iter()
+            .map(|r| PluginStatus {
+                name: r.plugin.name().to_string(),
+                found: r.found.load(Ordering::Relaxed),
+                skipped: r.skipped.load(Ordering::Relaxed),
+                resized: r.resized.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+/// Skips opportunities with a leg whose book is wider than
+/// STRATEGY_MAX_SPREAD_BPS (default 50)
+pub struct MaxSpreadPlugin {
+    max_spread_bps: f64,
+}
+
+impl MaxSpreadPlugin {
+    pub fn new(max_spread_bps: f64) -> Self {
+        Self { max_spread_bps }
+    }
+
+    pub fn from_env() -> Self {
+        let bps = std::env::var("STRATEGY_MAX_SPREAD_BPS")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+            .unwrap_or(50.0);
+        Self::new(bps)
+    }
+}
+
+impl StrategyPlugin for MaxSpreadPlugin {
+    fn name(&self) -> &str {
+        "max_spread"
+    }
+
+    fn select_action(&self, opp: &Opportunity, cache: &OrderBookCache) -> PluginAction {
+        for leg in &opp.legs_detail {
+            let Some(edge) = cache.get_price(&leg.pair) else { continue };
+            let mid = (edge.bid + edge.ask) / 2.0;
+            if mid <= 0.0 {
+                continue;
+            }
+            let spread_bps = (edge.ask - edge.bid) / mid * 10_000.0;
+            if spread_bps > self.max_spread_bps {
+                return PluginAction::Skip {
+                    reason: format!("{} spread {:.1} bps above {:.1}", leg.pair, spread_bps, self.max_spread_bps),
+                };
+            }
+        }
+        PluginAction::Execute
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Finds a fixed opportunity on every update of one pair and halves sizes
+    struct Echo;
+
+    impl StrategyPlugin for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn on_orderbook_update(&self, delta: &BookDelta, _cache: &OrderBookCache) -> Vec<Opportunity> {
+            if delta.pair != "BTC/USD" {
+                return Vec::new();
+            }
+            vec![Opportunity {
+                id: "echo".to_string(),
+                path: "USD → BTC → ETH → USD".to_string(),
+                legs: 3,
+                gross_profit_pct: 1.0,
+                fees_pct: 0.78,
+                net_profit_pct: 0.22,
+                is_profitable: true,
+                detected_at: chrono::Utc::now(),
+                fee_rate: 0.0026,
+                fee_source: "test".to_string(),
+                legs_detail: vec![],
+                strategy: Strategy::Triangular,
+                tags: vec![],
+                inverse: None,
+            }]
+        }
+
+        fn select_action(&self, _opp: &Opportunity, _cache: &OrderBookCache) -> PluginAction {
+            PluginAction::Resize { factor: 0.5 }
+        }
+    }
+
+    struct Veto;
+
+    impl StrategyPlugin for Veto {
+        fn name(&self) -> &str {
+            "veto"
+        }
+
+        fn select_action(&self, opp: &Opportunity, _cache: &OrderBookCache) -> PluginAction {
+            if opp.net_profit_pct < 0.5 {
+                PluginAction::Skip { reason: "too thin".to_string() }
+            } else {
+                PluginAction::Execute
+            }
+        }
+    }
+
+    #[test]
+    fn test_plugins_find_and_decide() {
+        let cache = OrderBookCache::new();
+        let plugins = StrategyPlugins::new();
+        plugins.register(Arc::new(Echo));
+
+        assert!(plugins.on_orderbook_update(&BookDelta::snapshot("ETH/USD"), &cache).is_empty());
+        let found = plugins.on_orderbook_update(&BookDelta::snapshot("BTC/USD"), &cache);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].strategy, found[0].tags.clone()), (Strategy::Plugin, vec!["plugin:echo".to_string()]));
+        assert_eq!(plugins.select_action(&found[0], &cache), PluginDecision::Execute { factor: 0.5 });
+
+        plugins.register(Arc::new(Veto));
+        assert_eq!(
+            plugins.select_action(&found[0], &cache),
+            PluginDecision::Skip { plugin: "veto".to_string(), reason: "too thin".to_string() }
+        );
+        let status = plugins.status();
+        assert_eq!((status[0].found, status[0].resized), (1, 2));
+        assert_eq!(status[1].skipped, 1);
+    }
+}
@@ -0,0 +1,612 @@
+//! Task Supervision
+//!
+//! A panic inside a spawned task only ends that task: the engine keeps
+//! reporting running=true while, say, the HFT loop or the private socket
+//! reader is gone. Two pieces close that gap:
+//! - a process-wide panic hook records every panic (thread, location,
+//!   message) and logs it, while still chaining to the default hook
+//! - long-lived tasks run under a `TaskSupervisor`, which notices a panicked
+//!   task and restarts it after a backoff, up to SUPERVISOR_MAX_RESTARTS times
+//!
+//! - tasks that can also stop without panicking (a closed channel) or hang
+//!   beat a `Heartbeat`; the watchdog reports those whose beat is missing
+//!   or older than WATCHDOG_STALE_MS, and the engine rebuilds them
+//!
+//! Any panic since the engine last started marks it Degraded in its stats.
+//! `get_errors` lists the panics and every supervised task's health.
+//! `shutdown` ends every supervised task for good (the engine is closing).
+#![allow(dead_code)]
+
+use crate::runtimes::spawn_on;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+
+/// Panics kept for the API
+const MAX_PANICS: usize = 50;
+
+/// How often a watched task beats while it has nothing else to do
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One panic seen by the hook
+#[derive(Debug, Clone, Serialize)]
+pub struct PanicReport {
+    pub at: DateTime<Utc>,
+    pub thread: String,
+    pub location: Option<String>,
+    pub message: String,
+}
+
+static PANICS: OnceLock<Mutex<VecDeque<PanicReport>>> = OnceLock::new();
+static PANIC_COUNT: AtomicU64 = AtomicU64::new(0);
+
+fn panics() -> &'static Mutex<VecDeque<PanicReport>> {
+    PANICS.get_or_init(|| Mutex::new(VecDeque::with_capacity(MAX_PANICS)))
+}
+
+/// Record panics before the default hook prints them (call once, early in main)
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = PanicReport {
+            at: Utc::now(),
+            thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+            location: info.location().map(|l| format!("{}:{}", l.file(), l.line())),
+            message: payload_message(info.payload()),
+        };
+        error!(
+            "Panic on thread '{}' at {}: {}",
+            report.thread, report.location.as_deref().unwrap_or("unknown location"), report.message
+        );
+        PANIC_COUNT.fetch_add(1, Ordering::SeqCst);
+        let mut recent = panics().lock();
+        if recent.len() == MAX_PANICS {
+            recent.pop_front();
+        }
+        recent.push_back(report);
+        drop(recent);
+        previous(info);
+    }));
+}
+
+/// Panics recorded since the process started
+pub fn panic_count() -> u64 {
+    PANIC_COUNT.load(Ordering::SeqCst)
+}
+
+/// Recent panics, newest first
+pub fn recent_panics() -> Vec<PanicReport> {
+    panics().lock().iter().rev().cloned().collect()
+}
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// How panicked tasks are restarted
+#[derive(Debug, Clone, Serialize)]
+pub struct RestartPolicy {
+    /// Restarts per task before it's left down (0 = never restart)
+    pub max_restarts: u32,
+    /// Wait before each restart, doubled per restart of the same task
+    pub backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self { max_restarts: 5, backoff_ms: 1_000 }
+    }
+}
+
+impl RestartPolicy {
+    /// Create from SUPERVISOR_MAX_RESTARTS and SUPERVISOR_RESTART_BACKOFF_MS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        Self {
+            max_restarts: env("SUPERVISOR_MAX_RESTARTS").map_or(defaults.max_restarts, |v| v as u32),
+            backoff_ms: env("SUPERVISOR_RESTART_BACKOFF_MS").unwrap_or(defaults.backoff_ms),
+        }
+    }
+
+    fn delay(&self, restart: u32) -> Duration {
+        Duration::from_millis(self.backoff_ms.saturating_mul(1 << restart.min(6)))
+    }
+}
+
+/// When watched tasks count as dead
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchdogPolicy {
+    /// Beat age after which a running task counts as hung (0 = watchdog off)
+    pub stale_after_ms: u64,
+    /// How often heartbeats are checked
+    pub check_interval_ms: u64,
+    /// Rebuilds per task before it's left down
+    pub max_restarts: u32,
+}
+
+impl Default for WatchdogPolicy {
+    fn default() -> Self {
+        Self { stale_after_ms: 15_000, check_interval_ms: 5_000, max_restarts: 5 }
+    }
+}
+
+impl WatchdogPolicy {
+    /// Create from WATCHDOG_STALE_MS, WATCHDOG_CHECK_MS and WATCHDOG_MAX_RESTARTS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        Self {
+            stale_after_ms: env("WATCHDOG_STALE_MS").unwrap_or(defaults.stale_after_ms),
+            check_interval_ms: env("WATCHDOG_CHECK_MS").unwrap_or(defaults.check_interval_ms).max(100),
+            max_restarts: env("WATCHDOG_MAX_RESTARTS").map_or(defaults.max_restarts, |v| v as u32),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.stale_after_ms > 0
+    }
+}
+
+/// Liveness of one watched task: the time of its last beat
+#[derive(Debug, Clone, Default)]
+pub struct Heartbeat(Arc<AtomicI64>);
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        self.0.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn last(&self) -> Option<DateTime<Utc>> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            ms => DateTime::from_timestamp_millis(ms),
+        }
+    }
+}
+
+/// A watched task the watchdog found dead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadTask {
+    pub name: &'static str,
+    pub reason: String,
+    /// Rebuilds already used up: report it, don't rebuild it
+    pub exhausted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Panicked, waiting to be restarted
+    Restarting,
+    /// Returned normally
+    Finished,
+    /// Panicked with no restarts left
+    Failed,
+}
+
+/// One supervised task
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskHealth {
+    pub name: &'static str,
+    pub state: TaskState,
+    pub started_at: DateTime<Utc>,
+    pub restarts: u32,
+    pub panics: u32,
+    pub last_panic: Option<String>,
+    pub last_panic_at: Option<DateTime<Utc>>,
+    /// Last beat, for tasks the watchdog watches
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    /// Times the watchdog rebuilt it after it stopped or hung
+    pub watchdog_restarts: u32,
+    /// Bumped each time the name is supervised anew, so a replaced run
+    /// ending late doesn't overwrite its successor's state
+    #[serde(skip)]
+    run: u64,
+}
+
+/// Panics and task health for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineErrors {
+    pub degraded: bool,
+    /// Panics since the engine last started
+    pub panics_since_start: u64,
+    pub panics_total: u64,
+    pub recent_panics: Vec<PanicReport>,
+    pub tasks: Vec<TaskHealth>,
+    pub policy: RestartPolicy,
+    pub watchdog: WatchdogPolicy,
+    pub watchdog_restarts: u64,
+}
+
+pub struct TaskSupervisor {
+    policy: RestartPolicy,
+    watchdog: WatchdogPolicy,
+    tasks: Mutex<BTreeMap<&'static str, TaskHealth>>,
+    heartbeats: Mutex<BTreeMap<&'static str, Heartbeat>>,
+    watchdog_restarts: AtomicU64,
+    /// Panic count when the engine last started
+    baseline: AtomicU64,
+    /// Set once by `shutdown`; supervised loops exit when it flips
+    closed: tokio::sync::watch::Sender<bool>,
+}
+
+impl TaskSupervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            watchdog: WatchdogPolicy::default(),
+            tasks: Mutex::new(BTreeMap::new()),
+            heartbeats: Mutex::new(BTreeMap::new()),
+            watchdog_restarts: AtomicU64::new(0),
+            baseline: AtomicU64::new(panic_count()),
+            closed: tokio::sync::watch::channel(false).0,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(RestartPolicy::from_env()).with_watchdog(WatchdogPolicy::from_env())
+    }
+
+    pub fn with_watchdog(mut self, watchdog: WatchdogPolicy) -> Self {
+        self.watchdog = watchdog;
+        self
+    }
+
+    pub fn watchdog_policy(&self) -> &WatchdogPolicy {
+        &self.watchdog
+    }
+
+    /// The heartbeat of task `name`, which the watchdog watches from now on.
+    /// A rebuilt task asks again and carries on with the same one.
+    pub fn heartbeat(&self, name: &'static str) -> Heartbeat {
+        let heartbeat = self.heartbeats.lock().entry(name).or_default().clone();
+        heartbeat.beat();
+        heartbeat
+    }
+
+    /// Stop watching `name` (it was stopped on purpose)
+    pub fn unwatch(&self, name: &'static str) {
+        self.heartbeats.lock().remove(name);
+    }
+
+    pub fn is_watched(&self, name: &'static str) -> bool {
+        self.heartbeats.lock().contains_key(name)
+    }
+
+    /// Watched tasks that stopped (finished or failed) or whose last beat is
+    /// older than the stale limit. Only meaningful while they should be up.
+    pub fn dead_tasks(&self, now: DateTime<Utc>) -> Vec<DeadTask> {
+        if !self.watchdog.enabled() {
+            return Vec::new();
+        }
+        let stale_after = chrono::Duration::milliseconds(self.watchdog.stale_after_ms as i64);
+        let heartbeats = self.heartbeats.lock();
+        let tasks = self.tasks.lock();
+        heartbeats
+            .iter()
+            .filter_map(|(&name, heartbeat)| {
+                let task = tasks.get(name)?;
+                let reason = match task.state {
+                    TaskState::Finished => "stopped".to_string(),
+                    TaskState::Failed => "failed".to_string(),
+                    TaskState::Running => {
+                        let age = now - heartbeat.last()?;
+                        if age <= stale_after {
+                            return None;
+                        }
+                        format!("no heartbeat for {}ms", age.num_milliseconds())
+                    }
+                    // The supervisor is already bringing it back
+                    TaskState::Restarting => return None,
+                };
+                let exhausted = task.watchdog_restarts >= self.watchdog.max_restarts;
+                Some(DeadTask { name, reason, exhausted })
+            })
+            .collect()
+    }
+
+    /// Count a rebuild of `name` by the watchdog
+    pub fn record_watchdog_restart(&self, name: &'static str) {
+        self.watchdog_restarts.fetch_add(1, Ordering::Relaxed);
+        if let Some(task) = self.tasks.lock().get_mut(name) {
+            task.watchdog_restarts += 1;
+        }
+    }
+
+    /// Rebuilds by the watchdog since the process started
+    pub fn watchdog_restarts(&self) -> u64 {
+        self.watchdog_restarts.load(Ordering::Relaxed)
+    }
+
+    /// Spawn `task()` on `runtime` (or the caller's runtime) and keep it
+    /// alive: after a panic it's started again from a fresh `task()` call.
+    /// The future must pick up where the last one stopped, so shared state
+    /// lives behind Arcs captured by `task`.
+    pub fn supervise<F, Fut>(self: &Arc<Self>, runtime: Option<&Handle>, name: &'static str, task: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if self.is_shut_down() {
+            warn!("Not starting task '{}': supervisor is shut down", name);
+            return;
+        }
+        let run = self.set_state(name, None, TaskState::Running);
+        let supervisor = Arc::clone(self);
+        let handle = runtime.cloned();
+        let mut closed = self.closed.subscribe();
+        spawn_on(runtime, async move {
+            let mut restarts = 0;
+            loop {
+                let mut current = spawn_on(handle.as_ref(), task());
+                let result = tokio::select! {
+                    result = &mut current => result,
+                    _ = closed.wait_for(|closed| *closed) => {
+                        current.abort();
+                        supervisor.set_state(name, Some(run), TaskState::Finished);
+                        return;
+                    }
+                };
+                let message = match result {
+                    Ok(()) => {
+                        supervisor.set_state(name, Some(run), TaskState::Finished);
+                        return;
+                    }
+                    Err(e) if e.is_panic() => payload_message(e.into_panic().as_ref()),
+                    // Runtime shutting down
+                    Err(_) => return,
+                };
+
+                let exhausted = restarts >= supervisor.policy.max_restarts;
+                supervisor.record_panic(name, run, &message, exhausted);
+                if exhausted {
+                    error!("Task '{}' panicked ({}), giving up after {} restarts", name, message, restarts);
+                    return;
+                }
+                let delay = supervisor.policy.delay(restarts);
+                warn!("Task '{}' panicked ({}), restarting in {}ms", name, message, delay.as_millis());
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = closed.wait_for(|closed| *closed) => return,
+                }
+                restarts += 1;
+                supervisor.set_state(name, Some(run), TaskState::Running);
+                info!("Task '{}' restarted ({} of {})", name, restarts, supervisor.policy.max_restarts);
+            }
+        });
+    }
+
+    /// Record `state` for `run` of task `name` (None = a new run) and return
+    /// the run. States from a run that has been replaced are dropped.
+    fn set_state(&self, name: &'static str, run: Option<u64>, state: TaskState) -> u64 {
+        let mut tasks = self.tasks.lock();
+        let now = Utc::now();
+        let entry = tasks.entry(name).or_insert_with(|| TaskHealth {
+            name,
+            state,
+            started_at: now,
+            restarts: 0,
+            panics: 0,
+            last_panic: None,
+            last_panic_at: None,
+            last_heartbeat: None,
+            watchdog_restarts: 0,
+            run: 0,
+        });
+        match run {
+            None => {
+                entry.run += 1;
+                entry.started_at = now;
+            }
+            Some(run) if run != entry.run => return run,
+            Some(_) if state == TaskState::Running => {
+                entry.restarts += 1;
+                entry.started_at = now;
+            }
+            Some(_) => {}
+        }
+        entry.state = state;
+        entry.run
+    }
+
+    fn record_panic(&self, name: &'static str, run: u64, message: &str, exhausted: bool) {
+        self.set_state(name, Some(run), if exhausted { TaskState::Failed } else { TaskState::Restarting });
+        if let Some(task) = self.tasks.lock().get_mut(name).filter(|t| t.run == run) {
+            task.panics += 1;
+            task.last_panic = Some(message.to_string());
+            task.last_panic_at = Some(Utc::now());
+        }
+    }
+
+    /// Abort every supervised task and refuse new ones. Watched tasks are
+    /// unwatched first so the watchdog doesn't count them as dead.
+    pub fn shutdown(&self) {
+        if self.closed.send_replace(true) {
+            return;
+        }
+        self.heartbeats.lock().clear();
+        info!("Task supervisor shut down");
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    /// Forget panics so far (the engine is starting)
+    pub fn reset(&self) {
+        self.baseline.store(panic_count(), Ordering::SeqCst);
+    }
+
+    /// Panics since the last reset, seen by the hook or by a supervised task
+    pub fn panics_since_start(&self) -> u64 {
+        let hooked = panic_count().saturating_sub(self.baseline.load(Ordering::SeqCst));
+        let failed = self.tasks.lock().values().any(|t| t.state == TaskState::Failed);
+        hooked.max(u64::from(failed))
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.panics_since_start() > 0
+    }
+
+    pub fn tasks(&self) -> Vec<TaskHealth> {
+        let heartbeats = self.heartbeats.lock();
+        let mut tasks: Vec<TaskHealth> = self.tasks.lock().values().cloned().collect();
+        for task in &mut tasks {
+            task.last_heartbeat = heartbeats.get(task.name).and_then(Heartbeat::last);
+        }
+        tasks
+    }
+
+    pub fn errors(&self) -> EngineErrors {
+        EngineErrors {
+            degraded: self.is_degraded(),
+            panics_since_start: self.panics_since_start(),
+            panics_total: panic_count(),
+            recent_panics: recent_panics(),
+            tasks: self.tasks(),
+            policy: self.policy.clone(),
+            watchdog: self.watchdog.clone(),
+            watchdog_restarts: self.watchdog_restarts(),
+        }
+    }
+}
+
+/// Run `task` under `supervisor` when there is one, otherwise spawn it once
+pub fn spawn_supervised<F, Fut>(supervisor: Option<&Arc<TaskSupervisor>>, runtime: Option<&Handle>, name: &'static str, task: F)
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    match supervisor {
+        Some(supervisor) => supervisor.supervise(runtime, name, task),
+        None => {
+            spawn_on(runtime, task());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    // Paused clock: the backoff sleeps only elapse once every task is idle,
+    // however long the panics take to unwind
+    #[tokio::test(start_paused = true)]
+    async fn test_panicked_task_restarts() {
+        let supervisor = Arc::new(TaskSupervisor::new(RestartPolicy { max_restarts: 2, backoff_ms: 1 }));
+        let runs = Arc::new(AtomicU32::new(0));
+
+        // Panics twice, then finishes
+        let counter = Arc::clone(&runs);
+        supervisor.supervise(None, "flaky", move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("boom");
+                }
+            }
+        });
+        // Always panics
+        supervisor.supervise(None, "broken", || async { panic!("always") });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+        let tasks = supervisor.tasks();
+        let flaky = tasks.iter().find(|t| t.name == "flaky").unwrap();
+        assert_eq!(flaky.state, TaskState::Finished);
+        assert_eq!((flaky.restarts, flaky.panics), (2, 2));
+        assert_eq!(flaky.last_panic.as_deref(), Some("boom"));
+
+        let broken = tasks.iter().find(|t| t.name == "broken").unwrap();
+        assert_eq!(broken.state, TaskState::Failed);
+        assert_eq!(broken.panics, 3);
+        assert!(supervisor.is_degraded());
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_finds_stopped_and_hung_tasks() {
+        let policy = WatchdogPolicy { stale_after_ms: 1_000, check_interval_ms: 100, max_restarts: 1 };
+        let supervisor = Arc::new(TaskSupervisor::new(RestartPolicy::default()).with_watchdog(policy));
+
+        // Channel closed: returns without panicking
+        let closed = supervisor.heartbeat("forwarder");
+        supervisor.supervise(None, "forwarder", move || {
+            let closed = closed.clone();
+            async move { closed.beat() }
+        });
+        // Still running, beating
+        let alive = supervisor.heartbeat("loop");
+        supervisor.supervise(None, "loop", std::future::pending);
+        // Not watched: finishing is fine
+        supervisor.supervise(None, "oneshot", || async {});
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let now = Utc::now();
+        let dead = supervisor.dead_tasks(now);
+        assert_eq!(dead, vec![DeadTask { name: "forwarder", reason: "stopped".to_string(), exhausted: false }]);
+
+        // The loop stops beating
+        alive.beat();
+        let later = now + chrono::Duration::seconds(5);
+        let hung: Vec<&str> = supervisor.dead_tasks(later).iter().map(|d| d.name).collect();
+        assert_eq!(hung, vec!["forwarder", "loop"]);
+
+        supervisor.record_watchdog_restart("forwarder");
+        assert!(supervisor.dead_tasks(now)[0].exhausted);
+        assert_eq!(supervisor.watchdog_restarts(), 1);
+        let forwarder = supervisor.tasks().into_iter().find(|t| t.name == "forwarder").unwrap();
+        assert_eq!(forwarder.watchdog_restarts, 1);
+        assert!(forwarder.last_heartbeat.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_ends_tasks_for_good() {
+        let supervisor = Arc::new(TaskSupervisor::new(RestartPolicy { max_restarts: 5, backoff_ms: 1 }));
+        let dropped = Arc::new(AtomicU32::new(0));
+
+        struct Guard(Arc<AtomicU32>);
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        let counter = Arc::clone(&dropped);
+        let _ = supervisor.heartbeat("loop");
+        supervisor.supervise(None, "loop", move || {
+            let guard = Guard(Arc::clone(&counter));
+            async move {
+                let _guard = guard;
+                std::future::pending::<()>().await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        supervisor.shutdown();
+        supervisor.shutdown();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+        assert_eq!(supervisor.tasks()[0].state, TaskState::Finished);
+        assert!(!supervisor.is_watched("loop"));
+        assert!(supervisor.dead_tasks(Utc::now()).is_empty());
+
+        supervisor.supervise(None, "late", || async {});
+        assert_eq!(supervisor.tasks().len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+//! Performance Throttle
+//!
+//! Opportunities are executed on the profit the books promise; slippage is
+//! only seen afterwards. When the last PERF_THROTTLE_WINDOW completed trades
+//! realized on average more than PERF_THROTTLE_SHORTFALL_PCT percentage
+//! points less than expected, the market has likely moved into a regime the
+//! scanner doesn't price, and the throttle engages for
+//! PERF_THROTTLE_COOLDOWN_SECS. PERF_THROTTLE selects what it does then:
+//!
+//! - raise (default): the effective profit threshold goes up by the measured
+//!   shortfall, so only opportunities that still clear it after the same
+//!   slippage execute
+//! - pause: nothing executes until the cooldown ends
+//! - off: never engages
+//!
+//! Each engagement starts a fresh window, so the throttle stays on only while
+//! new trades keep falling short. Its state is served with the circuit
+//! breaker and the live status.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// What an engaged throttle does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThrottleMode {
+    Raise,
+    Pause,
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ThrottlePolicy {
+    pub mode: ThrottleMode,
+    /// Completed trades compared
+    pub window: usize,
+    /// Mean shortfall (percentage points) that engages the throttle
+    pub max_shortfall_pct: f64,
+    pub cooldown_secs: u64,
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            mode: ThrottleMode::Raise,
+            window: 10,
+            max_shortfall_pct: 0.10,
+            cooldown_secs: 900,
+        }
+    }
+}
+
+impl ThrottlePolicy {
+    /// Read PERF_THROTTLE (raise / pause / off) and the PERF_THROTTLE_* settings
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        fn env<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+        Self {
+            mode: match std::env::var("PERF_THROTTLE").map(|v| v.to_lowercase()).as_deref() {
+                Ok("pause") => ThrottleMode::Pause,
+                Ok("off") | Ok("false") | Ok("0") => ThrottleMode::Off,
+                _ => ThrottleMode::Raise,
+            },
+            window: env("PERF_THROTTLE_WINDOW").filter(|w| *w > 0).unwrap_or(defaults.window),
+            max_shortfall_pct: env::<f64>("PERF_THROTTLE_SHORTFALL_PCT")
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(defaults.max_shortfall_pct),
+            cooldown_secs: env("PERF_THROTTLE_COOLDOWN_SECS").unwrap_or(defaults.cooldown_secs),
+        }
+    }
+}
+
+/// An opportunity kept from executing by the engaged throttle
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThrottleBlock {
+    pub mode: ThrottleMode,
+    pub net_profit_pct: f64,
+    /// Threshold including the raise (None when paused)
+    pub required_pct: Option<f64>,
+    pub shortfall_pct: f64,
+    pub remaining_secs: u64,
+}
+
+/// Snapshot for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ThrottleStatus {
+    pub policy: ThrottlePolicy,
+    pub engaged: bool,
+    /// Mean shortfall that engaged it
+    pub shortfall_pct: Option<f64>,
+    pub engaged_at: Option<DateTime<Utc>>,
+    pub remaining_secs: u64,
+    /// Trades in the current window and their mean shortfall
+    pub samples: usize,
+    pub window_shortfall_pct: Option<f64>,
+    pub times_engaged: u64,
+    /// Opportunities not executed while engaged
+    pub blocked_opportunities: u64,
+}
+
+struct Engagement {
+    shortfall_pct: f64,
+    engaged_at: DateTime<Utc>,
+    until: Instant,
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    /// Expected minus realized profit (percentage points), newest last
+    shortfalls: VecDeque<f64>,
+    engaged: Option<Engagement>,
+}
+
+impl ThrottleState {
+    fn mean_shortfall(&self) -> Option<f64> {
+        (!self.shortfalls.is_empty()).then(|| self.shortfalls.iter().sum::<f64>() / self.shortfalls.len() as f64)
+    }
+
+    /// The current engagement, dropped once its cooldown has passed
+    fn active(&mut self, now: Instant) -> Option<&Engagement> {
+        if self.engaged.as_ref().is_some_and(|e| now >= e.until) {
+            self.engaged = None;
+        }
+        self.engaged.as_ref()
+    }
+}
+
+pub struct PerformanceThrottle {
+    policy: ThrottlePolicy,
+    state: Mutex<ThrottleState>,
+    times_engaged: AtomicU64,
+    blocked: AtomicU64,
+}
+
+impl PerformanceThrottle {
+    pub fn new(policy: ThrottlePolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(ThrottleState::default()),
+            times_engaged: AtomicU64::new(0),
+            blocked: AtomicU64::new(0),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(ThrottlePolicy::from_env())
+    }
+
+    /// Record a completed trade. Returns the mean shortfall when this trade
+    /// filled a window that engages the throttle.
+    pub fn record(&self, expected_pct: f64, realized_pct: f64, now: Instant) -> Option<f64> {
+        if self.policy.mode == ThrottleMode::Off {
+            return None;
+        }
+        let mut state = self.state.lock();
+        state.shortfalls.push_back(expected_pct - realized_pct);
+        while state.shortfalls.len() > self.policy.window {
+            state.shortfalls.pop_front();
+        }
+        if state.shortfalls.len() < self.policy.window {
+            return None;
+        }
+        let shortfall = state.mean_shortfall()?;
+        if shortfall <= self.policy.max_shortfall_pct {
+            return None;
+        }
+        state.shortfalls.clear();
+        state.engaged = Some(Engagement {
+            shortfall_pct: shortfall,
+            engaged_at: Utc::now(),
+            until: now + Duration::from_secs(self.policy.cooldown_secs),
+        });
+        self.times_engaged.fetch_add(1, Ordering::Relaxed);
+        Some(shortfall)
+    }
+
+    /// Whether an opportunity at `net_profit_pct` may execute, given the
+    /// threshold it already cleared
+    pub fn check(&self, net_profit_pct: f64, threshold_pct: f64, now: Instant) -> Result<(), ThrottleBlock> {
+        let mut state = self.state.lock();
+        let Some(engagement) = state.active(now) else { return Ok(()) };
+        let required_pct = match self.policy.mode {
+            ThrottleMode::Pause => None,
+            _ => Some(threshold_pct + engagement.shortfall_pct),
+        };
+        if required_pct.is_some_and(|required| net_profit_pct > required) {
+            return Ok(());
+        }
+        self.blocked.fetch_add(1, Ordering::Relaxed);
+        Err(ThrottleBlock {
+            mode: self.policy.mode,
+            net_profit_pct,
+            required_pct,
+            shortfall_pct: engagement.shortfall_pct,
+            remaining_secs: engagement.until.saturating_duration_since(now).as_secs(),
+        })
+    }
+
+    pub fn status(&self, now: Instant) -> ThrottleStatus {
+        let mut state = self.state.lock();
+        let (shortfall_pct, engaged_at, remaining_secs) = match state.active(now) {
+            Some(e) => (Some(e.shortfall_pct), Some(e.engaged_at), e.until.saturating_duration_since(now).as_secs()),
+            None => (None, None, 0),
+        };
+        ThrottleStatus {
+            policy: self.policy,
+            engaged: shortfall_pct.is_some(),
+            shortfall_pct,
+            engaged_at,
+            remaining_secs,
+            samples: state.shortfalls.len(),
+            window_shortfall_pct: state.mean_shortfall(),
+            times_engaged: self.times_engaged.load(Ordering::Relaxed),
+            blocked_opportunities: self.blocked.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for PerformanceThrottle {
+    /// Never engages
+    fn default() -> Self {
+        Self::new(ThrottlePolicy { mode: ThrottleMode::Off, ..Default::default() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortfall_window_raises_threshold_then_expires() {
+        let policy = ThrottlePolicy { window: 3, max_shortfall_pct: 0.10, cooldown_secs: 60, ..Default::default() };
+        let throttle = PerformanceThrottle::new(policy);
+        let now = Instant::now();
+
+        // Expected 0.30%, realized 0.28%: within tolerance
+        for _ in 0..3 {
+            assert_eq!(throttle.record(0.30, 0.28, now), None);
+        }
+        assert!(throttle.check(0.12, 0.10, now).is_ok());
+
+        // Slippage regime: trades realizing 0.20 points less than expected
+        assert_eq!(throttle.record(0.30, 0.10, now), None);
+        let shortfall = throttle.record(0.30, 0.10, now).unwrap();
+        assert!((shortfall - 0.14).abs() < 1e-9, "mean of 0.02, 0.20, 0.20");
+
+        // Threshold 0.10% + 0.14 points
+        let block = throttle.check(0.20, 0.10, now).unwrap_err();
+        assert!((block.required_pct.unwrap() - 0.24).abs() < 1e-9);
+        assert_eq!(block.remaining_secs, 60);
+        assert!(throttle.check(0.30, 0.10, now).is_ok());
+
+        let status = throttle.status(now);
+        assert!(status.engaged);
+        assert_eq!(status.samples, 0, "engaging starts a fresh window");
+        assert_eq!(status.blocked_opportunities, 1);
+
+        let later = now + Duration::from_secs(61);
+        assert!(throttle.check(0.20, 0.10, later).is_ok());
+        assert!(!throttle.status(later).engaged);
+        assert_eq!(throttle.status(later).times_engaged, 1);
+
+        // Pause blocks everything while engaged; off never engages
+        let pause = PerformanceThrottle::new(ThrottlePolicy { mode: ThrottleMode::Pause, window: 1, ..policy });
+        pause.record(0.50, 0.0, now);
+        assert_eq!(pause.check(5.0, 0.10, now).unwrap_err().required_pct, None);
+        let off = PerformanceThrottle::default();
+        assert_eq!(off.record(0.50, 0.0, now), None);
+    }
+}
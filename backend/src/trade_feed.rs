@@ -0,0 +1,196 @@
+//! Trade Prints Feed
+//!
+//! With KRAKEN_WS_TRADES on, the public socket also subscribes to the trade
+//! channel for every monitored pair and the last TRADE_FEED_PRINTS prints
+//! of each pair are kept here. GET /api/trades-feed/:pair serves them, for
+//! context on where the spread actually traded.
+//!
+//! The prints also give a realized volatility per pair: the root of the
+//! summed squared log returns between consecutive prints over the last
+//! TRADE_FEED_VOL_WINDOW_SECS, in basis points. The executor uses it in two
+//! places, both off by default:
+//! - a cycle through a pair above TRADE_FEED_MAX_VOL_BPS is refused
+//! - a leg on a pair above TRADE_FEED_CAP_ABOVE_BPS goes out as an IOC limit
+//!   at most the realized volatility past the best price instead of at market
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Prints needed for a volatility estimate (two returns)
+const MIN_PRINTS_FOR_VOL: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeFeedPolicy {
+    /// Prints kept per pair
+    pub prints_per_pair: usize,
+    /// How far back realized volatility looks
+    pub vol_window_secs: u64,
+    /// Refuse cycles through a pair this volatile (0 = off)
+    pub max_vol_bps: f64,
+    /// Send legs on a pair this volatile as capped IOC limits (0 = off)
+    pub cap_above_bps: f64,
+}
+
+impl Default for TradeFeedPolicy {
+    fn default() -> Self {
+        Self { prints_per_pair: 100, vol_window_secs: 300, max_vol_bps: 0.0, cap_above_bps: 0.0 }
+    }
+}
+
+impl TradeFeedPolicy {
+    /// Create from TRADE_FEED_PRINTS, TRADE_FEED_VOL_WINDOW_SECS,
+    /// TRADE_FEED_MAX_VOL_BPS and TRADE_FEED_CAP_ABOVE_BPS
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        let bps = |key: &str, default: f64| env::<f64>(key).filter(|v| v.is_finite() && *v >= 0.0).unwrap_or(default);
+        Self {
+            prints_per_pair: env::<usize>("TRADE_FEED_PRINTS").filter(|v| *v > 0).unwrap_or(defaults.prints_per_pair),
+            vol_window_secs: env::<u64>("TRADE_FEED_VOL_WINDOW_SECS").filter(|v| *v > 0).unwrap_or(defaults.vol_window_secs),
+            max_vol_bps: bps("TRADE_FEED_MAX_VOL_BPS", defaults.max_vol_bps),
+            cap_above_bps: bps("TRADE_FEED_CAP_ABOVE_BPS", defaults.cap_above_bps),
+        }
+    }
+}
+
+/// One trade on the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradePrint {
+    pub price: f64,
+    pub qty: f64,
+    /// Taker side
+    pub side: String,
+    pub at: DateTime<Utc>,
+    #[serde(default)]
+    pub trade_id: Option<u64>,
+}
+
+/// Realized volatility of one pair
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RealizedVol {
+    pub vol_bps: f64,
+    /// Prints in the window it was computed from
+    pub prints: usize,
+    pub window_secs: u64,
+}
+
+/// Recent prints of a pair and its volatility, for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct PairTradeFeed {
+    pub pair: String,
+    /// Newest first
+    pub prints: Vec<TradePrint>,
+    pub realized_vol: Option<RealizedVol>,
+}
+
+pub struct TradeFeed {
+    policy: TradeFeedPolicy,
+    /// Oldest first
+    prints: DashMap<String, VecDeque<TradePrint>>,
+}
+
+impl TradeFeed {
+    pub fn new(policy: TradeFeedPolicy) -> Self {
+        Self { policy, prints: DashMap::new() }
+    }
+
+    pub fn policy(&self) -> &TradeFeedPolicy {
+        &self.policy
+    }
+
+    /// Add prints of `pair` in the order they traded
+    pub fn record(&self, pair: &str, prints: impl IntoIterator<Item = TradePrint>) {
+        let mut kept = self.prints.entry(pair.to_string()).or_default();
+        for print in prints {
+            if kept.len() == self.policy.prints_per_pair {
+                kept.pop_front();
+            }
+            kept.push_back(print);
+        }
+    }
+
+    /// Up to `limit` prints of `pair`, newest first
+    pub fn recent(&self, pair: &str, limit: usize) -> Vec<TradePrint> {
+        self.prints
+            .get(pair)
+            .map(|p| p.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Realized volatility of `pair` over the window ending at `now` (None
+    /// with fewer than three prints in it)
+    pub fn realized_vol(&self, pair: &str, now: DateTime<Utc>) -> Option<RealizedVol> {
+        let prints = self.prints.get(pair)?;
+        let since = now - chrono::Duration::seconds(self.policy.vol_window_secs as i64);
+        let prices: Vec<f64> = prints.iter().filter(|p| p.at >= since && p.price > 0.0).map(|p| p.price).collect();
+        if prices.len() < MIN_PRINTS_FOR_VOL {
+            return None;
+        }
+        let variance: f64 = prices.windows(2).map(|w| (w[1] / w[0]).ln().powi(2)).sum();
+        Some(RealizedVol {
+            vol_bps: variance.sqrt() * 10_000.0,
+            prints: prices.len(),
+            window_secs: self.policy.vol_window_secs,
+        })
+    }
+
+    pub fn pair_feed(&self, pair: &str, limit: usize) -> PairTradeFeed {
+        PairTradeFeed {
+            pair: pair.to_string(),
+            prints: self.recent(pair, limit),
+            realized_vol: self.realized_vol(pair, Utc::now()),
+        }
+    }
+
+    /// Forget a pair (unsubscribed)
+    pub fn release(&self, pair: &str) {
+        self.prints.remove(pair);
+    }
+
+    pub fn clear(&self) {
+        self.prints.clear();
+    }
+}
+
+impl Default for TradeFeed {
+    fn default() -> Self {
+        Self::new(TradeFeedPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn print(price: f64, secs_ago: i64, now: DateTime<Utc>) -> TradePrint {
+        TradePrint { price, qty: 0.1, side: "buy".to_string(), at: now - chrono::Duration::seconds(secs_ago), trade_id: None }
+    }
+
+    #[test]
+    fn test_recent_prints_and_realized_vol() {
+        let feed = TradeFeed::new(TradeFeedPolicy { prints_per_pair: 4, vol_window_secs: 60, ..TradeFeedPolicy::default() });
+        let now = Utc::now();
+
+        // The oldest print falls out of the buffer, the next one out of the window
+        let prices = [(90.0, 100), (100.0, 90), (101.0, 30), (100.0, 20), (102.0, 10)];
+        feed.record("BTC/USD", prices.iter().map(|&(price, ago)| print(price, ago, now)));
+        let recent: Vec<f64> = feed.recent("BTC/USD", 10).iter().map(|p| p.price).collect();
+        assert_eq!(recent, vec![102.0, 100.0, 101.0, 100.0]);
+        assert_eq!(feed.recent("BTC/USD", 2).len(), 2);
+
+        let vol = feed.realized_vol("BTC/USD", now).unwrap();
+        // 101 -> 100 -> 102: the print 90s ago is kept but outside the 60s window
+        let expected = ((100.0f64 / 101.0).ln().powi(2) + (102.0f64 / 100.0).ln().powi(2)).sqrt() * 10_000.0;
+        assert!((vol.vol_bps - expected).abs() < 1e-9);
+        assert_eq!(vol.prints, 3);
+
+        // Too few prints in the window for an estimate
+        assert!(feed.realized_vol("BTC/USD", now + chrono::Duration::seconds(45)).is_none());
+        assert!(feed.realized_vol("ETH/USD", now).is_none());
+    }
+}
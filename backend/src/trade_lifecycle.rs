@@ -0,0 +1,288 @@
+//! Trade Lifecycle
+//!
+//! Auto-execution and POST /api/live/execute used to record their trades
+//! separately: the HFT loop's cold path started cooldowns, counted stats
+//! and loss and saved the trade, while a manual trade was only saved, so it
+//! never reached the loss limits, cooldowns or notional limits. Every
+//! executed trade is now recorded here, whoever executed it:
+//! - cooldowns start (failed legs' pairs cool down on their own)
+//! - trade counters and P&L are counted in the loop's stats
+//! - the USD its filled legs spent counts towards the notional limits
+//! - the trade is saved and a completed one updates the trading state
+//! - the loss limits are checked against the updated totals
+//!
+//! Recordings are serialized, so concurrent trades can't interleave their
+//! updates or both slip under a loss limit the pair of them crosses. A
+//! trade id already recorded is ignored, so a trade is never counted twice.
+#![allow(dead_code)]
+
+use crate::db::{Database, NewLiveTrade};
+use crate::executor::LegResult;
+use crate::hft_loop::{CooldownTracker, HftConfig, HftStats};
+use crate::notional::NotionalTracker;
+use crate::valuation::{ReportedPnl, Valuator};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{Mutex, RwLock};
+use tracing::warn;
+
+/// Trade ids remembered to ignore repeated recordings
+const RECENT_IDS: usize = 1_024;
+
+/// An executed trade, ready to be recorded
+#[derive(Debug, Clone)]
+pub struct TradeRecord {
+    /// Row saved to live_trades; its trade_id identifies the trade
+    pub trade: NewLiveTrade,
+    /// P&L of a completed trade (None = failed or partial)
+    pub pnl: Option<ReportedPnl>,
+    pub is_partial: bool,
+    /// Pairs of the legs that failed
+    pub failed_pairs: Vec<String>,
+    /// USD spent by the legs that filled
+    pub notional_usd: f64,
+}
+
+/// What recording a trade changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordOutcome {
+    /// The trade had been recorded already; nothing changed
+    pub duplicate: bool,
+    /// Loss limit the updated totals exceed, if any
+    pub loss_limit_breach: Option<String>,
+}
+
+/// USD spent by `legs` that filled, each valued in its spent currency
+/// (`fallback_usd` per leg when that currency has no price)
+pub fn filled_notional(valuator: &Valuator, legs: &[LegResult], fallback_usd: f64) -> f64 {
+    legs.iter()
+        .filter(|l| l.success)
+        .map(|leg| {
+            let (base, quote) = leg.pair.split_once('/').unwrap_or((&leg.pair, ""));
+            let spent = if leg.side == "buy" { quote } else { base };
+            valuator.value(spent, leg.input_amount).usd_value.unwrap_or(fallback_usd)
+        })
+        .sum()
+}
+
+/// Records executed trades against the HFT loop's guard state
+pub struct TradeLifecycle {
+    stats: Arc<RwLock<HftStats>>,
+    config: Arc<RwLock<HftConfig>>,
+    cooldowns: Arc<RwLock<CooldownTracker>>,
+    notional: Arc<NotionalTracker>,
+    db: Database,
+    /// Held for a whole recording; trade ids recorded most recently
+    recorded: Mutex<VecDeque<String>>,
+}
+
+impl TradeLifecycle {
+    pub fn new(
+        stats: Arc<RwLock<HftStats>>,
+        config: Arc<RwLock<HftConfig>>,
+        cooldowns: Arc<RwLock<CooldownTracker>>,
+        notional: Arc<NotionalTracker>,
+        db: Database,
+    ) -> Self {
+        Self {
+            stats,
+            config,
+            cooldowns,
+            notional,
+            db,
+            recorded: Mutex::new(VecDeque::with_capacity(RECENT_IDS)),
+        }
+    }
+
+    /// Record an executed trade, start to finish, before any other
+    pub async fn record(&self, record: &TradeRecord) -> RecordOutcome {
+        let mut recorded = self.recorded.lock().await;
+        if recorded.contains(&record.trade.trade_id) {
+            warn!("Trade {} already recorded, ignoring", record.trade.trade_id);
+            return RecordOutcome { duplicate: true, loss_limit_breach: None };
+        }
+        if recorded.len() >= RECENT_IDS {
+            recorded.pop_front();
+        }
+        recorded.push_back(record.trade.trade_id.clone());
+
+        let config = self.config.read().await.clone();
+        let now = Instant::now();
+        let failed_pairs: Vec<&str> = record.failed_pairs.iter().map(String::as_str).collect();
+        self.cooldowns.write().await.record_trade(&config.cooldowns, &record.trade.path, &failed_pairs, now);
+        self.notional.record(record.notional_usd, now);
+
+        let (daily_loss, total_loss) = {
+            let mut stats = self.stats.write().await;
+            stats.trades_executed += 1;
+            match &record.pnl {
+                Some(pnl) => {
+                    stats.trades_successful += 1;
+                    let amount = pnl.guard_amount();
+                    if amount >= 0.0 {
+                        stats.total_profit += amount;
+                        stats.daily_profit += amount;
+                    } else {
+                        stats.total_loss += amount.abs();
+                        stats.daily_loss += amount.abs();
+                    }
+                }
+                None => {
+                    stats.trades_failed += 1;
+                    if record.is_partial {
+                        stats.trades_partial += 1;
+                    }
+                }
+            }
+            (stats.daily_loss, stats.total_loss)
+        };
+
+        if let Err(e) = self.db.save_trade(&record.trade).await {
+            warn!("Failed to save trade {} to DB: {}", record.trade.trade_id, e);
+        }
+
+        let Some(pnl) = &record.pnl else {
+            return RecordOutcome { duplicate: false, loss_limit_breach: None };
+        };
+        if pnl.reporting_amount.is_none() {
+            warn!(
+                "No {} rate for {}: P&L of {:.6} counted unconverted",
+                pnl.reporting_currency, pnl.currency, pnl.amount
+            );
+        }
+        let is_win = pnl.amount > 0.0;
+        if let Err(e) = self.db.record_trade_result(pnl.guard_amount(), record.trade.amount_in, is_win).await {
+            warn!("Failed to update trading state: {}", e);
+        }
+
+        let loss_limit_breach = if daily_loss > config.max_daily_loss {
+            Some(format!(
+                "Daily loss limit exceeded: {:.2} > {:.2} {}",
+                daily_loss, config.max_daily_loss, config.reporting_currency
+            ))
+        } else if total_loss > config.max_total_loss {
+            Some(format!(
+                "Total loss limit exceeded: {:.2} > {:.2} {}",
+                total_loss, config.max_total_loss, config.reporting_currency
+            ))
+        } else {
+            None
+        };
+        RecordOutcome { duplicate: false, loss_limit_breach }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hft_loop::CooldownConfig;
+    use crate::notional::{NotionalLimits, NotionalWindow};
+    use crate::stablecoin::StablecoinPolicy;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn trade(trade_id: &str, path: &str, pnl: Option<f64>) -> TradeRecord {
+        TradeRecord {
+            trade: NewLiveTrade {
+                trade_id: trade_id.to_string(),
+                path: path.to_string(),
+                legs: 3,
+                amount_in: 10.0,
+                amount_out: pnl.map(|p| 10.0 + p),
+                profit_loss: pnl,
+                profit_loss_pct: pnl.map(|p| p * 10.0),
+                pnl_currency: None,
+                pnl_reporting: None,
+                reporting_currency: None,
+                fx_rate: None,
+                status: if pnl.is_some() { "COMPLETED".to_string() } else { "PARTIAL".to_string() },
+                current_leg: None,
+                error_message: None,
+                held_currency: None,
+                held_amount: None,
+                held_value_usd: None,
+                order_ids: None,
+                leg_fills: None,
+                started_at: None,
+                completed_at: None,
+                total_execution_ms: None,
+                opportunity_profit_pct: None,
+                strategy: None,
+                tags: vec![],
+            },
+            pnl: pnl.map(|amount| ReportedPnl {
+                amount,
+                currency: "USD".to_string(),
+                reporting_amount: Some(amount),
+                reporting_currency: "USD".to_string(),
+                fx_rate: Some(1.0),
+            }),
+            is_partial: pnl.is_none(),
+            failed_pairs: if pnl.is_none() { vec!["ETH/BTC".to_string()] } else { vec![] },
+            notional_usd: 30.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_recordings_counted_once() {
+        let config = HftConfig {
+            min_profit_threshold: 0.0,
+            trade_amount: 10.0,
+            max_daily_loss: 2.5,
+            max_total_loss: 500.0,
+            reporting_currency: "USD".to_string(),
+            base_currencies: vec!["USD".to_string()],
+            sizing_tiers: Vec::new(),
+            max_safe_amount: None,
+            cooldowns: CooldownConfig { global_ms: 2_000, path_ms: 10_000, pair_failure_ms: 30_000 },
+            leg_liquidity: None,
+            reserves: HashMap::new(),
+            stablecoins: StablecoinPolicy::default(),
+            notional_limits: NotionalLimits::default(),
+            min_pair_quality: 0.0,
+        };
+        let stats = Arc::new(RwLock::new(HftStats::default()));
+        let cooldowns = Arc::new(RwLock::new(CooldownTracker::default()));
+        let notional = Arc::new(NotionalTracker::new());
+        let db = Database::connect_lazy("postgres://test@127.0.0.1:1/none", Duration::from_millis(20)).unwrap();
+        let lifecycle = Arc::new(TradeLifecycle::new(
+            Arc::clone(&stats),
+            Arc::new(RwLock::new(config)),
+            Arc::clone(&cooldowns),
+            Arc::clone(&notional),
+            db,
+        ));
+
+        // Six losing trades of 1.0 and one partial, each submitted twice at once
+        let mut records: Vec<TradeRecord> = (0..6)
+            .map(|i| trade(&format!("loss-{}", i), &format!("USD → C{} → BTC → USD", i), Some(-1.0)))
+            .collect();
+        records.push(trade("partial", "USD → ETH → BTC → USD", None));
+        let handles: Vec<_> = records
+            .iter()
+            .chain(records.iter())
+            .cloned()
+            .map(|record| {
+                let lifecycle = Arc::clone(&lifecycle);
+                tokio::spawn(async move { lifecycle.record(&record).await })
+            })
+            .collect();
+        let mut outcomes = Vec::new();
+        for handle in handles {
+            outcomes.push(handle.await.unwrap());
+        }
+
+        assert_eq!(outcomes.iter().filter(|o| o.duplicate).count(), 7);
+        // Loss totals 3.0, 4.0, ... 6.0 cross the 2.5 limit: four breaches
+        assert_eq!(outcomes.iter().filter(|o| o.loss_limit_breach.is_some()).count(), 4);
+
+        let stats = stats.read().await.clone();
+        assert_eq!((stats.trades_executed, stats.trades_successful, stats.trades_failed, stats.trades_partial), (7, 6, 1, 1));
+        assert_eq!(stats.daily_loss, 6.0);
+        assert_eq!(notional.used(NotionalWindow::Hour, Instant::now()), 210.0);
+
+        let active = cooldowns.write().await.active(Instant::now());
+        assert_eq!(active.len(), 9); // global, 7 paths and ETH/BTC
+    }
+}
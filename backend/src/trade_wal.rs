@@ -0,0 +1,604 @@
+//! Trade Write-Ahead Log
+//!
+//! If the process dies between two legs, the trade result never reaches the
+//! database and nothing remembers what the engine was holding. With
+//! TRADE_WAL=true the executor appends a line to a local file before each
+//! leg is submitted (intent) and after it settles (fill or failure), and a
+//! closing record when the trade returns.
+//!
+//! On startup any trade without a closing record is replayed from the file.
+//! Its unconfirmed leg is looked up in Kraken's trade history by pair, side
+//! and time; the trade is then saved as COMPLETED (every leg filled), FAILED
+//! (nothing left the start currency) or PARTIAL with the held currency and
+//! amount, which the existing partial-trade endpoints resolve. The trade is
+//! closed in the log once it's recorded.
+//!
+//! Records are JSON lines written by the log's own thread and flushed with
+//! fsync (unless TRADE_WAL_FSYNC=false); the executor waits for that before
+//! the order goes out. The file is truncated once no trade is open
+//! and it has grown past `COMPACT_BYTES`.
+
+use crate::reconcile::ExchangeTrade;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+/// Truncate the log past this size when no trade is open
+const COMPACT_BYTES: u64 = 1 << 20;
+
+/// Exchange trades this long before an intent still count as its fill
+/// (clock skew between us and Kraken)
+const MATCH_SKEW_SECS: i64 = 5;
+
+/// One line of the log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum WalRecord {
+    Begin {
+        trade_id: String,
+        path: String,
+        start_amount: f64,
+        at: DateTime<Utc>,
+    },
+    /// Written before the order is submitted
+    Intent {
+        trade_id: String,
+        leg: usize,
+        pair: String,
+        side: String,
+        from_currency: String,
+        to_currency: String,
+        amount: f64,
+        at: DateTime<Utc>,
+    },
+    /// Written after the order filled (output is net of fees)
+    Fill {
+        trade_id: String,
+        leg: usize,
+        order_id: String,
+        output_amount: f64,
+        avg_price: f64,
+        at: DateTime<Utc>,
+    },
+    /// Written after the order was rejected or timed out
+    LegFailed {
+        trade_id: String,
+        leg: usize,
+        error: String,
+        at: DateTime<Utc>,
+    },
+    /// The trade returned (or was recovered) and needs nothing more
+    End {
+        trade_id: String,
+        success: bool,
+        at: DateTime<Utc>,
+    },
+}
+
+impl WalRecord {
+    pub fn trade_id(&self) -> &str {
+        match self {
+            WalRecord::Begin { trade_id, .. }
+            | WalRecord::Intent { trade_id, .. }
+            | WalRecord::Fill { trade_id, .. }
+            | WalRecord::LegFailed { trade_id, .. }
+            | WalRecord::End { trade_id, .. } => trade_id,
+        }
+    }
+}
+
+/// A leg the log saw fill
+#[derive(Debug, Clone, Serialize)]
+pub struct FilledLeg {
+    pub leg: usize,
+    pub pair: String,
+    pub side: String,
+    pub order_id: String,
+    pub to_currency: String,
+    pub output_amount: f64,
+    pub avg_price: f64,
+}
+
+/// A leg submitted (or about to be) with no recorded outcome
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingLeg {
+    pub leg: usize,
+    pub pair: String,
+    pub side: String,
+    pub from_currency: String,
+    pub to_currency: String,
+    pub amount: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// A trade with no closing record
+#[derive(Debug, Clone, Serialize)]
+pub struct IncompleteTrade {
+    pub trade_id: String,
+    pub path: String,
+    pub start_amount: f64,
+    pub started_at: DateTime<Utc>,
+    pub filled: Vec<FilledLeg>,
+    pub pending: Option<PendingLeg>,
+}
+
+impl IncompleteTrade {
+    pub fn total_legs(&self) -> usize {
+        self.path.matches(" → ").count()
+    }
+
+    pub fn start_currency(&self) -> &str {
+        self.path.split(" → ").next().unwrap_or_default()
+    }
+
+    /// Currency and amount held after the last confirmed leg
+    pub fn held(&self) -> (String, f64) {
+        match self.filled.last() {
+            Some(leg) => (leg.to_currency.clone(), leg.output_amount),
+            None => (self.start_currency().to_string(), self.start_amount),
+        }
+    }
+}
+
+/// How a recovered trade is recorded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Recovery {
+    /// COMPLETED, PARTIAL or FAILED
+    pub status: &'static str,
+    pub legs_completed: usize,
+    pub held_currency: String,
+    pub held_amount: f64,
+    pub order_ids: Vec<String>,
+    /// The pending leg's outcome couldn't be checked against the exchange
+    pub unverified: bool,
+    pub note: String,
+}
+
+/// Whether a Kraken REST pair name ("XETHZUSD", "SOLUSD") is our "ETH/USD"
+fn same_pair(kraken: &str, pair: &str) -> bool {
+    let wanted = pair.replace('/', "").replace("BTC", "XBT").to_uppercase();
+    let kraken = kraken.to_uppercase();
+    if kraken == wanted {
+        return true;
+    }
+    // Legacy names prefix each 3-letter asset with X (crypto) or Z (fiat)
+    let bytes = kraken.as_bytes();
+    kraken.len() == 8
+        && matches!(bytes[0], b'X' | b'Z')
+        && matches!(bytes[4], b'X' | b'Z')
+        && format!("{}{}", &kraken[1..4], &kraken[5..]) == wanted
+}
+
+/// Decide what a trade left behind. `exchange` is Kraken's trade history
+/// since the trade began (None when it couldn't be fetched).
+pub fn settle(trade: &IncompleteTrade, exchange: Option<&[ExchangeTrade]>) -> Recovery {
+    let (mut held_currency, mut held_amount) = trade.held();
+    let mut legs_completed = trade.filled.len();
+    let mut order_ids: Vec<String> = trade.filled.iter().map(|l| l.order_id.clone()).collect();
+    let mut unverified = false;
+    let mut note = format!("Recovered after restart: {} of {} legs confirmed", legs_completed, trade.total_legs());
+
+    if let Some(pending) = &trade.pending {
+        let since = pending.at - chrono::Duration::seconds(MATCH_SKEW_SECS);
+        match exchange {
+            None => {
+                unverified = true;
+                note.push_str(&format!(
+                    "; leg {} ({} {}) outcome unknown, exchange not queried",
+                    pending.leg + 1, pending.side, pending.pair
+                ));
+            }
+            Some(trades) => {
+                let fills: Vec<&ExchangeTrade> = trades.iter()
+                    .filter(|t| same_pair(&t.pair, &pending.pair) && t.side.eq_ignore_ascii_case(&pending.side))
+                    .filter(|t| t.time.is_some_and(|time| time >= since))
+                    .collect();
+                if fills.is_empty() {
+                    note.push_str(&format!("; leg {} ({} {}) not filled on exchange", pending.leg + 1, pending.side, pending.pair));
+                } else {
+                    // Buys receive base, sells receive quote (before fees,
+                    // which TradesHistory reports separately)
+                    let received: f64 = fills.iter()
+                        .map(|t| if pending.side.eq_ignore_ascii_case("buy") { t.volume } else { t.volume * t.price })
+                        .sum();
+                    held_currency = pending.to_currency.clone();
+                    held_amount = received;
+                    legs_completed += 1;
+                    for fill in &fills {
+                        if !order_ids.contains(&fill.order_id) {
+                            order_ids.push(fill.order_id.clone());
+                        }
+                    }
+                    note.push_str(&format!(
+                        "; leg {} ({} {}) filled on exchange, {:.8} {} before fees",
+                        pending.leg + 1, pending.side, pending.pair, received, pending.to_currency
+                    ));
+                }
+            }
+        }
+    }
+
+    let status = if legs_completed == trade.total_legs() {
+        "COMPLETED"
+    } else if legs_completed == 0 && !unverified {
+        "FAILED"
+    } else {
+        "PARTIAL"
+    };
+
+    Recovery { status, legs_completed, held_currency, held_amount, order_ids, unverified, note }
+}
+
+/// Rebuild the trades without a closing record, oldest first
+pub fn replay(records: impl IntoIterator<Item = WalRecord>) -> Vec<IncompleteTrade> {
+    let mut trades: HashMap<String, IncompleteTrade> = HashMap::new();
+    // Intents by (trade, leg) until their outcome is seen
+    let mut intents: HashMap<(String, usize), PendingLeg> = HashMap::new();
+
+    for record in records {
+        match record {
+            WalRecord::Begin { trade_id, path, start_amount, at } => {
+                trades.insert(trade_id.clone(), IncompleteTrade {
+                    trade_id,
+                    path,
+                    start_amount,
+                    started_at: at,
+                    filled: Vec::new(),
+                    pending: None,
+                });
+            }
+            WalRecord::Intent { trade_id, leg, pair, side, from_currency, to_currency, amount, at } => {
+                let pending = PendingLeg { leg, pair, side, from_currency, to_currency, amount, at };
+                if let Some(trade) = trades.get_mut(&trade_id) {
+                    trade.pending = Some(pending.clone());
+                }
+                intents.insert((trade_id, leg), pending);
+            }
+            WalRecord::Fill { trade_id, leg, order_id, output_amount, avg_price, .. } => {
+                let intent = intents.remove(&(trade_id.clone(), leg));
+                if let (Some(trade), Some(intent)) = (trades.get_mut(&trade_id), intent) {
+                    trade.pending = None;
+                    trade.filled.push(FilledLeg {
+                        leg,
+                        pair: intent.pair,
+                        side: intent.side,
+                        order_id,
+                        to_currency: intent.to_currency,
+                        output_amount,
+                        avg_price,
+                    });
+                }
+            }
+            WalRecord::LegFailed { trade_id, leg, .. } => {
+                intents.remove(&(trade_id.clone(), leg));
+                if let Some(trade) = trades.get_mut(&trade_id) {
+                    trade.pending = None;
+                }
+            }
+            WalRecord::End { trade_id, .. } => {
+                trades.remove(&trade_id);
+                intents.retain(|(id, _), _| *id != trade_id);
+            }
+        }
+    }
+
+    let mut trades: Vec<IncompleteTrade> = trades.into_values().collect();
+    trades.sort_by_key(|t| t.started_at);
+    trades
+}
+
+/// WAL settings and counters for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeWalStatus {
+    pub enabled: bool,
+    pub path: String,
+    pub fsync: bool,
+    pub open_trades: usize,
+    pub records_written: u64,
+    pub write_errors: u64,
+}
+
+pub struct TradeWal {
+    /// Queue of the writer thread (None = disabled)
+    writer: Option<mpsc::Sender<WalWrite>>,
+    path: PathBuf,
+    fsync: bool,
+    open: Arc<Mutex<HashSet<String>>>,
+    records_written: Arc<AtomicU64>,
+    write_errors: Arc<AtomicU64>,
+}
+
+/// A record for the writer thread and where to say it's on disk
+struct WalWrite {
+    record: WalRecord,
+    done: oneshot::Sender<()>,
+}
+
+/// Owns the file on the log's own thread, so the write (and fsync) never
+/// blocks an async worker. Records are written in the order they were queued.
+struct WalWriter {
+    file: File,
+    fsync: bool,
+    open: Arc<Mutex<HashSet<String>>>,
+    records_written: Arc<AtomicU64>,
+    write_errors: Arc<AtomicU64>,
+}
+
+impl WalWriter {
+    /// Write until every sender is gone (the log was dropped)
+    fn run(mut self, queue: mpsc::Receiver<WalWrite>) {
+        for WalWrite { record, done } in queue {
+            self.write(&record);
+            let _ = done.send(());
+        }
+    }
+
+    fn write(&mut self, record: &WalRecord) {
+        let line = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to encode trade WAL record: {}", e);
+                self.write_errors.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+
+        let mut open = self.open.lock();
+        match record {
+            WalRecord::Begin { trade_id, .. } => {
+                open.insert(trade_id.clone());
+            }
+            WalRecord::End { trade_id, .. } => {
+                open.remove(trade_id);
+            }
+            _ => {}
+        }
+
+        let written = writeln!(self.file, "{}", line)
+            .and_then(|_| if self.fsync { self.file.sync_data() } else { Ok(()) });
+        if let Err(e) = written {
+            warn!("Failed to write trade WAL record for {}: {}", record.trade_id(), e);
+            self.write_errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.records_written.fetch_add(1, Ordering::Relaxed);
+
+        // Nothing open means nothing left to replay
+        if open.is_empty() && self.file.metadata().map(|m| m.len() > COMPACT_BYTES).unwrap_or(false) {
+            if let Err(e) = self.file.set_len(0) {
+                warn!("Failed to truncate trade WAL: {}", e);
+            }
+        }
+    }
+}
+
+impl TradeWal {
+    /// A log that records nothing
+    pub fn disabled() -> Self {
+        Self {
+            writer: None,
+            path: PathBuf::new(),
+            fsync: false,
+            open: Arc::new(Mutex::new(HashSet::new())),
+            records_written: Arc::new(AtomicU64::new(0)),
+            write_errors: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Append to (creating if needed) the log at `path`
+    pub fn open(path: impl AsRef<Path>, fsync: bool) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let wal = Self { path, fsync, ..Self::disabled() };
+        let writer = WalWriter {
+            file,
+            fsync,
+            open: Arc::clone(&wal.open),
+            records_written: Arc::clone(&wal.records_written),
+            write_errors: Arc::clone(&wal.write_errors),
+        };
+        let (queue, received) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("trade-wal".to_string())
+            .spawn(move || writer.run(received))?;
+        Ok(Self { writer: Some(queue), ..wal })
+    }
+
+    /// TRADE_WAL (default false), TRADE_WAL_PATH (default trade_wal.jsonl),
+    /// TRADE_WAL_FSYNC (default true)
+    pub fn from_env() -> Self {
+        let flag = |name: &str, default: bool| {
+            std::env::var(name)
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(default)
+        };
+        if !flag("TRADE_WAL", false) {
+            return Self::disabled();
+        }
+        let path = std::env::var("TRADE_WAL_PATH").unwrap_or_else(|_| "trade_wal.jsonl".to_string());
+        match Self::open(&path, flag("TRADE_WAL_FSYNC", true)) {
+            Ok(wal) => {
+                info!("Trade WAL enabled at {}", path);
+                wal
+            }
+            Err(e) => {
+                warn!("Failed to open trade WAL at {}: {} - in-flight trades won't survive a crash", path, e);
+                Self::disabled()
+            }
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Append one record, returning once the writer thread has written it
+    /// (and synced it, with fsync on). Write errors are logged and counted,
+    /// never returned: a trade isn't refused because the disk is full.
+    pub async fn append(&self, record: WalRecord) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        let (done, written) = oneshot::channel();
+        if writer.send(WalWrite { record, done }).is_err() {
+            warn!("Trade WAL writer has stopped - record dropped");
+            self.write_errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let _ = written.await;
+    }
+
+    /// Trades the log has no closing record for (empty when disabled)
+    pub fn incomplete(&self) -> Vec<IncompleteTrade> {
+        if self.writer.is_none() {
+            return Vec::new();
+        }
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!("Failed to read trade WAL: {}", e);
+                return Vec::new();
+            }
+        };
+        let records = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.trim().is_empty())
+            // A torn last line (crash mid-write) is skipped
+            .filter_map(|line| serde_json::from_str::<WalRecord>(&line).ok());
+        replay(records)
+    }
+
+    /// Close a trade the engine has recorded after recovery
+    pub async fn close(&self, trade_id: &str, success: bool) {
+        self.append(WalRecord::End { trade_id: trade_id.to_string(), success, at: Utc::now() }).await;
+    }
+
+    pub fn status(&self) -> TradeWalStatus {
+        TradeWalStatus {
+            enabled: self.is_enabled(),
+            path: self.path.display().to_string(),
+            fsync: self.fsync,
+            open_trades: self.open.lock().len(),
+            records_written: self.records_written.load(Ordering::Relaxed),
+            write_errors: self.write_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn begin(id: &str) -> WalRecord {
+        WalRecord::Begin { trade_id: id.into(), path: "USD → ETH → BTC → USD".into(), start_amount: 100.0, at: Utc::now() }
+    }
+
+    fn intent(id: &str, leg: usize, pair: &str, side: &str, from: &str, to: &str, amount: f64) -> WalRecord {
+        WalRecord::Intent {
+            trade_id: id.into(),
+            leg,
+            pair: pair.into(),
+            side: side.into(),
+            from_currency: from.into(),
+            to_currency: to.into(),
+            amount,
+            at: Utc::now(),
+        }
+    }
+
+    fn fill(id: &str, leg: usize, output: f64) -> WalRecord {
+        WalRecord::Fill { trade_id: id.into(), leg, order_id: format!("O{}", leg), output_amount: output, avg_price: 1.0, at: Utc::now() }
+    }
+
+    fn exchange_trade(pair: &str, side: &str, volume: f64, price: f64) -> ExchangeTrade {
+        ExchangeTrade {
+            trade_id: "T1".into(),
+            order_id: "OX".into(),
+            pair: pair.into(),
+            side: side.into(),
+            volume,
+            price,
+            time: Some(Utc::now()),
+        }
+    }
+
+    #[test]
+    fn test_replay_keeps_only_unfinished_trades() {
+        let records = vec![
+            begin("done"),
+            intent("done", 0, "ETH/USD", "buy", "USD", "ETH", 100.0),
+            fill("done", 0, 0.05),
+            WalRecord::End { trade_id: "done".into(), success: true, at: Utc::now() },
+            begin("open"),
+            intent("open", 0, "ETH/USD", "buy", "USD", "ETH", 100.0),
+            fill("open", 0, 0.05),
+            intent("open", 1, "ETH/BTC", "sell", "ETH", "BTC", 0.05),
+        ];
+        let trades = replay(records);
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.trade_id, "open");
+        assert_eq!(trade.held(), ("ETH".to_string(), 0.05));
+        assert_eq!(trade.pending.as_ref().map(|p| p.leg), Some(1));
+    }
+
+    #[test]
+    fn test_settle_against_exchange_history() {
+        let mut trade = replay(vec![
+            begin("t"),
+            intent("t", 0, "ETH/USD", "buy", "USD", "ETH", 100.0),
+            fill("t", 0, 0.05),
+            intent("t", 1, "ETH/BTC", "sell", "ETH", "BTC", 0.05),
+        ]).remove(0);
+
+        // Leg 2 reached the exchange: we hold BTC, one leg short of done
+        let filled = settle(&trade, Some(&[exchange_trade("XETHXXBT", "sell", 0.05, 0.06)]));
+        assert_eq!(filled.status, "PARTIAL");
+        assert_eq!(filled.legs_completed, 2);
+        assert_eq!(filled.held_currency, "BTC");
+        assert!((filled.held_amount - 0.003).abs() < 1e-12);
+
+        // It didn't: still holding ETH from leg 1
+        let missed = settle(&trade, Some(&[exchange_trade("XETHZUSD", "sell", 0.05, 2000.0)]));
+        assert_eq!((missed.status, missed.held_currency.as_str()), ("PARTIAL", "ETH"));
+
+        // Couldn't ask: keep what the log confirms, flagged
+        assert!(settle(&trade, None).unverified);
+
+        // Nothing filled and verified: a plain failure
+        trade.filled.clear();
+        trade.pending = None;
+        assert_eq!(settle(&trade, Some(&[])).status, "FAILED");
+    }
+
+    #[tokio::test]
+    async fn test_log_survives_reopen() {
+        let path = std::env::temp_dir().join(format!("trade_wal_{}.jsonl", uuid::Uuid::new_v4()));
+        {
+            let wal = TradeWal::open(&path, true).unwrap();
+            wal.append(begin("a")).await;
+            wal.append(intent("a", 0, "ETH/USD", "buy", "USD", "ETH", 100.0)).await;
+            wal.append(begin("b")).await;
+            wal.close("b", true).await;
+            assert_eq!((wal.status().records_written, wal.status().open_trades), (4, 1));
+        }
+        let wal = TradeWal::open(&path, false).unwrap();
+        let trades = wal.incomplete();
+        assert_eq!(trades.iter().map(|t| t.trade_id.as_str()).collect::<Vec<_>>(), vec!["a"]);
+        wal.close("a", false).await;
+        assert!(wal.incomplete().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
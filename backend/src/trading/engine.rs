@@ -3,27 +3,89 @@
 //! Unified scan + execute in single sequential path.
 //! Uses HftLoop for core trading logic.
 
-use crate::auth::KrakenAuth;
-use crate::config_manager::ConfigManager;
-use crate::db::{Database, LiveTradingConfig};
-use crate::executor::ExecutionEngine;
+use crate::analytics::{self, WhatIfCooldown, WhatIfReport};
+use crate::audit::{AuditActor, AuditCategory, AuditLog};
+use crate::auth::{KrakenAuth, TokenStats};
+use crate::breaker_sync::{self, BreakerDivergence, BreakerHeal, BreakerSync, BreakerSyncStatus};
+use crate::anomaly::{AnomalyDetector, AnomalyStatus};
+use crate::event_drops::{EventChannelReport, EventDropMonitor};
+use crate::chaos::{ChaosMonkey, ChaosStats};
+use crate::config_manager::{parse_leg_thresholds, ConfigManager};
+use crate::config_schema::{ConfigChange, ConfigDocument, ConfigError, ConfigPatch, FieldError, StartupConfigPolicy};
+use crate::consistency::{ConsistencyReport, PriceConsistencyMonitor};
+use crate::dead_man::{DeadManStatus, DeadManSwitch, WATCH_INTERVAL_MS};
+use crate::depth_tiers::{DepthTierStatus, DepthTiers};
+use crate::db::{Database, FeeConfiguration, LiveTradingConfig, LiveTradingState, NewLiveTrade, OrderFill, StatsSample};
+use crate::exec_queue::{ExecQueuePolicy, ExecQueueStatus, ExecutionQueue};
+use crate::executor::{parse_disabled_pairs, AbortRequest, BuySizing, ExecutionEngine, ExecutionError, ExecutionStats, FreshnessPolicy, FundsResizePolicy, InFlightStatus, MakerPolicy, MarketTifPolicy, PrefundPolicy, PriceCapPolicy, RetryPolicy, SignalGatePolicy};
+use crate::fill_journal::{FillJournal, FillJournalStats, FillOrderSummary};
+use crate::guards::GuardVerdict;
+use crate::strategy_plugin::{PluginStatus, StrategyPlugin, StrategyPlugins};
 
 // Re-export for API compatibility
 pub use crate::executor::TradeResult;
-use crate::hft_loop::{HftLoop, HftConfig, HftState, HftStats};
-use crate::kraken_pairs::{KrakenPairSelector, PairSelectionConfig};
-use crate::order_book::OrderBookCache;
-use crate::types::{EngineStats, Opportunity, OrderBookHealth};
-use crate::ws_v2::KrakenWebSocketV2;
+use crate::hft_loop::{parse_reserves, ActiveCooldown, CooldownConfig, CooldownScope, HftLoop, HftConfig, HftSnapshot, HftState, HftStats, SizingTier, WarmupProgress};
+use crate::index_price::{IndexPriceMonitor, IndexPriceSource, IndexReport};
+use crate::kraken_pairs::{normalize_currency, KrakenPairSelector, PairSelectionConfig, SelectedPair};
+use crate::loadgen::{LoadGenConfig, LoadGenReport, LoadGenerator};
+use crate::notifications::{Notification, Notifications, Severity};
+use crate::notional::{NotionalHeadroom, NotionalLimits};
+use crate::opportunity_cache::{OpportunityCache, OpportunityWithAge};
+use crate::opportunity_filter::{FilterConfig, FilterStatus};
+use crate::pair_quality::{self, PairQuality};
+use crate::pair_ranking::{path_participation, rank_pairs, PairRanking, RankingPolicy};
+use crate::pair_refresh::{PairSetDiff, PairSetRefresh};
+use crate::pair_registry::{PairMeta, PairRoute};
+use crate::order_book::{AllocationStats, BookSignals, CacheBudget, LevelPoolPolicy, OrderBookCache, RestrictedGraph};
+use crate::reconcile::{
+    compare, is_terminal, parse_open_orders, parse_trades, ExchangeState, InternalOrder, InternalState,
+    Reconciler, ReconciliationReport, ReconciliationStatus, GRACE_MS,
+};
+use crate::reconnect::{ReconnectPolicy, ReconnectStats, ReconnectTracker};
+use crate::rest_bootstrap::{BootstrapStatus, RestBootstrap};
+use crate::restrictions::RestrictionsManager;
+use crate::retention::{CleanupReport, Retention, RetentionStatus};
+use crate::safe_mode::{ResumeRecord, SafeMode, SafeModeStatus};
+use crate::scan_fairness::{FairnessStatus, ScanFairness};
+use crate::throttle::{PerformanceThrottle, ThrottleStatus};
+use crate::trade_feed::{PairTradeFeed, TradeFeed, TradeFeedPolicy};
+use crate::trade_lifecycle::{filled_notional, TradeRecord};
+use crate::runtimes::{spawn_on, EngineRuntimes, RuntimePolicy, RuntimeStats};
+use crate::scan_profile::{ScanProfile, ScanProfiler};
+use crate::scanner::{LiquidityRequirement, PathReport, ScanReport, Scanner};
+use crate::stablecoin::StablecoinPolicy;
+use crate::stats_history::{StatsHistory, StatsHistoryStats};
+use crate::supervisor::{DeadTask, EngineErrors, TaskSupervisor, HEARTBEAT_INTERVAL};
+use crate::trade_wal::{settle, TradeWal, TradeWalStatus};
+use crate::trading_day::{self, DailyResetStatus};
+use crate::universe::{Universe, UniverseStatus, Universes};
+use crate::types::{ActiveSubscription, BookDelta, CacheMemorySnapshot, EngineConfig, EngineStats, Opportunity, OrderBookHealth, OrderBookLevel, Strategy, WsTrafficSnapshot};
+use crate::valuation::{self, PricingSource, ReportedPnl, Valuation, Valuator};
+use crate::webhook::{OpportunityWebhook, WebhookStats};
+use crate::ws_v2::{KrakenWebSocketV2, WsV2Options};
 
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use thiserror::Error;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{info, warn};
-use rand::Rng;
+
+/// Seconds between balance refreshes while any reserve is configured
+const DEFAULT_BALANCE_REFRESH_SECS: u64 = 15;
+/// Minutes between TradeVolume fee checks while running (FEE_REFRESH_MINS, 0 = off)
+const DEFAULT_FEE_REFRESH_MINS: u64 = 60;
+/// Minutes between AssetPairs checks for listings and delistings while
+/// running (PAIR_REFRESH_MINS, 0 = off)
+const DEFAULT_PAIR_REFRESH_MINS: u64 = 60;
+
+/// How often the restrictions file is checked for edits (RESTRICTIONS_RELOAD_SECS)
+const DEFAULT_RESTRICTIONS_RELOAD_SECS: u64 = 5;
+
+/// Seconds `close` waits for in-flight trades to finish (CLOSE_DRAIN_SECS)
+const DEFAULT_CLOSE_DRAIN_SECS: u64 = 10;
 
 #[derive(Error, Debug)]
 pub enum EngineError {
@@ -39,6 +101,14 @@ pub enum EngineError {
     Database(String),
     #[error("Auth error: {0}")]
     Auth(String),
+    #[error("Insufficient {currency}: need {needed:.8}, have {available:.8}")]
+    InsufficientFunds {
+        currency: String,
+        needed: f64,
+        available: f64,
+        needed_usd: Option<f64>,
+        available_usd: Option<f64>,
+    },
 }
 
 // ==========================================
@@ -73,6 +143,21 @@ pub struct PriceInfo {
     pub spread_pct: f64,
 }
 
+/// Top-of-book ladder for one pair (levels best-first)
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderBookLadder {
+    pub pair: String,
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+    /// Levels available in the cache on each side
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+    pub sequence: u64,
+    /// Time of the last snapshot/update applied to the book
+    pub last_update: String,
+    pub staleness_ms: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventScannerStatsApi {
     pub scans_triggered: u64,
@@ -81,6 +166,125 @@ pub struct EventScannerStatsApi {
     pub pending_pairs: usize,
 }
 
+/// Result of TradingEngine::apply_config
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedConfig {
+    pub config: LiveTradingConfig,
+    pub fees: FeeConfiguration,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Event scanner stats with the per-phase scan profile
+#[derive(Debug, Clone, Serialize)]
+pub struct EventScannerStatsDetailed {
+    #[serde(flatten)]
+    pub stats: EventScannerStatsApi,
+    pub profile: ScanProfile,
+}
+
+/// A path priced against the current books plus the HFT loop's guards
+#[derive(Debug, Clone, Serialize)]
+pub struct PathInspection {
+    #[serde(flatten)]
+    pub report: PathReport,
+    /// Loop-side reasons it wouldn't execute now (safe mode, cooldowns, ...)
+    pub guards: Vec<String>,
+    /// Nothing in blocked_by or guards
+    pub would_execute: bool,
+}
+
+/// Engine stats, config and state gathered in one read, for pollers that
+/// would otherwise combine several getters taken at different instants
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineSnapshot {
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    pub is_running: bool,
+    pub uptime_seconds: u64,
+    pub degraded: bool,
+    pub auto_execution_enabled: bool,
+    /// HFT loop state, stats and config (None until the loop is created)
+    pub hft: Option<HftSnapshot>,
+    pub execution: ExecutionStats,
+    /// Fee rate and thresholds the scanner prices with
+    pub scanner_config: EngineConfig,
+    pub pairs_monitored: usize,
+    pub currencies_tracked: usize,
+    pub cache_memory: CacheMemorySnapshot,
+    pub opportunities_cached: usize,
+    pub opportunities_invalidated: u64,
+    pub ws_traffic: WsTrafficSnapshot,
+    pub safe_mode: SafeModeStatus,
+    pub throttle: ThrottleStatus,
+    /// Latest synthetic load run (None if none was started)
+    pub loadgen: Option<LoadGenReport>,
+}
+
+/// Parse sizing tiers from config, ignoring (with a warning) malformed JSON
+fn sizing_tiers_from_config(config: &LiveTradingConfig) -> Vec<SizingTier> {
+    match config.sizing_tiers.as_ref() {
+        Some(value) => SizingTier::parse_tiers(value).unwrap_or_else(|e| {
+            warn!("Ignoring sizing tiers: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    }
+}
+
+/// Reserves from config, ignoring (with a warning) malformed JSON
+fn reserves_from_config(config: &LiveTradingConfig) -> HashMap<String, f64> {
+    match config.currency_reserves.as_ref() {
+        Some(value) => parse_reserves(value).unwrap_or_else(|e| {
+            warn!("Ignoring currency reserves: {}", e);
+            HashMap::new()
+        }),
+        None => HashMap::new(),
+    }
+}
+
+/// Scan-only pairs from config, ignoring (with a warning) malformed JSON
+fn disabled_pairs_from_config(config: &LiveTradingConfig) -> HashSet<String> {
+    match config.execution_disabled_pairs.as_ref() {
+        Some(value) => parse_disabled_pairs(value).map(|pairs| pairs.into_iter().collect()).unwrap_or_else(|e| {
+            warn!("Ignoring execution-disabled pairs: {}", e);
+            HashSet::new()
+        }),
+        None => HashSet::new(),
+    }
+}
+
+/// Thresholds per path length from config, ignoring (with a warning) malformed JSON
+fn leg_thresholds_from_config(config: &LiveTradingConfig) -> BTreeMap<usize, f64> {
+    match config.leg_profit_thresholds.as_ref() {
+        Some(value) => parse_leg_thresholds(value).unwrap_or_else(|e| {
+            warn!("Ignoring leg profit thresholds: {}", e);
+            BTreeMap::new()
+        }),
+        None => BTreeMap::new(),
+    }
+}
+
+/// Cooldowns from config; unset columns leave that cooldown off
+fn cooldowns_from_config(config: &LiveTradingConfig) -> CooldownConfig {
+    let ms = |value: Option<i32>| value.map_or(0, |v| v.max(0) as u64);
+    CooldownConfig {
+        global_ms: ms(config.global_cooldown_ms),
+        path_ms: ms(config.path_cooldown_ms),
+        pair_failure_ms: ms(config.pair_failure_cooldown_ms),
+    }
+}
+
+/// Per-leg liquidity filter from config. Enabled once either column is set;
+/// the other falls back to 10 levels / 50 bps.
+fn leg_liquidity_from_config(config: &LiveTradingConfig) -> Option<LiquidityRequirement> {
+    if config.leg_depth_levels.is_none() && config.max_leg_slippage_bps.is_none() {
+        return None;
+    }
+    Some(LiquidityRequirement {
+        depth_levels: config.leg_depth_levels.map(|v| v.max(1) as usize).unwrap_or(10),
+        max_slippage_bps: config.max_leg_slippage_bps.unwrap_or(50.0),
+    })
+}
+
 // ==========================================
 // Trading Engine
 // ==========================================
@@ -90,10 +294,62 @@ pub struct TradingEngine {
     cache: Arc<OrderBookCache>,
     websocket: RwLock<Option<KrakenWebSocketV2>>,
     config_manager: Arc<ConfigManager>,
+    consistency: Arc<PriceConsistencyMonitor>,
+    index_prices: Arc<IndexPriceMonitor>,
+    dead_man: DeadManSwitch,
+    safe_mode: Arc<SafeMode>,
+    throttle: Arc<PerformanceThrottle>,
+    /// Periodic sweeps so quiet pairs' cycles are still evaluated
+    fairness: Arc<ScanFairness>,
+    // Opportunities waiting for execution, by freshness-adjusted profit
+    exec_queue: Arc<ExecutionQueue>,
+    /// Quarantines implausibly profitable opportunities (over 3% by default)
+    anomalies: Arc<AnomalyDetector>,
+    /// Custom detection and execution hooks
+    plugins: Arc<StrategyPlugins>,
+    /// REST Depth snapshots for pairs the socket is slow to deliver
+    rest_bootstrap: Arc<RestBootstrap>,
+    /// Market-data and execution runtimes
+    runtimes: Arc<EngineRuntimes>,
+    chaos: Arc<ChaosMonkey>,
+    audit: AuditLog,
+    opportunities: Arc<OpportunityCache>,
+    // Recent trade prints per pair (filled when KRAKEN_WS_TRADES is on)
+    trade_feed: Arc<TradeFeed>,
+    webhook: Arc<OpportunityWebhook>,
+    scan_profiler: Arc<ScanProfiler>,
+    // Named currency subgraphs with their own thresholds and trigger modes
+    universes: Arc<Universes>,
+    valuator: Arc<Valuator>,
+    fill_journal: Arc<FillJournal>,
+    // In-flight trades journaled to disk, replayed on restart
+    trade_wal: Arc<TradeWal>,
+    stats_history: Arc<StatsHistory>,
+    notifications: Arc<Notifications>,
+    reconciler: Reconciler,
+    pair_ranking: parking_lot::RwLock<PairRanking>,
+    // Candidates left out by ranking, available to subscribe at runtime
+    unselected_pairs: parking_lot::RwLock<HashMap<String, SelectedPair>>,
+    // AssetPairs listing between pair set checks
+    pair_refresh: PairSetRefresh,
+    /// Book depth per subscribed pair by cycle membership (off by default)
+    depth_tiers: DepthTiers,
+    /// Table retention and archival (opportunities after 7 days by default)
+    retention: Retention,
+    /// Synthetic book update runs for throughput measurements
+    loadgen: LoadGenerator,
+    /// Keeps the DB and HFT loop breakers tripped together (every 5s by default)
+    breaker_sync: BreakerSync,
+    /// Book events dropped on a full channel, per pair and per minute
+    event_drops: EventDropMonitor,
+
+    // Reconnect backoff + history for the public and private sockets
+    public_reconnect: Arc<ReconnectTracker>,
+    private_reconnect: Arc<ReconnectTracker>,
 
     // HFT Loop - unified scan + execute
     hft_loop: Arc<RwLock<Option<HftLoop>>>,
-    hft_event_tx: RwLock<Option<mpsc::Sender<String>>>,
+    hft_event_tx: RwLock<Option<mpsc::Sender<BookDelta>>>,
 
     // Execution engine (shared with HFT loop)
     execution_engine: Arc<RwLock<Option<ExecutionEngine>>>,
@@ -103,6 +359,9 @@ pub struct TradingEngine {
 
     // State
     is_running: AtomicBool,
+    /// Set by `close`; the engine can't be started again
+    closed: AtomicBool,
+    supervisor: Arc<TaskSupervisor>,
     start_time: RwLock<Option<Instant>>,
 
     // Auth
@@ -116,7 +375,9 @@ impl TradingEngine {
         api_secret: Option<String>,
         db: Database,
     ) -> Result<Self, EngineError> {
-        let cache = Arc::new(OrderBookCache::new());
+        let cache = Arc::new(OrderBookCache::new()
+            .with_level_pool(LevelPoolPolicy::from_env())
+            .with_budget(CacheBudget::from_env()));
         let engine_config = crate::types::EngineConfig::unconfigured();
         let config_manager = Arc::new(ConfigManager::new(engine_config));
 
@@ -137,15 +398,61 @@ impl TradingEngine {
             None
         };
 
+        let consistency = Arc::new(PriceConsistencyMonitor::new(Arc::clone(&cache)));
+        let index_prices = Arc::new(IndexPriceMonitor::from_env(Arc::clone(&cache)));
+        let valuator = Arc::new(Valuator::from_env(Arc::clone(&cache)));
+        let rest_bootstrap = Arc::new(RestBootstrap::from_env(Arc::clone(&cache)));
+        let reconnect_policy = ReconnectPolicy::from_env();
+        let webhook = OpportunityWebhook::from_env();
+        webhook.start();
+        let notifications = Arc::new(Notifications::new());
+        let anomalies = Arc::new(AnomalyDetector::from_env().with_notifications(Arc::clone(&notifications)));
+
         Ok(Self {
             cache,
             websocket: RwLock::new(None),
             config_manager,
+            consistency,
+            index_prices,
+            dead_man: DeadManSwitch::from_env(),
+            safe_mode: Arc::new(SafeMode::from_env()),
+            throttle: Arc::new(PerformanceThrottle::from_env()),
+            fairness: Arc::new(ScanFairness::from_env()),
+            exec_queue: Arc::new(ExecutionQueue::new(ExecQueuePolicy::from_env())),
+            anomalies,
+            plugins: Arc::new(StrategyPlugins::from_env()),
+            rest_bootstrap,
+            runtimes: Arc::new(EngineRuntimes::from_env()),
+            chaos: Arc::new(ChaosMonkey::from_env()),
+            audit: AuditLog::new(db.clone()),
+            opportunities: Arc::new(OpportunityCache::from_env()),
+            trade_feed: Arc::new(TradeFeed::new(TradeFeedPolicy::from_env())),
+            webhook,
+            scan_profiler: Arc::new(ScanProfiler::from_env()),
+            universes: Arc::new(Universes::from_env()),
+            valuator,
+            fill_journal: Arc::new(FillJournal::from_env(db.clone())),
+            trade_wal: Arc::new(TradeWal::from_env()),
+            stats_history: Arc::new(StatsHistory::from_env(db.clone())),
+            notifications,
+            reconciler: Reconciler::from_env(),
+            pair_ranking: parking_lot::RwLock::new(PairRanking::default()),
+            unselected_pairs: parking_lot::RwLock::new(HashMap::new()),
+            pair_refresh: PairSetRefresh::new(),
+            depth_tiers: DepthTiers::from_env(),
+            retention: Retention::from_env(),
+            loadgen: LoadGenerator::new(),
+            breaker_sync: BreakerSync::from_env(),
+            event_drops: EventDropMonitor::from_env(),
+            public_reconnect: Arc::new(ReconnectTracker::new("public", reconnect_policy.clone())),
+            private_reconnect: Arc::new(ReconnectTracker::new("private", reconnect_policy)),
             hft_loop: Arc::new(RwLock::new(None)),
             hft_event_tx: RwLock::new(None),
             execution_engine: Arc::new(RwLock::new(None)),
             db,
             is_running: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            supervisor: Arc::new(TaskSupervisor::from_env()),
             start_time: RwLock::new(None),
             auth,
         })
@@ -153,10 +460,14 @@ impl TradingEngine {
 
     /// Start the trading engine with HFT loop
     pub async fn start(&self) -> Result<(), EngineError> {
+        if self.is_closed() {
+            return Err(EngineError::Config("Engine is closed".to_string()));
+        }
         info!("Starting trading engine (HFT mode)...");
 
         // Clear cache from any previous run to ensure pair count matches new config
         self.cache.clear();
+        self.trade_feed.clear();
 
         // Load user configuration from database
         let db_config = self.db.get_config().await
@@ -180,6 +491,22 @@ impl TradingEngine {
             "max_cost_min not configured".to_string()
         ))?;
 
+        // Come up in safe mode when asked to, or while the breaker is still tripped
+        if self.safe_mode.enter_on_start() {
+            warn!("Starting in safe mode (SAFE_MODE_START=always)");
+        }
+        match self.db.get_state().await {
+            Ok(state) if state.is_circuit_broken => {
+                let reason = format!(
+                    "circuit breaker tripped: {}",
+                    state.circuit_broken_reason.unwrap_or_default()
+                );
+                self.enter_safe_mode(&reason);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to load trading state: {}", e),
+        }
+
         // Select pairs
         info!("Selecting high-liquidity pairs for HFT arbitrage...");
         let mut pair_config = PairSelectionConfig::default();
@@ -190,33 +517,95 @@ impl TradingEngine {
             return Err(EngineError::Config(e));
         }
 
+        self.pair_refresh.reset(pair_config.clone());
         let pair_selector = KrakenPairSelector::new(pair_config);
-        let selected_pairs = pair_selector.select_pairs().await
+        let candidates = pair_selector.select_candidates().await
             .map_err(|e| EngineError::WebSocket(format!("Pair selection failed: {}", e)))?;
+        let base_currencies: Vec<String> = start_currency.split(',').map(|s| s.trim().to_uppercase()).collect();
+        let selected_pairs = self.rank_candidates(candidates, &base_currencies, max_pairs as usize).await;
+        self.depth_tiers.reset(base_currencies.clone());
 
         if selected_pairs.is_empty() {
             return Err(EngineError::WebSocket("No pairs selected".to_string()));
         }
 
         info!("Selected {} pairs for HFT arbitrage", selected_pairs.len());
+        for pair in &selected_pairs {
+            self.valuator.record_last_trade(&pair.pair_name, pair.last_price);
+        }
 
         // Initialize WebSocket
         let mut ws = KrakenWebSocketV2::new(Arc::clone(&self.cache));
         ws.set_max_pairs(selected_pairs.len());
+        ws.set_subscription_priority(selected_pairs.iter().map(|p| p.pair_name.clone()).collect());
+        ws.set_options(WsV2Options::from_env());
+        ws.set_trade_feed(Arc::clone(&self.trade_feed));
+        ws.set_reconnect_tracker(Arc::clone(&self.public_reconnect));
+        ws.set_chaos(Arc::clone(&self.chaos));
+        ws.set_runtime(self.runtimes.market_data.handle().clone());
+        if self.depth_tiers.policy().enabled() {
+            let metas: Vec<PairMeta> = selected_pairs.iter().map(PairMeta::from).collect();
+            ws.set_pair_depths(
+                self.depth_tiers.assign(&metas).into_iter().filter_map(|p| Some((p.pair, p.depth?))).collect(),
+            );
+        }
 
         // Create HFT Loop
         let mut hft_loop = HftLoop::new(
             Arc::clone(&self.cache),
             Arc::clone(&self.config_manager),
             self.db.clone(),
-        );
+            self.audit.clone(),
+            Arc::clone(&self.opportunities),
+            Arc::clone(&self.scan_profiler),
+            Arc::clone(&self.valuator),
+        )
+        .with_webhook(Arc::clone(&self.webhook))
+        .with_safe_mode(Arc::clone(&self.safe_mode))
+        .with_throttle(Arc::clone(&self.throttle))
+        .with_universes(Arc::clone(&self.universes))
+        .with_fairness(Arc::clone(&self.fairness))
+        .with_exec_queue(Arc::clone(&self.exec_queue))
+        .with_plugins(Arc::clone(&self.plugins))
+        .with_anomalies(Arc::clone(&self.anomalies))
+        .with_supervisor(Arc::clone(&self.supervisor))
+        .with_runtime(self.runtimes.execution.handle().clone());
 
         // Initialize execution engine FIRST (before WebSocket starts sending events)
         if let Some(ref auth) = self.auth {
+            // Orders take the cached token; it's renewed off the hot path
+            auth.start_token_refresh();
             let exec_engine = ExecutionEngine::new(
                 Arc::clone(auth),
                 Arc::clone(&self.cache),
-            );
+            )
+            .with_audit(self.audit.clone())
+            .with_fill_journal(Arc::clone(&self.fill_journal))
+            .with_trade_wal(Arc::clone(&self.trade_wal))
+            .with_resize_policy(FundsResizePolicy::from_env())
+            .with_signal_gate(SignalGatePolicy::from_env())
+            .with_buy_sizing(BuySizing::from_env())
+            .with_freshness(FreshnessPolicy::from_env())
+            .with_prefund_check(PrefundPolicy::from_env())
+            .with_maker_policy(MakerPolicy::from_env())
+            .with_retry_policy(RetryPolicy::from_env())
+            .with_order_minimums(
+                selected_pairs.iter().map(|p| (p.pair_name.clone(), (p.ordermin, p.costmin))).collect(),
+            )
+            .with_price_cap(
+                PriceCapPolicy::from_env(),
+                selected_pairs
+                    .iter()
+                    .filter_map(|p| Some((p.pair_name.clone(), (p.pair_decimals?, p.lot_decimals?))))
+                    .collect(),
+            )
+            .with_market_tif(MarketTifPolicy::from_env())
+            .with_trade_feed(Arc::clone(&self.trade_feed))
+            .with_reconnect(Arc::clone(&self.private_reconnect))
+            .with_chaos(Arc::clone(&self.chaos))
+            .with_supervisor(Arc::clone(&self.supervisor))
+            .with_runtime(self.runtimes.execution.handle().clone());
+            exec_engine.set_execution_disabled(disabled_pairs_from_config(&db_config));
 
             if let Err(e) = exec_engine.connect().await {
                 warn!("Failed to connect execution engine: {}", e);
@@ -227,13 +616,24 @@ impl TradingEngine {
             }
         }
 
+        self.config_manager.update_leg_thresholds(leg_thresholds_from_config(&db_config));
+
         // Configure HFT loop with user settings (before starting event channel)
         let hft_config = HftConfig {
             min_profit_threshold: db_config.min_profit_threshold.unwrap_or(0.1),
             trade_amount: db_config.trade_amount.unwrap_or(10.0),
             max_daily_loss: db_config.max_daily_loss.unwrap_or(100.0),
             max_total_loss: db_config.max_total_loss.unwrap_or(500.0),
+            reporting_currency: valuation::reporting_currency_from_env(),
             base_currencies: start_currency.split(',').map(|s| s.trim().to_uppercase()).collect(),
+            sizing_tiers: sizing_tiers_from_config(&db_config),
+            max_safe_amount: db_config.max_safe_amount,
+            cooldowns: cooldowns_from_config(&db_config),
+            leg_liquidity: leg_liquidity_from_config(&db_config),
+            reserves: reserves_from_config(&db_config),
+            stablecoins: StablecoinPolicy::from_env(),
+            notional_limits: NotionalLimits { per_hour: db_config.max_notional_per_hour, per_day: db_config.max_notional_per_day },
+            min_pair_quality: pair_quality::min_score_from_env(),
         };
         hft_loop.update_config(hft_config).await;
 
@@ -251,40 +651,122 @@ impl TradingEngine {
         let hft_event_tx = hft_loop.create_event_channel();
 
         // Create WebSocket event channel
-        let (mut ws_event_rx, _) = ws.create_event_channel();
+        let (ws_event_rx, event_stats) = ws.create_event_channel();
+        self.event_drops.attach(event_stats);
 
-        // Forward WebSocket events to HFT loop
+        // Forward WebSocket events to HFT loop (a restart after a panic
+        // resumes on the same receiver). It beats while waiting on either
+        // side, so only a forwarder that's really stuck goes stale.
         let hft_tx_clone = hft_event_tx.clone();
-        tokio::spawn(async move {
-            while let Some(pair) = ws_event_rx.recv().await {
-                if hft_tx_clone.send(pair).await.is_err() {
-                    break;
+        let ws_event_rx = Arc::new(tokio::sync::Mutex::new(ws_event_rx));
+        let heartbeat = self.supervisor.heartbeat("event_forwarder");
+        self.supervisor.supervise(Some(self.runtimes.market_data.handle()), "event_forwarder", move || {
+            let ws_event_rx = Arc::clone(&ws_event_rx);
+            let hft_tx = hft_tx_clone.clone();
+            let heartbeat = heartbeat.clone();
+            async move {
+                let mut ws_event_rx = ws_event_rx.lock().await;
+                'forward: loop {
+                    let mut delta = match tokio::time::timeout(HEARTBEAT_INTERVAL, ws_event_rx.recv()).await {
+                        Ok(Some(delta)) => delta,
+                        Ok(None) => break,
+                        Err(_) => {
+                            heartbeat.beat();
+                            continue;
+                        }
+                    };
+                    loop {
+                        match hft_tx.send_timeout(delta, HEARTBEAT_INTERVAL).await {
+                            Ok(()) => break,
+                            Err(mpsc::error::SendTimeoutError::Timeout(pending)) => delta = pending,
+                            Err(mpsc::error::SendTimeoutError::Closed(_)) => break 'forward,
+                        }
+                        heartbeat.beat();
+                    }
+                    heartbeat.beat();
                 }
+                info!("WebSocket to HFT event forwarder stopped");
             }
-            info!("WebSocket to HFT event forwarder stopped");
         });
 
         // Initialize WebSocket with pairs and START (events will flow after this)
+        let bootstrap_order: Vec<String> = selected_pairs.iter().map(|p| p.pair_name.clone()).collect();
         ws.initialize_with_pairs(selected_pairs);
         ws.start(max_pairs as usize, 25).await
             .map_err(|e| EngineError::WebSocket(e.to_string()))?;
 
+        // Fill books over REST if the subscription snapshots are slow
+        let bootstrap = Arc::clone(&self.rest_bootstrap);
+        let bootstrap_events = hft_event_tx.clone();
+        spawn_on(Some(self.runtimes.market_data.handle()), async move {
+            bootstrap.run(bootstrap_order, Some(bootstrap_events)).await;
+        });
+
         *self.websocket.write().await = Some(ws);
 
+        // Cross-check cached prices and quarantine broken/stale pairs
+        self.consistency.start();
+        // Block pairs that drift from the external index (when enabled)
+        self.index_prices.start();
+
         // Store references
         *self.hft_loop.write().await = Some(hft_loop);
         *self.hft_event_tx.write().await = Some(hft_event_tx);
 
+        self.supervisor.reset();
         self.is_running.store(true, Ordering::SeqCst);
         *self.start_time.write().await = Some(Instant::now());
+        self.dead_man.rearm();
 
         info!("Trading engine started (HFT mode)");
         Ok(())
     }
 
+    /// Score candidates on volume, past profitable trades and triangle
+    /// membership, keep the best `max_pairs` (best first) and remember the
+    /// ranking for the API
+    async fn rank_candidates(&self, candidates: Vec<SelectedPair>, base_currencies: &[String], max_pairs: usize) -> Vec<SelectedPair> {
+        let policy = RankingPolicy::from_env();
+        let trades = self.db.get_trades(10_000, None, policy.history_hours).await.unwrap_or_else(|e| {
+            warn!("Pair ranking without trade history: {}", e);
+            Vec::new()
+        });
+        let profitable = trades.iter()
+            .filter(|t| t.profit_loss.unwrap_or(0.0) > 0.0 || t.opportunity_profit_pct.unwrap_or(0.0) > 0.0)
+            .map(|t| t.path.as_str());
+        let participation = path_participation(profitable, &candidates);
+        let ranked = rank_pairs(&candidates, &participation, base_currencies, &policy, max_pairs);
+
+        let mut by_name: HashMap<String, SelectedPair> = candidates.into_iter().map(|p| (p.pair_name.clone(), p)).collect();
+        let selected: Vec<SelectedPair> = ranked.iter()
+            .filter(|s| s.selected)
+            .filter_map(|s| by_name.remove(&s.pair))
+            .collect();
+        info!(
+            "Selected {} of {} candidate pairs by {} ({} profitable trades considered)",
+            selected.len(), ranked.len(), if policy.enabled { "score" } else { "volume" }, participation.values().sum::<u64>()
+        );
+
+        *self.pair_ranking.write() = PairRanking {
+            ranked_at: Some(chrono::Utc::now()),
+            selection: if policy.enabled { "score" } else { "volume" }.to_string(),
+            max_pairs,
+            candidates: ranked.len(),
+            pairs: ranked,
+        };
+        *self.unselected_pairs.write() = by_name;
+        selected
+    }
+
+    /// Candidate pairs with their scores from the last start
+    pub fn get_pair_ranking(&self) -> PairRanking {
+        self.pair_ranking.read().clone()
+    }
+
     /// Stop the trading engine
     pub async fn stop(&self) {
         info!("Stopping trading engine...");
+        self.supervisor.unwatch("event_forwarder");
 
         if let Some(ref hft_loop) = *self.hft_loop.read().await {
             hft_loop.stop();
@@ -294,13 +776,58 @@ impl TradingEngine {
             ws.stop().await;
         }
 
+        self.consistency.stop();
+        self.index_prices.stop();
+
         self.is_running.store(false, Ordering::SeqCst);
         info!("Trading engine stopped");
     }
 
+    /// Shut the engine down for good: stop trading and the public socket,
+    /// let in-flight trades finish (up to CLOSE_DRAIN_SECS), then stop every
+    /// background task and the dedicated runtimes, which closes the private
+    /// socket. Safe to call more than once; only the first call does anything.
+    pub async fn close(&self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        info!("Closing trading engine...");
+        self.stop().await;
+
+        let drain = std::env::var("CLOSE_DRAIN_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_CLOSE_DRAIN_SECS);
+        let deadline = Instant::now() + std::time::Duration::from_secs(drain);
+        loop {
+            let in_flight = self.in_flight_trades().await;
+            if in_flight.is_empty() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                let ids: Vec<&str> = in_flight.iter().map(|t| t.trade_id.as_str()).collect();
+                warn!("Closing with {} trade(s) still in flight: {:?} (recovered from the WAL on restart)", ids.len(), ids);
+                self.audit.record(AuditActor::System, AuditCategory::Order, "close_with_trades_in_flight", serde_json::json!({
+                    "trade_ids": ids,
+                    "drain_secs": drain,
+                }));
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        self.supervisor.shutdown();
+        self.runtimes.shutdown();
+        info!("Trading engine closed");
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
     /// Get engine statistics
     pub async fn get_stats(&self) -> EngineStats {
-        let (pairs, currencies, _) = self.cache.get_stats();
+        let cache = self.cache.get_stats();
         let uptime = self.start_time.read().await
             .map(|t| t.elapsed().as_secs())
             .unwrap_or(0);
@@ -311,20 +838,73 @@ impl TradingEngine {
             HftStats::default()
         };
 
+        let (ws_traffic, ws_parse) = self.websocket.read().await
+            .as_ref()
+            .map(|ws| (ws.get_traffic_stats(), ws.get_parse_stats()))
+            .unwrap_or_default();
+
         EngineStats {
             is_running: self.is_running.load(Ordering::Relaxed),
-            pairs_monitored: pairs,
-            currencies_tracked: currencies,
-            orderbooks_cached: pairs,
+            pairs_monitored: cache.pairs,
+            currencies_tracked: cache.currencies,
+            orderbooks_cached: cache.pairs,
             avg_orderbook_staleness_ms: 0.0,
             opportunities_found: hft_stats.opportunities_found,
             opportunities_per_second: 0.0,
+            paths_filtered_liquidity: hft_stats.skipped_illiquid,
+            events_skipped_deep: hft_stats.events_skipped_deep,
             uptime_seconds: uptime,
             scan_cycle_ms: 0.0,
             last_scan_at: String::new(),
+            ws_traffic,
+            ws_parse,
+            degraded: self.supervisor.is_degraded(),
+            panics_since_start: self.supervisor.panics_since_start(),
+            watchdog_restarts: self.supervisor.watchdog_restarts(),
+            cache_memory: cache.memory,
         }
     }
 
+    /// Stats, config and state in one struct. The HFT loop's part is read
+    /// with its locks held together and the loop handle held throughout;
+    /// everything else is counters read alongside it.
+    pub async fn get_engine_snapshot(&self) -> EngineSnapshot {
+        let hft_loop = self.hft_loop.read().await;
+        let (hft, execution) = match hft_loop.as_ref() {
+            Some(hft) => (Some(hft.snapshot().await), hft.get_execution_stats().await),
+            None => (None, ExecutionStats::default()),
+        };
+        let cache = self.cache.get_stats();
+        let uptime_seconds = self.start_time.read().await.map(|t| t.elapsed().as_secs()).unwrap_or(0);
+        let ws_traffic = self.websocket.read().await.as_ref().map(|ws| ws.get_traffic_stats()).unwrap_or_default();
+        let snapshot = EngineSnapshot {
+            taken_at: chrono::Utc::now(),
+            is_running: self.is_running.load(Ordering::Relaxed),
+            uptime_seconds,
+            degraded: self.supervisor.is_degraded(),
+            auto_execution_enabled: self.is_auto_execution_enabled(),
+            hft,
+            execution,
+            scanner_config: self.config_manager.get_config(),
+            pairs_monitored: cache.pairs,
+            currencies_tracked: cache.currencies,
+            cache_memory: cache.memory,
+            opportunities_cached: self.opportunities.len(),
+            opportunities_invalidated: self.opportunities.invalidated(),
+            ws_traffic,
+            safe_mode: self.get_safe_mode(),
+            throttle: self.get_throttle(),
+            loadgen: self.loadgen.report(),
+        };
+        drop(hft_loop);
+        snapshot
+    }
+
+    /// Panics and supervised task health
+    pub fn get_errors(&self) -> EngineErrors {
+        self.supervisor.errors()
+    }
+
     /// Get HFT loop state
     pub async fn get_hft_state(&self) -> Option<HftState> {
         if let Some(ref hft) = *self.hft_loop.read().await {
@@ -343,12 +923,260 @@ impl TradingEngine {
         }
     }
 
-    /// Reset circuit breaker
-    pub async fn reset_circuit_breaker(&self) {
+    /// Order/amend counters from the execution engine (zeros when not connected)
+    pub async fn get_execution_stats(&self) -> ExecutionStats {
+        match self.hft_loop.read().await.as_ref() {
+            Some(hft) => hft.get_execution_stats().await,
+            None => ExecutionStats::default(),
+        }
+    }
+
+    /// Reconnect counters and recent disconnects for both sockets
+    pub fn get_reconnect_stats(&self) -> Vec<ReconnectStats> {
+        vec![self.public_reconnect.stats(), self.private_reconnect.stats()]
+    }
+
+    /// WebSocket token fetches and cache hits (None without credentials)
+    pub fn get_ws_token_stats(&self) -> Option<TokenStats> {
+        self.auth.as_ref().map(|auth| auth.token_stats())
+    }
+
+    /// Queue latency and task counts of the market-data, execution and main runtimes
+    pub fn get_runtime_stats(&self) -> (RuntimePolicy, Vec<RuntimeStats>) {
+        (self.runtimes.policy(), self.runtimes.stats())
+    }
+
+    /// Start a synthetic load run on the market-data runtime, priced with the
+    /// scanner's current fees and threshold
+    pub fn start_loadgen(&self, config: LoadGenConfig) -> Result<(), String> {
+        self.loadgen.start(config, self.config_manager.get_config(), Some(self.runtimes.market_data.handle()))
+    }
+
+    /// Injected faults and observed recoveries (all zero unless CHAOS_ENABLED)
+    pub fn get_chaos_stats(&self) -> ChaosStats {
+        self.chaos.stats()
+    }
+
+    /// Warm-up progress (None when the HFT loop isn't running)
+    pub async fn get_warmup(&self) -> Option<WarmupProgress> {
+        self.hft_loop.read().await.as_ref().map(|hft| hft.get_warmup())
+    }
+
+    /// Every guard evaluated against each cached opportunity, newest first
+    /// (`id` narrows it to one; empty when the HFT loop isn't running)
+    pub async fn explain_opportunity_guards(&self, id: Option<&str>) -> Vec<GuardVerdict> {
+        let hft_loop = self.hft_loop.read().await;
+        let Some(hft) = hft_loop.as_ref() else { return Vec::new() };
+        let mut verdicts = Vec::new();
+        for cached in self.opportunities.get_with_age(&self.cache) {
+            if id.is_some_and(|id| id != cached.opportunity.id) {
+                continue;
+            }
+            verdicts.push(hft.explain_guards(&cached.opportunity, cached.age_ms).await);
+        }
+        verdicts
+    }
+
+    /// Active trade cooldowns (global, per-path, per-pair) with remaining time
+    pub async fn get_cooldowns(&self) -> Vec<ActiveCooldown> {
+        if let Some(ref hft) = *self.hft_loop.read().await {
+            hft.get_cooldowns().await
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Traded notional headroom (None when the HFT loop isn't running)
+    pub async fn get_notional_headroom(&self) -> Option<NotionalHeadroom> {
+        match *self.hft_loop.read().await {
+            Some(ref hft) => Some(hft.get_notional_headroom().await),
+            None => None,
+        }
+    }
+
+    /// Reset circuit breaker in the DB and the engine. Scanning resumes,
+    /// execution waits in safe mode for an operator to confirm (unless
+    /// SAFE_MODE_START=off).
+    pub async fn reset_circuit_breaker(&self) -> Result<LiveTradingState, EngineError> {
+        let _sync = self.breaker_sync.lock().await;
+        let state = self.db.reset_circuit_breaker().await
+            .map_err(|e| EngineError::Database(e.to_string()))?;
+        self.enter_safe_mode("circuit breaker reset");
         if let Some(ref hft) = *self.hft_loop.read().await {
             hft.reset_circuit_breaker().await;
         }
         info!("Circuit breaker reset");
+        Ok(state)
+    }
+
+    /// Enter safe mode for an automatic trigger (as SAFE_MODE_START allows)
+    fn enter_safe_mode(&self, reason: &str) {
+        if self.safe_mode.enter_auto(reason) {
+            warn!("Safe mode: {} - execution blocked until an operator confirms resume", reason);
+            self.audit.record(AuditActor::System, AuditCategory::Breaker, "safe_mode_entered", serde_json::json!({ "reason": reason }));
+        }
+    }
+
+    /// Safe mode state, reason and the last operator confirmation
+    pub fn get_safe_mode(&self) -> SafeModeStatus {
+        self.safe_mode.status()
+    }
+
+    /// Performance throttle state (realized vs expected profit)
+    pub fn get_throttle(&self) -> ThrottleStatus {
+        self.throttle.status(Instant::now())
+    }
+
+    /// Fairness sweeps and how long since every cycle was evaluated
+    pub fn get_scan_fairness(&self) -> FairnessStatus {
+        self.fairness.status(Instant::now())
+    }
+
+    /// Opportunities waiting for execution and what the queue dropped
+    pub fn get_exec_queue(&self) -> ExecQueueStatus {
+        self.exec_queue.status(chrono::Utc::now())
+    }
+
+    /// Pairs flagged by the anomaly detector and what it quarantined
+    pub fn get_anomalies(&self) -> AnomalyStatus {
+        self.anomalies.status(chrono::Utc::now())
+    }
+
+    /// Release a flagged pair; false when it wasn't flagged
+    pub fn clear_anomaly(&self, pair: &str) -> bool {
+        self.anomalies.clear(pair)
+    }
+
+    /// Add a strategy plugin (takes effect on the next book update)
+    pub fn register_strategy_plugin(&self, plugin: Arc<dyn StrategyPlugin>) {
+        self.plugins.register(plugin);
+    }
+
+    /// Registered strategy plugins and what they found, skipped and resized
+    pub fn get_strategy_plugins(&self) -> Vec<PluginStatus> {
+        self.plugins.status()
+    }
+
+    /// Last REST bootstrap and the pairs still waiting for a socket snapshot
+    pub fn get_rest_bootstrap(&self) -> BootstrapStatus {
+        self.rest_bootstrap.status()
+    }
+
+    /// Operator confirmation that execution may resume after safe mode
+    pub fn confirm_resume(&self, operator_id: &str, reason: &str) -> Result<ResumeRecord, String> {
+        let record = self.safe_mode.confirm_resume(operator_id, reason)?;
+        // A dead-man trip is cleared along with the safe mode it entered
+        self.dead_man.rearm();
+        info!("Safe mode cleared by {}: {}", record.operator_id, record.reason);
+        Ok(record)
+    }
+
+    /// Check whether the previous run ended while trading was enabled (a
+    /// crash or unclean restart). If so, start streaming and scanning again
+    /// in safe mode: nothing executes until `confirm_resume`.
+    pub async fn recover_after_restart(&self) {
+        self.recover_in_flight_trades().await;
+
+        let config = match self.db.get_config().await {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to load config for restart recovery: {}", e);
+                return;
+            }
+        };
+        if !config.is_enabled {
+            return;
+        }
+
+        self.enter_safe_mode("previous run ended with trading enabled (crash or unclean restart)");
+        if !self.safe_mode.is_active() {
+            // SAFE_MODE_START=off keeps the old behaviour: wait for a manual start
+            return;
+        }
+        info!("Restarting streaming and scanning in safe mode");
+        if let Err(e) = self.start().await {
+            warn!("Failed to restart engine in safe mode: {}", e);
+        }
+    }
+
+    /// Record trades the write-ahead log shows were still in flight when
+    /// the previous run died. The unconfirmed leg is checked against
+    /// Kraken's trade history; what's left is saved as COMPLETED, FAILED or
+    /// PARTIAL (held currency/amount set, resolved through the partial-trade
+    /// endpoints) and closed in the log.
+    pub async fn recover_in_flight_trades(&self) {
+        let trades = self.trade_wal.incomplete();
+        let Some(earliest) = trades.iter().map(|t| t.started_at).min() else {
+            return;
+        };
+        warn!("Trade WAL has {} trade(s) in flight from the previous run", trades.len());
+
+        let since = earliest.timestamp() - 60;
+        let history = match self.private_request("/0/private/TradesHistory", &format!("start={}", since)).await {
+            Ok(result) => Some(parse_trades(&result)),
+            Err(e) => {
+                warn!("Failed to fetch trade history for WAL recovery: {}", e);
+                None
+            }
+        };
+
+        for trade in trades {
+            let recovery = settle(&trade, history.as_deref());
+            let held = recovery.status == "PARTIAL";
+            let start_currency = trade.path.split(" → ").next().unwrap_or_default();
+            let pnl = (recovery.status == "COMPLETED")
+                .then(|| self.report_pnl(start_currency, recovery.held_amount - trade.start_amount));
+            let record = crate::db::NewLiveTrade {
+                trade_id: trade.trade_id.clone(),
+                path: trade.path.clone(),
+                legs: trade.total_legs() as i32,
+                amount_in: trade.start_amount,
+                amount_out: (recovery.status == "COMPLETED").then_some(recovery.held_amount),
+                profit_loss: pnl.as_ref().map(|p| p.amount),
+                profit_loss_pct: None,
+                pnl_currency: pnl.as_ref().map(|p| p.currency.clone()),
+                pnl_reporting: pnl.as_ref().and_then(|p| p.reporting_amount),
+                reporting_currency: pnl.as_ref().map(|p| p.reporting_currency.clone()),
+                fx_rate: pnl.as_ref().and_then(|p| p.fx_rate),
+                status: recovery.status.to_string(),
+                current_leg: Some(recovery.legs_completed as i32),
+                error_message: Some(recovery.note.clone()),
+                held_currency: held.then(|| recovery.held_currency.clone()),
+                held_amount: held.then_some(recovery.held_amount),
+                held_value_usd: None,
+                order_ids: serde_json::to_value(&recovery.order_ids).ok(),
+                leg_fills: serde_json::to_value(&trade.filled).ok(),
+                started_at: Some(trade.started_at),
+                completed_at: Some(chrono::Utc::now()),
+                total_execution_ms: None,
+                opportunity_profit_pct: None,
+                strategy: None,
+                tags: vec!["recovered".to_string()],
+            };
+            if let Err(e) = self.db.save_trade(&record).await {
+                // Stays open in the log; the next restart tries again
+                warn!("Failed to save recovered trade {}: {}", trade.trade_id, e);
+                continue;
+            }
+
+            self.audit.record(AuditActor::System, AuditCategory::Order, "trade_recovered", serde_json::json!({
+                "trade_id": trade.trade_id,
+                "path": trade.path,
+                "recovery": recovery,
+            }));
+            self.notifications.push(
+                if held { Severity::Critical } else { Severity::Warning },
+                "trade_wal",
+                format!("Recovered in-flight trade {} as {}", trade.path, recovery.status),
+                serde_json::to_value(&recovery).unwrap_or_default(),
+            );
+            self.trade_wal.close(&trade.trade_id, recovery.status == "COMPLETED").await;
+        }
+    }
+
+    /// Trade WAL settings and counters
+    pub fn get_trade_wal_status(&self) -> TradeWalStatus {
+        self.trade_wal.status()
     }
 
     /// Reset daily statistics
@@ -363,6 +1191,7 @@ impl TradingEngine {
     pub async fn sync_config(&self, config: &LiveTradingConfig) {
         let min_profit = config.min_profit_threshold.unwrap_or(0.1);
         self.config_manager.update_config(Some(min_profit), None);
+        self.config_manager.update_leg_thresholds(leg_thresholds_from_config(config));
 
         if let Some(ref hft) = *self.hft_loop.read().await {
             let hft_config = HftConfig {
@@ -370,18 +1199,126 @@ impl TradingEngine {
                 trade_amount: config.trade_amount.unwrap_or(10.0),
                 max_daily_loss: config.max_daily_loss.unwrap_or(100.0),
                 max_total_loss: config.max_total_loss.unwrap_or(500.0),
+                reporting_currency: valuation::reporting_currency_from_env(),
                 base_currencies: config.start_currency.clone()
                     .unwrap_or_default()
                     .split(',')
                     .map(|s| s.trim().to_uppercase())
                     .collect(),
+                sizing_tiers: sizing_tiers_from_config(config),
+                max_safe_amount: config.max_safe_amount,
+                cooldowns: cooldowns_from_config(config),
+                leg_liquidity: leg_liquidity_from_config(config),
+                reserves: reserves_from_config(config),
+                stablecoins: StablecoinPolicy::from_env(),
+                notional_limits: NotionalLimits { per_hour: config.max_notional_per_hour, per_day: config.max_notional_per_day },
+                min_pair_quality: pair_quality::min_score_from_env(),
             };
             hft.update_config(hft_config).await;
         }
+        if let Some(ref engine) = *self.execution_engine.read().await {
+            engine.set_execution_disabled(disabled_pairs_from_config(config));
+        }
 
         info!("Config synced: trade_amount={:?}", config.trade_amount);
     }
 
+    /// Apply the stored settings before anything runs, so scanning and the
+    /// API use the configured threshold, leg thresholds and fee rate from the
+    /// first request instead of engine defaults. An unreadable or invalid
+    /// stored config is an error; unset start settings and pending fees only
+    /// are under `policy.require_complete`. Returns what is still unset.
+    pub async fn load_startup_config(&self, policy: StartupConfigPolicy) -> Result<Vec<String>, EngineError> {
+        let (config, fees) = self.load_config_parts().await.map_err(|e| match e {
+            ConfigError::Database(e) => EngineError::Database(format!("Failed to load config: {}", e)),
+            e => EngineError::Config(e.to_string()),
+        })?;
+        let document = ConfigDocument::from_parts(&config, &fees)
+            .validate()
+            .map_err(|errors| EngineError::Config(format!("Stored configuration is invalid: {}", ConfigError::Invalid(errors))))?;
+
+        self.sync_config(&config).await;
+        let mut missing = document.missing_required();
+        if fees.fee_source == "pending" {
+            missing.push("fees".to_string());
+        } else {
+            self.config_manager.update_fee_rate(fees.taker_fee, &fees.fee_source);
+        }
+
+        if missing.is_empty() {
+            info!("Stored configuration applied");
+        } else if policy.require_complete {
+            return Err(EngineError::Config(format!(
+                "Required settings not configured: {} (STARTUP_REQUIRE_CONFIG is on)",
+                missing.join(", ")
+            )));
+        } else {
+            warn!("Stored configuration applied; not configured yet: {}", missing.join(", "));
+        }
+        Ok(missing)
+    }
+
+    /// Current settings as one document
+    pub async fn get_config_document(&self) -> Result<ConfigDocument, ConfigError> {
+        let (config, fees) = self.load_config_parts().await?;
+        Ok(ConfigDocument::from_parts(&config, &fees))
+    }
+
+    async fn load_config_parts(&self) -> Result<(LiveTradingConfig, FeeConfiguration), ConfigError> {
+        let db_error = |e: crate::db::DbError| ConfigError::Database(e.to_string());
+        let config = self.db.get_config().await.map_err(db_error)?;
+        let fees = self.db.get_fee_configuration().await.map_err(db_error)?;
+        Ok((config, fees))
+    }
+
+    /// The one update path for all settings: merge the patch over the current
+    /// document, validate it, persist what changed, push it to the config
+    /// manager and HFT loop, and log the diff. `source` names the endpoint.
+    pub async fn apply_config(&self, patch: ConfigPatch, actor: AuditActor, source: &str) -> Result<AppliedConfig, ConfigError> {
+        let (config, fees) = self.load_config_parts().await?;
+        let current = ConfigDocument::from_parts(&config, &fees);
+        let updated = current.merged(&patch)?.validate().map_err(ConfigError::Invalid)?;
+        let changes = updated.diff(&current);
+
+        let fees_changed = ConfigDocument::fees_changed(&changes);
+        if fees_changed && self.is_running() {
+            return Err(ConfigError::Invalid(vec![FieldError {
+                field: "fees".to_string(),
+                message: "cannot change while the engine is running - stop it first".to_string(),
+            }]));
+        }
+
+        let db_error = |e: crate::db::DbError| ConfigError::Database(e.to_string());
+        let config = if ConfigDocument::trading_changed(&changes) {
+            let config = self.db.update_config(updated.trading.clone()).await.map_err(db_error)?;
+            self.sync_config(&config).await;
+            config
+        } else {
+            config
+        };
+        let fees = if fees_changed {
+            let fees = self.db.update_fee_manual(updated.fees.maker_fee, updated.fees.taker_fee).await.map_err(db_error)?;
+            self.update_fee_config(Some(fees.maker_fee), Some(fees.taker_fee)).await;
+            fees
+        } else {
+            fees
+        };
+
+        if !changes.is_empty() {
+            info!(
+                "Config changed via {}: {}",
+                source,
+                changes.iter().map(|c| c.field.as_str()).collect::<Vec<_>>().join(", ")
+            );
+            self.audit.record(actor, AuditCategory::Config, "config_change", serde_json::json!({
+                "source": source,
+                "changes": changes,
+            }));
+        }
+
+        Ok(AppliedConfig { config, fees, changes })
+    }
+
     /// Check if engine is running
     pub fn is_running(&self) -> bool {
         self.is_running.load(Ordering::Relaxed)
@@ -389,7 +1326,7 @@ impl TradingEngine {
 
     /// Get scanner status
     pub fn get_scanner_status(&self) -> ScannerStatus {
-        let (pairs_count, _, _) = self.cache.get_stats();
+        let pairs_count = self.cache.get_stats().pairs;
 
         ScannerStatus {
             is_running: self.is_running.load(Ordering::Relaxed),
@@ -419,6 +1356,107 @@ impl TradingEngine {
         Vec::new()
     }
 
+    /// One full scan outside the HFT loop, reporting filter counts and the
+    /// best cycles (base currencies default to the configured start currencies)
+    pub async fn scan_detailed(&self, base_currencies: Option<Vec<String>>, top_n: usize) -> Result<ScanReport, EngineError> {
+        let (scanner, bases) = self.adhoc_scanner(base_currencies).await?;
+        tokio::task::spawn_blocking(move || scanner.scan_detailed(&bases, top_n))
+            .await
+            .map_err(|e| EngineError::Execution(e.to_string()))
+    }
+
+    /// Scan universes with their counters
+    pub fn get_universes(&self) -> Vec<UniverseStatus> {
+        self.universes.list()
+    }
+
+    /// Add or replace a scan universe (picked up by the next scan)
+    pub fn upsert_universe(&self, universe: Universe) -> Result<Universe, EngineError> {
+        self.universes.upsert(universe).map_err(EngineError::Config)
+    }
+
+    /// Drop a scan universe; false if it wasn't defined
+    pub fn remove_universe(&self, name: &str) -> bool {
+        self.universes.remove(name)
+    }
+
+    /// Profitable opportunities for several base currencies from one shared
+    /// traversal, keyed by base
+    pub async fn scan_batch(&self, base_currencies: Option<Vec<String>>) -> Result<HashMap<String, Vec<Opportunity>>, EngineError> {
+        let (scanner, bases) = self.adhoc_scanner(base_currencies).await?;
+        tokio::task::spawn_blocking(move || scanner.scan_batch(&bases))
+            .await
+            .map_err(|e| EngineError::Execution(e.to_string()))
+    }
+
+    /// Price one path against the current books with `amount` of its start
+    /// currency (the configured trade amount's equivalent unless given), and
+    /// list what would stop the HFT loop executing it
+    pub async fn inspect_path(&self, path: &str, amount: Option<f64>) -> Result<PathInspection, EngineError> {
+        let start = path.split('→').next().unwrap_or_default().trim().to_uppercase();
+        let (scanner, _) = self.adhoc_scanner(Some(vec![start.clone()])).await?;
+        let amount = match amount {
+            Some(amount) => amount,
+            None => {
+                let config = self.db.get_config().await.map_err(|e| EngineError::Database(e.to_string()))?;
+                let usd_rate = self.valuator.usd_rate(&start).map(|usd| usd.rate).unwrap_or(1.0);
+                config.trade_amount.unwrap_or(10.0) / usd_rate
+            }
+        };
+        let report = scanner.inspect_path(path, amount).map_err(EngineError::Config)?;
+
+        let mut guards = Vec::new();
+        if self.safe_mode.is_active() {
+            guards.push("safe mode is on".to_string());
+        }
+        let throttle = self.get_throttle();
+        if throttle.engaged {
+            guards.push(format!("performance throttle engaged ({}s left)", throttle.remaining_secs));
+        }
+        let pairs: Vec<&str> = report.legs.iter().filter_map(|l| l.pair.as_deref()).collect();
+        for cooldown in self.get_cooldowns().await {
+            let applies = match cooldown.scope {
+                CooldownScope::Global => Some("global"),
+                CooldownScope::Path => (cooldown.key == report.path).then_some("path"),
+                CooldownScope::Pair => pairs.contains(&cooldown.key.as_str()).then_some("pair"),
+            };
+            if let Some(scope) = applies {
+                guards.push(format!("{} cooldown on {} ({} ms left)", scope, cooldown.key, cooldown.remaining_ms));
+            }
+        }
+        if let Some(ref exec) = *self.execution_engine.read().await {
+            if let Some(pair) = exec.disabled_pair_on(&report.path) {
+                guards.push(format!("{} is scan-only", pair));
+            }
+        }
+
+        let would_execute = report.blocked_by.is_empty() && guards.is_empty();
+        Ok(PathInspection { report, guards, would_execute })
+    }
+
+    /// Scanner built from the live config, as the HFT loop builds it, plus
+    /// the bases to scan (the configured start currencies unless given)
+    async fn adhoc_scanner(&self, base_currencies: Option<Vec<String>>) -> Result<(Scanner, Vec<String>), EngineError> {
+        let config = self.db.get_config().await.map_err(|e| EngineError::Database(e.to_string()))?;
+        let bases: Vec<String> = base_currencies
+            .filter(|b| !b.is_empty())
+            .unwrap_or_else(|| config.start_currency.clone().unwrap_or_default().split(',').map(str::to_string).collect())
+            .iter()
+            .map(|b| b.trim().to_uppercase())
+            .filter(|b| !b.is_empty())
+            .collect();
+        if bases.is_empty() {
+            return Err(EngineError::Config("No base currencies to scan".to_string()));
+        }
+
+        let mut scanner = Scanner::new(Arc::clone(&self.cache), self.config_manager.get_config())
+            .with_stablecoins(StablecoinPolicy::from_env());
+        if let Some(requirement) = leg_liquidity_from_config(&config) {
+            scanner = scanner.with_liquidity(requirement, config.trade_amount.unwrap_or(10.0), Arc::new(AtomicU64::new(0)));
+        }
+        Ok((scanner, bases))
+    }
+
     /// Get event scanner stats (legacy API)
     pub fn get_event_scanner_stats(&self) -> EventScannerStatsApi {
         EventScannerStatsApi {
@@ -429,6 +1467,123 @@ impl TradingEngine {
         }
     }
 
+    /// Event scanner stats plus the scan timing breakdown (if profiling ran)
+    pub fn get_event_scanner_stats_detailed(&self) -> EventScannerStatsDetailed {
+        EventScannerStatsDetailed {
+            stats: self.get_event_scanner_stats(),
+            profile: self.scan_profiler.snapshot(),
+        }
+    }
+
+    /// Turn scan profiling on/off, optionally clearing what was collected
+    pub fn set_scanner_profiling(&self, enabled: bool, reset: bool) -> ScanProfile {
+        if reset {
+            self.scan_profiler.reset();
+        }
+        self.scan_profiler.set_enabled(enabled);
+        info!("Scanner profiling {}", if enabled { "enabled" } else { "disabled" });
+        self.scan_profiler.snapshot()
+    }
+
+    /// Opportunity webhook endpoint and delivery counters
+    pub fn get_webhook_stats(&self) -> WebhookStats {
+        self.webhook.stats()
+    }
+
+    /// Switch the opportunity webhook on/off, optionally changing its URL or
+    /// secret (an empty secret turns signing off)
+    pub fn configure_webhook(
+        &self,
+        enabled: bool,
+        url: Option<String>,
+        secret: Option<String>,
+    ) -> Result<WebhookStats, String> {
+        self.webhook.configure(enabled, url, secret)?;
+        Ok(self.webhook.stats())
+    }
+
+    /// Journaled executions for an order (by order_id or cl_ord_id).
+    /// Falls back to the database once the order left the in-memory window.
+    pub async fn get_fills(&self, order_id: &str) -> Result<Vec<OrderFill>, EngineError> {
+        let fills = self.fill_journal.get_fills(order_id);
+        if !fills.is_empty() || !self.fill_journal.is_persisted() {
+            return Ok(fills);
+        }
+        self.db.get_order_fills(order_id).await
+            .map_err(|e| EngineError::Database(e.to_string()))
+    }
+
+    /// Current engine, scanner and executor counters as one history sample
+    pub async fn sample_stats(&self) -> StatsSample {
+        let engine = self.get_stats().await;
+        let hft = self.get_hft_stats().await;
+        let exec = self.get_execution_stats().await;
+        StatsSample {
+            sampled_at: chrono::Utc::now(),
+            is_running: engine.is_running,
+            pairs_monitored: engine.pairs_monitored as i64,
+            cycles_completed: hft.cycles_completed as i64,
+            events_received: hft.events_received as i64,
+            opportunities_found: hft.opportunities_found as i64,
+            paths_filtered_liquidity: hft.skipped_illiquid as i64,
+            trades_executed: hft.trades_executed as i64,
+            trades_successful: hft.trades_successful as i64,
+            trades_failed: hft.trades_failed as i64,
+            total_profit: hft.total_profit,
+            total_loss: hft.total_loss,
+            daily_profit: hft.daily_profit,
+            daily_loss: hft.daily_loss,
+            orders_sent: exec.orders_sent as i64,
+            orders_filled: exec.orders_filled as i64,
+            orders_failed: exec.orders_failed as i64,
+            ws_wire_bytes: engine.ws_traffic.wire_bytes as i64,
+        }
+    }
+
+    /// Stats samples from the last `minutes`, oldest first. Served from memory
+    /// when the ring buffer reaches back far enough, else from the DB
+    /// (when persisted).
+    pub async fn get_stats_history(&self, minutes: u64) -> Result<(StatsHistoryStats, Vec<StatsSample>), EngineError> {
+        let since = chrono::Utc::now() - chrono::Duration::minutes(minutes as i64);
+        let stats = self.stats_history.stats();
+        if self.stats_history.covers(since) || !self.stats_history.is_persisted() {
+            return Ok((stats, self.stats_history.since(since)));
+        }
+        let samples = self.db.get_stats_samples(since).await
+            .map_err(|e| EngineError::Database(e.to_string()))?;
+        Ok((stats, samples))
+    }
+
+    /// Most recently journaled orders with their latest state
+    pub fn get_fill_orders(&self, limit: usize) -> (FillJournalStats, Vec<FillOrderSummary>) {
+        (self.fill_journal.stats(), self.fill_journal.recent_orders(limit))
+    }
+
+    /// Pricing source used when none is asked for (VALUATION_PRICING)
+    pub fn pricing_source(&self) -> PricingSource {
+        self.valuator.source()
+    }
+
+    /// USD value of a holding (configured pricing source unless given)
+    pub fn value_holding(&self, currency: &str, amount: f64, source: Option<PricingSource>) -> Valuation {
+        self.valuator.value_with(currency, amount, source.unwrap_or_else(|| self.valuator.source()))
+    }
+
+    /// P&L of a trade made in `currency`, converted into the reporting
+    /// currency at the current rate
+    pub fn report_pnl(&self, currency: &str, amount: f64) -> ReportedPnl {
+        self.valuator.report_pnl(currency, amount, &valuation::reporting_currency_from_env())
+    }
+
+    /// Positions from Kraken with their USD value filled in
+    pub async fn get_valued_positions(&self, source: Option<PricingSource>) -> Result<Vec<Position>, EngineError> {
+        let mut positions = self.get_positions().await?;
+        for position in &mut positions {
+            position.usd_value = self.value_holding(&position.currency, position.balance, source).usd_value;
+        }
+        Ok(positions)
+    }
+
     /// Get positions from Kraken
     pub async fn get_positions(&self) -> Result<Vec<Position>, EngineError> {
         let auth = match &self.auth {
@@ -477,7 +1632,7 @@ impl TradingEngine {
                 }
 
                 positions.push(Position {
-                    currency: self.normalize_currency(currency),
+                    currency: normalize_currency(currency),
                     balance: balance_f64,
                     usd_value: None,
                 });
@@ -487,19 +1642,6 @@ impl TradingEngine {
         Ok(positions)
     }
 
-    fn normalize_currency(&self, currency: &str) -> String {
-        match currency {
-            "XXBT" | "XBT" => "BTC".to_string(),
-            "XETH" => "ETH".to_string(),
-            "ZUSD" => "USD".to_string(),
-            "ZEUR" => "EUR".to_string(),
-            "ZGBP" => "GBP".to_string(),
-            "ZJPY" => "JPY".to_string(),
-            "ZCAD" => "CAD".to_string(),
-            other => other.to_string(),
-        }
-    }
-
     /// Get trade balance from Kraken (total portfolio value in USD)
     /// Uses /0/private/TradeBalance endpoint which returns "eb" (equivalent balance)
     pub async fn get_trade_balance(&self) -> Result<f64, EngineError> {
@@ -589,6 +1731,21 @@ impl TradingEngine {
         self.cache.get_price(pair).map(|edge| (edge.bid + edge.ask) / 2.0)
     }
 
+    /// Get the top `depth` levels of a pair's cached book (None if no data yet)
+    pub fn get_order_book(&self, pair: &str, depth: usize) -> Option<OrderBookLadder> {
+        let book = self.cache.get_order_book(pair)?;
+        Some(OrderBookLadder {
+            bid_levels: book.bids.len(),
+            ask_levels: book.asks.len(),
+            staleness_ms: book.staleness_ms(),
+            last_update: book.last_update.to_rfc3339(),
+            sequence: book.sequence,
+            bids: book.bids.into_iter().take(depth).collect(),
+            asks: book.asks.into_iter().take(depth).collect(),
+            pair: book.pair,
+        })
+    }
+
     /// Get currencies
     pub fn get_currencies(&self) -> Vec<String> {
         self.cache.get_currencies().into_iter().collect()
@@ -599,6 +1756,54 @@ impl TradingEngine {
         self.cache.get_all_pairs()
     }
 
+    /// AssetPairs metadata of every registered pair
+    pub fn get_pair_metadata(&self) -> Vec<PairMeta> {
+        self.cache.pair_registry().all()
+    }
+
+    /// Registered pair that trades `from` into `to`
+    pub fn find_pair(&self, from: &str, to: &str) -> Option<PairRoute> {
+        self.cache.find_pair(from, to)
+    }
+
+    /// Force a fresh book snapshot for one pair (unsubscribe + resubscribe)
+    pub async fn resubscribe_pair(&self, pair: &str) -> Result<(), EngineError> {
+        let ws = self.websocket.read().await;
+        let ws = ws.as_ref().ok_or(EngineError::NotInitialized)?;
+        ws.resubscribe_pair(pair).map_err(EngineError::WebSocket)
+    }
+
+    /// Pairs on the live public subscription
+    pub async fn get_active_subscriptions(&self) -> Result<Vec<ActiveSubscription>, EngineError> {
+        let ws = self.websocket.read().await;
+        let ws = ws.as_ref().ok_or(EngineError::NotInitialized)?;
+        Ok(ws.get_active_subscriptions())
+    }
+
+    /// Add a pair to the live subscription without reconnecting.
+    /// Candidates not picked at start are registered first.
+    pub async fn subscribe_pair(&self, pair: &str) -> Result<(), EngineError> {
+        let mut ws = self.websocket.write().await;
+        let ws = ws.as_mut().ok_or(EngineError::NotInitialized)?;
+        if !ws.is_running() {
+            return Err(EngineError::WebSocket("WebSocket not running".to_string()));
+        }
+        if self.cache.get_pair_info(pair).is_none() {
+            let candidate = self.unselected_pairs.write().remove(pair)
+                .ok_or_else(|| EngineError::WebSocket(format!("Pair {} is not a candidate pair", pair)))?;
+            self.valuator.record_last_trade(&candidate.pair_name, candidate.last_price);
+            ws.register_pair(&candidate);
+        }
+        ws.subscribe_pair(pair).map_err(EngineError::WebSocket)
+    }
+
+    /// Drop a pair from the live subscription without reconnecting
+    pub async fn unsubscribe_pair(&self, pair: &str) -> Result<(), EngineError> {
+        let ws = self.websocket.read().await;
+        let ws = ws.as_ref().ok_or(EngineError::NotInitialized)?;
+        ws.unsubscribe_pair(pair).map_err(EngineError::WebSocket)
+    }
+
     /// Fetch fees from Kraken
     pub async fn fetch_kraken_fees(&self) -> Result<serde_json::Value, String> {
         let auth = self.auth.as_ref()
@@ -679,9 +1884,139 @@ impl TradingEngine {
         OrderBookHealth::default()
     }
 
-    /// Get cached opportunities (empty for HFT - we execute immediately)
+    /// Restricted currencies and the pairs/edges they keep out of scanning
+    pub fn get_restricted_graph(&self) -> RestrictedGraph {
+        self.cache.restricted_graph()
+    }
+
+    /// Rolling spread/update-rate/gap quality per pair, best first
+    pub fn get_pair_quality(&self) -> Vec<PairQuality> {
+        self.cache.get_all_quality()
+    }
+
+    /// Imbalance and microprice per pair, for strategy research
+    pub fn get_book_signals(&self) -> Vec<BookSignals> {
+        self.cache.get_all_signals()
+    }
+
+    /// Last `limit` trade prints of `pair` with its realized volatility
+    /// (None for a pair that isn't registered)
+    pub fn get_trade_feed(&self, pair: &str, limit: usize) -> Option<PairTradeFeed> {
+        self.cache.get_pair_info(pair)?;
+        Some(self.trade_feed.pair_feed(pair, limit))
+    }
+
+    /// Book copy and level buffer allocation counters
+    pub fn get_book_allocations(&self) -> AllocationStats {
+        self.cache.allocation_stats()
+    }
+
+    /// Get the latest external index comparison (anomalous pairs, source errors)
+    pub fn get_index_report(&self) -> IndexReport {
+        self.index_prices.get_report()
+    }
+
+    /// Add a secondary price feed to the index check
+    pub fn add_index_source(&self, source: Arc<dyn IndexPriceSource>) {
+        self.index_prices.add_source(source);
+    }
+
+    /// Get latest price consistency report (flagged pairs, per-currency scores)
+    pub fn get_consistency_report(&self) -> ConsistencyReport {
+        self.consistency.get_report()
+    }
+
+    /// Get recently found opportunities whose books haven't moved since
     pub fn get_cached_opportunities(&self) -> Vec<Opportunity> {
-        Vec::new()
+        self.get_cached_opportunities_with_age()
+            .into_iter()
+            .map(|o| o.opportunity)
+            .collect()
+    }
+
+    /// Same as get_cached_opportunities, with age and price drift per opportunity
+    pub fn get_cached_opportunities_with_age(&self) -> Vec<OpportunityWithAge> {
+        self.opportunities.get_with_age(&self.cache)
+    }
+
+    /// Cached opportunities dropped because a leg's price moved
+    pub fn opportunities_invalidated(&self) -> u64 {
+        self.opportunities.invalidated()
+    }
+
+    /// Which finds are cached and sent to the webhook, with pass/filter counts
+    pub fn get_opportunity_filter(&self) -> FilterStatus {
+        self.opportunities.filter().status()
+    }
+
+    /// Replace the surfaced-opportunity filter (execution is unaffected)
+    pub fn set_opportunity_filter(&self, config: FilterConfig) -> Result<FilterStatus, String> {
+        let config = self.opportunities.filter().set(config)?;
+        info!("Opportunity filter set: {:?}", config);
+        Ok(self.opportunities.filter().status())
+    }
+
+    /// Spawn the watchdog: while the engine runs, an event path task that
+    /// stopped or stopped beating (see `TaskSupervisor::dead_tasks`) gets the
+    /// whole path rebuilt - socket, channels, forwarder and HFT loop - as
+    /// the pieces share channels. Rebuilds are counted in stats and raised
+    /// as notifications; a task out of rebuilds is reported once and left.
+    pub fn start_watchdog(self: &Arc<Self>) {
+        let policy = self.supervisor.watchdog_policy().clone();
+        if !policy.enabled() {
+            info!("Task watchdog disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "watchdog", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(policy.check_interval_ms));
+                let mut given_up: HashSet<&'static str> = HashSet::new();
+                loop {
+                    interval.tick().await;
+                    // Not running, or the HFT loop was stopped on purpose
+                    // (breaker): nothing should be beating
+                    if !engine.is_running() || !engine.supervisor.is_watched("hft_loop") {
+                        continue;
+                    }
+                    let (exhausted, dead): (Vec<DeadTask>, Vec<DeadTask>) =
+                        engine.supervisor.dead_tasks(chrono::Utc::now()).into_iter().partition(|d| d.exhausted);
+                    for task in exhausted {
+                        if given_up.insert(task.name) {
+                            engine.notifications.push(
+                                Severity::Critical,
+                                "watchdog",
+                                format!("Task '{}' {} and is out of restarts", task.name, task.reason),
+                                serde_json::json!({ "task": task.name, "reason": task.reason }),
+                            );
+                        }
+                    }
+                    if dead.is_empty() {
+                        continue;
+                    }
+                    engine.rebuild_event_path(&dead).await;
+                }
+            }
+        });
+    }
+
+    async fn rebuild_event_path(&self, dead: &[DeadTask]) {
+        for task in dead {
+            self.supervisor.record_watchdog_restart(task.name);
+        }
+        let tasks: Vec<_> = dead.iter().map(|d| serde_json::json!({ "task": d.name, "reason": d.reason })).collect();
+        let summary = dead.iter().map(|d| format!("{} ({})", d.name, d.reason)).collect::<Vec<_>>().join(", ");
+        let result = self.restart_websocket().await;
+        let (severity, title) = match &result {
+            Ok(()) => (Severity::Warning, format!("Event path rebuilt: {}", summary)),
+            Err(e) => (Severity::Critical, format!("Event path rebuild failed ({}): {}", summary, e)),
+        };
+        self.notifications.push(severity, "watchdog", title, serde_json::json!({
+            "tasks": tasks,
+            "rebuilt": result.is_ok(),
+            "watchdog_restarts": self.supervisor.watchdog_restarts(),
+        }));
     }
 
     /// Restart WebSocket
@@ -717,16 +2052,817 @@ impl TradingEngine {
         self.is_running.load(Ordering::Relaxed)
     }
 
+    // ==========================================
+    // Dead-Man's Switch
+    // ==========================================
+
+    /// Record an operator heartbeat
+    pub fn heartbeat(&self) -> DeadManStatus {
+        self.dead_man.heartbeat();
+        self.dead_man.status()
+    }
+
+    pub fn configure_dead_man_switch(&self, enabled: bool, timeout_secs: Option<u64>) -> Result<DeadManStatus, String> {
+        self.dead_man.configure(enabled, timeout_secs)?;
+        info!("Dead-man's switch {} (timeout {}s)",
+            if enabled { "enabled" } else { "disabled" }, self.dead_man.status().timeout_secs);
+        Ok(self.dead_man.status())
+    }
+
+    pub fn get_dead_man_status(&self) -> DeadManStatus {
+        self.dead_man.status()
+    }
+
+    /// Audit log shared by the executor, HFT loop and API handlers
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Spawn the watcher that halts trading when heartbeats stop
+    pub fn start_dead_man_watch(self: &Arc<Self>) {
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "dead_man_watch", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(WATCH_INTERVAL_MS));
+                loop {
+                    interval.tick().await;
+                    if !engine.is_running() {
+                        continue;
+                    }
+                    if let Some(reason) = engine.dead_man.check() {
+                        engine.halt_for_dead_man(&reason).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Spawn the stats history sampler (STATS_SAMPLE_SECS, 0 = off). Runs
+    /// while the engine is stopped too, so the series has no gaps.
+    pub fn start_stats_history(self: &Arc<Self>) {
+        let every = self.stats_history.policy().sample_secs;
+        if every == 0 {
+            info!("Stats history disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "stats_history", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every));
+                loop {
+                    interval.tick().await;
+                    let sample = engine.sample_stats().await;
+                    engine.stats_history.record(sample);
+                }
+            }
+        });
+    }
+
+    /// Spawn the task that resets the daily counters at midnight in the
+    /// configured `daily_reset_timezone` (catching up a missed rollover)
+    pub fn start_daily_reset(self: &Arc<Self>) {
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "daily_reset", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(trading_day::CHECK_INTERVAL_SECS));
+                loop {
+                    interval.tick().await;
+                    engine.roll_daily_if_due().await;
+                }
+            }
+        });
+    }
+
+    /// Reset the daily counters when the trading day has changed since the
+    /// last reset. Returns the schedule after a rollover.
+    pub async fn roll_daily_if_due(&self) -> Option<DailyResetStatus> {
+        let (config, state) = match (self.db.get_config().await, self.db.get_state().await) {
+            (Ok(config), Ok(state)) => (config, state),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Daily reset check failed: {}", e);
+                return None;
+            }
+        };
+        let tz = trading_day::timezone_or_utc(config.daily_reset_timezone.as_deref());
+        let now = chrono::Utc::now();
+        if !trading_day::needs_reset(tz, state.last_daily_reset, now) {
+            return None;
+        }
+
+        let state = match self.db.reset_daily_stats().await {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("Daily reset failed: {}", e);
+                return None;
+            }
+        };
+        self.reset_daily_stats().await;
+
+        let status = DailyResetStatus::new(tz, state.last_daily_reset, now);
+        info!("Daily counters rolled over for {} ({}), next reset at {}", status.trading_day, status.timezone, status.next_reset);
+        self.audit.record(AuditActor::System, AuditCategory::Breaker, "daily_reset", serde_json::json!({
+            "timezone": status.timezone,
+            "trading_day": status.trading_day,
+            "next_reset": status.next_reset,
+        }));
+        Some(status)
+    }
+
+    /// Timezone, current trading day and next rollover of the daily counters
+    pub async fn get_daily_reset(&self) -> Result<DailyResetStatus, EngineError> {
+        let config = self.db.get_config().await.map_err(|e| EngineError::Database(e.to_string()))?;
+        let state = self.db.get_state().await.map_err(|e| EngineError::Database(e.to_string()))?;
+        let tz = trading_day::timezone_or_utc(config.daily_reset_timezone.as_deref());
+        Ok(DailyResetStatus::new(tz, state.last_daily_reset, chrono::Utc::now()))
+    }
+
+    /// Spawn the periodic reconciliation against Kraken (RECONCILE_INTERVAL_SECS, 0 = off)
+    pub fn start_reconciliation(self: &Arc<Self>) {
+        let every = self.reconciler.interval_secs();
+        if every == 0 {
+            info!("Exchange reconciliation disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "reconciliation", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every));
+                interval.tick().await; // first tick fires immediately
+                loop {
+                    interval.tick().await;
+                    if !engine.is_running() || !engine.auth.as_ref().is_some_and(|a| a.is_configured()) {
+                        continue;
+                    }
+                    engine.reconcile_now().await;
+                }
+            }
+        });
+    }
+
+    /// Compare engine bookkeeping with Kraken's open orders, recent trades
+    /// and balances. New discrepancies are pushed to notifications.
+    pub async fn reconcile_now(&self) -> ReconciliationReport {
+        let started = Instant::now();
+        let internal = self.internal_order_state().await;
+        let exchange = self.exchange_order_state().await;
+
+        let mut report = ReconciliationReport {
+            ran_at: chrono::Utc::now(),
+            duration_ms: 0,
+            exchange_open_orders: 0,
+            exchange_trades: 0,
+            internal_open_orders: internal.open_orders.len(),
+            discrepancies: Vec::new(),
+            new_discrepancies: 0,
+            error: None,
+        };
+        match exchange {
+            Ok(exchange) => {
+                report.exchange_open_orders = exchange.open_orders.len();
+                report.exchange_trades = exchange.trades.len();
+                report.discrepancies = compare(&internal, &exchange, self.reconciler.balance_tolerance_pct(), report.ran_at);
+
+                // The exchange is authoritative: correct the running snapshot
+                if internal.balances.is_some() {
+                    if let Some(ref hft) = *self.hft_loop.read().await {
+                        hft.set_balances(exchange.balances).await;
+                    }
+                }
+            }
+            Err(e) => report.error = Some(e.to_string()),
+        }
+        report.duration_ms = started.elapsed().as_millis() as u64;
+
+        for discrepancy in self.reconciler.record(&mut report) {
+            self.notifications.push(
+                Severity::Warning,
+                "reconciliation",
+                discrepancy.title(),
+                serde_json::to_value(&discrepancy).unwrap_or_default(),
+            );
+        }
+        if let Some(ref error) = report.error {
+            warn!("Reconciliation skipped: {}", error);
+        }
+        report
+    }
+
+    /// Orders the executor is waiting on, plus everything the fill journal
+    /// has seen, and the HFT loop's balance snapshot
+    async fn internal_order_state(&self) -> InternalState {
+        let now = chrono::Utc::now();
+        let mut internal = InternalState::default();
+
+        for order in self.fill_journal.recent_orders(usize::MAX) {
+            internal.known_ids.insert(order.order_id.clone());
+            internal.known_ids.extend(order.cl_ord_id.clone());
+            if !is_terminal(&order.last_status) && (now - order.last_at).num_milliseconds() > GRACE_MS {
+                internal.open_orders.push(InternalOrder {
+                    order_id: Some(order.order_id),
+                    cl_ord_id: order.cl_ord_id,
+                    status: order.last_status,
+                    source: "journal",
+                });
+            }
+        }
+
+        if let Some(ref hft) = *self.hft_loop.read().await {
+            for (client_id, age) in hft.pending_orders().await {
+                internal.known_ids.insert(client_id.clone());
+                let journaled = internal.open_orders.iter().any(|o| o.cl_ord_id.as_deref() == Some(client_id.as_str()));
+                if !journaled && age.as_millis() as i64 > GRACE_MS {
+                    internal.open_orders.push(InternalOrder {
+                        order_id: None,
+                        cl_ord_id: Some(client_id),
+                        status: "pending".to_string(),
+                        source: "pending",
+                    });
+                }
+            }
+            internal.balances = hft.get_balances().await;
+        }
+        internal
+    }
+
+    /// Kraken's open orders, trades since the engine started (at most the
+    /// last hour, matching what the journal still remembers) and balances
+    async fn exchange_order_state(&self) -> Result<ExchangeState, EngineError> {
+        let open = self.private_request("/0/private/OpenOrders", "").await?;
+        let started_secs = self.start_time.read().await.map(|t| t.elapsed().as_secs()).unwrap_or(0);
+        let trades = if started_secs > 0 {
+            let since = chrono::Utc::now().timestamp() - started_secs.min(3_600) as i64;
+            parse_trades(&self.private_request("/0/private/TradesHistory", &format!("start={}", since)).await?)
+        } else {
+            Vec::new()
+        };
+        let balances = self.get_positions().await?
+            .into_iter()
+            .map(|p| (p.currency, p.balance))
+            .collect();
+
+        Ok(ExchangeState { open_orders: parse_open_orders(&open), trades, balances })
+    }
+
+    /// Last reconciliation run and settings
+    pub fn get_reconciliation(&self) -> ReconciliationStatus {
+        self.reconciler.status()
+    }
+
+    /// Recent notifications, newest first
+    pub fn get_notifications(&self, since_id: Option<u64>, limit: usize) -> Vec<Notification> {
+        self.notifications.recent(since_id, limit)
+    }
+
+    /// Signed POST to a Kraken private REST endpoint; returns its `result`
+    async fn private_request(&self, path: &str, params: &str) -> Result<serde_json::Value, EngineError> {
+        let auth = match &self.auth {
+            Some(a) if a.is_configured() => a,
+            _ => return Err(EngineError::Auth("Kraken API credentials not configured".to_string())),
+        };
+
+        // Use shared nonce from KrakenAuth to prevent conflicts with other API calls
+        let nonce = auth.next_nonce();
+        let post_data = if params.is_empty() {
+            format!("nonce={}", nonce)
+        } else {
+            format!("nonce={}&{}", nonce, params)
+        };
+        let signature = auth.sign_request(path, nonce, &post_data)
+            .map_err(|e| EngineError::Auth(format!("Failed to sign: {}", e)))?;
+
+        let response = reqwest::Client::new()
+            .post(format!("https://api.kraken.com{}", path))
+            .header("API-Key", auth.api_key())
+            .header("API-Sign", signature)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(post_data)
+            .send()
+            .await
+            .map_err(|e| EngineError::Execution(format!("Request failed: {}", e)))?;
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| EngineError::Execution(format!("Parse failed: {}", e)))?;
+
+        if let Some(error) = json.get("error").and_then(|e| e.as_array()) {
+            if !error.is_empty() {
+                return Err(EngineError::Execution(format!("API error: {:?}", error)));
+            }
+        }
+        Ok(json.get("result").cloned().unwrap_or_default())
+    }
+
+    /// Spawn the task that keeps the HFT loop's balance snapshot current,
+    /// so configured reserves are checked against real balances
+    pub fn start_balance_refresh(self: &Arc<Self>) {
+        let engine = Arc::clone(self);
+        let every = std::env::var("BALANCE_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_BALANCE_REFRESH_SECS);
+        self.supervisor.supervise(None, "balance_refresh", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every.max(1)));
+                loop {
+                    interval.tick().await;
+                    if !engine.is_running() {
+                        continue;
+                    }
+                    let hft_guard = engine.hft_loop.read().await;
+                    let hft = match hft_guard.as_ref() {
+                        Some(hft) => hft,
+                        None => continue,
+                    };
+                    if !hft.has_reserves().await {
+                        continue;
+                    }
+                    match engine.get_positions().await {
+                        Ok(positions) => {
+                            hft.set_balances(positions.into_iter().map(|p| (p.currency, p.balance)).collect()).await;
+                        }
+                        Err(e) => warn!("Balance refresh failed: {}", e),
+                    }
+                }
+            }
+        });
+    }
+
+    /// Re-query the fee tier every FEE_REFRESH_MINS while running, so a
+    /// 30-day volume crossing a tier mid-session takes effect without a restart
+    pub fn start_fee_refresh(self: &Arc<Self>) {
+        let every = std::env::var("FEE_REFRESH_MINS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_FEE_REFRESH_MINS);
+        if every == 0 {
+            info!("Fee tier refresh disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "fee_refresh", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every * 60));
+                interval.tick().await; // start() already fetched fees
+                loop {
+                    interval.tick().await;
+                    if !engine.is_running() || !engine.auth.as_ref().is_some_and(|a| a.is_configured()) {
+                        continue;
+                    }
+                    if let Err(e) = engine.refresh_fees().await {
+                        warn!("Fee tier refresh failed: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Check AssetPairs every PAIR_REFRESH_MINS while running and follow
+    /// listings and delistings without a restart
+    pub fn start_pair_refresh(self: &Arc<Self>) {
+        let every = std::env::var("PAIR_REFRESH_MINS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_PAIR_REFRESH_MINS);
+        if every == 0 {
+            info!("Pair set refresh disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "pair_refresh", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every * 60));
+                loop {
+                    interval.tick().await;
+                    if !engine.is_running() {
+                        continue;
+                    }
+                    if let Err(e) = engine.refresh_pair_set().await {
+                        warn!("Pair set refresh failed: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Compare AssetPairs with the registered pairs: delisted and halted
+    /// pairs are unsubscribed and unregistered (the graph rebuilds without
+    /// them), newly listed pairs that pass the start filters become
+    /// candidates and are subscribed while there is room under max_pairs
+    pub async fn refresh_pair_set(&self) -> Result<PairSetDiff, EngineError> {
+        let config = self.pair_refresh.selection().ok_or(EngineError::NotInitialized)?;
+        let max_pairs = config.get_max_pairs().map_err(EngineError::Config)?;
+        let selector = KrakenPairSelector::new(config);
+        let listing = selector.fetch_listing().await
+            .map_err(|e| EngineError::WebSocket(format!("AssetPairs refresh failed: {}", e)))?;
+        let mut diff = self.pair_refresh.compare(&self.cache.pair_registry().all(), &listing);
+
+        let online: HashSet<&str> = listing.iter().filter(|l| l.is_online()).map(|l| l.pair_name.as_str()).collect();
+        self.unselected_pairs.write().retain(|pair, _| online.contains(pair.as_str()));
+
+        let subscribed = {
+            let mut ws = self.websocket.write().await;
+            let ws = ws.as_mut().ok_or(EngineError::NotInitialized)?;
+            let subscribed: HashSet<String> = ws.get_active_subscriptions().into_iter().map(|s| s.pair).collect();
+            for delisted in &diff.delisted {
+                if subscribed.contains(&delisted.pair) {
+                    diff.unsubscribed.push(delisted.pair.clone());
+                }
+                ws.delist_pair(&delisted.pair);
+            }
+            subscribed.len() - diff.unsubscribed.len()
+        };
+
+        if !diff.listed.is_empty() {
+            let candidates = selector.select_candidates().await
+                .map_err(|e| EngineError::WebSocket(format!("Pair selection failed: {}", e)))?;
+            let new: Vec<SelectedPair> = candidates.into_iter().filter(|c| diff.listed.contains(&c.pair_name)).collect();
+            diff.candidates = new.iter().map(|c| c.pair_name.clone()).collect();
+            let mut room = max_pairs.saturating_sub(subscribed);
+            for candidate in new {
+                let pair = candidate.pair_name.clone();
+                self.unselected_pairs.write().insert(pair.clone(), candidate);
+                if room == 0 {
+                    continue;
+                }
+                match self.subscribe_pair(&pair).await {
+                    Ok(()) => {
+                        diff.subscribed.push(pair);
+                        room -= 1;
+                    }
+                    Err(e) => warn!("Could not subscribe newly listed {}: {}", pair, e),
+                }
+            }
+        }
+
+        if !diff.is_empty() {
+            let severity = if diff.delisted.is_empty() { Severity::Info } else { Severity::Warning };
+            let details = serde_json::to_value(&diff).unwrap_or_default();
+            self.audit.record(AuditActor::System, AuditCategory::Config, "pair_set_changed", details.clone());
+            self.notifications.push(
+                severity,
+                "pairs",
+                format!(
+                    "AssetPairs changed: {} listed ({} subscribed), {} delisted ({} unsubscribed)",
+                    diff.listed.len(), diff.subscribed.len(), diff.delisted.len(), diff.unsubscribed.len()
+                ),
+                details,
+            );
+            self.pair_refresh.record(diff.clone());
+            if let Err(e) = self.apply_depth_tiers().await {
+                warn!("Depth tiers not applied after pair set change: {}", e);
+            }
+        }
+        Ok(diff)
+    }
+
+    /// Re-evaluate subscription depth tiers every BOOK_DEPTH_TIER_SECS
+    /// (when BOOK_DEPTH_TIERS is set)
+    pub fn start_depth_tiers(self: &Arc<Self>) {
+        let policy = self.depth_tiers.policy();
+        if !policy.enabled() || policy.interval_secs == 0 {
+            info!("Periodic depth tier evaluation disabled");
+            return;
+        }
+        let every = policy.interval_secs;
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "depth_tiers", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every));
+                loop {
+                    interval.tick().await;
+                    if !engine.is_running() {
+                        continue;
+                    }
+                    if let Err(e) = engine.apply_depth_tiers().await {
+                        warn!("Depth tier evaluation failed: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Assign every subscribed pair its tier's depth from the cycles it is
+    /// in now, moving pairs whose tier changed. Returns the pairs moved.
+    pub async fn apply_depth_tiers(&self) -> Result<usize, EngineError> {
+        if !self.depth_tiers.policy().enabled() {
+            return Ok(0);
+        }
+        let ws = self.websocket.read().await;
+        let ws = ws.as_ref().ok_or(EngineError::NotInitialized)?;
+        let registry = self.cache.pair_registry();
+        let metas: Vec<PairMeta> = ws
+            .get_active_subscriptions()
+            .into_iter()
+            .filter_map(|s| registry.get(&s.pair))
+            .collect();
+
+        let mut moved = Vec::new();
+        for assigned in self.depth_tiers.assign(&metas) {
+            match ws.set_pair_depth(&assigned.pair, assigned.depth) {
+                Ok(true) => moved.push(assigned),
+                Ok(false) => {}
+                Err(e) => warn!("Could not move {} to its depth tier: {}", assigned.pair, e),
+            }
+        }
+        if !moved.is_empty() {
+            info!("Moved {} pairs to another depth tier", moved.len());
+            self.depth_tiers.record_moves(moved.len());
+            self.audit.record(AuditActor::System, AuditCategory::Config, "depth_tiers_changed", serde_json::json!({
+                "moved": moved,
+            }));
+        }
+        Ok(moved.len())
+    }
+
+    /// Depth tier policy and how many pairs sit at each depth
+    pub fn get_depth_tiers(&self) -> DepthTierStatus {
+        self.depth_tiers.status()
+    }
+
+    /// Clean up tables past their retention every RETENTION_INTERVAL_SECS,
+    /// whether or not the engine is running
+    pub fn start_retention(self: &Arc<Self>) {
+        let every = self.retention.policy().interval_secs;
+        if every == 0 {
+            info!("Scheduled retention cleanup disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "retention", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every));
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = engine.run_cleanup(AuditActor::System, "scheduled").await {
+                        warn!("Retention cleanup skipped: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Archive and delete rows past their retention now
+    pub async fn run_cleanup(&self, actor: AuditActor, trigger: &'static str) -> Result<CleanupReport, String> {
+        let report = self.retention.run(&self.db, trigger).await?;
+        if report.deleted() > 0 || actor == AuditActor::Api {
+            self.audit.record(actor, AuditCategory::Config, "retention_cleanup", serde_json::json!({
+                "trigger": trigger,
+                "tables": report.tables,
+            }));
+        }
+        for failed in report.tables.iter().filter(|t| t.error.is_some()) {
+            self.notifications.push(
+                Severity::Warning,
+                "retention",
+                format!("Cleanup of {} stopped: {}", failed.table.table_name(), failed.error.as_deref().unwrap_or_default()),
+                serde_json::to_value(failed).unwrap_or_default(),
+            );
+        }
+        Ok(report)
+    }
+
+    pub fn get_retention(&self) -> RetentionStatus {
+        self.retention.status()
+    }
+
+    /// Compare the DB and HFT loop breakers every BREAKER_SYNC_SECS and trip
+    /// whichever one isn't
+    pub fn start_breaker_sync(self: &Arc<Self>) {
+        let every = self.breaker_sync.policy().interval_secs;
+        if every == 0 {
+            info!("Circuit breaker reconciliation disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "breaker_sync", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every));
+                loop {
+                    interval.tick().await;
+                    engine.sync_circuit_breaker().await;
+                }
+            }
+        });
+    }
+
+    /// Heal a divergence between the DB and HFT loop breakers (nothing to
+    /// compare while the loop doesn't exist)
+    async fn sync_circuit_breaker(&self) {
+        let _sync = self.breaker_sync.lock().await;
+        let hft_loop = self.hft_loop.read().await;
+        let Some(hft) = hft_loop.as_ref() else { return };
+        let db_state = match self.db.get_state().await {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("Circuit breaker check skipped: {}", e);
+                return;
+            }
+        };
+        self.breaker_sync.record_check();
+
+        let db_reason = db_state.is_circuit_broken
+            .then(|| db_state.circuit_broken_reason.unwrap_or_else(|| "circuit breaker tripped".to_string()));
+        let engine_reason = hft.breaker_reason();
+        let Some(heal) = breaker_sync::reconcile(db_reason.as_deref(), engine_reason.as_deref()) else { return };
+
+        let error = match &heal {
+            BreakerHeal::Engine { reason } => {
+                hft.trip_circuit_breaker(reason);
+                None
+            }
+            BreakerHeal::Db { reason } => self.db.trip_circuit_breaker(reason).await.err().map(|e| e.to_string()),
+        };
+        let divergence = BreakerDivergence { at: chrono::Utc::now(), heal, error };
+        let title = match &divergence.heal {
+            BreakerHeal::Engine { reason } => format!("Circuit breaker tripped in the DB only ({}): stopped the engine", reason),
+            BreakerHeal::Db { reason } => format!("Circuit breaker tripped in the engine only ({}): tripped it in the DB", reason),
+        };
+        let details = serde_json::to_value(&divergence).unwrap_or_default();
+        self.audit.record(AuditActor::System, AuditCategory::Breaker, "breaker_divergence", details.clone());
+        self.notifications.push(Severity::Warning, "breaker_sync", title, details);
+        self.breaker_sync.record_divergence(divergence);
+    }
+
+    pub fn get_breaker_sync(&self) -> BreakerSyncStatus {
+        self.breaker_sync.status()
+    }
+
+    /// Sample event channel drops every minute, shedding the worst pairs
+    /// when a drop rate is sustained (if configured)
+    pub fn start_event_drop_watch(self: &Arc<Self>) {
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "event_drop_watch", move || {
+            let engine = Arc::clone(&engine);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    engine.watch_event_drops().await;
+                }
+            }
+        });
+    }
+
+    async fn watch_event_drops(&self) {
+        let Some(sample) = self.event_drops.sample(chrono::Utc::now()) else { return };
+        let minute = &sample.minute;
+        if minute.dropped > 0 {
+            let top: Vec<String> = minute.top_pairs.iter().map(|p| format!("{} {}", p.pair, p.dropped)).collect();
+            warn!(
+                "Event channel dropped {} of {} book events in the last minute ({:.1}%): {}",
+                minute.dropped, minute.sent + minute.dropped, minute.drop_pct, top.join(", ")
+            );
+        }
+        let Some(escalation) = sample.escalation else { return };
+
+        let mut shed = Vec::new();
+        for pair in &escalation.pairs {
+            match self.unsubscribe_pair(&pair.pair).await {
+                Ok(()) => shed.push(pair.pair.clone()),
+                Err(e) => warn!("Couldn't shed {} after sustained event drops: {}", pair.pair, e),
+            }
+        }
+        let title = format!(
+            "Event channel dropped {:.1}% of events for {} minutes: unsubscribed {}",
+            escalation.drop_pct, escalation.minutes, shed.join(", ")
+        );
+        warn!("{}", title);
+        let details = serde_json::to_value(&escalation).unwrap_or_default();
+        self.audit.record(AuditActor::System, AuditCategory::Guard, "event_drop_escalation", details.clone());
+        self.notifications.push(Severity::Warning, "event_channel", title, details);
+    }
+
+    pub fn get_event_channel_stats(&self) -> EventChannelReport {
+        self.event_drops.report()
+    }
+
+    /// Pair set checks that changed something, newest first
+    pub fn get_pair_refresh_history(&self) -> Vec<PairSetDiff> {
+        self.pair_refresh.history()
+    }
+
+    /// Keep scanning clear of restricted currencies: apply them now, then
+    /// re-read the restrictions file every RESTRICTIONS_RELOAD_SECS and apply
+    /// any change, whether edited on disk or through the API
+    pub fn start_restrictions_watch(self: &Arc<Self>, restrictions: Arc<RestrictionsManager>) {
+        self.apply_restrictions(&restrictions);
+        let every = std::env::var("RESTRICTIONS_RELOAD_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_RESTRICTIONS_RELOAD_SECS);
+        if every == 0 {
+            info!("Restrictions hot reload disabled");
+            return;
+        }
+        let engine = Arc::clone(self);
+        self.supervisor.supervise(None, "restrictions_watch", move || {
+            let engine = Arc::clone(&engine);
+            let restrictions = Arc::clone(&restrictions);
+            async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(every));
+                loop {
+                    interval.tick().await;
+                    match restrictions.reload_if_changed() {
+                        Ok(true) => info!("Restrictions file changed, reloaded"),
+                        Ok(false) => {}
+                        Err(e) => warn!("Failed to reload restrictions: {}", e),
+                    }
+                    engine.apply_restrictions(&restrictions);
+                }
+            }
+        });
+    }
+
+    /// Leave the currently blocked currencies out of every scan graph
+    pub fn apply_restrictions(&self, restrictions: &RestrictionsManager) {
+        if self.cache.set_blocked_currencies(restrictions.blocked_set()) {
+            let restricted = self.cache.restricted_graph();
+            info!(
+                "Restricted currencies {:?} excluded from scanning: {} pairs, {} edges",
+                restricted.currencies, restricted.pairs.len(), restricted.excluded_edges
+            );
+            self.audit.record(AuditActor::System, AuditCategory::Config, "restrictions_applied", serde_json::json!(restricted));
+        }
+    }
+
+    /// Fetch the current fee tier from Kraken and apply it when it changed:
+    /// fee_configuration, then the scanner's fee rate in one config write.
+    /// Manually entered fees are left alone. Returns the stored config when
+    /// the rates changed.
+    pub async fn refresh_fees(&self) -> Result<Option<FeeConfiguration>, String> {
+        let current = self.db.get_fee_configuration().await.map_err(|e| e.to_string())?;
+        if current.fee_source == "manual" {
+            return Ok(None);
+        }
+
+        let fee_data = self.fetch_kraken_fees().await?;
+        let taker_fee = fee_data.get("taker_fee").and_then(|v| v.as_f64()).ok_or("Missing taker fee")?;
+        let maker_fee = fee_data.get("maker_fee").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let volume = fee_data.get("volume_30d").and_then(|v| v.as_str()).and_then(|v| v.parse::<f64>().ok());
+
+        let changed = (current.taker_fee - taker_fee).abs() > 1e-9 || (current.maker_fee - maker_fee).abs() > 1e-9;
+        let stored = self.db.update_fee_from_kraken(maker_fee, taker_fee, None, volume).await
+            .map_err(|e| e.to_string())?;
+        if !changed {
+            return Ok(None);
+        }
+
+        self.config_manager.update_fee_rate(taker_fee, "kraken_api");
+        info!(
+            "Fee tier changed: taker {:.4}% -> {:.4}%, maker {:.4}% -> {:.4}% (30d volume {:?})",
+            current.taker_fee * 100.0, taker_fee * 100.0, current.maker_fee * 100.0, maker_fee * 100.0, volume
+        );
+        self.audit.record(AuditActor::System, AuditCategory::Config, "fee_tier_changed", serde_json::json!({
+            "old_taker_fee": current.taker_fee,
+            "new_taker_fee": taker_fee,
+            "old_maker_fee": current.maker_fee,
+            "new_maker_fee": maker_fee,
+            "thirty_day_volume": volume,
+        }));
+        Ok(Some(stored))
+    }
+
+    /// Block auto-execution (safe mode) and cancel open orders. Streaming and
+    /// scanning keep running; trading resumes once an operator confirms.
+    async fn halt_for_dead_man(&self, reason: &str) {
+        warn!("{} - disabling auto-execution", reason);
+        self.audit.record(AuditActor::System, AuditCategory::Breaker, "dead_man_tripped", serde_json::json!({ "reason": reason }));
+        self.safe_mode.enter(reason);
+
+        if let Some(ref hft) = *self.hft_loop.read().await {
+            if let Err(e) = hft.cancel_open_orders().await {
+                warn!("Failed to cancel open orders: {}", e);
+            }
+        }
+    }
+
     /// Trip circuit breaker
     pub async fn trip_circuit_breaker(&self, reason: &str) {
         warn!("Circuit breaker tripped: {}", reason);
         if let Some(ref hft) = *self.hft_loop.read().await {
+            hft.trip_circuit_breaker(reason);
             hft.stop();
         }
     }
 
     /// Execute a trade manually
-    pub async fn execute_trade(&self, path: &str, amount: f64) -> Result<TradeResult, EngineError> {
+    pub async fn execute_trade(&self, path: &str, amount: f64, tags: Vec<String>) -> Result<TradeResult, EngineError> {
+        if self.safe_mode.is_active() {
+            return Err(EngineError::Execution(
+                "Engine is in safe mode - confirm resume before executing".to_string()
+            ));
+        }
+
         // Get execution engine
         let engine_guard = self.execution_engine.read().await;
         let engine = engine_guard.as_ref()
@@ -745,10 +2881,99 @@ impl TradingEngine {
             fee_rate: 0.0026,
             fee_source: "manual".to_string(),
             legs_detail: Vec::new(),
+            strategy: Strategy::Manual,
+            tags,
+            inverse: None,
         };
 
-        engine.execute_opportunity(&opportunity, amount).await
-            .map_err(|e| EngineError::Execution(e.to_string()))
+        let result = engine.execute_opportunity(&opportunity, amount).await
+            .map_err(|e| match e {
+                ExecutionError::InsufficientFunds { currency, needed, available, needed_usd, available_usd } => {
+                    EngineError::InsufficientFunds { currency, needed, available, needed_usd, available_usd }
+                }
+                e => EngineError::Execution(e.to_string()),
+            })?;
+        drop(engine_guard);
+
+        self.record_manual_trade(&result).await;
+        Ok(result)
+    }
+
+    /// Record a manual trade like an auto-executed one (stats, cooldowns,
+    /// notional, loss limits), tripping the breaker past a loss limit
+    async fn record_manual_trade(&self, result: &TradeResult) {
+        let start_currency = result.path.split(" → ").next().unwrap_or_default();
+        let pnl = self.report_pnl(start_currency, result.profit_amount);
+        let start_rate = self.valuator.usd_rate(start_currency).map(|usd| usd.rate).unwrap_or(1.0);
+        let is_partial = !result.success
+            && result.legs.iter().any(|l| l.success)
+            && result.legs.last().is_some_and(|l| !l.success);
+
+        let record = TradeRecord {
+            trade: NewLiveTrade {
+                trade_id: result.id.clone(),
+                path: result.path.clone(),
+                legs: result.legs.len() as i32,
+                amount_in: result.start_amount,
+                amount_out: Some(result.end_amount),
+                profit_loss: Some(result.profit_amount),
+                profit_loss_pct: Some(result.profit_pct),
+                pnl_currency: Some(pnl.currency.clone()),
+                pnl_reporting: pnl.reporting_amount,
+                reporting_currency: Some(pnl.reporting_currency.clone()),
+                fx_rate: pnl.fx_rate,
+                status: if result.success { "COMPLETED".to_string() } else { "FAILED".to_string() },
+                current_leg: Some(result.legs.len() as i32),
+                error_message: result.error.clone(),
+                held_currency: None,
+                held_amount: None,
+                held_value_usd: None,
+                order_ids: Some(serde_json::json!(result.legs.iter().map(|l| &l.order_id).collect::<Vec<_>>())),
+                leg_fills: Some(serde_json::to_value(&result.legs).unwrap_or_default()),
+                started_at: Some(result.executed_at),
+                completed_at: Some(chrono::Utc::now()),
+                total_execution_ms: Some(result.total_duration_ms as f64),
+                opportunity_profit_pct: None,
+                strategy: Some(result.strategy.as_str().to_string()),
+                tags: result.tags.clone(),
+            },
+            pnl: result.success.then_some(pnl),
+            is_partial,
+            failed_pairs: result.legs.iter().filter(|l| !l.success).map(|l| l.pair.clone()).collect(),
+            notional_usd: filled_notional(&self.valuator, &result.legs, result.start_amount * start_rate),
+        };
+
+        let lifecycle = self.hft_loop.read().await.as_ref().map(|hft| hft.lifecycle());
+        let Some(lifecycle) = lifecycle else {
+            warn!("No HFT loop: manual trade {} saved without guard accounting", result.id);
+            if let Err(e) = self.db.save_trade(&record.trade).await {
+                warn!("Failed to save trade {} to DB: {}", result.id, e);
+            }
+            return;
+        };
+        if let Some(reason) = lifecycle.record(&record).await.loss_limit_breach {
+            self.audit.record(AuditActor::Api, AuditCategory::Breaker, "circuit_breaker_tripped", serde_json::json!({
+                "reason": reason,
+                "trade_id": result.id,
+            }));
+            self.trip_circuit_breaker(&reason).await;
+        }
+    }
+
+    /// Trades the execution engine is in the middle of
+    pub async fn in_flight_trades(&self) -> Vec<InFlightStatus> {
+        match *self.execution_engine.read().await {
+            Some(ref engine) => engine.in_flight_trades(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Abort one in-flight trade (None when no such trade is executing)
+    pub async fn abort_trade(&self, trade_id: &str) -> Result<Option<AbortRequest>, EngineError> {
+        let engine_guard = self.execution_engine.read().await;
+        let engine = engine_guard.as_ref()
+            .ok_or(EngineError::NotInitialized)?;
+        Ok(engine.abort_trade(trade_id).await)
     }
 
     /// Resolve partial trade
@@ -779,4 +3004,62 @@ impl TradingEngine {
         self.db.get_opportunities(limit, None, hours).await
             .map_err(|e| EngineError::Database(e.to_string()))
     }
+
+    /// Replay the last `hours` of recorded opportunities against each
+    /// threshold and cooldown (the configured ones when none are given).
+    /// Also returns whether the history was cut at `max_opportunities`.
+    pub async fn what_if(
+        &self,
+        hours: i64,
+        mut thresholds_pct: Vec<f64>,
+        mut cooldowns: Vec<WhatIfCooldown>,
+        max_opportunities: i64,
+    ) -> Result<(WhatIfReport, bool), EngineError> {
+        let config = self.db.get_config().await.map_err(|e| EngineError::Database(e.to_string()))?;
+        if thresholds_pct.is_empty() {
+            let configured = config.min_profit_threshold
+                .ok_or_else(|| EngineError::Config("No thresholds given and min_profit_threshold isn't configured".to_string()))?;
+            thresholds_pct.push(configured * 100.0);
+        }
+        if cooldowns.is_empty() {
+            let configured = cooldowns_from_config(&config);
+            cooldowns.push(WhatIfCooldown { global_ms: configured.global_ms, path_ms: configured.path_ms });
+        }
+
+        let opportunities = self.db.get_opportunities(max_opportunities, None, hours as i32).await
+            .map_err(|e| EngineError::Database(e.to_string()))?;
+        let truncated = opportunities.len() as i64 == max_opportunities;
+        let report = analytics::what_if(
+            &opportunities,
+            &thresholds_pct,
+            &cooldowns,
+            config.trade_amount,
+            chrono::Duration::hours(hours),
+            chrono::Utc::now(),
+        );
+        Ok((report, truncated))
+    }
+}
+
+/// Last resort for an engine dropped without `close`: nothing can be awaited
+/// here, so in-flight trades aren't drained, but the loop is told to stop and
+/// every task and runtime is torn down instead of left running unowned.
+impl Drop for TradingEngine {
+    fn drop(&mut self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if self.is_running.load(Ordering::SeqCst) {
+            warn!("Trading engine dropped while running; call close() to drain in-flight trades first");
+        }
+        if let Ok(hft_loop) = self.hft_loop.try_read() {
+            if let Some(ref hft_loop) = *hft_loop {
+                hft_loop.stop();
+            }
+        }
+        self.consistency.stop();
+        self.index_prices.stop();
+        self.supervisor.shutdown();
+        self.runtimes.shutdown();
+    }
 }
@@ -6,4 +6,4 @@
 
 mod engine;
 
-pub use engine::TradingEngine;
+pub use engine::{EngineError, TradingEngine};
@@ -0,0 +1,115 @@
+//! Trading Day
+//!
+//! Daily loss/profit counters used to roll over only through
+//! `POST /api/live/reset-daily`, and any notion of "a day" was implicitly UTC.
+//! The trading day now ends at local midnight in `daily_reset_timezone`
+//! (IANA name from the live config, default UTC). A background task resets
+//! the daily counters in the DB and the HFT loop at each rollover, stamps
+//! `last_daily_reset`, and audits it. A reset missed while the server was
+//! down is caught up on the first check after start.
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+/// Seconds between rollover checks
+pub const CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Parse an IANA timezone name (e.g. "America/New_York", "UTC")
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| format!("unknown timezone '{}' (expected an IANA name like America/New_York)", name.trim()))
+}
+
+/// Timezone from the config value, falling back to UTC when unset or invalid
+pub fn timezone_or_utc(name: Option<&str>) -> Tz {
+    name.and_then(|n| parse_timezone(n).ok()).unwrap_or(Tz::UTC)
+}
+
+/// Local date of `now` in `tz`
+pub fn trading_day(tz: Tz, now: DateTime<Utc>) -> NaiveDate {
+    now.with_timezone(&tz).date_naive()
+}
+
+/// Instant the local `day` begins in `tz`. On a DST change at midnight the
+/// day starts at the first local time that exists.
+pub fn day_start(tz: Tz, day: NaiveDate) -> DateTime<Utc> {
+    let midnight = day.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    (0..=3)
+        .find_map(|hour| tz.from_local_datetime(&(midnight + Duration::hours(hour))).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// Next rollover after `now`
+pub fn next_reset(tz: Tz, now: DateTime<Utc>) -> DateTime<Utc> {
+    let today = trading_day(tz, now);
+    day_start(tz, today.succ_opt().unwrap_or(today))
+}
+
+/// Whether the daily counters still belong to an earlier trading day
+pub fn needs_reset(tz: Tz, last_reset: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    let start = day_start(tz, trading_day(tz, now));
+    last_reset.is_none_or(|last| last < start)
+}
+
+/// Rollover schedule for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyResetStatus {
+    pub timezone: String,
+    pub trading_day: NaiveDate,
+    pub last_reset: Option<DateTime<Utc>>,
+    pub next_reset: DateTime<Utc>,
+    pub seconds_until_reset: i64,
+}
+
+impl DailyResetStatus {
+    pub fn new(tz: Tz, last_reset: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Self {
+        let next = next_reset(tz, now);
+        Self {
+            timezone: tz.name().to_string(),
+            trading_day: trading_day(tz, now),
+            last_reset,
+            next_reset: next,
+            seconds_until_reset: (next - now).num_seconds(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_new_york_rollover_across_dst() {
+        let tz = parse_timezone("America/New_York").unwrap();
+        assert!(parse_timezone("Mars/Olympus").is_err());
+        assert_eq!(timezone_or_utc(None), Tz::UTC);
+
+        // 2026-03-08: clocks spring forward at 02:00, the day starts at EST midnight
+        let now = utc("2026-03-08T12:00:00Z");
+        assert_eq!(trading_day(tz, now), NaiveDate::from_ymd_opt(2026, 3, 8).unwrap());
+        assert_eq!(day_start(tz, trading_day(tz, now)), utc("2026-03-08T05:00:00Z"));
+        // ...and the next one at EDT midnight
+        assert_eq!(next_reset(tz, now), utc("2026-03-09T04:00:00Z"));
+
+        // Still the previous New York day at 03:00 UTC
+        assert_eq!(trading_day(tz, utc("2026-03-08T03:00:00Z")), NaiveDate::from_ymd_opt(2026, 3, 7).unwrap());
+
+        assert!(needs_reset(tz, None, now));
+        assert!(needs_reset(tz, Some(utc("2026-03-08T04:59:59Z")), now));
+        assert!(!needs_reset(tz, Some(utc("2026-03-08T05:00:00Z")), now));
+        // UTC midnight has passed but New York's hasn't
+        assert!(!needs_reset(tz, Some(utc("2026-03-09T00:30:00Z")), utc("2026-03-09T03:00:00Z")));
+        assert!(needs_reset(Tz::UTC, Some(utc("2026-03-08T23:59:00Z")), utc("2026-03-09T00:30:00Z")));
+
+        let status = DailyResetStatus::new(tz, Some(utc("2026-03-08T05:00:00Z")), now);
+        assert_eq!(status.timezone, "America/New_York");
+        assert_eq!(status.seconds_until_reset, 16 * 3600);
+    }
+}
@@ -4,6 +4,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // ============================================================================
 // HFT Configuration Constants
@@ -29,6 +30,44 @@ pub struct OrderBookLevel {
     pub qty: f64,
 }
 
+/// What one book update changed. Sent on the event channel in place of the
+/// bare pair name so the HFT loop can skip updates no cycle can see.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookDelta {
+    pub pair: String,
+    /// Full snapshot - treat everything as changed
+    pub snapshot: bool,
+    pub bids_changed: bool,
+    pub asks_changed: bool,
+    /// Levels inserted, resized or removed
+    pub levels_changed: u32,
+    /// Position of the best-placed level touched on either side (0 = top of
+    /// book, usize::MAX = nothing touched)
+    pub shallowest_level: usize,
+    /// Best bid or best ask price changed
+    pub top_moved: bool,
+}
+
+impl BookDelta {
+    pub fn snapshot(pair: &str) -> Self {
+        Self {
+            pair: pair.to_string(),
+            snapshot: true,
+            bids_changed: true,
+            asks_changed: true,
+            levels_changed: 0,
+            shallowest_level: 0,
+            top_moved: true,
+        }
+    }
+
+    /// Whether a scan reading the top `depth_levels` of each book could see
+    /// this update
+    pub fn can_affect_cycles(&self, depth_levels: usize) -> bool {
+        self.snapshot || self.top_moved || self.shallowest_level < depth_levels
+    }
+}
+
 /// Complete order book
 #[derive(Debug, Clone)]
 pub struct OrderBook {
@@ -93,10 +132,67 @@ pub struct PriceEdge {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LegDetail {
     pub pair: String,
+    /// Side traded on `pair`: "buy" or "sell"
     pub action: String,
     pub rate: f64,
 }
 
+/// The same cycle traded the other way round (sides and fees differ)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InverseQuote {
+    pub path: String,
+    pub gross_profit_pct: f64,
+    pub fees_pct: f64,
+    pub net_profit_pct: f64,
+    pub is_profitable: bool,
+}
+
+impl InverseQuote {
+    pub fn of(opp: &Opportunity) -> Self {
+        Self {
+            path: opp.path.clone(),
+            gross_profit_pct: opp.gross_profit_pct,
+            fees_pct: opp.fees_pct,
+            net_profit_pct: opp.net_profit_pct,
+            is_profitable: opp.is_profitable,
+        }
+    }
+}
+
+/// Detection strategy an opportunity (and the resulting trade) is attributed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// 3-leg cycle found by the scanner
+    #[default]
+    Triangular,
+    /// Cycle through more than 3 pairs
+    CrossPair,
+    /// Path submitted by the operator
+    Manual,
+    /// Cycle through fiat and stablecoins only (see stablecoin.rs)
+    Stablecoin,
+    /// Found by a strategy plugin (see strategy_plugin.rs)
+    Plugin,
+}
+
+impl Strategy {
+    /// Strategy for a scanner-found cycle with this many legs
+    pub fn for_legs(legs: usize) -> Self {
+        if legs > 3 { Strategy::CrossPair } else { Strategy::Triangular }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Strategy::Triangular => "triangular",
+            Strategy::CrossPair => "cross_pair",
+            Strategy::Manual => "manual",
+            Strategy::Stablecoin => "stablecoin",
+            Strategy::Plugin => "plugin",
+        }
+    }
+}
+
 /// Arbitrage opportunity
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Opportunity {
@@ -112,6 +208,15 @@ pub struct Opportunity {
     pub fee_rate: f64,
     pub fee_source: String,
     pub legs_detail: Vec<LegDetail>,
+    #[serde(default)]
+    pub strategy: Strategy,
+    /// Free-form labels carried through to the trade record
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The reverse direction, priced when the cycle was found (the better
+    /// of the two is the opportunity itself)
+    #[serde(default)]
+    pub inverse: Option<InverseQuote>,
 }
 
 /// Default opportunity TTL in milliseconds for HFT
@@ -152,6 +257,29 @@ impl Opportunity {
         });
         serde_json::to_string(&snapshot).unwrap_or_else(|_| "{}".to_string())
     }
+
+    /// A fresh find on `path` ("USD → BTC → ETH → USD") netting
+    /// `net_profit_pct` after 3 × 0.26% fees, with no leg detail
+    #[cfg(test)]
+    pub fn for_test(path: &str, net_profit_pct: f64) -> Self {
+        let legs = path.split(" → ").count() - 1;
+        Self {
+            id: path.to_string(),
+            path: path.to_string(),
+            legs,
+            gross_profit_pct: net_profit_pct + 0.78,
+            fees_pct: 0.78,
+            net_profit_pct,
+            is_profitable: net_profit_pct > 0.0,
+            detected_at: Utc::now(),
+            fee_rate: 0.0026,
+            fee_source: "test".to_string(),
+            legs_detail: Vec::new(),
+            strategy: Strategy::for_legs(legs),
+            tags: Vec::new(),
+            inverse: None,
+        }
+    }
 }
 
 /// Slippage calculation result for a single leg
@@ -186,9 +314,86 @@ pub struct EngineStats {
     pub avg_orderbook_staleness_ms: f64,
     pub opportunities_found: u64,
     pub opportunities_per_second: f64,
+    /// Profitable paths dropped because a leg lacked depth
+    pub paths_filtered_liquidity: u64,
+    /// Book updates that didn't trigger a scan (changed only deep levels)
+    pub events_skipped_deep: u64,
     pub uptime_seconds: u64,
     pub scan_cycle_ms: f64,
     pub last_scan_at: String,
+    pub ws_traffic: WsTrafficSnapshot,
+    pub ws_parse: ParseLatencySnapshot,
+    /// A task panicked since the engine last started
+    #[serde(default)]
+    pub degraded: bool,
+    #[serde(default)]
+    pub panics_since_start: u64,
+    /// Event path rebuilds after a task stopped or hung
+    #[serde(default)]
+    pub watchdog_restarts: u64,
+    #[serde(default)]
+    pub cache_memory: CacheMemorySnapshot,
+}
+
+/// WebSocket traffic counters for bandwidth monitoring
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WsTrafficSnapshot {
+    pub text_frames: u64,
+    pub binary_frames: u64,
+    /// Bytes received on the socket
+    pub wire_bytes: u64,
+    /// Bytes handed to the JSON parser
+    pub payload_bytes: u64,
+    pub book_messages: u64,
+    pub book_bytes: u64,
+    pub ticker_messages: u64,
+    pub ticker_bytes: u64,
+}
+
+/// One pair on the live public WebSocket subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActiveSubscription {
+    pub pair: String,
+    /// WebSocket symbol (e.g., "XBT/USD")
+    pub symbol: String,
+    /// Subscribed book depth (0 = ticker only)
+    pub depth: usize,
+    /// Also subscribed to the ticker channel
+    pub ticker: bool,
+    /// Levels currently cached per side
+    pub bid_levels: usize,
+    pub ask_levels: usize,
+}
+
+/// Estimated order book cache footprint against its budget
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheMemorySnapshot {
+    /// Books, levels (by capacity), prices, quality history and pooled copies
+    pub total_bytes: usize,
+    pub book_bytes: usize,
+    pub price_bytes: usize,
+    pub quality_bytes: usize,
+    pub pooled_bytes: usize,
+    /// Books held, against max_books (0 = unbounded)
+    pub books: usize,
+    pub max_books: usize,
+    /// 0 = unbounded
+    pub max_bytes: usize,
+    /// Unsubscribed pairs whose book can still be evicted
+    pub released_pairs: usize,
+    /// Books evicted since start
+    pub evictions: u64,
+}
+
+/// Frame parse time percentiles (microseconds)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParseLatencySnapshot {
+    /// Total frames parsed since start
+    pub samples: u64,
+    pub p50_us: f64,
+    pub p90_us: f64,
+    pub p99_us: f64,
+    pub max_us: f64,
 }
 
 /// Engine configuration
@@ -197,7 +402,7 @@ pub struct EngineStats {
 /// - User input via dashboard (min_profit_threshold)
 /// - Kraken API (fee_rate from fee_configuration table)
 /// - User settings (min_profit_threshold from live_trading_config table)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EngineConfig {
     /// Minimum profit threshold (as decimal, e.g., 0.003 = 0.3%) - from user config
     pub min_profit_threshold: f64,
@@ -205,6 +410,9 @@ pub struct EngineConfig {
     pub fee_rate: f64,
     /// Source of fee data: "kraken_api", "manual", "pending"
     pub fee_source: String,
+    /// Thresholds (decimal) by path length that override min_profit_threshold
+    /// for longer/riskier cycles, e.g. {3: 0.0015, 4: 0.0035}
+    pub leg_thresholds: BTreeMap<usize, f64>,
 }
 
 impl EngineConfig {
@@ -232,6 +440,7 @@ impl EngineConfig {
             min_profit_threshold: min_profit,
             fee_rate: fee,
             fee_source,
+            leg_thresholds: BTreeMap::new(),
         })
     }
 
@@ -242,9 +451,21 @@ impl EngineConfig {
             min_profit_threshold: 0.0,
             fee_rate: 0.0,
             fee_source: "pending".to_string(),
+            leg_thresholds: BTreeMap::new(),
         }
     }
 
+    /// Threshold configured for a path of `legs` legs: the entry for the
+    /// longest configured length not above it (None = use the global one)
+    pub fn leg_threshold(&self, legs: usize) -> Option<f64> {
+        self.leg_thresholds.range(..=legs).next_back().map(|(_, threshold)| *threshold)
+    }
+
+    /// Threshold (decimal) an opportunity with `legs` legs must beat
+    pub fn threshold_for(&self, legs: usize) -> f64 {
+        self.leg_threshold(legs).unwrap_or(self.min_profit_threshold)
+    }
+
     /// Check if this config is valid for starting the engine
     /// Note: min_profit_threshold can be negative (user may want to execute losing trades for testing)
     pub fn is_valid(&self) -> bool {
@@ -284,6 +505,14 @@ pub struct OrderBookHealth {
     pub skipped_stale: u32,
     pub skipped_bad_spread: u32,
     pub skipped_no_price: u32,
+    /// Quarantined by the price consistency monitor
+    pub skipped_inconsistent: u32,
+    /// Rolling quality score under the configured minimum
+    #[serde(default)]
+    pub skipped_low_quality: u32,
+    /// Graph edges left out because a currency on them is restricted
+    #[serde(default)]
+    pub excluded_restricted_edges: u32,
     pub avg_freshness_ms: f64,
     pub avg_spread_pct: f64,
     pub avg_depth: f64,
@@ -0,0 +1,260 @@
+//! Scan Universes
+//!
+//! A universe is a named currency subgraph scanned on its own, e.g.
+//! "majors" (BTC, ETH, SOL, USD, EUR) or "stables" (USD, USDT, USDC, DAI).
+//! Each has its own profit threshold and trigger mode:
+//! - execute: its first qualifying opportunity is handed to execution when
+//!   the main scan found nothing
+//! - observe: opportunities are counted and cached, never traded
+//!
+//! Universes are defined with SCAN_UNIVERSES (a JSON array) and edited at
+//! runtime through the API. Every book update that triggers the main scan
+//! also scans each universe, in parallel, so a universe costs one extra
+//! graph build and DFS per update. None are defined by default.
+#![allow(dead_code)]
+
+use crate::order_book::OrderBookCache;
+use crate::scanner::Scanner;
+use crate::stablecoin::StablecoinPolicy;
+use crate::types::{EngineConfig, Opportunity};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UniverseTrigger {
+    Execute,
+    #[default]
+    Observe,
+}
+
+/// One named subgraph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Universe {
+    pub name: String,
+    pub currencies: Vec<String>,
+    /// Net profit a cycle needs (percent; None = the main threshold)
+    #[serde(default)]
+    pub min_profit_pct: Option<f64>,
+    #[serde(default)]
+    pub trigger: UniverseTrigger,
+    /// Currencies cycles start from (None = the configured base currencies
+    /// that are in the universe)
+    #[serde(default)]
+    pub base_currencies: Option<Vec<String>>,
+}
+
+impl Universe {
+    /// Uppercase the currencies and check the definition
+    pub fn validated(mut self) -> Result<Self, String> {
+        if self.name.is_empty() || !self.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Invalid universe name '{}' (letters, digits, - and _)", self.name));
+        }
+        let mut seen = HashSet::new();
+        self.currencies = self.currencies.iter()
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty() && seen.insert(c.clone()))
+            .collect();
+        if self.currencies.len() < 3 {
+            return Err(format!("Universe '{}' needs at least 3 currencies to form a cycle", self.name));
+        }
+        if let Some(pct) = self.min_profit_pct {
+            if !pct.is_finite() {
+                return Err(format!("Universe '{}' has an invalid min_profit_pct", self.name));
+            }
+        }
+        if let Some(bases) = &mut self.base_currencies {
+            *bases = bases.iter().map(|c| c.trim().to_uppercase()).collect();
+            if let Some(outside) = bases.iter().find(|b| !self.currencies.contains(b)) {
+                return Err(format!("Base currency {} is not in universe '{}'", outside, self.name));
+            }
+        }
+        Ok(self)
+    }
+
+    fn bases(&self, configured: &[String]) -> Vec<String> {
+        match &self.base_currencies {
+            Some(bases) => bases.clone(),
+            None => configured.iter().filter(|b| self.currencies.contains(b)).cloned().collect(),
+        }
+    }
+}
+
+/// Counters for one universe
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UniverseStats {
+    pub scans: u64,
+    pub opportunities: u64,
+    /// Opportunities picked for execution (execute mode only; guards such
+    /// as cooldowns or safe mode may still block them)
+    pub selected: u64,
+    pub best_profit_pct: Option<f64>,
+    pub last_profit_pct: Option<f64>,
+    pub last_opportunity_at: Option<DateTime<Utc>>,
+    pub avg_scan_ms: f64,
+}
+
+/// A universe with its counters, for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct UniverseStatus {
+    #[serde(flatten)]
+    pub universe: Universe,
+    pub stats: UniverseStats,
+}
+
+#[derive(Default)]
+pub struct Universes {
+    defined: RwLock<Vec<Universe>>,
+    stats: RwLock<HashMap<String, UniverseStats>>,
+}
+
+impl Universes {
+    /// Create from SCAN_UNIVERSES, e.g.
+    /// `[{"name":"majors","currencies":["BTC","ETH","SOL","USD","EUR"],"min_profit_pct":0.2,"trigger":"execute"}]`
+    pub fn from_env() -> Self {
+        let universes = Self::default();
+        let Ok(value) = std::env::var("SCAN_UNIVERSES") else {
+            return universes;
+        };
+        match serde_json::from_str::<Vec<Universe>>(&value) {
+            Ok(defined) => {
+                for universe in defined {
+                    if let Err(e) = universes.upsert(universe) {
+                        warn!("Ignoring SCAN_UNIVERSES entry: {}", e);
+                    }
+                }
+            }
+            Err(e) => warn!("Invalid SCAN_UNIVERSES: {}", e),
+        }
+        universes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defined.read().is_empty()
+    }
+
+    /// Add or replace a universe by name (its counters restart)
+    pub fn upsert(&self, universe: Universe) -> Result<Universe, String> {
+        let universe = universe.validated()?;
+        let mut defined = self.defined.write();
+        match defined.iter_mut().find(|u| u.name == universe.name) {
+            Some(existing) => *existing = universe.clone(),
+            None => defined.push(universe.clone()),
+        }
+        self.stats.write().insert(universe.name.clone(), UniverseStats::default());
+        Ok(universe)
+    }
+
+    pub fn remove(&self, name: &str) -> bool {
+        let mut defined = self.defined.write();
+        let before = defined.len();
+        defined.retain(|u| u.name != name);
+        self.stats.write().remove(name);
+        defined.len() != before
+    }
+
+    pub fn list(&self) -> Vec<UniverseStatus> {
+        let stats = self.stats.read();
+        self.defined.read().iter()
+            .map(|u| UniverseStatus {
+                universe: u.clone(),
+                stats: stats.get(&u.name).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Scan every universe in parallel. Returns what each one found (its
+    /// first qualifying cycle, tagged `universe:<name>`) in definition order.
+    pub fn scan(
+        &self,
+        cache: &Arc<OrderBookCache>,
+        config: &EngineConfig,
+        stablecoins: StablecoinPolicy,
+        base_currencies: &[String],
+    ) -> Vec<(UniverseTrigger, Opportunity)> {
+        let defined = self.defined.read().clone();
+        let found: Vec<_> = defined
+            .par_iter()
+            .map(|universe| {
+                let started = Instant::now();
+                let bases = universe.bases(base_currencies);
+                let threshold = universe.min_profit_pct.map_or(config.min_profit_threshold, |pct| pct / 100.0);
+                let scanner = Scanner::new(Arc::clone(cache), config.clone())
+                    .with_stablecoins(stablecoins)
+                    .with_currencies(universe.currencies.iter().cloned().collect());
+                let opportunity = scanner.scan_first(&bases, threshold).map(|mut opp| {
+                    opp.tags.push(format!("universe:{}", universe.name));
+                    (universe.trigger, opp)
+                });
+                (universe.name.clone(), started.elapsed().as_secs_f64() * 1000.0, opportunity)
+            })
+            .collect();
+
+        let mut stats = self.stats.write();
+        found.into_iter()
+            .filter_map(|(name, scan_ms, opportunity)| {
+                let entry = stats.entry(name).or_default();
+                entry.scans += 1;
+                entry.avg_scan_ms += (scan_ms - entry.avg_scan_ms) / entry.scans as f64;
+                let (trigger, opp) = opportunity?;
+                entry.opportunities += 1;
+                entry.last_profit_pct = Some(opp.net_profit_pct);
+                entry.best_profit_pct = Some(entry.best_profit_pct.map_or(opp.net_profit_pct, |b| b.max(opp.net_profit_pct)));
+                entry.last_opportunity_at = Some(Utc::now());
+                Some((trigger, opp))
+            })
+            .collect()
+    }
+
+    /// Count a universe's opportunity being picked for execution
+    pub fn record_selected(&self, opp: &Opportunity) {
+        let Some(name) = opp.tags.iter().find_map(|t| t.strip_prefix("universe:")) else {
+            return;
+        };
+        if let Some(stats) = self.stats.write().get_mut(name) {
+            stats.selected += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn universe(name: &str, currencies: &[&str]) -> Universe {
+        Universe {
+            name: name.to_string(),
+            currencies: currencies.iter().map(|c| c.to_string()).collect(),
+            min_profit_pct: None,
+            trigger: UniverseTrigger::Observe,
+            base_currencies: None,
+        }
+    }
+
+    #[test]
+    fn test_universe_validation_and_bases() {
+        let majors = universe("majors", &["btc", "ETH", "usd", "ETH"]).validated().unwrap();
+        assert_eq!(majors.currencies, vec!["BTC", "ETH", "USD"]);
+        let configured = vec!["USD".to_string(), "EUR".to_string()];
+        assert_eq!(majors.bases(&configured), vec!["USD"]);
+
+        assert!(universe("two", &["BTC", "USD"]).validated().is_err());
+        assert!(universe("bad name", &["BTC", "ETH", "USD"]).validated().is_err());
+        let outside = Universe { base_currencies: Some(vec!["EUR".into()]), ..universe("m", &["BTC", "ETH", "USD"]) };
+        assert!(outside.validated().is_err());
+
+        let universes = Universes::default();
+        universes.upsert(majors.clone()).unwrap();
+        universes.upsert(Universe { trigger: UniverseTrigger::Execute, ..majors }).unwrap();
+        assert_eq!(universes.list().len(), 1);
+        assert_eq!(universes.list()[0].universe.trigger, UniverseTrigger::Execute);
+        assert!(universes.remove("majors"));
+        assert!(universes.is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+//! Position Valuation
+//!
+//! Turns an amount of any currency into USD from the cached books. Used by
+//! the positions API, the partial-trade preview and the HFT loop's exposure
+//! sizing, so all three agree on what a holding is worth.
+//!
+//! Pricing sources:
+//! - bid: what the holding fetches if sold now (conservative: selling the
+//!   base of a pair gets the bid, buying through an inverted pair pays the ask)
+//! - mid: halfway between bid and ask (default)
+//! - last: last traded price, seeded from the ticker at pair selection;
+//!   pairs without one fall back to mid
+//!
+//! Direct USD pairs are used when present. Anything else is converted along
+//! the shortest route through the cached pairs (at most `MAX_HOPS` legs,
+//! e.g. DOT → EUR → USD). Stablecoins without any route are taken at par.
+//!
+//! Trade P&L is kept in the currency the trade started in and converted
+//! into the reporting currency (REPORTING_CURRENCY, default USD) at the rate
+//! snapshotted when the trade completes; loss limits are counted in the
+//! reporting currency so a EUR cycle and a USD cycle add up correctly.
+#![allow(dead_code)]
+
+use crate::order_book::OrderBookCache;
+use crate::types::PriceEdge;
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Longest conversion route searched
+pub const MAX_HOPS: usize = 3;
+
+/// Codes that are USD themselves
+const USD_CODES: [&str; 2] = ["USD", "ZUSD"];
+
+/// Taken at 1 USD when no pair prices them
+const PAR_STABLECOINS: [&str; 2] = ["USDT", "USDC"];
+
+/// Currency P&L and loss limits are reported in unless configured
+pub const DEFAULT_REPORTING_CURRENCY: &str = "USD";
+
+/// REPORTING_CURRENCY (default USD)
+pub fn reporting_currency_from_env() -> String {
+    std::env::var("REPORTING_CURRENCY")
+        .ok()
+        .map(|v| v.trim().to_uppercase())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_REPORTING_CURRENCY.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PricingSource {
+    Bid,
+    Mid,
+    Last,
+}
+
+impl PricingSource {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "bid" => Ok(PricingSource::Bid),
+            "mid" => Ok(PricingSource::Mid),
+            "last" => Ok(PricingSource::Last),
+            other => Err(format!("Unknown pricing source '{}' (expected bid, mid or last)", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PricingSource::Bid => "bid",
+            PricingSource::Mid => "mid",
+            PricingSource::Last => "last",
+        }
+    }
+}
+
+/// USD per unit of a currency and the pairs it was derived from
+#[derive(Debug, Clone, Serialize)]
+pub struct UsdRate {
+    pub rate: f64,
+    /// Pairs walked, in order (empty for USD and par stablecoins)
+    pub route: Vec<String>,
+    pub source: PricingSource,
+}
+
+/// Value of one holding (usd_value None when no route prices it)
+#[derive(Debug, Clone, Serialize)]
+pub struct Valuation {
+    pub currency: String,
+    pub amount: f64,
+    pub usd_value: Option<f64>,
+    pub usd_rate: Option<f64>,
+    pub route: Vec<String>,
+    pub source: PricingSource,
+}
+
+/// A trade's P&L in the currency it was made in and in the reporting
+/// currency, at the rate taken when the trade completed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportedPnl {
+    pub amount: f64,
+    pub currency: String,
+    /// None when nothing priced `currency` at completion
+    pub reporting_amount: Option<f64>,
+    pub reporting_currency: String,
+    /// Reporting currency per unit of `currency`
+    pub fx_rate: Option<f64>,
+}
+
+impl ReportedPnl {
+    /// What counts against loss limits: the conversion, or the amount as-is
+    /// when it couldn't be converted
+    pub fn guard_amount(&self) -> f64 {
+        self.reporting_amount.unwrap_or(self.amount)
+    }
+}
+
+/// One conversion step: through `edge`, selling its base (true) or buying it
+struct Hop {
+    edge: PriceEdge,
+    sell_base: bool,
+}
+
+pub struct Valuator {
+    cache: Arc<OrderBookCache>,
+    source: RwLock<PricingSource>,
+    last_trades: DashMap<String, f64>,
+}
+
+impl Valuator {
+    pub fn new(cache: Arc<OrderBookCache>, source: PricingSource) -> Self {
+        Self {
+            cache,
+            source: RwLock::new(source),
+            last_trades: DashMap::new(),
+        }
+    }
+
+    /// Create from VALUATION_PRICING (bid, mid or last; default mid)
+    pub fn from_env(cache: Arc<OrderBookCache>) -> Self {
+        let source = std::env::var("VALUATION_PRICING")
+            .ok()
+            .and_then(|v| PricingSource::parse(&v).ok())
+            .unwrap_or(PricingSource::Mid);
+        Self::new(cache, source)
+    }
+
+    pub fn source(&self) -> PricingSource {
+        *self.source.read()
+    }
+
+    pub fn set_source(&self, source: PricingSource) {
+        *self.source.write() = source;
+    }
+
+    /// Remember the last traded price of a pair (for PricingSource::Last)
+    pub fn record_last_trade(&self, pair: &str, price: f64) {
+        if price > 0.0 {
+            self.last_trades.insert(pair.to_string(), price);
+        }
+    }
+
+    /// Value `amount` of `currency` with the configured source
+    pub fn value(&self, currency: &str, amount: f64) -> Valuation {
+        self.value_with(currency, amount, self.source())
+    }
+
+    pub fn value_with(&self, currency: &str, amount: f64, source: PricingSource) -> Valuation {
+        let rate = self.usd_rate_with(currency, source);
+        Valuation {
+            currency: currency.to_string(),
+            amount,
+            usd_value: rate.as_ref().map(|r| amount * r.rate),
+            usd_rate: rate.as_ref().map(|r| r.rate),
+            route: rate.map(|r| r.route).unwrap_or_default(),
+            source,
+        }
+    }
+
+    /// USD per unit of `currency` with the configured source
+    pub fn usd_rate(&self, currency: &str) -> Option<UsdRate> {
+        self.usd_rate_with(currency, self.source())
+    }
+
+    pub fn usd_rate_with(&self, currency: &str, source: PricingSource) -> Option<UsdRate> {
+        let currency = currency.to_uppercase();
+        if USD_CODES.contains(&currency.as_str()) {
+            return Some(UsdRate { rate: 1.0, route: Vec::new(), source });
+        }
+
+        let hops = self.direct_route(&currency).or_else(|| self.search_route(&currency));
+        match hops {
+            Some(hops) => {
+                let mut rate = 1.0;
+                for hop in &hops {
+                    rate *= self.hop_rate(hop, source)?;
+                }
+                Some(UsdRate { rate, route: hops.into_iter().map(|h| h.edge.pair).collect(), source })
+            }
+            None if PAR_STABLECOINS.contains(&currency.as_str()) => {
+                Some(UsdRate { rate: 1.0, route: Vec::new(), source })
+            }
+            None => None,
+        }
+    }
+
+    /// Units of `to` per unit of `from` (through USD) with the configured source
+    pub fn fx_rate(&self, from: &str, to: &str) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(1.0);
+        }
+        let from = self.usd_rate(from)?.rate;
+        let to = self.usd_rate(to)?.rate;
+        (to > 0.0).then(|| from / to)
+    }
+
+    /// Snapshot `amount` of `currency` P&L in the reporting currency
+    pub fn report_pnl(&self, currency: &str, amount: f64, reporting_currency: &str) -> ReportedPnl {
+        let fx_rate = self.fx_rate(currency, reporting_currency);
+        ReportedPnl {
+            amount,
+            currency: currency.to_uppercase(),
+            reporting_amount: fx_rate.map(|rate| amount * rate),
+            reporting_currency: reporting_currency.to_uppercase(),
+            fx_rate,
+        }
+    }
+
+    /// Rate of one step: units of the next currency per unit of the current
+    fn hop_rate(&self, hop: &Hop, source: PricingSource) -> Option<f64> {
+        let edge = &hop.edge;
+        let mid = (edge.bid + edge.ask) / 2.0;
+        let price = match source {
+            PricingSource::Bid if hop.sell_base => edge.bid,
+            PricingSource::Bid => edge.ask,
+            PricingSource::Mid => mid,
+            PricingSource::Last => self.last_trades.get(&edge.pair).map(|p| *p).unwrap_or(mid),
+        };
+        if price <= 0.0 {
+            return None;
+        }
+        Some(if hop.sell_base { price } else { 1.0 / price })
+    }
+
+    /// Quoted book on a pair (None without a two-sided price)
+    fn quoted(&self, pair: &str) -> Option<PriceEdge> {
+        self.cache.get_price(pair).filter(|e| e.bid > 0.0 && e.ask > 0.0)
+    }
+
+    fn direct_route(&self, currency: &str) -> Option<Vec<Hop>> {
+        let route = self.cache.find_pair(currency, "USD")?;
+        self.quoted(&route.pair).map(|edge| vec![Hop { edge, sell_base: route.sells_base }])
+    }
+
+    /// Breadth-first search to USD over all quoted pairs (neighbours in pair
+    /// name order, so the same books always give the same route)
+    fn search_route(&self, currency: &str) -> Option<Vec<Hop>> {
+        let prices = self.cache.get_all_prices();
+        let mut edges: Vec<&PriceEdge> = prices.values().filter(|e| e.bid > 0.0 && e.ask > 0.0).collect();
+        edges.sort_by(|a, b| a.pair.cmp(&b.pair));
+
+        let mut adjacency: HashMap<&str, Vec<(&str, &PriceEdge, bool)>> = HashMap::new();
+        for edge in edges {
+            adjacency.entry(edge.base.as_str()).or_default().push((edge.quote.as_str(), edge, true));
+            adjacency.entry(edge.quote.as_str()).or_default().push((edge.base.as_str(), edge, false));
+        }
+
+        let mut previous: HashMap<&str, (&str, &PriceEdge, bool)> = HashMap::new();
+        let mut queue = VecDeque::from([(currency, 0usize)]);
+        while let Some((node, depth)) = queue.pop_front() {
+            if USD_CODES.contains(&node) {
+                let mut hops = Vec::new();
+                let mut at = node;
+                while let Some((from, edge, sell_base)) = previous.get(at) {
+                    hops.push(Hop { edge: (*edge).clone(), sell_base: *sell_base });
+                    at = from;
+                }
+                hops.reverse();
+                return Some(hops);
+            }
+            if depth == MAX_HOPS {
+                continue;
+            }
+            for (next, edge, sell_base) in adjacency.get(node).map(|v| v.as_slice()).unwrap_or_default() {
+                if *next == currency || previous.contains_key(next) {
+                    continue;
+                }
+                previous.insert(next, (node, edge, *sell_base));
+                queue.push_back((next, depth + 1));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::PairInfo;
+    use crate::types::OrderBookLevel;
+
+    #[test]
+    fn test_multi_hop_valuation_by_source() {
+        let cache = Arc::new(OrderBookCache::new());
+        for (base, quote, bid, ask) in [
+            ("BTC", "USD", 49_990.0, 50_010.0),
+            ("EUR", "USD", 1.08, 1.10),
+            ("DOT", "EUR", 5.0, 5.2),
+            ("USD", "CAD", 1.35, 1.37),
+        ] {
+            let pair = format!("{}/{}", base, quote);
+            cache.register_pair(PairInfo {
+                pair_name: pair.clone(),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                kraken_id: pair.replace('/', ""),
+                ws_name: pair.clone(),
+                volume_24h: 0.0,
+            });
+            cache.update_snapshot(
+                &pair,
+                vec![OrderBookLevel { price: bid, qty: 1.0 }],
+                vec![OrderBookLevel { price: ask, qty: 1.0 }],
+                1,
+            );
+        }
+        let valuator = Valuator::new(Arc::clone(&cache), PricingSource::Mid);
+
+        // Two hops: DOT → EUR → USD
+        let dot = valuator.value("DOT", 10.0);
+        assert_eq!(dot.route, vec!["DOT/EUR".to_string(), "EUR/USD".to_string()]);
+        assert!((dot.usd_value.unwrap() - 10.0 * 5.1 * 1.09).abs() < 1e-9);
+
+        let bid = valuator.value_with("DOT", 10.0, PricingSource::Bid);
+        assert!((bid.usd_value.unwrap() - 10.0 * 5.0 * 1.08).abs() < 1e-9);
+
+        // Inverted pair: CAD buys USD at the ask when conservative
+        let cad = valuator.usd_rate_with("CAD", PricingSource::Bid).unwrap();
+        assert_eq!(cad.route, vec!["USD/CAD".to_string()]);
+        assert!((cad.rate - 1.0 / 1.37).abs() < 1e-12);
+
+        // Last trade where known, mid otherwise
+        valuator.record_last_trade("BTC/USD", 49_000.0);
+        assert_eq!(valuator.usd_rate_with("BTC", PricingSource::Last).unwrap().rate, 49_000.0);
+        assert!((valuator.usd_rate_with("EUR", PricingSource::Last).unwrap().rate - 1.09).abs() < 1e-12);
+
+        assert_eq!(valuator.usd_rate("ZUSD").unwrap().rate, 1.0);
+        assert_eq!(valuator.usd_rate("USDT").unwrap().rate, 1.0); // par, no pair
+        assert!(valuator.value("SOL", 1.0).usd_value.is_none());
+        assert!(PricingSource::parse("ask").is_err());
+    }
+
+    #[test]
+    fn test_pnl_reported_at_snapshot_rate() {
+        let cache = Arc::new(OrderBookCache::new());
+        cache.register_pair(PairInfo {
+            pair_name: "EUR/USD".to_string(),
+            base: "EUR".to_string(),
+            quote: "USD".to_string(),
+            kraken_id: "ZEURZUSD".to_string(),
+            ws_name: "EUR/USD".to_string(),
+            volume_24h: 0.0,
+        });
+        let quote = |bid: f64, ask: f64| {
+            cache.update_snapshot(
+                "EUR/USD",
+                vec![OrderBookLevel { price: bid, qty: 1.0 }],
+                vec![OrderBookLevel { price: ask, qty: 1.0 }],
+                1,
+            )
+        };
+        quote(1.09, 1.11);
+        let valuator = Valuator::new(Arc::clone(&cache), PricingSource::Mid);
+
+        let loss = valuator.report_pnl("eur", -10.0, "USD");
+        assert_eq!(loss.currency, "EUR");
+        assert!((loss.fx_rate.unwrap() - 1.10).abs() < 1e-12);
+        assert!((loss.guard_amount() + 11.0).abs() < 1e-9);
+
+        // The snapshot keeps its rate when the market moves
+        quote(1.19, 1.21);
+        assert!((loss.reporting_amount.unwrap() + 11.0).abs() < 1e-9);
+        let in_eur = valuator.report_pnl("USD", 12.0, "EUR");
+        assert!((in_eur.reporting_amount.unwrap() - 10.0).abs() < 1e-9);
+
+        // Unpriced: reported as None, guarded at face value
+        let sol = valuator.report_pnl("SOL", -2.0, "USD");
+        assert_eq!((sol.reporting_amount, sol.guard_amount()), (None, -2.0));
+        assert_eq!(valuator.report_pnl("USD", 5.0, "usd").reporting_amount, Some(5.0));
+    }
+}
@@ -0,0 +1,474 @@
+//! Opportunity Webhook
+//!
+//! For setups that only want detection: every opportunity the HFT loop finds
+//! can be POSTed as JSON to an external endpoint (a strategy engine, a
+//! notebook, a queue bridge). The body carries the opportunity with its leg
+//! detail plus how fresh each leg's book was at detection.
+//!
+//! Publishing never blocks the hot path: opportunities go onto a bounded
+//! queue (dropped and counted when it is full) and one worker delivers them
+//! in order. A path is sent at most once per `min_interval_ms`. Failed
+//! deliveries (transport error or non-2xx) are retried with the reconnect
+//! backoff up to `max_attempts` times in total.
+//!
+//! With a secret set each request is signed:
+//! `X-Webhook-Signature: sha256=base64(HMAC-SHA256(secret, "{timestamp}.{body}"))`
+//! where the timestamp (unix ms) is sent as `X-Webhook-Timestamp`.
+//!
+//! Configured from OPPORTUNITY_WEBHOOK_* and switchable at runtime through
+//! POST /api/webhook.
+#![allow(dead_code)]
+
+use crate::order_book::OrderBookCache;
+use crate::reconnect::ReconnectPolicy;
+use crate::types::Opportunity;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Deliveries waiting for the worker
+const QUEUE_LEN: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    /// Attempts per opportunity, including the first
+    pub max_attempts: u32,
+    pub timeout_ms: u64,
+    /// Minimum gap between two sends of the same path
+    pub min_interval_ms: u64,
+    /// Delay between attempts
+    pub retry: ReconnectPolicy,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: None,
+            secret: None,
+            max_attempts: 3,
+            timeout_ms: 2_000,
+            min_interval_ms: 1_000,
+            retry: ReconnectPolicy {
+                base_delay_ms: 250,
+                max_delay_ms: 5_000,
+                max_attempts: 0,
+                jitter_pct: 20.0,
+                reset_after_secs: 0,
+            },
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Create from OPPORTUNITY_WEBHOOK_URL, _SECRET, _ENABLED (default: on
+    /// when a URL is set), _MAX_ATTEMPTS, _TIMEOUT_MS and _MIN_INTERVAL_MS
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        let url = std::env::var("OPPORTUNITY_WEBHOOK_URL").ok().filter(|v| !v.trim().is_empty());
+        Self {
+            enabled: env("OPPORTUNITY_WEBHOOK_ENABLED").unwrap_or(url.is_some()),
+            secret: std::env::var("OPPORTUNITY_WEBHOOK_SECRET").ok().filter(|v| !v.is_empty()),
+            max_attempts: env("OPPORTUNITY_WEBHOOK_MAX_ATTEMPTS").unwrap_or(defaults.max_attempts).max(1),
+            timeout_ms: env("OPPORTUNITY_WEBHOOK_TIMEOUT_MS").unwrap_or(defaults.timeout_ms),
+            min_interval_ms: env("OPPORTUNITY_WEBHOOK_MIN_INTERVAL_MS").unwrap_or(defaults.min_interval_ms),
+            url,
+            retry: defaults.retry,
+        }
+    }
+}
+
+/// Check an endpoint URL (http or https with a host)
+pub fn validate_url(value: &str) -> Result<(), String> {
+    let parsed = url::Url::parse(value).map_err(|e| format!("Invalid webhook URL '{}': {}", value, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(format!("Webhook URL must be http(s) with a host, got '{}'", value));
+    }
+    Ok(())
+}
+
+/// Signature header value for `body` sent at `timestamp_ms`
+pub fn sign(secret: &str, timestamp_ms: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp_ms.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", BASE64.encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookStats {
+    pub enabled: bool,
+    pub url: Option<String>,
+    pub signed: bool,
+    /// Opportunities queued for delivery
+    pub published: u64,
+    /// Skipped because the path was sent within min_interval_ms
+    pub throttled: u64,
+    /// Lost because the queue was full
+    pub dropped: u64,
+    pub delivered: u64,
+    /// Given up after max_attempts
+    pub failed: u64,
+    pub retries: u64,
+    pub last_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub last_delivered_at: Option<DateTime<Utc>>,
+    pub avg_latency_ms: f64,
+}
+
+#[derive(Default)]
+struct Counters {
+    published: AtomicU64,
+    throttled: AtomicU64,
+    dropped: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    retries: AtomicU64,
+    latency_total_ms: AtomicU64,
+}
+
+#[derive(Default)]
+struct LastDelivery {
+    status: Option<u16>,
+    error: Option<String>,
+    delivered_at: Option<DateTime<Utc>>,
+}
+
+struct Delivery {
+    id: String,
+    body: String,
+}
+
+pub struct OpportunityWebhook {
+    config: RwLock<WebhookConfig>,
+    enabled: AtomicBool,
+    queue: mpsc::Sender<Delivery>,
+    receiver: Mutex<Option<mpsc::Receiver<Delivery>>>,
+    last_sent: Mutex<HashMap<String, Instant>>,
+    counters: Counters,
+    last: RwLock<LastDelivery>,
+    client: reqwest::Client,
+}
+
+impl OpportunityWebhook {
+    pub fn new(config: WebhookConfig) -> Arc<Self> {
+        let (queue, receiver) = mpsc::channel(QUEUE_LEN);
+        Arc::new(Self {
+            enabled: AtomicBool::new(config.enabled && config.url.is_some()),
+            config: RwLock::new(config),
+            queue,
+            receiver: Mutex::new(Some(receiver)),
+            last_sent: Mutex::new(HashMap::new()),
+            counters: Counters::default(),
+            last: RwLock::new(LastDelivery::default()),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    pub fn from_env() -> Arc<Self> {
+        Self::new(WebhookConfig::from_env())
+    }
+
+    /// Spawn the delivery worker (once; later calls do nothing)
+    pub fn start(self: &Arc<Self>) {
+        let Some(mut receiver) = self.receiver.lock().take() else { return };
+        let webhook = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Some(delivery) = receiver.recv().await {
+                webhook.deliver(delivery).await;
+            }
+        });
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Change the endpoint and/or switch delivery on or off
+    pub fn configure(&self, enabled: bool, url: Option<String>, secret: Option<String>) -> Result<(), String> {
+        if let Some(url) = &url {
+            validate_url(url)?;
+        }
+        let mut config = self.config.write();
+        if url.is_some() {
+            config.url = url;
+        }
+        if let Some(secret) = secret {
+            config.secret = Some(secret).filter(|s| !s.is_empty());
+        }
+        if enabled && config.url.is_none() {
+            return Err("Cannot enable the opportunity webhook without a URL".to_string());
+        }
+        config.enabled = enabled;
+        self.enabled.store(enabled, Ordering::Relaxed);
+        info!("Opportunity webhook {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Queue an opportunity for delivery. Returns false when it wasn't
+    /// queued (disabled, throttled or queue full).
+    pub fn publish(&self, opportunity: &Opportunity, cache: &OrderBookCache) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+
+        let min_interval = Duration::from_millis(self.config.read().min_interval_ms);
+        let now = Instant::now();
+        {
+            let mut last_sent = self.last_sent.lock();
+            if last_sent.get(&opportunity.path).is_some_and(|at| now.duration_since(*at) < min_interval) {
+                self.counters.throttled.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+            last_sent.retain(|_, at| now.duration_since(*at) < min_interval);
+            last_sent.insert(opportunity.path.clone(), now);
+        }
+
+        let delivery = Delivery { id: opportunity.id.clone(), body: Self::payload(opportunity, cache).to_string() };
+        match self.queue.try_send(delivery) {
+            Ok(()) => {
+                self.counters.published.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(_) => {
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    /// Opportunity plus the age and book freshness of each leg
+    fn payload(opportunity: &Opportunity, cache: &OrderBookCache) -> Value {
+        let legs: Vec<Value> = opportunity.legs_detail
+            .iter()
+            .map(|leg| {
+                let price = cache.get_price(&leg.pair);
+                json!({
+                    "pair": leg.pair,
+                    "staleness_ms": cache.get_staleness(&leg.pair),
+                    "bid": price.as_ref().map(|p| p.bid),
+                    "ask": price.as_ref().map(|p| p.ask),
+                })
+            })
+            .collect();
+        let max_staleness_ms = legs.iter().filter_map(|l| l["staleness_ms"].as_i64()).max();
+
+        json!({
+            "event": "opportunity",
+            "sent_at": Utc::now().to_rfc3339(),
+            "opportunity": opportunity,
+            "freshness": {
+                "age_ms": opportunity.age_ms(),
+                "max_staleness_ms": max_staleness_ms,
+                "legs": legs,
+            },
+        })
+    }
+
+    async fn deliver(&self, delivery: Delivery) {
+        let config = self.config.read().clone();
+        let Some(url) = config.url.clone() else { return };
+
+        for attempt in 1..=config.max_attempts.max(1) {
+            let started = Instant::now();
+            let result = self.post(&url, config.secret.as_deref(), config.timeout_ms, &delivery).await;
+            match result {
+                Ok(status) => {
+                    let latency = started.elapsed().as_millis() as u64;
+                    self.counters.delivered.fetch_add(1, Ordering::Relaxed);
+                    self.counters.latency_total_ms.fetch_add(latency, Ordering::Relaxed);
+                    let mut last = self.last.write();
+                    last.status = Some(status);
+                    last.delivered_at = Some(Utc::now());
+                    debug!("Webhook delivered {} ({} ms)", delivery.id, latency);
+                    return;
+                }
+                Err((status, error)) => {
+                    {
+                        let mut last = self.last.write();
+                        last.status = status;
+                        last.error = Some(error.clone());
+                    }
+                    if attempt == config.max_attempts.max(1) {
+                        self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                        warn!("Webhook gave up on {} after {} attempts: {}", delivery.id, attempt, error);
+                        return;
+                    }
+                    self.counters.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(config.retry.delay(attempt)).await;
+                }
+            }
+        }
+    }
+
+    /// POST once; Ok(status) for 2xx, Err((status, reason)) otherwise
+    async fn post(
+        &self,
+        url: &str,
+        secret: Option<&str>,
+        timeout_ms: u64,
+        delivery: &Delivery,
+    ) -> Result<u16, (Option<u16>, String)> {
+        let timestamp = Utc::now().timestamp_millis();
+        let mut request = self.client
+            .post(url)
+            .timeout(Duration::from_millis(timeout_ms))
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Id", &delivery.id)
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .body(delivery.body.clone());
+        if let Some(secret) = secret {
+            request = request.header("X-Webhook-Signature", sign(secret, timestamp, &delivery.body));
+        }
+
+        let response = request.send().await.map_err(|e| (None, e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(status.as_u16())
+        } else {
+            Err((Some(status.as_u16()), format!("HTTP {}", status)))
+        }
+    }
+
+    pub fn stats(&self) -> WebhookStats {
+        let config = self.config.read();
+        let last = self.last.read();
+        let delivered = self.counters.delivered.load(Ordering::Relaxed);
+        WebhookStats {
+            enabled: self.is_enabled(),
+            url: config.url.clone(),
+            signed: config.secret.is_some(),
+            published: self.counters.published.load(Ordering::Relaxed),
+            throttled: self.counters.throttled.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            delivered,
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            retries: self.counters.retries.load(Ordering::Relaxed),
+            last_status: last.status,
+            last_error: last.error.clone(),
+            last_delivered_at: last.delivered_at,
+            avg_latency_ms: if delivered > 0 {
+                self.counters.latency_total_ms.load(Ordering::Relaxed) as f64 / delivered as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LegDetail;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn header(head: &str, name: &str) -> String {
+        head.lines()
+            .filter_map(|l| l.split_once(':'))
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Answers 500 to the first request and 204 after; returns the bodies
+    /// and signature headers it got
+    async fn endpoint() -> (String, mpsc::UnboundedReceiver<(String, String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut served = 0;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let (head, body) = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = header(head, "content-length").parse::<usize>().unwrap_or(0);
+                        if body.len() >= length {
+                            break (head.to_string(), body.to_string());
+                        }
+                    }
+                };
+                tx.send((body, header(&head, "x-webhook-timestamp"), header(&head, "x-webhook-signature"))).unwrap();
+
+                served += 1;
+                let status = if served == 1 { "500 Internal Server Error" } else { "204 No Content" };
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    fn opportunity(path: &str) -> Opportunity {
+        Opportunity {
+            legs_detail: vec![LegDetail {
+                pair: "BTC/USD".to_string(),
+                action: "buy".to_string(),
+                rate: 50_000.0,
+            }],
+            ..Opportunity::for_test(path, 0.24)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_delivery_with_retry_and_throttle() {
+        let (url, mut received) = endpoint().await;
+        let mut config = WebhookConfig { min_interval_ms: 60_000, ..WebhookConfig::default() };
+        config.retry.base_delay_ms = 10;
+        let webhook = OpportunityWebhook::new(config);
+        webhook.start();
+        let cache = OrderBookCache::new();
+
+        // Off until configured
+        assert!(!webhook.publish(&opportunity("USD → BTC → USD"), &cache));
+        assert!(webhook.configure(true, None, None).is_err());
+        assert!(webhook.configure(true, Some("ftp://example.com".to_string()), None).is_err());
+        webhook.configure(true, Some(url), Some("s3cret".to_string())).unwrap();
+
+        assert!(webhook.publish(&opportunity("USD → BTC → USD"), &cache));
+        assert!(!webhook.publish(&opportunity("USD → BTC → USD"), &cache));
+
+        // The 500 is retried with the same signed body
+        let (first, _, _) = received.recv().await.unwrap();
+        let (body, timestamp, signature) = received.recv().await.unwrap();
+        assert_eq!(first, body);
+        assert_eq!(signature, sign("s3cret", timestamp.parse().unwrap(), &body));
+        let payload: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["opportunity"]["path"], "USD → BTC → USD");
+        assert_eq!(payload["freshness"]["legs"][0]["pair"], "BTC/USD");
+
+        for _ in 0..100 {
+            if webhook.stats().delivered == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let stats = webhook.stats();
+        assert_eq!((stats.published, stats.throttled, stats.delivered, stats.retries, stats.failed), (1, 1, 1, 1, 0));
+        assert_eq!(stats.last_status, Some(204));
+    }
+}
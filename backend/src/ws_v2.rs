@@ -11,15 +11,25 @@
 //! - CRC32 checksum validation
 #![allow(dead_code)]
 
+use crate::chaos::{ChaosMonkey, Fault};
+use crate::runtimes::spawn_on;
 use crate::kraken_pairs::SelectedPair;
+use crate::kraken_proto::v2;
 use crate::order_book::{OrderBookCache, PairInfo};
-use crate::types::OrderBookLevel;
+use crate::pair_registry::PairMeta;
+use crate::reconnect::{ReconnectPolicy, ReconnectTracker};
+use crate::trade_feed::{TradeFeed, TradePrint};
+use crate::types::{ActiveSubscription, BookDelta, OrderBookLevel, ParseLatencySnapshot, WsTrafficSnapshot};
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
-use serde_json::{json, Value};
-use std::collections::HashMap;
+use serde_json::Value;
+use parking_lot::RwLock;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
+use tokio::runtime::Handle;
 use tokio::sync::mpsc;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, trace, warn};
@@ -30,6 +40,58 @@ fn get_kraken_ws_public_url() -> String {
         .unwrap_or_else(|_| "wss://ws.kraken.com/v2".to_string())
 }
 
+/// Book depths accepted by the v2 book channel
+const SUPPORTED_BOOK_DEPTHS: [usize; 5] = [10, 25, 100, 500, 1000];
+
+/// Round a requested depth up to the nearest depth Kraken accepts
+pub fn supported_book_depth(depth: usize) -> usize {
+    SUPPORTED_BOOK_DEPTHS
+        .iter()
+        .copied()
+        .find(|&d| d >= depth)
+        .unwrap_or(SUPPORTED_BOOK_DEPTHS[SUPPORTED_BOOK_DEPTHS.len() - 1])
+}
+
+// ============================================================================
+// Connection Options
+// ============================================================================
+
+/// Bandwidth-related options for the public WebSocket
+#[derive(Debug, Clone)]
+pub struct WsV2Options {
+    /// Subscribe to the ticker channel (book-only mode cuts payload roughly in half)
+    pub ticker_enabled: bool,
+    /// Subscribe to the trade channel (needs a trade feed to keep the prints)
+    pub trades_enabled: bool,
+}
+
+impl Default for WsV2Options {
+    fn default() -> Self {
+        Self {
+            ticker_enabled: true,
+            trades_enabled: false,
+        }
+    }
+}
+
+impl WsV2Options {
+    /// Load options from environment
+    /// - KRAKEN_WS_TICKER=false      -> book channel only (reduced payload)
+    /// - KRAKEN_WS_TRADES=true       -> also subscribe to trade prints
+    pub fn from_env() -> Self {
+        let flag = |name: &str, default: bool| {
+            std::env::var(name)
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+                .unwrap_or(default)
+        };
+
+        Self {
+            ticker_enabled: flag("KRAKEN_WS_TICKER", true),
+            trades_enabled: flag("KRAKEN_WS_TRADES", false),
+        }
+    }
+}
+
 // ============================================================================
 // WebSocket v2 Message Types
 // ============================================================================
@@ -55,19 +117,18 @@ pub struct V2Message {
 #[allow(dead_code)]
 pub struct V2BookData {
     pub symbol: String,
+    #[serde(default)]
     pub bids: Vec<V2Level>,
+    #[serde(default)]
     pub asks: Vec<V2Level>,
     pub checksum: Option<u32>,
     pub timestamp: Option<String>,
 }
 
-/// Single price level in v2 format
-#[derive(Debug, Deserialize)]
-#[allow(dead_code)]
-pub struct V2Level {
-    pub price: f64,  // v2 uses numbers, not strings
-    pub qty: f64,
-}
+/// Single price level in v2 format: {"price": 123.45, "qty": 1.5}
+/// Same shape as OrderBookLevel, so levels decode straight into cache types
+/// (v2 uses numbers, not strings)
+pub type V2Level = OrderBookLevel;
 
 /// Ticker channel data
 #[derive(Debug, Deserialize)]
@@ -87,6 +148,68 @@ pub struct V2TickerData {
     pub change_pct: f64,
 }
 
+/// Book channel frame
+#[derive(Debug, Deserialize)]
+struct V2BookFrame {
+    #[serde(rename = "type")]
+    msg_type: String,
+    #[serde(default)]
+    data: Vec<V2BookData>,
+}
+
+/// Ticker channel frame
+#[derive(Debug, Deserialize)]
+struct V2TickerFrame {
+    #[serde(default)]
+    data: Vec<V2TickerUpdate>,
+}
+
+/// Ticker fields used by the cache (lenient - fields may be null)
+#[derive(Debug, Deserialize)]
+struct V2TickerUpdate {
+    symbol: String,
+    bid: Option<f64>,
+    ask: Option<f64>,
+    volume: Option<f64>,
+}
+
+/// Trade channel frame
+#[derive(Debug, Deserialize)]
+struct V2TradeFrame {
+    #[serde(default)]
+    data: Vec<V2TradeUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct V2TradeUpdate {
+    symbol: String,
+    side: String,
+    price: f64,
+    qty: f64,
+    trade_id: Option<u64>,
+    timestamp: Option<String>,
+}
+
+/// Status channel frame
+#[derive(Debug, Deserialize)]
+struct V2StatusFrame {
+    #[serde(default)]
+    data: Vec<V2StatusItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct V2StatusItem {
+    system: Option<String>,
+}
+
+/// Response to a method call (subscribe, ping, ...)
+#[derive(Debug, Deserialize)]
+struct V2MethodResponse {
+    method: Option<String>,
+    success: Option<bool>,
+    error: Option<String>,
+}
+
 /// Instrument channel data (trading pair info)
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -102,6 +225,159 @@ pub struct V2InstrumentData {
     pub cost_min: f64,
 }
 
+// ============================================================================
+// Frame Parsing
+// ============================================================================
+
+/// How far into a frame to look for the channel key
+/// Kraken puts "channel" first, so this only needs to cover the envelope prefix;
+/// frames whose key falls outside it take a full parse
+const CHANNEL_PEEK_BYTES: usize = 64;
+
+/// Number of parse timings kept for percentile calculation
+const PARSE_TIMING_SAMPLES: usize = 4096;
+
+/// Extract the channel name from a raw frame without parsing it.
+/// Only a top-level key counts: method responses (subscribe acks carry
+/// `result.channel`) return None. None also means the key wasn't found in
+/// the first CHANNEL_PEEK_BYTES; see `FrameParser::channel`.
+fn peek_channel(text: &str) -> Option<&str> {
+    const KEY: &[u8] = b"\"channel\"";
+    let bytes = text.as_bytes();
+    let head = &bytes[..bytes.len().min(CHANNEL_PEEK_BYTES)];
+    let skip_whitespace = |mut i: usize| {
+        while head.get(i).is_some_and(|b| b.is_ascii_whitespace()) {
+            i += 1;
+        }
+        i
+    };
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    let mut start = None;
+    for (i, &b) in head.iter().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' if depth == 1 && head[i..].starts_with(KEY) => {
+                let colon = skip_whitespace(i + KEY.len());
+                if head.get(colon) != Some(&b':') {
+                    return None;
+                }
+                let quote = skip_whitespace(colon + 1);
+                if head.get(quote) != Some(&b'"') {
+                    return None;
+                }
+                start = Some(quote + 1);
+                break;
+            }
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    let start = start?;
+    let len = bytes[start..].iter().position(|&b| b == b'"')?;
+    std::str::from_utf8(&bytes[start..start + len]).ok()
+}
+
+/// Top-level channel of a frame, for frames `peek_channel` can't place
+#[derive(Debug, Deserialize)]
+struct V2Envelope {
+    channel: Option<String>,
+}
+
+/// Rolling window of frame parse times
+pub struct ParseTimings {
+    samples: parking_lot::Mutex<VecDeque<u32>>,
+    total: AtomicU64,
+}
+
+impl Default for ParseTimings {
+    fn default() -> Self {
+        Self {
+            samples: parking_lot::Mutex::new(VecDeque::with_capacity(PARSE_TIMING_SAMPLES)),
+            total: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ParseTimings {
+    pub fn record(&self, nanos: u32) {
+        let mut samples = self.samples.lock();
+        if samples.len() == PARSE_TIMING_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(nanos);
+        self.total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ParseLatencySnapshot {
+        let mut sorted: Vec<u32> = self.samples.lock().iter().copied().collect();
+        sorted.sort_unstable();
+
+        let percentile = |p: f64| -> f64 {
+            if sorted.is_empty() {
+                return 0.0;
+            }
+            let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+            sorted[idx] as f64 / 1000.0
+        };
+
+        ParseLatencySnapshot {
+            samples: self.total.load(Ordering::Relaxed),
+            p50_us: percentile(0.50),
+            p90_us: percentile(0.90),
+            p99_us: percentile(0.99),
+            max_us: sorted.last().map(|&n| n as f64 / 1000.0).unwrap_or(0.0),
+        }
+    }
+}
+
+/// Per-connection frame parser
+/// simd-json parses in place, so each frame is copied into a reused input
+/// buffer; its scratch buffers are reused across frames as well
+pub struct FrameParser {
+    input: Vec<u8>,
+    buffers: simd_json::Buffers,
+    timings: Arc<ParseTimings>,
+}
+
+impl FrameParser {
+    pub fn new(timings: Arc<ParseTimings>) -> Self {
+        Self {
+            input: Vec::with_capacity(64 * 1024),
+            buffers: simd_json::Buffers::new(64 * 1024),
+            timings,
+        }
+    }
+
+    /// Decode a frame into a typed struct, recording parse time
+    fn parse<T: serde::de::DeserializeOwned>(&mut self, text: &str) -> Result<T, simd_json::Error> {
+        let start = Instant::now();
+        self.input.clear();
+        self.input.extend_from_slice(text.as_bytes());
+        let result = simd_json::serde::from_slice_with_buffers(&mut self.input, &mut self.buffers);
+        self.timings.record(start.elapsed().as_nanos().min(u32::MAX as u128) as u32);
+        result
+    }
+
+    /// Channel of a frame: peeked from the prefix when possible, otherwise
+    /// from a full parse of the envelope (None for method responses)
+    fn channel<'a>(&mut self, text: &'a str) -> Option<Cow<'a, str>> {
+        if let Some(channel) = peek_channel(text) {
+            return Some(Cow::Borrowed(channel));
+        }
+        self.parse::<V2Envelope>(text).ok()?.channel.map(Cow::Owned)
+    }
+}
+
 // ============================================================================
 // Event Handler Trait
 // ============================================================================
@@ -132,6 +408,8 @@ const EVENT_CHANNEL_CAPACITY: usize = 1000;
 pub struct EventChannelStats {
     pub events_sent: AtomicU64,
     pub events_dropped: AtomicU64,
+    /// Dropped events per pair (only touched on a drop)
+    dropped_by_pair: parking_lot::Mutex<HashMap<String, u64>>,
 }
 
 impl Default for EventChannelStats {
@@ -139,6 +417,49 @@ impl Default for EventChannelStats {
         Self {
             events_sent: AtomicU64::new(0),
             events_dropped: AtomicU64::new(0),
+            dropped_by_pair: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl EventChannelStats {
+    pub fn record_drop(&self, pair: &str) {
+        self.events_dropped.fetch_add(1, Ordering::Relaxed);
+        *self.dropped_by_pair.lock().entry(pair.to_string()).or_insert(0) += 1;
+    }
+
+    /// Dropped events per pair since the channel was created
+    pub fn dropped_by_pair(&self) -> HashMap<String, u64> {
+        self.dropped_by_pair.lock().clone()
+    }
+}
+
+/// Byte/message counters for the public socket
+/// wire_bytes is what arrived on the socket, payload_bytes is what was handed to
+/// the JSON parser (binary frames that aren't UTF-8 never reach it)
+#[derive(Default)]
+pub struct WsTrafficStats {
+    pub text_frames: AtomicU64,
+    pub binary_frames: AtomicU64,
+    pub wire_bytes: AtomicU64,
+    pub payload_bytes: AtomicU64,
+    pub book_messages: AtomicU64,
+    pub book_bytes: AtomicU64,
+    pub ticker_messages: AtomicU64,
+    pub ticker_bytes: AtomicU64,
+}
+
+impl WsTrafficStats {
+    pub fn snapshot(&self) -> WsTrafficSnapshot {
+        WsTrafficSnapshot {
+            text_frames: self.text_frames.load(Ordering::Relaxed),
+            binary_frames: self.binary_frames.load(Ordering::Relaxed),
+            wire_bytes: self.wire_bytes.load(Ordering::Relaxed),
+            payload_bytes: self.payload_bytes.load(Ordering::Relaxed),
+            book_messages: self.book_messages.load(Ordering::Relaxed),
+            book_bytes: self.book_bytes.load(Ordering::Relaxed),
+            ticker_messages: self.ticker_messages.load(Ordering::Relaxed),
+            ticker_bytes: self.ticker_bytes.load(Ordering::Relaxed),
         }
     }
 }
@@ -149,14 +470,84 @@ pub struct KrakenWebSocketV2 {
     is_running: Arc<AtomicBool>,
     messages_received: Arc<AtomicU64>,
     shutdown_tx: Option<mpsc::Sender<()>>,
+    // Subscription changes applied on the live connection
+    control_tx: Option<mpsc::UnboundedSender<SubscriptionCommand>>,
+    // Pairs currently subscribed (restored on every reconnect)
+    subscribed: Arc<RwLock<BTreeSet<String>>>,
     max_pairs: usize,
+    // Pairs to subscribe first, best first (empty = by volume)
+    subscription_priority: Vec<String>,
     orderbook_depth: usize,
+    // Book depth of pairs not at orderbook_depth (0 = ticker only)
+    pair_depths: Arc<RwLock<HashMap<String, usize>>>,
     // Symbol to pair name mapping (v2 uses symbols like "BTC/USD")
     symbol_to_pair: HashMap<String, String>,
+    // (price, qty) decimals per pair, for book checksums
+    pair_precision: Arc<RwLock<HashMap<String, (u32, u32)>>>,
     // Bounded channel to emit order book update events for event-driven scanning
-    event_tx: Option<mpsc::Sender<String>>,
+    event_tx: Option<mpsc::Sender<BookDelta>>,
     // Statistics for event channel
     event_stats: Arc<EventChannelStats>,
+    // Bandwidth options and traffic counters
+    options: WsV2Options,
+    traffic: Arc<WsTrafficStats>,
+    parse_timings: Arc<ParseTimings>,
+    reconnect: Arc<ReconnectTracker>,
+    chaos: Arc<ChaosMonkey>,
+    /// Runtime the socket task runs on (None = the caller's)
+    runtime: Option<Handle>,
+    /// Where trade prints go (trade channel subscribed only when set)
+    trade_feed: Option<Arc<TradeFeed>>,
+}
+
+/// Subscription change for one pair, sent to the socket task
+#[derive(Debug, Clone)]
+enum SubscriptionCommand {
+    Subscribe(String),
+    Unsubscribe(String),
+    /// Unsubscribe + subscribe to get a fresh snapshot
+    Resubscribe(String),
+    /// Unsubscribe, then drop the pair from the cache (delisted)
+    Delist(String),
+    /// Resubscribe at the pair's new depth, leaving the old one
+    SetDepth { pair: String, from: usize },
+}
+
+/// Channels a pair at `depth` is subscribed to (depth 0 = ticker only)
+fn pair_channels(depth: usize, ticker_enabled: bool, trades: bool) -> Vec<v2::Channel> {
+    let mut channels = Vec::with_capacity(3);
+    if depth > 0 {
+        channels.push(v2::Channel::Book);
+    }
+    if ticker_enabled || depth == 0 {
+        channels.push(v2::Channel::Ticker);
+    }
+    if trades {
+        channels.push(v2::Channel::Trade);
+    }
+    channels
+}
+
+/// Book checksum verification while chaos testing: pairs whose cached book
+/// no longer matches the exchange checksum are resubscribed
+struct ChecksumGuard {
+    chaos: Arc<ChaosMonkey>,
+    control_tx: mpsc::UnboundedSender<SubscriptionCommand>,
+    pair_precision: Arc<RwLock<HashMap<String, (u32, u32)>>>,
+}
+
+impl ChecksumGuard {
+    fn verify(&self, cache: &OrderBookCache, pair: &str, expected: u32) {
+        // Without the pair's precision the checksum can't be reproduced
+        let Some((price_precision, qty_precision)) = self.pair_precision.read().get(pair).copied() else { return };
+        let Some(book) = cache.get_order_book(pair) else { return };
+        if calculate_book_checksum(&book.bids, &book.asks, price_precision, qty_precision) != expected
+            && self.chaos.checksum_mismatch(pair)
+        {
+            warn!("Book checksum mismatch on {} - resyncing", pair);
+            let _ = self.control_tx.send(SubscriptionCommand::Resubscribe(pair.to_string()));
+        }
+    }
 }
 
 impl KrakenWebSocketV2 {
@@ -166,22 +557,70 @@ impl KrakenWebSocketV2 {
             is_running: Arc::new(AtomicBool::new(false)),
             messages_received: Arc::new(AtomicU64::new(0)),
             shutdown_tx: None,
+            control_tx: None,
+            subscribed: Arc::new(RwLock::new(BTreeSet::new())),
             max_pairs: 200,
+            subscription_priority: Vec::new(),
             orderbook_depth: 25,
+            pair_depths: Arc::new(RwLock::new(HashMap::new())),
             symbol_to_pair: HashMap::new(),
+            pair_precision: Arc::new(RwLock::new(HashMap::new())),
             event_tx: None,
             event_stats: Arc::new(EventChannelStats::default()),
+            options: WsV2Options::default(),
+            traffic: Arc::new(WsTrafficStats::default()),
+            parse_timings: Arc::new(ParseTimings::default()),
+            reconnect: Arc::new(ReconnectTracker::new("public", ReconnectPolicy::default())),
+            chaos: Arc::new(ChaosMonkey::default()),
+            runtime: None,
+            trade_feed: None,
         }
     }
 
+    /// Use a shared reconnect tracker (takes effect on next start)
+    pub fn set_reconnect_tracker(&mut self, tracker: Arc<ReconnectTracker>) {
+        self.reconnect = tracker;
+    }
+
+    /// Inject faults from a shared chaos layer (takes effect on next start)
+    pub fn set_chaos(&mut self, chaos: Arc<ChaosMonkey>) {
+        self.chaos = chaos;
+    }
+
+    /// Run the socket task on `runtime` (takes effect on next start)
+    pub fn set_runtime(&mut self, runtime: Handle) {
+        self.runtime = Some(runtime);
+    }
+
+    /// Keep trade prints in `feed` when KRAKEN_WS_TRADES is on (takes effect
+    /// on next start)
+    pub fn set_trade_feed(&mut self, feed: Arc<TradeFeed>) {
+        self.trade_feed = Some(feed);
+    }
+
+    /// Set bandwidth options (takes effect on next start)
+    pub fn set_options(&mut self, options: WsV2Options) {
+        self.options = options;
+    }
+
+    /// Get traffic counters (bytes/messages per channel)
+    pub fn get_traffic_stats(&self) -> WsTrafficSnapshot {
+        self.traffic.snapshot()
+    }
+
+    /// Get frame parse time percentiles
+    pub fn get_parse_stats(&self) -> ParseLatencySnapshot {
+        self.parse_timings.snapshot()
+    }
+
     /// Set the event channel for order book update notifications (bounded)
-    pub fn set_event_channel(&mut self, tx: mpsc::Sender<String>) {
+    pub fn set_event_channel(&mut self, tx: mpsc::Sender<BookDelta>) {
         self.event_tx = Some(tx);
     }
 
     /// Get a receiver for order book update events (bounded channel)
     /// Returns (receiver, stats) - stats can be used to monitor dropped events
-    pub fn create_event_channel(&mut self) -> (mpsc::Receiver<String>, Arc<EventChannelStats>) {
+    pub fn create_event_channel(&mut self) -> (mpsc::Receiver<BookDelta>, Arc<EventChannelStats>) {
         let (tx, rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
         self.event_tx = Some(tx);
         self.event_stats = Arc::new(EventChannelStats::default());
@@ -200,6 +639,11 @@ impl KrakenWebSocketV2 {
         self.max_pairs = max_pairs;
     }
 
+    /// Subscribe these pairs first, in this order (the rest follow by volume)
+    pub fn set_subscription_priority(&mut self, pairs: Vec<String>) {
+        self.subscription_priority = pairs;
+    }
+
     pub fn set_orderbook_depth(&mut self, depth: usize) {
         self.orderbook_depth = depth;
     }
@@ -208,37 +652,101 @@ impl KrakenWebSocketV2 {
         self.orderbook_depth
     }
 
+    /// Book depth a pair is (or will be) subscribed at (0 = ticker only)
+    pub fn pair_depth(&self, pair: &str) -> usize {
+        self.pair_depths.read().get(pair).copied().unwrap_or(self.orderbook_depth)
+    }
+
+    /// Subscribe these pairs at their own depth (takes effect on next start)
+    pub fn set_pair_depths(&mut self, depths: HashMap<String, usize>) {
+        *self.pair_depths.write() = depths;
+    }
+
+    /// Move a subscribed pair to another depth (None = the default) on the
+    /// live connection. Returns false when it already is at that depth.
+    pub fn set_pair_depth(&self, pair: &str, depth: Option<usize>) -> Result<bool, String> {
+        if !self.subscribed.read().contains(pair) {
+            return Err(format!("Pair {} is not subscribed", pair));
+        }
+        let from = self.pair_depth(pair);
+        let to = depth.unwrap_or(self.orderbook_depth);
+        if from == to {
+            return Ok(false);
+        }
+        {
+            let mut depths = self.pair_depths.write();
+            if to == self.orderbook_depth {
+                depths.remove(pair);
+            } else {
+                depths.insert(pair.to_string(), to);
+            }
+        }
+        self.send_command(SubscriptionCommand::SetDepth { pair: pair.to_string(), from })?;
+        Ok(true)
+    }
+
     /// Initialize with pre-selected pairs from KrakenPairSelector
     /// This replaces the old initialize() that fetched all pairs
     pub fn initialize_with_pairs(&mut self, pairs: Vec<SelectedPair>) {
         info!("Initializing WebSocket with {} pre-selected pairs", pairs.len());
 
-        for pair in pairs {
-            // Register pair in cache
-            self.cache.register_pair(PairInfo {
-                pair_name: pair.pair_name.clone(),
-                base: pair.base.clone(),
-                quote: pair.quote.clone(),
-                kraken_id: pair.kraken_id.clone(),
-                ws_name: pair.ws_name.clone(),
-                volume_24h: pair.volume_24h_usd,
-            });
-
-            // Build symbol to pair mapping for v2 messages
-            self.symbol_to_pair.insert(pair.ws_name.clone(), pair.pair_name.clone());
+        for pair in &pairs {
+            self.register_pair(pair);
         }
 
         info!("Registered {} trading pairs for WebSocket subscription", self.cache.get_all_pairs().len());
     }
 
+    /// Register one pair in the cache so it can be subscribed
+    pub fn register_pair(&mut self, pair: &SelectedPair) {
+        self.cache.pair_registry().register(PairMeta::from(pair));
+        self.cache.register_pair(PairInfo {
+            pair_name: pair.pair_name.clone(),
+            base: pair.base.clone(),
+            quote: pair.quote.clone(),
+            kraken_id: pair.kraken_id.clone(),
+            ws_name: pair.ws_name.clone(),
+            volume_24h: pair.volume_24h_usd,
+        });
+
+        // Build symbol to pair mapping for v2 messages
+        self.symbol_to_pair.insert(pair.ws_name.clone(), pair.pair_name.clone());
+        if let (Some(price), Some(qty)) = (pair.pair_decimals, pair.lot_decimals) {
+            self.pair_precision.write().insert(pair.pair_name.clone(), (price, qty));
+        }
+    }
+
+    /// Registered pairs in subscription order, at most `limit`
+    fn subscription_order(&self, limit: usize) -> Vec<String> {
+        let by_volume = self.cache.get_pairs_by_volume(usize::MAX);
+        let mut remaining: HashSet<&String> = by_volume.iter().collect();
+        self.subscription_priority
+            .iter()
+            .chain(by_volume.iter())
+            .filter(|pair| remaining.remove(pair))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
     /// Start WebSocket v2 connection and subscribe to channels
     pub async fn start(&mut self, pairs_limit: usize, depth: usize) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
         self.shutdown_tx = Some(shutdown_tx);
-        self.orderbook_depth = depth;
+        let (control_tx, mut control_rx) = mpsc::unbounded_channel::<SubscriptionCommand>();
+        let checksum_guard = ChecksumGuard {
+            chaos: Arc::clone(&self.chaos),
+            control_tx: control_tx.clone(),
+            pair_precision: Arc::clone(&self.pair_precision),
+        };
+        self.control_tx = Some(control_tx);
+        self.orderbook_depth = supported_book_depth(depth);
+        if self.orderbook_depth != depth {
+            info!("Book depth {} not supported by Kraken, using {}", depth, self.orderbook_depth);
+        }
 
-        // Get top pairs by volume (already limited in cache)
-        let pairs_to_subscribe = self.cache.get_pairs_by_volume(pairs_limit);
+        // Ranked pairs first, then the rest by volume (already limited in cache)
+        let pairs_to_subscribe = self.subscription_order(pairs_limit);
 
         if pairs_to_subscribe.is_empty() {
             warn!("No pairs to subscribe to");
@@ -246,58 +754,77 @@ impl KrakenWebSocketV2 {
         }
 
         info!("Subscribing to {} pairs via WebSocket v2", pairs_to_subscribe.len());
+        *self.subscribed.write() = pairs_to_subscribe.into_iter().collect();
 
         let cache = Arc::clone(&self.cache);
         let is_running = Arc::clone(&self.is_running);
         let messages_received = Arc::clone(&self.messages_received);
-
-        // Get ws_names (symbols) for subscription
-        let symbols: Vec<String> = pairs_to_subscribe
-            .iter()
-            .filter_map(|p| self.cache.get_pair_info(p).map(|i| i.ws_name))
-            .collect();
-
-        // Build symbol to pair name lookup
-        let symbol_to_pair: HashMap<String, String> = pairs_to_subscribe
-            .iter()
-            .filter_map(|p| {
-                self.cache.get_pair_info(p).map(|i| (i.ws_name.clone(), p.clone()))
-            })
-            .collect();
+        let subscribed = Arc::clone(&self.subscribed);
 
         // Clone event channel and stats for the task
         let event_tx = self.event_tx.clone();
         let event_stats = Arc::clone(&self.event_stats);
+        let traffic = Arc::clone(&self.traffic);
+        let parse_timings = Arc::clone(&self.parse_timings);
+        let ticker_enabled = self.options.ticker_enabled;
+        let trade_feed = self.trade_feed.clone().filter(|_| self.options.trades_enabled);
+        let reconnect = Arc::clone(&self.reconnect);
+        let pair_depths = Arc::clone(&self.pair_depths);
 
         // Spawn WebSocket task
         let ws_depth = self.orderbook_depth;
-        tokio::spawn(async move {
+        spawn_on(self.runtime.as_ref(), async move {
             is_running.store(true, Ordering::SeqCst);
 
             loop {
-                match Self::run_websocket_v2(
+                // Symbol to pair name lookup for the current subscription set
+                let mut symbol_to_pair: HashMap<String, String> = subscribed
+                    .read()
+                    .iter()
+                    .filter_map(|p| cache.get_pair_info(p).map(|i| (i.ws_name, p.clone())))
+                    .collect();
+
+                let reason = match Self::run_websocket_v2(
                     &cache,
-                    &symbols,
-                    &symbol_to_pair,
+                    &mut symbol_to_pair,
                     &is_running,
                     &messages_received,
                     &mut shutdown_rx,
+                    &mut control_rx,
                     ws_depth,
+                    &pair_depths,
                     event_tx.clone(),
                     Arc::clone(&event_stats),
+                    ticker_enabled,
+                    trade_feed.as_deref(),
+                    &traffic,
+                    &parse_timings,
+                    &reconnect,
+                    &checksum_guard,
                 ).await {
                     Ok(_) => {
                         if !is_running.load(Ordering::SeqCst) {
                             break;
                         }
-                        warn!("WebSocket v2 disconnected, reconnecting in 5s...");
+                        "disconnected".to_string()
                     }
                     Err(e) => {
                         error!("WebSocket v2 error: {}", e);
+                        e.to_string()
                     }
-                }
+                };
 
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                match reconnect.disconnected(&reason) {
+                    Some(delay) => {
+                        warn!("WebSocket v2 reconnecting in {}ms...", delay.as_millis());
+                        tokio::time::sleep(delay).await;
+                    }
+                    None => {
+                        error!("WebSocket v2 giving up after repeated reconnect failures");
+                        is_running.store(false, Ordering::SeqCst);
+                        break;
+                    }
+                }
             }
 
             info!("WebSocket v2 task stopped");
@@ -307,64 +834,96 @@ impl KrakenWebSocketV2 {
     }
 
     /// Main WebSocket v2 loop
+    #[allow(clippy::too_many_arguments)]
     async fn run_websocket_v2(
         cache: &Arc<OrderBookCache>,
-        symbols: &[String],
-        symbol_to_pair: &HashMap<String, String>,
+        symbol_to_pair: &mut HashMap<String, String>,
         is_running: &Arc<AtomicBool>,
         messages_received: &Arc<AtomicU64>,
         shutdown_rx: &mut mpsc::Receiver<()>,
+        control_rx: &mut mpsc::UnboundedReceiver<SubscriptionCommand>,
         depth: usize,
-        event_tx: Option<mpsc::Sender<String>>,
+        pair_depths: &RwLock<HashMap<String, usize>>,
+        event_tx: Option<mpsc::Sender<BookDelta>>,
         event_stats: Arc<EventChannelStats>,
+        ticker_enabled: bool,
+        trade_feed: Option<&TradeFeed>,
+        traffic: &Arc<WsTrafficStats>,
+        parse_timings: &Arc<ParseTimings>,
+        reconnect: &ReconnectTracker,
+        checksum_guard: &ChecksumGuard,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let ws_url = get_kraken_ws_public_url();
         let (ws_stream, _) = connect_async(&ws_url).await?;
+        reconnect.connected();
+        let chaos = &checksum_guard.chaos;
+        chaos.reconnected();
         let (mut write, mut read) = ws_stream.split();
 
         info!("WebSocket v2 connected to {}", ws_url);
 
+        // Parse buffers live as long as the connection
+        let mut parser = FrameParser::new(Arc::clone(parse_timings));
+
         // Request ID counter
         let mut req_id: u64 = 1;
 
-        // Subscribe to book channel (L2 order book)
+        let symbols: Vec<String> = symbol_to_pair.keys().cloned().collect();
+        let depth_of = |symbol: &String| {
+            symbol_to_pair.get(symbol).and_then(|pair| pair_depths.read().get(pair).copied()).unwrap_or(depth)
+        };
+
+        // Subscribe to book channel (L2 order book), one subscription per depth tier
         // v2 allows up to 1000 symbols per subscription
-        for chunk in symbols.chunks(500) {
-            let subscribe_msg = json!({
-                "method": "subscribe",
-                "params": {
-                    "channel": "book",
-                    "symbol": chunk,
-                    "depth": depth
-                },
-                "req_id": req_id
-            });
+        let mut by_depth: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for symbol in &symbols {
+            by_depth.entry(depth_of(symbol)).or_default().push(symbol.clone());
+        }
+        for (&tier_depth, tier_symbols) in by_depth.iter().filter(|(d, _)| **d > 0) {
+            for chunk in tier_symbols.chunks(500) {
+                let subscribe_msg = v2::Request::new(v2::Method::Subscribe(v2::SubscribeParams::book(chunk.to_vec(), tier_depth)))
+                    .with_req_id(req_id);
+                req_id += 1;
+
+                write.send(Message::Text(subscribe_msg.to_json())).await?;
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            }
+            info!("Subscribed {} pairs to book channel (depth={})", tier_symbols.len(), tier_depth);
+        }
+
+        // Subscribe to ticker channel for volume updates (skipped in book-only
+        // mode, except for ticker-only pairs)
+        let ticker_symbols: Vec<String> = if ticker_enabled {
+            symbols.clone()
+        } else {
+            info!("Ticker channel disabled - book-only payload");
+            by_depth.get(&0).cloned().unwrap_or_default()
+        };
+        for chunk in ticker_symbols.chunks(500) {
+            let subscribe_msg = v2::Request::new(v2::Method::Subscribe(v2::SubscribeParams::ticker(chunk.to_vec())))
+                .with_req_id(req_id);
             req_id += 1;
 
-            write.send(Message::Text(subscribe_msg.to_string())).await?;
+            write.send(Message::Text(subscribe_msg.to_json())).await?;
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
 
-        info!("Subscribed to book channel (depth={})", depth);
+        if !ticker_symbols.is_empty() {
+            info!("Subscribed {} pairs to ticker channel", ticker_symbols.len());
+        }
 
-        // Subscribe to ticker channel for volume updates
-        for chunk in symbols.chunks(500) {
-            let subscribe_msg = json!({
-                "method": "subscribe",
-                "params": {
-                    "channel": "ticker",
-                    "symbol": chunk
-                },
-                "req_id": req_id
-            });
-            req_id += 1;
+        if trade_feed.is_some() {
+            for chunk in symbols.chunks(500) {
+                let subscribe_msg = v2::Request::new(v2::Method::Subscribe(v2::SubscribeParams::symbols(v2::Channel::Trade, chunk.to_vec())))
+                    .with_req_id(req_id);
+                req_id += 1;
 
-            write.send(Message::Text(subscribe_msg.to_string())).await?;
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                write.send(Message::Text(subscribe_msg.to_json())).await?;
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            }
+            info!("Subscribed to trade channel");
         }
 
-        info!("Subscribed to ticker channel");
-
         // Message loop
         loop {
             tokio::select! {
@@ -372,7 +931,31 @@ impl KrakenWebSocketV2 {
                     match msg {
                         Some(Ok(Message::Text(text))) => {
                             messages_received.fetch_add(1, Ordering::Relaxed);
-                            Self::handle_v2_message(cache, symbol_to_pair, &text, &event_tx, &event_stats);
+                            traffic.text_frames.fetch_add(1, Ordering::Relaxed);
+                            traffic.wire_bytes.fetch_add(text.len() as u64, Ordering::Relaxed);
+                            if chaos.inject(Fault::Disconnect) {
+                                return Err("chaos: forced disconnect".into());
+                            }
+                            if chaos.inject(Fault::WsDrop) {
+                                continue;
+                            }
+                            Self::handle_v2_message(cache, &*symbol_to_pair, &text, &event_tx, &event_stats, traffic, &mut parser, checksum_guard, trade_feed);
+                        }
+                        Some(Ok(Message::Binary(data))) => {
+                            // Binary frames carry the same JSON payload as UTF-8 bytes
+                            messages_received.fetch_add(1, Ordering::Relaxed);
+                            traffic.binary_frames.fetch_add(1, Ordering::Relaxed);
+                            traffic.wire_bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+                            if chaos.inject(Fault::Disconnect) {
+                                return Err("chaos: forced disconnect".into());
+                            }
+                            if chaos.inject(Fault::WsDrop) {
+                                continue;
+                            }
+                            match std::str::from_utf8(&data) {
+                                Ok(text) => Self::handle_v2_message(cache, &*symbol_to_pair, text, &event_tx, &event_stats, traffic, &mut parser, checksum_guard, trade_feed),
+                                Err(e) => debug!("Non-UTF8 binary frame ({} bytes): {}", data.len(), e),
+                            }
                         }
                         Some(Ok(Message::Ping(data))) => {
                             let _ = write.send(Message::Pong(data)).await;
@@ -392,6 +975,78 @@ impl KrakenWebSocketV2 {
                         _ => {}
                     }
                 }
+                Some(command) = control_rx.recv() => {
+                    let delist = matches!(command, SubscriptionCommand::Delist(_));
+                    let (pair, unsubscribe, subscribe, from_depth) = match command {
+                        SubscriptionCommand::Subscribe(pair) => (pair, false, true, None),
+                        SubscriptionCommand::Unsubscribe(pair) | SubscriptionCommand::Delist(pair) => (pair, true, false, None),
+                        SubscriptionCommand::Resubscribe(pair) => (pair, true, true, None),
+                        SubscriptionCommand::SetDepth { pair, from } => (pair, true, true, Some(from)),
+                    };
+                    let Some(symbol) = cache.get_pair_info(&pair).map(|i| i.ws_name) else {
+                        continue;
+                    };
+                    let pair_depth = pair_depths.read().get(&pair).copied().unwrap_or(depth);
+                    match (unsubscribe, subscribe, from_depth) {
+                        (_, _, Some(from)) => info!("Moving {} ({}) from depth {} to {}", pair, symbol, from, pair_depth),
+                        (true, true, _) => info!("Resubscribing {} ({})", pair, symbol),
+                        (false, true, _) => info!("Subscribing {} ({})", pair, symbol),
+                        _ => info!("Unsubscribing {} ({})", pair, symbol),
+                    }
+
+                    let trades = trade_feed.is_some();
+                    let old_channels = pair_channels(from_depth.unwrap_or(pair_depth), ticker_enabled, trades);
+                    let new_channels = pair_channels(pair_depth, ticker_enabled, trades);
+                    // A depth move leaves the ticker and trade channels alone when both depths use them
+                    let (old_channels, new_channels): (Vec<v2::Channel>, Vec<v2::Channel>) = if from_depth.is_some() {
+                        (
+                            old_channels.iter().copied().filter(|c| *c == v2::Channel::Book || !new_channels.contains(c)).collect(),
+                            new_channels.iter().copied().filter(|c| *c == v2::Channel::Book || !old_channels.contains(c)).collect(),
+                        )
+                    } else {
+                        (old_channels, new_channels)
+                    };
+
+                    if unsubscribe {
+                        for &channel in &old_channels {
+                            let params = v2::SubscribeParams::symbols(channel, vec![symbol.clone()]);
+                            write.send(Message::Text(v2::Request::new(v2::Method::Unsubscribe(params)).with_req_id(req_id).to_json())).await?;
+                            req_id += 1;
+                        }
+                    }
+                    if subscribe {
+                        // Nothing from the old subscription may survive into the new snapshot
+                        if old_channels.contains(&v2::Channel::Book) || new_channels.contains(&v2::Channel::Book) {
+                            cache.reset_pair(&pair);
+                        }
+                        for &channel in &new_channels {
+                            let symbols = vec![symbol.clone()];
+                            let params = if channel == v2::Channel::Book {
+                                v2::SubscribeParams::book(symbols, pair_depth)
+                            } else {
+                                v2::SubscribeParams::symbols(channel, symbols)
+                            };
+                            write.send(Message::Text(v2::Request::new(v2::Method::Subscribe(params)).with_req_id(req_id).to_json())).await?;
+                            req_id += 1;
+                        }
+                    }
+
+                    // Late frames for a dropped pair are ignored
+                    if subscribe {
+                        symbol_to_pair.insert(symbol, pair);
+                    } else {
+                        symbol_to_pair.remove(&symbol);
+                        pair_depths.write().remove(&pair);
+                        if delist {
+                            cache.unregister_pair(&pair);
+                        } else {
+                            cache.release_pair(&pair);
+                        }
+                        if let Some(feed) = trade_feed {
+                            feed.release(&pair);
+                        }
+                    }
+                }
                 _ = shutdown_rx.recv() => {
                     info!("Shutdown signal received");
                     is_running.store(false, Ordering::SeqCst);
@@ -404,41 +1059,59 @@ impl KrakenWebSocketV2 {
     }
 
     /// Handle incoming WebSocket v2 message
+    /// Dispatches on the channel name (peeked from the raw text) and decodes
+    /// straight into typed frames - no intermediate serde_json::Value
+    #[allow(clippy::too_many_arguments)]
     fn handle_v2_message(
         cache: &Arc<OrderBookCache>,
         symbol_to_pair: &HashMap<String, String>,
         text: &str,
-        event_tx: &Option<mpsc::Sender<String>>,
+        event_tx: &Option<mpsc::Sender<BookDelta>>,
         event_stats: &Arc<EventChannelStats>,
+        traffic: &Arc<WsTrafficStats>,
+        parser: &mut FrameParser,
+        checksum_guard: &ChecksumGuard,
+        trade_feed: Option<&TradeFeed>,
     ) {
-        let value: Value = match serde_json::from_str(text) {
-            Ok(v) => v,
-            Err(e) => {
-                debug!("Failed to parse WebSocket message: {}", e);
-                return;
-            }
-        };
+        traffic.payload_bytes.fetch_add(text.len() as u64, Ordering::Relaxed);
 
         // Check for channel data (book, ticker, etc.)
-        if let Some(channel) = value.get("channel").and_then(|c| c.as_str()) {
-            let msg_type = value.get("type").and_then(|t| t.as_str()).unwrap_or("");
-
-            match channel {
+        if let Some(channel) = parser.channel(text) {
+            match channel.as_ref() {
                 "book" => {
-                    let is_snapshot = msg_type == "snapshot";
-                    Self::handle_v2_book_message(cache, symbol_to_pair, &value, is_snapshot, event_tx, event_stats);
+                    traffic.book_messages.fetch_add(1, Ordering::Relaxed);
+                    traffic.book_bytes.fetch_add(text.len() as u64, Ordering::Relaxed);
+                    match parser.parse::<V2BookFrame>(text) {
+                        Ok(frame) => {
+                            let is_snapshot = frame.msg_type == "snapshot";
+                            Self::handle_v2_book_message(cache, symbol_to_pair, frame.data, is_snapshot, event_tx, event_stats, checksum_guard);
+                        }
+                        Err(e) => debug!("Failed to parse book message: {}", e),
+                    }
                 }
                 "ticker" => {
-                    Self::handle_v2_ticker_message(cache, symbol_to_pair, &value);
+                    traffic.ticker_messages.fetch_add(1, Ordering::Relaxed);
+                    traffic.ticker_bytes.fetch_add(text.len() as u64, Ordering::Relaxed);
+                    match parser.parse::<V2TickerFrame>(text) {
+                        Ok(frame) => Self::handle_v2_ticker_message(cache, symbol_to_pair, frame.data),
+                        Err(e) => debug!("Failed to parse ticker message: {}", e),
+                    }
+                }
+                "trade" => {
+                    let Some(feed) = trade_feed else { return };
+                    match parser.parse::<V2TradeFrame>(text) {
+                        Ok(frame) => Self::handle_v2_trade_message(feed, symbol_to_pair, frame.data),
+                        Err(e) => debug!("Failed to parse trade message: {}", e),
+                    }
                 }
                 "heartbeat" => {
                     // Heartbeat messages - ignore
                 }
                 "status" => {
                     // System status
-                    if let Some(data) = value.get("data").and_then(|d| d.as_array()) {
-                        for item in data {
-                            if let Some(status) = item.get("system").and_then(|s| s.as_str()) {
+                    if let Ok(frame) = parser.parse::<V2StatusFrame>(text) {
+                        for item in frame.data {
+                            if let Some(status) = item.system {
                                 info!("Kraken system status: {}", status);
                             }
                         }
@@ -452,15 +1125,19 @@ impl KrakenWebSocketV2 {
         }
 
         // Check for subscription response
-        if let Some(method) = value.get("method").and_then(|m| m.as_str()) {
+        let response = match parser.parse::<V2MethodResponse>(text) {
+            Ok(r) => r,
+            Err(e) => {
+                debug!("Failed to parse WebSocket message: {}", e);
+                return;
+            }
+        };
+
+        if let Some(method) = response.method.as_deref() {
             match method {
-                "subscribe" => {
-                    if let Some(success) = value.get("success").and_then(|s| s.as_bool()) {
-                        if !success {
-                            if let Some(err) = value.get("error").and_then(|e| e.as_str()) {
-                                warn!("Subscription error: {}", err);
-                            }
-                        }
+                "subscribe" | "unsubscribe" if response.success == Some(false) => {
+                    if let Some(err) = response.error {
+                        warn!("{} error: {}", method, err);
                     }
                 }
                 "pong" => {
@@ -475,70 +1152,55 @@ impl KrakenWebSocketV2 {
     fn handle_v2_book_message(
         cache: &Arc<OrderBookCache>,
         symbol_to_pair: &HashMap<String, String>,
-        value: &Value,
+        items: Vec<V2BookData>,
         is_snapshot: bool,
-        event_tx: &Option<mpsc::Sender<String>>,
+        event_tx: &Option<mpsc::Sender<BookDelta>>,
         event_stats: &Arc<EventChannelStats>,
+        checksum_guard: &ChecksumGuard,
     ) {
-        // v2 book data can come as either:
-        // 1. Array: [{"symbol": "BTC/USD", "bids": [...], "asks": [...]}]
-        // 2. Single object: {"symbol": "BTC/USD", "bids": [...], "asks": [...]}
-
-        let items: Vec<&Value> = if let Some(arr) = value.get("data").and_then(|d| d.as_array()) {
-            arr.iter().collect()
-        } else if let Some(obj) = value.get("data") {
-            vec![obj]
-        } else {
-            debug!("No data in book message");
-            return;
-        };
-
         for item in items {
-            let symbol = match item.get("symbol").and_then(|s| s.as_str()) {
-                Some(s) => s,
-                None => {
-                    debug!("No symbol in book data");
-                    continue;
-                }
-            };
-
-            let pair_name = match symbol_to_pair.get(symbol) {
+            let pair_name = match symbol_to_pair.get(&item.symbol) {
                 Some(p) => p,
                 None => {
-                    trace!("Symbol not found in mapping: {}", symbol);
+                    trace!("Symbol not found in mapping: {}", item.symbol);
                     continue;
                 }
             };
 
-            // Parse bids and asks - v2 uses numeric values directly
-            let bids = Self::parse_v2_levels(item.get("bids"));
-            let asks = Self::parse_v2_levels(item.get("asks"));
+            // Levels are decoded directly into OrderBookLevel - no conversion pass
+            let chaos = &checksum_guard.chaos;
+            let mut checksum = item.checksum.unwrap_or(0);
 
-            // Get checksum for validation (optional)
-            let checksum = item.get("checksum")
-                .and_then(|c| c.as_u64())
-                .map(|c| c as u32)
-                .unwrap_or(0);
-
-            if is_snapshot {
+            let delta = if is_snapshot {
                 // For snapshot, we use checksum as sequence
-                cache.update_snapshot(pair_name, bids, asks, checksum as u64);
+                cache.update_snapshot(pair_name, item.bids, item.asks, checksum as u64);
+                chaos.snapshot_received(pair_name);
+                BookDelta::snapshot(pair_name)
             } else {
                 // For incremental updates, pass 0 to skip sequence checking
                 // v2 uses checksums for integrity, not sequences for ordering
-                cache.update_incremental(pair_name, bids, asks, 0);
-            }
+                let Some(delta) = cache.update_incremental(pair_name, item.bids, item.asks, 0) else {
+                    continue;
+                };
+                if item.checksum.is_some() && chaos.verifies_checksums() {
+                    if chaos.inject(Fault::Checksum) {
+                        checksum = !checksum;
+                    }
+                    checksum_guard.verify(cache, pair_name, checksum);
+                }
+                delta
+            };
 
             // Emit event for event-driven scanning using bounded channel
             if let Some(tx) = event_tx {
                 // Use try_send for non-blocking send with backpressure
-                match tx.try_send(pair_name.clone()) {
+                match tx.try_send(delta) {
                     Ok(_) => {
                         event_stats.events_sent.fetch_add(1, Ordering::Relaxed);
                     }
-                    Err(mpsc::error::TrySendError::Full(_)) => {
+                    Err(mpsc::error::TrySendError::Full(delta)) => {
                         // Channel is full - drop event (acceptable for order book updates)
-                        event_stats.events_dropped.fetch_add(1, Ordering::Relaxed);
+                        event_stats.record_drop(&delta.pair);
                     }
                     Err(mpsc::error::TrySendError::Closed(_)) => {
                         // Channel closed - receiver dropped
@@ -549,52 +1211,115 @@ impl KrakenWebSocketV2 {
         }
     }
 
-    /// Parse v2 order book levels
-    fn parse_v2_levels(value: Option<&Value>) -> Vec<OrderBookLevel> {
-        value
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|level| {
-                        // v2 uses object format: {"price": 123.45, "qty": 1.5}
-                        let price = level.get("price")?.as_f64()?;
-                        let qty = level.get("qty")?.as_f64()?;
-                        Some(OrderBookLevel { price, qty })
-                    })
-                    .collect()
-            })
-            .unwrap_or_default()
-    }
-
     /// Handle v2 ticker channel message
     fn handle_v2_ticker_message(
         cache: &Arc<OrderBookCache>,
         symbol_to_pair: &HashMap<String, String>,
-        value: &Value,
+        items: Vec<V2TickerUpdate>,
     ) {
-        let data = match value.get("data").and_then(|d| d.as_array()) {
-            Some(d) => d,
-            None => return,
-        };
-
-        for item in data {
-            let symbol = match item.get("symbol").and_then(|s| s.as_str()) {
-                Some(s) => s,
-                None => continue,
-            };
-
-            let pair_name = match symbol_to_pair.get(symbol) {
+        for item in items {
+            let pair_name = match symbol_to_pair.get(&item.symbol) {
                 Some(p) => p,
                 None => continue,
             };
 
-            // v2 ticker uses numeric values
-            let bid = item.get("bid").and_then(|b| b.as_f64()).unwrap_or(0.0);
-            let ask = item.get("ask").and_then(|a| a.as_f64()).unwrap_or(0.0);
-            let volume = item.get("volume").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            cache.update_price_ticker(
+                pair_name,
+                item.bid.unwrap_or(0.0),
+                item.ask.unwrap_or(0.0),
+                item.volume.unwrap_or(0.0),
+            );
+        }
+    }
+
+    /// Handle v2 trade channel message (prints arrive oldest first)
+    fn handle_v2_trade_message(feed: &TradeFeed, symbol_to_pair: &HashMap<String, String>, items: Vec<V2TradeUpdate>) {
+        for item in items {
+            let Some(pair_name) = symbol_to_pair.get(&item.symbol) else { continue };
+            let at = item
+                .timestamp
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&chrono::Utc))
+                .unwrap_or_else(chrono::Utc::now);
+            feed.record(pair_name, [TradePrint { price: item.price, qty: item.qty, side: item.side, at, trade_id: item.trade_id }]);
+        }
+    }
 
-            cache.update_price_ticker(pair_name, bid, ask, volume);
+    /// Unsubscribe one pair, drop its cached book and subscribe again,
+    /// leaving every other subscription untouched
+    pub fn resubscribe_pair(&self, pair: &str) -> Result<(), String> {
+        if !self.subscribed.read().contains(pair) {
+            return Err(format!("Pair {} is not subscribed", pair));
         }
+        self.send_command(SubscriptionCommand::Resubscribe(pair.to_string()))
+    }
+
+    /// Add one registered pair to the live subscription
+    pub fn subscribe_pair(&self, pair: &str) -> Result<(), String> {
+        if self.cache.get_pair_info(pair).is_none() {
+            return Err(format!("Pair {} is not a known pair", pair));
+        }
+        if self.subscribed.read().contains(pair) {
+            return Err(format!("Pair {} is already subscribed", pair));
+        }
+        self.send_command(SubscriptionCommand::Subscribe(pair.to_string()))?;
+        self.subscribed.write().insert(pair.to_string());
+        Ok(())
+    }
+
+    /// Drop one pair from the live subscription and clear its cached book
+    pub fn unsubscribe_pair(&self, pair: &str) -> Result<(), String> {
+        if !self.subscribed.read().contains(pair) {
+            return Err(format!("Pair {} is not subscribed", pair));
+        }
+        self.send_command(SubscriptionCommand::Unsubscribe(pair.to_string()))?;
+        self.subscribed.write().remove(pair);
+        Ok(())
+    }
+
+    /// Forget a delisted pair: unsubscribe it if subscribed, then drop it
+    /// from the cache and the pair registry
+    pub fn delist_pair(&mut self, pair: &str) {
+        if let Some(info) = self.cache.get_pair_info(pair) {
+            self.symbol_to_pair.remove(&info.ws_name);
+        }
+        self.pair_precision.write().remove(pair);
+        self.subscription_priority.retain(|p| p != pair);
+        let subscribed = self.subscribed.write().remove(pair);
+        // The socket task unregisters it once the unsubscribe went out
+        if subscribed && self.send_command(SubscriptionCommand::Delist(pair.to_string())).is_ok() {
+            return;
+        }
+        self.cache.unregister_pair(pair);
+    }
+
+    fn send_command(&self, command: SubscriptionCommand) -> Result<(), String> {
+        match &self.control_tx {
+            Some(tx) if self.is_running() => tx
+                .send(command)
+                .map_err(|_| "WebSocket task stopped".to_string()),
+            _ => Err("WebSocket not running".to_string()),
+        }
+    }
+
+    /// Pairs currently subscribed, with their book depth and cached levels
+    pub fn get_active_subscriptions(&self) -> Vec<ActiveSubscription> {
+        self.subscribed
+            .read()
+            .iter()
+            .map(|pair| {
+                let book = self.cache.get_order_book(pair);
+                ActiveSubscription {
+                    pair: pair.clone(),
+                    symbol: self.cache.get_pair_info(pair).map(|i| i.ws_name).unwrap_or_default(),
+                    depth: self.pair_depth(pair),
+                    ticker: self.options.ticker_enabled || self.pair_depth(pair) == 0,
+                    bid_levels: book.as_ref().map_or(0, |b| b.bids.len()),
+                    ask_levels: book.as_ref().map_or(0, |b| b.asks.len()),
+                }
+            })
+            .collect()
     }
 
     /// Stop WebSocket connection
@@ -621,40 +1346,26 @@ impl KrakenWebSocketV2 {
 // ============================================================================
 
 /// Calculate CRC32 checksum for order book validation
-/// Kraken uses CRC32 IEEE polynomial
-pub fn calculate_book_checksum(bids: &[OrderBookLevel], asks: &[OrderBookLevel]) -> u32 {
+/// Kraken uses CRC32 IEEE polynomial; prices and quantities are formatted at
+/// the pair's price and qty precision
+pub fn calculate_book_checksum(bids: &[OrderBookLevel], asks: &[OrderBookLevel], price_precision: u32, qty_precision: u32) -> u32 {
     // Take top 10 levels from each side
     let mut checksum_str = String::new();
 
-    // Add asks (top 10, ascending by price)
-    for level in asks.iter().take(10) {
-        // Remove decimal point and leading zeros
-        let price_str = format_checksum_number(level.price);
-        let qty_str = format_checksum_number(level.qty);
-        checksum_str.push_str(&price_str);
-        checksum_str.push_str(&qty_str);
-    }
-
-    // Add bids (top 10, descending by price)
-    for level in bids.iter().take(10) {
-        let price_str = format_checksum_number(level.price);
-        let qty_str = format_checksum_number(level.qty);
-        checksum_str.push_str(&price_str);
-        checksum_str.push_str(&qty_str);
+    // Add asks (top 10, ascending by price), then bids (top 10, descending by price)
+    for level in asks.iter().take(10).chain(bids.iter().take(10)) {
+        checksum_str.push_str(&format_checksum_number(level.price, price_precision));
+        checksum_str.push_str(&format_checksum_number(level.qty, qty_precision));
     }
 
     // Calculate CRC32
     crc32_ieee(checksum_str.as_bytes())
 }
 
-/// Format number for checksum (remove decimal, strip leading zeros)
-fn format_checksum_number(value: f64) -> String {
-    // Convert to string with enough precision
-    let s = format!("{:.10}", value);
-    // Remove decimal point
-    let s = s.replace('.', "");
-    // Strip leading zeros
-    s.trim_start_matches('0').to_string()
+/// Format number for checksum (fixed precision, remove decimal, strip leading zeros)
+fn format_checksum_number(value: f64, precision: u32) -> String {
+    let s = format!("{:.*}", precision as usize, value);
+    s.replace('.', "").trim_start_matches('0').to_string()
 }
 
 /// Simple CRC32 IEEE implementation
@@ -687,9 +1398,116 @@ mod tests {
         assert_eq!(result, 0xCBF43926);
     }
 
+    #[test]
+    fn test_peek_channel() {
+        assert_eq!(peek_channel(r#"{"channel":"book","type":"update","data":[]}"#), Some("book"));
+        assert_eq!(peek_channel(r#"{"method":"subscribe","success":true}"#), None);
+        // Subscribe acks name the channel inside result
+        let ack = r#"{"method":"subscribe","req_id":1,"result":{"channel":"book","depth":10,"snapshot":true,"symbol":"BTC/USD"},"success":true,"time_in":"2024-01-01T00:00:00.000000Z","time_out":"2024-01-01T00:00:00.000100Z"}"#;
+        assert_eq!(peek_channel(ack), None);
+        assert_eq!(peek_channel(r#"{ "type":"update", "channel":"ticker","data":[{"symbol":"A\"B"}]}"#), Some("ticker"));
+        assert_eq!(peek_channel(r#"{ "channel" : "book", "type": "update", "data": []}"#), Some("book"));
+        assert_eq!(peek_channel("{\n  \"channel\":\n  \"trade\"}"), Some("trade"));
+    }
+
+    #[test]
+    fn test_frame_channel_falls_back_to_full_parse() {
+        let mut parser = FrameParser::new(Arc::new(ParseTimings::default()));
+        // Key past the peek window
+        let late = r#"{"type":"update","data":[{"symbol":"BTC/USD","bid":50000.0,"ask":50001.0}],"channel":"ticker"}"#;
+        assert_eq!(peek_channel(late), None);
+        assert_eq!(parser.channel(late).as_deref(), Some("ticker"));
+        // Key split across the window edge
+        let padded = format!(r#"{{"type":"update",{}"channel":"book","data":[]}}"#, r#""pad":"xxxxxxxxxxxxxxxxxxxxxxxxxxxx","#);
+        assert_eq!(parser.channel(&padded).as_deref(), Some("book"));
+        assert_eq!(parser.channel(r#"{"channel":"heartbeat"}"#), Some(Cow::Borrowed("heartbeat")));
+        // Method responses still have no channel
+        let ack = r#"{"method":"subscribe","req_id":1,"result":{"channel":"book","depth":10,"snapshot":true,"symbol":"BTC/USD"},"success":true}"#;
+        assert_eq!(parser.channel(ack), None);
+    }
+
+    #[test]
+    fn test_parse_book_frame() {
+        let mut parser = FrameParser::new(Arc::new(ParseTimings::default()));
+        let text = r#"{"channel":"book","type":"snapshot","data":[{"symbol":"BTC/USD","bids":[{"price":50000.1,"qty":0.5}],"asks":[{"price":50001.2,"qty":1.25}],"checksum":123}]}"#;
+        let frame: V2BookFrame = parser.parse(text).unwrap();
+        assert_eq!(frame.msg_type, "snapshot");
+        assert_eq!(frame.data[0].symbol, "BTC/USD");
+        assert_eq!(frame.data[0].bids[0].price, 50000.1);
+        assert_eq!(frame.data[0].asks[0].qty, 1.25);
+        assert_eq!(frame.data[0].checksum, Some(123));
+        assert_eq!(parser.timings.snapshot().samples, 1);
+    }
+
+    #[test]
+    fn test_supported_book_depth() {
+        assert_eq!(supported_book_depth(10), 10);
+        assert_eq!(supported_book_depth(20), 25);
+        assert_eq!(supported_book_depth(25), 25);
+        assert_eq!(supported_book_depth(5000), 1000);
+    }
+
+    #[test]
+    fn test_subscription_changes_need_running_socket() {
+        let mut ws = KrakenWebSocketV2::new(Arc::new(OrderBookCache::new()));
+        ws.initialize_with_pairs(vec![SelectedPair {
+            pair_name: "BTC/USD".to_string(),
+            base: "BTC".to_string(),
+            quote: "USD".to_string(),
+            kraken_id: "XXBTZUSD".to_string(),
+            ws_name: "BTC/USD".to_string(),
+            altname: "XBTUSD".to_string(),
+            status: "online".to_string(),
+            volume_24h_usd: 1_000_000.0,
+            ordermin: 0.0001,
+            costmin: 0.5,
+            pair_decimals: Some(1),
+            lot_decimals: Some(8),
+            last_price: 50_000.0,
+        }]);
+
+        assert!(ws.subscribe_pair("ETH/USD").unwrap_err().contains("not a known pair"));
+        assert!(ws.unsubscribe_pair("BTC/USD").unwrap_err().contains("not subscribed"));
+        assert_eq!(ws.subscribe_pair("BTC/USD").unwrap_err(), "WebSocket not running");
+        assert!(ws.get_active_subscriptions().is_empty());
+    }
+
     #[test]
     fn test_format_checksum() {
-        assert_eq!(format_checksum_number(1234.56789), "123456789");
-        assert_eq!(format_checksum_number(0.00012345), "12345");
+        assert_eq!(format_checksum_number(1234.56789, 5), "123456789");
+        assert_eq!(format_checksum_number(0.00012345, 8), "12345");
+        assert_eq!(format_checksum_number(45285.2, 1), "452852");
+        assert_eq!(format_checksum_number(0.001, 8), "100000");
+    }
+
+    #[test]
+    fn test_book_checksum_matches_kraken_example() {
+        // BTC/USD snapshot from Kraken's v2 book checksum guide
+        let levels = |raw: &[(f64, f64)]| raw.iter().map(|&(price, qty)| OrderBookLevel { price, qty }).collect::<Vec<_>>();
+        let bids = levels(&[
+            (45283.5, 0.1),
+            (45283.4, 1.54582015),
+            (45282.1, 0.1),
+            (45281.0, 0.1),
+            (45280.3, 1.54592586),
+            (45279.0, 0.0799),
+            (45277.6, 0.03310103),
+            (45277.5, 0.3),
+            (45277.3, 1.54602737),
+            (45276.6, 0.15445238),
+        ]);
+        let asks = levels(&[
+            (45285.2, 0.001),
+            (45286.4, 1.54571953),
+            (45286.6, 1.54571109),
+            (45289.6, 1.54560911),
+            (45290.2, 0.1589066),
+            (45291.8, 1.54553491),
+            (45294.7, 0.04454749),
+            (45296.1, 0.3538),
+            (45297.5, 0.09945542),
+            (45299.5, 0.18772827),
+        ]);
+        assert_eq!(calculate_book_checksum(&bids, &asks, 1, 8), 3310070434);
     }
 }
@@ -58,6 +58,25 @@ export interface LiveConfig {
   max_pairs: number | null;
   min_volume_24h_usd: number | null;
   max_cost_min: number | null;
+  // Profit-tiered sizing (optional)
+  sizing_tiers: SizingTier[] | null;
+  max_safe_amount: number | null;
+  global_cooldown_ms: number | null;
+  path_cooldown_ms: number | null;
+  pair_failure_cooldown_ms: number | null;
+  leg_depth_levels: number | null;
+  max_leg_slippage_bps: number | null;
+  // Balance per currency never used for trading, e.g. { USD: 500 }
+  currency_reserves: Record<string, number> | null;
+  // USD filled across all legs per rolling hour / 24h (null = no limit)
+  max_notional_per_hour: number | null;
+  max_notional_per_day: number | null;
+  // Pairs scanned but never traded, e.g. ["DOGE/BTC"]
+  execution_disabled_pairs: string[] | null;
+  // Min net profit (decimal) by path length, e.g. {"4": 0.0035}
+  leg_profit_thresholds: Record<string, number> | null;
+  // IANA timezone whose midnight resets the daily counters (null = UTC)
+  daily_reset_timezone: string | null;
   // Session tracking
   session: TradingSession | null;
 }
@@ -72,6 +91,50 @@ export interface ConfigUpdate {
   max_pairs?: number;
   min_volume_24h_usd?: number;
   max_cost_min?: number;
+  // Profit-tiered sizing
+  sizing_tiers?: SizingTier[];
+  max_safe_amount?: number;
+  global_cooldown_ms?: number;
+  path_cooldown_ms?: number;
+  pair_failure_cooldown_ms?: number;
+  leg_depth_levels?: number;
+  max_leg_slippage_bps?: number;
+  currency_reserves?: Record<string, number>;
+  max_notional_per_hour?: number;
+  max_notional_per_day?: number;
+  execution_disabled_pairs?: string[];
+  leg_profit_thresholds?: Record<string, number>;
+  daily_reset_timezone?: string;
+}
+
+// Unified configuration document (GET/PUT /api/config)
+export interface ConfigDocument {
+  trading: ConfigUpdate;
+  fees: { maker_fee: number; taker_fee: number };
+}
+
+export interface ConfigPatch {
+  trading?: ConfigUpdate;
+  fees?: { maker_fee?: number; taker_fee?: number };
+}
+
+// One changed field, e.g. field "trading.trade_amount"
+export interface ConfigChange {
+  field: string;
+  old: unknown;
+  new: unknown;
+}
+
+// One invalid field returned with a 400 from config updates
+export interface ConfigFieldError {
+  field: string;
+  message: string;
+}
+
+// Sizing ladder rung: opportunities at or above min_profit_pct (percent) trade `amount`
+export interface SizingTier {
+  min_profit_pct: number;
+  amount: number;
 }
 
 // Live Trading State
@@ -126,6 +189,8 @@ export interface LiveTrade {
   completed_at: string | null;
   total_execution_ms: number | null;
   opportunity_profit_pct: number | null;
+  strategy: string | null;
+  tags: string[];
   created_at: string | null;
 }
 
@@ -136,6 +201,7 @@ export interface LegFill {
   duration_ms: number;
   success: boolean;
   error: string | null;
+  resized_from?: number;
 }
 
 // Scanner Status
@@ -168,6 +234,22 @@ export interface OpportunityPrice {
   volume: number;
 }
 
+export interface OrderBookLevel {
+  price: number;
+  qty: number;
+}
+
+export interface OrderBookLadder {
+  pair: string;
+  bids: OrderBookLevel[];
+  asks: OrderBookLevel[];
+  bid_levels: number;
+  ask_levels: number;
+  sequence: number;
+  last_update: string;
+  staleness_ms: number;
+}
+
 // Status Response
 export interface StatusResponse {
   status: string;
@@ -225,11 +307,14 @@ export interface AccountBalances {
   eur_usd_rate: number;
 }
 
+export type PricingSource = 'bid' | 'mid' | 'last';
+
 export interface PositionsResponse {
   success: boolean;
   connected: boolean;
   balances: AccountBalances;
   fetched_at: string;
+  pricing_source?: PricingSource;
   positions: Position[];
   error?: string;
 }
@@ -248,3 +333,43 @@ export interface RestrictionsConfig {
   notes: string;
   sources: string[];
 }
+
+// Audit Log
+export interface AuditEntry {
+  id: number;
+  occurred_at: string | null;
+  actor: 'auto' | 'api' | 'system';
+  category: 'order' | 'guard' | 'config' | 'breaker';
+  action: string;
+  details: Record<string, unknown> | null;
+}
+
+// Order Fill Journal (GET /api/live/fills)
+export interface OrderFill {
+  received_at: string;
+  order_id: string;
+  cl_ord_id: string | null;
+  exec_id: string | null;
+  exec_type: string;
+  order_status: string;
+  symbol: string | null;
+  side: string | null;
+  last_qty: number;
+  last_price: number;
+  cum_qty: number;
+  cum_cost: number;
+  avg_price: number;
+  fee_usd: number;
+  matched: boolean;
+  duplicate: boolean;
+}
+
+// Operator Notifications (GET /api/notifications)
+export interface Notification {
+  id: number;
+  at: string;
+  severity: 'info' | 'warning' | 'critical';
+  source: string;
+  title: string;
+  details: Record<string, unknown>;
+}